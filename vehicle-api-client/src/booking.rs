@@ -0,0 +1,92 @@
+use bson::oid::ObjectId;
+use reqwest::Method;
+use vehicle_api_types::{
+    Booking, BookingValidationReport, CreateBookingRequest, UpdateBookingRequest,
+};
+
+use crate::{Client, ClientResult, Page, PageQuery};
+
+impl Client {
+    /// POST /bookings (Customer)
+    pub async fn create_booking(&self, request: &CreateBookingRequest) -> ClientResult<Booking> {
+        self.send(self.protected(Method::POST, "/bookings").json(request))
+            .await
+    }
+
+    /// POST /bookings/validate (Customer), every problem of a prospective booking
+    pub async fn validate_booking(
+        &self,
+        request: &CreateBookingRequest,
+    ) -> ClientResult<BookingValidationReport> {
+        self.send(
+            self.protected(Method::POST, "/bookings/validate")
+                .json(request),
+        )
+        .await
+    }
+
+    /// GET /bookings (Customer: own bookings, Admin/Managers: all)
+    pub async fn list_bookings(&self, page: PageQuery) -> ClientResult<Page<Booking>> {
+        self.send_page(self.protected(Method::GET, "/bookings"), page)
+            .await
+    }
+
+    /// GET /bookings/{booking_id}
+    pub async fn get_booking(&self, booking_id: &ObjectId) -> ClientResult<Booking> {
+        self.send(self.protected(Method::GET, &format!("/bookings/{}", booking_id)))
+            .await
+    }
+
+    /// PATCH /bookings/{booking_id} (Admin, CarManager, MotorbikeManager, Customer)
+    pub async fn update_booking(
+        &self,
+        booking_id: &ObjectId,
+        request: &UpdateBookingRequest,
+    ) -> ClientResult<Booking> {
+        self.send(
+            self.protected(Method::PATCH, &format!("/bookings/{}", booking_id))
+                .json(request),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use vehicle_api_types::BookingStatus;
+
+    #[test]
+    fn test_create_booking_body_uses_hex_vehicle_id() {
+        let request = CreateBookingRequest {
+            vehicle_id: ObjectId::parse_str("66b1f0c2a1b2c3d4e5f60718").unwrap(),
+            from_date: NaiveDate::from_ymd_opt(2025, 8, 1).unwrap(),
+            to_date: NaiveDate::from_ymd_opt(2025, 8, 10).unwrap(),
+            driver: None,
+        };
+        let body = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(body["vehicle_id"], "66b1f0c2a1b2c3d4e5f60718");
+    }
+
+    #[test]
+    fn test_booking_deserializes_from_api_response() {
+        let json = r#"{
+            "id": "66b1f0c2a1b2c3d4e5f60718",
+            "vehicle_id": "66b1f0c2a1b2c3d4e5f60719",
+            "customer_id": "customer_user_1",
+            "from_date": "2025-08-01",
+            "to_date": "2025-08-10",
+            "status": "CANCELLED",
+            "reason": "Changed plans",
+            "order_date": "2025-07-20T08:30:00Z"
+        }"#;
+        let booking: Booking = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            booking.status,
+            BookingStatus::Cancelled("Changed plans".to_string())
+        );
+    }
+}
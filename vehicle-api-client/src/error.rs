@@ -0,0 +1,66 @@
+use derive_more::Display;
+use vehicle_api_types::ErrorResponse;
+
+#[derive(Debug, Display)]
+pub enum ClientError {
+    #[display("HTTP error: {}", _0)]
+    Http(reqwest::Error),
+    #[display("API error {}: {}", _0.code, _0.message)]
+    Api(ErrorResponse),
+}
+
+pub type ClientResult<T> = std::result::Result<T, ClientError>;
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(error: reqwest::Error) -> Self {
+        ClientError::Http(error)
+    }
+}
+
+impl ClientError {
+    /// Build an API error from a non-2xx response, falling back to the raw body
+    /// when it is not an ErrorResponse (e.g. a proxy error page)
+    pub(crate) fn from_response(status: u16, body: &str) -> Self {
+        let error = serde_json::from_str::<ErrorResponse>(body).unwrap_or_else(|_| ErrorResponse {
+            code: status,
+            message: body.to_string(),
+            error_type: "Unknown".to_string(),
+        });
+
+        ClientError::Api(error)
+    }
+
+    /// HTTP status returned by the API, if the request reached it
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::Http(error) => error.status().map(|status| status.as_u16()),
+            ClientError::Api(error) => Some(error.code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_from_api_error_response() {
+        let body =
+            r#"{"code":404,"message":"Not found: Vehicle not found","error_type":"NotFound"}"#;
+        let error = ClientError::from_response(404, body);
+
+        assert_eq!(error.status(), Some(404));
+        assert_eq!(
+            error.to_string(),
+            "API error 404: Not found: Vehicle not found"
+        );
+    }
+
+    #[test]
+    fn test_error_from_plain_body() {
+        let error = ClientError::from_response(502, "Bad Gateway");
+        assert_eq!(error.to_string(), "API error 502: Bad Gateway");
+    }
+}
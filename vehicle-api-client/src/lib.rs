@@ -0,0 +1,129 @@
+//! Typed async client for the vehicle booking API.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), vehicle_api_client::ClientError> {
+//! use vehicle_api_client::{Client, PageQuery};
+//!
+//! let client = Client::new("http://localhost:8080", "vk_...");
+//! let bookings = client.list_bookings(PageQuery::default()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod booking;
+mod error;
+mod meta;
+mod page;
+mod vehicle;
+
+use reqwest::{Method, RequestBuilder};
+use serde::de::DeserializeOwned;
+use vehicle_api_types::Identity;
+
+pub use error::{ClientError, ClientResult};
+pub use page::{Page, PageQuery};
+pub use vehicle_api_types as types;
+
+/// Header carrying the API key on every authenticated request
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// `Prefer` header value asking for `{ data, meta, warnings }` responses, lists read
+/// the page they got from `meta`
+const PREFER_ENVELOPE: &str = "envelope";
+
+#[derive(Clone, Debug)]
+pub struct Client {
+    base_url: String,
+    api_key: String,
+    http: reqwest::Client,
+}
+
+impl Client {
+    pub fn new(base_url: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self::with_http_client(base_url, api_key, reqwest::Client::new())
+    }
+
+    /// Build a client around an existing reqwest client (custom timeouts, proxies...)
+    pub fn with_http_client(
+        base_url: impl Into<String>,
+        api_key: impl Into<String>,
+        http: reqwest::Client,
+    ) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: api_key.into(),
+            http,
+        }
+    }
+
+    /// Request on a public endpoint
+    fn public(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}{}", self.base_url, path))
+    }
+
+    /// Request on an endpoint of the /protected scope
+    fn protected(&self, method: Method, path: &str) -> RequestBuilder {
+        self.public(method, &format!("/protected{}", path))
+            .header(API_KEY_HEADER, &self.api_key)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> ClientResult<T> {
+        let response = request.send().await?;
+        let status = response.status();
+
+        if status.is_success() {
+            Ok(response.json::<T>().await?)
+        } else {
+            let body = response.text().await.unwrap_or_default();
+            Err(ClientError::from_response(status.as_u16(), &body))
+        }
+    }
+
+    /// Request one page of a list endpoint
+    async fn send_page<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        page: PageQuery,
+    ) -> ClientResult<Page<T>> {
+        let request = request.query(&page).header("Prefer", PREFER_ENVELOPE);
+        let enveloped: page::Enveloped<T> = self.send(request).await?;
+        Ok(enveloped.into_page(page))
+    }
+
+    /// GET /health/mongodb
+    pub async fn health(&self) -> ClientResult<serde_json::Value> {
+        self.send(self.public(Method::GET, "/health/mongodb")).await
+    }
+
+    /// GET /protected/identity
+    pub async fn identity(&self) -> ClientResult<Identity> {
+        self.send(self.protected(Method::GET, "/identity")).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protected_request_has_api_key_and_prefix() {
+        let client = Client::new("http://localhost:8080/", "Admin");
+        let request = client.protected(Method::GET, "/vehicles").build().unwrap();
+
+        assert_eq!(
+            request.url().as_str(),
+            "http://localhost:8080/protected/vehicles"
+        );
+        assert_eq!(request.headers()[API_KEY_HEADER], "Admin");
+    }
+
+    #[test]
+    fn test_public_request_has_no_api_key() {
+        let client = Client::new("http://localhost:8080", "Admin");
+        let request = client.public(Method::GET, "/meta/events").build().unwrap();
+
+        assert_eq!(request.url().as_str(), "http://localhost:8080/meta/events");
+        assert!(request.headers().get(API_KEY_HEADER).is_none());
+    }
+}
@@ -0,0 +1,28 @@
+use reqwest::Method;
+use vehicle_api_types::{EventSchema, JwkSet};
+
+use crate::{Client, ClientResult};
+
+impl Client {
+    /// GET /meta/events (Public)
+    pub async fn event_schemas(&self) -> ClientResult<Vec<EventSchema>> {
+        self.send(self.public(Method::GET, "/meta/events")).await
+    }
+
+    /// GET /meta/schemas (Public)
+    pub async fn schema_names(&self) -> ClientResult<Vec<String>> {
+        self.send(self.public(Method::GET, "/meta/schemas")).await
+    }
+
+    /// GET /meta/schemas/{name} (Public)
+    pub async fn schema(&self, name: &str) -> ClientResult<serde_json::Value> {
+        self.send(self.public(Method::GET, &format!("/meta/schemas/{}", name)))
+            .await
+    }
+
+    /// GET /webhooks/signing-keys (Public)
+    pub async fn webhook_signing_keys(&self) -> ClientResult<JwkSet> {
+        self.send(self.public(Method::GET, "/webhooks/signing-keys"))
+            .await
+    }
+}
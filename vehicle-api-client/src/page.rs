@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+/// `page` and `limit` query parameters of the list endpoints. Without a limit the server
+/// uses its default page size.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq)]
+pub struct PageQuery {
+    pub page: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+}
+
+/// Page of a list as the server served it: its rows, the page number and page size it
+/// used (a `limit` above its maximum is clamped) and the warnings it sent
+#[derive(Clone, Debug, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: i64,
+    pub limit: i64,
+    pub warnings: Vec<String>,
+}
+
+// Enveloped list response: `{ "data": [...], "meta": { "page": {...} }, "warnings": [...] }`
+#[derive(Deserialize)]
+pub(crate) struct Enveloped<T> {
+    data: Vec<T>,
+    #[serde(default)]
+    meta: Meta,
+    #[serde(default)]
+    warnings: Vec<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct Meta {
+    page: Option<PageMeta>,
+}
+
+#[derive(Deserialize)]
+struct PageMeta {
+    page: i64,
+    limit: i64,
+}
+
+impl Default for PageQuery {
+    fn default() -> Self {
+        Self {
+            page: 1,
+            limit: None,
+        }
+    }
+}
+
+impl PageQuery {
+    pub fn new(page: i64, limit: Option<i64>) -> Self {
+        Self { page, limit }
+    }
+}
+
+impl<T> Page<T> {
+    /// Query of the following page, None once a page is not full
+    pub fn next(&self) -> Option<PageQuery> {
+        (!self.items.is_empty() && self.items.len() as i64 >= self.limit)
+            .then(|| PageQuery::new(self.page + 1, Some(self.limit)))
+    }
+}
+
+impl<T> Enveloped<T> {
+    /// Page of the response to `requested`. Servers not reporting the page they served
+    /// are taken at their word for the page number, the rows returned give the size.
+    pub(crate) fn into_page(self, requested: PageQuery) -> Page<T> {
+        let (page, limit) = match self.meta.page {
+            Some(meta) => (meta.page, meta.limit),
+            None => (
+                requested.page,
+                requested.limit.unwrap_or(self.data.len() as i64),
+            ),
+        };
+        Page {
+            items: self.data,
+            page,
+            limit,
+            warnings: self.warnings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_reads_the_served_page_size() {
+        let json = r#"{
+            "data": [1, 2, 3],
+            "meta": { "page": { "page": 2, "limit": 3 }, "quota": { "remaining": 12 } },
+            "warnings": ["limit 1000 is out of range, 3 rows per page returned (at most 3)"]
+        }"#;
+        let enveloped: Enveloped<u32> = serde_json::from_str(json).unwrap();
+        let page = enveloped.into_page(PageQuery::new(2, Some(1000)));
+
+        assert_eq!(page.items, vec![1, 2, 3]);
+        assert_eq!(page.limit, 3);
+        assert_eq!(page.warnings.len(), 1);
+        assert_eq!(page.next(), Some(PageQuery::new(3, Some(3))));
+    }
+
+    #[test]
+    fn test_last_page_has_no_next() {
+        let json = r#"{ "data": [1], "meta": { "page": { "page": 4, "limit": 20 } } }"#;
+        let enveloped: Enveloped<u32> = serde_json::from_str(json).unwrap();
+        let page = enveloped.into_page(PageQuery::new(4, None));
+
+        assert!(page.warnings.is_empty());
+        assert_eq!(page.next(), None);
+    }
+}
@@ -0,0 +1,114 @@
+use bson::oid::ObjectId;
+use reqwest::Method;
+use vehicle_api_types::{
+    Booking, CreateVehicleRequest, UpdateVehicleRequest, Vehicle, VehicleFilters,
+};
+
+use crate::{Client, ClientResult, Page, PageQuery};
+
+impl Client {
+    /// POST /vehicles (Admin)
+    pub async fn create_vehicle(&self, request: &CreateVehicleRequest) -> ClientResult<Vehicle> {
+        self.send(self.protected(Method::POST, "/vehicles").json(request))
+            .await
+    }
+
+    /// GET /vehicles (All), `sort` e.g. `-price_by_day`
+    pub async fn list_vehicles(
+        &self,
+        filters: &VehicleFilters,
+        sort: Option<&str>,
+        page: PageQuery,
+    ) -> ClientResult<Page<Vehicle>> {
+        let sort = sort.map(|sort| [("sort", sort)]);
+        self.send_page(
+            self.protected(Method::GET, "/vehicles")
+                .query(filters)
+                .query(&sort),
+            page,
+        )
+        .await
+    }
+
+    /// GET /vehicles/{vehicle_id} (All)
+    pub async fn get_vehicle(&self, vehicle_id: &ObjectId) -> ClientResult<Vehicle> {
+        self.send(self.protected(Method::GET, &format!("/vehicles/{}", vehicle_id)))
+            .await
+    }
+
+    /// PATCH /vehicles/{vehicle_id} (Admin, CarManager, MotorbikeManager)
+    pub async fn update_vehicle(
+        &self,
+        vehicle_id: &ObjectId,
+        request: &UpdateVehicleRequest,
+    ) -> ClientResult<Vehicle> {
+        self.send(
+            self.protected(Method::PATCH, &format!("/vehicles/{}", vehicle_id))
+                .json(request),
+        )
+        .await
+    }
+
+    /// GET /vehicles/{vehicle_id}/bookings (Admin, CarManager, MotorbikeManager)
+    pub async fn list_vehicle_bookings(
+        &self,
+        vehicle_id: &ObjectId,
+        page: PageQuery,
+    ) -> ClientResult<Page<Booking>> {
+        self.send_page(
+            self.protected(Method::GET, &format!("/vehicles/{}/bookings", vehicle_id)),
+            page,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vehicle_api_types::{Brand, Currency, FuelType};
+
+    #[test]
+    fn test_list_vehicles_query_string() {
+        let client = Client::new("http://localhost:8080", "Admin");
+        let filters = VehicleFilters {
+            brand: Some(vec![Brand::TESLA, Brand::MERCEDES]),
+            fuel_type: Some(vec![FuelType::ELECTRIC]),
+            min_price: Some(50.0),
+            ..Default::default()
+        };
+
+        let request = client
+            .protected(Method::GET, "/vehicles")
+            .query(&filters)
+            .query(&[("sort", "-price_by_day")])
+            .query(&PageQuery::new(2, Some(10)))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            request.url().query(),
+            Some("brand=TESLA%2CMERCEDES&min_price=50.0&fuel_type=ELECTRIC&sort=-price_by_day&page=2&limit=10")
+        );
+    }
+
+    #[test]
+    fn test_vehicle_deserializes_from_api_response() {
+        let json = r#"{
+            "id": "66b1f0c2a1b2c3d4e5f60718",
+            "brand": "TESLA",
+            "type": "CAR",
+            "metadata": {"model": "MODEL_3", "seats": 5, "fuel_type": "ELECTRIC", "gearbox": "AUTOMATIC", "engine_cc": 0},
+            "description": "Long range",
+            "price_by_day": {"amount": 89.9, "currency": "EUR"},
+            "year_of_production": 2023,
+            "added_at": "2025-08-01T10:00:00Z",
+            "added_by": "Admin"
+        }"#;
+        let vehicle: Vehicle = serde_json::from_str(json).unwrap();
+
+        assert_eq!(vehicle.id.unwrap().to_hex(), "66b1f0c2a1b2c3d4e5f60718");
+        assert_eq!(vehicle.brand, Brand::TESLA);
+        assert_eq!(vehicle.price_by_day.currency, Currency::EUR);
+    }
+}
@@ -0,0 +1,327 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use validator::Validate;
+
+use crate::money::{Currency, Money};
+use crate::pii::DriverDetails;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, EnumString, Display, PartialEq)]
+#[serde(tag = "status", content = "reason", rename_all = "UPPERCASE")]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum BookingStatus {
+    Pending,
+    Confirmed,
+    Rejected(String),
+    Cancelled(String),
+}
+
+/// Problems reported by the booking pre-check
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BookingIssueCode {
+    InvalidDateRange,
+    StartInPast,
+    VehicleNotFound,
+    Unavailable,
+    HolidayBlackout,
+    LongRental,
+    HolidaySurcharge,
+    UnverifiedCustomer,
+    TooShortNotice,
+    TooLongRental,
+    BeyondHorizon,
+    QuotaExceeded,
+    OutsideOfficeHours,
+    OutOfService,
+    StationClosed,
+}
+
+// =============================================================================
+// MAIN BOOKING STRUCT
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Booking {
+    #[cfg_attr(feature = "bson-storage", serde(rename = "_id", alias = "id"))]
+    #[cfg_attr(
+        not(feature = "bson-storage"),
+        serde(alias = "_id", with = "crate::serde_helpers::option_object_id")
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(rename = "id", with = "Option<String>")]
+    pub id: Option<ObjectId>,
+    #[cfg_attr(
+        not(feature = "bson-storage"),
+        serde(with = "crate::serde_helpers::object_id")
+    )]
+    #[schemars(with = "String")]
+    pub vehicle_id: ObjectId,
+    pub customer_id: String,  // User ID of the customer who made the booking
+    pub from_date: NaiveDate, // Local dates in `timezone`, to_date included
+    pub to_date: NaiveDate,
+    #[serde(default = "crate::vehicle::default_timezone")]
+    pub timezone: String,
+    /// Canonical UTC instants of the booking boundaries (start of from_date, end of to_date)
+    #[serde(default, with = "crate::serde_helpers::option_datetime")]
+    #[schemars(with = "Option<DateTime<Utc>>")]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "crate::serde_helpers::option_datetime")]
+    #[schemars(with = "Option<DateTime<Utc>>")]
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub status: BookingStatus,
+    #[serde(with = "crate::serde_helpers::datetime")]
+    #[schemars(with = "DateTime<Utc>")]
+    pub order_date: DateTime<Utc>, // When the booking was created
+    /// Every status the booking went through, oldest first
+    #[serde(default)]
+    pub history: Vec<BookingHistoryEntry>,
+    /// Driver details, encrypted at rest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub driver: Option<DriverDetails>,
+    /// Price of the whole rental, holiday surcharges included, computed by the server
+    /// from the vehicle's `price_by_day`, in its currency. The fees below are in the same
+    /// currency. None for bookings stored before prices were.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_price: Option<Money>,
+    /// Fee kept when the customer cancelled a confirmed booking, from the cancellation
+    /// policy. None for bookings not cancelled by their customer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancellation_fee: Option<Money>,
+    /// Vehicle handed over to the customer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check_in: Option<Handover>,
+    /// Vehicle returned by the customer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check_out: Option<Handover>,
+    /// Fee charged at check-out for returning the vehicle after `to_date`. None for
+    /// bookings returned on time or not returned yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub late_fee: Option<Money>,
+    /// When the customer's payment of `total_price` succeeded, None while unpaid
+    #[serde(default, with = "crate::serde_helpers::option_datetime")]
+    #[schemars(with = "Option<DateTime<Utc>>")]
+    pub paid_at: Option<DateTime<Utc>>,
+    /// When the customer was reminded the rental starts soon, None until then
+    #[serde(default, with = "crate::serde_helpers::option_datetime")]
+    #[schemars(with = "Option<DateTime<Utc>>")]
+    pub reminder_sent_at: Option<DateTime<Utc>>,
+    /// Rental company the booking belongs to, set by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+/// One status change of a booking
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct BookingHistoryEntry {
+    #[serde(flatten)]
+    pub status: BookingStatus,
+    pub changed_by: String, // User ID, or "auto-confirm"
+    /// Auto-confirm rule that set the status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    #[serde(with = "crate::serde_helpers::datetime")]
+    #[schemars(with = "DateTime<Utc>")]
+    pub changed_at: DateTime<Utc>,
+}
+
+/// State of the vehicle when handed over (check-in) or returned (check-out)
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Handover {
+    #[serde(with = "crate::serde_helpers::datetime")]
+    #[schemars(with = "DateTime<Utc>")]
+    pub at: DateTime<Utc>,
+    pub odometer_km: u32,
+    pub fuel_percent: u8,
+    pub recorded_by: String, // User ID of the manager
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct CreateBookingRequest {
+    #[serde(with = "crate::serde_helpers::object_id")]
+    #[schemars(with = "String")]
+    pub vehicle_id: ObjectId,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub driver: Option<DriverDetails>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct UpdateBookingRequest {
+    pub status: Option<BookingStatus>,
+    /// New dates, only while the booking is PENDING. The price is computed again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_date: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_date: Option<NaiveDate>,
+}
+
+/// Odometer and fuel read when the vehicle is handed over or returned
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct HandoverRequest {
+    #[validate(range(max = 2_000_000))]
+    pub odometer_km: u32,
+    #[validate(range(max = 100))]
+    pub fuel_percent: u8,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct BookingIssue {
+    pub code: BookingIssueCode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub message: String,
+}
+
+/// Every problem of a prospective booking, returned by `POST /bookings/validate`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct BookingValidationReport {
+    pub valid: bool,
+    pub errors: Vec<BookingIssue>,
+    pub warnings: Vec<BookingIssue>,
+    pub estimated_price: Option<Money>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS - CORE BOOKING METHODS
+// =============================================================================
+
+impl Booking {
+    pub fn new(request: CreateBookingRequest, customer_id: String) -> Self {
+        let order_date = Utc::now();
+        Self {
+            id: None,
+            vehicle_id: request.vehicle_id,
+            history: vec![BookingHistoryEntry {
+                status: BookingStatus::Pending,
+                changed_by: customer_id.clone(),
+                rule: None,
+                changed_at: order_date,
+            }],
+            customer_id,
+            from_date: request.from_date,
+            to_date: request.to_date,
+            timezone: crate::vehicle::default_timezone(),
+            starts_at: None,
+            ends_at: None,
+            status: BookingStatus::Pending,
+            order_date,
+            driver: request.driver,
+            total_price: None,
+            cancellation_fee: None,
+            check_in: None,
+            check_out: None,
+            late_fee: None,
+            paid_at: None,
+            reminder_sent_at: None,
+            tenant_id: None,
+        }
+    }
+
+    /// Currency of the price, the fees and the ledger, EUR for bookings stored before
+    /// prices were
+    pub fn currency(&self) -> Currency {
+        self.total_price
+            .map(|price| price.currency)
+            .unwrap_or_default()
+    }
+
+    /// Change the status and record it in the history
+    pub fn set_status(&mut self, status: BookingStatus, changed_by: String, rule: Option<String>) {
+        self.history.push(BookingHistoryEntry {
+            status: status.clone(),
+            changed_by,
+            rule,
+            changed_at: Utc::now(),
+        });
+        self.status = status;
+    }
+}
+
+impl BookingIssue {
+    pub fn new(code: BookingIssueCode, field: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            field: field.map(|f| f.to_string()),
+            message: message.into(),
+        }
+    }
+}
+
+impl BookingValidationReport {
+    pub fn new(
+        errors: Vec<BookingIssue>,
+        warnings: Vec<BookingIssue>,
+        estimated_price: Option<Money>,
+    ) -> Self {
+        Self {
+            valid: errors.is_empty(),
+            errors,
+            warnings,
+            estimated_price,
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Decimal;
+
+    #[test]
+    fn test_booking_status_serialization() {
+        let pending = BookingStatus::Pending;
+        let cancelled = BookingStatus::Cancelled("User request".to_string());
+        let rejected = BookingStatus::Rejected("Invalid dates".to_string());
+
+        // Test that serialization works and uses UPPERCASE
+        let pending_json = serde_json::to_string(&pending).unwrap();
+        let cancelled_json = serde_json::to_string(&cancelled).unwrap();
+        let rejected_json = serde_json::to_string(&rejected).unwrap();
+
+        // Verify uppercase status values
+        assert!(pending_json.contains("\"status\":\"PENDING\""));
+        assert!(cancelled_json.contains("\"status\":\"CANCELLED\""));
+        assert!(cancelled_json.contains("\"reason\":\"User request\""));
+        assert!(rejected_json.contains("\"status\":\"REJECTED\""));
+        assert!(rejected_json.contains("\"reason\":\"Invalid dates\""));
+    }
+
+    #[test]
+    fn test_validation_report_is_valid_without_errors() {
+        let warning = BookingIssue::new(BookingIssueCode::LongRental, None, "Long rental");
+        let report = BookingValidationReport::new(
+            vec![],
+            vec![warning],
+            Some(Money::new(Decimal::new(90, 0), Currency::EUR)),
+        );
+        assert!(report.valid);
+
+        let error = BookingIssue::new(
+            BookingIssueCode::InvalidDateRange,
+            Some("to_date"),
+            "from_date must be before to_date",
+        );
+        let report = BookingValidationReport::new(vec![error], vec![], None);
+        assert!(!report.valid);
+        assert!(serde_json::to_string(&report)
+            .unwrap()
+            .contains("\"code\":\"INVALID_DATE_RANGE\""));
+    }
+}
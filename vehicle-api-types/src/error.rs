@@ -0,0 +1,10 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Body of every error response returned by the API
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ErrorResponse {
+    pub code: u16,
+    pub message: String,
+    pub error_type: String,
+}
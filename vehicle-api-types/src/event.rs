@@ -0,0 +1,75 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString};
+
+use crate::BookingStatus;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+/// Every event type sent to webhook/broker consumers
+#[derive(
+    Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, EnumString, EnumIter, Display, PartialEq,
+)]
+pub enum EventType {
+    #[serde(rename = "booking.created")]
+    #[strum(serialize = "booking.created")]
+    BookingCreated,
+    #[serde(rename = "booking.status_changed")]
+    #[strum(serialize = "booking.status_changed")]
+    BookingStatusChanged,
+    #[serde(rename = "booking.reminder")]
+    #[strum(serialize = "booking.reminder")]
+    BookingReminder,
+}
+
+// =============================================================================
+// EVENT STRUCTS
+// =============================================================================
+
+/// Envelope shared by all events
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Event<T> {
+    pub event_type: EventType,
+    pub occurred_at: DateTime<Utc>,
+    pub data: T,
+}
+
+/// Payload of booking.created, booking.status_changed and booking.reminder
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BookingEventData {
+    pub booking_id: String,
+    pub vehicle_id: String,
+    pub customer_id: String,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    #[serde(flatten)]
+    pub status: BookingStatus,
+}
+
+/// Registry entry returned by GET /meta/events
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventSchema {
+    pub event_type: EventType,
+    pub schema: serde_json::Value,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl EventType {
+    /// JSON Schema of the full event (envelope + payload) for this type
+    pub fn schema(&self) -> serde_json::Value {
+        let schema = match self {
+            EventType::BookingCreated
+            | EventType::BookingStatusChanged
+            | EventType::BookingReminder => {
+                schemars::schema_for!(Event<BookingEventData>)
+            }
+        };
+        schema.to_value()
+    }
+}
@@ -0,0 +1,26 @@
+//! Models shared by the vehicle booking API, its client and integration tests.
+//!
+//! Only depends on serde-level crates (no actix, no mongodb driver).
+//! The `bson-storage` feature switches to the MongoDB document format
+//! (`_id` field, BSON datetimes); without it models use the API JSON format.
+
+pub mod booking;
+pub mod error;
+pub mod event;
+pub mod identity;
+pub mod locale;
+pub mod money;
+pub mod pii;
+pub mod serde_helpers;
+pub mod vehicle;
+pub mod webhook;
+
+pub use booking::*;
+pub use error::*;
+pub use event::*;
+pub use identity::*;
+pub use locale::*;
+pub use money::*;
+pub use pii::*;
+pub use vehicle::*;
+pub use webhook::*;
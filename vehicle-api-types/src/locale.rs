@@ -0,0 +1,259 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer, Serialize};
+use validator::ValidationError;
+
+/// Language of the texts written before they were translated
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Characters allowed in each translation
+pub const MAX_TRANSLATION_CHARS: usize = 249;
+
+/// Lowercase language code, optionally with a region (e.g. `fr`, `pt-br`)
+const LANGUAGE_PATTERN: &str = "^[a-z]{2,3}(-[a-z0-9]{2,8})?$";
+
+// =============================================================================
+// MAIN LOCALIZED TEXT STRUCT
+// =============================================================================
+
+/// Text translated in several languages, keyed by language code:
+/// `{"en": "Long range", "fr": "Grande autonomie"}`. A plain string is read as the
+/// `DEFAULT_LANGUAGE` translation, as texts were stored before.
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+#[serde(transparent)]
+pub struct LocalizedText(pub BTreeMap<String, String>);
+
+impl<'de> Deserialize<'de> for LocalizedText {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Plain(String),
+            Translations(BTreeMap<String, String>),
+        }
+
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::Plain(text) => Self::single(DEFAULT_LANGUAGE, text),
+            Stored::Translations(translations) => Self(
+                translations
+                    .into_iter()
+                    .map(|(language, text)| (language.to_lowercase(), text))
+                    .collect(),
+            ),
+        })
+    }
+}
+
+impl JsonSchema for LocalizedText {
+    fn schema_name() -> Cow<'static, str> {
+        "LocalizedText".into()
+    }
+
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "object",
+            "description": "Translations keyed by language code, e.g. {\"en\": \"...\", \"fr\": \"...\"}",
+            "minProperties": 1,
+            "propertyNames": { "pattern": LANGUAGE_PATTERN },
+            "additionalProperties": {
+                "type": "string",
+                "minLength": 1,
+                "maxLength": MAX_TRANSLATION_CHARS,
+            },
+        })
+    }
+}
+
+impl LocalizedText {
+    pub fn single(language: &str, text: impl Into<String>) -> Self {
+        Self(BTreeMap::from([(language.to_lowercase(), text.into())]))
+    }
+
+    pub fn get(&self, language: &str) -> Option<&str> {
+        self.0.get(language).map(String::as_str)
+    }
+
+    /// Languages translated, sorted
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// Add or replace the translations of `other`, keeping the other languages
+    pub fn merge(&mut self, other: LocalizedText) {
+        self.0.extend(other.0);
+    }
+
+    /// Language best matching `preferences` (most preferred first): an exact match, then
+    /// the same language without region (`fr-ca` matches `fr`), then `default`, then the
+    /// first translation
+    pub fn best_language(&self, preferences: &[String], default: &str) -> Option<&str> {
+        let exact = preferences
+            .iter()
+            .find_map(|language| self.0.get_key_value(language.as_str()));
+        let primary = || {
+            preferences.iter().find_map(|language| {
+                let primary = language.split('-').next().unwrap_or(language);
+                self.0.get_key_value(primary)
+            })
+        };
+        exact
+            .or_else(primary)
+            .or_else(|| self.0.get_key_value(default))
+            .or_else(|| self.0.iter().next())
+            .map(|(language, _)| language.as_str())
+    }
+
+    /// Keep only the translation best matching `preferences`, see `best_language`
+    pub fn narrow(&mut self, preferences: &[String], default: &str) {
+        if let Some(language) = self.best_language(preferences, default).map(str::to_string) {
+            self.0.retain(|key, _| *key == language);
+        }
+    }
+}
+
+// =============================================================================
+// VALIDATION
+// =============================================================================
+
+/// At least one translation, language codes like `en` or `pt-br`, and each translation
+/// between 1 and `MAX_TRANSLATION_CHARS` characters
+pub fn validate_translations(text: &LocalizedText) -> Result<(), ValidationError> {
+    let error = |code: &'static str, message: String| {
+        let mut error = ValidationError::new(code);
+        error.message = Some(message.into());
+        Err(error)
+    };
+
+    if text.0.is_empty() {
+        return error(
+            "translations",
+            "At least one translation is required".into(),
+        );
+    }
+    for (language, translation) in &text.0 {
+        if !is_language_code(language) {
+            return error("language", format!("Invalid language code '{}'", language));
+        }
+        let length = translation.chars().count();
+        if length == 0 || length > MAX_TRANSLATION_CHARS {
+            return error(
+                "translation_length",
+                format!(
+                    "Translation '{}' must be between 1 and {} characters",
+                    language, MAX_TRANSLATION_CHARS
+                ),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Whether a lowercase code matches `LANGUAGE_PATTERN`
+fn is_language_code(code: &str) -> bool {
+    let mut parts = code.splitn(2, '-');
+    let language = parts.next().unwrap_or_default();
+    let language_ok =
+        (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_lowercase());
+    let region_ok = parts.next().is_none_or(|region| {
+        (2..=8).contains(&region.len())
+            && region
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    });
+    language_ok && region_ok
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(translations: &[(&str, &str)]) -> LocalizedText {
+        LocalizedText(
+            translations
+                .iter()
+                .map(|(language, text)| (language.to_string(), text.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_plain_strings_are_default_language_translations() {
+        let plain: LocalizedText = serde_json::from_str(r#""Long range""#).unwrap();
+        assert_eq!(plain, LocalizedText::single("en", "Long range"));
+
+        let translated: LocalizedText =
+            serde_json::from_str(r#"{"EN": "Long range", "fr": "Grande autonomie"}"#).unwrap();
+        assert_eq!(
+            translated,
+            text(&[("en", "Long range"), ("fr", "Grande autonomie")])
+        );
+        assert_eq!(
+            serde_json::to_string(&translated).unwrap(),
+            r#"{"en":"Long range","fr":"Grande autonomie"}"#
+        );
+    }
+
+    #[test]
+    fn test_best_language_falls_back_to_the_default() {
+        let description = text(&[
+            ("de", "Reichweite"),
+            ("en", "Long range"),
+            ("fr", "Autonomie"),
+        ]);
+        let prefer = |languages: &[&str]| -> Vec<String> {
+            languages
+                .iter()
+                .map(|language| language.to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            description.best_language(&prefer(&["fr", "en"]), "en"),
+            Some("fr")
+        );
+        assert_eq!(
+            description.best_language(&prefer(&["fr-ca"]), "en"),
+            Some("fr")
+        );
+        assert_eq!(
+            description.best_language(&prefer(&["it"]), "en"),
+            Some("en")
+        );
+        assert_eq!(description.best_language(&[], "it"), Some("de"));
+
+        let mut narrowed = description.clone();
+        narrowed.narrow(&prefer(&["it", "de"]), "en");
+        assert_eq!(narrowed, text(&[("de", "Reichweite")]));
+    }
+
+    #[test]
+    fn test_limits_apply_to_each_translation() {
+        assert!(validate_translations(&text(&[("en", "Long range"), ("pt-br", "Longo")])).is_ok());
+
+        let too_long = "a".repeat(MAX_TRANSLATION_CHARS + 1);
+        let error = validate_translations(&text(&[("en", "ok"), ("fr", &too_long)])).unwrap_err();
+        assert_eq!(error.code, "translation_length");
+        assert_eq!(
+            validate_translations(&text(&[("english", "Long range")]))
+                .unwrap_err()
+                .code,
+            "language"
+        );
+        assert_eq!(
+            validate_translations(&LocalizedText::default())
+                .unwrap_err()
+                .code,
+            "translations"
+        );
+    }
+}
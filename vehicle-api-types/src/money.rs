@@ -0,0 +1,411 @@
+use std::str::FromStr;
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use strum::{Display, EnumIter, EnumString};
+use validator::ValidationError;
+
+pub use rust_decimal::Decimal;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+/// ISO 4217 currency of an amount. EUR is the currency of the prices stored before
+/// currencies were.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    EnumString,
+    EnumIter,
+    Display,
+    PartialEq,
+    Eq,
+    Hash,
+)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    #[default]
+    EUR,
+    USD,
+    GBP,
+    CHF,
+}
+
+// =============================================================================
+// MAIN MONEY STRUCT
+// =============================================================================
+
+/// Amount in a currency, exact to the cent. Sent as `{"amount": "89.90", "currency": "EUR"}`,
+/// the amount as a string so no client reads it as a float, and stored with the amount as
+/// a Decimal128 (`bson-storage`) so MongoDB can filter and sort on it exactly. A plain
+/// number is read as an amount in the default currency, as prices were stored before.
+#[derive(Clone, Copy, Debug, Default, Serialize, JsonSchema, PartialEq, Eq)]
+pub struct Money {
+    #[serde(with = "amount")]
+    #[schemars(with = "String", extend("format" = "decimal"))]
+    pub amount: Decimal,
+    pub currency: Currency,
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Money {
+                #[serde(with = "amount")]
+                amount: Decimal,
+                currency: Currency,
+            },
+            Amount(#[serde(with = "amount")] Decimal),
+        }
+
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::Amount(amount) => Money::new(amount, Currency::default()),
+            Stored::Money { amount, currency } => Money::new(amount, currency),
+        })
+    }
+}
+
+/// Decimal written as a Decimal128 (`bson-storage`) or a string, read back from either
+/// format or from a number, e.g. `89.9` or `"89.90"`
+pub mod amount {
+    use std::str::FromStr;
+
+    use bson::Bson;
+    use rust_decimal::prelude::FromPrimitive;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::Decimal;
+
+    pub fn serialize<S>(amount: &Decimal, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[cfg(feature = "bson-storage")]
+        {
+            use serde::Serialize;
+            bson::Decimal128::from_str(&amount.to_string())
+                .map_err(serde::ser::Error::custom)?
+                .serialize(serializer)
+        }
+        #[cfg(not(feature = "bson-storage"))]
+        {
+            serializer.collect_str(amount)
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Decimal, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let amount = match Bson::deserialize(deserializer)? {
+            Bson::Decimal128(amount) => Decimal::from_str(&amount.to_string())
+                .or_else(|_| Decimal::from_scientific(&amount.to_string()))
+                .ok(),
+            Bson::String(amount) => Decimal::from_str(amount.trim()).ok(),
+            Bson::Int32(amount) => Some(Decimal::from(amount)),
+            Bson::Int64(amount) => Some(Decimal::from(amount)),
+            // Shortest representation, 89.9 stays 89.9 rather than 89.900000000000005684...
+            Bson::Double(amount) => Decimal::from_str(&amount.to_string())
+                .ok()
+                .or_else(|| Decimal::from_f64(amount)),
+            _ => None,
+        };
+        amount.ok_or_else(|| serde::de::Error::custom("invalid amount"))
+    }
+
+    /// Same as `amount` for optional amounts, missing or null is None
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        use super::Decimal;
+
+        pub fn serialize<S>(amount: &Option<Decimal>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match amount {
+                Some(amount) => super::serialize(amount, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Decimal>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            #[derive(Deserialize)]
+            struct Amount(#[serde(with = "super")] Decimal);
+
+            Ok(Option::<Amount>::deserialize(deserializer)?.map(|Amount(amount)| amount))
+        }
+    }
+}
+
+/// Money as the API sends it whatever the storage format, for payloads the server
+/// serializes to JSON itself, e.g. published events
+pub mod json {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{Currency, Money};
+
+    #[derive(Serialize)]
+    struct Sent {
+        amount: String,
+        currency: Currency,
+    }
+
+    pub fn serialize<S>(money: &Money, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Sent {
+            amount: money.amount.to_string(),
+            currency: money.currency,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Money, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Money::deserialize(deserializer)
+    }
+
+    /// Same as `json` for optional amounts
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        use super::Money;
+
+        pub fn serialize<S>(money: &Option<Money>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match money {
+                Some(money) => super::serialize(money, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Money>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            Option::<Money>::deserialize(deserializer)
+        }
+    }
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: Currency) -> Self {
+        Self { amount, currency }
+    }
+
+    /// Amount given as a float, e.g. from a query parameter, in `currency`
+    pub fn from_f64(amount: f64, currency: Currency) -> Option<Self> {
+        let amount = Decimal::from_str(&amount.to_string())
+            .ok()
+            .or_else(|| Decimal::from_f64(amount))?;
+        Some(Self::new(amount, currency))
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(Decimal::ZERO, currency)
+    }
+
+    /// Rounded to the cent, half away from zero
+    pub fn round(self) -> Self {
+        Self::new(
+            self.amount
+                .round_dp_with_strategy(2, rust_decimal::RoundingStrategy::MidpointAwayFromZero),
+            self.currency,
+        )
+    }
+
+    /// Amount as a float, for scores and ratios, never for sums
+    pub fn to_f64(&self) -> f64 {
+        self.amount.to_f64().unwrap_or_default()
+    }
+
+    /// Amount as MongoDB stores it, to filter on amounts exactly
+    pub fn to_decimal128(&self) -> bson::Decimal128 {
+        bson::Decimal128::from_str(&self.amount.to_string())
+            .expect("a Decimal fits in a Decimal128")
+    }
+
+    /// Same currency, amount multiplied by `factor`
+    pub fn times(self, factor: Decimal) -> Self {
+        Self::new(self.amount * factor, self.currency)
+    }
+
+    /// Sum of two amounts of the same currency, None when they differ
+    pub fn checked_add(self, other: Money) -> Option<Self> {
+        (self.currency == other.currency)
+            .then(|| Self::new(self.amount + other.amount, self.currency))
+    }
+
+    /// One sum per currency, amounts of different currencies are never added up.
+    /// Currencies come in the order they first appear.
+    pub fn sum_by_currency(amounts: impl IntoIterator<Item = Money>) -> Vec<Money> {
+        let mut sums: Vec<Money> = Vec::new();
+        for money in amounts {
+            match sums.iter_mut().find(|sum| sum.currency == money.currency) {
+                Some(sum) => sum.amount += money.amount,
+                None => sums.push(money),
+            }
+        }
+        sums
+    }
+}
+
+/// Same document as stored, to use amounts in filters and updates
+impl From<Money> for bson::Bson {
+    fn from(money: Money) -> Self {
+        bson::Bson::Document(bson::doc! {
+            "amount": money.to_decimal128(),
+            "currency": money.currency.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.2} {}", self.amount, self.currency)
+    }
+}
+
+// =============================================================================
+// QUERY STRUCTS
+// =============================================================================
+
+/// `?currency=USD`: prices of a list converted to this currency, and price filters given in
+/// it. Without it prices are returned in their own currency.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CurrencyQuery {
+    pub currency: Option<Currency>,
+}
+
+// =============================================================================
+// VALIDATION
+// =============================================================================
+
+/// Strictly positive amount with at most 2 decimals
+pub fn validate_price(price: &Money) -> Result<(), ValidationError> {
+    let error = |code: &'static str, message: &'static str| {
+        let mut error = ValidationError::new(code);
+        error.message = Some(message.into());
+        Err(error)
+    };
+
+    if price.amount <= Decimal::ZERO {
+        return error("range", "Price must be greater than 0");
+    }
+    if price.amount.normalize().scale() > 2 {
+        return error("precision", "Price cannot have more than 2 decimals");
+    }
+    Ok(())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_numbers_are_default_currency_amounts() {
+        let legacy: Money = serde_json::from_str("89.9").unwrap();
+        assert_eq!(legacy, Money::new(Decimal::new(899, 1), Currency::EUR));
+
+        let money: Money =
+            serde_json::from_str(r#"{"amount": "120.50", "currency": "USD"}"#).unwrap();
+        assert_eq!(money, Money::new(Decimal::new(12050, 2), Currency::USD));
+        #[cfg(not(feature = "bson-storage"))]
+        assert_eq!(
+            serde_json::to_value(money).unwrap(),
+            serde_json::json!({ "amount": "120.50", "currency": "USD" })
+        );
+    }
+
+    #[test]
+    fn test_amounts_roundtrip_bson_and_json() {
+        #[derive(Serialize, Deserialize)]
+        struct Priced {
+            #[serde(with = "json")]
+            price: Money,
+        }
+        let price = Money::new(Decimal::new(8990, 2), Currency::EUR);
+
+        // Written in the storage format, read back from BSON
+        let document = bson::to_document(&Priced { price }).unwrap();
+        #[cfg(feature = "bson-storage")]
+        {
+            let stored = bson::to_document(&price).unwrap();
+            assert_eq!(stored.get("amount"), Some(&price.to_decimal128().into()));
+        }
+        let parsed: Priced = bson::from_document(document).unwrap();
+        assert_eq!(parsed.price, price);
+        let stored: Money = bson::from_bson(bson::Bson::from(price)).unwrap();
+        assert_eq!(stored, price);
+
+        // Sent as a string whatever the storage format
+        let sent = serde_json::to_value(Priced { price }).unwrap();
+        assert_eq!(
+            sent,
+            serde_json::json!({ "price": { "amount": "89.90", "currency": "EUR" } })
+        );
+        let parsed: Priced = serde_json::from_value(sent).unwrap();
+        assert_eq!(parsed.price, price);
+    }
+
+    #[test]
+    fn test_amounts_stay_exact() {
+        let price = Money::from_f64(33.33, Currency::EUR).unwrap();
+        let total = price.times(Decimal::from(3));
+
+        assert_eq!(total.amount, Decimal::new(9999, 2));
+        assert_eq!(
+            Money::new(Decimal::new(10005, 3), Currency::EUR)
+                .round()
+                .amount,
+            Decimal::new(1001, 2)
+        );
+        assert_eq!(price.checked_add(Money::zero(Currency::USD)), None);
+        assert_eq!(
+            Money::sum_by_currency([price, Money::new(Decimal::from(10), Currency::USD), price,]),
+            vec![
+                Money::new(Decimal::new(6666, 2), Currency::EUR),
+                Money::new(Decimal::from(10), Currency::USD),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prices_are_positive_cents() {
+        assert!(validate_price(&Money::new(Decimal::new(8990, 2), Currency::EUR)).is_ok());
+        assert_eq!(
+            validate_price(&Money::zero(Currency::EUR))
+                .unwrap_err()
+                .code,
+            "range"
+        );
+        assert_eq!(
+            validate_price(&Money::new(Decimal::new(1001, 3), Currency::EUR))
+                .unwrap_err()
+                .code,
+            "precision"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// SENSITIVE VALUES
+// =============================================================================
+
+/// Personal data field. Stored encrypted by the API, returned in clear to the
+/// callers allowed to read it and masked to the others.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum SensitiveString {
+    Encrypted(EncryptedValue),
+    Plain(String),
+}
+
+/// AES-256-GCM ciphertext of a field, with the ID of the key that sealed it
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EncryptedValue {
+    pub kid: String,
+    pub nonce: String,      // Base64
+    pub ciphertext: String, // Base64, tag included
+}
+
+/// Driver of a booking, every field is personal data
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct DriverDetails {
+    #[schemars(with = "String")]
+    pub license_number: SensitiveString,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub phone: Option<SensitiveString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub address: Option<SensitiveString>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl SensitiveString {
+    pub fn plain(&self) -> Option<&str> {
+        match self {
+            SensitiveString::Plain(value) => Some(value),
+            SensitiveString::Encrypted(_) => None,
+        }
+    }
+
+    /// Masked form shown to callers without access: the last 2 characters of a
+    /// clear value, nothing of an encrypted one
+    pub fn masked(&self) -> String {
+        match self {
+            SensitiveString::Plain(value) => {
+                let chars: Vec<char> = value.chars().collect();
+                let hidden = if chars.len() <= 2 {
+                    chars.len()
+                } else {
+                    chars.len() - 2
+                };
+                "*".repeat(hidden) + &chars[hidden..].iter().collect::<String>()
+            }
+            SensitiveString::Encrypted(_) => "********".to_string(),
+        }
+    }
+}
+
+impl From<&str> for SensitiveString {
+    fn from(value: &str) -> Self {
+        SensitiveString::Plain(value.to_string())
+    }
+}
+
+impl DriverDetails {
+    pub fn fields_mut(&mut self) -> Vec<&mut SensitiveString> {
+        let mut fields = vec![&mut self.license_number];
+        fields.extend(self.phone.as_mut());
+        fields.extend(self.address.as_mut());
+        fields
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sensitive_string_formats() {
+        let plain: SensitiveString = serde_json::from_str(r#""B1234567""#).unwrap();
+        assert_eq!(plain.plain(), Some("B1234567"));
+        assert_eq!(plain.masked(), "******67");
+        assert_eq!(SensitiveString::from("AB").masked(), "**");
+
+        let encrypted: SensitiveString =
+            serde_json::from_str(r#"{ "kid": "k1", "nonce": "bm9uY2U", "ciphertext": "Y3Q" }"#)
+                .unwrap();
+        assert!(matches!(encrypted, SensitiveString::Encrypted(_)));
+        assert_eq!(encrypted.masked(), "********");
+    }
+}
@@ -0,0 +1,257 @@
+use serde::{Deserialize, Deserializer, Serializer};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Deserialize a comma-separated string into a Vec<T>
+pub fn deserialize_comma_separated<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s {
+        Some(s) if !s.trim().is_empty() => {
+            let items: Result<Vec<T>, _> = s
+                .split(',')
+                .map(|item| item.trim())
+                .filter(|item| !item.is_empty())
+                .map(|item| item.parse::<T>())
+                .collect();
+
+            items
+                .map(Some)
+                .map_err(|e| serde::de::Error::custom(format!("Failed to parse item: {}", e)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Serialize a Vec<T> into a comma-separated string (inverse of deserialize_comma_separated)
+pub fn serialize_comma_separated<S, T>(
+    values: &Option<Vec<T>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Display,
+{
+    match values {
+        Some(values) => serializer.serialize_str(
+            &values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// DateTime<Utc> written as a BSON datetime (`bson-storage`) or an RFC 3339 string,
+/// read back from either format
+pub mod datetime {
+    use bson::Bson;
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S>(value: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[cfg(feature = "bson-storage")]
+        {
+            bson::DateTime::from_chrono(*value).serialize(serializer)
+        }
+        #[cfg(not(feature = "bson-storage"))]
+        {
+            value.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Bson::deserialize(deserializer)? {
+            Bson::DateTime(date) => Ok(date.to_chrono()),
+            Bson::String(s) => DateTime::parse_from_rfc3339(&s)
+                .map(|date| date.with_timezone(&Utc))
+                .map_err(serde::de::Error::custom),
+            other => Err(serde::de::Error::custom(format!(
+                "Expected a datetime, found {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Same as `datetime` for optional dates
+pub mod option_datetime {
+    use bson::Bson;
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(value) => super::datetime::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Bson::deserialize(deserializer)? {
+            Bson::Null => Ok(None),
+            Bson::DateTime(date) => Ok(Some(date.to_chrono())),
+            Bson::String(s) => DateTime::parse_from_rfc3339(&s)
+                .map(|date| Some(date.with_timezone(&Utc)))
+                .map_err(serde::de::Error::custom),
+            other => Err(serde::de::Error::custom(format!(
+                "Expected a datetime, found {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// ObjectId written as a plain hex string, read from a hex string or extended JSON
+pub mod object_id {
+    use bson::oid::ObjectId;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &ObjectId, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&value.to_hex())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<ObjectId, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        ObjectId::deserialize(deserializer)
+    }
+}
+
+/// Same as `object_id` for optional ids
+pub mod option_object_id {
+    use bson::oid::ObjectId;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &Option<ObjectId>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serializer.serialize_str(&value.to_hex()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<ObjectId>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<ObjectId>::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone, Utc};
+    use serde::Serialize;
+    use serde_json;
+
+    #[derive(serde::Deserialize)]
+    struct TestStruct {
+        #[serde(deserialize_with = "deserialize_comma_separated")]
+        values: Option<Vec<String>>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct TestBrandStruct {
+        #[serde(deserialize_with = "deserialize_comma_separated")]
+        brands: Option<Vec<crate::Brand>>,
+    }
+
+    #[derive(Serialize)]
+    struct TestSerializeStruct {
+        #[serde(serialize_with = "serialize_comma_separated")]
+        brands: Option<Vec<crate::Brand>>,
+    }
+
+    #[derive(Serialize, serde::Deserialize)]
+    struct TestDateStruct {
+        #[serde(with = "datetime")]
+        date: DateTime<Utc>,
+    }
+
+    #[test]
+    fn test_comma_separated_deserialization() {
+        let json = r#"{"values": "a,b,c"}"#;
+        let parsed: TestStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            parsed.values,
+            Some(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_comma_separated_brand_deserialization() {
+        let json = r#"{"brands": "TESLA,MERCEDES"}"#;
+        let parsed: TestBrandStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            parsed.brands,
+            Some(vec![crate::Brand::TESLA, crate::Brand::MERCEDES])
+        );
+    }
+
+    #[test]
+    fn test_comma_separated_serialization() {
+        let value = TestSerializeStruct {
+            brands: Some(vec![crate::Brand::TESLA, crate::Brand::MERCEDES]),
+        };
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"brands":"TESLA,MERCEDES"}"#
+        );
+    }
+
+    #[test]
+    fn test_datetime_roundtrip_bson_and_json() {
+        let date = Utc.with_ymd_and_hms(2025, 8, 1, 10, 0, 0).unwrap();
+
+        // Written in the storage format, read back from BSON
+        let document = bson::to_document(&TestDateStruct { date }).unwrap();
+        #[cfg(feature = "bson-storage")]
+        assert!(document.get_datetime("date").is_ok());
+        let parsed: TestDateStruct = bson::from_document(document).unwrap();
+        assert_eq!(parsed.date, date);
+
+        // Read from an API response
+        let json = r#"{"date": "2025-08-01T10:00:00Z"}"#;
+        let parsed: TestDateStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.date, date);
+    }
+
+    #[test]
+    fn test_object_id_as_hex_string() {
+        #[derive(Serialize, serde::Deserialize)]
+        struct TestIdStruct {
+            #[serde(with = "object_id")]
+            id: bson::oid::ObjectId,
+        }
+
+        let json = r#"{"id":"66b1f0c2a1b2c3d4e5f60718"}"#;
+        let parsed: TestIdStruct = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+    }
+}
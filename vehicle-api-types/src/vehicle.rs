@@ -0,0 +1,416 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use validator::{Validate, ValidationError};
+
+use crate::locale::LocalizedText;
+use crate::money::Money;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, EnumString, Display, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Gearbox {
+    MANUAL,
+    AUTOMATIC,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, EnumString, Display, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum FuelType {
+    PETROL,
+    DIESEL,
+    ELECTRIC,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, EnumString, Display, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Brand {
+    // Car brands
+    TESLA,
+    MERCEDES,
+    // Motorbike brands
+    HONDA,
+    YAMAHA,
+    KAWASAKI,
+    DUCATI,
+    BMW,
+    HARLEY_DAVIDSON,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, EnumString, Display, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CarModel {
+    // Tesla models
+    MODEL_S,
+    MODEL_3,
+    MODEL_X,
+    MODEL_Y,
+    CYBERTRUCK,
+    ROADSTER,
+    // Mercedes models
+    A_CLASS,
+    C_CLASS,
+    E_CLASS,
+    S_CLASS,
+    G_CLASS,
+    GLC,
+    GLE,
+    AMG_GT,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, EnumString, Display, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum MotorbikeModel {
+    SPORTBIKE,
+    CRUISER,
+}
+
+/// Whether a vehicle can be rented, set by its managers
+#[derive(
+    Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, EnumString, Display, PartialEq,
+)]
+#[serde(rename_all = "UPPERCASE")]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum VehicleStatus {
+    #[default]
+    Available,
+    Maintenance, // Temporarily out of service
+    Retired,     // Out of the fleet for good, soft deletion aside
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub enum GeoPointType {
+    #[default]
+    Point,
+}
+
+// =============================================================================
+// METADATA STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CarMetadata {
+    pub model: CarModel,
+    pub seats: u8,
+    pub fuel_type: FuelType,
+    pub gearbox: Gearbox,
+    pub engine_cc: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MotorbikeMetadata {
+    pub model: MotorbikeModel,
+    pub engine_cc: u32,
+    pub has_sidecar: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "metadata", rename_all = "UPPERCASE")]
+pub enum VehicleMetadata {
+    Car(CarMetadata),
+    Motorbike(MotorbikeMetadata),
+}
+
+// =============================================================================
+// MAIN VEHICLE STRUCT
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Vehicle {
+    #[cfg_attr(feature = "bson-storage", serde(rename = "_id", alias = "id"))]
+    #[cfg_attr(
+        not(feature = "bson-storage"),
+        serde(alias = "_id", with = "crate::serde_helpers::option_object_id")
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(rename = "id", with = "Option<String>")]
+    pub id: Option<ObjectId>,
+    pub brand: Brand,
+    #[serde(flatten)]
+    pub metadata: VehicleMetadata,
+    /// Translations by language, narrowed to the `Accept-Language` of the request when sent
+    pub description: Option<LocalizedText>,
+    /// Price and currency, a plain number is read as EUR
+    pub price_by_day: Money,
+    pub year_of_production: u32,
+    #[serde(with = "crate::serde_helpers::datetime")]
+    #[schemars(with = "DateTime<Utc>")]
+    pub added_at: DateTime<Utc>,
+    pub added_by: String,
+    /// IANA time zone of the branch the vehicle is rented from, booking dates are local to it
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// ISO 3166-1 alpha-2 country of the branch, selects the holiday calendar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// Rental company owning the vehicle, set by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// Set when the vehicle was soft deleted: kept for its bookings, hidden everywhere else
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_helpers::option_datetime"
+    )]
+    #[schemars(with = "Option<DateTime<Utc>>")]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Photos, in upload order, served by GET /vehicles/{id}/images/{image_id}
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<VehicleImage>,
+    /// Only AVAILABLE vehicles can be booked, stored vehicles without one are
+    #[serde(default)]
+    pub status: VehicleStatus,
+    /// Where the vehicle is picked up, found by the `lat`/`lng` filters of GET /vehicles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<GeoPoint>,
+    /// Pickup station, its location, time zone and opening hours apply to the vehicle
+    #[cfg_attr(
+        not(feature = "bson-storage"),
+        serde(with = "crate::serde_helpers::option_object_id")
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub station_id: Option<ObjectId>,
+}
+
+/// GeoJSON point, `{"type": "Point", "coordinates": [longitude, latitude]}`
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct GeoPoint {
+    #[serde(rename = "type", default)]
+    pub kind: GeoPointType,
+    pub coordinates: [f64; 2],
+}
+
+/// Photo of a vehicle, its bytes are kept in the file storage
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct VehicleImage {
+    pub id: String,           // Hex ObjectId, unique per upload
+    pub content_type: String, // e.g. "image/jpeg", detected from the file itself
+    pub size_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>, // Name of the uploaded file, informative only
+    pub uploaded_by: String, // User ID
+    #[serde(with = "crate::serde_helpers::datetime")]
+    #[schemars(with = "DateTime<Utc>")]
+    pub uploaded_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct CreateVehicleRequest {
+    pub brand: Brand,
+    #[serde(flatten)]
+    pub metadata: VehicleMetadata,
+    /// A plain string or translations by language, each 1 to 249 characters
+    #[validate(custom(function = "crate::locale::validate_translations"))]
+    pub description: Option<LocalizedText>,
+    /// `{"amount": 89.9, "currency": "USD"}`, or a plain amount in EUR
+    #[validate(custom(function = "crate::money::validate_price"))]
+    pub price_by_day: Money,
+    #[validate(range(min = 1900, max = 2030, message = "Year must be between 1900 and 2030"))]
+    pub year_of_production: u32,
+    /// IANA time zone name (e.g. "Europe/Paris"), defaults to UTC
+    #[validate(length(min = 1, max = 64))]
+    pub timezone: Option<String>,
+    /// ISO 3166-1 alpha-2 country code (e.g. "FR")
+    #[validate(length(equal = 2, message = "Country must be a 2 letters ISO code"))]
+    pub country: Option<String>,
+    /// GeoJSON point of the pickup place
+    #[validate(custom(function = "validate_location"))]
+    pub location: Option<GeoPoint>,
+    /// Pickup station, sets the location and time zone
+    #[serde(default, with = "crate::serde_helpers::option_object_id")]
+    #[schemars(with = "Option<String>")]
+    pub station_id: Option<ObjectId>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct UpdateVehicleRequest {
+    /// Translations to add or replace, the other languages are kept
+    #[validate(custom(function = "crate::locale::validate_translations"))]
+    pub description: Option<LocalizedText>,
+    #[validate(custom(function = "crate::money::validate_price"))]
+    pub price_by_day: Option<Money>,
+    /// Pickup place moved
+    #[validate(custom(function = "validate_location"))]
+    pub location: Option<GeoPoint>,
+    /// Vehicle moved to another station, sets the location and time zone
+    #[serde(default, with = "crate::serde_helpers::option_object_id")]
+    #[schemars(with = "Option<String>")]
+    pub station_id: Option<ObjectId>,
+}
+
+/// Body of PUT /vehicles/{vehicle_id}/status
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct UpdateVehicleStatusRequest {
+    pub status: VehicleStatus,
+    /// Why, kept in the audit log
+    #[validate(length(min = 1, max = 500))]
+    pub reason: Option<String>,
+}
+
+// =============================================================================
+// FILTERING AND PAGINATION STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct VehicleFilters {
+    // Brand and model filters (comma-separated, using enum types)
+    #[serde(
+        serialize_with = "crate::serde_helpers::serialize_comma_separated",
+        deserialize_with = "crate::serde_helpers::deserialize_comma_separated",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub brand: Option<Vec<Brand>>,
+
+    // Model can be either CarModel or MotorbikeModel, so we keep it as String for flexibility
+    #[serde(
+        serialize_with = "crate::serde_helpers::serialize_comma_separated",
+        deserialize_with = "crate::serde_helpers::deserialize_comma_separated",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub model: Option<Vec<String>>,
+
+    // Price filters (min and max separately), in the `currency` of the request
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+
+    // Year filters (comma-separated)
+    #[serde(
+        serialize_with = "crate::serde_helpers::serialize_comma_separated",
+        deserialize_with = "crate::serde_helpers::deserialize_comma_separated",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub year_of_production: Option<Vec<u32>>,
+
+    // Vehicle metadata filters (using enum types)
+    #[serde(
+        serialize_with = "crate::serde_helpers::serialize_comma_separated",
+        deserialize_with = "crate::serde_helpers::deserialize_comma_separated",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub fuel_type: Option<Vec<FuelType>>,
+
+    #[serde(
+        serialize_with = "crate::serde_helpers::serialize_comma_separated",
+        deserialize_with = "crate::serde_helpers::deserialize_comma_separated",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub gearbox: Option<Vec<Gearbox>>,
+
+    #[serde(
+        serialize_with = "crate::serde_helpers::serialize_comma_separated",
+        deserialize_with = "crate::serde_helpers::deserialize_comma_separated",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub seats: Option<Vec<u8>>,
+
+    #[serde(
+        serialize_with = "crate::serde_helpers::serialize_comma_separated",
+        deserialize_with = "crate::serde_helpers::deserialize_comma_separated",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub engine_cc: Option<Vec<u32>>,
+
+    // Boolean filter for motorbike sidecar
+    pub has_sidecar: Option<bool>,
+
+    // Date range filters (for added_at field)
+    pub added_at_from: Option<DateTime<Utc>>,
+    pub added_at_to: Option<DateTime<Utc>>,
+
+    // Vehicles within `radius_km` of a point, nearest first
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub radius_km: Option<f64>,
+
+    // Vehicles of a pickup station, hex ObjectId
+    pub station_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct VehiclePagination {
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+    pub sort: Option<String>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS - CORE VEHICLE METHODS
+// =============================================================================
+
+/// Time zone of vehicles and bookings created before time zones were stored
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+
+pub fn default_timezone() -> String {
+    DEFAULT_TIMEZONE.to_string()
+}
+
+impl Vehicle {
+    pub fn new(request: CreateVehicleRequest, added_by: String) -> Result<Self, String> {
+        Ok(Self {
+            id: None,
+            brand: request.brand,
+            metadata: request.metadata,
+            description: request.description,
+            price_by_day: request.price_by_day,
+            year_of_production: request.year_of_production,
+            added_at: Utc::now(),
+            added_by,
+            timezone: request.timezone.unwrap_or_else(default_timezone),
+            country: request.country.map(|country| country.to_uppercase()),
+            tenant_id: None,
+            deleted_at: None,
+            images: Vec::new(),
+            status: VehicleStatus::Available,
+            location: request.location,
+            station_id: request.station_id,
+        })
+    }
+}
+
+impl GeoPoint {
+    pub fn new(lat: f64, lng: f64) -> Self {
+        Self {
+            kind: GeoPointType::Point,
+            coordinates: [lng, lat],
+        }
+    }
+
+    pub fn lat(&self) -> f64 {
+        self.coordinates[1]
+    }
+
+    pub fn lng(&self) -> f64 {
+        self.coordinates[0]
+    }
+
+    /// Latitude within ±90 and longitude within ±180 degrees
+    pub fn is_valid(&self) -> bool {
+        (-90.0..=90.0).contains(&self.lat()) && (-180.0..=180.0).contains(&self.lng())
+    }
+}
+
+pub fn validate_location(location: &GeoPoint) -> Result<(), ValidationError> {
+    if !location.is_valid() {
+        let mut error = ValidationError::new("range");
+        error.message = Some("Coordinates must be [longitude, latitude] in degrees".into());
+        return Err(error);
+    }
+    Ok(())
+}
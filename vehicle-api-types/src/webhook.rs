@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// SIGNING KEY STRUCTS (JWKS)
+// =============================================================================
+
+/// Public webhook signing key in JWK format (Ed25519 / OKP)
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Jwk {
+    pub kty: String,
+    pub crv: String,
+    pub kid: String,
+    pub x: String, // base64url encoded public key
+    #[serde(rename = "use")]
+    pub key_use: String,
+    pub alg: String,
+    #[serde(default)]
+    pub active: bool, // Key currently used for signing, others are kept for rotation
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
@@ -0,0 +1,89 @@
+use bson::doc;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+
+use super::identity::{Identity, Role};
+use crate::error::{AppError, AppResult};
+use crate::models::ApiKey;
+use crate::services;
+
+/// Prefix of generated keys, makes leaked keys easy to spot
+pub const KEY_PREFIX: &str = "vk_";
+
+/// Generate a new random API key
+pub fn generate_key() -> String {
+    format!("{}{}", KEY_PREFIX, super::session::random_token(32))
+}
+
+/// SHA-256 hash (hex) under which a key is stored
+pub fn hash_key(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Resolve an API key to an identity, keys on the revocation list are rejected with
+/// `Unauthorized`, expired keys with `ApiKeyExpired` and keys used outside of their
+/// allowed networks with `Forbidden`.
+/// BOOTSTRAP_ADMIN_API_KEY, when set, grants Admin so the first keys can be created.
+pub async fn resolve_identity(key: &str, client_ip: Option<IpAddr>) -> AppResult<Option<Identity>> {
+    let key_hash = hash_key(key);
+    if super::revocation::is_revoked(&key_hash) {
+        return Err(AppError::unauthorized("API key has been revoked"));
+    }
+
+    if let Ok(bootstrap_key) = std::env::var("BOOTSTRAP_ADMIN_API_KEY") {
+        if !bootstrap_key.is_empty() && hash_key(&bootstrap_key) == key_hash {
+            return Ok(Some(Identity {
+                role: Role::Admin,
+                user_id: "bootstrap-admin".to_string(),
+                email: None,
+                tenant_id: None,
+                permissions: Vec::new(),
+                profile: None,
+                impersonated_by: None,
+            }));
+        }
+    }
+
+    let filter = doc! { "key_hash": key_hash, "revoked_at": null };
+    let api_key: Option<ApiKey> = services::mongodb::get_one(filter, None).await?;
+
+    match api_key {
+        Some(api_key) if api_key.is_expired(chrono::Utc::now()) => {
+            Err(AppError::api_key_expired(format!(
+                "key {} expired at {}. Ask an admin to rotate it with POST /protected/api-keys/{}/rotate",
+                api_key.prefix,
+                api_key.expires_at.map(|date| date.to_rfc3339()).unwrap_or_default(),
+                api_key.id.map(|id| id.to_hex()).unwrap_or_default()
+            )))
+        }
+        Some(api_key) if !api_key.allows(client_ip) => Err(AppError::forbidden(format!(
+            "API key {} cannot be used from {}, it is restricted to {}",
+            api_key.prefix,
+            client_ip
+                .map(|ip| ip.to_string())
+                .unwrap_or_else(|| "an unknown address".to_string()),
+            api_key.allowed_networks.join(", ")
+        ))),
+        Some(api_key) => Ok(Some(api_key.identity())),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_keys_are_unique_and_prefixed() {
+        let first = generate_key();
+        let second = generate_key();
+
+        assert!(first.starts_with(KEY_PREFIX));
+        assert_ne!(first, second);
+        assert_eq!(hash_key(&first).len(), 64);
+        assert_eq!(hash_key(&first), hash_key(&first));
+    }
+}
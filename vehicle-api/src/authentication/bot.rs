@@ -0,0 +1,448 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderMap, ACCEPT, ACCEPT_LANGUAGE, USER_AGENT},
+    middleware, Error, ResponseError, Result,
+};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::{LazyLock, Mutex};
+
+use crate::error::AppError;
+use crate::models::{BotAction, BotClient, BotOverride};
+
+/// Clients are pruned once the map holds this many
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Request times kept per client, enough to tell any configured velocity apart
+const MAX_TRACKED_REQUESTS: usize = 1_000;
+
+/// User agents of HTTP libraries and crawlers, lowercase
+const AUTOMATION_USER_AGENTS: [&str; 11] = [
+    "curl",
+    "wget",
+    "python-requests",
+    "python-urllib",
+    "scrapy",
+    "go-http-client",
+    "okhttp",
+    "headless",
+    "bot",
+    "crawler",
+    "spider",
+];
+
+/// Scores and responses of the bot detection
+pub struct BotPolicy {
+    pub enabled: bool,
+    pub throttle_score: u32,
+    pub tarpit_score: u32,
+    pub block_score: u32,
+    pub velocity_per_minute: usize, // More anonymous requests than this look automated
+    pub throttled_per_minute: usize, // Allowed to throttled clients
+    pub tarpit_delay: std::time::Duration,
+    /// Query parameters no form of the API sends, only bots filling every field do
+    pub honeypot_params: Vec<String>,
+}
+
+/// Recent requests of an anonymous client
+#[derive(Clone, Debug)]
+pub struct ClientRecord {
+    requests: VecDeque<DateTime<Utc>>,
+    honeypot_hits: u32,
+    score: u32,
+    signals: Vec<&'static str>,
+    first_seen_at: DateTime<Utc>,
+    override_action: Option<BotOverride>,
+}
+
+// Read once from the BOT_* variables
+pub(super) static BOT_POLICY: LazyLock<BotPolicy> = LazyLock::new(BotPolicy::from_env);
+
+// Clients by source address, per instance like the rate limit buckets
+static CLIENTS: LazyLock<Mutex<HashMap<IpAddr, ClientRecord>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+impl BotPolicy {
+    /// Disabled unless BOT_DETECTION=true. Scores of 40, 70 and 100 throttle, tarpit
+    /// and block; more than 120 requests a minute is suspicious.
+    pub fn from_env() -> Self {
+        let read = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or(default)
+        };
+        Self {
+            enabled: std::env::var("BOT_DETECTION").as_deref() == Ok("true"),
+            throttle_score: read("BOT_THROTTLE_SCORE", 40) as u32,
+            tarpit_score: read("BOT_TARPIT_SCORE", 70) as u32,
+            block_score: read("BOT_BLOCK_SCORE", 100) as u32,
+            velocity_per_minute: read("BOT_VELOCITY_PER_MINUTE", 120) as usize,
+            throttled_per_minute: read("BOT_THROTTLED_PER_MINUTE", 10) as usize,
+            tarpit_delay: std::time::Duration::from_millis(read("BOT_TARPIT_MS", 3_000)),
+            honeypot_params: std::env::var("BOT_HONEYPOT_PARAMS")
+                .unwrap_or_else(|_| "website,fax".to_string())
+                .split(',')
+                .map(|param| param.trim().to_string())
+                .filter(|param| !param.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Action for a score, an admin override wins
+    pub fn action(&self, score: u32, override_action: Option<BotOverride>) -> BotAction {
+        match override_action {
+            Some(BotOverride::Allow) => BotAction::Allow,
+            Some(BotOverride::Block) => BotAction::Block,
+            None if score >= self.block_score => BotAction::Block,
+            None if score >= self.tarpit_score => BotAction::Tarpit,
+            None if score >= self.throttle_score => BotAction::Throttle,
+            None => BotAction::Allow,
+        }
+    }
+
+    /// Whether a query string sets one of the honeypot parameters
+    pub fn hits_honeypot(&self, query: &str) -> bool {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split('=').next())
+            .any(|name| self.honeypot_params.iter().any(|param| param == name))
+    }
+}
+
+/// Heuristics on the headers of a request, with their score. Browsers always send a
+/// user agent, `Accept` and `Accept-Language`; HTTP libraries name themselves.
+pub fn fingerprint(headers: &HeaderMap) -> Vec<(&'static str, u32)> {
+    let mut signals = Vec::new();
+    match headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+    {
+        None | Some("") => signals.push(("missing_user_agent", 40)),
+        Some(user_agent) => {
+            let user_agent = user_agent.to_ascii_lowercase();
+            if AUTOMATION_USER_AGENTS
+                .iter()
+                .any(|automation| user_agent.contains(automation))
+            {
+                signals.push(("automation_user_agent", 50));
+            }
+        }
+    }
+    if !headers.contains_key(ACCEPT) {
+        signals.push(("missing_accept", 10));
+    }
+    if !headers.contains_key(ACCEPT_LANGUAGE) {
+        signals.push(("missing_accept_language", 15));
+    }
+    signals
+}
+
+impl ClientRecord {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            requests: VecDeque::new(),
+            honeypot_hits: 0,
+            score: 0,
+            signals: Vec::new(),
+            first_seen_at: now,
+            override_action: None,
+        }
+    }
+
+    /// Requests of the last minute, the one being scored included
+    pub fn requests_last_minute(&self, now: DateTime<Utc>) -> usize {
+        self.requests
+            .iter()
+            .filter(|at| now - **at < Duration::minutes(1))
+            .count()
+    }
+
+    /// Record a request and score the client: header heuristics of the request, its
+    /// velocity, and honeypot hits which are never forgotten
+    pub fn observe(
+        &mut self,
+        policy: &BotPolicy,
+        fingerprint: Vec<(&'static str, u32)>,
+        honeypot: bool,
+        now: DateTime<Utc>,
+    ) -> BotAction {
+        while self
+            .requests
+            .front()
+            .is_some_and(|at| now - *at >= Duration::minutes(1))
+            || self.requests.len() >= MAX_TRACKED_REQUESTS
+        {
+            self.requests.pop_front();
+        }
+        self.requests.push_back(now);
+        if honeypot {
+            self.honeypot_hits += 1;
+        }
+
+        let mut signals = fingerprint;
+        if self.requests_last_minute(now) > policy.velocity_per_minute {
+            signals.push(("high_velocity", 40));
+        }
+        if self.honeypot_hits > 0 {
+            signals.push(("honeypot", policy.block_score));
+        }
+        self.score = signals.iter().map(|(_, score)| score).sum();
+        self.signals = signals.into_iter().map(|(signal, _)| signal).collect();
+
+        // Throttled clients are only turned away beyond their allowance
+        let action = policy.action(self.score, self.override_action);
+        if action == BotAction::Throttle
+            && self.requests_last_minute(now) <= policy.throttled_per_minute
+        {
+            return BotAction::Allow;
+        }
+        action
+    }
+
+    fn last_seen_at(&self) -> DateTime<Utc> {
+        self.requests.back().copied().unwrap_or(self.first_seen_at)
+    }
+
+    fn to_client(&self, ip: &IpAddr, policy: &BotPolicy, now: DateTime<Utc>) -> BotClient {
+        BotClient {
+            ip: ip.to_string(),
+            score: self.score,
+            action: policy.action(self.score, self.override_action),
+            signals: self
+                .signals
+                .iter()
+                .map(|signal| signal.to_string())
+                .collect(),
+            requests_last_minute: self.requests_last_minute(now),
+            honeypot_hits: self.honeypot_hits,
+            first_seen_at: self.first_seen_at,
+            last_seen_at: self.last_seen_at(),
+            override_action: self.override_action,
+        }
+    }
+}
+
+/// Score a request of an anonymous client and decide what to do with it
+fn observe(ip: IpAddr, headers: &HeaderMap, query: &str) -> BotAction {
+    let policy = &*BOT_POLICY;
+    let Ok(mut clients) = CLIENTS.lock() else {
+        return BotAction::Allow;
+    };
+
+    let now = Utc::now();
+    if clients.len() >= MAX_TRACKED_CLIENTS {
+        // Quiet clients nobody made a decision about are forgotten first
+        clients.retain(|_, record| {
+            record.override_action.is_some() || now - record.last_seen_at() < Duration::minutes(10)
+        });
+    }
+
+    clients
+        .entry(ip)
+        .or_insert_with(|| ClientRecord::new(now))
+        .observe(
+            policy,
+            fingerprint(headers),
+            policy.hits_honeypot(query),
+            now,
+        )
+}
+
+/// Clients scored at least the throttle score or overridden by an admin, highest first
+pub fn list() -> Vec<BotClient> {
+    let policy = &*BOT_POLICY;
+    let now = Utc::now();
+    let Ok(clients) = CLIENTS.lock() else {
+        return Vec::new();
+    };
+
+    let mut flagged: Vec<BotClient> = clients
+        .iter()
+        .filter(|(_, record)| {
+            record.override_action.is_some() || record.score >= policy.throttle_score
+        })
+        .map(|(ip, record)| record.to_client(ip, policy, now))
+        .collect();
+    flagged.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(b.last_seen_at.cmp(&a.last_seen_at))
+    });
+    flagged
+}
+
+/// Allow or block a client whatever its score
+pub fn set_override(ip: IpAddr, action: BotOverride) -> Option<BotClient> {
+    let now = Utc::now();
+    let mut clients = CLIENTS.lock().ok()?;
+    let record = clients.entry(ip).or_insert_with(|| ClientRecord::new(now));
+    record.override_action = Some(action);
+    Some(record.to_client(&ip, &BOT_POLICY, now))
+}
+
+/// Forget a client, its override included, false when it was not tracked
+pub fn clear(ip: IpAddr) -> bool {
+    CLIENTS
+        .lock()
+        .map(|mut clients| clients.remove(&ip).is_some())
+        .unwrap_or(false)
+}
+
+// Bot Detection Middleware using from_fn: scores anonymous requests and throttles,
+// tarpits or blocks suspected scrapers. Callers with credentials are rate limited instead.
+pub async fn bot_detection_middleware(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let authenticated = super::middleware::extract_api_key(req.request()).is_some()
+        || super::middleware::extract_bearer_token(req.request()).is_some();
+    let ip = super::middleware::client_ip(req.request());
+
+    let action = match ip {
+        Some(ip) if BOT_POLICY.enabled && !authenticated && !req.path().starts_with("/health") => {
+            observe(ip, req.headers(), req.query_string())
+        }
+        _ => BotAction::Allow,
+    };
+
+    let error = match action {
+        BotAction::Allow => None,
+        BotAction::Tarpit => {
+            actix_web::rt::time::sleep(BOT_POLICY.tarpit_delay).await;
+            None
+        }
+        BotAction::Throttle => Some(AppError::too_many_requests(
+            "Too many requests from this address, slow down",
+            60,
+        )),
+        BotAction::Block => {
+            log::warn!("Blocked a suspected bot from {:?} on {}", ip, req.path());
+            Some(AppError::forbidden("Automated traffic is not allowed"))
+        }
+    };
+
+    match error {
+        None => Ok(next.call(req).await?.map_into_left_body()),
+        Some(error) => Ok(req
+            .into_response(error.error_response())
+            .map_into_right_body()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::HeaderValue;
+
+    fn policy() -> BotPolicy {
+        BotPolicy {
+            enabled: true,
+            throttle_score: 40,
+            tarpit_score: 70,
+            block_score: 100,
+            velocity_per_minute: 5,
+            throttled_per_minute: 3,
+            tarpit_delay: std::time::Duration::from_millis(10),
+            honeypot_params: vec!["website".to_string()],
+        }
+    }
+
+    fn browser() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            USER_AGENT,
+            HeaderValue::from_static("Mozilla/5.0 (X11; Linux x86_64) Firefox/128.0"),
+        );
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("text/html,application/json"),
+        );
+        headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("fr-FR,fr;q=0.9"));
+        headers
+    }
+
+    #[test]
+    fn test_fingerprint_of_http_libraries() {
+        assert!(fingerprint(&browser()).is_empty());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, HeaderValue::from_static("python-requests/2.32"));
+        let signals: Vec<_> = fingerprint(&headers)
+            .into_iter()
+            .map(|(signal, _)| signal)
+            .collect();
+        assert_eq!(
+            signals,
+            vec![
+                "automation_user_agent",
+                "missing_accept",
+                "missing_accept_language"
+            ]
+        );
+        assert_eq!(
+            fingerprint(&headers)
+                .iter()
+                .map(|(_, score)| score)
+                .sum::<u32>(),
+            75
+        );
+    }
+
+    #[test]
+    fn test_velocity_and_honeypot_escalate() {
+        let policy = policy();
+        let now = Utc::now();
+        let mut record = ClientRecord::new(now);
+
+        for _ in 0..5 {
+            assert_eq!(
+                record.observe(&policy, fingerprint(&browser()), false, now),
+                BotAction::Allow
+            );
+        }
+        // The 6th request of the minute is fast for a human, the throttled allowance is spent
+        assert_eq!(
+            record.observe(&policy, fingerprint(&browser()), false, now),
+            BotAction::Throttle
+        );
+        assert_eq!(record.signals, vec!["high_velocity"]);
+
+        // A minute later the client is back to normal
+        let later = now + Duration::seconds(61);
+        assert_eq!(
+            record.observe(&policy, fingerprint(&browser()), false, later),
+            BotAction::Allow
+        );
+
+        // Honeypots are never forgotten, unless an admin allows the client
+        assert!(policy.hits_honeypot("brand=Fiat&website="));
+        assert!(!policy.hits_honeypot("brand=Fiat&websites=1"));
+        assert_eq!(
+            record.observe(&policy, fingerprint(&browser()), true, later),
+            BotAction::Block
+        );
+        let much_later = later + Duration::hours(1);
+        assert_eq!(
+            record.observe(&policy, fingerprint(&browser()), false, much_later),
+            BotAction::Block
+        );
+        record.override_action = Some(BotOverride::Allow);
+        assert_eq!(
+            record.observe(&policy, fingerprint(&browser()), false, much_later),
+            BotAction::Allow
+        );
+    }
+
+    #[test]
+    fn test_actions_by_score() {
+        let policy = policy();
+        assert_eq!(policy.action(25, None), BotAction::Allow);
+        assert_eq!(policy.action(40, None), BotAction::Throttle);
+        assert_eq!(policy.action(75, None), BotAction::Tarpit);
+        assert_eq!(policy.action(100, None), BotAction::Block);
+        assert_eq!(policy.action(0, Some(BotOverride::Block)), BotAction::Block);
+    }
+}
@@ -0,0 +1,236 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::Method,
+    middleware, Error, ResponseError, Result,
+};
+use serde::Deserialize;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use crate::error::{AppError, AppResult};
+
+/// Header carrying the token the captcha widget gave the client
+pub const CAPTCHA_TOKEN_HEADER: &str = "X-Captcha-Token";
+
+/// Checks a captcha token with its provider. Turnstile, hCaptcha and reCAPTCHA share
+/// the same siteverify API, another provider only has to implement this trait.
+pub(crate) trait CaptchaVerifier {
+    /// Whether the provider accepts the token, errors when it cannot be reached
+    async fn verify(&self, token: &str, remote_ip: Option<IpAddr>) -> AppResult<bool>;
+}
+
+/// Provider answering on a siteverify endpoint
+pub struct SiteVerifyCaptcha {
+    url: &'static str,
+    secret: String,
+    client: reqwest::Client,
+}
+
+/// Verifier selected by CAPTCHA_PROVIDER
+pub enum CaptchaBackend {
+    SiteVerify(SiteVerifyCaptcha),
+}
+
+/// Routes requiring a captcha, `*` at the end of a path matches any suffix
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CaptchaRoutes {
+    routes: Vec<(Method, String)>,
+}
+
+#[derive(Deserialize)]
+struct SiteVerifyResponse {
+    success: bool,
+}
+
+// Read once from CAPTCHA_ROUTES
+pub(super) static CAPTCHA_ROUTES: LazyLock<CaptchaRoutes> = LazyLock::new(CaptchaRoutes::from_env);
+
+// Selected once from CAPTCHA_PROVIDER and CAPTCHA_SECRET
+pub(super) static CAPTCHA_BACKEND: LazyLock<Option<CaptchaBackend>> =
+    LazyLock::new(CaptchaBackend::from_env);
+
+impl CaptchaRoutes {
+    /// CAPTCHA_ROUTES="GET /catalog/*,POST /auth/service-token", none by default
+    pub fn from_env() -> Self {
+        let value = std::env::var("CAPTCHA_ROUTES").unwrap_or_default();
+        Self::parse(&value).unwrap_or_else(|error| {
+            log::error!("Invalid CAPTCHA_ROUTES, captcha checks disabled: {}", error);
+            Self::default()
+        })
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let routes = value
+            .split(',')
+            .map(|entry| entry.trim())
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (method, path) = entry
+                    .split_once(' ')
+                    .ok_or_else(|| format!("Expected METHOD /path, got {}", entry))?;
+                let method = Method::from_str(&method.to_ascii_uppercase())
+                    .map_err(|_| format!("Invalid method in {}", entry))?;
+                let path = path.trim();
+                if !path.starts_with('/') {
+                    return Err(format!("Path must start with /, got {}", path));
+                }
+                Ok((method, path.to_string()))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Self { routes })
+    }
+
+    pub fn requires_captcha(&self, method: &Method, path: &str) -> bool {
+        self.routes.iter().any(|(route_method, route_path)| {
+            route_method == method
+                && match route_path.strip_suffix('*') {
+                    Some(prefix) => path.starts_with(prefix),
+                    None => path == route_path,
+                }
+        })
+    }
+}
+
+impl CaptchaBackend {
+    /// CAPTCHA_PROVIDER=turnstile|hcaptcha|recaptcha with its CAPTCHA_SECRET
+    pub fn from_env() -> Option<Self> {
+        let provider = std::env::var("CAPTCHA_PROVIDER").ok()?;
+        let url = match provider.as_str() {
+            "turnstile" => "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+            "hcaptcha" => "https://api.hcaptcha.com/siteverify",
+            "recaptcha" => "https://www.google.com/recaptcha/api/siteverify",
+            _ => {
+                log::error!(
+                    "Unknown CAPTCHA_PROVIDER {}, captcha checks disabled",
+                    provider
+                );
+                return None;
+            }
+        };
+        let Ok(secret) = std::env::var("CAPTCHA_SECRET") else {
+            log::error!("CAPTCHA_PROVIDER needs CAPTCHA_SECRET, captcha checks disabled");
+            return None;
+        };
+
+        Some(Self::SiteVerify(SiteVerifyCaptcha {
+            url,
+            secret,
+            client: reqwest::Client::new(),
+        }))
+    }
+}
+
+impl CaptchaVerifier for CaptchaBackend {
+    async fn verify(&self, token: &str, remote_ip: Option<IpAddr>) -> AppResult<bool> {
+        match self {
+            Self::SiteVerify(verifier) => verifier.verify(token, remote_ip).await,
+        }
+    }
+}
+
+impl CaptchaVerifier for SiteVerifyCaptcha {
+    async fn verify(&self, token: &str, remote_ip: Option<IpAddr>) -> AppResult<bool> {
+        let mut form = vec![
+            ("secret", self.secret.clone()),
+            ("response", token.to_string()),
+        ];
+        if let Some(ip) = remote_ip {
+            form.push(("remoteip", ip.to_string()));
+        }
+
+        let response = self
+            .client
+            .post(self.url)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| {
+                AppError::internal_server_error(format!("Captcha verification failed: {}", e))
+            })?;
+        let body: SiteVerifyResponse = response.json().await.map_err(|e| {
+            AppError::internal_server_error(format!("Invalid captcha verification response: {}", e))
+        })?;
+        Ok(body.success)
+    }
+}
+
+// Captcha Middleware using from_fn: anonymous requests to the routes of CAPTCHA_ROUTES
+// must carry a token the provider accepts. Requests with credentials are left to the
+// authentication and rate limits.
+pub async fn captcha_middleware(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let Some(verifier) = CAPTCHA_BACKEND.as_ref() else {
+        return Ok(next.call(req).await?.map_into_left_body());
+    };
+    let authenticated = super::middleware::extract_api_key(req.request()).is_some()
+        || super::middleware::extract_bearer_token(req.request()).is_some();
+    if authenticated || !CAPTCHA_ROUTES.requires_captcha(req.method(), req.path()) {
+        return Ok(next.call(req).await?.map_into_left_body());
+    }
+
+    let token = req
+        .headers()
+        .get(CAPTCHA_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty());
+    let outcome = match token {
+        None => Err(AppError::forbidden(format!(
+            "A captcha token is required in the {} header",
+            CAPTCHA_TOKEN_HEADER
+        ))),
+        Some(token) => {
+            let remote_ip = super::middleware::client_ip(req.request());
+            match verifier.verify(token, remote_ip).await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(AppError::forbidden("Invalid or expired captcha token")),
+                // Fail closed, the routes are protected because they are expensive
+                Err(error) => {
+                    log::error!("{}", error);
+                    Err(AppError::forbidden(
+                        "Captcha could not be verified, try again",
+                    ))
+                }
+            }
+        }
+    };
+
+    match outcome {
+        Ok(()) => Ok(next.call(req).await?.map_into_left_body()),
+        Err(error) => Ok(req
+            .into_response(error.error_response())
+            .map_into_right_body()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_captcha_routes() {
+        let routes = CaptchaRoutes::parse("get /catalog/*, POST /auth/service-token").unwrap();
+
+        assert!(routes.requires_captcha(&Method::GET, "/catalog/vehicles"));
+        assert!(!routes.requires_captcha(&Method::POST, "/catalog/vehicles"));
+        assert!(routes.requires_captcha(&Method::POST, "/auth/service-token"));
+        assert!(!routes.requires_captcha(&Method::POST, "/auth/service-token/extra"));
+        assert_eq!(CaptchaRoutes::parse("").unwrap(), CaptchaRoutes::default());
+        assert!(CaptchaRoutes::parse("/catalog").is_err());
+        assert!(CaptchaRoutes::parse("GET catalog").is_err());
+    }
+
+    #[test]
+    fn test_siteverify_response() {
+        let body: SiteVerifyResponse = serde_json::from_str(
+            r#"{ "success": false, "error-codes": ["timeout-or-duplicate"] }"#,
+        )
+        .unwrap();
+        assert!(!body.success);
+    }
+}
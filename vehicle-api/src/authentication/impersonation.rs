@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+use bson::doc;
+
+use super::identity::{Identity, Role};
+use super::session;
+use crate::error::{AppError, AppResult};
+use crate::models::{Booking, Customer, ImpersonationTokenResponse};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// Issue a short-lived token acting as a customer. Requests made with it carry the
+/// customer's identity and the admin in `impersonated_by`, no refresh token is issued.
+pub async fn issue_token(admin: &Identity, user_id: &str) -> AppResult<ImpersonationTokenResponse> {
+    if admin.impersonated_by.is_some() {
+        return Err(AppError::forbidden(
+            "Impersonation tokens cannot start another impersonation.",
+        ));
+    }
+
+    let customer: Customer = services::mongodb::get_one(doc! { "user_id": user_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Customer not found"))?;
+
+    // Customers are deployment wide: an admin of a tenant only impersonates the ones who
+    // booked with it, and the token stays within that tenant
+    if let Some(tenant_id) = &admin.tenant_id {
+        let filter = doc! { "customer_id": &customer.user_id, "tenant_id": tenant_id };
+        if services::mongodb::count(Booking::get_collection(), filter, None).await? == 0 {
+            return Err(AppError::not_found("Customer not found"));
+        }
+    }
+    let identity = Identity {
+        role: Role::Customer,
+        user_id: customer.user_id,
+        email: customer.email,
+        tenant_id: admin.tenant_id.clone(),
+        permissions: Vec::new(),
+        profile: None,
+        impersonated_by: Some(admin.user_id.clone()),
+    };
+    let (session, token) = session::create_session(&identity, session::impersonation_ttl()).await?;
+
+    notify(&identity, admin);
+    Ok(ImpersonationTokenResponse {
+        token,
+        token_type: "Bearer".to_string(),
+        expires_at: session.expires_at,
+        identity,
+    })
+}
+
+/// Report the start of an impersonation to Sentry
+fn notify(identity: &Identity, admin: &Identity) {
+    log::warn!(
+        "Admin {} impersonates customer {}",
+        admin.user_id,
+        identity.user_id
+    );
+
+    let mut tags = BTreeMap::new();
+    tags.insert("customer_id".to_string(), identity.user_id.clone());
+    tags.insert("impersonated_by".to_string(), admin.user_id.clone());
+
+    sentry::capture_event(sentry::protocol::Event {
+        message: Some(format!(
+            "Admin {} impersonates customer {}",
+            admin.user_id, identity.user_id
+        )),
+        level: sentry::Level::Warning,
+        tags,
+        ..Default::default()
+    });
+}
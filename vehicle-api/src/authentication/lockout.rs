@@ -0,0 +1,189 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{LazyLock, Mutex};
+
+use crate::models::IpLockout;
+
+/// Addresses are pruned once the map holds this many
+const MAX_TRACKED_IPS: usize = 10_000;
+
+/// When an address gets locked out and for how long
+pub struct LockoutPolicy {
+    pub max_failures: u32, // 0 disables lockouts
+    pub duration: Duration,
+}
+
+/// Invalid API key attempts of one address
+#[derive(Clone, Debug)]
+pub struct FailureRecord {
+    consecutive_failures: u32,
+    last_failure_at: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+// Read once from API_KEY_LOCKOUT_THRESHOLD and API_KEY_LOCKOUT_SECS
+pub(super) static LOCKOUT_POLICY: LazyLock<LockoutPolicy> = LazyLock::new(LockoutPolicy::from_env);
+
+// Failures by source address, per instance like the rate limit buckets
+static FAILURES: LazyLock<Mutex<HashMap<IpAddr, FailureRecord>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+impl LockoutPolicy {
+    /// 10 consecutive invalid keys lock the address out for 15 minutes by default
+    pub fn from_env() -> Self {
+        let read = |name: &str, default: i64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse::<i64>().ok())
+                .filter(|value| *value >= 0)
+                .unwrap_or(default)
+        };
+        Self {
+            max_failures: read("API_KEY_LOCKOUT_THRESHOLD", 10) as u32,
+            duration: Duration::seconds(read("API_KEY_LOCKOUT_SECS", 15 * 60)),
+        }
+    }
+}
+
+impl FailureRecord {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            consecutive_failures: 0,
+            last_failure_at: now,
+            locked_until: None,
+        }
+    }
+
+    /// Seconds left before the address may try again, None when it is not locked
+    pub fn retry_after(&self, now: DateTime<Utc>) -> Option<u64> {
+        let locked_until = self.locked_until.filter(|until| *until > now)?;
+        Some(((locked_until - now).num_milliseconds() as u64).div_ceil(1000))
+    }
+
+    /// Count an invalid key, the address is locked out when it reaches the threshold
+    pub fn fail(&mut self, policy: &LockoutPolicy, now: DateTime<Utc>) {
+        self.consecutive_failures += 1;
+        self.last_failure_at = now;
+        if self.consecutive_failures >= policy.max_failures {
+            self.consecutive_failures = 0;
+            self.locked_until = Some(now + policy.duration);
+        }
+    }
+
+    fn is_stale(&self, policy: &LockoutPolicy, now: DateTime<Utc>) -> bool {
+        self.retry_after(now).is_none() && self.last_failure_at + policy.duration <= now
+    }
+
+    fn to_lockout(&self, ip: &IpAddr, now: DateTime<Utc>) -> IpLockout {
+        IpLockout {
+            ip: ip.to_string(),
+            consecutive_failures: self.consecutive_failures,
+            last_failure_at: self.last_failure_at,
+            locked_until: self.locked_until.filter(|until| *until > now),
+        }
+    }
+}
+
+/// Seconds an address must wait before presenting an API key again, None when allowed
+pub fn retry_after(ip: IpAddr) -> Option<u64> {
+    let failures = FAILURES.lock().ok()?;
+    failures.get(&ip)?.retry_after(Utc::now())
+}
+
+/// Track the outcome of an API key check: an unknown key counts as a failure,
+/// a valid one clears the address' failures
+pub fn record(ip: IpAddr, valid: bool) {
+    let policy = &*LOCKOUT_POLICY;
+    if policy.max_failures == 0 {
+        return;
+    }
+    let Ok(mut failures) = FAILURES.lock() else {
+        return;
+    };
+
+    let now = Utc::now();
+    if valid {
+        failures.remove(&ip);
+        return;
+    }
+    if failures.len() >= MAX_TRACKED_IPS {
+        failures.retain(|_, record| !record.is_stale(policy, now));
+    }
+
+    let record = failures
+        .entry(ip)
+        .or_insert_with(|| FailureRecord::new(now));
+    record.fail(policy, now);
+    if let Some(retry_after) = record.retry_after(now) {
+        log::warn!(
+            "{} locked out for {} seconds after repeated invalid API keys",
+            ip,
+            retry_after
+        );
+    }
+}
+
+/// Tracked addresses, locked out ones first
+pub fn list() -> Vec<IpLockout> {
+    let now = Utc::now();
+    let Ok(failures) = FAILURES.lock() else {
+        return Vec::new();
+    };
+
+    let mut lockouts: Vec<IpLockout> = failures
+        .iter()
+        .map(|(ip, record)| record.to_lockout(ip, now))
+        .collect();
+    lockouts.sort_by(|a, b| {
+        b.locked_until
+            .is_some()
+            .cmp(&a.locked_until.is_some())
+            .then(b.last_failure_at.cmp(&a.last_failure_at))
+    });
+    lockouts
+}
+
+/// Forget the failures of an address and lift its lockout, false when it was not tracked
+pub fn clear(ip: IpAddr) -> bool {
+    FAILURES
+        .lock()
+        .map(|mut failures| failures.remove(&ip).is_some())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockout_after_consecutive_failures() {
+        let policy = LockoutPolicy {
+            max_failures: 3,
+            duration: Duration::seconds(600),
+        };
+        let now = Utc::now();
+        let mut record = FailureRecord::new(now);
+
+        record.fail(&policy, now);
+        record.fail(&policy, now);
+        assert_eq!(record.retry_after(now), None);
+
+        record.fail(&policy, now);
+        assert_eq!(record.retry_after(now), Some(600));
+        assert_eq!(
+            record.retry_after(now + Duration::milliseconds(599_500)),
+            Some(1)
+        );
+
+        // The lockout ends on its own, the count starts over
+        let later = now + Duration::seconds(600);
+        assert_eq!(record.retry_after(later), None);
+        assert_eq!(
+            record
+                .to_lockout(&"203.0.113.7".parse().unwrap(), later)
+                .consecutive_failures,
+            0
+        );
+    }
+}
@@ -2,127 +2,248 @@ use actix_web::{
     body::MessageBody,
     dev::{ServiceRequest, ServiceResponse},
     error::ErrorUnauthorized,
-    middleware, Error, HttpMessage, HttpRequest, Result,
+    middleware, web, Error, HttpMessage, HttpRequest, Result,
 };
 use actix_web_grants::authorities::AttachAuthorities;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+
+use crate::authentication::identity::Identity;
+use crate::error::AppError;
+use crate::models::{AuthAuditEntry, AuthMethod};
+use crate::services;
 
 // Authentication functions
-fn extract_api_key(req: &HttpRequest) -> Option<String> {
+pub(crate) fn extract_api_key(req: &HttpRequest) -> Option<String> {
     req.headers()
         .get("X-API-Key")
         .and_then(|h| h.to_str().ok())
         .map(|s| s.to_string())
+        .or_else(|| extract_feed_api_key(req))
+}
+
+/// Calendar apps subscribing to a feed (`*.ics`) cannot send headers, only there
+/// the key may come as the `api_key` query parameter
+fn extract_feed_api_key(req: &HttpRequest) -> Option<String> {
+    if !req.path().ends_with(".ics") {
+        return None;
+    }
+    web::Query::<HashMap<String, String>>::from_query(req.query_string())
+        .ok()?
+        .remove("api_key")
+}
+
+pub(super) fn extract_bearer_token(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "))
+        .map(|s| s.trim().to_string())
+}
+
+/// Address of the caller. Forwarded headers are only trusted behind a proxy
+/// (TRUST_PROXY_HEADERS=true), the TCP peer is used otherwise.
+pub(super) fn client_ip(req: &HttpRequest) -> Option<IpAddr> {
+    let trust_proxy = std::env::var("TRUST_PROXY_HEADERS")
+        .map(|value| value == "true")
+        .unwrap_or(false);
+    if !trust_proxy {
+        return req.peer_addr().map(|address| address.ip());
+    }
+
+    let connection_info = req.connection_info();
+    let address = connection_info.realip_remote_addr()?;
+    address
+        .parse::<IpAddr>()
+        .or_else(|_| address.parse::<SocketAddr>().map(|address| address.ip()))
+        .ok()
+}
+
+/// Resolve the caller from an API key (stored hashed), an OIDC session token,
+/// a request signed with a shared secret or a TLS client certificate.
+/// Returns the method used and the credential presented along with the outcome.
+async fn authenticate(
+    req: &mut ServiceRequest,
+    client_ip: Option<IpAddr>,
+) -> (AuthMethod, Option<String>, Result<Identity, Error>) {
+    if let Some(key) = extract_api_key(req.request()) {
+        // Addresses guessing keys are turned away before the key is even looked up
+        if let Some(retry_after) = client_ip.and_then(super::lockout::retry_after) {
+            let error = AppError::too_many_requests(
+                format!(
+                    "Too many invalid API keys from this address, retry in {} seconds",
+                    retry_after
+                ),
+                retry_after,
+            );
+            return (AuthMethod::ApiKey, Some(key), Err(error.into()));
+        }
+
+        let resolved = super::api_key::resolve_identity(&key, client_ip).await;
+        // Unknown keys count towards a lockout, other errors (expired, revoked, ...) do not
+        if let (Some(ip), Ok(identity)) = (client_ip, &resolved) {
+            super::lockout::record(ip, identity.is_some());
+        }
+        let result = match resolved {
+            Ok(Some(identity)) => Ok(identity),
+            Ok(None) => Err(ErrorUnauthorized("Invalid API key")),
+            Err(error) => Err(error.into()),
+        };
+        (AuthMethod::ApiKey, Some(key), result)
+    } else if let Some(token) = extract_bearer_token(req.request()) {
+        let result = match super::session::resolve_session(&token).await {
+            Ok(Some(identity)) => Ok(identity),
+            Ok(None) => Err(ErrorUnauthorized("Invalid or expired session token")),
+            Err(error) => Err(error.into()),
+        };
+        (AuthMethod::Session, Some(token), result)
+    } else if let Some(key_id) = super::request_signing::extract_key_id(req.request()) {
+        let result = super::request_signing::resolve_identity(req)
+            .await
+            .map_err(Error::from);
+        (AuthMethod::Signature, Some(key_id), result)
+    } else if let Some(certificate) = super::mtls::extract_client_certificate(req.request()) {
+        let result = super::mtls::resolve_identity(&certificate).map_err(Error::from);
+        let user_id = result
+            .as_ref()
+            .ok()
+            .map(|identity| identity.user_id.clone());
+        (AuthMethod::Certificate, user_id, result)
+    } else {
+        (
+            AuthMethod::None,
+            None,
+            Err(ErrorUnauthorized(
+                "Missing X-API-Key, Authorization or X-Signature header",
+            )),
+        )
+    }
 }
 
 // API Key Authentication Middleware using from_fn
 pub async fn api_key_auth_middleware(
-    req: ServiceRequest,
+    mut req: ServiceRequest,
     next: middleware::Next<impl MessageBody>,
 ) -> Result<ServiceResponse<impl MessageBody>, Error> {
-    // Extract API key from request
-    let api_key = extract_api_key(req.request());
-
-    match api_key {
-        Some(key) => {
-            // Handle customer API keys and role mapping
-            let (role, user_id) = match key.as_str() {
-                "Admin" => (super::identity::Role::Admin, "Admin".to_string()),
-                "CarManager" => (super::identity::Role::CarManager, "CarManager".to_string()),
-                "MotorbikeManager" => (
-                    super::identity::Role::MotorbikeManager,
-                    "MotorbikeManager".to_string(),
-                ),
-                // Customer API keys map to Customer role but different user_ids
-                "Customer1" => (
-                    super::identity::Role::Customer,
-                    "customer_user_1".to_string(),
-                ),
-                "Customer2" => (
-                    super::identity::Role::Customer,
-                    "customer_user_2".to_string(),
-                ),
-                _ => return Err(ErrorUnauthorized("Invalid API key")),
-            };
-
-            let identity = super::identity::Identity {
-                role: role.clone(),
-                user_id,
-            };
-
-            // Capture identity to Sentry using breadcrumbs and user context
-            sentry::configure_scope(|scope| {
-                // Set user context for the entire scope
-                scope.set_user(Some(sentry::User {
-                    id: Some(identity.user_id.clone()),
-                    username: Some(identity.user_id.clone()),
-                    email: None,
-                    ip_address: None,
-                    other: {
-                        let mut map = std::collections::BTreeMap::new();
-                        map.insert(
-                            "role".to_string(),
-                            sentry::protocol::Value::String(identity.role.to_string()),
-                        );
-                        map.insert(
-                            "api_key".to_string(),
-                            sentry::protocol::Value::String(key.clone()),
-                        );
-                        map
-                    },
-                }));
-                scope.set_tag("user_role", &identity.role.to_string());
-                scope.set_tag("user_id", &identity.user_id);
-            });
-
-            // Add breadcrumb for authentication event
-            sentry::add_breadcrumb(sentry::Breadcrumb {
-                ty: "auth".to_string(),
-                category: Some("authentication".to_string()),
-                message: Some(format!(
-                    "User authenticated: {} with role {}",
-                    identity.user_id, identity.role
-                )),
-                data: {
-                    let mut map = std::collections::BTreeMap::new();
-                    map.insert(
-                        "user_id".to_string(),
-                        sentry::protocol::Value::String(identity.user_id.clone()),
-                    );
-                    map.insert(
-                        "role".to_string(),
-                        sentry::protocol::Value::String(identity.role.to_string()),
-                    );
-                    map.insert(
-                        "api_key".to_string(),
-                        sentry::protocol::Value::String(key.clone()),
-                    );
-                    map.insert(
-                        "method".to_string(),
-                        sentry::protocol::Value::String(req.method().to_string()),
-                    );
-                    map.insert(
-                        "path".to_string(),
-                        sentry::protocol::Value::String(req.path().to_string()),
-                    );
-                    map.insert(
-                        "timestamp".to_string(),
-                        sentry::protocol::Value::String(chrono::Utc::now().to_rfc3339()),
-                    );
-                    map
-                },
-                level: sentry::Level::Info,
-                timestamp: std::time::SystemTime::now(),
-            });
-
-            // Attach authorities (roles) for actix-web-grants
-            req.attach(vec![role.clone()]);
-
-            // Attach role and identity to request extensions
-            req.extensions_mut().insert(identity);
-
-            // Continue to next middleware/handler
-            next.call(req).await
+    // Every attempt, successful or not, goes to the authentication audit log
+    let ip = client_ip(req.request());
+    let (method, credential, result) = authenticate(&mut req, ip).await;
+    services::audit::record(AuthAuditEntry::new(
+        method,
+        credential.as_deref(),
+        result.as_ref().map_err(|error| error.to_string()),
+        ip.map(|ip| ip.to_string()),
+        req.method().as_str(),
+        req.path(),
+    ));
+    let mut identity = result?;
+    let credential = credential.unwrap_or_default();
+
+    // Customers get their profile embedded, so handlers need no extra query for it
+    let (suspension, profile) = services::fanout::try_join2(
+        super::suspension::active_suspension(&identity),
+        super::profile::load(&identity),
+    )
+    .await?;
+    super::profile::enrich(&mut identity, profile);
+    let role = identity.role.clone();
+
+    // Only the credential prefix is reported, never the credential itself
+    let key: String = credential.chars().take(10).collect();
+
+    // Capture identity to Sentry using breadcrumbs and user context
+    sentry::configure_scope(|scope| {
+        // Set user context for the entire scope
+        scope.set_user(Some(sentry::User {
+            id: Some(identity.user_id.clone()),
+            username: Some(identity.user_id.clone()),
+            email: None,
+            ip_address: None,
+            other: {
+                let mut map = std::collections::BTreeMap::new();
+                map.insert(
+                    "role".to_string(),
+                    sentry::protocol::Value::String(identity.role.to_string()),
+                );
+                map.insert(
+                    "api_key_prefix".to_string(),
+                    sentry::protocol::Value::String(key.clone()),
+                );
+                map
+            },
+        }));
+        scope.set_tag("user_role", &identity.role.to_string());
+        scope.set_tag("user_id", &identity.user_id);
+        scope.set_tag("suspended", suspension.is_some());
+        if let Some(tenant_id) = &identity.tenant_id {
+            scope.set_tag("tenant_id", tenant_id);
         }
-        None => Err(ErrorUnauthorized("Missing X-API-Key header")),
+        // Requests made on behalf of a customer are flagged with the acting admin
+        scope.set_tag("impersonated", identity.impersonated_by.is_some());
+        if let Some(admin_id) = &identity.impersonated_by {
+            scope.set_tag("impersonated_by", admin_id);
+        }
+    });
+
+    // Add breadcrumb for authentication event
+    sentry::add_breadcrumb(sentry::Breadcrumb {
+        ty: "auth".to_string(),
+        category: Some("authentication".to_string()),
+        message: Some(format!(
+            "User authenticated: {} with role {}",
+            identity.user_id, identity.role
+        )),
+        data: {
+            let mut map = std::collections::BTreeMap::new();
+            map.insert(
+                "user_id".to_string(),
+                sentry::protocol::Value::String(identity.user_id.clone()),
+            );
+            map.insert(
+                "role".to_string(),
+                sentry::protocol::Value::String(identity.role.to_string()),
+            );
+            map.insert(
+                "api_key_prefix".to_string(),
+                sentry::protocol::Value::String(key.clone()),
+            );
+            if let Some(admin_id) = &identity.impersonated_by {
+                map.insert(
+                    "impersonated_by".to_string(),
+                    sentry::protocol::Value::String(admin_id.clone()),
+                );
+            }
+            map.insert(
+                "method".to_string(),
+                sentry::protocol::Value::String(req.method().to_string()),
+            );
+            map.insert(
+                "path".to_string(),
+                sentry::protocol::Value::String(req.path().to_string()),
+            );
+            map.insert(
+                "timestamp".to_string(),
+                sentry::protocol::Value::String(chrono::Utc::now().to_rfc3339()),
+            );
+            map
+        },
+        level: sentry::Level::Info,
+        timestamp: std::time::SystemTime::now(),
+    });
+
+    // Attach authorities (role and its permissions) for actix-web-grants,
+    // a suspended customer loses the right to create bookings
+    let mut permissions = super::permission::granted(&identity);
+    if suspension.is_some() {
+        permissions = super::suspension::restrict(permissions);
     }
+    req.attach(vec![role.clone()]);
+    req.attach(permissions.into_iter().collect::<Vec<_>>());
+
+    // Attach role and identity to request extensions
+    let tenant_id = identity.tenant_id.clone();
+    req.extensions_mut().insert(identity);
+
+    // Continue to next middleware/handler, MongoDB access limited to the caller's tenant
+    services::mongodb::tenant::scope(tenant_id, next.call(req)).await
 }
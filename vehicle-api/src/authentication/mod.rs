@@ -1,2 +1,31 @@
+use std::sync::LazyLock;
+
+pub mod api_key;
+pub mod bot;
+pub mod captcha;
 pub mod identity;
+pub mod impersonation;
+pub mod lockout;
 pub mod middleware;
+pub mod mtls;
+pub mod oidc;
+pub mod permission;
+pub mod profile;
+pub mod rate_limit;
+pub mod request_signing;
+pub mod revocation;
+pub mod service_account;
+pub mod session;
+pub mod suspension;
+
+/// Read the authentication settings (rate limits, lockouts, signing clients,
+/// certificate mapping, captcha routes, bot detection) now rather than on the first request
+pub fn preload() {
+    LazyLock::force(&rate_limit::RATE_LIMITS);
+    LazyLock::force(&lockout::LOCKOUT_POLICY);
+    LazyLock::force(&request_signing::SIGNING_CLIENTS);
+    LazyLock::force(&mtls::CERTIFICATE_IDENTITIES);
+    LazyLock::force(&captcha::CAPTCHA_ROUTES);
+    LazyLock::force(&bot::BOT_POLICY);
+    LazyLock::force(&captcha::CAPTCHA_BACKEND);
+}
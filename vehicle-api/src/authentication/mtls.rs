@@ -0,0 +1,237 @@
+use actix_tls::accept::rustls_0_23::TlsStream;
+use actix_web::{dev::Extensions, rt::net::TcpStream, HttpRequest};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use std::any::Any;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock};
+use x509_parser::prelude::{FromDer, GeneralName, X509Certificate};
+
+use super::identity::{Identity, Role};
+use crate::error::{AppError, AppResult};
+
+/// Prefix of the user_id of certificate identities, followed by the matched name
+pub const CERTIFICATE_USER_PREFIX: &str = "cert:";
+
+/// Client certificate (DER) presented on the TLS connection, set by `on_connect`
+#[derive(Clone, Debug)]
+pub struct ClientCertificate(pub CertificateDer<'static>);
+
+/// TLS settings of the server, read from the environment
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+    pub client_ca_path: Option<String>,
+    pub client_cert_required: bool,
+}
+
+/// Certificate names (CN or SAN) allowed to call the API, with their role
+pub struct CertificateIdentities {
+    roles: HashMap<String, Role>,
+}
+
+// Mapping read once from MTLS_IDENTITY_MAPPING
+pub(super) static CERTIFICATE_IDENTITIES: LazyLock<CertificateIdentities> =
+    LazyLock::new(CertificateIdentities::from_env);
+
+/// Whether callers may authenticate with a client certificate
+pub fn is_enabled() -> bool {
+    TlsConfig::from_env().is_some_and(|tls| tls.client_ca_path.is_some())
+}
+
+impl TlsConfig {
+    /// TLS_CERT_PATH and TLS_KEY_PATH enable TLS, TLS_CLIENT_CA_PATH enables client
+    /// certificates (optional unless TLS_CLIENT_CERT_REQUIRED=true). None serves plain HTTP.
+    pub fn from_env() -> Option<Self> {
+        let cert_path = std::env::var("TLS_CERT_PATH").ok()?;
+        let key_path = std::env::var("TLS_KEY_PATH").ok()?;
+
+        Some(Self {
+            cert_path,
+            key_path,
+            client_ca_path: std::env::var("TLS_CLIENT_CA_PATH").ok(),
+            client_cert_required: std::env::var("TLS_CLIENT_CERT_REQUIRED")
+                .map(|value| value == "true")
+                .unwrap_or(false),
+        })
+    }
+
+    /// rustls configuration verifying client certificates against the client CA
+    pub fn server_config(&self) -> Result<ServerConfig, String> {
+        let certs = CertificateDer::pem_file_iter(&self.cert_path)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Cannot read {}: {}", self.cert_path, e))?;
+        let key = PrivateKeyDer::from_pem_file(&self.key_path)
+            .map_err(|e| format!("Cannot read {}: {}", self.key_path, e))?;
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| e.to_string())?;
+
+        let builder = match &self.client_ca_path {
+            Some(client_ca_path) => {
+                let mut roots = RootCertStore::empty();
+                for ca in CertificateDer::pem_file_iter(client_ca_path)
+                    .map_err(|e| format!("Cannot read {}: {}", client_ca_path, e))?
+                {
+                    let ca = ca.map_err(|e| format!("Cannot read {}: {}", client_ca_path, e))?;
+                    roots.add(ca).map_err(|e| e.to_string())?;
+                }
+
+                let verifier =
+                    WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+                // Without a certificate, callers fall back to API keys and tokens
+                let verifier = if self.client_cert_required {
+                    verifier.build()
+                } else {
+                    verifier.allow_unauthenticated().build()
+                }
+                .map_err(|e| e.to_string())?;
+
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        builder
+            .with_single_cert(certs, key)
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl CertificateIdentities {
+    /// MTLS_IDENTITY_MAPPING="billing.internal:Admin,fleet-sync.internal:CarManager",
+    /// certificates matching no name are rejected
+    pub fn from_env() -> Self {
+        let value = std::env::var("MTLS_IDENTITY_MAPPING").unwrap_or_default();
+
+        Self::parse(&value).unwrap_or_else(|error| {
+            log::error!(
+                "Invalid MTLS_IDENTITY_MAPPING, certificates ignored: {}",
+                error
+            );
+            Self {
+                roles: HashMap::new(),
+            }
+        })
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let roles = value
+            .split(',')
+            .map(|entry| entry.trim())
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (name, role) = entry
+                    .rsplit_once(':')
+                    .ok_or_else(|| format!("Expected name:role, got {}", entry))?;
+                let role = Role::from_str(role.trim())
+                    .map_err(|_| format!("Unknown role {}", role.trim()))?;
+                Ok((name.trim().to_lowercase(), role))
+            })
+            .collect::<Result<HashMap<_, _>, String>>()?;
+
+        Ok(Self { roles })
+    }
+
+    /// Map the first known name of a certificate, SAN entries before the CN
+    pub fn resolve(&self, names: &[String]) -> Option<Identity> {
+        names.iter().find_map(|name| {
+            let name = name.to_lowercase();
+            self.roles.get(&name).map(|role| Identity {
+                role: role.clone(),
+                user_id: format!("{}{}", CERTIFICATE_USER_PREFIX, name),
+                email: None,
+                tenant_id: None, // Certificates identify services of the deployment itself
+                permissions: Vec::new(),
+                profile: None,
+                impersonated_by: None,
+            })
+        })
+    }
+}
+
+/// DNS and URI subject alternative names, then the common names of a certificate
+pub fn certificate_names(der: &[u8]) -> Result<Vec<String>, String> {
+    let (_, certificate) =
+        X509Certificate::from_der(der).map_err(|e| format!("Invalid certificate: {}", e))?;
+
+    let mut names = Vec::new();
+    if let Ok(Some(san)) = certificate.subject_alternative_name() {
+        for name in &san.value.general_names {
+            match name {
+                GeneralName::DNSName(name) | GeneralName::URI(name) => names.push(name.to_string()),
+                _ => {}
+            }
+        }
+    }
+    names.extend(
+        certificate
+            .subject()
+            .iter_common_name()
+            .filter_map(|cn| cn.as_str().ok())
+            .map(str::to_string),
+    );
+
+    Ok(names)
+}
+
+/// `HttpServer::on_connect` hook keeping the verified client certificate for the requests
+pub fn on_connect(connection: &dyn Any, data: &mut Extensions) {
+    if let Some(stream) = connection.downcast_ref::<TlsStream<TcpStream>>() {
+        let (_, session) = stream.get_ref();
+        if let Some(certificate) = session.peer_certificates().and_then(|certs| certs.first()) {
+            data.insert(ClientCertificate(certificate.clone().into_owned()));
+        }
+    }
+}
+
+pub(super) fn extract_client_certificate(req: &HttpRequest) -> Option<ClientCertificate> {
+    req.conn_data::<ClientCertificate>().cloned()
+}
+
+/// Resolve a client certificate, already verified by TLS, to an identity
+pub fn resolve_identity(certificate: &ClientCertificate) -> AppResult<Identity> {
+    let names = certificate_names(&certificate.0).map_err(AppError::unauthorized)?;
+
+    CERTIFICATE_IDENTITIES.resolve(&names).ok_or_else(|| {
+        AppError::unauthorized(format!(
+            "Client certificate ({}) is not mapped to a role",
+            names.join(", ")
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_certificate_identity_mapping() {
+        let identities =
+            CertificateIdentities::parse("billing.internal:Admin, spiffe://fleet/sync:CarManager")
+                .unwrap();
+
+        let identity = identities
+            .resolve(&["unknown".to_string(), "Billing.Internal".to_string()])
+            .unwrap();
+        assert_eq!(identity.role, Role::Admin);
+        assert_eq!(identity.user_id, "cert:billing.internal");
+
+        let identity = identities
+            .resolve(&["spiffe://fleet/sync".to_string()])
+            .unwrap();
+        assert_eq!(identity.role, Role::CarManager);
+
+        assert!(identities
+            .resolve(&["other.internal".to_string()])
+            .is_none());
+        assert!(CertificateIdentities::parse("billing.internal=Admin").is_err());
+        assert!(CertificateIdentities::parse("billing.internal:Driver").is_err());
+    }
+}
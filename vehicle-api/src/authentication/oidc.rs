@@ -0,0 +1,333 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::env;
+
+use super::identity::{Identity, Role};
+use crate::error::{AppError, AppResult};
+
+/// OIDC client configuration, login is disabled when OIDC_ISSUER is not set
+#[derive(Clone, Debug)]
+pub struct OidcConfig {
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+    pub scopes: String,
+    pub role_claim: String,
+    /// Claim value -> role, e.g. OIDC_ROLE_MAPPING="fleet-admins:Admin,cars:CarManager"
+    pub role_mapping: Vec<(String, Role)>,
+    /// Claim holding the rental company of the user, none for a single company
+    pub tenant_claim: Option<String>,
+}
+
+/// Endpoints published by the provider at /.well-known/openid-configuration
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+// Provider metadata, discovered once
+static PROVIDER_METADATA: tokio::sync::OnceCell<ProviderMetadata> =
+    tokio::sync::OnceCell::const_new();
+
+/// Whether OIDC login is offered (OIDC_ISSUER is set)
+pub fn is_configured() -> bool {
+    env::var("OIDC_ISSUER").is_ok()
+}
+
+impl OidcConfig {
+    pub fn from_env() -> AppResult<Self> {
+        let issuer = env::var("OIDC_ISSUER")
+            .map_err(|_| AppError::not_found("OIDC login is not configured"))?;
+        let required = |name: &str| {
+            env::var(name)
+                .map_err(|_| AppError::internal_server_error(format!("{} must be set", name)))
+        };
+
+        Ok(Self {
+            issuer: issuer.trim_end_matches('/').to_string(),
+            client_id: required("OIDC_CLIENT_ID")?,
+            client_secret: required("OIDC_CLIENT_SECRET")?,
+            redirect_uri: required("OIDC_REDIRECT_URI")?,
+            scopes: env::var("OIDC_SCOPES").unwrap_or_else(|_| "openid email profile".to_string()),
+            role_claim: env::var("OIDC_ROLE_CLAIM").unwrap_or_else(|_| "roles".to_string()),
+            role_mapping: parse_role_mapping(&env::var("OIDC_ROLE_MAPPING").unwrap_or_default())?,
+            tenant_claim: env::var("OIDC_TENANT_CLAIM").ok(),
+        })
+    }
+
+    /// Map the role claim to the most privileged mapped role, Customer by default
+    pub fn identity_from_claims(&self, claims: &Value) -> AppResult<Identity> {
+        let subject = claims["sub"]
+            .as_str()
+            .ok_or_else(|| AppError::unauthorized("ID token has no subject"))?;
+
+        let values: Vec<&str> = match &claims[self.role_claim.as_str()] {
+            Value::String(value) => vec![value.as_str()],
+            Value::Array(values) => values.iter().filter_map(|v| v.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        let role = [Role::Admin, Role::CarManager, Role::MotorbikeManager]
+            .into_iter()
+            .find(|role| {
+                self.role_mapping
+                    .iter()
+                    .any(|(value, mapped)| mapped == role && values.contains(&value.as_str()))
+            })
+            .unwrap_or(Role::Customer);
+
+        Ok(Identity {
+            role,
+            user_id: format!("oidc:{}", subject),
+            email: claims["email"].as_str().map(|email| email.to_string()),
+            tenant_id: self
+                .tenant_claim
+                .as_ref()
+                .and_then(|claim| claims[claim.as_str()].as_str())
+                .map(|tenant_id| tenant_id.to_string()),
+            permissions: Vec::new(),
+            profile: None,
+            impersonated_by: None,
+        })
+    }
+
+    /// Check an ID token received from the token endpoint.
+    /// The token comes straight from the provider over TLS, so the signature check is
+    /// replaced by TLS server validation (OpenID Connect Core 3.1.3.7).
+    pub fn validate_claims(
+        &self,
+        metadata: &ProviderMetadata,
+        claims: &Value,
+        nonce: &str,
+        now: i64,
+    ) -> Result<(), String> {
+        if claims["iss"].as_str() != Some(metadata.issuer.as_str()) {
+            return Err("ID token issuer mismatch.".to_string());
+        }
+        let audience_ok = match &claims["aud"] {
+            Value::String(aud) => aud == &self.client_id,
+            Value::Array(auds) => auds.iter().any(|aud| aud.as_str() == Some(&self.client_id)),
+            _ => false,
+        };
+        if !audience_ok {
+            return Err("ID token audience mismatch.".to_string());
+        }
+        if claims["exp"].as_i64().unwrap_or(0) <= now {
+            return Err("ID token expired.".to_string());
+        }
+        if claims["nonce"].as_str() != Some(nonce) {
+            return Err("ID token nonce mismatch.".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn parse_role_mapping(raw: &str) -> AppResult<Vec<(String, Role)>> {
+    raw.split(',')
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (value, role) = entry.rsplit_once(':').ok_or_else(|| {
+                AppError::internal_server_error("OIDC_ROLE_MAPPING entries must be 'value:Role'")
+            })?;
+            let role = role.parse::<Role>().map_err(|_| {
+                AppError::internal_server_error(format!(
+                    "Unknown role '{}' in OIDC_ROLE_MAPPING",
+                    role
+                ))
+            })?;
+            Ok((value.to_string(), role))
+        })
+        .collect()
+}
+
+/// Discover the provider endpoints
+pub async fn get_provider_metadata(config: &OidcConfig) -> AppResult<&'static ProviderMetadata> {
+    PROVIDER_METADATA
+        .get_or_try_init(|| async {
+            let url = format!("{}/.well-known/openid-configuration", config.issuer);
+            reqwest::get(&url)
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| {
+                    AppError::internal_server_error(format!("OIDC discovery failed: {}", e))
+                })?
+                .json::<ProviderMetadata>()
+                .await
+                .map_err(|e| {
+                    AppError::internal_server_error(format!(
+                        "Invalid OIDC discovery document: {}",
+                        e
+                    ))
+                })
+        })
+        .await
+}
+
+/// PKCE S256 challenge of a code verifier
+pub fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// URL of the provider login page
+pub fn authorization_url(
+    config: &OidcConfig,
+    metadata: &ProviderMetadata,
+    state: &str,
+    nonce: &str,
+    code_verifier: &str,
+) -> AppResult<String> {
+    let challenge = pkce_challenge(code_verifier);
+    reqwest::Url::parse_with_params(
+        &metadata.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", config.client_id.as_str()),
+            ("redirect_uri", config.redirect_uri.as_str()),
+            ("scope", config.scopes.as_str()),
+            ("state", state),
+            ("nonce", nonce),
+            ("code_challenge", challenge.as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map(|url| url.to_string())
+    .map_err(|e| AppError::internal_server_error(format!("Invalid authorization endpoint: {}", e)))
+}
+
+/// Exchange an authorization code for the ID token claims
+pub async fn exchange_code(
+    config: &OidcConfig,
+    metadata: &ProviderMetadata,
+    code: &str,
+    code_verifier: &str,
+) -> AppResult<Value> {
+    let response = reqwest::Client::new()
+        .post(&metadata.token_endpoint)
+        .basic_auth(&config.client_id, Some(&config.client_secret))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", config.redirect_uri.as_str()),
+            ("code_verifier", code_verifier),
+        ])
+        .send()
+        .await
+        .map_err(|e| {
+            AppError::internal_server_error(format!("OIDC token request failed: {}", e))
+        })?;
+
+    if !response.status().is_success() {
+        return Err(AppError::unauthorized(
+            "Authorization code was rejected by the provider",
+        ));
+    }
+
+    let tokens: TokenResponse = response.json().await.map_err(|e| {
+        AppError::internal_server_error(format!("Invalid OIDC token response: {}", e))
+    })?;
+
+    decode_claims(&tokens.id_token).map_err(AppError::unauthorized)
+}
+
+/// Payload of a JWT (signature not checked, see `validate_claims`)
+pub fn decode_claims(jwt: &str) -> Result<Value, String> {
+    let payload = jwt
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| "Malformed ID token.".to_string())?;
+    let bytes = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|_| "Malformed ID token.".to_string())?;
+
+    serde_json::from_slice(&bytes).map_err(|_| "Malformed ID token.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config() -> OidcConfig {
+        OidcConfig {
+            issuer: "https://idp.example.com".to_string(),
+            client_id: "vehicle-api".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_uri: "http://localhost:8080/auth/callback".to_string(),
+            scopes: "openid".to_string(),
+            role_claim: "groups".to_string(),
+            role_mapping: parse_role_mapping("fleet-admins:Admin,cars:CarManager").unwrap(),
+            tenant_claim: Some("org".to_string()),
+        }
+    }
+
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata {
+            issuer: "https://idp.example.com".to_string(),
+            authorization_endpoint: "https://idp.example.com/authorize".to_string(),
+            token_endpoint: "https://idp.example.com/token".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_claims_mapping_to_role() {
+        let config = config();
+
+        let admin = config
+            .identity_from_claims(&json!({ "sub": "42", "groups": ["cars", "fleet-admins"] }))
+            .unwrap();
+        assert_eq!(admin.role, Role::Admin);
+        assert_eq!(admin.user_id, "oidc:42");
+
+        let customer = config
+            .identity_from_claims(&json!({ "sub": "43", "org": "acme-rentals" }))
+            .unwrap();
+        assert_eq!(customer.role, Role::Customer);
+        assert_eq!(customer.tenant_id.as_deref(), Some("acme-rentals"));
+        assert!(admin.tenant_id.is_none());
+    }
+
+    #[test]
+    fn test_validate_claims() {
+        let config = config();
+        let claims = json!({
+            "iss": "https://idp.example.com",
+            "aud": ["vehicle-api"],
+            "exp": 2_000,
+            "nonce": "n-1"
+        });
+
+        assert!(config
+            .validate_claims(&metadata(), &claims, "n-1", 1_000)
+            .is_ok());
+        assert!(config
+            .validate_claims(&metadata(), &claims, "n-2", 1_000)
+            .is_err());
+        assert!(config
+            .validate_claims(&metadata(), &claims, "n-1", 3_000)
+            .is_err());
+    }
+
+    #[test]
+    fn test_decode_claims_and_pkce() {
+        let payload = URL_SAFE_NO_PAD.encode(br#"{"sub":"42"}"#);
+        let claims = decode_claims(&format!("header.{}.signature", payload)).unwrap();
+        assert_eq!(claims["sub"], "42");
+
+        // S256 challenge: base64url(sha256(verifier)) without padding
+        let challenge = pkce_challenge("verifier");
+        assert_eq!(challenge.len(), 43);
+        assert_eq!(challenge, pkce_challenge("verifier"));
+        assert_ne!(challenge, pkce_challenge("other verifier"));
+    }
+}
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use strum::{Display, EnumString};
+
+use super::identity::{Identity, Role};
+
+/// What an identity may do, granted through its role
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, EnumString, Display)]
+pub enum Permission {
+    #[serde(rename = "vehicle:create")]
+    #[strum(serialize = "vehicle:create")]
+    VehicleCreate,
+    #[serde(rename = "vehicle:update")]
+    #[strum(serialize = "vehicle:update")]
+    VehicleUpdate,
+    #[serde(rename = "vehicle:delete")]
+    #[strum(serialize = "vehicle:delete")]
+    VehicleDelete,
+    #[serde(rename = "vehicle:read_bookings")]
+    #[strum(serialize = "vehicle:read_bookings")]
+    VehicleReadBookings,
+    #[serde(rename = "booking:create")]
+    #[strum(serialize = "booking:create")]
+    BookingCreate,
+    #[serde(rename = "booking:approve")]
+    #[strum(serialize = "booking:approve")]
+    BookingApprove,
+    #[serde(rename = "pii:read")]
+    #[strum(serialize = "pii:read")]
+    PiiRead,
+}
+
+/// Permissions granted to each role, roles missing from the map have none
+#[derive(Clone, Debug, Deserialize)]
+pub struct RolePermissions(HashMap<Role, HashSet<Permission>>);
+
+// Loaded once at startup
+static ROLE_PERMISSIONS: OnceLock<RolePermissions> = OnceLock::new();
+
+impl Default for RolePermissions {
+    /// Built-in mapping, same access as the original role checks
+    fn default() -> Self {
+        use Permission::*;
+
+        let staff = HashSet::from([
+            VehicleUpdate,
+            VehicleDelete,
+            VehicleReadBookings,
+            BookingApprove,
+        ]);
+        let mut admin = staff.clone();
+        admin.extend([VehicleCreate, PiiRead]);
+
+        Self(HashMap::from([
+            (Role::Admin, admin),
+            (Role::CarManager, staff.clone()),
+            (Role::MotorbikeManager, staff),
+            (Role::Customer, HashSet::from([BookingCreate])),
+        ]))
+    }
+}
+
+impl RolePermissions {
+    /// JSON file from PERMISSIONS_PATH when set, built-in mapping otherwise
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("PERMISSIONS_PATH") {
+            Ok(path) => {
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Cannot read {}: {}", path, e))?;
+                serde_json::from_str(&content)
+                    .map_err(|e| format!("Invalid permissions in {}: {}", path, e))
+            }
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn for_role(&self, role: &Role) -> HashSet<Permission> {
+        self.0.get(role).cloned().unwrap_or_default()
+    }
+}
+
+/// Load the role to permission mapping, called once from main
+pub fn load() -> Result<(), String> {
+    let permissions = RolePermissions::from_env()?;
+    ROLE_PERMISSIONS
+        .set(permissions)
+        .map_err(|_| "Permissions are already loaded".to_string())
+}
+
+/// Permissions of a role, from the mapping loaded at startup
+pub fn permissions_for(role: &Role) -> HashSet<Permission> {
+    ROLE_PERMISSIONS
+        .get_or_init(RolePermissions::default)
+        .for_role(role)
+}
+
+/// Permissions of an identity: those granted to the account for a service account,
+/// those of its role otherwise
+pub fn granted(identity: &Identity) -> HashSet<Permission> {
+    match identity.role {
+        Role::Service => identity
+            .permissions
+            .iter()
+            .filter_map(|permission| permission.parse().ok())
+            .collect(),
+        _ => permissions_for(&identity.role),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_default_mapping_matches_role_checks() {
+        let permissions = RolePermissions::default();
+
+        assert!(permissions
+            .for_role(&Role::Admin)
+            .contains(&Permission::VehicleCreate));
+        assert!(!permissions
+            .for_role(&Role::CarManager)
+            .contains(&Permission::VehicleCreate));
+        assert!(permissions
+            .for_role(&Role::MotorbikeManager)
+            .contains(&Permission::BookingApprove));
+        assert_eq!(
+            permissions.for_role(&Role::Customer),
+            HashSet::from([Permission::BookingCreate])
+        );
+    }
+
+    #[test]
+    fn test_parse_mapping() {
+        let permissions: RolePermissions = serde_json::from_str(
+            r#"{ "Admin": ["vehicle:create", "booking:approve"], "Customer": [] }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            permissions.for_role(&Role::Admin),
+            HashSet::from([Permission::VehicleCreate, Permission::BookingApprove])
+        );
+        assert!(permissions.for_role(&Role::CarManager).is_empty());
+        assert_eq!(
+            Permission::from_str("vehicle:update").unwrap(),
+            Permission::VehicleUpdate
+        );
+        assert!(
+            serde_json::from_str::<RolePermissions>(r#"{ "Admin": ["vehicle:fly"] }"#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_service_accounts_only_get_their_grants() {
+        let mut identity = Identity {
+            role: Role::Service,
+            user_id: "service:billing".to_string(),
+            email: None,
+            tenant_id: None,
+            permissions: vec!["pii:read".to_string(), "vehicle:fly".to_string()],
+            profile: None,
+            impersonated_by: None,
+        };
+        assert_eq!(granted(&identity), HashSet::from([Permission::PiiRead]));
+
+        // Grants are ignored for human roles
+        identity.role = Role::Customer;
+        assert_eq!(
+            granted(&identity),
+            HashSet::from([Permission::BookingCreate])
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use bson::doc;
+
+use super::identity::{CustomerProfile, Identity, Role};
+use crate::error::AppResult;
+use crate::models::Customer;
+use crate::services;
+
+/// Only verified customers may create bookings (REQUIRE_VERIFIED_CUSTOMERS=true)
+pub fn verification_required() -> bool {
+    std::env::var("REQUIRE_VERIFIED_CUSTOMERS")
+        .map(|value| value == "true")
+        .unwrap_or(false)
+}
+
+/// Profile of a customer, None for the other roles and customers without a profile
+pub async fn load(identity: &Identity) -> AppResult<Option<CustomerProfile>> {
+    if identity.role != Role::Customer {
+        return Ok(None);
+    }
+
+    let customer: Option<Customer> =
+        services::mongodb::get_one(doc! { "user_id": &identity.user_id }, None).await?;
+    Ok(customer.map(CustomerProfile::from))
+}
+
+/// Embed a profile in the identity, its email fills in for credentials without one
+pub fn enrich(identity: &mut Identity, profile: Option<CustomerProfile>) {
+    if identity.email.is_none() {
+        identity.email = profile.as_ref().and_then(|profile| profile.email.clone());
+    }
+    identity.profile = profile;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enrich_keeps_the_session_email() {
+        let mut identity = Identity {
+            role: Role::Customer,
+            user_id: "customer_user_1".to_string(),
+            email: None,
+            tenant_id: None,
+            permissions: Vec::new(),
+            profile: None,
+            impersonated_by: None,
+        };
+        assert!(!identity.is_verified_customer());
+
+        let profile = CustomerProfile {
+            name: Some("Ada".to_string()),
+            email: Some("ada@example.com".to_string()),
+            verified: true,
+        };
+        enrich(&mut identity, Some(profile.clone()));
+        assert!(identity.is_verified_customer());
+        assert_eq!(identity.email.as_deref(), Some("ada@example.com"));
+
+        identity.email = Some("ada@oidc.example.com".to_string());
+        enrich(&mut identity, Some(profile));
+        assert_eq!(identity.email.as_deref(), Some("ada@oidc.example.com"));
+    }
+}
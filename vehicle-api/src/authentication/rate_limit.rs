@@ -0,0 +1,331 @@
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue, WARNING},
+    middleware, Error, HttpMessage, ResponseError, Result,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use super::identity::{Identity, Role};
+use crate::error::AppError;
+use crate::util::envelope;
+
+/// Buckets are pruned once the map holds this many keys
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// Requests per minute per role, roles missing from the map are unlimited
+pub struct RateLimits {
+    per_minute: HashMap<Role, u32>,
+}
+
+/// Token bucket of a single credential, `capacity` tokens refilled over a minute
+#[derive(Clone, Debug)]
+pub struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Outcome of a request against its bucket
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset_after: u64, // Seconds until the bucket is full again
+    pub retry_after: u64, // Seconds until the next token, 0 when allowed
+}
+
+/// `meta.quota` block of an enveloped response
+#[derive(Clone, Debug, Serialize)]
+pub struct QuotaMeta {
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset_after: u64,
+    pub reset_at: DateTime<Utc>,
+    /// Set once the remaining requests fall under the warning ratio
+    pub warning: Option<String>,
+}
+
+// Per role limits, read once from RATE_LIMITS
+pub(super) static RATE_LIMITS: LazyLock<RateLimits> = LazyLock::new(RateLimits::from_env);
+
+// Share of the limit left under which responses carry a warning, read once from
+// RATE_LIMIT_WARNING_RATIO (0.2 by default, 0 disables warnings)
+static WARNING_RATIO: LazyLock<f64> = LazyLock::new(|| {
+    std::env::var("RATE_LIMIT_WARNING_RATIO")
+        .ok()
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|ratio| (0.0..=1.0).contains(ratio))
+        .unwrap_or(0.2)
+});
+
+// Buckets keyed by the hash of the API key, session token, signing key ID or certificate name
+static BUCKETS: LazyLock<Mutex<HashMap<String, TokenBucket>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+impl RateLimits {
+    /// RATE_LIMITS="Customer:60,CarManager:600,MotorbikeManager:600" (the default),
+    /// Admin is unlimited unless listed
+    pub fn from_env() -> Self {
+        let value = std::env::var("RATE_LIMITS")
+            .unwrap_or_else(|_| "Customer:60,CarManager:600,MotorbikeManager:600".to_string());
+
+        Self::parse(&value).unwrap_or_else(|error| {
+            log::error!("Invalid RATE_LIMITS, rate limiting disabled: {}", error);
+            Self {
+                per_minute: HashMap::new(),
+            }
+        })
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let per_minute = value
+            .split(',')
+            .map(|entry| entry.trim())
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (role, limit) = entry
+                    .split_once(':')
+                    .ok_or_else(|| format!("Expected role:limit, got {}", entry))?;
+                let role = Role::from_str(role.trim())
+                    .map_err(|_| format!("Unknown role {}", role.trim()))?;
+                let limit = limit
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| format!("Invalid limit for {}: {}", role, limit.trim()))?;
+                Ok((role, limit))
+            })
+            .collect::<Result<HashMap<_, _>, String>>()?;
+
+        Ok(Self { per_minute })
+    }
+
+    /// Requests per minute allowed for a role, None when unlimited
+    pub fn limit_for(&self, role: &Role) -> Option<u32> {
+        self.per_minute
+            .get(role)
+            .copied()
+            .filter(|limit| *limit > 0)
+    }
+}
+
+impl RateLimitDecision {
+    /// Warning for an allowed request leaving at most `ratio` of the limit, so clients
+    /// can slow down before they get a 429
+    pub fn low_quota_warning(&self, ratio: f64) -> Option<String> {
+        let threshold = (self.limit as f64 * ratio).floor() as u32;
+        (self.allowed && ratio > 0.0 && self.remaining <= threshold).then(|| {
+            format!(
+                "{} of {} requests left this minute, the limit resets in {} seconds",
+                self.remaining, self.limit, self.reset_after
+            )
+        })
+    }
+
+    pub fn quota_meta(&self, ratio: f64, now: DateTime<Utc>) -> QuotaMeta {
+        QuotaMeta {
+            limit: self.limit,
+            remaining: self.remaining,
+            reset_after: self.reset_after,
+            reset_at: now + chrono::Duration::seconds(self.reset_after as i64),
+            warning: self.low_quota_warning(ratio),
+        }
+    }
+}
+
+impl TokenBucket {
+    pub fn full(capacity: u32, now: Instant) -> Self {
+        Self {
+            tokens: capacity as f64,
+            updated_at: now,
+        }
+    }
+
+    /// Refill the bucket for the elapsed time then try to take a token
+    pub fn take(&mut self, capacity: u32, now: Instant) -> RateLimitDecision {
+        let refill_per_sec = capacity as f64 / 60.0;
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * refill_per_sec).min(capacity as f64);
+        self.updated_at = now;
+
+        let allowed = self.tokens >= 1.0;
+        if allowed {
+            self.tokens -= 1.0;
+        }
+
+        let seconds_for = |tokens: f64| (tokens / refill_per_sec).ceil().max(0.0) as u64;
+        RateLimitDecision {
+            allowed,
+            limit: capacity,
+            remaining: self.tokens.floor() as u32,
+            reset_after: seconds_for(capacity as f64 - self.tokens),
+            retry_after: if allowed {
+                0
+            } else {
+                seconds_for(1.0 - self.tokens).max(1)
+            },
+        }
+    }
+
+    fn is_full(&self, capacity: u32, now: Instant) -> bool {
+        now.saturating_duration_since(self.updated_at) >= Duration::from_secs(60)
+            || self.tokens >= capacity as f64
+    }
+}
+
+fn check(key: String, role: &Role) -> Option<RateLimitDecision> {
+    let limit = RATE_LIMITS.limit_for(role)?;
+    let now = Instant::now();
+
+    let mut buckets = BUCKETS.lock().ok()?;
+    if buckets.len() >= MAX_TRACKED_KEYS {
+        // A full bucket behaves like a missing one, forget them
+        buckets.retain(|_, bucket| !bucket.is_full(limit, now));
+    }
+
+    Some(
+        buckets
+            .entry(key)
+            .or_insert_with(|| TokenBucket::full(limit, now))
+            .take(limit, now),
+    )
+}
+
+fn set_headers(headers: &mut actix_web::http::header::HeaderMap, decision: &RateLimitDecision) {
+    for (name, value) in [
+        ("x-ratelimit-limit", decision.limit as u64),
+        ("x-ratelimit-remaining", decision.remaining as u64),
+        ("x-ratelimit-reset", decision.reset_after),
+    ] {
+        headers.insert(HeaderName::from_static(name), HeaderValue::from(value));
+    }
+}
+
+// Rate Limiting Middleware using from_fn, registered after api_key_auth_middleware.
+// Successful responses running low on quota carry a `Warning` header, the quota goes to
+// `meta.quota` of enveloped responses.
+pub async fn rate_limit_middleware(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let identity = req.extensions().get::<Identity>().cloned();
+    let credential = super::middleware::extract_api_key(req.request())
+        .or_else(|| super::middleware::extract_bearer_token(req.request()))
+        .or_else(|| super::request_signing::extract_key_id(req.request()));
+
+    // Certificate callers are keyed by their mapped name
+    let credential = credential.or_else(|| {
+        super::mtls::extract_client_certificate(req.request())
+            .and(identity.as_ref().map(|identity| identity.user_id.clone()))
+    });
+
+    let decision = match (identity, credential) {
+        (Some(identity), Some(credential)) => {
+            check(super::api_key::hash_key(&credential), &identity.role)
+        }
+        _ => None,
+    };
+
+    let Some(decision) = decision else {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    };
+
+    if !decision.allowed {
+        let error = AppError::too_many_requests(
+            format!(
+                "Rate limit of {} requests per minute exceeded, retry in {} seconds",
+                decision.limit, decision.retry_after
+            ),
+            decision.retry_after,
+        );
+        let mut response = error.error_response();
+        set_headers(response.headers_mut(), &decision);
+        return Ok(req.into_response(response).map_into_boxed_body());
+    }
+
+    let mut response = next.call(req).await?;
+    set_headers(response.headers_mut(), &decision);
+    if !response.status().is_success() {
+        return Ok(response.map_into_boxed_body());
+    }
+
+    let quota = decision.quota_meta(*WARNING_RATIO, Utc::now());
+    if let Some(warning) = &quota.warning {
+        if let Ok(value) = HeaderValue::from_str(&format!("299 - \"{}\"", warning)) {
+            response.headers_mut().append(WARNING, value);
+        }
+        envelope::add_warning(response.request(), warning.clone());
+    }
+    envelope::add_meta(response.request(), "quota", &quota);
+    Ok(response.map_into_boxed_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rate_limits() {
+        let limits = RateLimits::parse("Customer:60, CarManager:0").unwrap();
+
+        assert_eq!(limits.limit_for(&Role::Customer), Some(60));
+        assert_eq!(limits.limit_for(&Role::CarManager), None);
+        assert_eq!(limits.limit_for(&Role::Admin), None);
+        assert!(RateLimits::parse("Customer=60").is_err());
+        assert!(RateLimits::parse("Driver:60").is_err());
+    }
+
+    #[test]
+    fn test_token_bucket_refills_over_a_minute() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::full(2, start);
+
+        assert!(bucket.take(2, start).allowed);
+        let decision = bucket.take(2, start);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+
+        let decision = bucket.take(2, start);
+        assert!(!decision.allowed);
+        assert_eq!(decision.retry_after, 30);
+        assert_eq!(decision.reset_after, 60);
+
+        // Half a minute later one of the two tokens is back
+        let decision = bucket.take(2, start + Duration::from_secs(31));
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+    }
+
+    #[test]
+    fn test_low_quota_warning() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::full(10, start);
+
+        // 3 of 10 left, above the 20% warning ratio
+        let decision = (0..7).map(|_| bucket.take(10, start)).last().unwrap();
+        assert_eq!(decision.remaining, 3);
+        assert!(decision.low_quota_warning(0.2).is_none());
+
+        let decision = bucket.take(10, start);
+        assert_eq!(
+            decision.low_quota_warning(0.2).as_deref(),
+            Some("2 of 10 requests left this minute, the limit resets in 48 seconds")
+        );
+        assert!(decision.low_quota_warning(0.0).is_none());
+
+        let now = Utc::now();
+        let quota = decision.quota_meta(0.2, now);
+        assert_eq!(quota.reset_at, now + chrono::Duration::seconds(48));
+        assert!(quota.warning.is_some());
+
+        // A refused request gets a 429, not a warning
+        bucket.take(10, start);
+        bucket.take(10, start);
+        assert!(bucket.take(10, start).low_quota_warning(0.2).is_none());
+    }
+}
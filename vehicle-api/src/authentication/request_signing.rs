@@ -0,0 +1,290 @@
+use actix_web::{dev::Payload, dev::ServiceRequest, web, HttpRequest};
+use hmac::{Hmac, KeyInit, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use super::identity::{Identity, Role};
+use crate::error::{AppError, AppResult};
+
+/// Headers of a signed request
+pub const KEY_ID_HEADER: &str = "X-Key-Id";
+pub const TIMESTAMP_HEADER: &str = "X-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+
+/// Maximum accepted age (in seconds) of a request timestamp
+pub const DEFAULT_TOLERANCE_SECS: i64 = 300;
+
+/// A caller allowed to sign its requests with a shared secret
+#[derive(Clone, Debug, Deserialize)]
+pub struct SigningClient {
+    pub key_id: String,
+    pub secret: String,
+    pub role: Role,
+    pub user_id: String,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+/// Clients of the signed request mode, keyed by key ID
+pub struct SigningClients {
+    clients: HashMap<String, SigningClient>,
+    tolerance_secs: i64,
+}
+
+// Clients read once from SIGNING_CLIENTS_PATH
+pub(super) static SIGNING_CLIENTS: LazyLock<SigningClients> =
+    LazyLock::new(SigningClients::from_env);
+
+// Signatures accepted within the tolerance window, with their timestamp
+static SEEN_SIGNATURES: LazyLock<Mutex<HashMap<String, i64>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Bytes covered by the signature: method, path with query, timestamp and body
+pub fn canonical_request(method: &str, path: &str, timestamp: &str, body: &[u8]) -> Vec<u8> {
+    let mut message = format!("{}\n{}\n{}\n", method.to_uppercase(), path, timestamp).into_bytes();
+    message.extend_from_slice(body);
+    message
+}
+
+fn mac(secret: &str, message: &[u8]) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(message);
+    mac
+}
+
+/// Hex encoded HMAC-SHA256 of a request, as clients send it in `X-Signature`.
+/// Meant for clients and tests, the API itself only verifies.
+#[allow(dead_code)]
+pub fn sign(secret: &str, method: &str, path: &str, timestamp: &str, body: &[u8]) -> String {
+    mac(secret, &canonical_request(method, path, timestamp, body))
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub(crate) fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Whether signed requests are accepted, at least one signing client is configured
+pub fn is_enabled() -> bool {
+    !SIGNING_CLIENTS.clients.is_empty()
+}
+
+impl SigningClients {
+    pub fn new(clients: Vec<SigningClient>, tolerance_secs: i64) -> Self {
+        Self {
+            clients: clients
+                .into_iter()
+                .map(|client| (client.key_id.clone(), client))
+                .collect(),
+            tolerance_secs,
+        }
+    }
+
+    /// JSON list of clients from SIGNING_CLIENTS_PATH, the mode is disabled when unset.
+    /// SIGNATURE_TOLERANCE_SECS overrides the accepted clock skew.
+    pub fn from_env() -> Self {
+        let tolerance_secs = std::env::var("SIGNATURE_TOLERANCE_SECS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_TOLERANCE_SECS);
+
+        let clients = match std::env::var("SIGNING_CLIENTS_PATH") {
+            Ok(path) => std::fs::read_to_string(&path)
+                .map_err(|e| format!("Cannot read {}: {}", path, e))
+                .and_then(|content| {
+                    serde_json::from_str(&content)
+                        .map_err(|e| format!("Invalid signing clients in {}: {}", path, e))
+                })
+                .unwrap_or_else(|error| {
+                    log::error!("Signed requests disabled: {}", error);
+                    Vec::new()
+                }),
+            Err(_) => Vec::new(),
+        };
+
+        Self::new(clients, tolerance_secs)
+    }
+
+    /// Check a signed request, returns the identity of its client
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify(
+        &self,
+        key_id: &str,
+        timestamp: &str,
+        signature: &str,
+        method: &str,
+        path: &str,
+        body: &[u8],
+        now: i64,
+    ) -> Result<Identity, String> {
+        let client = self
+            .clients
+            .get(key_id)
+            .ok_or_else(|| "Unknown signing key ID".to_string())?;
+
+        let sent_at: i64 = timestamp
+            .parse()
+            .map_err(|_| "X-Timestamp must be a unix timestamp in seconds".to_string())?;
+        if (now - sent_at).abs() > self.tolerance_secs {
+            return Err(format!(
+                "Request timestamp outside the accepted window of {} seconds",
+                self.tolerance_secs
+            ));
+        }
+
+        let signature = decode_hex(signature)
+            .ok_or_else(|| "X-Signature must be a hex encoded HMAC-SHA256".to_string())?;
+        mac(
+            &client.secret,
+            &canonical_request(method, path, timestamp, body),
+        )
+        .verify_slice(&signature)
+        .map_err(|_| "Invalid request signature".to_string())?;
+
+        Ok(Identity {
+            role: client.role.clone(),
+            user_id: client.user_id.clone(),
+            email: None,
+            tenant_id: client.tenant_id.clone(),
+            permissions: Vec::new(),
+            profile: None,
+            impersonated_by: None,
+        })
+    }
+
+    /// Remember an accepted signature, false when it was already used in the window
+    fn record(&self, signature: &str, now: i64) -> bool {
+        let Ok(mut seen) = SEEN_SIGNATURES.lock() else {
+            return true;
+        };
+        seen.retain(|_, sent_at| now - *sent_at <= self.tolerance_secs);
+        seen.insert(signature.to_lowercase(), now).is_none()
+    }
+}
+
+fn header(req: &HttpRequest, name: &str) -> Option<String> {
+    req.headers()
+        .get(name)
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.trim().to_string())
+}
+
+pub(super) fn extract_key_id(req: &HttpRequest) -> Option<String> {
+    header(req, SIGNATURE_HEADER).and_then(|_| header(req, KEY_ID_HEADER))
+}
+
+/// Resolve a signed request to its client identity. The body is buffered to be
+/// verified then handed back to the request.
+pub async fn resolve_identity(req: &mut ServiceRequest) -> AppResult<Identity> {
+    let (Some(key_id), Some(timestamp), Some(signature)) = (
+        header(req.request(), KEY_ID_HEADER),
+        header(req.request(), TIMESTAMP_HEADER),
+        header(req.request(), SIGNATURE_HEADER),
+    ) else {
+        return Err(AppError::unauthorized(
+            "Signed requests need X-Key-Id, X-Timestamp and X-Signature headers",
+        ));
+    };
+
+    let body = req
+        .extract::<web::Bytes>()
+        .await
+        .map_err(|e| AppError::bad_request(format!("Cannot read request body: {}", e)))?;
+    req.set_payload(Payload::from(body.clone()));
+
+    let path = req
+        .uri()
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or_else(|| req.path())
+        .to_string();
+    let now = chrono::Utc::now().timestamp();
+
+    let identity = SIGNING_CLIENTS
+        .verify(
+            &key_id,
+            &timestamp,
+            &signature,
+            req.method().as_str(),
+            &path,
+            &body,
+            now,
+        )
+        .map_err(AppError::unauthorized)?;
+
+    if !SIGNING_CLIENTS.record(&signature, now) {
+        return Err(AppError::unauthorized("Request signature already used"));
+    }
+
+    Ok(identity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clients() -> SigningClients {
+        SigningClients::new(
+            vec![SigningClient {
+                key_id: "billing".to_string(),
+                secret: "s3cret".to_string(),
+                role: Role::Admin,
+                user_id: "svc_billing".to_string(),
+                tenant_id: None,
+            }],
+            300,
+        )
+    }
+
+    #[test]
+    fn test_verify_signed_request() {
+        let clients = clients();
+        let body = br#"{"status":"CONFIRMED"}"#;
+        let signature = sign("s3cret", "PATCH", "/protected/bookings/1", "1000", body);
+
+        let identity = clients
+            .verify(
+                "billing",
+                "1000",
+                &signature,
+                "PATCH",
+                "/protected/bookings/1",
+                body,
+                1100,
+            )
+            .unwrap();
+        assert_eq!(identity.user_id, "svc_billing");
+
+        // Tampered body, path or key
+        let verify = |key_id: &str, path: &str, body: &[u8], now: i64| {
+            clients.verify(key_id, "1000", &signature, "PATCH", path, body, now)
+        };
+        assert!(verify("billing", "/protected/bookings/1", b"{}", 1100).is_err());
+        assert!(verify("billing", "/protected/bookings/2", body, 1100).is_err());
+        assert!(verify("other", "/protected/bookings/1", body, 1100).is_err());
+        // Stale timestamp
+        assert!(verify("billing", "/protected/bookings/1", body, 1301).is_err());
+    }
+
+    #[test]
+    fn test_signature_cannot_be_replayed() {
+        let clients = clients();
+        let signature = sign("s3cret", "GET", "/protected/vehicles", "2000", b"");
+
+        assert!(clients.record(&signature, 2000));
+        assert!(!clients.record(&signature, 2010));
+    }
+}
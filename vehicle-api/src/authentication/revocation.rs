@@ -0,0 +1,68 @@
+use bson::doc;
+use std::collections::HashSet;
+use std::sync::{LazyLock, RwLock};
+use std::time::Duration;
+
+use crate::error::AppResult;
+use crate::models::ApiKeyRevocation;
+use crate::services;
+
+// Hashes of revoked keys, a copy of the api_key_revocations collection
+static REVOKED_KEYS: LazyLock<RwLock<HashSet<String>>> =
+    LazyLock::new(|| RwLock::new(HashSet::new()));
+
+/// Seconds between two reloads of the revocation list (API_KEY_REVOCATION_REFRESH_SECS,
+/// default 5). Revocations made on another instance take at most this long to apply.
+fn refresh_interval() -> Duration {
+    let seconds = std::env::var("API_KEY_REVOCATION_REFRESH_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|seconds| *seconds > 0)
+        .unwrap_or(5);
+
+    Duration::from_secs(seconds)
+}
+
+/// Whether a key hash is on the revocation list, answered from memory
+pub fn is_revoked(key_hash: &str) -> bool {
+    REVOKED_KEYS
+        .read()
+        .map(|revoked| revoked.contains(key_hash))
+        .unwrap_or(false)
+}
+
+/// Apply a revocation made on this instance without waiting for the next reload
+pub fn invalidate(key_hash: &str) {
+    if let Ok(mut revoked) = REVOKED_KEYS.write() {
+        revoked.insert(key_hash.to_string());
+    }
+}
+
+/// Reload the revocation list from MongoDB, returns its size
+pub async fn refresh() -> AppResult<usize> {
+    let hashes: HashSet<String> =
+        services::mongodb::distinct::<ApiKeyRevocation>("key_hash", doc! {})
+            .await?
+            .into_iter()
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect();
+
+    let size = hashes.len();
+    if let Ok(mut revoked) = REVOKED_KEYS.write() {
+        *revoked = hashes;
+    }
+    Ok(size)
+}
+
+/// Reload the revocation list periodically in the background
+pub fn spawn_refresh() {
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(refresh_interval());
+        loop {
+            ticker.tick().await;
+            if let Err(error) = refresh().await {
+                log::error!("Failed to reload the API key revocation list: {}", error);
+            }
+        }
+    });
+}
@@ -0,0 +1,41 @@
+use bson::{doc, oid::ObjectId};
+
+use super::api_key::hash_key;
+use super::session;
+use crate::error::{AppError, AppResult};
+use crate::models::{ServiceAccount, ServiceTokenRequest, ServiceTokenResponse};
+use crate::services;
+
+/// Prefix of service account secrets, makes leaked secrets easy to spot
+pub const SECRET_PREFIX: &str = "vsa_";
+
+/// Generate a new random client secret
+pub fn generate_secret() -> String {
+    format!("{}{}", SECRET_PREFIX, session::random_token(32))
+}
+
+/// Exchange client credentials for a short-lived session token. No refresh token is
+/// issued, the integration asks for a new token with its secret.
+pub async fn issue_token(request: &ServiceTokenRequest) -> AppResult<ServiceTokenResponse> {
+    let invalid = || AppError::unauthorized("Invalid client credentials");
+
+    let id = ObjectId::parse_str(&request.client_id).map_err(|_| invalid())?;
+    let filter = doc! {
+        "_id": id,
+        "secret_hash": hash_key(&request.client_secret),
+        "disabled_at": null,
+    };
+    let service_account: ServiceAccount = services::mongodb::get_one(filter, None)
+        .await?
+        .ok_or_else(invalid)?;
+
+    let identity = service_account.identity();
+    let (session, token) = session::create_session(&identity, session::service_token_ttl()).await?;
+
+    Ok(ServiceTokenResponse {
+        token,
+        token_type: "Bearer".to_string(),
+        expires_at: session.expires_at,
+        identity,
+    })
+}
@@ -0,0 +1,202 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bson::{doc, oid::ObjectId};
+use chrono::{Duration, Utc};
+use rand::RngCore;
+
+use super::api_key::hash_key;
+use super::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{RefreshToken, RefreshTokenState, Session, SessionResponse};
+use crate::services;
+
+/// Prefix of session tokens, sent as `Authorization: Bearer vs_...`
+pub const TOKEN_PREFIX: &str = "vs_";
+
+/// Prefix of refresh tokens, exchanged on POST /auth/refresh
+pub const REFRESH_TOKEN_PREFIX: &str = "vr_";
+
+fn ttl_from_env(name: &str, default_seconds: i64) -> Duration {
+    let seconds = std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default_seconds);
+    Duration::seconds(seconds)
+}
+
+/// Session lifetime in seconds (SESSION_TTL_SECS, default 15 minutes)
+fn session_ttl() -> Duration {
+    ttl_from_env("SESSION_TTL_SECS", 15 * 60)
+}
+
+/// Refresh token lifetime in seconds (REFRESH_TOKEN_TTL_SECS, default 30 days)
+fn refresh_token_ttl() -> Duration {
+    ttl_from_env("REFRESH_TOKEN_TTL_SECS", 30 * 24 * 3600)
+}
+
+/// Service account token lifetime in seconds (SERVICE_TOKEN_TTL_SECS, default 1 hour)
+pub(super) fn service_token_ttl() -> Duration {
+    ttl_from_env("SERVICE_TOKEN_TTL_SECS", 3600)
+}
+
+/// Impersonation token lifetime in seconds (IMPERSONATION_TTL_SECS, default 15 minutes)
+pub(super) fn impersonation_ttl() -> Duration {
+    ttl_from_env("IMPERSONATION_TTL_SECS", 15 * 60)
+}
+
+/// Random URL safe string of `bytes` random bytes
+pub fn random_token(bytes: usize) -> String {
+    let mut buffer = vec![0u8; bytes];
+    rand::rngs::OsRng.fill_bytes(&mut buffer);
+    URL_SAFE_NO_PAD.encode(buffer)
+}
+
+/// Create a session for an identity, returns it with its plain token
+pub async fn create_session(identity: &Identity, ttl: Duration) -> AppResult<(Session, String)> {
+    let token = format!("{}{}", TOKEN_PREFIX, random_token(32));
+    let now = Utc::now();
+
+    let mut session = Session {
+        id: None,
+        token_hash: hash_key(&token),
+        role: identity.role.clone(),
+        user_id: identity.user_id.clone(),
+        email: identity.email.clone(),
+        tenant_id: identity.tenant_id.clone(),
+        permissions: identity.permissions.clone(),
+        impersonated_by: identity.impersonated_by.clone(),
+        created_at: now,
+        expires_at: now + ttl,
+    };
+    session.id = Some(services::mongodb::insert_one(&session, None).await?);
+
+    Ok((session, token))
+}
+
+/// Resolve a session token to an identity, None when unknown or expired
+pub async fn resolve_session(token: &str) -> AppResult<Option<Identity>> {
+    let filter = doc! {
+        "token_hash": hash_key(token),
+        "expires_at": { "$gt": bson::DateTime::from_chrono(Utc::now()) },
+    };
+    let session: Option<Session> = services::mongodb::get_one(filter, None).await?;
+
+    Ok(session.map(|session| session.identity()))
+}
+
+/// Create a refresh token for an identity, in a new family unless one is given
+async fn create_refresh_token(
+    identity: &Identity,
+    family_id: Option<ObjectId>,
+) -> AppResult<(RefreshToken, String)> {
+    let token = format!("{}{}", REFRESH_TOKEN_PREFIX, random_token(32));
+    let now = Utc::now();
+
+    let mut refresh_token = RefreshToken {
+        id: None,
+        token_hash: hash_key(&token),
+        family_id: family_id.unwrap_or_default(),
+        role: identity.role.clone(),
+        user_id: identity.user_id.clone(),
+        email: identity.email.clone(),
+        tenant_id: identity.tenant_id.clone(),
+        created_at: now,
+        expires_at: now + refresh_token_ttl(),
+        used_at: None,
+        revoked_at: None,
+    };
+    refresh_token.id = Some(services::mongodb::insert_one(&refresh_token, None).await?);
+
+    Ok((refresh_token, token))
+}
+
+/// Issue a session and its refresh token
+pub async fn issue_session(
+    identity: Identity,
+    family_id: Option<ObjectId>,
+) -> AppResult<SessionResponse> {
+    let (session, token) = create_session(&identity, session_ttl()).await?;
+    let (refresh_token, refresh_token_value) = create_refresh_token(&identity, family_id).await?;
+
+    Ok(SessionResponse {
+        token,
+        token_type: "Bearer".to_string(),
+        expires_at: session.expires_at,
+        refresh_token: refresh_token_value,
+        refresh_expires_at: refresh_token.expires_at,
+        identity,
+    })
+}
+
+/// Revoke every refresh token descending from the same login
+async fn revoke_family(family_id: &ObjectId) -> AppResult<()> {
+    services::mongodb::update_many(
+        "refresh_tokens",
+        doc! { "family_id": family_id, "revoked_at": null },
+        doc! { "$set": { "revoked_at": bson::DateTime::from_chrono(Utc::now()) } },
+        None,
+    )
+    .await?;
+    Ok(())
+}
+
+async fn find_refresh_token(token: &str) -> AppResult<RefreshToken> {
+    services::mongodb::get_one(doc! { "token_hash": hash_key(token) }, None)
+        .await?
+        .ok_or_else(|| AppError::unauthorized("Invalid refresh token"))
+}
+
+/// Exchange a refresh token for a new session and a new refresh token.
+/// A token can only be used once: presenting it again revokes its whole family,
+/// since either the client or an attacker holds a stolen copy.
+pub async fn refresh_session(token: &str) -> AppResult<SessionResponse> {
+    let refresh_token = find_refresh_token(token).await?;
+    let now = Utc::now();
+
+    match refresh_token.state(now) {
+        RefreshTokenState::Active => {}
+        RefreshTokenState::Used => {
+            log::warn!(
+                "Refresh token reused for {}, revoking its family",
+                refresh_token.user_id
+            );
+            revoke_family(&refresh_token.family_id).await?;
+            return Err(AppError::unauthorized(
+                "Refresh token already used, please log in again",
+            ));
+        }
+        RefreshTokenState::Revoked => {
+            return Err(AppError::unauthorized(
+                "Refresh token revoked, please log in again",
+            ))
+        }
+        RefreshTokenState::Expired => {
+            return Err(AppError::unauthorized(
+                "Refresh token expired, please log in again",
+            ))
+        }
+    }
+
+    // Only one of two concurrent refreshes may win, the other is a reuse
+    let result = services::mongodb::update_one(
+        "refresh_tokens",
+        doc! { "_id": refresh_token.id, "used_at": null, "revoked_at": null },
+        doc! { "$set": { "used_at": bson::DateTime::from_chrono(now) } },
+        None,
+    )
+    .await?;
+    if result.modified_count == 0 {
+        revoke_family(&refresh_token.family_id).await?;
+        return Err(AppError::unauthorized(
+            "Refresh token already used, please log in again",
+        ));
+    }
+
+    issue_session(refresh_token.identity(), Some(refresh_token.family_id)).await
+}
+
+/// Revoke a refresh token and every token rotated from the same login (logout)
+pub async fn revoke_refresh_token(token: &str) -> AppResult<()> {
+    let refresh_token = find_refresh_token(token).await?;
+    revoke_family(&refresh_token.family_id).await
+}
@@ -0,0 +1,44 @@
+use chrono::Utc;
+use std::collections::HashSet;
+
+use super::identity::{Identity, Role};
+use super::permission::Permission;
+use crate::error::AppResult;
+use crate::models::Suspension;
+use crate::services;
+
+/// Permissions a suspended customer loses, everything else keeps working
+pub const SUSPENDED_PERMISSIONS: [Permission; 1] = [Permission::BookingCreate];
+
+/// Suspension in force for an identity, only customers can be suspended
+pub async fn active_suspension(identity: &Identity) -> AppResult<Option<Suspension>> {
+    if identity.role != Role::Customer {
+        return Ok(None);
+    }
+
+    let filter = Suspension::active_filter(&identity.user_id, Utc::now());
+    services::mongodb::get_one(filter, None).await
+}
+
+/// Remove the permissions a suspension takes away
+pub fn restrict(mut permissions: HashSet<Permission>) -> HashSet<Permission> {
+    for permission in SUSPENDED_PERMISSIONS {
+        permissions.remove(&permission);
+    }
+    permissions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suspended_customer_keeps_read_access() {
+        let permissions = HashSet::from([Permission::BookingCreate, Permission::BookingApprove]);
+
+        assert_eq!(
+            restrict(permissions),
+            HashSet::from([Permission::BookingApprove])
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::StatusCode,
+    middleware, Error, HttpResponse, Result,
+};
+use std::time::Duration;
+
+use crate::models::Fault;
+
+// Chaos Middleware using from_fn, only registered when chaos::is_available()
+pub async fn chaos_middleware(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    // Never break the endpoint used to turn chaos off
+    if req.path().contains("/chaos") {
+        return Ok(next.call(req).await?.map_into_left_body());
+    }
+
+    let roll = rand::random::<f64>() * 100.0;
+    let fault = super::get_config().fault_for(req.path(), roll);
+
+    match fault {
+        None => Ok(next.call(req).await?.map_into_left_body()),
+        Some(Fault::Latency { delay_ms }) => {
+            log::warn!("Chaos: delaying {} by {}ms", req.path(), delay_ms);
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            Ok(next.call(req).await?.map_into_left_body())
+        }
+        Some(Fault::Error { status }) => {
+            log::warn!("Chaos: injecting {} on {}", status, req.path());
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            let response = HttpResponse::build(status).json(serde_json::json!({
+                "code": status.as_u16(),
+                "message": "Injected fault (chaos testing)",
+                "error_type": "ChaosFault"
+            }));
+            Ok(req.into_response(response).map_into_right_body())
+        }
+        Some(Fault::Close { after_ms }) => {
+            log::warn!("Chaos: closing {} after {}ms", req.path(), after_ms);
+            tokio::time::sleep(Duration::from_millis(after_ms)).await;
+            let response = HttpResponse::BadGateway().force_close().finish();
+            Ok(req.into_response(response).map_into_right_body())
+        }
+    }
+}
@@ -0,0 +1,31 @@
+pub mod middleware;
+
+use std::sync::RwLock;
+
+use crate::models::ChaosConfig;
+
+// Runtime chaos configuration, changed through the admin endpoint
+static CHAOS_CONFIG: RwLock<ChaosConfig> = RwLock::new(ChaosConfig::disabled());
+
+/// Chaos testing is opt-in (CHAOS_ENABLED=true) and never available in prod
+pub fn is_available() -> bool {
+    let env = std::env::var("ENV").unwrap_or_else(|_| "dev".to_string());
+    let opted_in = std::env::var("CHAOS_ENABLED")
+        .map(|value| value == "true")
+        .unwrap_or(false);
+
+    env != "prod" && opted_in
+}
+
+pub fn get_config() -> ChaosConfig {
+    CHAOS_CONFIG
+        .read()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_config(config: ChaosConfig) {
+    if let Ok(mut current) = CHAOS_CONFIG.write() {
+        *current = config;
+    }
+}
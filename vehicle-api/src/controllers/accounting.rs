@@ -0,0 +1,45 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{AccountingExport, CreateExportRequest};
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+/// Start an export of the ledger over a period, the file is produced in the background
+/// (Admin only)
+pub async fn create(
+    identity: &Identity,
+    request: CreateExportRequest,
+) -> AppResult<AccountingExport> {
+    request.validate().map_err(AppError::bad_request)?;
+
+    let export = services::accounting::start(request, Some(identity.user_id.clone())).await?;
+
+    // The export only covers the ledger of the admin's tenant
+    let tenant_id = identity.tenant_id.clone();
+    let running = export.clone();
+    actix_web::rt::spawn(services::mongodb::tenant::scope(
+        tenant_id,
+        services::accounting::run(running),
+    ));
+
+    Ok(export)
+}
+
+/// Exports, newest first (Admin only)
+pub async fn list(page: PageQuery) -> AppResult<Vec<AccountingExport>> {
+    let mut options = FindOptions::builder()
+        .sort(doc! { "started_at": -1 })
+        .build();
+    page.apply(&mut options);
+    services::mongodb::collect_many(doc! {}, options).await
+}
+
+/// (Admin only)
+pub async fn get(export_id: &ObjectId) -> AppResult<AccountingExport> {
+    services::mongodb::get_one(doc! { "_id": export_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Export not found"))
+}
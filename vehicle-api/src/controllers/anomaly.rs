@@ -0,0 +1,50 @@
+use bson::{doc, oid::ObjectId, Document};
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{Anomaly, AnomalyFilters};
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+/// List anomalies, newest first, open ones only unless asked otherwise (Admin only)
+pub async fn list(filters: AnomalyFilters, page: PageQuery) -> AppResult<Vec<Anomaly>> {
+    let mut filter = Document::new();
+    if let Some(kind) = filters.kind {
+        filter.insert("kind", kind.to_string());
+    }
+    if !filters.include_acknowledged {
+        filter.insert("acknowledged_at", bson::Bson::Null);
+    }
+
+    let mut options = FindOptions::builder()
+        .sort(doc! { "detected_at": -1 })
+        .build();
+    page.apply(&mut options);
+
+    services::mongodb::collect_many(filter, options).await
+}
+
+/// Run the anomaly scan now, returns the newly raised anomalies (Admin only)
+pub async fn scan() -> AppResult<Vec<Anomaly>> {
+    services::anomaly::scan().await
+}
+
+/// Close an anomaly, the same pattern is raised again on a later scan (Admin only)
+pub async fn acknowledge(identity: &Identity, anomaly_id: &ObjectId) -> AppResult<Anomaly> {
+    let filter = doc! { "_id": anomaly_id };
+
+    let mut anomaly: Anomaly = services::mongodb::get_one(filter.clone(), None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Anomaly not found"))?;
+
+    if anomaly.acknowledged_at.is_none() {
+        anomaly.acknowledged_at = Some(chrono::Utc::now());
+        anomaly.acknowledged_by = Some(identity.user_id.clone());
+        services::mongodb::find_one_and_replace(filter, &anomaly, None)
+            .await?
+            .ok_or_else(|| AppError::internal_server_error("Failed to acknowledge anomaly"))?;
+    }
+
+    Ok(anomaly)
+}
@@ -0,0 +1,182 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::{api_key, revocation};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    ApiKey, ApiKeyRevocation, CreateApiKeyRequest, RevokeApiKeyRequest, RotateApiKeyRequest,
+    UpdateAllowedNetworksRequest,
+};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+use crate::util::pagination::PageQuery;
+
+/// Hours the old key keeps working after a rotation when no grace period is given
+const DEFAULT_ROTATION_GRACE_HOURS: i64 = 24;
+
+/// Create an API key, returns it with its plain key (Admin only)
+pub async fn create(
+    identity: &Identity,
+    mut request: CreateApiKeyRequest,
+) -> AppResult<(ApiKey, String)> {
+    // Keys of a rental company can only be issued for that company
+    match (&identity.tenant_id, &request.tenant_id) {
+        (Some(own), Some(requested)) if own != requested => {
+            return Err(AppError::forbidden(
+                "You can only create API keys for your own tenant.",
+            ));
+        }
+        (Some(own), None) => request.tenant_id = Some(own.clone()),
+        _ => {}
+    }
+
+    let key = api_key::generate_key();
+    let mut api_key = ApiKey::new(request, &key, identity.user_id.clone());
+
+    let inserted_id = services::mongodb::insert_one(&api_key, None).await?;
+    api_key.id = Some(inserted_id);
+
+    Ok((api_key, key))
+}
+
+/// List every API key, revoked ones included (Admin only)
+pub async fn list(page: PageQuery) -> AppResult<Vec<ApiKey>> {
+    services::mongodb::collect_many(doc! {}, page.to_find_options()).await
+}
+
+/// Revoke an API key, it stops authenticating immediately on this instance and
+/// within seconds on the others (Admin only)
+pub async fn revoke(identity: &Identity, api_key_id: &ObjectId) -> AppResult<ApiKey> {
+    let filter = doc! { "_id": api_key_id };
+
+    let mut api_key: ApiKey = services::mongodb::get_one(filter.clone(), None)
+        .await?
+        .ok_or_else(|| AppError::not_found("API key not found"))?;
+
+    if api_key.revoked_at.is_none() {
+        api_key.revoked_at = Some(chrono::Utc::now());
+        services::mongodb::find_one_and_replace(filter, &api_key, None)
+            .await?
+            .ok_or_else(|| AppError::internal_server_error("Failed to revoke API key"))?;
+    }
+
+    add_revocation(ApiKeyRevocation::new(
+        api_key.key_hash.clone(),
+        Some(api_key.prefix.clone()),
+        None,
+        identity.user_id.clone(),
+    ))
+    .await?;
+
+    Ok(api_key)
+}
+
+/// Blacklist a leaked key by its value or hash, whether it is stored or not
+/// (e.g. the bootstrap key) (Admin only)
+pub async fn blacklist(
+    identity: &Identity,
+    request: RevokeApiKeyRequest,
+) -> AppResult<ApiKeyRevocation> {
+    let key_hash = request.key_hash().map_err(AppError::bad_request)?;
+
+    // A stored key is revoked as well so it shows up as such in listings
+    let now = bson::DateTime::from_chrono(chrono::Utc::now());
+    services::mongodb::update_one(
+        ApiKey::get_collection(),
+        doc! { "key_hash": &key_hash, "revoked_at": null },
+        doc! { "$set": { "revoked_at": now } },
+        None,
+    )
+    .await?;
+
+    add_revocation(ApiKeyRevocation::new(
+        key_hash,
+        request.prefix(),
+        request.reason,
+        identity.user_id.clone(),
+    ))
+    .await
+}
+
+/// Keys on the revocation list, newest first (Admin only)
+pub async fn list_revocations(page: PageQuery) -> AppResult<Vec<ApiKeyRevocation>> {
+    let mut options = FindOptions::builder()
+        .sort(doc! { "revoked_at": -1 })
+        .build();
+    page.apply(&mut options);
+    services::mongodb::collect_many(doc! {}, options).await
+}
+
+/// Store a revocation once per key and apply it right away on this instance
+async fn add_revocation(mut revocation: ApiKeyRevocation) -> AppResult<ApiKeyRevocation> {
+    revocation::invalidate(&revocation.key_hash);
+
+    let existing: Option<ApiKeyRevocation> =
+        services::mongodb::get_one(doc! { "key_hash": &revocation.key_hash }, None).await?;
+    if let Some(existing) = existing {
+        return Ok(existing);
+    }
+
+    revocation.id = Some(services::mongodb::insert_one(&revocation, None).await?);
+    Ok(revocation)
+}
+
+/// Restrict an API key to networks, an empty list lifts the restriction (Admin only)
+pub async fn update_allowed_networks(
+    api_key_id: &ObjectId,
+    request: UpdateAllowedNetworksRequest,
+) -> AppResult<ApiKey> {
+    let filter = doc! { "_id": api_key_id };
+
+    let mut api_key: ApiKey = services::mongodb::get_one(filter.clone(), None)
+        .await?
+        .ok_or_else(|| AppError::not_found("API key not found"))?;
+
+    api_key.allowed_networks = request.allowed_networks;
+    services::mongodb::find_one_and_replace(filter, &api_key, None)
+        .await?
+        .ok_or_else(|| AppError::internal_server_error("Failed to update API key"))?;
+
+    Ok(api_key)
+}
+
+/// Replace an API key with a new one, the old key keeps working during the
+/// grace period (Admin only)
+pub async fn rotate(
+    identity: &Identity,
+    api_key_id: &ObjectId,
+    request: RotateApiKeyRequest,
+) -> AppResult<(ApiKey, String)> {
+    let filter = doc! { "_id": api_key_id };
+
+    let mut old_api_key: ApiKey = services::mongodb::get_one(filter.clone(), None)
+        .await?
+        .ok_or_else(|| AppError::not_found("API key not found"))?;
+
+    if old_api_key.revoked_at.is_some() {
+        return Err(AppError::bad_request("A revoked API key cannot be rotated"));
+    }
+
+    let key = api_key::generate_key();
+    let mut api_key = old_api_key.rotate(&key, identity.user_id.clone());
+
+    let inserted_id = services::mongodb::insert_one(&api_key, None).await?;
+    api_key.id = Some(inserted_id);
+
+    let grace_period = chrono::Duration::hours(
+        request
+            .grace_period_hours
+            .unwrap_or(DEFAULT_ROTATION_GRACE_HOURS),
+    );
+    let grace_end = chrono::Utc::now() + grace_period;
+    old_api_key.expires_at = Some(match old_api_key.expires_at {
+        Some(expires_at) => expires_at.min(grace_end),
+        None => grace_end,
+    });
+    services::mongodb::find_one_and_replace(filter, &old_api_key, None)
+        .await?
+        .ok_or_else(|| AppError::internal_server_error("Failed to expire the rotated API key"))?;
+
+    Ok((api_key, key))
+}
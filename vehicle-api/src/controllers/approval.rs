@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use bson::{doc, oid::ObjectId, Document};
+use chrono::{Duration, Utc};
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::Identity;
+use crate::controllers;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    approval_sla, ApprovalItem, ApprovalMetrics, ApprovalMetricsQuery, Booking, BookingStatus,
+    UpdateBookingRequest, Vehicle, VehicleType,
+};
+use crate::services;
+
+/// Vehicles whose bookings the identity approves, by ID
+async fn managed_vehicles(identity: &Identity) -> AppResult<HashMap<ObjectId, VehicleType>> {
+    let mut filter = Document::new();
+    if let Some(vehicle_type) = VehicleType::managed_by(&identity.role) {
+        filter.insert("type", vehicle_type.to_string());
+    }
+    let vehicles: Vec<Vehicle> = services::mongodb::collect_many(filter, None).await?;
+
+    Ok(vehicles
+        .iter()
+        .filter_map(|vehicle| vehicle.id.map(|id| (id, VehicleType::of(vehicle))))
+        .collect())
+}
+
+/// Pending bookings of the vehicles the identity manages, oldest first
+/// (Admin, CarManager, MotorbikeManager)
+pub async fn list(identity: &Identity) -> AppResult<Vec<ApprovalItem>> {
+    let vehicles = managed_vehicles(identity).await?;
+    pending(identity, &vehicles).await
+}
+
+/// Pending bookings of the given vehicles, oldest first
+async fn pending(
+    identity: &Identity,
+    vehicles: &HashMap<ObjectId, VehicleType>,
+) -> AppResult<Vec<ApprovalItem>> {
+    let filter = doc! {
+        "status": "PENDING",
+        "vehicle_id": { "$in": vehicles.keys().collect::<Vec<_>>() },
+    };
+    let options = FindOptions::builder()
+        .sort(doc! { "order_date": 1 })
+        .build();
+    let mut bookings: Vec<Booking> = services::mongodb::collect_many(filter, options).await?;
+    services::encryption::present_bookings(&mut bookings, identity).await?;
+
+    let sla = approval_sla();
+    let calendar = services::office_hours::calendar().await?;
+    let now = Utc::now();
+    Ok(bookings
+        .into_iter()
+        .filter_map(|booking| {
+            let vehicle_type = vehicles.get(&booking.vehicle_id)?.clone();
+            Some(ApprovalItem::new(
+                booking,
+                vehicle_type,
+                sla,
+                &calendar,
+                now,
+            ))
+        })
+        .collect())
+}
+
+/// Confirm or reject a pending booking of a managed vehicle
+/// (Admin, CarManager, MotorbikeManager)
+pub async fn decide(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    status: BookingStatus,
+) -> AppResult<Booking> {
+    let booking: Booking = services::mongodb::get_one(doc! { "_id": booking_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))?;
+
+    if !managed_vehicles(identity)
+        .await?
+        .contains_key(&booking.vehicle_id)
+    {
+        return Err(AppError::forbidden(
+            "You can only approve bookings of the vehicles you manage.",
+        ));
+    }
+
+    let request = UpdateBookingRequest {
+        status: Some(status),
+        from_date: None,
+        to_date: None,
+    };
+    controllers::booking::update(identity, booking_id, request).await
+}
+
+/// Queue size, SLA breaches and average approval time (Admin, CarManager, MotorbikeManager)
+pub async fn metrics(
+    identity: &Identity,
+    query: ApprovalMetricsQuery,
+) -> AppResult<ApprovalMetrics> {
+    let days = query.days.unwrap_or(30);
+    if !(1..=365).contains(&days) {
+        return Err(AppError::bad_request("days must be between 1 and 365"));
+    }
+
+    let vehicles = managed_vehicles(identity).await?;
+    let since = Utc::now() - Duration::days(days);
+    let filter = doc! {
+        "status": { "$in": ["CONFIRMED", "REJECTED"] },
+        "vehicle_id": { "$in": vehicles.keys().collect::<Vec<_>>() },
+        "order_date": { "$gte": bson::DateTime::from_chrono(since) },
+    };
+    let (pending, decided, calendar) = services::fanout::try_join3(
+        pending(identity, &vehicles),
+        services::mongodb::collect_many::<Booking>(filter, None),
+        services::office_hours::calendar(),
+    )
+    .await?;
+
+    Ok(ApprovalMetrics::compute(
+        &pending,
+        &decided,
+        approval_sla(),
+        &calendar,
+    ))
+}
@@ -0,0 +1,34 @@
+use crate::error::AppResult;
+use bson::doc;
+use mongodb::options::FindOptions;
+
+use crate::models::{
+    ActionAuditEntry, AuthAuditEntry, AuthAuditFilters, AuthAuditPage, ToBsonFilter, ToFindOptions,
+};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+use crate::util::pagination::PageQuery;
+
+/// Search the authentication audit log, newest attempts first (Admin only)
+pub async fn list_auth(filters: AuthAuditFilters) -> AppResult<AuthAuditPage> {
+    let filter = filters.to_bson_filter();
+
+    let total =
+        services::mongodb::count(AuthAuditEntry::get_collection(), filter.clone(), None).await?;
+    let entries: Vec<AuthAuditEntry> =
+        services::mongodb::collect_many(filter, filters.to_find_options()).await?;
+
+    Ok(AuthAuditPage {
+        entries,
+        page: filters.page(),
+        limit: filters.limit(),
+        total,
+    })
+}
+
+/// Irreversible admin actions, newest first (Admin only)
+pub async fn list_actions(page: PageQuery) -> AppResult<Vec<ActionAuditEntry>> {
+    let mut options = FindOptions::builder().sort(doc! { "at": -1 }).build();
+    page.apply(&mut options);
+    services::mongodb::collect_many(doc! {}, options).await
+}
@@ -0,0 +1,90 @@
+use bson::doc;
+use chrono::{Duration, Utc};
+
+use crate::authentication::{oidc, service_account, session};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    OidcCallbackQuery, OidcLoginState, RefreshTokenRequest, ServiceTokenRequest,
+    ServiceTokenResponse, SessionResponse,
+};
+use crate::services;
+
+/// Time allowed between /auth/login and /auth/callback
+const LOGIN_STATE_TTL_MINUTES: i64 = 10;
+
+/// Start an OIDC login, returns the provider URL to redirect to
+pub async fn login() -> AppResult<String> {
+    let config = oidc::OidcConfig::from_env()?;
+    let metadata = oidc::get_provider_metadata(&config).await?;
+
+    let login_state = OidcLoginState {
+        id: None,
+        state: session::random_token(24),
+        nonce: session::random_token(24),
+        code_verifier: session::random_token(48),
+        created_at: Utc::now(),
+    };
+    services::mongodb::insert_one(&login_state, None).await?;
+
+    oidc::authorization_url(
+        &config,
+        metadata,
+        &login_state.state,
+        &login_state.nonce,
+        &login_state.code_verifier,
+    )
+}
+
+/// Complete an OIDC login and issue a session token
+pub async fn callback(query: OidcCallbackQuery) -> AppResult<SessionResponse> {
+    let config = oidc::OidcConfig::from_env()?;
+    let metadata = oidc::get_provider_metadata(&config).await?;
+
+    // The state can only be used once
+    let filter = doc! { "state": &query.state };
+    let login_state: OidcLoginState = services::mongodb::get_one(filter.clone(), None)
+        .await?
+        .ok_or_else(|| AppError::unauthorized("Unknown or already used login state"))?;
+    services::mongodb::delete_one("oidc_login_states", filter, None).await?;
+
+    if login_state.created_at + Duration::minutes(LOGIN_STATE_TTL_MINUTES) < Utc::now() {
+        return Err(AppError::unauthorized("Login expired, please retry"));
+    }
+    if let Some(error) = query.error {
+        return Err(AppError::unauthorized(format!(
+            "Login refused by the provider: {}",
+            query.error_description.unwrap_or(error)
+        )));
+    }
+    let code = query
+        .code
+        .ok_or_else(|| AppError::bad_request("Missing authorization code"))?;
+
+    let claims = oidc::exchange_code(&config, metadata, &code, &login_state.code_verifier).await?;
+    config
+        .validate_claims(
+            metadata,
+            &claims,
+            &login_state.nonce,
+            Utc::now().timestamp(),
+        )
+        .map_err(AppError::unauthorized)?;
+
+    let identity = config.identity_from_claims(&claims)?;
+    session::issue_session(identity, None).await
+}
+
+/// Rotate a refresh token, returns a new session and refresh token
+pub async fn refresh(request: RefreshTokenRequest) -> AppResult<SessionResponse> {
+    session::refresh_session(&request.refresh_token).await
+}
+
+/// Revoke a refresh token and the tokens rotated with it
+pub async fn revoke(request: RefreshTokenRequest) -> AppResult<()> {
+    session::revoke_refresh_token(&request.refresh_token).await
+}
+
+/// Exchange service account credentials for a short-lived token
+pub async fn service_token(request: ServiceTokenRequest) -> AppResult<ServiceTokenResponse> {
+    service_account::issue_token(&request).await
+}
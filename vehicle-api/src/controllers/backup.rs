@@ -0,0 +1,58 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{Backup, RestoreReport};
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+/// Backups hold every tenant's data, tenant admins cannot touch them
+fn check_platform_admin(identity: &Identity) -> AppResult<()> {
+    match identity.tenant_id {
+        Some(_) => Err(AppError::forbidden(
+            "Backups are only available to admins outside a tenant",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Start a backup of the database, the files are produced in the background (Admin only)
+pub async fn create(identity: &Identity) -> AppResult<Backup> {
+    check_platform_admin(identity)?;
+
+    let backup = services::backup::start(Some(identity.user_id.clone())).await?;
+    actix_web::rt::spawn(services::backup::run(backup.clone()));
+
+    Ok(backup)
+}
+
+/// Backups, newest first (Admin only)
+pub async fn list(identity: &Identity, page: PageQuery) -> AppResult<Vec<Backup>> {
+    check_platform_admin(identity)?;
+
+    let mut options = FindOptions::builder()
+        .sort(doc! { "started_at": -1 })
+        .build();
+    page.apply(&mut options);
+    services::mongodb::collect_many(doc! {}, options).await
+}
+
+/// (Admin only)
+pub async fn get(identity: &Identity, backup_id: &ObjectId) -> AppResult<Backup> {
+    check_platform_admin(identity)?;
+
+    services::mongodb::get_one(doc! { "_id": backup_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Backup not found"))
+}
+
+/// Check that a backup could be restored, nothing is written (Admin only)
+pub async fn restore_dry_run(
+    identity: &Identity,
+    backup_id: &ObjectId,
+) -> AppResult<RestoreReport> {
+    let backup = get(identity, backup_id).await?;
+
+    services::backup::restore_dry_run(&backup).await
+}
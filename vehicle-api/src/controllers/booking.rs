@@ -1,51 +1,130 @@
 use bson::{doc, oid::ObjectId};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::options::{FindOneOptions, FindOptions};
+use std::collections::HashMap;
 
-use crate::authentication::identity::Identity;
+use crate::authentication::identity::{Identity, Role};
 use crate::error::{AppError, AppResult};
-use crate::models::{Booking, CreateBookingRequest, UpdateBookingRequest, Vehicle};
+use crate::models::{
+    ActionAuditEntry, AuditAction, Booking, BookingLedger, BookingStats, BookingStatus,
+    BookingValidationReport, BulkUpdateBookingRequest, BulkUpdateItem, BulkUpdateReport,
+    CancellationPolicy, Change, ChangesetKind, ConditionStage, CreateBookingRequest, CurrencyQuery,
+    Handover, HandoverRequest, LateFee, LedgerEntryKind, LedgerTotals, NewLedgerEntry,
+    RiskAssessment, UpdateBookingRequest, Vehicle, VehicleTypeScope,
+};
 use crate::services;
+use crate::services::mongodb::MongoStruct;
+use crate::util::pagination::PageQuery;
 use crate::validator;
 
 /// Create a new booking (Customer)
-pub async fn create(identity: &Identity, request: CreateBookingRequest) -> AppResult<Booking> {
+pub async fn create(
+    identity: &Identity,
+    request: CreateBookingRequest,
+    client_country: Option<String>,
+) -> AppResult<Booking> {
     // Validate booking creation (date range and overlap checking)
-    crate::validator::booking::validate_booking_creation(identity, &request)
-        .await
-        .map_err(|e| AppError::bad_request(&e))?;
-
-    // Check if vehicle exists
-    let vehicle_filter = doc! { "_id": request.vehicle_id };
-    let _vehicle: Vehicle = services::mongodb::get_one(vehicle_filter, None)
-        .await?
-        .ok_or_else(|| AppError::not_found("Vehicle not found"))?;
-
-    // Create the booking
-    let mut booking = Booking::new(request, identity.user_id.clone());
-
-    let inserted_id = services::mongodb::insert_one(&booking, None).await?;
-    booking.id = Some(inserted_id);
+    crate::validator::booking::validate_booking_creation(identity, &request).await?;
 
+    let mut booking = services::booking::create(identity, request, client_country).await?;
+    services::encryption::present_booking(&mut booking, identity).await?;
     Ok(booking)
 }
 
+/// Pre-check a prospective booking and report every problem at once (Customer)
+pub async fn validate(
+    identity: &Identity,
+    request: CreateBookingRequest,
+) -> AppResult<BookingValidationReport> {
+    validator::booking::precheck_booking(identity, &request).await
+}
+
 /// List bookings (simplified without filters and pagination)
-pub async fn list(identity: &Identity) -> AppResult<Vec<Booking>> {
+pub async fn list(
+    identity: &Identity,
+    scope: VehicleTypeScope,
+    page: PageQuery,
+    currency: CurrencyQuery,
+) -> AppResult<Vec<Booking>> {
+    let rates = services::currency::rates();
+    rates.check(currency.currency)?;
     let mut filter = bson::Document::new();
 
     // Apply permission-based filtering for customers
-    if matches!(
-        identity.role,
-        crate::authentication::identity::Role::Customer
-    ) {
+    if matches!(identity.role, Role::Customer) {
         // Customers can only see their own bookings
         filter.insert("customer_id", &identity.user_id);
     }
 
-    let bookings = services::mongodb::collect_many(filter, None).await?;
+    // Managers see the bookings of their vehicle type unless they ask for another one
+    if let Some(vehicle_type) = scope.resolve(&identity.role) {
+        let vehicle_ids = services::mongodb::distinct::<Vehicle>(
+            "_id",
+            doc! { "type": vehicle_type.to_string() },
+        )
+        .await?;
+        filter.insert("vehicle_id", doc! { "$in": vehicle_ids });
+    }
+
+    let mut bookings: Vec<Booking> =
+        services::mongodb::collect_many(filter, page.to_find_options()).await?;
+    services::encryption::present_bookings(&mut bookings, identity).await?;
+    // Only the total is converted, fees stay in the booking's currency
+    for booking in &mut bookings {
+        if let Some(total_price) = booking.total_price.as_mut() {
+            rates.convert_in_place(total_price, currency.currency);
+        }
+    }
 
     Ok(bookings)
 }
 
+/// Counts by status, bookings per vehicle, revenue per month and average length, over the
+/// bookings of the caller's vehicle type (Admin, CarManager, MotorbikeManager)
+pub async fn stats(identity: &Identity, scope: VehicleTypeScope) -> AppResult<BookingStats> {
+    let mut filter = bson::Document::new();
+    if let Some(vehicle_type) = scope.resolve(&identity.role) {
+        let vehicle_ids = services::mongodb::distinct::<Vehicle>(
+            "_id",
+            doc! { "type": vehicle_type.to_string() },
+        )
+        .await?;
+        filter.insert("vehicle_id", doc! { "$in": vehicle_ids });
+    }
+
+    // The $facet stage always yields exactly one row
+    let rows: Vec<BookingStats> =
+        services::mongodb::aggregate::<Booking, BookingStats>(BookingStats::pipeline(filter))
+            .await?
+            .try_collect()
+            .await?;
+    rows.into_iter()
+        .next()
+        .ok_or_else(|| AppError::internal_server_error("Booking statistics returned no row"))
+}
+
+/// Confirmed bookings of the caller as an iCalendar feed (Customer)
+pub async fn export_calendar(identity: &Identity) -> AppResult<String> {
+    let filter = doc! { "customer_id": &identity.user_id, "status": "CONFIRMED" };
+    let options = FindOptions::builder().sort(doc! { "from_date": 1 }).build();
+    let bookings: Vec<Booking> = services::mongodb::collect_many(filter, options).await?;
+
+    let vehicle_ids: Vec<ObjectId> = bookings.iter().map(|booking| booking.vehicle_id).collect();
+    let vehicles: Vec<Vehicle> =
+        services::mongodb::collect_many(doc! { "_id": { "$in": vehicle_ids } }, None).await?;
+    let vehicles: HashMap<ObjectId, Vehicle> = vehicles
+        .into_iter()
+        .filter_map(|vehicle| vehicle.id.map(|id| (id, vehicle)))
+        .collect();
+
+    Ok(services::calendar::render_bookings(
+        &bookings,
+        &vehicles,
+        Utc::now(),
+    ))
+}
+
 /// Update a booking (Admin, CarManager, MotorbikeManager, Customer - for their own bookings)
 pub async fn update(
     identity: &Identity,
@@ -55,35 +134,245 @@ pub async fn update(
     // Get the existing booking
     let filter = doc! { "_id": booking_id };
 
-    let mut booking: Booking = services::mongodb::get_one(filter.clone(), None)
+    let booking: Booking = services::mongodb::get_one(filter, None)
         .await?
         .ok_or_else(|| AppError::not_found("Booking not found"))?;
 
-    // Validate the update (permissions and business rules)
-    validator::booking::validate_update_booking(identity, &booking, &request)?;
+    // Validate the update (permissions and transition policy)
+    let policy = services::mongodb::booking::get_booking_policy().await?;
+    validator::booking::validate_update_booking(identity, &booking, &request, &policy)?;
+
+    let mut booking = services::booking::update(identity, booking, request).await?;
+    services::encryption::present_booking(&mut booking, identity).await?;
+    Ok(booking)
+}
+
+/// Record the odometer and fuel level when the vehicle of a confirmed booking is handed
+/// over (check-in) or returned (check-out) (Admin, CarManager, MotorbikeManager)
+pub async fn handover(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    stage: ConditionStage,
+    request: HandoverRequest,
+) -> AppResult<Booking> {
+    let booking: Booking = services::mongodb::get_one(doc! { "_id": booking_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))?;
+    // Latest reading the vehicle came back with, from its previous rentals
+    let filter = doc! {
+        "vehicle_id": booking.vehicle_id,
+        "_id": { "$ne": booking_id },
+        "check_out": { "$ne": null },
+    };
+    let options = FindOneOptions::builder()
+        .sort(doc! { "check_out.at": -1 })
+        .build();
+    let last_odometer_km = services::mongodb::get_one::<Booking>(filter, options)
+        .await?
+        .and_then(|previous| previous.check_out)
+        .map(|check_out| check_out.odometer_km);
 
-    // Update the booking status
-    if let Some(new_status) = request.status {
-        booking.status = new_status;
+    let now = Utc::now();
+    validator::booking::validate_handover(&booking, stage, &request, last_odometer_km, now)?;
+
+    let handover = Handover {
+        at: now,
+        odometer_km: request.odometer_km,
+        fuel_percent: request.fuel_percent,
+        recorded_by: identity.user_id.clone(),
+    };
+    let handover = bson::to_bson(&handover).map_err(|e| {
+        AppError::internal_server_error(format!("Cannot serialize handover: {}", e))
+    })?;
+    let (field, late_fee) = match stage {
+        ConditionStage::CheckIn => ("check_in", None),
+        ConditionStage::CheckOut => ("check_out", late_fee(&booking, now).await?),
+    };
+    let mut set = doc! { field: handover };
+    if let Some(late_fee) = late_fee {
+        set.insert("late_fee", late_fee.amount);
+    }
+    // Only while still confirmed and not recorded yet, two managers cannot both record it
+    let result = services::mongodb::update_one(
+        Booking::get_collection(),
+        doc! { "_id": booking_id, "status": "CONFIRMED", field: null },
+        doc! { "$set": set },
+        None,
+    )
+    .await?;
+    if result.modified_count == 0 {
+        return Err(AppError::conflict("Booking changed meanwhile, retry later"));
+    }
+    if let Some(late_fee) = late_fee {
+        services::ledger::append(NewLedgerEntry {
+            booking_id: *booking_id,
+            customer_id: booking.customer_id.clone(),
+            kind: LedgerEntryKind::Fee,
+            amount: late_fee.amount,
+            description: format!(
+                "Late return, {} day(s) after {}",
+                late_fee.days, booking.to_date
+            ),
+            payment_id: None,
+            recorded_by: identity.user_id.clone(),
+        })
+        .await?;
     }
 
-    // Save the updated booking
-    services::mongodb::find_one_and_replace(filter, &booking, None)
+    get(identity, booking_id)
         .await?
-        .ok_or_else(|| AppError::internal_server_error("Failed to update booking"))?;
+        .ok_or_else(|| AppError::not_found("Booking not found"))
+}
 
-    Ok(booking)
+/// Fee of a vehicle returned now, from the price by day of the vehicle
+async fn late_fee(booking: &Booking, returned_at: DateTime<Utc>) -> AppResult<Option<LateFee>> {
+    let (_, ends_at) = validator::booking::booked_window(booking)?;
+    if returned_at <= ends_at {
+        return Ok(None);
+    }
+    let vehicle: Vehicle = services::mongodb::get_one(doc! { "_id": booking.vehicle_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Vehicle not found"))?;
+
+    // Charged in the currency of the booking's ledger, the vehicle may be priced in another
+    let price_by_day = services::currency::rates()
+        .convert(vehicle.price_by_day, booking.currency())
+        .ok_or_else(|| {
+            AppError::internal_server_error(format!(
+                "No exchange rate from {} to {}",
+                vehicle.price_by_day.currency,
+                booking.currency()
+            ))
+        })?;
+
+    Ok(LateFee::compute(
+        ends_at,
+        returned_at,
+        price_by_day,
+        LateFee::multiplier(),
+    ))
+}
+
+/// Set the same status on many bookings, each one checked and updated as by `update`.
+/// A booking failing does not stop the others, the report tells which did.
+/// (Admin, CarManager, MotorbikeManager)
+pub async fn bulk_update(
+    identity: &Identity,
+    request: BulkUpdateBookingRequest,
+) -> AppResult<BulkUpdateReport> {
+    let mut items = Vec::with_capacity(request.booking_ids.len());
+    for booking_id in request.booking_ids {
+        let update_request = UpdateBookingRequest {
+            status: Some(request.status.clone()),
+            from_date: None,
+            to_date: None,
+        };
+        let item = match update(identity, &booking_id, update_request).await {
+            Ok(booking) => BulkUpdateItem {
+                booking_id,
+                status: Some(booking.status),
+                error: None,
+            },
+            Err(error) => BulkUpdateItem {
+                booking_id,
+                status: None,
+                error: Some(error.to_response()),
+            },
+        };
+        items.push(item);
+    }
+    Ok(BulkUpdateReport::new(items))
+}
+
+/// Permanently delete a cancelled or rejected booking, the booking is kept in the
+/// action audit log (Admin only)
+pub async fn delete(identity: &Identity, booking_id: &ObjectId) -> AppResult<()> {
+    let booking: Booking = services::mongodb::get_one(doc! { "_id": booking_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))?;
+    if !matches!(
+        booking.status,
+        BookingStatus::Cancelled(_) | BookingStatus::Rejected(_)
+    ) {
+        return Err(AppError::bad_request(
+            "Only CANCELLED or REJECTED bookings can be deleted",
+        ));
+    }
+
+    // Audited first: a deletion that cannot be recorded does not happen
+    let snapshot = bson::to_document(&booking)
+        .map_err(|e| AppError::internal_server_error(format!("Cannot serialize booking: {}", e)))?;
+    services::changeset::record(
+        identity,
+        ChangesetKind::BookingDeletion,
+        Booking::get_collection(),
+        vec![Change::Deleted {
+            resource_id: *booking_id,
+            document: snapshot.clone(),
+        }],
+    )
+    .await?;
+    let entry = ActionAuditEntry::new(
+        AuditAction::BookingDeleted,
+        identity,
+        booking_id.to_hex(),
+        Some(snapshot),
+    );
+    services::mongodb::insert_one(&entry, None).await?;
+
+    // The status is checked again in case the booking changed meanwhile
+    services::mongodb::delete_one(
+        Booking::get_collection(),
+        doc! { "_id": booking_id, "status": { "$in": ["CANCELLED", "REJECTED"] } },
+        None,
+    )
+    .await?;
+    services::reservation::release(*booking_id).await?;
+    log::warn!("Booking {} deleted by {}", booking_id, identity.user_id);
+    Ok(())
+}
+
+/// Cancellation fees applied to confirmed bookings cancelled by their customer
+pub async fn cancellation_policy() -> AppResult<CancellationPolicy> {
+    services::mongodb::booking::get_cancellation_policy().await
 }
 
 /// Get a single booking by ID
 pub async fn get(identity: &Identity, booking_id: &ObjectId) -> AppResult<Option<Booking>> {
     let filter = doc! { "_id": booking_id };
-    let booking: Option<Booking> = services::mongodb::get_one(filter, None).await?;
+    let mut booking: Option<Booking> = services::mongodb::get_one(filter, None).await?;
+    // Old bookings are read from the archive, callers cannot tell the difference
+    if booking.is_none() {
+        booking = services::archive::find(booking_id).await?;
+    }
 
     // Check permissions for viewing this booking
-    if let Some(ref booking) = booking {
+    if let Some(ref mut booking) = booking {
         validator::booking::check_booking_view_permission(identity, booking)?;
+        services::encryption::present_booking(booking, identity).await?;
     }
 
     Ok(booking)
 }
+
+/// Financial entries of a booking with their totals
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+pub async fn ledger(identity: &Identity, booking_id: &ObjectId) -> AppResult<BookingLedger> {
+    let booking = get(identity, booking_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))?;
+
+    let entries = services::ledger::entries(booking_id).await?;
+    Ok(BookingLedger {
+        booking_id: *booking_id,
+        totals: LedgerTotals::compute(booking.currency(), &entries),
+        entries,
+    })
+}
+
+/// Fraud risk assessment of a booking (Admin, CarManager, MotorbikeManager)
+pub async fn risk(booking_id: &ObjectId) -> AppResult<RiskAssessment> {
+    services::mongodb::get_one(doc! { "booking_id": booking_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Risk assessment not found"))
+}
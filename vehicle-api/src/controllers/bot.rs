@@ -0,0 +1,25 @@
+use std::net::IpAddr;
+
+use crate::authentication::bot;
+use crate::error::{AppError, AppResult};
+use crate::models::{BotClient, BotOverride};
+
+/// Anonymous clients flagged as bots on this instance, highest score first (Admin only)
+pub async fn list() -> AppResult<Vec<BotClient>> {
+    Ok(bot::list())
+}
+
+/// Allow or block a client whatever its score (Admin only)
+pub async fn set_override(ip: IpAddr, action: BotOverride) -> AppResult<BotClient> {
+    bot::set_override(ip, action)
+        .ok_or_else(|| AppError::internal_server_error("Failed to override bot detection"))
+}
+
+/// Forget a client and its override (Admin only)
+pub async fn clear(ip: IpAddr) -> AppResult<()> {
+    if bot::clear(ip) {
+        Ok(())
+    } else {
+        Err(AppError::not_found(format!("No tracked client {}", ip)))
+    }
+}
@@ -0,0 +1,66 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{Broadcast, BroadcastDelivery, BroadcastDeliveryQuery, CreateBroadcastRequest};
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+/// Broadcast an urgent message to the customers of the matching active and upcoming
+/// bookings, delivered in the background (Admin only)
+pub async fn create(identity: &Identity, request: CreateBroadcastRequest) -> AppResult<Broadcast> {
+    request.validate().map_err(AppError::bad_request)?;
+
+    let (broadcast, deliveries) =
+        services::broadcast::start(request, identity.user_id.clone()).await?;
+
+    // Notifications and deliveries belong to the admin's tenant
+    let tenant_id = identity.tenant_id.clone();
+    actix_web::rt::spawn(services::mongodb::tenant::scope(
+        tenant_id,
+        services::broadcast::run(broadcast.clone(), deliveries),
+    ));
+
+    Ok(broadcast)
+}
+
+/// Broadcasts, newest first (Admin only)
+pub async fn list(page: PageQuery) -> AppResult<Vec<Broadcast>> {
+    let mut options = FindOptions::builder()
+        .sort(doc! { "created_at": -1 })
+        .build();
+    page.apply(&mut options);
+    services::mongodb::collect_many(doc! {}, options).await
+}
+
+/// Broadcast with its delivery counts by channel (Admin only)
+pub async fn get(broadcast_id: &ObjectId) -> AppResult<Broadcast> {
+    services::mongodb::get_one(doc! { "_id": broadcast_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Broadcast not found"))
+}
+
+/// Delivery of a broadcast to each customer, optionally those with a channel in a
+/// status (Admin only)
+pub async fn deliveries(
+    broadcast_id: &ObjectId,
+    query: BroadcastDeliveryQuery,
+    page: PageQuery,
+) -> AppResult<Vec<BroadcastDelivery>> {
+    get(broadcast_id).await?;
+
+    let mut filter = doc! { "broadcast_id": broadcast_id };
+    if let Some(status) = query.status {
+        let status = status.to_string();
+        filter.insert(
+            "$or",
+            vec![doc! { "in_app": &status }, doc! { "email": &status }],
+        );
+    }
+    let mut options = FindOptions::builder()
+        .sort(doc! { "customer_id": 1 })
+        .build();
+    page.apply(&mut options);
+    services::mongodb::collect_many(filter, options).await
+}
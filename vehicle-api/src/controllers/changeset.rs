@@ -0,0 +1,26 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::Identity;
+use crate::error::AppResult;
+use crate::models::{Changeset, ChangesetRollback};
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+/// Change-sets that can still be rolled back, newest first (Admin only)
+pub async fn list(page: PageQuery) -> AppResult<Vec<Changeset>> {
+    let filter = doc! {
+        "rolled_back_at": null,
+        "expires_at": { "$gt": bson::DateTime::now() },
+    };
+    let mut options = FindOptions::builder()
+        .sort(doc! { "created_at": -1 })
+        .build();
+    page.apply(&mut options);
+    services::mongodb::collect_many(filter, options).await
+}
+
+/// Restore the documents of a change-set (Admin only)
+pub async fn rollback(identity: &Identity, changeset_id: ObjectId) -> AppResult<ChangesetRollback> {
+    services::changeset::rollback(identity, changeset_id).await
+}
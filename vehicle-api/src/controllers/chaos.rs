@@ -0,0 +1,38 @@
+use crate::chaos;
+use crate::error::{AppError, AppResult};
+use crate::models::ChaosConfig;
+
+/// Get the current chaos configuration (Admin only)
+pub async fn get() -> AppResult<ChaosConfig> {
+    check_available()?;
+
+    Ok(chaos::get_config())
+}
+
+/// Replace the chaos configuration (Admin only)
+pub async fn update(config: ChaosConfig) -> AppResult<ChaosConfig> {
+    check_available()?;
+    config.validate().map_err(AppError::bad_request)?;
+
+    chaos::set_config(config.clone());
+
+    Ok(config)
+}
+
+/// Disable every chaos rule (Admin only)
+pub async fn reset() -> AppResult<ChaosConfig> {
+    check_available()?;
+    chaos::set_config(ChaosConfig::disabled());
+
+    Ok(ChaosConfig::disabled())
+}
+
+fn check_available() -> AppResult<()> {
+    if chaos::is_available() {
+        Ok(())
+    } else {
+        Err(AppError::not_found(
+            "Chaos testing is disabled on this deployment",
+        ))
+    }
+}
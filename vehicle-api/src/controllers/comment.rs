@@ -0,0 +1,87 @@
+use bson::{doc, oid::ObjectId};
+use chrono::Utc;
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::Identity;
+use crate::controllers;
+use crate::error::{AppError, AppResult};
+use crate::models::{AddCommentRequest, BookingComment, UpdateCommentRequest};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+use crate::util::pagination::PageQuery;
+
+/// Comment on a booking the caller may see, the customer and the staff handling the
+/// vehicle are notified of the comments they can read
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+pub async fn add(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    request: AddCommentRequest,
+) -> AppResult<BookingComment> {
+    let booking = controllers::booking::get(identity, booking_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))?;
+
+    let mut comment = BookingComment::new(*booking_id, identity, request);
+    comment.id = Some(services::mongodb::insert_one(&comment, None).await?);
+
+    // Internal comments stay between the staff, nobody is notified of them
+    if !comment.internal {
+        let message = format!("New comment on your booking: {}", comment.body);
+        controllers::dispute::notify(identity, &booking, "New comment", message).await;
+    }
+
+    Ok(comment)
+}
+
+/// Thread of a booking, oldest first. Customers do not see internal comments
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+pub async fn list(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    page: PageQuery,
+) -> AppResult<Vec<BookingComment>> {
+    controllers::booking::get(identity, booking_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))?;
+
+    let mut options = FindOptions::builder()
+        .sort(doc! { "created_at": 1 })
+        .build();
+    page.apply(&mut options);
+    let filter = BookingComment::visible_filter(identity, booking_id);
+
+    services::mongodb::collect_many(filter, options).await
+}
+
+/// Mark a comment internal, or show it to the customer again
+/// (Admin, CarManager, MotorbikeManager)
+pub async fn update(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    comment_id: &ObjectId,
+    request: UpdateCommentRequest,
+) -> AppResult<BookingComment> {
+    controllers::booking::get(identity, booking_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))?;
+
+    let filter = doc! { "_id": comment_id, "booking_id": booking_id };
+    let result = services::mongodb::update_one(
+        BookingComment::get_collection(),
+        filter.clone(),
+        doc! { "$set": {
+            "internal": request.internal,
+            "updated_at": bson::DateTime::from_chrono(Utc::now()),
+        } },
+        None,
+    )
+    .await?;
+    if result.matched_count == 0 {
+        return Err(AppError::not_found("Comment not found"));
+    }
+
+    services::mongodb::get_one(filter, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Comment not found"))
+}
@@ -0,0 +1,88 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::Identity;
+use crate::controllers;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AddConditionPhotosRequest, AnnotatePhotoRequest, Booking, BookingStatus, ConditionDiff,
+    ConditionPhoto, PhotoAnnotation,
+};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// Booking the caller may see, the photos of other bookings stay hidden
+async fn visible_booking(identity: &Identity, booking_id: &ObjectId) -> AppResult<Booking> {
+    controllers::booking::get(identity, booking_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))
+}
+
+/// Record check-in or check-out photos of a confirmed booking
+/// (Admin, CarManager, MotorbikeManager)
+pub async fn add_photos(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    request: AddConditionPhotosRequest,
+) -> AppResult<Vec<ConditionPhoto>> {
+    let booking = visible_booking(identity, booking_id).await?;
+    if booking.status != BookingStatus::Confirmed {
+        return Err(AppError::bad_request(
+            "Condition photos can only be recorded for confirmed bookings",
+        ));
+    }
+
+    let mut photos = Vec::with_capacity(request.photos.len());
+    for photo in request.photos {
+        let mut photo =
+            ConditionPhoto::new(*booking_id, request.stage, photo, identity.user_id.clone());
+        photo.id = Some(services::mongodb::insert_one(&photo, None).await?);
+        photos.push(photo);
+    }
+    Ok(photos)
+}
+
+/// Add a manager's remark to a photo (Admin, CarManager, MotorbikeManager)
+pub async fn annotate(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    photo_id: &ObjectId,
+    request: AnnotatePhotoRequest,
+) -> AppResult<ConditionPhoto> {
+    visible_booking(identity, booking_id).await?;
+
+    let annotation = PhotoAnnotation::new(request, identity.user_id.clone());
+    let annotation = bson::to_bson(&annotation).map_err(|e| {
+        AppError::internal_server_error(format!("Cannot serialize annotation: {}", e))
+    })?;
+    // Pushed rather than replaced, managers annotating at the same time keep both remarks
+    let filter = doc! { "_id": photo_id, "booking_id": booking_id };
+    let result = services::mongodb::update_one(
+        ConditionPhoto::get_collection(),
+        filter.clone(),
+        doc! { "$push": { "annotations": annotation } },
+        None,
+    )
+    .await?;
+    if result.matched_count == 0 {
+        return Err(AppError::not_found("Photo not found"));
+    }
+
+    services::mongodb::get_one(filter, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Photo not found"))
+}
+
+/// Check-in and check-out photos of a booking paired by angle, with their annotations
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+pub async fn diff(identity: &Identity, booking_id: &ObjectId) -> AppResult<ConditionDiff> {
+    visible_booking(identity, booking_id).await?;
+
+    let options = FindOptions::builder()
+        .sort(doc! { "taken_at": 1, "_id": 1 })
+        .build();
+    let photos: Vec<ConditionPhoto> =
+        services::mongodb::collect_many(doc! { "booking_id": booking_id }, options).await?;
+
+    Ok(ConditionDiff::pair(*booking_id, photos))
+}
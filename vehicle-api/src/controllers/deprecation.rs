@@ -0,0 +1,34 @@
+use bson::doc;
+use mongodb::options::FindOptions;
+use strum::IntoEnumIterator;
+
+use crate::deprecation;
+use crate::error::AppResult;
+use crate::models::{DeprecatedFeature, DeprecationQuery, DeprecationReport, DeprecationUsage};
+use crate::services;
+
+/// Callers still relying on each deprecated feature, features nobody uses anymore
+/// included with no callers (Admin only)
+pub async fn report(query: DeprecationQuery) -> AppResult<Vec<DeprecationReport>> {
+    let mut filter = doc! {};
+    if let Some(feature) = query.feature {
+        filter.insert("feature", feature.to_string());
+    }
+    let options = FindOptions::builder()
+        .sort(doc! { "last_used_at": -1 })
+        .build();
+    let usages: Vec<DeprecationUsage> = services::mongodb::collect_many(filter, options).await?;
+
+    let reports = DeprecatedFeature::iter()
+        .filter(|feature| query.feature.is_none_or(|wanted| wanted == *feature))
+        .map(|feature| {
+            let callers = usages
+                .iter()
+                .filter(|usage| usage.feature == feature)
+                .cloned()
+                .collect();
+            DeprecationReport::new(feature, deprecation::sunsets().get(feature), callers)
+        })
+        .collect();
+    Ok(reports)
+}
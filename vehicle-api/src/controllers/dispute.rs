@@ -0,0 +1,348 @@
+use bson::{doc, oid::ObjectId};
+use chrono::Utc;
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::{Identity, Role};
+use crate::controllers;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AddDisputeEvidenceRequest, AddDisputeNoteRequest, Booking, Dispute, DisputeEvidence,
+    DisputeNote, DisputeOutcome, DisputeResolution, DisputeStats, DisputeStatus, LedgerTotals,
+    Money, Notification, OpenDisputeRequest, PaymentRequest, ResolveDisputeRequest, Vehicle,
+    VehicleType,
+};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+use crate::util::pagination::PageQuery;
+
+/// Booking the caller may see, the disputes of other bookings stay hidden
+async fn visible_booking(identity: &Identity, booking_id: &ObjectId) -> AppResult<Booking> {
+    controllers::booking::get(identity, booking_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))
+}
+
+/// Staff notes are internal, customers only see the evidence and the resolution
+fn present(identity: &Identity, mut dispute: Dispute) -> Dispute {
+    if identity.role == Role::Customer {
+        dispute.notes.clear();
+    }
+    dispute
+}
+
+async fn get_dispute(booking_id: &ObjectId, dispute_id: &ObjectId) -> AppResult<Dispute> {
+    services::mongodb::get_one(doc! { "_id": dispute_id, "booking_id": booking_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Dispute not found"))
+}
+
+/// Tell the customer and the staff handling the vehicle about a change of a booking
+/// (dispute, comment). The party making the change is not notified of it.
+pub(crate) async fn notify(identity: &Identity, booking: &Booking, subject: &str, message: String) {
+    let mut notifications = Vec::new();
+    if booking.customer_id != identity.user_id {
+        notifications.push(Notification::to_user(
+            &booking.customer_id,
+            subject,
+            &message,
+        ));
+    }
+    if identity.role == Role::Customer {
+        notifications.push(Notification::to_role(Role::Admin, subject, &message));
+        match services::mongodb::get_one::<Vehicle>(doc! { "_id": booking.vehicle_id }, None).await
+        {
+            Ok(Some(vehicle)) => notifications.push(Notification::to_role(
+                VehicleType::of(&vehicle).manager(),
+                subject,
+                &message,
+            )),
+            Ok(None) => {}
+            Err(error) => log::error!("Failed to load vehicle {}: {}", booking.vehicle_id, error),
+        }
+    }
+
+    if let Some(booking_id) = booking.id {
+        notifications = notifications
+            .into_iter()
+            .map(|notification| notification.about_booking(booking_id))
+            .collect();
+    }
+    services::notification::send(notifications).await;
+}
+
+/// Open a dispute on a booking, one open dispute at a time
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+pub async fn open(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    request: OpenDisputeRequest,
+) -> AppResult<Dispute> {
+    let booking = visible_booking(identity, booking_id).await?;
+
+    let filter = doc! {
+        "booking_id": booking_id,
+        "status": { "$ne": DisputeStatus::Resolved.to_string() },
+    };
+    if services::mongodb::count(Dispute::get_collection(), filter, None).await? > 0 {
+        return Err(AppError::bad_request(
+            "This booking already has a dispute in progress",
+        ));
+    }
+
+    let mut dispute = Dispute::new(
+        *booking_id,
+        booking.customer_id.clone(),
+        request,
+        identity.user_id.clone(),
+    );
+    dispute.id = Some(services::mongodb::insert_one(&dispute, None).await?);
+
+    let message = format!("A dispute was opened: {}", dispute.reason);
+    notify(identity, &booking, "Dispute opened", message).await;
+
+    Ok(present(identity, dispute))
+}
+
+/// Disputes of a booking, newest first
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+pub async fn list(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    page: PageQuery,
+) -> AppResult<Vec<Dispute>> {
+    visible_booking(identity, booking_id).await?;
+
+    let mut options = FindOptions::builder()
+        .sort(doc! { "opened_at": -1 })
+        .build();
+    page.apply(&mut options);
+    let disputes: Vec<Dispute> =
+        services::mongodb::collect_many(doc! { "booking_id": booking_id }, options).await?;
+
+    Ok(disputes
+        .into_iter()
+        .map(|dispute| present(identity, dispute))
+        .collect())
+}
+
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+pub async fn get(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    dispute_id: &ObjectId,
+) -> AppResult<Dispute> {
+    visible_booking(identity, booking_id).await?;
+    let dispute = get_dispute(booking_id, dispute_id).await?;
+    Ok(present(identity, dispute))
+}
+
+/// Attach a document or photo to an unresolved dispute, from either party
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+pub async fn add_evidence(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    dispute_id: &ObjectId,
+    request: AddDisputeEvidenceRequest,
+) -> AppResult<Dispute> {
+    let booking = visible_booking(identity, booking_id).await?;
+
+    let evidence = DisputeEvidence {
+        url: request.url,
+        description: request.description,
+        added_by: identity.user_id.clone(),
+        added_at: Utc::now(),
+    };
+    let evidence = bson::to_bson(&evidence).map_err(|e| {
+        AppError::internal_server_error(format!("Cannot serialize evidence: {}", e))
+    })?;
+    let dispute = push_unresolved(
+        booking_id,
+        dispute_id,
+        doc! { "$push": { "evidence": evidence } },
+    )
+    .await?;
+
+    let message = format!("New evidence was added to the dispute: {}", dispute.reason);
+    notify(identity, &booking, "Dispute evidence added", message).await;
+
+    Ok(present(identity, dispute))
+}
+
+/// Add an internal note, the dispute is then under review
+/// (Admin, CarManager, MotorbikeManager)
+pub async fn add_note(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    dispute_id: &ObjectId,
+    request: AddDisputeNoteRequest,
+) -> AppResult<Dispute> {
+    let booking = visible_booking(identity, booking_id).await?;
+    let was_open = get_dispute(booking_id, dispute_id).await?.status == DisputeStatus::Open;
+
+    let note = DisputeNote {
+        note: request.note,
+        added_by: identity.user_id.clone(),
+        added_at: Utc::now(),
+    };
+    let note = bson::to_bson(&note)
+        .map_err(|e| AppError::internal_server_error(format!("Cannot serialize note: {}", e)))?;
+    let dispute = push_unresolved(
+        booking_id,
+        dispute_id,
+        doc! {
+            "$push": { "notes": note },
+            "$set": { "status": DisputeStatus::UnderReview.to_string() },
+        },
+    )
+    .await?;
+
+    // Notes stay internal, the customer only learns the dispute is being looked at
+    if was_open {
+        let message = format!("Your dispute is under review: {}", dispute.reason);
+        notify(identity, &booking, "Dispute under review", message).await;
+    }
+
+    Ok(dispute)
+}
+
+/// Apply an update to an unresolved dispute, returns the updated dispute
+async fn push_unresolved(
+    booking_id: &ObjectId,
+    dispute_id: &ObjectId,
+    mut update: bson::Document,
+) -> AppResult<Dispute> {
+    let filter = doc! {
+        "_id": dispute_id,
+        "booking_id": booking_id,
+        "status": { "$ne": DisputeStatus::Resolved.to_string() },
+    };
+    let now = bson::DateTime::from_chrono(Utc::now());
+    match update.get_document_mut("$set") {
+        Ok(set) => {
+            set.insert("updated_at", now);
+        }
+        Err(_) => {
+            update.insert("$set", doc! { "updated_at": now });
+        }
+    }
+
+    let result =
+        services::mongodb::update_one(Dispute::get_collection(), filter, update, None).await?;
+    if result.matched_count == 0 {
+        let dispute = get_dispute(booking_id, dispute_id).await?;
+        if dispute.is_resolved() {
+            return Err(AppError::bad_request("This dispute is already resolved"));
+        }
+    }
+    get_dispute(booking_id, dispute_id).await
+}
+
+/// Close a dispute, refunding or charging the customer through the payment provider
+/// (Admin, CarManager, MotorbikeManager)
+pub async fn resolve(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    dispute_id: &ObjectId,
+    request: ResolveDisputeRequest,
+) -> AppResult<Dispute> {
+    let booking = visible_booking(identity, booking_id).await?;
+    let mut dispute = get_dispute(booking_id, dispute_id).await?;
+    if dispute.is_resolved() {
+        return Err(AppError::bad_request("This dispute is already resolved"));
+    }
+
+    let amount = match (request.outcome.payment_kind(), request.amount) {
+        (Some(_), None) => {
+            return Err(AppError::bad_request(format!(
+                "amount is required for a {} outcome",
+                request.outcome
+            )))
+        }
+        (None, Some(_)) => {
+            return Err(AppError::bad_request(
+                "amount must be omitted when no action is taken",
+            ))
+        }
+        (_, amount) => amount.map(|amount| Money::new(amount, booking.currency())),
+    };
+    if let (DisputeOutcome::Refund, Some(amount)) = (request.outcome, amount) {
+        let totals = LedgerTotals::compute(
+            booking.currency(),
+            &services::ledger::entries(booking_id).await?,
+        );
+        if amount.amount > totals.balance.amount {
+            return Err(AppError::bad_request(format!(
+                "A refund cannot exceed the {} charged for the booking",
+                totals.balance
+            )));
+        }
+    }
+
+    // Claim the dispute first so two managers cannot both move money
+    let filter = doc! {
+        "_id": dispute_id,
+        "booking_id": booking_id,
+        "status": { "$ne": DisputeStatus::Resolved.to_string() },
+    };
+    let now = Utc::now();
+    let claimed = services::mongodb::update_one(
+        Dispute::get_collection(),
+        filter,
+        doc! { "$set": {
+            "status": DisputeStatus::Resolved.to_string(),
+            "updated_at": bson::DateTime::from_chrono(now),
+        } },
+        None,
+    )
+    .await?;
+    if claimed.modified_count == 0 {
+        return Err(AppError::bad_request("This dispute is already resolved"));
+    }
+
+    let payment = match (request.outcome.payment_kind(), amount) {
+        (Some(kind), Some(amount)) => Some(
+            services::payments::execute(PaymentRequest {
+                booking_id: *booking_id,
+                customer_id: booking.customer_id.clone(),
+                kind,
+                amount,
+                reason: format!("Dispute {}: {}", dispute_id, dispute.reason),
+                requested_by: identity.user_id.clone(),
+                idempotency_key: None,
+            })
+            .await?,
+        ),
+        _ => None,
+    };
+
+    dispute.status = DisputeStatus::Resolved;
+    dispute.updated_at = now;
+    dispute.resolution = Some(DisputeResolution {
+        outcome: request.outcome,
+        amount,
+        payment_id: payment.and_then(|payment| payment.id),
+        comment: request.comment,
+        resolved_by: identity.user_id.clone(),
+        resolved_at: now,
+    });
+    services::mongodb::find_one_and_replace(doc! { "_id": dispute_id }, &dispute, None)
+        .await?
+        .ok_or_else(|| AppError::internal_server_error("Failed to resolve dispute"))?;
+
+    let message = match amount {
+        Some(amount) => format!(
+            "Your dispute was resolved ({} of {}): {}",
+            request.outcome, amount, dispute.reason
+        ),
+        None => format!("Your dispute was resolved: {}", dispute.reason),
+    };
+    notify(identity, &booking, "Dispute resolved", message).await;
+
+    Ok(dispute)
+}
+
+/// Disputes by status and money refunded or charged through them (Admin only)
+pub async fn stats() -> AppResult<DisputeStats> {
+    let disputes: Vec<Dispute> = services::mongodb::collect_many(doc! {}, None).await?;
+    Ok(DisputeStats::compute(&disputes))
+}
@@ -0,0 +1,44 @@
+use bson::doc;
+use futures::TryStreamExt;
+use mongodb::options::{FindOneAndReplaceOptions, FindOptions};
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::experiment;
+use crate::models::{Experiment, ExperimentExposure, ExperimentRequest, VariantExposures};
+use crate::services;
+
+/// Every experiment, running or not (Admin only)
+pub async fn list() -> AppResult<Vec<Experiment>> {
+    let options = FindOptions::builder().sort(doc! { "_id": 1 }).build();
+    services::mongodb::collect_many(doc! {}, options).await
+}
+
+/// Create or replace an experiment, applied at once on this instance (Admin only)
+pub async fn upsert(
+    identity: &Identity,
+    key: String,
+    request: ExperimentRequest,
+) -> AppResult<Experiment> {
+    ExperimentRequest::validate_key(&key).map_err(AppError::bad_request)?;
+
+    let experiment = Experiment::new(key, request, identity.user_id.clone());
+    let options = FindOneAndReplaceOptions::builder().upsert(true).build();
+    services::mongodb::find_one_and_replace(doc! { "_id": &experiment.key }, &experiment, options)
+        .await?;
+    experiment::invalidate(experiment.clone());
+
+    Ok(experiment)
+}
+
+/// Callers exposed to each variant of an experiment (Admin only)
+pub async fn exposures(key: &str) -> AppResult<Vec<VariantExposures>> {
+    let exposures = services::mongodb::aggregate::<ExperimentExposure, VariantExposures>(
+        VariantExposures::pipeline(key),
+    )
+    .await?
+    .try_collect::<Vec<_>>()
+    .await?;
+
+    Ok(exposures)
+}
@@ -0,0 +1,273 @@
+use bson::{doc, oid::ObjectId};
+use chrono::Utc;
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::{Identity, Role};
+use crate::controllers;
+use crate::domain;
+use crate::domain::events::BookingDatesChanged;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Booking, BookingExtension, Decimal, ExtendBookingRequest, ExtensionStatus, LedgerEntryKind,
+    Money, NewLedgerEntry, Notification, RejectExtensionRequest, Vehicle, VehicleType,
+};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+use crate::validator;
+
+async fn get_booking(booking_id: &ObjectId) -> AppResult<Booking> {
+    services::mongodb::get_one(doc! { "_id": booking_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))
+}
+
+async fn get_pending(
+    booking_id: &ObjectId,
+    extension_id: &ObjectId,
+) -> AppResult<BookingExtension> {
+    let filter = doc! { "_id": extension_id, "booking_id": booking_id };
+    let extension: BookingExtension = services::mongodb::get_one(filter, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Extension not found"))?;
+    if extension.status != ExtensionStatus::Pending {
+        return Err(AppError::conflict(format!(
+            "Extension already {}",
+            extension.status
+        )));
+    }
+    Ok(extension)
+}
+
+/// Move a pending extension to its decision, fails when another manager decided first
+async fn decide(
+    identity: &Identity,
+    extension: &mut BookingExtension,
+    status: ExtensionStatus,
+    reason: Option<String>,
+) -> AppResult<()> {
+    extension.status = status;
+    extension.decided_by = Some(identity.user_id.clone());
+    extension.decided_at = Some(Utc::now());
+    extension.reason = reason;
+
+    let filter = doc! { "_id": extension.id, "status": ExtensionStatus::Pending.to_string() };
+    services::mongodb::find_one_and_replace(filter, &*extension, None)
+        .await?
+        .ok_or_else(|| AppError::conflict("Extension decided meanwhile"))?;
+    Ok(())
+}
+
+/// Ask to keep the vehicle of a confirmed booking until a later day. The extra days are
+/// checked and held like a new booking, then wait for a manager.
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+pub async fn request(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    request: ExtendBookingRequest,
+) -> AppResult<BookingExtension> {
+    let booking = get_booking(booking_id).await?;
+    validator::booking::check_booking_update_permission(identity, &booking)?;
+    BookingExtension::validate_request(&booking, request.to_date).map_err(AppError::bad_request)?;
+
+    let filter = doc! {
+        "booking_id": booking_id,
+        "status": ExtensionStatus::Pending.to_string(),
+    };
+    if services::mongodb::count(BookingExtension::get_collection(), filter, None).await? > 0 {
+        return Err(AppError::conflict(
+            "This booking already has an extension waiting for a decision",
+        ));
+    }
+
+    // Priced as the whole booking over the new dates, blackout holidays checked
+    let mut extended = booking.clone();
+    services::booking::change_dates(&mut extended, booking.from_date, request.to_date).await?;
+    let previous_price = booking
+        .total_price
+        .map(|price| price.amount)
+        .unwrap_or_default();
+    let extra_price = extended
+        .total_price
+        .map(|total| Money::new(total.amount - previous_price, total.currency).round());
+
+    let mut extension = BookingExtension::new(
+        *booking_id,
+        &booking,
+        request.to_date,
+        extra_price,
+        identity.user_id.clone(),
+    );
+    validator::booking::validate_date_change(&booking, extension.first_day(), request.to_date)
+        .await?;
+    services::reservation::claim(
+        *booking_id,
+        booking.vehicle_id,
+        extension.first_day(),
+        request.to_date,
+    )
+    .await?;
+    extension.id = Some(services::mongodb::insert_one(&extension, None).await?);
+
+    if identity.role == Role::Customer {
+        notify_managers(&booking, *booking_id, &extension).await;
+    }
+    Ok(extension)
+}
+
+/// Extensions of a booking, newest first
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+pub async fn list(identity: &Identity, booking_id: &ObjectId) -> AppResult<Vec<BookingExtension>> {
+    controllers::booking::get(identity, booking_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))?;
+
+    let options = FindOptions::builder()
+        .sort(doc! { "requested_at": -1 })
+        .build();
+    services::mongodb::collect_many(doc! { "booking_id": booking_id }, options).await
+}
+
+/// Move the booking to the new to_date, priced again, and charge the difference
+/// (Admin, CarManager, MotorbikeManager)
+pub async fn approve(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    extension_id: &ObjectId,
+) -> AppResult<BookingExtension> {
+    let mut extension = get_pending(booking_id, extension_id).await?;
+    let mut booking = get_booking(booking_id).await?;
+    BookingExtension::validate_request(&booking, extension.to_date)
+        .map_err(AppError::bad_request)?;
+    if booking.to_date != extension.current_to_date {
+        return Err(AppError::conflict(
+            "Booking dates changed since the extension was requested",
+        ));
+    }
+
+    let previous_price = booking
+        .total_price
+        .map(|price| price.amount)
+        .unwrap_or_default();
+    let from_date = booking.from_date;
+    services::booking::change_dates(&mut booking, from_date, extension.to_date).await?;
+    let extra_price = booking
+        .total_price
+        .map(|total| Money::new(total.amount - previous_price, total.currency).round());
+    extension.extra_price = extra_price;
+    decide(identity, &mut extension, ExtensionStatus::Approved, None).await?;
+
+    let filter = doc! {
+        "_id": booking_id,
+        "status": "CONFIRMED",
+        "to_date": extension.current_to_date.to_string(),
+    };
+    let replaced = services::mongodb::find_one_and_replace(filter, &booking, None).await?;
+    if replaced.is_none() {
+        // Back to pending, the manager can decide again once the booking is settled
+        services::mongodb::update_one(
+            BookingExtension::get_collection(),
+            doc! { "_id": extension_id },
+            doc! { "$set": {
+                "status": ExtensionStatus::Pending.to_string(),
+                "decided_by": null,
+                "decided_at": null,
+            } },
+            None,
+        )
+        .await?;
+        return Err(AppError::conflict("Booking changed meanwhile, retry later"));
+    }
+    domain::events::publish(BookingDatesChanged::of(
+        &booking,
+        from_date,
+        extension.current_to_date,
+    ))
+    .await;
+
+    if let Some(extra_price) = extra_price.filter(|price| price.amount > Decimal::ZERO) {
+        services::ledger::append(NewLedgerEntry {
+            booking_id: *booking_id,
+            customer_id: booking.customer_id.clone(),
+            kind: LedgerEntryKind::Charge,
+            amount: extra_price,
+            description: format!(
+                "Extension from {} to {}",
+                extension.current_to_date, extension.to_date
+            ),
+            payment_id: None,
+            recorded_by: identity.user_id.clone(),
+        })
+        .await?;
+    }
+
+    let notification = Notification::to_user(
+        &booking.customer_id,
+        "Booking extended",
+        format!(
+            "Your booking now ends on {} instead of {}.",
+            extension.to_date, extension.current_to_date
+        ),
+    )
+    .about_booking(*booking_id);
+    services::notification::send(vec![notification]).await;
+
+    Ok(extension)
+}
+
+/// Refuse an extension, its days are free again (Admin, CarManager, MotorbikeManager)
+pub async fn reject(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    extension_id: &ObjectId,
+    request: RejectExtensionRequest,
+) -> AppResult<BookingExtension> {
+    let mut extension = get_pending(booking_id, extension_id).await?;
+    let booking = get_booking(booking_id).await?;
+    decide(
+        identity,
+        &mut extension,
+        ExtensionStatus::Rejected,
+        Some(request.reason.clone()),
+    )
+    .await?;
+
+    services::reservation::release_outside(*booking_id, booking.from_date, booking.to_date).await?;
+
+    let notification = Notification::to_user(
+        &booking.customer_id,
+        "Extension refused",
+        format!(
+            "Your booking still ends on {}: {}",
+            booking.to_date, request.reason
+        ),
+    )
+    .about_booking(*booking_id);
+    services::notification::send(vec![notification]).await;
+
+    Ok(extension)
+}
+
+/// Tell the staff handling the vehicle that an extension waits for them
+async fn notify_managers(booking: &Booking, booking_id: ObjectId, extension: &BookingExtension) {
+    let subject = "Extension requested";
+    let message = format!(
+        "The customer asks to keep the vehicle until {} instead of {}.",
+        extension.to_date, extension.current_to_date
+    );
+    let mut notifications = vec![Notification::to_role(Role::Admin, subject, &message)];
+    match services::mongodb::get_one::<Vehicle>(doc! { "_id": booking.vehicle_id }, None).await {
+        Ok(Some(vehicle)) => notifications.push(Notification::to_role(
+            VehicleType::of(&vehicle).manager(),
+            subject,
+            &message,
+        )),
+        Ok(None) => {}
+        Err(error) => log::error!("Failed to load vehicle {}: {}", booking.vehicle_id, error),
+    }
+
+    let notifications = notifications
+        .into_iter()
+        .map(|notification| notification.about_booking(booking_id))
+        .collect();
+    services::notification::send(notifications).await;
+}
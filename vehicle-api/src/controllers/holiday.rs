@@ -0,0 +1,52 @@
+use bson::doc;
+use chrono::{Datelike, NaiveDate, Utc};
+use mongodb::options::{FindOneAndReplaceOptions, ReturnDocument};
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{Holiday, HolidayOverride, HolidayQuery, UpsertHolidayRequest};
+use crate::services;
+
+/// Loaded holiday calendar of a country for a year, overrides applied (Admin only)
+pub async fn list(query: HolidayQuery) -> AppResult<Vec<Holiday>> {
+    let country = query
+        .country
+        .map(|country| country.to_uppercase())
+        .unwrap_or_else(services::holidays::default_country);
+    let year = query.year.unwrap_or_else(|| Utc::now().year());
+
+    let from =
+        NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(|| AppError::bad_request("Invalid year"))?;
+    let to = NaiveDate::from_ymd_opt(year, 12, 31)
+        .ok_or_else(|| AppError::bad_request("Invalid year"))?;
+
+    services::holidays::get_calendar(&country, from, to).await
+}
+
+/// Add, change or remove (`active: false`) a holiday of the calendar (Admin only)
+pub async fn upsert(
+    identity: &Identity,
+    country: &str,
+    date: NaiveDate,
+    request: UpsertHolidayRequest,
+) -> AppResult<HolidayOverride> {
+    let country = country.to_uppercase();
+    let filter = doc! { "country": &country, "date": date.to_string() };
+    let holiday_override = HolidayOverride::new(country, date, request, identity.user_id.clone());
+
+    let options = FindOneAndReplaceOptions::builder()
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .build();
+
+    services::mongodb::find_one_and_replace(filter, &holiday_override, options)
+        .await?
+        .ok_or_else(|| AppError::internal_server_error("Failed to save holiday override"))
+}
+
+/// Remove an override, the day falls back to the imported calendar (Admin only)
+pub async fn delete_override(country: &str, date: NaiveDate) -> AppResult<()> {
+    let filter = doc! { "country": country.to_uppercase(), "date": date.to_string() };
+
+    services::mongodb::delete_one("holiday_overrides", filter, None).await
+}
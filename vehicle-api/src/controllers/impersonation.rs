@@ -0,0 +1,12 @@
+use crate::authentication::identity::Identity;
+use crate::authentication::impersonation;
+use crate::error::AppResult;
+use crate::models::ImpersonationTokenResponse;
+
+/// Issue a token acting as a customer (Admin only)
+pub async fn impersonate(
+    identity: &Identity,
+    user_id: &str,
+) -> AppResult<ImpersonationTokenResponse> {
+    impersonation::issue_token(identity, user_id).await
+}
@@ -0,0 +1,36 @@
+use bson::doc;
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::IntegrityReport;
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+/// Reservation days are shared by every tenant, tenant admins cannot check them
+fn check_platform_admin(identity: &Identity) -> AppResult<()> {
+    match identity.tenant_id {
+        Some(_) => Err(AppError::forbidden(
+            "Integrity checks are only available to admins outside a tenant",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Cross-check bookings and reservation days now, repairing when asked (Admin only)
+pub async fn check(identity: &Identity, repair: bool) -> AppResult<IntegrityReport> {
+    check_platform_admin(identity)?;
+
+    services::integrity::check(repair, Some(identity.user_id.clone())).await
+}
+
+/// Reports of the checks, newest first (Admin only)
+pub async fn list(identity: &Identity, page: PageQuery) -> AppResult<Vec<IntegrityReport>> {
+    check_platform_admin(identity)?;
+
+    let mut options = FindOptions::builder()
+        .sort(doc! { "started_at": -1 })
+        .build();
+    page.apply(&mut options);
+    services::mongodb::collect_many(doc! {}, options).await
+}
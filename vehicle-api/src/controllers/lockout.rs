@@ -0,0 +1,19 @@
+use std::net::IpAddr;
+
+use crate::authentication::lockout;
+use crate::error::{AppError, AppResult};
+use crate::models::IpLockout;
+
+/// Addresses with invalid API key attempts on this instance, locked out ones first (Admin only)
+pub async fn list() -> AppResult<Vec<IpLockout>> {
+    Ok(lockout::list())
+}
+
+/// Lift the lockout of an address and forget its failures (Admin only)
+pub async fn clear(ip: IpAddr) -> AppResult<()> {
+    if lockout::clear(ip) {
+        Ok(())
+    } else {
+        Err(AppError::not_found(format!("No lockout for {}", ip)))
+    }
+}
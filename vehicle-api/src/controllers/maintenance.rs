@@ -0,0 +1,108 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::options::{FindOneAndReplaceOptions, FindOptions, ReturnDocument};
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    exclude_deleted, CreateMaintenanceRecordRequest, MaintenanceRecord,
+    UpdateMaintenanceRecordRequest, Vehicle,
+};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+use crate::util::pagination::PageQuery;
+use crate::validator;
+
+/// Vehicle of the records, which the caller must manage
+async fn managed_vehicle(identity: &Identity, vehicle_id: &ObjectId) -> AppResult<Vehicle> {
+    let mut filter = doc! { "_id": vehicle_id };
+    exclude_deleted(&mut filter);
+    let vehicle: Vehicle = services::mongodb::get_one(filter, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Vehicle not found"))?;
+
+    validator::vehicle::check_vehicle_type_permission(identity, &vehicle)?;
+    Ok(vehicle)
+}
+
+/// Record a service of a vehicle (Admin, CarManager, MotorbikeManager)
+pub async fn create(
+    identity: &Identity,
+    vehicle_id: &ObjectId,
+    request: CreateMaintenanceRecordRequest,
+) -> AppResult<MaintenanceRecord> {
+    managed_vehicle(identity, vehicle_id).await?;
+
+    let mut record = MaintenanceRecord::new(*vehicle_id, identity, request);
+    record.check_next_service().map_err(AppError::bad_request)?;
+    record.id = Some(services::mongodb::insert_one(&record, None).await?);
+
+    Ok(record)
+}
+
+/// Services of a vehicle, latest first (Admin, CarManager, MotorbikeManager)
+pub async fn list(
+    identity: &Identity,
+    vehicle_id: &ObjectId,
+    page: PageQuery,
+) -> AppResult<Vec<MaintenanceRecord>> {
+    managed_vehicle(identity, vehicle_id).await?;
+
+    let mut options = FindOptions::builder()
+        .sort(doc! { "service_date": -1, "mileage_km": -1 })
+        .build();
+    page.apply(&mut options);
+
+    services::mongodb::collect_many(doc! { "vehicle_id": vehicle_id }, options).await
+}
+
+/// One service of a vehicle (Admin, CarManager, MotorbikeManager)
+pub async fn get(
+    identity: &Identity,
+    vehicle_id: &ObjectId,
+    record_id: &ObjectId,
+) -> AppResult<MaintenanceRecord> {
+    managed_vehicle(identity, vehicle_id).await?;
+
+    services::mongodb::get_one(doc! { "_id": record_id, "vehicle_id": vehicle_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Maintenance record not found"))
+}
+
+/// Correct a service, e.g. its cost once invoiced (Admin, CarManager, MotorbikeManager)
+pub async fn update(
+    identity: &Identity,
+    vehicle_id: &ObjectId,
+    record_id: &ObjectId,
+    request: UpdateMaintenanceRecordRequest,
+) -> AppResult<MaintenanceRecord> {
+    let mut record = get(identity, vehicle_id, record_id).await?;
+    record.apply(request);
+    record.check_next_service().map_err(AppError::bad_request)?;
+
+    let options = FindOneAndReplaceOptions::builder()
+        .return_document(ReturnDocument::After)
+        .build();
+    services::mongodb::find_one_and_replace(
+        doc! { "_id": record_id, "vehicle_id": vehicle_id },
+        &record,
+        options,
+    )
+    .await?
+    .ok_or_else(|| AppError::not_found("Maintenance record not found"))
+}
+
+/// Remove a service recorded by mistake (Admin, CarManager, MotorbikeManager)
+pub async fn delete(
+    identity: &Identity,
+    vehicle_id: &ObjectId,
+    record_id: &ObjectId,
+) -> AppResult<()> {
+    get(identity, vehicle_id, record_id).await?;
+
+    services::mongodb::delete_one(
+        MaintenanceRecord::get_collection(),
+        doc! { "_id": record_id, "vehicle_id": vehicle_id },
+        None,
+    )
+    .await
+}
@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use strum::IntoEnumIterator;
+
+use crate::authentication;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    ApiDiscovery, AuthMethod, AuthMethodInfo, DiscoveryFeatures, EventSchema, SchemaName,
+};
+use crate::{chaos, priority, services, util};
+
+/// What this deployment offers: version, enabled features, how to authenticate and
+/// where to go next (All, unauthenticated)
+pub async fn discovery() -> AppResult<ApiDiscovery> {
+    let oidc_login = authentication::oidc::is_configured();
+
+    let mut auth_methods = vec![AuthMethodInfo {
+        method: AuthMethod::ApiKey,
+        usage: "X-API-Key header",
+    }];
+    auth_methods.push(AuthMethodInfo {
+        method: AuthMethod::Session,
+        usage: if oidc_login {
+            "Authorization: Bearer <token> from /auth/login or /auth/service-token"
+        } else {
+            "Authorization: Bearer <token> from /auth/service-token"
+        },
+    });
+    if authentication::request_signing::is_enabled() {
+        auth_methods.push(AuthMethodInfo {
+            method: AuthMethod::Signature,
+            usage: "X-Key-Id, X-Timestamp and X-Signature headers",
+        });
+    }
+    if authentication::mtls::is_enabled() {
+        auth_methods.push(AuthMethodInfo {
+            method: AuthMethod::Certificate,
+            usage: "TLS client certificate",
+        });
+    }
+
+    let mut links = BTreeMap::from([
+        ("self", "/".to_string()),
+        ("schemas", "/meta/schemas".to_string()),
+        ("events", "/meta/events".to_string()),
+        ("webhook_signing_keys", "/webhooks/signing-keys".to_string()),
+        ("health", "/health/mongodb".to_string()),
+        ("readiness", "/health/ready".to_string()),
+        ("identity", "/protected/identity".to_string()),
+    ]);
+    if oidc_login {
+        links.insert("login", "/auth/login".to_string());
+    }
+    if let Ok(docs_url) = std::env::var("DOCS_URL") {
+        links.insert("docs", docs_url);
+    }
+
+    Ok(ApiDiscovery {
+        name: "Vehicle Booking API",
+        version: env!("CARGO_PKG_VERSION"),
+        features: DiscoveryFeatures {
+            webhooks: true,
+            email_notifications: services::email::is_configured(),
+            oidc_login,
+            priority_lanes: priority::is_enabled(),
+            chaos_testing: chaos::is_available(),
+            response_envelope_by_default: util::envelope::is_default(),
+            read_only: util::read_only::is_enabled(),
+        },
+        auth_methods,
+        links,
+    })
+}
+
+/// JSON Schemas of every webhook/broker event type (All, unauthenticated)
+pub async fn events() -> AppResult<Vec<EventSchema>> {
+    Ok(services::webhook::schema::event_schemas())
+}
+
+/// Names of the models with a published JSON Schema (All, unauthenticated)
+pub async fn schemas() -> AppResult<Vec<SchemaName>> {
+    Ok(SchemaName::iter().collect())
+}
+
+/// JSON Schema of a single request/response model (All, unauthenticated)
+pub async fn schema(name: &str) -> AppResult<serde_json::Value> {
+    let schema_name = SchemaName::from_str(name)
+        .map_err(|_| AppError::not_found(format!("Unknown schema: {}", name)))?;
+
+    Ok(schema_name.schema())
+}
@@ -0,0 +1,7 @@
+use crate::error::AppResult;
+use crate::services;
+
+/// Registered metrics in the Prometheus text format
+pub async fn render() -> AppResult<String> {
+    services::metrics::render()
+}
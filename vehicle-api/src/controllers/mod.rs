@@ -1,2 +1,40 @@
+pub mod accounting;
+pub mod anomaly;
+pub mod api_key;
+pub mod approval;
+pub mod audit;
+pub mod auth;
+pub mod backup;
 pub mod booking;
-pub mod vehicle;
\ No newline at end of file
+pub mod bot;
+pub mod broadcast;
+pub mod changeset;
+pub mod chaos;
+pub mod comment;
+pub mod condition;
+pub mod deprecation;
+pub mod dispute;
+pub mod experiment;
+pub mod extension;
+pub mod holiday;
+pub mod impersonation;
+pub mod integrity;
+pub mod lockout;
+pub mod maintenance;
+pub mod meta;
+pub mod metrics;
+pub mod notification;
+pub mod office_hours;
+pub mod payment;
+pub mod pii;
+pub mod priority;
+pub mod quarantine;
+pub mod recording;
+pub mod report;
+pub mod schema;
+pub mod service_account;
+pub mod station;
+pub mod suspension;
+pub mod vehicle;
+pub mod webhook;
+pub mod webhook_endpoint;
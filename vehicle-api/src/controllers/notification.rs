@@ -0,0 +1,70 @@
+use bson::doc;
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::{Identity, Role};
+use crate::error::{AppError, AppResult};
+use crate::models::{Customer, EmailPreferences, Notification, UnsubscribeQuery};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+use crate::util::pagination::PageQuery;
+
+/// Notifications addressed to the caller or its role, newest first
+pub async fn list(identity: &Identity, page: PageQuery) -> AppResult<Vec<Notification>> {
+    let mut options = FindOptions::builder()
+        .sort(doc! { "created_at": -1 })
+        .build();
+    page.apply(&mut options);
+    services::mongodb::collect_many(Notification::inbox_filter(identity), options).await
+}
+
+/// Optional emails the caller receives (Customer)
+pub async fn preferences(identity: &Identity) -> AppResult<EmailPreferences> {
+    if identity.role != Role::Customer {
+        return Err(AppError::forbidden("Only customers have email preferences"));
+    }
+    let customer: Option<Customer> =
+        services::mongodb::get_one(doc! { "user_id": &identity.user_id }, None).await?;
+    Ok(customer
+        .map(|customer| customer.email_preferences)
+        .unwrap_or_default())
+}
+
+/// Choose the optional emails the caller receives (Customer)
+pub async fn update_preferences(
+    identity: &Identity,
+    preferences: EmailPreferences,
+) -> AppResult<EmailPreferences> {
+    if identity.role != Role::Customer {
+        return Err(AppError::forbidden("Only customers have email preferences"));
+    }
+    save_preferences(&identity.user_id, &preferences).await?;
+    Ok(preferences)
+}
+
+/// Turn the weekly digest off from the link of a digest email (unauthenticated)
+pub async fn unsubscribe(query: UnsubscribeQuery) -> AppResult<()> {
+    if !services::email::unsubscribe::verify(&query.user_id, &query.token) {
+        return Err(AppError::forbidden("Invalid unsubscribe link"));
+    }
+    let preferences = EmailPreferences {
+        weekly_digest: false,
+    };
+    save_preferences(&query.user_id, &preferences).await
+}
+
+async fn save_preferences(user_id: &str, preferences: &EmailPreferences) -> AppResult<()> {
+    let preferences = bson::to_bson(preferences).map_err(|e| {
+        AppError::internal_server_error(format!("Cannot serialize preferences: {}", e))
+    })?;
+    let result = services::mongodb::update_one(
+        Customer::get_collection(),
+        doc! { "user_id": user_id },
+        doc! { "$set": { "email_preferences": preferences } },
+        None,
+    )
+    .await?;
+    if result.matched_count == 0 {
+        return Err(AppError::not_found("Customer profile not found"));
+    }
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use bson::doc;
+use chrono::Utc;
+use mongodb::options::{FindOneAndReplaceOptions, FindOptions, ReturnDocument};
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{OfficeHours, SetOfficeHoursRequest};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+use crate::util;
+
+/// Office hours the identity's tenant uses, its own and the ones set for every tenant
+/// (Admin only)
+pub async fn list(identity: &Identity) -> AppResult<Vec<OfficeHours>> {
+    let filter = services::office_hours::tenant_filter(identity.tenant_id.as_deref());
+    let options = FindOptions::builder()
+        .sort(doc! { "location": 1, "tenant_id": 1 })
+        .build();
+
+    services::mongodb::collect_many(filter, options).await
+}
+
+/// Set the office hours of a location, for the identity's tenant or, for deployment wide
+/// admins, every tenant without hours of its own (Admin only)
+pub async fn set(
+    identity: &Identity,
+    location: &str,
+    request: SetOfficeHoursRequest,
+) -> AppResult<OfficeHours> {
+    util::timezone::parse_timezone(location).map_err(AppError::bad_request)?;
+    request.validate().map_err(AppError::bad_request)?;
+
+    let filter = doc! { "location": location, "tenant_id": &identity.tenant_id };
+    let hours = OfficeHours {
+        id: None,
+        location: location.to_string(),
+        tenant_id: identity.tenant_id.clone(),
+        opening_hours: request.opening_hours,
+        outside_hours: request.outside_hours,
+        updated_by: identity.user_id.clone(),
+        updated_at: Utc::now(),
+    };
+    let options = FindOneAndReplaceOptions::builder()
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .build();
+
+    services::mongodb::find_one_and_replace(filter, &hours, options)
+        .await?
+        .ok_or_else(|| AppError::internal_server_error("Failed to save office hours"))
+}
+
+/// Drop the office hours of a location, its branches count as always staffed again
+/// unless hours are set for every tenant (Admin only)
+pub async fn delete(identity: &Identity, location: &str) -> AppResult<()> {
+    let filter = doc! { "location": location, "tenant_id": &identity.tenant_id };
+
+    services::mongodb::delete_one(OfficeHours::get_collection(), filter, None).await
+}
@@ -0,0 +1,238 @@
+use bson::{doc, oid::ObjectId};
+use chrono::Utc;
+
+use crate::authentication::identity::Identity;
+use crate::controllers;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Booking, BookingPaymentIntent, BookingStatus, Decimal, LedgerEntry, LedgerTotals, Money,
+    NewLedgerEntry, Notification, Payment, PaymentKind, PaymentRequest, PaymentStatus,
+    RefundRequest, StripePaymentIntent,
+};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// Provider name stored on the payments made through Stripe
+const STRIPE_PROVIDER: &str = "stripe";
+
+/// Refund part or all of what the customer was charged for a booking, as recorded on
+/// its ledger (Admin only).
+/// A retry with the same idempotency key returns the first refund, the bool tells
+/// whether the refund was created by this call.
+pub async fn refund(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    request: RefundRequest,
+    idempotency_key: Option<String>,
+) -> AppResult<(Payment, bool)> {
+    let booking = controllers::booking::get(identity, booking_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))?;
+
+    if let Some(key) = &idempotency_key {
+        let filter = doc! {
+            "booking_id": booking_id,
+            "kind": PaymentKind::Refund.to_string(),
+            "idempotency_key": key,
+        };
+        if let Some(payment) = services::mongodb::get_one::<Payment>(filter, None).await? {
+            // A key stands for one request, reusing it for another amount is a client bug
+            if payment.amount.amount != request.amount {
+                return Err(AppError::bad_request(
+                    "Idempotency-Key was already used for a different refund",
+                ));
+            }
+            return Ok((payment, false));
+        }
+    }
+
+    let amount = Money::new(request.amount, booking.currency());
+    let totals = LedgerTotals::compute(
+        booking.currency(),
+        &services::ledger::entries(booking_id).await?,
+    );
+    if amount.amount > totals.balance.amount {
+        return Err(AppError::bad_request(format!(
+            "Refund of {} exceeds the {} charged for the booking (refunded so far {})",
+            amount, totals.balance, totals.refunded
+        )));
+    }
+
+    let payment = services::payments::execute(PaymentRequest {
+        booking_id: *booking_id,
+        customer_id: booking.customer_id.clone(),
+        kind: PaymentKind::Refund,
+        amount,
+        reason: request.reason,
+        requested_by: identity.user_id.clone(),
+        idempotency_key,
+    })
+    .await?;
+
+    let notification = Notification::to_user(
+        &booking.customer_id,
+        "Refund issued",
+        format!(
+            "A refund of {} was issued for your booking: {}",
+            payment.amount, payment.reason
+        ),
+    )
+    .about_booking(*booking_id);
+    services::notification::send(vec![notification]).await;
+
+    Ok((payment, true))
+}
+
+/// Start paying the total price of a booking with Stripe (Customer, own bookings).
+/// Calling again returns the same PaymentIntent while the price does not change.
+pub async fn pay(identity: &Identity, booking_id: &ObjectId) -> AppResult<BookingPaymentIntent> {
+    let stripe = services::payments::stripe::get()?;
+    let booking = controllers::booking::get(identity, booking_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking not found"))?;
+
+    if booking.customer_id != identity.user_id {
+        return Err(AppError::forbidden(
+            "Only the customer of a booking can pay it",
+        ));
+    }
+    if booking.paid_at.is_some() {
+        return Err(AppError::conflict("Booking is already paid"));
+    }
+    // Confirmed bookings had their rental charged on the ledger when confirmed
+    if booking.status != BookingStatus::Pending {
+        return Err(AppError::bad_request("Only pending bookings can be paid"));
+    }
+    let price = booking
+        .total_price
+        .filter(|price| price.amount > Decimal::ZERO)
+        .ok_or_else(|| AppError::bad_request("Booking has no price to pay"))?;
+    // The ledger keeps the booking's currency, Stripe is not asked to convert
+    if !price
+        .currency
+        .to_string()
+        .eq_ignore_ascii_case(&stripe.currency)
+    {
+        return Err(AppError::bad_request(format!(
+            "Bookings priced in {} cannot be paid online, payments are in {}",
+            price.currency,
+            stripe.currency.to_uppercase()
+        )));
+    }
+    // The price is part of the key: a booking repriced after its dates changed gets a new intent
+    let intent = stripe
+        .create_payment_intent(
+            price,
+            &[
+                ("booking_id", booking_id.to_hex()),
+                ("customer_id", booking.customer_id.clone()),
+            ],
+            &format!("booking-{}-{}", booking_id.to_hex(), price.amount),
+        )
+        .await?;
+    let client_secret = intent
+        .client_secret
+        .clone()
+        .ok_or_else(|| AppError::internal_server_error("Stripe did not return a client secret"))?;
+
+    let filter = doc! { "provider": STRIPE_PROVIDER, "provider_reference": &intent.id };
+    let payment_id = match services::mongodb::get_one::<Payment>(filter, None).await? {
+        Some(payment) => payment.id,
+        None => {
+            let mut payment = Payment::new(
+                PaymentRequest {
+                    booking_id: *booking_id,
+                    customer_id: booking.customer_id.clone(),
+                    kind: PaymentKind::Rental,
+                    amount: price,
+                    reason: "Rental price".to_string(),
+                    requested_by: identity.user_id.clone(),
+                    idempotency_key: None,
+                },
+                STRIPE_PROVIDER,
+            );
+            payment.provider_reference = Some(intent.id.clone());
+            Some(services::mongodb::insert_one(&payment, None).await?)
+        }
+    };
+
+    Ok(BookingPaymentIntent {
+        payment_id: payment_id
+            .ok_or_else(|| AppError::internal_server_error("Payment without ID"))?,
+        intent_id: intent.id,
+        client_secret,
+        amount: price,
+    })
+}
+
+/// Apply a PaymentIntent event posted by Stripe (public, signed). Stripe retries events
+/// until it gets a 2xx, applying one twice changes nothing.
+pub async fn stripe_webhook(payload: &[u8], signature: &str) -> AppResult<()> {
+    let stripe = services::payments::stripe::get()?;
+    let event = stripe
+        .verify_event(payload, signature, Utc::now().timestamp())
+        .map_err(AppError::bad_request)?;
+
+    let status = match event.event_type.as_str() {
+        "payment_intent.succeeded" => PaymentStatus::Succeeded,
+        "payment_intent.payment_failed" => PaymentStatus::Failed,
+        _ => return Ok(()),
+    };
+    let intent: StripePaymentIntent = serde_json::from_value(event.data.object).map_err(|e| {
+        AppError::bad_request(format!("Invalid PaymentIntent in {}: {}", event.id, e))
+    })?;
+
+    // Only an intent the API created, for the amount it asked, pays a booking
+    let filter = doc! { "provider": STRIPE_PROVIDER, "provider_reference": &intent.id };
+    let Some(payment) = services::mongodb::get_one::<Payment>(filter, None).await? else {
+        log::warn!(
+            "Ignoring PaymentIntent {} the API did not create",
+            intent.id
+        );
+        return Ok(());
+    };
+    if status == PaymentStatus::Succeeded
+        && (intent.status != "succeeded" || !intent.is_for(payment.amount))
+    {
+        return Err(AppError::bad_request(format!(
+            "PaymentIntent {} ({}, {} {}) does not match payment of {}",
+            intent.id, intent.status, intent.amount, intent.currency, payment.amount
+        )));
+    }
+
+    // Bookings are paid once, a late failure never undoes a success
+    services::mongodb::update_one(
+        Payment::get_collection(),
+        doc! {
+            "provider": STRIPE_PROVIDER,
+            "provider_reference": &intent.id,
+            "status": { "$ne": PaymentStatus::Succeeded.to_string() },
+        },
+        doc! { "$set": { "status": status.to_string() } },
+        None,
+    )
+    .await?;
+
+    if status == PaymentStatus::Succeeded {
+        let booking_id = intent
+            .booking_id()
+            .ok_or_else(|| AppError::bad_request("PaymentIntent without booking_id metadata"))?;
+        // The payment charges the rental, once per intent whatever Stripe redelivers
+        let filter = doc! { "payment_id": payment.id };
+        if services::mongodb::get_one::<LedgerEntry>(filter, None)
+            .await?
+            .is_none()
+        {
+            services::ledger::append(NewLedgerEntry::of_payment(&payment)).await?;
+        }
+        services::mongodb::update_one(
+            Booking::get_collection(),
+            doc! { "_id": booking_id, "paid_at": null },
+            doc! { "$set": { "paid_at": bson::DateTime::now() } },
+            None,
+        )
+        .await?;
+        log::info!("Booking {} paid with {}", booking_id, intent.id);
+    }
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use bson::doc;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Booking, PiiRotationReport};
+use crate::services;
+
+/// Re-encrypt the personal data sealed with older keys, run after adding a key
+/// in front of PII_ENCRYPTION_KEYS (Admin only)
+pub async fn rotate() -> AppResult<PiiRotationReport> {
+    let cipher = services::encryption::get_field_cipher().await?;
+
+    let filter = doc! { "driver": { "$exists": true } };
+    let bookings: Vec<Booking> = services::mongodb::collect_many(filter, None).await?;
+
+    let mut stale = Vec::new();
+    for mut booking in bookings.iter().cloned() {
+        if cipher.rotate(&mut booking)? {
+            stale.push(booking);
+        }
+    }
+    let rotated = stale.len();
+
+    services::fanout::try_join_all(stale.iter().map(|booking| async move {
+        services::mongodb::find_one_and_replace(doc! { "_id": booking.id }, booking, None)
+            .await?
+            .ok_or_else(|| AppError::internal_server_error("Failed to rotate booking"))
+    }))
+    .await?;
+
+    Ok(PiiRotationReport {
+        key_id: cipher.current_key_id().to_string(),
+        scanned: bookings.len(),
+        rotated,
+    })
+}
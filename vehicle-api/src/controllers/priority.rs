@@ -0,0 +1,8 @@
+use crate::error::AppResult;
+use crate::models::PriorityLanes;
+use crate::priority;
+
+/// Queue depth and shed requests of each priority lane on this instance (Admin only)
+pub async fn stats() -> AppResult<PriorityLanes> {
+    Ok(priority::stats())
+}
@@ -0,0 +1,22 @@
+use bson::{doc, Document};
+use mongodb::options::FindOptions;
+
+use crate::error::AppResult;
+use crate::models::{QuarantineQuery, QuarantinedDocument};
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+/// Documents lenient reads skipped, most recently read first (Admin only)
+pub async fn list(query: QuarantineQuery, page: PageQuery) -> AppResult<Vec<QuarantinedDocument>> {
+    let mut filter = Document::new();
+    if let Some(collection) = query.collection {
+        filter.insert("collection", collection);
+    }
+
+    let mut options = FindOptions::builder()
+        .sort(doc! { "last_seen_at": -1 })
+        .build();
+    page.apply(&mut options);
+
+    services::mongodb::collect_many(filter, options).await
+}
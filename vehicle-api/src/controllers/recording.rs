@@ -0,0 +1,176 @@
+use ::validator::Validate;
+use bson::{doc, oid::ObjectId, Document};
+use mongodb::options::FindOptions;
+use serde::de::DeserializeOwned;
+
+use crate::authentication::{self, identity::Identity};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Booking, CreateBookingRequest, CreateVehicleRequest, Recording, RecordingFilters,
+    RecordingTargets, ReplayResult, UpdateBookingRequest, UpdateVehicleRequest, Vehicle,
+};
+use crate::recording;
+use crate::services;
+use crate::util::pagination::PageQuery;
+use crate::validator::{self, CustomValidateTrait};
+
+/// Get the users and request IDs currently recorded (Admin only)
+pub async fn get_targets() -> AppResult<RecordingTargets> {
+    Ok(recording::get_targets())
+}
+
+/// Replace the users and request IDs to record (Admin only)
+pub async fn update_targets(targets: RecordingTargets) -> AppResult<RecordingTargets> {
+    recording::set_targets(targets.clone());
+
+    Ok(targets)
+}
+
+/// List recordings, newest first (Admin only)
+pub async fn list(filters: RecordingFilters, page: PageQuery) -> AppResult<Vec<Recording>> {
+    let mut filter = Document::new();
+    if let Some(user_id) = filters.user_id {
+        filter.insert("user_id", user_id);
+    }
+    if let Some(request_id) = filters.request_id {
+        filter.insert("request_id", request_id);
+    }
+
+    let mut options = FindOptions::builder()
+        .sort(doc! { "recorded_at": -1 })
+        .build();
+    page.apply(&mut options);
+
+    services::mongodb::collect_many(filter, options).await
+}
+
+/// Get a single recording by ID (Admin only)
+pub async fn get(recording_id: &ObjectId) -> AppResult<Option<Recording>> {
+    services::mongodb::get_one(doc! { "_id": recording_id }, None).await
+}
+
+/// Replay a recorded request against the current validation code (Admin only).
+/// Dry-run: documents may be read but nothing is ever written.
+pub async fn replay(recording_id: &ObjectId) -> AppResult<ReplayResult> {
+    let recording = get(recording_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Recording not found"))?;
+
+    let mut identity = Identity {
+        role: recording.role.clone(),
+        user_id: recording.user_id.clone(),
+        email: None,
+        tenant_id: recording.tenant_id.clone(),
+        permissions: Vec::new(),
+        profile: None,
+        impersonated_by: None,
+    };
+    let profile = authentication::profile::load(&identity).await?;
+    authentication::profile::enrich(&mut identity, profile);
+    let segments: Vec<&str> = recording
+        .path
+        .trim_start_matches("/protected")
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    let outcome = match (recording.method.as_str(), segments.as_slice()) {
+        ("POST", ["vehicles"]) => {
+            let request: CreateVehicleRequest = parse_body(&recording)?;
+            dry_run_create_vehicle(&identity, request).await
+        }
+        ("PATCH", ["vehicles", id]) => {
+            let request: UpdateVehicleRequest = parse_body(&recording)?;
+            dry_run_update_vehicle(&identity, &parse_id(id)?, request).await?
+        }
+        ("POST", ["bookings"]) => {
+            let request: CreateBookingRequest = parse_body(&recording)?;
+            dry_run_create_booking(&identity, request).await
+        }
+        ("PATCH", ["bookings", id]) => {
+            let request: UpdateBookingRequest = parse_body(&recording)?;
+            dry_run_update_booking(&identity, &parse_id(id)?, request).await?
+        }
+        _ => {
+            return Err(AppError::bad_request(format!(
+                "Replay is not supported for {} {}",
+                recording.method, recording.path
+            )))
+        }
+    };
+
+    Ok(ReplayResult::new(&recording, outcome))
+}
+
+async fn dry_run_create_vehicle(
+    identity: &Identity,
+    request: CreateVehicleRequest,
+) -> Result<(), String> {
+    Validate::validate(&request).map_err(|e| e.to_string())?;
+    CustomValidateTrait::validate(&request, identity).await?;
+    validator::vehicle::validate_brand_model(&request.brand, &request.metadata).await?;
+    Vehicle::new(request, identity.user_id.clone()).map(|_| ())
+}
+
+async fn dry_run_update_vehicle(
+    identity: &Identity,
+    vehicle_id: &ObjectId,
+    request: UpdateVehicleRequest,
+) -> AppResult<Result<(), String>> {
+    if let Err(error) = Validate::validate(&request) {
+        return Ok(Err(error.to_string()));
+    }
+
+    let vehicle: Vehicle = services::mongodb::get_one(doc! { "_id": vehicle_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Vehicle of the recording no longer exists"))?;
+
+    Ok(
+        validator::vehicle::validate_update_vehicle(identity, &vehicle, &request)
+            .map_err(|e| e.to_string()),
+    )
+}
+
+async fn dry_run_create_booking(
+    identity: &Identity,
+    request: CreateBookingRequest,
+) -> Result<(), String> {
+    Validate::validate(&request).map_err(|e| e.to_string())?;
+    validator::booking::validate_booking_creation(identity, &request)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn dry_run_update_booking(
+    identity: &Identity,
+    booking_id: &ObjectId,
+    request: UpdateBookingRequest,
+) -> AppResult<Result<(), String>> {
+    if let Err(error) = Validate::validate(&request) {
+        return Ok(Err(error.to_string()));
+    }
+
+    let booking: Booking = services::mongodb::get_one(doc! { "_id": booking_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Booking of the recording no longer exists"))?;
+    let policy = services::mongodb::booking::get_booking_policy().await?;
+
+    Ok(
+        validator::booking::validate_update_booking(identity, &booking, &request, &policy)
+            .map_err(|e| e.to_string()),
+    )
+}
+
+fn parse_body<T: DeserializeOwned>(recording: &Recording) -> AppResult<T> {
+    let body = recording
+        .request_body
+        .clone()
+        .ok_or_else(|| AppError::bad_request("Recording has no JSON request body"))?;
+
+    serde_json::from_value(body)
+        .map_err(|e| AppError::bad_request(format!("Recorded body no longer parses: {}", e)))
+}
+
+fn parse_id(id: &str) -> AppResult<ObjectId> {
+    ObjectId::parse_str(id).map_err(|_| AppError::bad_request("Recorded path has an invalid ID"))
+}
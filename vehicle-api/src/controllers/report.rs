@@ -0,0 +1,44 @@
+use bson::{doc, Document};
+use futures::Stream;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Booking, CapacityReport, ReportQuery, RevenueRow, UtilizationRow, Vehicle};
+use crate::services;
+
+/// Project fleet demand against supply per day (Admin, CarManager, MotorbikeManager)
+pub async fn capacity(query: ReportQuery) -> AppResult<CapacityReport> {
+    query.validate().map_err(AppError::bad_request)?;
+
+    let mut vehicle_filter = Document::new();
+    if let Some(vehicle_type) = &query.vehicle_type {
+        vehicle_filter.insert("type", vehicle_type.to_string());
+    }
+    let vehicles: Vec<Vehicle> = services::mongodb::collect_many(vehicle_filter, None).await?;
+    let vehicle_ids: Vec<_> = vehicles.iter().filter_map(|vehicle| vehicle.id).collect();
+
+    // Active bookings of these vehicles overlapping the period
+    let booking_filter = doc! {
+        "vehicle_id": { "$in": vehicle_ids },
+        "from_date": { "$lte": query.to.to_string() },
+        "to_date": { "$gte": query.from.to_string() },
+        "status": { "$in": ["PENDING", "CONFIRMED"] },
+    };
+    let bookings: Vec<Booking> = services::mongodb::collect_many(booking_filter, None).await?;
+
+    Ok(CapacityReport::project(&query, &vehicles, &bookings))
+}
+
+/// Booked days per vehicle over the period, streamed from the aggregation cursor
+/// (Admin, CarManager, MotorbikeManager)
+pub async fn utilization(
+    query: ReportQuery,
+) -> AppResult<impl Stream<Item = AppResult<UtilizationRow>>> {
+    query.validate().map_err(AppError::bad_request)?;
+    services::mongodb::aggregate::<Booking, _>(query.utilization_pipeline()).await
+}
+
+/// Confirmed revenue per month over the period, streamed from the aggregation cursor (Admin)
+pub async fn revenue(query: ReportQuery) -> AppResult<impl Stream<Item = AppResult<RevenueRow>>> {
+    query.validate().map_err(AppError::bad_request)?;
+    services::mongodb::aggregate::<Booking, _>(query.revenue_pipeline()).await
+}
@@ -0,0 +1,10 @@
+use crate::error::AppResult;
+use crate::models::SchemaViolationReport;
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+/// Stored vehicles or bookings breaking the schema of their collection, a page of them
+/// by `_id` (Admin only)
+pub async fn violations(collection: &str, page: PageQuery) -> AppResult<SchemaViolationReport> {
+    services::mongodb::validation::violations(collection, page).await
+}
@@ -0,0 +1,69 @@
+use bson::{doc, oid::ObjectId};
+use chrono::Utc;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::service_account;
+use crate::error::{AppError, AppResult};
+use crate::models::{CreateServiceAccountRequest, ServiceAccount};
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+/// Create a service account, returns it with its plain client secret (Admin only)
+pub async fn create(
+    identity: &Identity,
+    mut request: CreateServiceAccountRequest,
+) -> AppResult<(ServiceAccount, String)> {
+    // Accounts of a rental company can only be created for that company
+    match (&identity.tenant_id, &request.tenant_id) {
+        (Some(own), Some(requested)) if own != requested => {
+            return Err(AppError::forbidden(
+                "You can only create service accounts for your own tenant.",
+            ));
+        }
+        (Some(own), None) => request.tenant_id = Some(own.clone()),
+        _ => {}
+    }
+
+    let secret = service_account::generate_secret();
+    let mut service_account = ServiceAccount::new(request, &secret, identity.user_id.clone());
+
+    let inserted_id = services::mongodb::insert_one(&service_account, None).await?;
+    service_account.id = Some(inserted_id);
+
+    Ok((service_account, secret))
+}
+
+/// List every service account, disabled ones included (Admin only)
+pub async fn list(page: PageQuery) -> AppResult<Vec<ServiceAccount>> {
+    services::mongodb::collect_many(doc! {}, page.to_find_options()).await
+}
+
+/// Disable a service account and end the sessions of its tokens (Admin only)
+pub async fn disable(service_account_id: &ObjectId) -> AppResult<ServiceAccount> {
+    let filter = doc! { "_id": service_account_id };
+
+    let mut service_account: ServiceAccount = services::mongodb::get_one(filter.clone(), None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Service account not found"))?;
+
+    if service_account.disabled_at.is_none() {
+        let now = Utc::now();
+        service_account.disabled_at = Some(now);
+        services::mongodb::find_one_and_replace(filter, &service_account, None)
+            .await?
+            .ok_or_else(|| AppError::internal_server_error("Failed to disable service account"))?;
+
+        services::mongodb::update_many(
+            "sessions",
+            doc! {
+                "user_id": service_account.user_id(),
+                "expires_at": { "$gt": bson::DateTime::from_chrono(now) },
+            },
+            doc! { "$set": { "expires_at": bson::DateTime::from_chrono(now) } },
+            None,
+        )
+        .await?;
+    }
+
+    Ok(service_account)
+}
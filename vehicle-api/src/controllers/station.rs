@@ -0,0 +1,105 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::options::{FindOneAndReplaceOptions, FindOptions, ReturnDocument};
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    exclude_deleted, CreateStationRequest, Station, UpdateStationRequest, Vehicle,
+};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+use crate::util::pagination::PageQuery;
+
+/// Pickup stations, by name (All users)
+pub async fn list(page: PageQuery) -> AppResult<Vec<Station>> {
+    let mut options = FindOptions::builder().sort(doc! { "name": 1 }).build();
+    page.apply(&mut options);
+
+    services::mongodb::collect_many(doc! {}, options).await
+}
+
+/// One pickup station (All users)
+pub async fn get(station_id: &ObjectId) -> AppResult<Station> {
+    services::station::get(station_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Station not found"))
+}
+
+/// Open a pickup station (Admin only)
+pub async fn create(identity: &Identity, request: CreateStationRequest) -> AppResult<Station> {
+    let mut station = Station::new(request, identity.user_id.clone());
+    station.id = Some(services::mongodb::insert_one(&station, None).await?);
+
+    Ok(station)
+}
+
+/// Change a station. A new location or time zone is carried to its vehicles (Admin only)
+pub async fn update(
+    identity: &Identity,
+    station_id: &ObjectId,
+    request: UpdateStationRequest,
+) -> AppResult<Station> {
+    let mut station = get(station_id).await?;
+    let moved = request.location.is_some() || request.timezone.is_some();
+    station.apply(request);
+
+    let options = FindOneAndReplaceOptions::builder()
+        .return_document(ReturnDocument::After)
+        .build();
+    let station =
+        services::mongodb::find_one_and_replace(doc! { "_id": station_id }, &station, options)
+            .await?
+            .ok_or_else(|| AppError::not_found("Station not found"))?;
+
+    if moved {
+        let result = services::mongodb::update_many(
+            Vehicle::get_collection(),
+            doc! { "station_id": station_id },
+            doc! { "$set": {
+                "location": bson::to_bson(&station.location).map_err(|e| {
+                    AppError::internal_server_error(format!("Cannot serialize location: {}", e))
+                })?,
+                "timezone": &station.timezone,
+            } },
+            None,
+        )
+        .await?;
+        log::info!(
+            "Station {} moved by {}, {} vehicles updated",
+            station_id,
+            identity.user_id,
+            result.modified_count
+        );
+    }
+
+    Ok(station)
+}
+
+/// Close a station, refused while vehicles are still picked up there (Admin only)
+pub async fn delete(station_id: &ObjectId) -> AppResult<()> {
+    get(station_id).await?;
+
+    let mut filter = doc! { "station_id": station_id };
+    exclude_deleted(&mut filter);
+    let vehicles = services::mongodb::count(Vehicle::get_collection(), filter, None).await?;
+    if vehicles > 0 {
+        return Err(AppError::conflict(format!(
+            "{} vehicles are picked up at this station, move them first",
+            vehicles
+        )));
+    }
+
+    services::mongodb::delete_one(Station::get_collection(), doc! { "_id": station_id }, None).await
+}
+
+/// Attach a vehicle to a station, it takes the station's location and time zone
+pub async fn assign(vehicle: &mut Vehicle, station_id: ObjectId) -> AppResult<()> {
+    let station = services::station::get(&station_id)
+        .await?
+        .ok_or_else(|| AppError::bad_request("Station not found"))?;
+
+    vehicle.station_id = Some(station_id);
+    vehicle.location = Some(station.location);
+    vehicle.timezone = station.timezone;
+    Ok(())
+}
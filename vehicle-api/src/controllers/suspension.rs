@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+
+use bson::{doc, oid::ObjectId, Document};
+use chrono::Utc;
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{SuspendCustomerRequest, Suspension, SuspensionFilters};
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+/// Suspend a customer, they can no longer create bookings (Admin only)
+pub async fn suspend(
+    identity: &Identity,
+    request: SuspendCustomerRequest,
+) -> AppResult<Suspension> {
+    let now = Utc::now();
+    let active: Option<Suspension> =
+        services::mongodb::get_one(Suspension::active_filter(&request.user_id, now), None).await?;
+    if active.is_some() {
+        return Err(AppError::bad_request(format!(
+            "Customer {} is already suspended",
+            request.user_id
+        )));
+    }
+
+    let mut suspension = Suspension::new(request, identity.user_id.clone());
+    suspension.id = Some(services::mongodb::insert_one(&suspension, None).await?);
+
+    notify(&suspension, "suspended");
+    Ok(suspension)
+}
+
+/// List suspensions, newest first, the ones in force only unless asked otherwise (Admin only)
+pub async fn list(filters: SuspensionFilters, page: PageQuery) -> AppResult<Vec<Suspension>> {
+    let now = Utc::now();
+    let mut filter = if filters.include_inactive {
+        Document::new()
+    } else {
+        Suspension::in_force_filter(now)
+    };
+    if let Some(user_id) = &filters.user_id {
+        filter.insert("user_id", user_id);
+    }
+
+    let mut options = FindOptions::builder()
+        .sort(doc! { "suspended_at": -1 })
+        .build();
+    page.apply(&mut options);
+    services::mongodb::collect_many(filter, options).await
+}
+
+/// Reinstate a suspended customer before the end of the suspension (Admin only)
+pub async fn lift(identity: &Identity, suspension_id: &ObjectId) -> AppResult<Suspension> {
+    let filter = doc! { "_id": suspension_id };
+
+    let mut suspension: Suspension = services::mongodb::get_one(filter.clone(), None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Suspension not found"))?;
+
+    if !suspension.is_active(Utc::now()) {
+        return Err(AppError::bad_request("Suspension is no longer in force"));
+    }
+
+    suspension.lifted_at = Some(Utc::now());
+    suspension.lifted_by = Some(identity.user_id.clone());
+    services::mongodb::find_one_and_replace(filter, &suspension, None)
+        .await?
+        .ok_or_else(|| AppError::internal_server_error("Failed to lift suspension"))?;
+
+    notify(&suspension, "reinstated");
+    Ok(suspension)
+}
+
+/// Report a suspension change to Sentry, tagged with the customer
+fn notify(suspension: &Suspension, action: &str) {
+    log::info!(
+        "Customer {} {} by {}: {}",
+        suspension.user_id,
+        action,
+        suspension
+            .lifted_by
+            .as_deref()
+            .unwrap_or(&suspension.suspended_by),
+        suspension.reason
+    );
+
+    let mut extra = BTreeMap::new();
+    extra.insert(
+        "suspension_id".to_string(),
+        sentry::protocol::Value::from(suspension.id.map(|id| id.to_hex())),
+    );
+    extra.insert(
+        "reason".to_string(),
+        sentry::protocol::Value::from(suspension.reason.clone()),
+    );
+    extra.insert(
+        "ends_at".to_string(),
+        sentry::protocol::Value::from(suspension.ends_at.map(|ends_at| ends_at.to_rfc3339())),
+    );
+
+    let mut tags = BTreeMap::new();
+    tags.insert("customer_id".to_string(), suspension.user_id.clone());
+    tags.insert("suspension_action".to_string(), action.to_string());
+
+    sentry::capture_event(sentry::protocol::Event {
+        message: Some(format!("Customer {} {}", suspension.user_id, action)),
+        level: sentry::Level::Info,
+        extra,
+        tags,
+        ..Default::default()
+    });
+}
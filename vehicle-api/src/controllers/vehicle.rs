@@ -1,12 +1,26 @@
 use bson::{doc, oid::ObjectId};
+use futures::TryStreamExt;
 
 use crate::authentication::identity::Identity;
+use crate::authentication::permission::{self, Permission};
+use crate::controllers;
+use crate::domain;
+use crate::domain::events::{VehicleCreated, VehicleDeleted, VehicleUpdated};
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    Booking, CreateVehicleRequest, UpdateVehicleRequest, Vehicle, VehicleFilters,
-    VehiclePagination, VehicleQueryBuilder,
+    exclude_deleted, ActionAuditEntry, AuditAction, Booking, ChangesetKind, CreateVehicleRequest,
+    CustomerAffinity, DeletedScope, ExperimentAssignments, PriceAdjustmentRequest,
+    PriceAdjustmentResult, PriceChange, SimilarQuery, SimilarVehicle, SuggestionQuery,
+    UpdateVehicleRequest, UpdateVehicleStatusRequest, Vehicle, VehicleDetail, VehicleFilters,
+    VehicleImage, VehicleListQuery, VehiclePagination, VehicleQueryBuilder, VehicleSearchQuery,
+    VehicleSearchResults, VehicleStatus, VehicleSuggestion, RANKING_EXPERIMENT,
 };
 use crate::services;
+use crate::services::image::{ImageFormat, ImageUpload, MAX_IMAGES_PER_VEHICLE};
+use crate::services::mongodb::MongoStruct;
+use crate::services::search::{SearchProvider, SEARCH_BACKEND};
+use crate::util::locale;
+use crate::util::pagination::PageQuery;
 use crate::validator;
 
 /// Create a new vehicle (Admin only)
@@ -17,78 +31,584 @@ pub async fn create(identity: &Identity, request: CreateVehicleRequest) -> AppRe
 
     let mut vehicle =
         Vehicle::new(request, identity.user_id.clone()).map_err(|e| AppError::bad_request(&e))?;
+    if let Some(station_id) = vehicle.station_id {
+        controllers::station::assign(&mut vehicle, station_id).await?;
+    }
 
     let inserted_id = services::mongodb::insert_one(&vehicle, None).await?;
     vehicle.id = Some(inserted_id);
+    domain::events::publish(VehicleCreated::of(&vehicle)).await;
 
     Ok(vehicle)
 }
 
-/// Get vehicles with filters and pagination (All users)
+/// Get vehicles with filters and pagination, soft deleted ones only for Admins asking
+/// for them (All users)
 pub async fn list(
+    identity: &Identity,
     filters: VehicleFilters,
+    deleted: DeletedScope,
     pagination: VehiclePagination,
+    query: VehicleListQuery,
+    assignments: Option<&ExperimentAssignments>,
+    languages: Option<&[String]>,
 ) -> AppResult<Vec<Vehicle>> {
+    let VehicleListQuery {
+        scope,
+        rank,
+        currency,
+    } = query;
+    let rates = services::currency::rates();
+    rates.check(currency.currency)?;
+    validator::vehicle::validate_vehicle_filters(&filters)?;
+    let include_deleted = deleted
+        .resolve(&identity.role)
+        .map_err(AppError::forbidden)?;
+
+    // Without `rank` nor `sort`, the caller's ranking experiment variant picks the order.
+    // Nearby vehicles come nearest first instead, `$nearSphere` cannot run in a pipeline.
+    let nearby = filters.lat.is_some();
+    let variant = assignments
+        .and_then(|assignments| assignments.variant(RANKING_EXPERIMENT))
+        .filter(|_| !nearby);
+    let strategy = rank
+        .resolve(pagination.sort.as_deref(), variant)
+        .map_err(|e| AppError::bad_request(&e))?;
+    if nearby && strategy.is_some() {
+        return Err(AppError::bad_request(
+            "rank cannot be combined with lat and lng, nearby vehicles come nearest first",
+        ));
+    }
+    let sorted = pagination.sort.is_some();
+    let page = PageQuery::new(pagination.page, pagination.limit);
+
     let query_builder = VehicleQueryBuilder {
         filters: Some(filters),
         pagination: Some(pagination),
+        currency: currency.currency,
     };
 
-    let (filter, options) = query_builder.build_query();
+    let (mut filter, mut options) = query_builder.build_query();
+    if nearby && !sorted {
+        // The `_id` tiebreaker would replace the distance order
+        options.sort = None;
+    }
+    // Managers see the vehicles of their type unless they ask for another one
+    if let Some(vehicle_type) = scope.resolve(&identity.role) {
+        filter.insert("type", vehicle_type.to_string());
+    }
+    if !include_deleted {
+        exclude_deleted(&mut filter);
+    }
+
+    let mut vehicles: Vec<Vehicle> = match strategy {
+        None => services::mongodb::collect_many(filter, options).await?,
+        Some(strategy) => {
+            let affinity = if strategy.is_personalized() {
+                customer_affinity(&identity.user_id).await?
+            } else {
+                CustomerAffinity::default()
+            };
+            let pipeline = strategy.pipeline(filter, &affinity, page);
 
-    let vehicles = services::mongodb::collect_many(filter, options).await?;
+            services::mongodb::aggregate::<Vehicle, Vehicle>(pipeline)
+                .await?
+                .try_collect()
+                .await?
+        }
+    };
+    for vehicle in &mut vehicles {
+        locale::localize(vehicle, languages);
+        rates.convert_in_place(&mut vehicle.price_by_day, currency.currency);
+    }
 
     Ok(vehicles)
 }
 
+/// Brands and types of the vehicles a customer booked, empty without history
+async fn customer_affinity(customer_id: &str) -> AppResult<CustomerAffinity> {
+    let vehicle_ids = services::mongodb::distinct::<Booking>(
+        "vehicle_id",
+        doc! {
+            "customer_id": customer_id,
+            "status": { "$in": ["PENDING", "CONFIRMED"] },
+        },
+    )
+    .await?;
+    if vehicle_ids.is_empty() {
+        return Ok(CustomerAffinity::default());
+    }
+
+    let booked = doc! { "_id": { "$in": vehicle_ids } };
+    let (brands, vehicle_types) = services::fanout::try_join2(
+        services::mongodb::distinct::<Vehicle>("brand", booked.clone()),
+        services::mongodb::distinct::<Vehicle>("type", booked),
+    )
+    .await?;
+    let strings = |values: Vec<bson::Bson>| -> Vec<String> {
+        values
+            .into_iter()
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect()
+    };
+
+    Ok(CustomerAffinity {
+        brands: strings(brands),
+        vehicle_types: strings(vehicle_types),
+    })
+}
+
+/// Free text search with facets, through the configured search provider (All users)
+pub async fn search(
+    query: VehicleSearchQuery,
+    page: PageQuery,
+    languages: Option<&[String]>,
+) -> AppResult<VehicleSearchResults> {
+    query.validate().map_err(|e| AppError::bad_request(&e))?;
+    let rates = services::currency::rates();
+    rates.check(query.currency)?;
+
+    let mut results = SEARCH_BACKEND.search(&query, page).await?;
+    for vehicle in &mut results.vehicles {
+        locale::localize(vehicle, languages);
+        rates.convert_in_place(&mut vehicle.price_by_day, query.currency);
+    }
+
+    Ok(results)
+}
+
+/// Brands and models completing a prefix (All users)
+pub async fn suggest(query: SuggestionQuery) -> AppResult<Vec<VehicleSuggestion>> {
+    if query.prefix().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    SEARCH_BACKEND.suggest(query.prefix(), query.limit()).await
+}
+
 /// Update a vehicle (Admin, CarManager, MotorbikeManager)
 pub async fn update(
     identity: &Identity,
     vehicle_id: &ObjectId,
     request: UpdateVehicleRequest,
 ) -> AppResult<Vehicle> {
-    let filter = doc! { "_id": vehicle_id };
+    let mut filter = doc! { "_id": vehicle_id };
+    exclude_deleted(&mut filter);
 
     let mut vehicle: Vehicle = services::mongodb::get_one(filter.clone(), None)
         .await?
         .ok_or_else(|| AppError::not_found("Vehicle not found"))?;
     validator::vehicle::validate_update_vehicle(identity, &vehicle, &request)?;
 
-    // Update the vehicle (only description and price allowed)
+    // Update the vehicle (only description, price, location and station allowed),
+    // translations not sent are kept
     if let Some(description) = request.description {
-        vehicle.description = Some(description);
+        vehicle
+            .description
+            .get_or_insert_with(Default::default)
+            .merge(description);
     }
     if let Some(price_by_day) = request.price_by_day {
         vehicle.price_by_day = price_by_day;
     }
+    if let Some(location) = request.location {
+        vehicle.location = Some(location);
+    }
+    // The station's location wins over one sent along
+    if let Some(station_id) = request.station_id {
+        controllers::station::assign(&mut vehicle, station_id).await?;
+    }
 
     // Save the updated vehicle using find_one_and_replace
     services::mongodb::find_one_and_replace(filter, &vehicle, None)
         .await?
         .ok_or_else(|| AppError::internal_server_error(format!("Failed to update vehicle")))?;
+    domain::events::publish(VehicleUpdated::of(&vehicle)).await;
 
     Ok(vehicle)
 }
 
-/// Get a single vehicle by ID (All users)
-pub async fn get(vehicle_id: &ObjectId) -> AppResult<Option<Vehicle>> {
-    let filter = doc! { "_id": vehicle_id };
+/// Reprice the vehicles matching a filter, or only preview it with `dry_run`. Every
+/// vehicle is audited and the adjustment kept in a change-set to roll it back (Admin only)
+pub async fn adjust_prices(
+    identity: &Identity,
+    request: PriceAdjustmentRequest,
+) -> AppResult<PriceAdjustmentResult> {
+    let vehicles: Vec<Vehicle> =
+        services::mongodb::collect_many(request.vehicle_filter(), None).await?;
+    let changes = request.changes(&vehicles).map_err(AppError::bad_request)?;
+    if request.dry_run || changes.is_empty() {
+        return Ok(PriceAdjustmentResult {
+            dry_run: request.dry_run,
+            changes,
+            changeset_id: None,
+            rollback_expires_at: None,
+        });
+    }
+
+    // One update per previous price: a vehicle repriced since the preview is left alone
+    for (old_price, new_price, vehicle_ids) in PriceChange::grouped(&changes) {
+        services::mongodb::update_many(
+            Vehicle::get_collection(),
+            doc! {
+                "_id": { "$in": vehicle_ids },
+                "price_by_day.amount": old_price.to_decimal128(),
+                "price_by_day.currency": old_price.currency.to_string(),
+            },
+            doc! { "$set": { "price_by_day": new_price } },
+            None,
+        )
+        .await?;
+    }
+
+    let changeset = services::changeset::record(
+        identity,
+        ChangesetKind::VehiclePriceAdjustment,
+        Vehicle::get_collection(),
+        changes.iter().map(PriceChange::to_change).collect(),
+    )
+    .await?;
+    for change in &changes {
+        let mut snapshot = bson::to_document(change).map_err(|e| {
+            AppError::internal_server_error(format!("Cannot serialize change: {}", e))
+        })?;
+        snapshot.insert("changeset_id", changeset.id);
+        let entry = ActionAuditEntry::new(
+            AuditAction::VehiclePriceAdjusted,
+            identity,
+            change.vehicle_id.to_hex(),
+            Some(snapshot),
+        );
+        services::mongodb::insert_one(&entry, None).await?;
+    }
+    for mut vehicle in vehicles {
+        let Some(change) = changes
+            .iter()
+            .find(|change| Some(change.vehicle_id) == vehicle.id)
+        else {
+            continue;
+        };
+        vehicle.price_by_day = change.new_price;
+        domain::events::publish(VehicleUpdated::of(&vehicle)).await;
+    }
+
+    Ok(PriceAdjustmentResult {
+        dry_run: false,
+        changes,
+        changeset_id: changeset.id,
+        rollback_expires_at: Some(changeset.expires_at),
+    })
+}
+
+/// Soft delete a vehicle: its bookings keep it, it leaves the lists, search and new
+/// bookings. Refused while it has bookings to come (Admin, CarManager, MotorbikeManager)
+pub async fn delete(identity: &Identity, vehicle_id: &ObjectId) -> AppResult<()> {
+    let mut filter = doc! { "_id": vehicle_id };
+    exclude_deleted(&mut filter);
+
+    let mut vehicle: Vehicle = services::mongodb::get_one(filter.clone(), None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Vehicle not found"))?;
+    validator::vehicle::check_vehicle_type_permission(identity, &vehicle)?;
+
+    let upcoming = upcoming_bookings(vehicle_id).await?;
+    if upcoming > 0 {
+        return Err(AppError::conflict(format!(
+            "Vehicle has {} pending or confirmed bookings to come, cancel them first",
+            upcoming
+        )));
+    }
+
+    let snapshot = bson::to_document(&vehicle)
+        .map_err(|e| AppError::internal_server_error(format!("Cannot serialize vehicle: {}", e)))?;
+    let deleted_at = chrono::Utc::now();
+    let result = services::mongodb::update_one(
+        Vehicle::get_collection(),
+        filter,
+        doc! { "$set": { "deleted_at": bson::DateTime::from_chrono(deleted_at) } },
+        None,
+    )
+    .await?;
+    if result.modified_count == 0 {
+        return Err(AppError::not_found("Vehicle not found"));
+    }
+    vehicle.deleted_at = Some(deleted_at);
+
+    let entry = ActionAuditEntry::new(
+        AuditAction::VehicleDeleted,
+        identity,
+        vehicle_id.to_hex(),
+        Some(snapshot),
+    );
+    services::mongodb::insert_one(&entry, None).await?;
+    domain::events::publish(VehicleDeleted::of(&vehicle)).await;
+    log::warn!("Vehicle {} deleted by {}", vehicle_id, identity.user_id);
+
+    Ok(())
+}
 
-    let vehicle = services::mongodb::get_one(filter, None).await?;
+/// Pending and confirmed bookings of a vehicle not over yet
+async fn upcoming_bookings(vehicle_id: &ObjectId) -> AppResult<u64> {
+    let today = chrono::Utc::now().date_naive();
+    services::mongodb::count(
+        Booking::get_collection(),
+        doc! {
+            "vehicle_id": vehicle_id,
+            "status": { "$in": ["PENDING", "CONFIRMED"] },
+            "to_date": { "$gte": today.to_string() },
+        },
+        None,
+    )
+    .await
+}
+
+/// Put a vehicle in maintenance, retire it or make it available again. Only available
+/// vehicles can be booked, bookings already made are kept; retiring is refused while
+/// bookings are to come (Admin, CarManager, MotorbikeManager)
+pub async fn set_status(
+    identity: &Identity,
+    vehicle_id: &ObjectId,
+    request: UpdateVehicleStatusRequest,
+) -> AppResult<Vehicle> {
+    let mut filter = doc! { "_id": vehicle_id };
+    exclude_deleted(&mut filter);
+
+    let mut vehicle: Vehicle = services::mongodb::get_one(filter.clone(), None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Vehicle not found"))?;
+    validator::vehicle::validate_update_vehicle_status(identity, &vehicle, &request)?;
+    if vehicle.status == request.status {
+        return Ok(vehicle);
+    }
+
+    if request.status == VehicleStatus::Retired {
+        let upcoming = upcoming_bookings(vehicle_id).await?;
+        if upcoming > 0 {
+            return Err(AppError::conflict(format!(
+                "Vehicle has {} pending or confirmed bookings to come, cancel them first",
+                upcoming
+            )));
+        }
+    }
+
+    // The previous status must still be the one read, a concurrent change wins. Vehicles
+    // stored before statuses have none and are available.
+    match vehicle.status {
+        VehicleStatus::Available => filter.insert("status", doc! { "$in": ["AVAILABLE", null] }),
+        status => filter.insert("status", status.to_string()),
+    };
+    let result = services::mongodb::update_one(
+        Vehicle::get_collection(),
+        filter,
+        doc! { "$set": { "status": request.status.to_string() } },
+        None,
+    )
+    .await?;
+    if result.matched_count == 0 {
+        return Err(AppError::conflict(
+            "Vehicle status changed meanwhile, read it again",
+        ));
+    }
+
+    let entry = ActionAuditEntry::new(
+        AuditAction::VehicleStatusChanged,
+        identity,
+        vehicle_id.to_hex(),
+        Some(doc! {
+            "previous_status": vehicle.status.to_string(),
+            "status": request.status.to_string(),
+            "reason": request.reason,
+        }),
+    );
+    services::mongodb::insert_one(&entry, None).await?;
+    vehicle.status = request.status;
+    domain::events::publish(VehicleUpdated::of(&vehicle)).await;
 
     Ok(vehicle)
 }
 
-/// Get bookings for a specific vehicle (Admin, CarManager, MotorbikeManager)
-pub async fn list_bookings(identity: &Identity, vehicle_id: &ObjectId) -> AppResult<Vec<Booking>> {
-    let vehicle_filter = doc! { "_id": vehicle_id };
-    let vehicle: Vehicle = services::mongodb::get_one(vehicle_filter, None)
+/// Add photos to a vehicle, stored then listed on it in upload order. Every file is
+/// checked before any is stored (Admin, CarManager, MotorbikeManager)
+pub async fn add_images(
+    identity: &Identity,
+    vehicle_id: &ObjectId,
+    uploads: Vec<ImageUpload>,
+) -> AppResult<Vec<VehicleImage>> {
+    if uploads.is_empty() {
+        return Err(AppError::bad_request("Send at least one image file"));
+    }
+    let mut filter = doc! { "_id": vehicle_id };
+    exclude_deleted(&mut filter);
+
+    let mut vehicle: Vehicle = services::mongodb::get_one(filter.clone(), None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Vehicle not found"))?;
+    validator::vehicle::check_vehicle_type_permission(identity, &vehicle)?;
+    if vehicle.images.len() + uploads.len() > MAX_IMAGES_PER_VEHICLE {
+        return Err(AppError::conflict(format!(
+            "Vehicle has {} images, at most {} are allowed",
+            vehicle.images.len(),
+            MAX_IMAGES_PER_VEHICLE
+        )));
+    }
+
+    let mut files = Vec::with_capacity(uploads.len());
+    for upload in uploads {
+        let format = ImageFormat::sniff(&upload.bytes).ok_or_else(|| {
+            AppError::bad_request(format!(
+                "{} is not a JPEG, PNG or WebP image",
+                upload.filename.as_deref().unwrap_or("File")
+            ))
+        })?;
+        let image = VehicleImage {
+            id: ObjectId::new().to_hex(),
+            content_type: format.content_type().to_string(),
+            size_bytes: upload.bytes.len() as u64,
+            filename: upload
+                .filename
+                .map(|filename| filename.chars().take(255).collect()),
+            uploaded_by: identity.user_id.clone(),
+            uploaded_at: chrono::Utc::now(),
+        };
+        files.push((image, format, upload.bytes));
+    }
+
+    let mut images = Vec::with_capacity(files.len());
+    for (image, format, bytes) in files {
+        services::image::store(vehicle_id, &image.id, format, bytes).await?;
+        images.push(image);
+    }
+
+    // The limit is checked again by the update, concurrent uploads cannot exceed it
+    filter.insert(
+        format!("images.{}", MAX_IMAGES_PER_VEHICLE - images.len()),
+        doc! { "$exists": false },
+    );
+    let pushed = bson::to_bson(&images)
+        .map_err(|e| AppError::internal_server_error(format!("Cannot serialize images: {}", e)))?;
+    let result = services::mongodb::update_one(
+        Vehicle::get_collection(),
+        filter,
+        doc! { "$push": { "images": { "$each": pushed } } },
+        None,
+    )
+    .await?;
+    if result.matched_count == 0 {
+        return Err(AppError::conflict(
+            "Vehicle was deleted or reached its image limit meanwhile",
+        ));
+    }
+    vehicle.images.extend(images.iter().cloned());
+    domain::events::publish(VehicleUpdated::of(&vehicle)).await;
+
+    Ok(images)
+}
+
+/// Photo of a vehicle and its bytes, when its format is one the client accepts (All users)
+pub async fn get_image(
+    vehicle_id: &ObjectId,
+    image_id: &str,
+    accept: Option<&str>,
+) -> AppResult<(VehicleImage, Vec<u8>)> {
+    let mut filter = doc! { "_id": vehicle_id, "images.id": image_id };
+    exclude_deleted(&mut filter);
+
+    let image = services::mongodb::get_one::<Vehicle>(filter, None)
+        .await?
+        .and_then(|vehicle| {
+            vehicle
+                .images
+                .into_iter()
+                .find(|image| image.id == image_id)
+        })
+        .ok_or_else(|| AppError::not_found("Image not found"))?;
+    if !services::image::accepts(accept, &image.content_type) {
+        return Err(AppError::not_acceptable(format!(
+            "Image is only available as {}",
+            image.content_type
+        )));
+    }
+
+    let bytes = services::image::load(vehicle_id, &image.id).await?;
+    Ok((image, bytes))
+}
+
+/// Get a single vehicle by ID, a soft deleted one only for Admins asking for it (All users).
+/// The staff managing the vehicle also get its service warnings.
+pub async fn get(
+    identity: &Identity,
+    vehicle_id: &ObjectId,
+    deleted: DeletedScope,
+    languages: Option<&[String]>,
+) -> AppResult<Option<VehicleDetail>> {
+    let mut filter = doc! { "_id": vehicle_id };
+    if !deleted
+        .resolve(&identity.role)
+        .map_err(AppError::forbidden)?
+    {
+        exclude_deleted(&mut filter);
+    }
+
+    let Some(mut vehicle) = services::mongodb::get_one::<Vehicle>(filter, None).await? else {
+        return Ok(None);
+    };
+    locale::localize(&mut vehicle, languages);
+
+    let manages = permission::granted(identity).contains(&Permission::VehicleUpdate)
+        && validator::vehicle::check_vehicle_type_permission(identity, &vehicle).is_ok();
+    let service_warnings = if manages {
+        services::maintenance::service_warnings(vehicle_id).await?
+    } else {
+        Vec::new()
+    };
+
+    Ok(Some(VehicleDetail {
+        vehicle,
+        service_warnings,
+    }))
+}
+
+/// Vehicles most like the given one, optionally only those free from `from` to `to` (All users)
+pub async fn similar(
+    vehicle_id: &ObjectId,
+    query: SimilarQuery,
+    languages: Option<&[String]>,
+) -> AppResult<Vec<SimilarVehicle>> {
+    query.validate().map_err(|e| AppError::bad_request(&e))?;
+    let mut filter = doc! { "_id": vehicle_id };
+    exclude_deleted(&mut filter);
+    let vehicle: Vehicle = services::mongodb::get_one(filter, None)
         .await?
         .ok_or_else(|| AppError::not_found("Vehicle not found"))?;
 
+    let free_on = query.from.zip(query.to);
+    let mut vehicles = services::similar::similar(&vehicle, free_on, query.limit()).await?;
+    for similar in &mut vehicles {
+        locale::localize(&mut similar.vehicle, languages);
+    }
+
+    Ok(vehicles)
+}
+
+/// Get bookings for a specific vehicle (Admin, CarManager, MotorbikeManager)
+pub async fn list_bookings(
+    identity: &Identity,
+    vehicle_id: &ObjectId,
+    page: PageQuery,
+) -> AppResult<Vec<Booking>> {
+    // Bookings are fetched along with the vehicle, dropped if the permission check fails
+    let (vehicle, mut bookings) = services::fanout::try_join2(
+        services::mongodb::get_one::<Vehicle>(doc! { "_id": vehicle_id }, None),
+        services::mongodb::collect_many::<Booking>(
+            doc! { "vehicle_id": vehicle_id },
+            page.to_find_options(),
+        ),
+    )
+    .await?;
+    let vehicle = vehicle.ok_or_else(|| AppError::not_found("Vehicle not found"))?;
+
     validator::vehicle::check_vehicle_type_permission(identity, &vehicle)?;
-    let booking_filter = doc! { "vehicle_id": vehicle_id };
-    let bookings = services::mongodb::collect_many(booking_filter, None).await?;
+    services::encryption::present_bookings(&mut bookings, identity).await?;
 
     Ok(bookings)
 }
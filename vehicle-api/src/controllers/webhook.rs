@@ -0,0 +1,10 @@
+use crate::error::AppResult;
+use crate::models::JwkSet;
+use crate::services;
+
+/// Public keys partners use to verify webhook signatures (All, unauthenticated)
+pub async fn signing_keys() -> AppResult<JwkSet> {
+    let key_ring = services::webhook::get_key_ring().await?;
+
+    Ok(key_ring.jwks())
+}
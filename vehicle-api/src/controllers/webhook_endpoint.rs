@@ -0,0 +1,48 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::options::FindOptions;
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{CreateWebhookEndpointRequest, WebhookDelivery, WebhookEndpoint};
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+/// Register a callback URL for booking events (Admin only)
+pub async fn create(
+    identity: &Identity,
+    request: CreateWebhookEndpointRequest,
+) -> AppResult<WebhookEndpoint> {
+    let mut endpoint = WebhookEndpoint::new(request, identity.user_id.clone());
+    endpoint.tenant_id = identity.tenant_id.clone();
+
+    endpoint.id = Some(services::mongodb::insert_one(&endpoint, None).await?);
+    Ok(endpoint)
+}
+
+/// List the registered endpoints (Admin only)
+pub async fn list(page: PageQuery) -> AppResult<Vec<WebhookEndpoint>> {
+    services::mongodb::collect_many(doc! {}, page.to_find_options()).await
+}
+
+/// Stop sending events to an endpoint, its deliveries are kept (Admin only)
+pub async fn delete(endpoint_id: &ObjectId) -> AppResult<()> {
+    let filter = doc! { "_id": endpoint_id };
+    services::mongodb::get_one::<WebhookEndpoint>(filter.clone(), None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Webhook endpoint not found"))?;
+
+    services::mongodb::delete_one("webhook_endpoints", filter, None).await
+}
+
+/// Deliveries of an endpoint with their status, newest first (Admin only)
+pub async fn deliveries(
+    endpoint_id: &ObjectId,
+    page: PageQuery,
+) -> AppResult<Vec<WebhookDelivery>> {
+    let mut options = FindOptions::builder()
+        .sort(doc! { "created_at": -1 })
+        .build();
+    page.apply(&mut options);
+
+    services::mongodb::collect_many(doc! { "endpoint_id": endpoint_id }, options).await
+}
@@ -0,0 +1,70 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue, WARNING},
+    middleware, web, Error, HttpMessage, Result,
+};
+use serde_json::Value;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::middleware::extract_api_key;
+use crate::util::envelope;
+
+// Deprecation Middleware using from_fn, must run after api_key_auth_middleware. Counts
+// the requests relying on deprecated features and tells their callers.
+pub async fn deprecation_middleware(
+    mut req: ServiceRequest,
+    next: middleware::Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    if !super::inspects(req.method(), req.path()) {
+        return next.call(req).await;
+    }
+    let Some(identity) = req.extensions().get::<Identity>().cloned() else {
+        return next.call(req).await;
+    };
+
+    // Buffer the request body so it can be inspected and still reach the handler
+    let bytes = req.extract::<web::Bytes>().await?;
+    req.set_payload(Payload::from(bytes.clone()));
+    let features = serde_json::from_slice::<Value>(&bytes)
+        .map(|body| super::detect(&body))
+        .unwrap_or_default();
+    if features.is_empty() {
+        return next.call(req).await;
+    }
+
+    // Same prefix as the API key listings, never the whole key
+    let api_key_prefix: Option<String> =
+        extract_api_key(req.request()).map(|key| key.chars().take(10).collect());
+    let path = req.path().to_string();
+    let sunsets = super::sunsets();
+
+    let mut response = next.call(req).await?;
+    let headers = response.headers_mut();
+    headers.insert(
+        HeaderName::from_static("deprecation"),
+        HeaderValue::from_static("true"),
+    );
+    if let Some(value) = sunsets
+        .header_value(&features)
+        .and_then(|sunset| HeaderValue::from_str(&sunset).ok())
+    {
+        headers.insert(HeaderName::from_static("sunset"), value);
+    }
+    for feature in &features {
+        let warning = feature.warning(sunsets.get(*feature));
+        if let Ok(value) = HeaderValue::from_str(&format!("299 - \"{}\"", warning)) {
+            response.headers_mut().append(WARNING, value);
+        }
+        envelope::add_warning(response.request(), warning);
+    }
+
+    // Usages are stored in the background, a failure never fails the request
+    actix_web::rt::spawn(async move {
+        let prefix = api_key_prefix.as_deref();
+        if let Err(error) = super::record_usage(&identity, prefix, &path, &features).await {
+            log::error!("Failed to record deprecated feature usage: {}", error);
+        }
+    });
+    Ok(response)
+}
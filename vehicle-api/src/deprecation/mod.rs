@@ -0,0 +1,186 @@
+pub mod middleware;
+
+use actix_web::http::Method;
+use bson::doc;
+use chrono::{NaiveDate, Utc};
+use mongodb::options::UpdateOptions;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use crate::authentication::identity::Identity;
+use crate::error::AppResult;
+use crate::models::{DeprecatedFeature, DeprecationUsage};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// Removal dates announced to the callers of each deprecated feature
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Sunsets(HashMap<DeprecatedFeature, NaiveDate>);
+
+// Read once from DEPRECATION_SUNSETS
+static SUNSETS: LazyLock<Sunsets> =
+    LazyLock::new(|| Sunsets::parse(&std::env::var("DEPRECATION_SUNSETS").unwrap_or_default()));
+
+/// Removal dates of the instance
+pub fn sunsets() -> &'static Sunsets {
+    &SUNSETS
+}
+
+impl Sunsets {
+    /// `plain_price=2027-03-31,plain_description=2027-06-30`, unknown features and
+    /// invalid dates are ignored
+    pub fn parse(value: &str) -> Self {
+        let mut sunsets = HashMap::new();
+        for pair in value.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let parsed = pair.split_once('=').and_then(|(feature, date)| {
+                let feature = DeprecatedFeature::from_str(feature.trim()).ok()?;
+                let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()?;
+                Some((feature, date))
+            });
+            match parsed {
+                Some((feature, date)) => {
+                    sunsets.insert(feature, date);
+                }
+                None => log::warn!("Ignored deprecation sunset {:?}", pair),
+            }
+        }
+        Self(sunsets)
+    }
+
+    pub fn get(&self, feature: DeprecatedFeature) -> Option<NaiveDate> {
+        self.0.get(&feature).copied()
+    }
+
+    /// `Sunset` header value (an HTTP date) of the earliest removal among `features`
+    pub fn header_value(&self, features: &[DeprecatedFeature]) -> Option<String> {
+        let sunset = features
+            .iter()
+            .filter_map(|feature| self.get(*feature))
+            .min()?;
+        Some(
+            sunset
+                .and_hms_opt(0, 0, 0)?
+                .and_utc()
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string(),
+        )
+    }
+}
+
+/// Whether a request may send a vehicle body, the only place deprecated features are
+/// accepted
+pub fn inspects(method: &Method, path: &str) -> bool {
+    match path.strip_prefix("/protected/vehicles") {
+        Some("") => *method == Method::POST,
+        Some(rest) => *method == Method::PATCH && rest.starts_with('/') && !rest[1..].contains('/'),
+        None => false,
+    }
+}
+
+/// Deprecated features a vehicle body relies on
+pub fn detect(body: &Value) -> Vec<DeprecatedFeature> {
+    let mut features = Vec::new();
+    if body.get("price_by_day").is_some_and(Value::is_number) {
+        features.push(DeprecatedFeature::PlainPrice);
+    }
+    if body.get("description").is_some_and(Value::is_string) {
+        features.push(DeprecatedFeature::PlainDescription);
+    }
+    features
+}
+
+/// Count a request of the caller on each feature it relied on
+pub async fn record_usage(
+    identity: &Identity,
+    api_key_prefix: Option<&str>,
+    path: &str,
+    features: &[DeprecatedFeature],
+) -> AppResult<()> {
+    let now = bson::DateTime::from_chrono(Utc::now());
+    for feature in features {
+        services::mongodb::update_one(
+            DeprecationUsage::get_collection(),
+            doc! { "_id": DeprecationUsage::key(*feature, &identity.user_id, api_key_prefix) },
+            doc! {
+                "$setOnInsert": {
+                    "feature": feature.to_string(),
+                    "user_id": &identity.user_id,
+                    "api_key_prefix": api_key_prefix,
+                    "tenant_id": identity.tenant_id.clone(),
+                    "first_used_at": now,
+                },
+                "$inc": { "requests": 1_i64 },
+                "$set": { "last_path": path, "last_used_at": now },
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_only_vehicle_bodies_are_inspected() {
+        assert!(inspects(&Method::POST, "/protected/vehicles"));
+        assert!(inspects(
+            &Method::PATCH,
+            "/protected/vehicles/66b1f0c2a1b2c3d4e5f60718"
+        ));
+
+        assert!(!inspects(&Method::GET, "/protected/vehicles"));
+        assert!(!inspects(&Method::POST, "/protected/vehicles-import"));
+        assert!(!inspects(
+            &Method::PATCH,
+            "/protected/vehicles/66b1f0c2a1b2c3d4e5f60718/bookings"
+        ));
+    }
+
+    #[test]
+    fn test_plain_prices_and_descriptions_are_detected() {
+        let legacy = json!({ "price_by_day": 89.9, "description": "Long range" });
+        assert_eq!(
+            detect(&legacy),
+            vec![
+                DeprecatedFeature::PlainPrice,
+                DeprecatedFeature::PlainDescription
+            ]
+        );
+
+        let current = json!({
+            "price_by_day": { "amount": 89.9, "currency": "EUR" },
+            "description": { "en": "Long range" },
+        });
+        assert!(detect(&current).is_empty());
+    }
+
+    #[test]
+    fn test_sunsets_give_the_earliest_http_date() {
+        let sunsets =
+            Sunsets::parse("plain_price=2027-03-31, plain_description=2027-06-30,flat=2027-01-01");
+
+        assert_eq!(
+            sunsets.get(DeprecatedFeature::PlainPrice),
+            NaiveDate::from_ymd_opt(2027, 3, 31)
+        );
+        assert_eq!(
+            sunsets
+                .header_value(&[
+                    DeprecatedFeature::PlainDescription,
+                    DeprecatedFeature::PlainPrice
+                ])
+                .as_deref(),
+            Some("Wed, 31 Mar 2027 00:00:00 GMT")
+        );
+        assert_eq!(
+            Sunsets::default().header_value(&[DeprecatedFeature::PlainPrice]),
+            None
+        );
+    }
+}
@@ -0,0 +1,470 @@
+pub mod outbox;
+pub mod publisher;
+pub use publisher::publish;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    Booking, BookingEventData, BookingStatus, Brand, LocalizedText, Money, Vehicle,
+};
+
+/// Something that happened to a booking or a vehicle. Its payload is serialized as is in
+/// an `EventEnvelope`, so any change of its fields is a change of the published shape.
+pub trait DomainEvent: Serialize + DeserializeOwned {
+    /// Name consumers subscribe to, e.g. `booking.created`
+    const NAME: &'static str;
+    /// Bumped on every breaking change of the payload
+    const VERSION: u32;
+
+    /// Rental company the event belongs to
+    fn tenant_id(&self) -> Option<String>;
+}
+
+// =============================================================================
+// ENVELOPE
+// =============================================================================
+
+/// Versioned form of an event handed to the publishers
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EventEnvelope {
+    pub name: String,
+    pub version: u32,
+    pub occurred_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    pub payload: serde_json::Value,
+}
+
+impl EventEnvelope {
+    pub fn wrap<E: DomainEvent>(event: &E, occurred_at: DateTime<Utc>) -> Result<Self, String> {
+        let payload = serde_json::to_value(event)
+            .map_err(|e| format!("Cannot serialize {}: {}", E::NAME, e))?;
+        Ok(Self {
+            name: E::NAME.to_string(),
+            version: E::VERSION,
+            occurred_at,
+            tenant_id: event.tenant_id(),
+            payload,
+        })
+    }
+
+    pub fn is<E: DomainEvent>(&self) -> bool {
+        self.name == E::NAME
+    }
+
+    /// Typed payload, only of the version this code knows
+    pub fn open<E: DomainEvent>(&self) -> Result<E, String> {
+        if !self.is::<E>() || self.version != E::VERSION {
+            return Err(format!(
+                "{} v{} is not {} v{}",
+                self.name,
+                self.version,
+                E::NAME,
+                E::VERSION
+            ));
+        }
+        serde_json::from_value(self.payload.clone())
+            .map_err(|e| format!("Invalid {} payload: {}", E::NAME, e))
+    }
+}
+
+// =============================================================================
+// BOOKING EVENTS
+// =============================================================================
+
+/// A booking was made, PENDING or already CONFIRMED
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BookingCreated {
+    pub booking_id: String,
+    pub vehicle_id: String,
+    pub customer_id: String,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub status: BookingStatus,
+    /// Amount as a string and currency, version 2 had a number amount and version 1 a
+    /// plain number
+    #[serde(with = "vehicle_api_types::money::json::option")]
+    pub total_price: Option<Money>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+/// A booking was confirmed, rejected (expirations included) or cancelled
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BookingStatusChanged {
+    pub booking_id: String,
+    pub vehicle_id: String,
+    pub customer_id: String,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub previous_status: BookingStatus,
+    pub status: BookingStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+/// A booking was moved or extended, and priced again
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BookingDatesChanged {
+    pub booking_id: String,
+    pub vehicle_id: String,
+    pub previous_from_date: NaiveDate,
+    pub previous_to_date: NaiveDate,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    /// Amount as a string and currency, version 2 had a number amount and version 1 a
+    /// plain number
+    #[serde(with = "vehicle_api_types::money::json::option")]
+    pub total_price: Option<Money>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+/// The rental of a confirmed booking starts soon, see services::reminder
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BookingReminder {
+    pub booking_id: String,
+    pub vehicle_id: String,
+    pub customer_id: String,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+impl BookingCreated {
+    /// None for a booking not stored yet
+    pub fn of(booking: &Booking) -> Option<Self> {
+        Some(Self {
+            booking_id: booking.id?.to_hex(),
+            vehicle_id: booking.vehicle_id.to_hex(),
+            customer_id: booking.customer_id.clone(),
+            from_date: booking.from_date,
+            to_date: booking.to_date,
+            status: booking.status.clone(),
+            total_price: booking.total_price,
+            tenant_id: booking.tenant_id.clone(),
+        })
+    }
+
+    /// Payload of the `booking.created` webhook
+    pub fn webhook_data(&self) -> BookingEventData {
+        BookingEventData {
+            booking_id: self.booking_id.clone(),
+            vehicle_id: self.vehicle_id.clone(),
+            customer_id: self.customer_id.clone(),
+            from_date: self.from_date,
+            to_date: self.to_date,
+            status: self.status.clone(),
+        }
+    }
+}
+
+impl BookingStatusChanged {
+    /// None for a booking not stored yet
+    pub fn of(booking: &Booking, previous_status: BookingStatus) -> Option<Self> {
+        Some(Self {
+            booking_id: booking.id?.to_hex(),
+            vehicle_id: booking.vehicle_id.to_hex(),
+            customer_id: booking.customer_id.clone(),
+            from_date: booking.from_date,
+            to_date: booking.to_date,
+            previous_status,
+            status: booking.status.clone(),
+            tenant_id: booking.tenant_id.clone(),
+        })
+    }
+
+    /// Payload of the `booking.status_changed` webhook
+    pub fn webhook_data(&self) -> BookingEventData {
+        BookingEventData {
+            booking_id: self.booking_id.clone(),
+            vehicle_id: self.vehicle_id.clone(),
+            customer_id: self.customer_id.clone(),
+            from_date: self.from_date,
+            to_date: self.to_date,
+            status: self.status.clone(),
+        }
+    }
+}
+
+impl BookingDatesChanged {
+    /// None for a booking not stored yet
+    pub fn of(
+        booking: &Booking,
+        previous_from_date: NaiveDate,
+        previous_to_date: NaiveDate,
+    ) -> Option<Self> {
+        Some(Self {
+            booking_id: booking.id?.to_hex(),
+            vehicle_id: booking.vehicle_id.to_hex(),
+            previous_from_date,
+            previous_to_date,
+            from_date: booking.from_date,
+            to_date: booking.to_date,
+            total_price: booking.total_price,
+            tenant_id: booking.tenant_id.clone(),
+        })
+    }
+}
+
+impl BookingReminder {
+    /// None for a booking not stored yet
+    pub fn of(booking: &Booking) -> Option<Self> {
+        Some(Self {
+            booking_id: booking.id?.to_hex(),
+            vehicle_id: booking.vehicle_id.to_hex(),
+            customer_id: booking.customer_id.clone(),
+            from_date: booking.from_date,
+            to_date: booking.to_date,
+            starts_at: booking.starts_at,
+            tenant_id: booking.tenant_id.clone(),
+        })
+    }
+
+    /// Payload of the `booking.reminder` webhook, only confirmed bookings are reminded
+    pub fn webhook_data(&self) -> BookingEventData {
+        BookingEventData {
+            booking_id: self.booking_id.clone(),
+            vehicle_id: self.vehicle_id.clone(),
+            customer_id: self.customer_id.clone(),
+            from_date: self.from_date,
+            to_date: self.to_date,
+            status: BookingStatus::Confirmed,
+        }
+    }
+}
+
+impl DomainEvent for BookingCreated {
+    const NAME: &'static str = "booking.created";
+    const VERSION: u32 = 3;
+
+    fn tenant_id(&self) -> Option<String> {
+        self.tenant_id.clone()
+    }
+}
+
+impl DomainEvent for BookingStatusChanged {
+    const NAME: &'static str = "booking.status_changed";
+    const VERSION: u32 = 1;
+
+    fn tenant_id(&self) -> Option<String> {
+        self.tenant_id.clone()
+    }
+}
+
+impl DomainEvent for BookingReminder {
+    const NAME: &'static str = "booking.reminder";
+    const VERSION: u32 = 1;
+
+    fn tenant_id(&self) -> Option<String> {
+        self.tenant_id.clone()
+    }
+}
+
+impl DomainEvent for BookingDatesChanged {
+    const NAME: &'static str = "booking.dates_changed";
+    const VERSION: u32 = 3;
+
+    fn tenant_id(&self) -> Option<String> {
+        self.tenant_id.clone()
+    }
+}
+
+// =============================================================================
+// VEHICLE EVENTS
+// =============================================================================
+
+/// A vehicle was added to the fleet
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VehicleCreated {
+    pub vehicle_id: String,
+    pub brand: Brand,
+    /// Amount as a string and currency, version 2 had a number amount and version 1 a
+    /// plain number
+    #[serde(with = "vehicle_api_types::money::json")]
+    pub price_by_day: Money,
+    pub added_by: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+/// The description or price of a vehicle changed, the payload has the new values
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VehicleUpdated {
+    pub vehicle_id: String,
+    /// Every translation, version 1 had a plain string
+    pub description: Option<LocalizedText>,
+    /// Amount as a string and currency, version 3 had a number amount and versions 1
+    /// and 2 a plain number
+    #[serde(with = "vehicle_api_types::money::json")]
+    pub price_by_day: Money,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+/// A vehicle was soft deleted, its bookings keep it
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VehicleDeleted {
+    pub vehicle_id: String,
+    pub deleted_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+impl VehicleCreated {
+    /// None for a vehicle not stored yet
+    pub fn of(vehicle: &Vehicle) -> Option<Self> {
+        Some(Self {
+            vehicle_id: vehicle.id?.to_hex(),
+            brand: vehicle.brand.clone(),
+            price_by_day: vehicle.price_by_day,
+            added_by: vehicle.added_by.clone(),
+            tenant_id: vehicle.tenant_id.clone(),
+        })
+    }
+}
+
+impl VehicleUpdated {
+    /// None for a vehicle not stored yet
+    pub fn of(vehicle: &Vehicle) -> Option<Self> {
+        Some(Self {
+            vehicle_id: vehicle.id?.to_hex(),
+            description: vehicle.description.clone(),
+            price_by_day: vehicle.price_by_day,
+            tenant_id: vehicle.tenant_id.clone(),
+        })
+    }
+}
+
+impl VehicleDeleted {
+    /// None for a vehicle not stored or not deleted
+    pub fn of(vehicle: &Vehicle) -> Option<Self> {
+        Some(Self {
+            vehicle_id: vehicle.id?.to_hex(),
+            deleted_at: vehicle.deleted_at?,
+            tenant_id: vehicle.tenant_id.clone(),
+        })
+    }
+}
+
+impl DomainEvent for VehicleCreated {
+    const NAME: &'static str = "vehicle.created";
+    const VERSION: u32 = 3;
+
+    fn tenant_id(&self) -> Option<String> {
+        self.tenant_id.clone()
+    }
+}
+
+impl DomainEvent for VehicleUpdated {
+    const NAME: &'static str = "vehicle.updated";
+    const VERSION: u32 = 4;
+
+    fn tenant_id(&self) -> Option<String> {
+        self.tenant_id.clone()
+    }
+}
+
+impl DomainEvent for VehicleDeleted {
+    const NAME: &'static str = "vehicle.deleted";
+    const VERSION: u32 = 1;
+
+    fn tenant_id(&self) -> Option<String> {
+        self.tenant_id.clone()
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Currency, Decimal, EventType};
+    use serde_json::json;
+
+    fn status_changed() -> BookingStatusChanged {
+        BookingStatusChanged {
+            booking_id: "66b1f0c2a1b2c3d4e5f60718".to_string(),
+            vehicle_id: "66b1f0c2a1b2c3d4e5f60719".to_string(),
+            customer_id: "customer_user_1".to_string(),
+            from_date: NaiveDate::from_ymd_opt(2025, 8, 1).unwrap(),
+            to_date: NaiveDate::from_ymd_opt(2025, 8, 10).unwrap(),
+            previous_status: BookingStatus::Pending,
+            status: BookingStatus::Rejected("Maintenance".to_string()),
+            tenant_id: Some("acme".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_envelope_keeps_the_published_shape() {
+        let envelope = EventEnvelope::wrap(&status_changed(), Utc::now()).unwrap();
+
+        assert_eq!(envelope.name, "booking.status_changed");
+        assert_eq!(envelope.version, 1);
+        assert_eq!(envelope.tenant_id.as_deref(), Some("acme"));
+        assert_eq!(
+            envelope.payload,
+            json!({
+                "booking_id": "66b1f0c2a1b2c3d4e5f60718",
+                "vehicle_id": "66b1f0c2a1b2c3d4e5f60719",
+                "customer_id": "customer_user_1",
+                "from_date": "2025-08-01",
+                "to_date": "2025-08-10",
+                "previous_status": { "status": "PENDING" },
+                "status": { "status": "REJECTED", "reason": "Maintenance" },
+                "tenant_id": "acme",
+            })
+        );
+        assert_eq!(
+            envelope.open::<BookingStatusChanged>(),
+            Ok(status_changed())
+        );
+    }
+
+    #[test]
+    fn test_amounts_are_published_as_strings() {
+        let event = VehicleCreated {
+            vehicle_id: "66b1f0c2a1b2c3d4e5f60719".to_string(),
+            brand: Brand::TESLA,
+            price_by_day: Money::new(Decimal::new(8990, 2), Currency::EUR),
+            added_by: "admin_user_1".to_string(),
+            tenant_id: None,
+        };
+        let envelope = EventEnvelope::wrap(&event, Utc::now()).unwrap();
+
+        assert_eq!(
+            envelope.payload["price_by_day"],
+            json!({ "amount": "89.90", "currency": "EUR" })
+        );
+        assert_eq!(envelope.open::<VehicleCreated>(), Ok(event));
+    }
+
+    #[test]
+    fn test_only_known_name_and_version_open() {
+        let mut envelope = EventEnvelope::wrap(&status_changed(), Utc::now()).unwrap();
+        assert!(envelope.is::<BookingStatusChanged>());
+        assert!(envelope.open::<BookingCreated>().is_err());
+
+        envelope.version = 2;
+        assert!(envelope.open::<BookingStatusChanged>().is_err());
+    }
+
+    #[test]
+    fn test_webhook_events_share_their_names() {
+        assert_eq!(EventType::BookingCreated.to_string(), BookingCreated::NAME);
+        assert_eq!(
+            EventType::BookingStatusChanged.to_string(),
+            BookingStatusChanged::NAME
+        );
+        assert_eq!(
+            EventType::BookingReminder.to_string(),
+            BookingReminder::NAME
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use bson::{doc, oid::ObjectId};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::publisher;
+use super::EventEnvelope;
+use crate::error::AppResult;
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// Event stored with the change it belongs to, dispatched to the publishers once the
+/// change is committed. Entries left behind by a crash are dispatched by the relay.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub envelope: EventEnvelope,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub recorded_at: DateTime<Utc>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub dispatched_at: Option<DateTime<Utc>>,
+}
+
+impl MongoStruct for OutboxEntry {
+    fn get_collection() -> &'static str {
+        "event_outbox"
+    }
+}
+
+/// Age after which an entry not dispatched yet is left to the relay, the unit of work
+/// that recorded it having had time to dispatch it
+const RELAY_DELAY_SECONDS: i64 = 60;
+
+/// Seconds between two relay sweeps (EVENT_OUTBOX_INTERVAL_SECS, default 60, 0 disables
+/// the relay)
+fn relay_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("EVENT_OUTBOX_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(60);
+
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// Dispatch the entries left behind periodically in the background
+pub fn spawn_scheduler() {
+    let Some(interval) = relay_interval() else {
+        log::info!("Event outbox relay disabled");
+        return;
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match relay(Utc::now()).await {
+                Ok(0) => {}
+                Ok(relayed) => log::info!("{} outbox events relayed", relayed),
+                Err(error) => log::error!("Event outbox relay failed: {}", error),
+            }
+        }
+    });
+}
+
+/// Store events to dispatch, before the change they belong to is committed
+pub(crate) async fn record(envelopes: Vec<EventEnvelope>) -> AppResult<Vec<OutboxEntry>> {
+    let mut entries = Vec::with_capacity(envelopes.len());
+    for envelope in envelopes {
+        let mut entry = OutboxEntry {
+            id: None,
+            envelope,
+            recorded_at: Utc::now(),
+            dispatched_at: None,
+        };
+        match services::mongodb::insert_one(&entry, None).await {
+            Ok(id) => entry.id = Some(id),
+            Err(error) => {
+                // All or nothing, the change is rolled back
+                discard(&entries).await?;
+                return Err(error);
+            }
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Drop events whose change was rolled back
+pub(crate) async fn discard(entries: &[OutboxEntry]) -> AppResult<()> {
+    let ids: Vec<ObjectId> = entries.iter().filter_map(|entry| entry.id).collect();
+    if !ids.is_empty() {
+        services::mongodb::delete_many(
+            OutboxEntry::get_collection(),
+            doc! { "_id": { "$in": ids } },
+            None,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Hand an entry to the publishers, unless another dispatcher got it first
+pub(crate) async fn dispatch(entry: &OutboxEntry) -> AppResult<bool> {
+    let Some(id) = entry.id else {
+        return Ok(false);
+    };
+    let claimed = services::mongodb::update_one(
+        OutboxEntry::get_collection(),
+        doc! { "_id": id, "dispatched_at": null },
+        doc! { "$set": { "dispatched_at": bson::DateTime::from_chrono(Utc::now()) } },
+        None,
+    )
+    .await?;
+    if claimed.modified_count == 0 {
+        return Ok(false);
+    }
+    publisher::dispatch(&entry.envelope).await;
+    Ok(true)
+}
+
+/// Dispatch the entries recorded more than a minute before `now` and still waiting,
+/// oldest first. Returns the number of entries dispatched.
+pub async fn relay(now: DateTime<Utc>) -> AppResult<usize> {
+    let filter = doc! {
+        "dispatched_at": null,
+        "recorded_at": {
+            "$lt": bson::DateTime::from_chrono(now - Duration::seconds(RELAY_DELAY_SECONDS)),
+        },
+    };
+    let options = mongodb::options::FindOptions::builder()
+        .sort(doc! { "recorded_at": 1 })
+        .build();
+    let entries: Vec<OutboxEntry> = services::mongodb::collect_many(filter, options).await?;
+
+    let mut relayed = 0;
+    for entry in &entries {
+        if dispatch(entry).await? {
+            relayed += 1;
+        }
+    }
+    Ok(relayed)
+}
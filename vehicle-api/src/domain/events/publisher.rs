@@ -0,0 +1,124 @@
+use std::sync::LazyLock;
+
+use bson::oid::ObjectId;
+use chrono::Utc;
+
+use super::{BookingCreated, BookingReminder, BookingStatusChanged, DomainEvent, EventEnvelope};
+use crate::error::{AppError, AppResult};
+use crate::models::{Event, EventType};
+use crate::services;
+
+/// Destination of the domain events
+pub(crate) trait EventPublisher {
+    async fn publish(&self, envelope: &EventEnvelope) -> AppResult<()>;
+}
+
+/// Every event in the log, at debug level
+pub struct LogPublisher;
+
+/// Booking creations, status changes and reminders to the webhook endpoints of their
+/// rental company
+pub struct WebhookPublisher;
+
+/// Publishers an event goes through, in order
+pub enum Publisher {
+    Log(LogPublisher),
+    Webhook(WebhookPublisher),
+}
+
+static PUBLISHERS: LazyLock<Vec<Publisher>> = LazyLock::new(|| {
+    vec![
+        Publisher::Log(LogPublisher),
+        Publisher::Webhook(WebhookPublisher),
+    ]
+});
+
+impl EventPublisher for LogPublisher {
+    async fn publish(&self, envelope: &EventEnvelope) -> AppResult<()> {
+        log::debug!(
+            "Event {} v{} (tenant {:?}): {}",
+            envelope.name,
+            envelope.version,
+            envelope.tenant_id,
+            envelope.payload
+        );
+        Ok(())
+    }
+}
+
+impl EventPublisher for WebhookPublisher {
+    async fn publish(&self, envelope: &EventEnvelope) -> AppResult<()> {
+        let (event_type, booking_id, data) = if envelope.is::<BookingCreated>() {
+            let event: BookingCreated = envelope.open().map_err(AppError::internal_server_error)?;
+            (
+                EventType::BookingCreated,
+                event.booking_id.clone(),
+                event.webhook_data(),
+            )
+        } else if envelope.is::<BookingStatusChanged>() {
+            let event: BookingStatusChanged =
+                envelope.open().map_err(AppError::internal_server_error)?;
+            (
+                EventType::BookingStatusChanged,
+                event.booking_id.clone(),
+                event.webhook_data(),
+            )
+        } else if envelope.is::<BookingReminder>() {
+            let event: BookingReminder =
+                envelope.open().map_err(AppError::internal_server_error)?;
+            (
+                EventType::BookingReminder,
+                event.booking_id.clone(),
+                event.webhook_data(),
+            )
+        } else {
+            return Ok(());
+        };
+
+        let booking_id = ObjectId::parse_str(&booking_id)
+            .map_err(|_| AppError::internal_server_error("Invalid booking ID in event"))?;
+        let event = Event {
+            event_type,
+            occurred_at: envelope.occurred_at,
+            data,
+        };
+        services::webhook::publish(booking_id, envelope.tenant_id.clone(), event).await;
+        Ok(())
+    }
+}
+
+impl EventPublisher for Publisher {
+    async fn publish(&self, envelope: &EventEnvelope) -> AppResult<()> {
+        match self {
+            Self::Log(publisher) => publisher.publish(envelope).await,
+            Self::Webhook(publisher) => publisher.publish(envelope).await,
+        }
+    }
+}
+
+/// Hand an event to every publisher, None (an entity not stored yet) publishes nothing.
+/// Best effort: a failure is logged and never fails the change being published. Changes
+/// made in a `UnitOfWork` record their events there instead.
+pub async fn publish<E: DomainEvent>(event: Option<E>) {
+    let Some(event) = event else {
+        return;
+    };
+    match EventEnvelope::wrap(&event, Utc::now()) {
+        Ok(envelope) => dispatch(&envelope).await,
+        Err(error) => log::error!("{}", error),
+    }
+}
+
+/// Hand an envelope to every publisher, failures are logged
+pub(crate) async fn dispatch(envelope: &EventEnvelope) {
+    for publisher in PUBLISHERS.iter() {
+        if let Err(error) = publisher.publish(envelope).await {
+            log::error!(
+                "Failed to publish {} v{}: {}",
+                envelope.name,
+                envelope.version,
+                error
+            );
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod events;
+pub mod unit_of_work;
+pub use unit_of_work::{Undo, UnitOfWork};
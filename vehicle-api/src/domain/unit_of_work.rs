@@ -0,0 +1,173 @@
+use bson::{doc, oid::ObjectId};
+use chrono::{NaiveDate, Utc};
+
+use crate::domain::events::{outbox, DomainEvent, EventEnvelope};
+use crate::error::AppResult;
+use crate::models::Booking;
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// How to take back a write of a unit of work when a later step fails
+#[derive(Clone, Debug)]
+pub enum Undo {
+    /// Free every day a booking holds
+    ReleaseDays(ObjectId),
+    /// Free the days a booking holds outside these dates
+    ReleaseDaysOutside {
+        booking_id: ObjectId,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+    },
+    /// Delete a booking just inserted
+    DeleteBooking(ObjectId),
+    /// Put a booking back as it was
+    RestoreBooking(Box<Booking>),
+}
+
+/// Boundary of a business operation writing several documents. MongoDB transactions need
+/// a replica set, so each write registers how to undo it instead: the operation either
+/// commits, storing its events in the outbox then dispatching them, or rolls back, undoing
+/// its writes newest first and publishing nothing.
+///
+/// ```ignore
+/// let mut work = UnitOfWork::new();
+/// let result = steps(&mut work).await;
+/// work.finish(result).await
+/// ```
+#[derive(Debug, Default)]
+pub struct UnitOfWork {
+    undo: Vec<Undo>,
+    events: Vec<EventEnvelope>,
+}
+
+impl UnitOfWork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register how to undo the write just made
+    pub fn on_rollback(&mut self, undo: Undo) {
+        self.undo.push(undo);
+    }
+
+    /// Event to publish once the work is committed, None (an entity not stored yet)
+    /// records nothing
+    pub fn record<E: DomainEvent>(&mut self, event: Option<E>) {
+        let Some(event) = event else {
+            return;
+        };
+        match EventEnvelope::wrap(&event, Utc::now()) {
+            Ok(envelope) => self.events.push(envelope),
+            Err(error) => log::error!("{}", error),
+        }
+    }
+
+    /// Events recorded so far, oldest first
+    #[cfg(test)]
+    pub fn events(&self) -> &[EventEnvelope] {
+        &self.events
+    }
+
+    /// Commit the work when its steps succeeded, roll it back otherwise. The error of a
+    /// failed step is returned as is.
+    pub async fn finish<T>(self, result: AppResult<T>) -> AppResult<T> {
+        match result {
+            Ok(value) => {
+                self.commit().await?;
+                Ok(value)
+            }
+            Err(error) => {
+                undo_all(self.undo).await;
+                Err(error)
+            }
+        }
+    }
+
+    /// Store the events, then dispatch them. The writes are undone when the events cannot
+    /// be stored, an event failing to dispatch is left to the outbox relay.
+    async fn commit(self) -> AppResult<()> {
+        let entries = match outbox::record(self.events).await {
+            Ok(entries) => entries,
+            Err(error) => {
+                undo_all(self.undo).await;
+                return Err(error);
+            }
+        };
+        for entry in &entries {
+            if let Err(error) = outbox::dispatch(entry).await {
+                log::error!(
+                    "Event {} left to the outbox relay: {}",
+                    entry.envelope.name,
+                    error
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run the undo steps newest first. Best effort: a failure is logged, the integrity
+/// check reports what is left.
+async fn undo_all(steps: Vec<Undo>) {
+    for step in steps.into_iter().rev() {
+        if let Err(error) = undo(&step).await {
+            log::error!("Failed to roll back ({:?}): {}", step, error);
+        }
+    }
+}
+
+async fn undo(step: &Undo) -> AppResult<()> {
+    match step {
+        Undo::ReleaseDays(booking_id) => services::reservation::release(*booking_id).await,
+        Undo::ReleaseDaysOutside {
+            booking_id,
+            from_date,
+            to_date,
+        } => services::reservation::release_outside(*booking_id, *from_date, *to_date).await,
+        Undo::DeleteBooking(booking_id) => {
+            services::mongodb::delete_one(
+                Booking::get_collection(),
+                doc! { "_id": booking_id },
+                None,
+            )
+            .await
+        }
+        Undo::RestoreBooking(booking) => {
+            services::mongodb::find_one_and_replace(doc! { "_id": booking.id }, &**booking, None)
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::events::BookingCreated;
+    use crate::error::AppError;
+    use crate::models::fixtures::booking;
+
+    #[test]
+    fn test_only_stored_entities_record_events() {
+        let mut work = UnitOfWork::new();
+        let mut booking = booking();
+        work.record(BookingCreated::of(&booking));
+        assert!(work.events().is_empty());
+
+        booking.id = Some(ObjectId::new());
+        work.record(BookingCreated::of(&booking));
+        assert_eq!(work.events().len(), 1);
+        assert!(work.events()[0].is::<BookingCreated>());
+    }
+
+    #[tokio::test]
+    async fn test_finish_returns_the_result_of_the_steps() {
+        assert_eq!(UnitOfWork::new().finish(Ok(3)).await.unwrap(), 3);
+
+        let failed: AppResult<()> = Err(AppError::bad_request("Vehicle not available"));
+        assert!(matches!(
+            UnitOfWork::new().finish(failed).await,
+            Err(AppError::BadRequest { .. })
+        ));
+    }
+}
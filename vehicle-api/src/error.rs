@@ -2,6 +2,7 @@ use actix_web::{body, dev, http::StatusCode};
 use actix_web::{HttpResponse, ResponseError};
 use derive_more::Display;
 use serde::Serialize;
+use vehicle_api_types::ErrorResponse;
 
 #[allow(dead_code)]
 #[derive(Debug, Display, Serialize)]
@@ -16,6 +17,20 @@ pub enum AppError {
     InternalServerError { message: String },
     #[display("Invalid request parameters: {}", message)]
     BadRequest { message: String },
+    #[display("API key expired: {}", message)]
+    ApiKeyExpired { message: String },
+    #[display("Conflict: {}", message)]
+    Conflict { message: String },
+    #[display("Too many requests: {}", message)]
+    TooManyRequests { message: String, retry_after: u64 },
+    #[display("Service unavailable: {}", message)]
+    ServiceUnavailable { message: String, retry_after: u64 },
+    #[display("Gateway timeout: {}", message)]
+    GatewayTimeout { message: String },
+    #[display("Payload too large: {}", message)]
+    PayloadTooLarge { message: String },
+    #[display("Not acceptable: {}", message)]
+    NotAcceptable { message: String },
 }
 
 pub type AppResult<T> = std::result::Result<T, AppError>;
@@ -33,16 +48,34 @@ macro_rules! internal_error {
     }
 }
 
-internal_error!(
-    AppError: std::io::Error,
-    mongodb::error::Error
-);
+internal_error!(AppError: std::io::Error);
 
-#[derive(Serialize)]
-struct ErrorResponse {
-    code: u16,
-    message: String,
-    error_type: String,
+// MongoDB error code of an operation stopped by its max_time (request deadline)
+const MAX_TIME_MS_EXPIRED: i32 = 50;
+
+// MongoDB error code of a write breaking a unique index
+const DUPLICATE_KEY: i32 = 11000;
+
+impl From<mongodb::error::Error> for AppError {
+    fn from(error: mongodb::error::Error) -> Self {
+        match *error.kind {
+            mongodb::error::ErrorKind::Command(ref command)
+                if command.code == MAX_TIME_MS_EXPIRED =>
+            {
+                Self::GatewayTimeout {
+                    message: "Request deadline exceeded".to_string(),
+                }
+            }
+            mongodb::error::ErrorKind::Write(mongodb::error::WriteFailure::WriteError(
+                ref write,
+            )) if write.code == DUPLICATE_KEY => Self::Conflict {
+                message: write.message.clone(),
+            },
+            _ => Self::InternalServerError {
+                message: error.to_string(),
+            },
+        }
+    }
 }
 
 impl ResponseError for AppError {
@@ -55,23 +88,57 @@ impl ResponseError for AppError {
                 actix_web::http::StatusCode::INTERNAL_SERVER_ERROR
             }
             AppError::BadRequest { .. } => actix_web::http::StatusCode::BAD_REQUEST,
+            AppError::ApiKeyExpired { .. } => actix_web::http::StatusCode::UNAUTHORIZED,
+            AppError::Conflict { .. } => actix_web::http::StatusCode::CONFLICT,
+            AppError::TooManyRequests { .. } => actix_web::http::StatusCode::TOO_MANY_REQUESTS,
+            AppError::ServiceUnavailable { .. } => actix_web::http::StatusCode::SERVICE_UNAVAILABLE,
+            AppError::GatewayTimeout { .. } => actix_web::http::StatusCode::GATEWAY_TIMEOUT,
+            AppError::PayloadTooLarge { .. } => actix_web::http::StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::NotAcceptable { .. } => actix_web::http::StatusCode::NOT_ACCEPTABLE,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
-        let status_code = self.status_code();
-        let error_response = ErrorResponse {
-            code: status_code.as_u16(),
-            message: self.to_string(),
-            error_type: format!("{:?}", self),
-        };
+        let mut response = HttpResponse::build(self.status_code());
+        if let AppError::TooManyRequests { retry_after, .. }
+        | AppError::ServiceUnavailable { retry_after, .. } = self
+        {
+            response.insert_header(("Retry-After", retry_after.to_string()));
+        }
 
-        HttpResponse::build(status_code).json(error_response)
+        response.json(self.to_response())
     }
 }
 
 #[allow(dead_code)]
 impl AppError {
+    /// Body of the error response
+    pub fn to_response(&self) -> ErrorResponse {
+        ErrorResponse {
+            code: self.status_code().as_u16(),
+            message: self.to_string(),
+            error_type: self.error_type().to_string(),
+        }
+    }
+
+    /// Variant name, sent as `error_type` so clients can branch on it
+    pub fn error_type(&self) -> &'static str {
+        match self {
+            AppError::NotFound { .. } => "NotFound",
+            AppError::Forbidden { .. } => "Forbidden",
+            AppError::Unauthorized { .. } => "Unauthorized",
+            AppError::InternalServerError { .. } => "InternalServerError",
+            AppError::BadRequest { .. } => "BadRequest",
+            AppError::ApiKeyExpired { .. } => "ApiKeyExpired",
+            AppError::Conflict { .. } => "Conflict",
+            AppError::TooManyRequests { .. } => "TooManyRequests",
+            AppError::ServiceUnavailable { .. } => "ServiceUnavailable",
+            AppError::GatewayTimeout { .. } => "GatewayTimeout",
+            AppError::PayloadTooLarge { .. } => "PayloadTooLarge",
+            AppError::NotAcceptable { .. } => "NotAcceptable",
+        }
+    }
+
     pub fn not_found(message: impl Into<String>) -> Self {
         AppError::NotFound {
             message: message.into(),
@@ -101,6 +168,50 @@ impl AppError {
             message: message.into(),
         }
     }
+
+    pub fn api_key_expired(message: impl Into<String>) -> Self {
+        AppError::ApiKeyExpired {
+            message: message.into(),
+        }
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        AppError::Conflict {
+            message: message.into(),
+        }
+    }
+
+    pub fn too_many_requests(message: impl Into<String>, retry_after: u64) -> Self {
+        AppError::TooManyRequests {
+            message: message.into(),
+            retry_after,
+        }
+    }
+
+    pub fn service_unavailable(message: impl Into<String>, retry_after: u64) -> Self {
+        AppError::ServiceUnavailable {
+            message: message.into(),
+            retry_after,
+        }
+    }
+
+    pub fn gateway_timeout(message: impl Into<String>) -> Self {
+        AppError::GatewayTimeout {
+            message: message.into(),
+        }
+    }
+
+    pub fn payload_too_large(message: impl Into<String>) -> Self {
+        AppError::PayloadTooLarge {
+            message: message.into(),
+        }
+    }
+
+    pub fn not_acceptable(message: impl Into<String>) -> Self {
+        AppError::NotAcceptable {
+            message: message.into(),
+        }
+    }
 }
 
 async fn generic_error_handler<B>(
@@ -117,6 +228,14 @@ where
 
     let body_bytes = body::to_bytes(body).await.ok().unwrap_or_default();
 
+    // Errors raised as AppError are already structured, keep them as they are
+    if let Ok(error_response) = serde_json::from_slice::<ErrorResponse>(&body_bytes) {
+        let response = HttpResponse::build(status_code)
+            .json(error_response)
+            .map_into_right_body();
+        return Ok(dev::ServiceResponse::new(req, response));
+    }
+
     let body_string = if body_bytes.is_empty() {
         "".to_string()
     } else {
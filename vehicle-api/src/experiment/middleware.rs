@@ -0,0 +1,45 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware, Error, HttpMessage, Result,
+};
+
+use crate::authentication::identity::Identity;
+use crate::util::envelope;
+
+// Experiment Middleware using from_fn, must run after api_key_auth_middleware. Handlers
+// read the variants from the ExperimentAssignments request extension.
+pub async fn experiment_middleware(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let Some(identity) = req.extensions().get::<Identity>().cloned() else {
+        return next.call(req).await;
+    };
+    let path = req.path().to_string();
+    let assignments = super::assign(&identity, &path);
+    if assignments.0.is_empty() {
+        return next.call(req).await;
+    }
+    req.extensions_mut().insert(assignments.clone());
+
+    let mut response = next.call(req).await?;
+    if !response.status().is_success() {
+        return Ok(response);
+    }
+    if let Ok(value) = HeaderValue::from_str(&assignments.header_value()) {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static("x-experiments"), value);
+    }
+    envelope::add_meta(response.request(), "experiments", &assignments.0);
+
+    // Exposures are stored in the background, a failure never fails the request
+    actix_web::rt::spawn(async move {
+        if let Err(error) = super::record_exposures(&identity, &path, &assignments).await {
+            log::error!("Failed to record experiment exposures: {}", error);
+        }
+    });
+    Ok(response)
+}
@@ -0,0 +1,115 @@
+pub mod middleware;
+
+use bson::doc;
+use chrono::Utc;
+use mongodb::options::UpdateOptions;
+use std::sync::{LazyLock, RwLock};
+use std::time::Duration;
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{Experiment, ExperimentAssignment, ExperimentAssignments, ExperimentExposure};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+// Copy of the experiments collection, assignments never wait on MongoDB
+static EXPERIMENTS: LazyLock<RwLock<Vec<Experiment>>> = LazyLock::new(|| RwLock::new(Vec::new()));
+
+/// Seconds between two reloads of the experiments (EXPERIMENT_REFRESH_SECS, default 30).
+/// Changes made on another instance take at most this long to apply.
+fn refresh_interval() -> Duration {
+    let seconds = std::env::var("EXPERIMENT_REFRESH_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|seconds| *seconds > 0)
+        .unwrap_or(30);
+
+    Duration::from_secs(seconds)
+}
+
+/// Variants of the experiments running on `path` for the caller
+pub fn assign(identity: &Identity, path: &str) -> ExperimentAssignments {
+    let Ok(experiments) = EXPERIMENTS.read() else {
+        return ExperimentAssignments::default();
+    };
+    let assignments = experiments
+        .iter()
+        .filter(|experiment| experiment.applies_to(path))
+        .filter_map(|experiment| {
+            experiment
+                .assign(&identity.user_id)
+                .map(|variant| ExperimentAssignment {
+                    experiment: experiment.key.clone(),
+                    variant: variant.name.clone(),
+                })
+        })
+        .collect();
+    ExperimentAssignments(assignments)
+}
+
+/// Apply a change made on this instance without waiting for the next reload
+pub fn invalidate(experiment: Experiment) {
+    if let Ok(mut experiments) = EXPERIMENTS.write() {
+        experiments.retain(|current| current.key != experiment.key);
+        experiments.push(experiment);
+    }
+}
+
+/// Reload the experiments from MongoDB, returns how many there are
+pub async fn refresh() -> AppResult<usize> {
+    let loaded: Vec<Experiment> = services::mongodb::collect_many(doc! {}, None).await?;
+
+    let size = loaded.len();
+    if let Ok(mut experiments) = EXPERIMENTS.write() {
+        *experiments = loaded;
+    }
+    Ok(size)
+}
+
+/// Reload the experiments periodically in the background
+pub fn spawn_refresh() {
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(refresh_interval());
+        loop {
+            ticker.tick().await;
+            if let Err(error) = refresh().await {
+                log::error!("Failed to reload the experiments: {}", error);
+            }
+        }
+    });
+}
+
+/// Store the exposure events of a request, only the first exposure of a caller to a
+/// variant is kept
+pub async fn record_exposures(
+    identity: &Identity,
+    path: &str,
+    assignments: &ExperimentAssignments,
+) -> AppResult<()> {
+    for assignment in &assignments.0 {
+        let exposure = ExperimentExposure {
+            id: ExperimentExposure::key(
+                &assignment.experiment,
+                &assignment.variant,
+                &identity.user_id,
+            ),
+            experiment: assignment.experiment.clone(),
+            variant: assignment.variant.clone(),
+            user_id: identity.user_id.clone(),
+            tenant_id: identity.tenant_id.clone(),
+            path: path.to_string(),
+            exposed_at: Utc::now(),
+        };
+        let document = bson::to_document(&exposure).map_err(|e| {
+            AppError::internal_server_error(format!("Cannot serialize document: {}", e))
+        })?;
+        services::mongodb::update_one(
+            ExperimentExposure::get_collection(),
+            doc! { "_id": &exposure.id },
+            doc! { "$setOnInsert": document },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+    }
+    Ok(())
+}
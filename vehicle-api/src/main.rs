@@ -1,7 +1,13 @@
 mod authentication;
+mod chaos;
 mod controllers;
+mod deprecation;
+mod domain;
 mod error;
+mod experiment;
 mod models;
+mod priority;
+mod recording;
 mod routes;
 mod services;
 mod util;
@@ -13,6 +19,7 @@ use actix_web::web::ReqData;
 use actix_web::{http::StatusCode, middleware, web, App, HttpResponse, HttpServer, Result};
 use actix_web_lab::middleware::ErrorHandlers;
 use authentication::middleware::api_key_auth_middleware;
+use authentication::rate_limit::rate_limit_middleware;
 
 use crate::error::{
     bad_request_handler, internal_server_error_handler, not_found_handler, unauthorized_handler,
@@ -47,29 +54,80 @@ async fn get_identity(
     Ok(HttpResponse::Ok().json(identity.into_inner()))
 }
 
+/// MongoDB health, with the number of times the client was replaced
 #[get("/health/mongodb")]
 async fn mongodb_health() -> Result<HttpResponse, actix_web::Error> {
-    match services::mongodb::get_database("vehicle_booking").await {
-        Ok(db) => {
-            // Try to ping the database
-            match db.run_command(mongodb::bson::doc! { "ping": 1 }).await {
-                Ok(_) => Ok(HttpResponse::Ok().json(serde_json::json!({
-                    "status": "healthy",
-                    "message": "MongoDB connection is working"
-                }))),
-                Err(e) => Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
-                    "status": "unhealthy",
-                    "message": format!("MongoDB ping failed: {}", e)
-                }))),
-            }
-        }
-        Err(e) => Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
-            "status": "unhealthy",
-            "message": format!("MongoDB client not available: {}", e)
-        }))),
+    let health = services::mongodb::health::check().await;
+    if health.status == "healthy" {
+        Ok(HttpResponse::Ok().json(health))
+    } else {
+        Ok(HttpResponse::ServiceUnavailable().json(health))
+    }
+}
+
+/// Readiness probe, 503 until the startup warm-up is done
+#[get("/health/ready")]
+async fn readiness() -> Result<HttpResponse, actix_web::Error> {
+    if services::warmup::is_ready() {
+        Ok(HttpResponse::Ok().json(serde_json::json!({ "status": "ready" })))
+    } else {
+        Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+            "status": "warming_up",
+            "message": "Startup warm-up in progress"
+        })))
     }
 }
 
+/// Endpoints answering without authentication
+fn public_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(mongodb_health)
+        .service(readiness)
+        .configure(routes::auth::configure)
+        .configure(routes::email::configure)
+        .configure(routes::meta::configure)
+        .configure(routes::metrics::configure)
+        .configure(routes::webhook::configure);
+}
+
+/// Endpoints of the /protected scope, called with an API key or a session token
+fn protected_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_identity)
+        .configure(routes::accounting::configure)
+        .configure(routes::anomaly::configure)
+        .configure(routes::api_key::configure)
+        .configure(routes::approval::configure)
+        .configure(routes::audit::configure)
+        .configure(routes::backup::configure)
+        .configure(routes::bot::configure)
+        .configure(routes::broadcast::configure)
+        .configure(routes::changeset::configure)
+        .configure(routes::chaos::configure)
+        .configure(routes::comment::configure)
+        .configure(routes::deprecation::configure)
+        .configure(routes::dispute::configure)
+        .configure(routes::experiment::configure)
+        .configure(routes::holiday::configure)
+        .configure(routes::impersonation::configure)
+        .configure(routes::integrity::configure)
+        .configure(routes::lockout::configure)
+        .configure(routes::maintenance::configure)
+        .configure(routes::notification::configure)
+        .configure(routes::office_hours::configure)
+        .configure(routes::payment::configure)
+        .configure(routes::pii::configure)
+        .configure(routes::priority::configure)
+        .configure(routes::quarantine::configure)
+        .configure(routes::recording::configure)
+        .configure(routes::schema::configure)
+        .configure(routes::service_account::configure)
+        .configure(routes::station::configure)
+        .configure(routes::suspension::configure)
+        .configure(routes::vehicle::configure)
+        .configure(routes::webhook_endpoint::configure)
+        .configure(routes::booking::configure)
+        .configure(routes::report::configure);
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv::dotenv().ok();
@@ -93,10 +151,54 @@ async fn main() -> std::io::Result<()> {
         .unwrap_or_else(|_| std::env::var("PORT").unwrap_or_else(|_| String::from("8080")));
 
     println!("Starting Vehicle Booking API on port {}", port);
-    println!("Available API Keys: Admin, CarManager, MotorbikeManager, Customer1, Customer2");
+    println!("API keys are managed with /protected/api-keys (see BOOTSTRAP_ADMIN_API_KEY)");
 
-    HttpServer::new(move || {
+    authentication::permission::load()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    // The jobs write and send, a read-only instance leaves them to the primary region
+    if util::read_only::is_enabled() {
+        log::warn!("Read-only mode: writes are refused and background jobs are not started");
+    } else {
+        services::anomaly::spawn_scheduler();
+        services::accounting::spawn_scheduler();
+        services::backup::spawn_scheduler();
+        services::digest::spawn_scheduler();
+        domain::events::outbox::spawn_scheduler();
+        services::expiration::spawn_scheduler();
+        services::archive::spawn_scheduler();
+        services::integrity::spawn_scheduler();
+        services::reminder::spawn_scheduler();
+        services::webhook::delivery::spawn_scheduler();
+    }
+    authentication::revocation::spawn_refresh();
+    experiment::spawn_refresh();
+    services::mongodb::health::spawn_monitor();
+    services::warmup::spawn();
+
+    let server = HttpServer::new(move || {
         App::new()
+            // Request guards, registered before CORS and logging so those still wrap
+            // the responses the guards answer with
+            .wrap(middleware::Condition::new(
+                priority::is_enabled(),
+                middleware::from_fn(priority::middleware::priority_middleware),
+            ))
+            .wrap(middleware::from_fn(util::deadline::deadline_middleware))
+            .wrap(middleware::from_fn(
+                authentication::captcha::captcha_middleware,
+            ))
+            .wrap(middleware::from_fn(
+                authentication::bot::bot_detection_middleware,
+            ))
+            .wrap(middleware::Condition::new(
+                chaos::is_available(),
+                middleware::from_fn(chaos::middleware::chaos_middleware),
+            ))
+            // Last of the request guards so writes are refused before any other work
+            .wrap(middleware::Condition::new(
+                util::read_only::is_enabled(),
+                middleware::from_fn(util::read_only::read_only_middleware),
+            ))
             .wrap(cors())
             .wrap(middleware::Logger::new(
                 "%{r}a %r %s %b %{Referer}i %{User-Agent}i %T",
@@ -112,21 +214,154 @@ async fn main() -> std::io::Result<()> {
                         internal_server_error_handler,
                     ),
             )
+            .wrap(middleware::from_fn(util::envelope::envelope_middleware))
             .wrap(middleware::Compress::default()) // Error handlers are now before compression
-            .route(
-                "/",
-                web::get().to(|| async { HttpResponse::Ok().json("Vehicle Booking API") }),
-            )
-            .service(mongodb_health)
+            .configure(public_routes)
             .service(
                 web::scope("/protected")
+                    // Registered before the auth middleware so they run after authentication
+                    .wrap(middleware::from_fn(
+                        experiment::middleware::experiment_middleware,
+                    ))
+                    .wrap(middleware::from_fn(
+                        recording::middleware::recording_middleware,
+                    ))
+                    .wrap(middleware::from_fn(
+                        deprecation::middleware::deprecation_middleware,
+                    ))
+                    .wrap(middleware::from_fn(rate_limit_middleware))
                     .wrap(middleware::from_fn(api_key_auth_middleware))
-                    .service(get_identity)
-                    .configure(routes::vehicle::configure)
-                    .configure(routes::booking::configure),
+                    .wrap(middleware::from_fn(
+                        util::pagination::page_size_warning_middleware,
+                    ))
+                    .configure(protected_routes),
             )
     })
-    .bind(format!("0.0.0.0:{}", port))?
-    .run()
-    .await
+    .on_connect(authentication::mtls::on_connect);
+
+    // TLS with optional client certificates when configured, plain HTTP otherwise
+    let address = format!("0.0.0.0:{}", port);
+    match authentication::mtls::TlsConfig::from_env() {
+        Some(tls) => {
+            let config = tls
+                .server_config()
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            println!(
+                "TLS enabled, client certificates {}",
+                match (&tls.client_ca_path, tls.client_cert_required) {
+                    (None, _) => "disabled",
+                    (Some(_), false) => "optional",
+                    (Some(_), true) => "required",
+                }
+            );
+            server.bind_rustls_0_23(address, config)?.run().await
+        }
+        None => server.bind(address)?.run().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::oid::ObjectId;
+    use serde_json::json;
+    use vehicle_api_client::{Client, ClientError, ClientResult, PageQuery};
+
+    /// Whether the server has no route for a request of the client: actix answers 404
+    /// without an error body, or 405 to another method on a known path
+    fn is_unrouted<T>(result: &ClientResult<T>) -> bool {
+        match result {
+            Err(ClientError::Api(error)) => {
+                error.code == 405 || (error.code == 404 && error.error_type == "Unknown")
+            }
+            _ => false,
+        }
+    }
+
+    // Requests fail before reaching the handlers (no identity without the auth
+    // middleware), except public ones which must also decode into the client's types
+    #[actix_web::test]
+    async fn test_client_requests_match_server_routes() {
+        let server = HttpServer::new(|| {
+            App::new()
+                .configure(public_routes)
+                .service(web::scope("/protected").configure(protected_routes))
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let address = server.addrs()[0];
+        let server = server.run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        let client = Client::new(format!("http://{}", address), "Admin");
+        let id = ObjectId::new();
+        let booking = serde_json::from_value(json!({
+            "vehicle_id": id.to_hex(),
+            "from_date": "2025-08-01",
+            "to_date": "2025-08-03",
+        }))
+        .unwrap();
+        let vehicle = serde_json::from_value(json!({
+            "brand": "TESLA",
+            "type": "CAR",
+            "metadata": { "model": "MODEL_3", "seats": 5, "fuel_type": "ELECTRIC", "gearbox": "AUTOMATIC", "engine_cc": 0 },
+            "price_by_day": { "amount": "89.90", "currency": "EUR" },
+            "year_of_production": 2023,
+        }))
+        .unwrap();
+        let update_booking =
+            serde_json::from_value(json!({ "status": { "status": "CONFIRMED" } })).unwrap();
+        let update_vehicle = serde_json::from_value(json!({})).unwrap();
+        let page = PageQuery::default();
+
+        let unrouted = [
+            ("identity", is_unrouted(&client.identity().await)),
+            (
+                "create_booking",
+                is_unrouted(&client.create_booking(&booking).await),
+            ),
+            (
+                "validate_booking",
+                is_unrouted(&client.validate_booking(&booking).await),
+            ),
+            (
+                "list_bookings",
+                is_unrouted(&client.list_bookings(page).await),
+            ),
+            ("get_booking", is_unrouted(&client.get_booking(&id).await)),
+            (
+                "update_booking",
+                is_unrouted(&client.update_booking(&id, &update_booking).await),
+            ),
+            (
+                "create_vehicle",
+                is_unrouted(&client.create_vehicle(&vehicle).await),
+            ),
+            (
+                "list_vehicles",
+                is_unrouted(&client.list_vehicles(&Default::default(), None, page).await),
+            ),
+            ("get_vehicle", is_unrouted(&client.get_vehicle(&id).await)),
+            (
+                "update_vehicle",
+                is_unrouted(&client.update_vehicle(&id, &update_vehicle).await),
+            ),
+            (
+                "list_vehicle_bookings",
+                is_unrouted(&client.list_vehicle_bookings(&id, page).await),
+            ),
+            ("schema", is_unrouted(&client.schema("unknown").await)),
+        ];
+        for (method, unrouted) in unrouted {
+            assert!(!unrouted, "Client::{} has no matching server route", method);
+        }
+
+        assert!(!client.event_schemas().await.unwrap().is_empty());
+        let names = client.schema_names().await.unwrap();
+        assert!(client.schema(&names[0]).await.is_ok());
+
+        handle.stop(false).await;
+    }
 }
@@ -0,0 +1,185 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::models::MAX_REPORT_DAYS;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, EnumString, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+#[strum(serialize_all = "UPPERCASE", ascii_case_insensitive)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Datev, // DATEV Buchungsstapel (EXTF), for German accounting
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExportStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Periods exported by the scheduled job
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum ExportPeriod {
+    Daily,
+    Monthly,
+}
+
+// =============================================================================
+// MAIN ACCOUNTING EXPORT STRUCT
+// =============================================================================
+
+/// File of the ledger entries recorded over a period, delivered to the storage
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccountingExport {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub format: ExportFormat,
+    pub from: NaiveDate, // Both days included
+    pub to: NaiveDate,
+    pub status: ExportStatus,
+    /// User who asked for it, None for the scheduled job
+    pub requested_by: Option<String>,
+    pub entries: u32,
+    /// Where the file was delivered
+    pub location: Option<String>,
+    pub error: Option<String>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub started_at: DateTime<Utc>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CreateExportRequest {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub format: ExportFormat,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for AccountingExport {
+    fn get_collection() -> &'static str {
+        "accounting_exports"
+    }
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv | ExportFormat::Datev => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv | ExportFormat::Datev => "text/csv",
+            ExportFormat::Json => "application/json",
+        }
+    }
+}
+
+impl ExportPeriod {
+    /// Last period fully over on `today`: yesterday, or the previous month
+    pub fn last_completed(&self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let yesterday = today - Duration::days(1);
+        match self {
+            ExportPeriod::Daily => (yesterday, yesterday),
+            ExportPeriod::Monthly => {
+                let to = today.with_day(1).unwrap_or(today) - Duration::days(1);
+                (to.with_day(1).unwrap_or(to), to)
+            }
+        }
+    }
+}
+
+impl CreateExportRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.from > self.to {
+            return Err("from must be before or equal to to".to_string());
+        }
+        if (self.to - self.from).num_days() >= MAX_REPORT_DAYS {
+            return Err(format!("An export covers at most {} days", MAX_REPORT_DAYS));
+        }
+        Ok(())
+    }
+}
+
+impl AccountingExport {
+    pub fn new(request: CreateExportRequest, requested_by: Option<String>) -> Self {
+        Self {
+            id: None,
+            format: request.format,
+            from: request.from,
+            to: request.to,
+            status: ExportStatus::Running,
+            requested_by,
+            entries: 0,
+            location: None,
+            error: None,
+            started_at: Utc::now(),
+            finished_at: None,
+        }
+    }
+
+    /// Storage key of the file, unique per export
+    pub fn key(&self) -> String {
+        format!(
+            "accounting/{}_{}_{}.{}",
+            self.from,
+            self.to,
+            self.id.map(|id| id.to_hex()).unwrap_or_default(),
+            self.format.extension()
+        )
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_last_completed_period() {
+        assert_eq!(
+            ExportPeriod::Daily.last_completed(date(2025, 3, 1)),
+            (date(2025, 2, 28), date(2025, 2, 28))
+        );
+        assert_eq!(
+            ExportPeriod::Monthly.last_completed(date(2025, 3, 17)),
+            (date(2025, 2, 1), date(2025, 2, 28))
+        );
+        assert_eq!(
+            ExportPeriod::Monthly.last_completed(date(2025, 1, 1)),
+            (date(2024, 12, 1), date(2024, 12, 31))
+        );
+    }
+}
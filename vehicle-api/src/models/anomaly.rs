@@ -0,0 +1,259 @@
+use std::collections::{HashMap, HashSet};
+
+use bson::oid::ObjectId;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use crate::models::{Booking, BookingStatus, Vehicle};
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(
+    Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum AnomalyKind {
+    BookingSpike,     // Many bookings from one customer in the window
+    CreateCancelLoop, // Bookings created then cancelled over and over by one customer
+    IdleVehicle,      // Vehicle in the fleet that has never been booked
+}
+
+// =============================================================================
+// MAIN ANOMALY STRUCT
+// =============================================================================
+
+/// A suspicious booking pattern, kept open until an admin acknowledges it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Anomaly {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub kind: AnomalyKind,
+    pub subject: String, // Customer ID, or vehicle ID for idle vehicles
+    pub message: String,
+    pub booking_ids: Vec<ObjectId>, // Evidence
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub detected_at: DateTime<Utc>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub acknowledged_by: Option<String>,
+}
+
+/// Detection thresholds, read from the environment
+#[derive(Clone, Debug, Serialize)]
+pub struct AnomalyThresholds {
+    pub window_hours: i64,
+    pub spike_bookings: usize,
+    pub cancelled_bookings: usize,
+    pub idle_days: i64,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AnomalyFilters {
+    pub kind: Option<AnomalyKind>,
+    #[serde(default)]
+    pub include_acknowledged: bool,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Anomaly {
+    fn get_collection() -> &'static str {
+        "anomalies"
+    }
+}
+
+impl Default for AnomalyThresholds {
+    fn default() -> Self {
+        Self {
+            window_hours: 24,
+            spike_bookings: 5,
+            cancelled_bookings: 3,
+            idle_days: 30,
+        }
+    }
+}
+
+impl AnomalyThresholds {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default)
+        }
+
+        let default = Self::default();
+        Self {
+            window_hours: var("ANOMALY_WINDOW_HOURS", default.window_hours),
+            spike_bookings: var("ANOMALY_SPIKE_BOOKINGS", default.spike_bookings),
+            cancelled_bookings: var("ANOMALY_CANCELLED_BOOKINGS", default.cancelled_bookings),
+            idle_days: var("ANOMALY_IDLE_DAYS", default.idle_days),
+        }
+    }
+}
+
+impl Anomaly {
+    fn new(
+        kind: AnomalyKind,
+        subject: String,
+        message: String,
+        booking_ids: Vec<ObjectId>,
+    ) -> Self {
+        Self {
+            id: None,
+            kind,
+            subject,
+            message,
+            booking_ids,
+            detected_at: Utc::now(),
+            acknowledged_at: None,
+            acknowledged_by: None,
+        }
+    }
+
+    /// Flag anomalies in the bookings ordered during the window and in the fleet.
+    /// `booked_vehicle_ids` holds every vehicle that has ever been booked.
+    pub fn detect(
+        recent_bookings: &[Booking],
+        vehicles: &[Vehicle],
+        booked_vehicle_ids: &HashSet<ObjectId>,
+        thresholds: &AnomalyThresholds,
+        now: DateTime<Utc>,
+    ) -> Vec<Self> {
+        let window_start = now - Duration::hours(thresholds.window_hours);
+
+        let mut by_customer: HashMap<&str, Vec<&Booking>> = HashMap::new();
+        for booking in recent_bookings
+            .iter()
+            .filter(|booking| booking.order_date >= window_start)
+        {
+            by_customer
+                .entry(booking.customer_id.as_str())
+                .or_default()
+                .push(booking);
+        }
+
+        let mut anomalies = Vec::new();
+        for (customer_id, bookings) in by_customer {
+            if bookings.len() >= thresholds.spike_bookings {
+                anomalies.push(Self::new(
+                    AnomalyKind::BookingSpike,
+                    customer_id.to_string(),
+                    format!(
+                        "{} bookings in the last {} hours",
+                        bookings.len(),
+                        thresholds.window_hours
+                    ),
+                    bookings.iter().filter_map(|booking| booking.id).collect(),
+                ));
+            }
+
+            let cancelled: Vec<ObjectId> = bookings
+                .iter()
+                .filter(|booking| matches!(booking.status, BookingStatus::Cancelled(_)))
+                .filter_map(|booking| booking.id)
+                .collect();
+            if cancelled.len() >= thresholds.cancelled_bookings {
+                anomalies.push(Self::new(
+                    AnomalyKind::CreateCancelLoop,
+                    customer_id.to_string(),
+                    format!(
+                        "{} bookings created then cancelled in the last {} hours",
+                        cancelled.len(),
+                        thresholds.window_hours
+                    ),
+                    cancelled,
+                ));
+            }
+        }
+
+        let idle_since = now - Duration::days(thresholds.idle_days);
+        for vehicle in vehicles {
+            let Some(vehicle_id) = vehicle.id else {
+                continue;
+            };
+            if vehicle.added_at <= idle_since && !booked_vehicle_ids.contains(&vehicle_id) {
+                anomalies.push(Self::new(
+                    AnomalyKind::IdleVehicle,
+                    vehicle_id.to_hex(),
+                    format!(
+                        "Never booked since it was added on {}",
+                        vehicle.added_at.date_naive()
+                    ),
+                    Vec::new(),
+                ));
+            }
+        }
+
+        anomalies.sort_by(|a, b| (a.kind, &a.subject).cmp(&(b.kind, &b.subject)));
+        anomalies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures;
+
+    fn booking(customer_id: &str, status: BookingStatus, hours_ago: i64) -> Booking {
+        let mut booking = fixtures::booking();
+        booking.customer_id = customer_id.to_string();
+        booking.id = Some(ObjectId::new());
+        booking.status = status;
+        booking.order_date = Utc::now() - Duration::hours(hours_ago);
+        booking
+    }
+
+    #[test]
+    fn test_detect_spike_and_create_cancel_loop() {
+        let thresholds = AnomalyThresholds {
+            spike_bookings: 4,
+            cancelled_bookings: 2,
+            ..AnomalyThresholds::default()
+        };
+        let cancelled = || BookingStatus::Cancelled("Changed plans".to_string());
+        let bookings = vec![
+            booking("customer_user_1", BookingStatus::Pending, 1),
+            booking("customer_user_1", BookingStatus::Pending, 2),
+            booking("customer_user_1", cancelled(), 3),
+            booking("customer_user_1", cancelled(), 4),
+            booking("customer_user_2", cancelled(), 5),
+            // Outside the window
+            booking("customer_user_2", cancelled(), 48),
+        ];
+
+        let anomalies = Anomaly::detect(&bookings, &[], &HashSet::new(), &thresholds, Utc::now());
+
+        let found: Vec<_> = anomalies
+            .iter()
+            .map(|anomaly| {
+                (
+                    anomaly.kind,
+                    anomaly.subject.as_str(),
+                    anomaly.booking_ids.len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (AnomalyKind::BookingSpike, "customer_user_1", 4),
+                (AnomalyKind::CreateCancelLoop, "customer_user_1", 2),
+            ]
+        );
+    }
+}
@@ -0,0 +1,393 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Duration, Utc};
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use validator::Validate;
+
+use crate::authentication::identity::{Identity, Role};
+
+// =============================================================================
+// MAIN API KEY STRUCT
+// =============================================================================
+
+/// An API key as stored in MongoDB. Only the SHA-256 hash of the key is kept.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApiKey {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    pub key_hash: String,
+    pub prefix: String, // First characters of the key, to recognize it in listings
+    pub role: Role,
+    pub user_id: String,
+    pub created_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub revoked_at: Option<DateTime<Utc>>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub rotated_from: Option<ObjectId>, // Key replaced by this one
+    /// Networks (CIDR or single IP) the key can be used from, anywhere when empty
+    #[serde(default)]
+    pub allowed_networks: Vec<String>,
+    /// Rental company the key acts for, None for deployment wide keys
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+/// Hash of a revoked or compromised key. Every instance keeps the list in memory
+/// and rejects these keys before any other check.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApiKeyRevocation {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub key_hash: String,
+    pub prefix: Option<String>, // Unknown when only the hash was reported
+    pub reason: Option<String>,
+    pub revoked_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub revoked_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct CreateApiKeyRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub role: Role,
+    #[validate(length(min = 1, max = 100))]
+    pub user_id: String,
+    /// Lifetime of the key, never expires when omitted
+    #[validate(range(min = 1, max = 3650))]
+    pub expires_in_days: Option<i64>,
+    /// Networks the key is restricted to, e.g. ["203.0.113.0/24", "2001:db8::1"]
+    #[serde(default)]
+    #[validate(length(max = 50))]
+    pub allowed_networks: Vec<String>,
+    /// Rental company of the key, the creator's own when omitted
+    #[validate(length(min = 1, max = 100))]
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct UpdateAllowedNetworksRequest {
+    /// Replaces the current list, empty lifts the restriction
+    #[validate(length(max = 50))]
+    pub allowed_networks: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Validate)]
+pub struct RotateApiKeyRequest {
+    /// How long the old key keeps working, 24 hours when omitted (0 revokes it now)
+    #[validate(range(min = 0, max = 720))]
+    pub grace_period_hours: Option<i64>,
+}
+
+/// Blacklist a leaked key, by its value or its SHA-256 hash, stored or not
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct RevokeApiKeyRequest {
+    pub key: Option<String>,
+    pub key_hash: Option<String>,
+    #[validate(length(max = 500))]
+    pub reason: Option<String>,
+}
+
+/// API key as returned by the API, never includes the hash
+#[derive(Clone, Debug, Serialize)]
+pub struct ApiKeyResponse {
+    pub id: Option<String>,
+    pub name: String,
+    pub prefix: String,
+    pub role: Role,
+    pub user_id: String,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub rotated_from: Option<String>,
+    pub allowed_networks: Vec<String>,
+    pub tenant_id: Option<String>,
+}
+
+/// Returned once on creation, the plain key cannot be retrieved afterwards
+#[derive(Clone, Debug, Serialize)]
+pub struct CreatedApiKeyResponse {
+    pub key: String,
+    #[serde(flatten)]
+    pub api_key: ApiKeyResponse,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for ApiKey {
+    fn get_collection() -> &'static str {
+        "api_keys"
+    }
+}
+
+impl crate::services::mongodb::MongoStruct for ApiKeyRevocation {
+    fn get_collection() -> &'static str {
+        "api_key_revocations"
+    }
+}
+
+impl ApiKey {
+    pub fn new(request: CreateApiKeyRequest, key: &str, created_by: String) -> Self {
+        let created_at = Utc::now();
+        Self {
+            id: None,
+            name: request.name,
+            key_hash: crate::authentication::api_key::hash_key(key),
+            prefix: key.chars().take(10).collect(),
+            role: request.role,
+            user_id: request.user_id,
+            created_by,
+            created_at,
+            revoked_at: None,
+            expires_at: request
+                .expires_in_days
+                .map(|days| created_at + Duration::days(days)),
+            rotated_from: None,
+            allowed_networks: request.allowed_networks,
+            tenant_id: request.tenant_id,
+        }
+    }
+
+    /// Replacement key with the same identity and lifetime
+    pub fn rotate(&self, key: &str, created_by: String) -> Self {
+        let request = CreateApiKeyRequest {
+            name: self.name.clone(),
+            role: self.role.clone(),
+            user_id: self.user_id.clone(),
+            expires_in_days: None,
+            allowed_networks: self.allowed_networks.clone(),
+            tenant_id: self.tenant_id.clone(),
+        };
+        let mut rotated = Self::new(request, key, created_by);
+        rotated.expires_at = self
+            .expires_at
+            .map(|expires_at| rotated.created_at + (expires_at - self.created_at));
+        rotated.rotated_from = self.id;
+        rotated
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Whether the key can be used from an address, unknown addresses only pass
+    /// keys without restriction
+    pub fn allows(&self, ip: Option<IpAddr>) -> bool {
+        if self.allowed_networks.is_empty() {
+            return true;
+        }
+        ip.is_some_and(|ip| {
+            self.allowed_networks
+                .iter()
+                .filter_map(|network| parse_network(network).ok())
+                .any(|network| network.contains(&ip))
+        })
+    }
+
+    pub fn identity(&self) -> Identity {
+        Identity {
+            role: self.role.clone(),
+            user_id: self.user_id.clone(),
+            email: None,
+            tenant_id: self.tenant_id.clone(),
+            permissions: Vec::new(),
+            profile: None,
+            impersonated_by: None,
+        }
+    }
+}
+
+impl ApiKeyRevocation {
+    pub fn new(
+        key_hash: String,
+        prefix: Option<String>,
+        reason: Option<String>,
+        revoked_by: String,
+    ) -> Self {
+        Self {
+            id: None,
+            key_hash,
+            prefix,
+            reason,
+            revoked_by,
+            revoked_at: Utc::now(),
+        }
+    }
+}
+
+impl RevokeApiKeyRequest {
+    /// Hash to blacklist, from the key when given
+    pub fn key_hash(&self) -> Result<String, String> {
+        match (&self.key, &self.key_hash) {
+            (Some(key), None) if !key.trim().is_empty() => {
+                Ok(crate::authentication::api_key::hash_key(key.trim()))
+            }
+            (None, Some(hash))
+                if hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) =>
+            {
+                Ok(hash.to_lowercase())
+            }
+            (None, Some(_)) => Err("key_hash must be a hex encoded SHA-256 hash".to_string()),
+            _ => Err("Exactly one of key or key_hash is required".to_string()),
+        }
+    }
+
+    /// Prefix to recognize the key in listings, never the whole key
+    pub fn prefix(&self) -> Option<String> {
+        self.key
+            .as_ref()
+            .map(|key| key.trim().chars().take(10).collect())
+    }
+}
+
+/// CIDR network, a single IP is a network of one address
+pub fn parse_network(value: &str) -> Result<IpNet, String> {
+    let value = value.trim();
+    value
+        .parse::<IpNet>()
+        .or_else(|_| value.parse::<IpAddr>().map(IpNet::from))
+        .map_err(|_| format!("{} is not a valid CIDR network or IP address", value))
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(api_key: ApiKey) -> Self {
+        Self {
+            id: api_key.id.map(|id| id.to_hex()),
+            name: api_key.name,
+            prefix: api_key.prefix,
+            role: api_key.role,
+            user_id: api_key.user_id,
+            created_by: api_key.created_by,
+            created_at: api_key.created_at,
+            revoked_at: api_key.revoked_at,
+            expires_at: api_key.expires_at,
+            rotated_from: api_key.rotated_from.map(|id| id.to_hex()),
+            allowed_networks: api_key.allowed_networks,
+            tenant_id: api_key.tenant_id,
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_never_contains_hash() {
+        let request = CreateApiKeyRequest {
+            name: "Front desk".to_string(),
+            role: Role::CarManager,
+            user_id: "front_desk".to_string(),
+            expires_in_days: None,
+            allowed_networks: Vec::new(),
+            tenant_id: None,
+        };
+        let api_key = ApiKey::new(request, "vk_abcdefghijklmnop", "Admin".to_string());
+        assert_eq!(api_key.prefix, "vk_abcdefg");
+
+        let json = serde_json::to_value(ApiKeyResponse::from(api_key)).unwrap();
+        assert!(json.get("key_hash").is_none());
+        assert_eq!(json["role"], "CarManager");
+    }
+
+    #[test]
+    fn test_rotation_keeps_identity_and_lifetime() {
+        let request = CreateApiKeyRequest {
+            name: "Partner".to_string(),
+            role: Role::Customer,
+            user_id: "customer_user_1".to_string(),
+            expires_in_days: Some(90),
+            allowed_networks: Vec::new(),
+            tenant_id: None,
+        };
+        let mut original = ApiKey::new(request, "vk_original", "Admin".to_string());
+        original.id = Some(ObjectId::new());
+
+        let rotated = original.rotate("vk_rotated", "Admin".to_string());
+
+        assert_eq!(rotated.user_id, original.user_id);
+        assert_eq!(rotated.rotated_from, original.id);
+        assert_eq!(
+            rotated.expires_at.unwrap() - rotated.created_at,
+            Duration::days(90)
+        );
+        assert_ne!(rotated.key_hash, original.key_hash);
+        assert!(!rotated.is_expired(Utc::now()));
+        assert!(rotated.is_expired(Utc::now() + Duration::days(91)));
+    }
+
+    #[test]
+    fn test_revoke_request_hash() {
+        let by_key = RevokeApiKeyRequest {
+            key: Some("vk_leaked_key".to_string()),
+            key_hash: None,
+            reason: None,
+        };
+        let hash = by_key.key_hash().unwrap();
+        assert_eq!(
+            hash,
+            crate::authentication::api_key::hash_key("vk_leaked_key")
+        );
+        assert_eq!(by_key.prefix().as_deref(), Some("vk_leaked_"));
+
+        let by_hash = RevokeApiKeyRequest {
+            key: None,
+            key_hash: Some(hash.to_uppercase()),
+            reason: None,
+        };
+        assert_eq!(by_hash.key_hash().unwrap(), hash);
+        assert!(by_hash.prefix().is_none());
+
+        let both = RevokeApiKeyRequest {
+            key: by_key.key.clone(),
+            ..by_hash
+        };
+        assert!(both.key_hash().is_err());
+    }
+
+    #[test]
+    fn test_allowed_networks() {
+        let request = CreateApiKeyRequest {
+            name: "Partner".to_string(),
+            role: Role::Customer,
+            user_id: "customer_user_1".to_string(),
+            expires_in_days: None,
+            allowed_networks: vec!["203.0.113.0/24".to_string(), "2001:db8::1".to_string()],
+            tenant_id: None,
+        };
+        let mut api_key = ApiKey::new(request, "vk_partner", "Admin".to_string());
+
+        let ip = |value: &str| Some(value.parse::<IpAddr>().unwrap());
+        assert!(api_key.allows(ip("203.0.113.42")));
+        assert!(api_key.allows(ip("2001:db8::1")));
+        assert!(!api_key.allows(ip("198.51.100.7")));
+        assert!(!api_key.allows(None));
+
+        api_key.allowed_networks.clear();
+        assert!(api_key.allows(None));
+        assert!(parse_network("10.0.0.0/33").is_err());
+    }
+}
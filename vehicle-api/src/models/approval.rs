@@ -0,0 +1,200 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::authentication::identity::Role;
+use crate::models::{Booking, BookingStatus, OfficeCalendar, VehicleType};
+
+/// Hours a booking may wait for approval (APPROVAL_SLA_HOURS overrides it)
+pub const DEFAULT_APPROVAL_SLA_HOURS: i64 = 24;
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// A pending booking waiting for a manager
+#[derive(Clone, Debug, Serialize)]
+pub struct ApprovalItem {
+    pub booking: Booking,
+    pub vehicle_type: VehicleType,
+    pub waiting_minutes: i64, // Office time only where the branch has office hours
+    pub sla_breached: bool,
+    pub outside_office_hours: bool, // Pickup on a day or time the branch is not staffed
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct RejectBookingRequest {
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ApprovalMetricsQuery {
+    pub days: Option<i64>, // Decisions of the last days, 30 by default
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct ApprovalMetrics {
+    pub sla_hours: i64,
+    pub pending: usize,
+    pub pending_sla_breached: usize,
+    pub decided: usize, // Bookings confirmed or rejected by a manager in the period
+    pub decided_sla_breached: usize,
+    pub average_approval_minutes: Option<f64>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+/// SLA configured with APPROVAL_SLA_HOURS
+pub fn approval_sla() -> Duration {
+    let hours = std::env::var("APPROVAL_SLA_HOURS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_APPROVAL_SLA_HOURS);
+
+    Duration::hours(hours)
+}
+
+impl VehicleType {
+    /// Vehicle type a role manages, None for every type
+    pub fn managed_by(role: &Role) -> Option<Self> {
+        match role {
+            Role::CarManager => Some(VehicleType::Car),
+            Role::MotorbikeManager => Some(VehicleType::Motorbike),
+            Role::Admin | Role::Customer | Role::Service => None,
+        }
+    }
+
+    /// Manager role handling the bookings of the type
+    pub fn manager(&self) -> Role {
+        match self {
+            VehicleType::Car => Role::CarManager,
+            VehicleType::Motorbike => Role::MotorbikeManager,
+        }
+    }
+}
+
+impl ApprovalItem {
+    pub fn new(
+        booking: Booking,
+        vehicle_type: VehicleType,
+        sla: Duration,
+        calendar: &OfficeCalendar,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let tenant_id = booking.tenant_id.as_deref();
+        let waiting = calendar.elapsed(tenant_id, &booking.timezone, booking.order_date, now);
+        let outside_office_hours =
+            !calendar.allows_pickup(tenant_id, &booking.timezone, booking.from_date, now);
+        Self {
+            booking,
+            vehicle_type,
+            waiting_minutes: waiting.num_minutes(),
+            sla_breached: waiting > sla,
+            outside_office_hours,
+        }
+    }
+}
+
+/// Time between creation and the first confirmation or rejection, taken from the
+/// booking history, in office time where the branch has office hours. None while
+/// pending, for bookings without history and for bookings confirmed automatically.
+pub fn approval_time(booking: &Booking, calendar: &OfficeCalendar) -> Option<Duration> {
+    booking
+        .history
+        .iter()
+        .find(|entry| {
+            matches!(
+                entry.status,
+                BookingStatus::Confirmed | BookingStatus::Rejected(_)
+            )
+        })
+        .filter(|entry| entry.rule.is_none())
+        .map(|entry| {
+            calendar.elapsed(
+                booking.tenant_id.as_deref(),
+                &booking.timezone,
+                booking.order_date,
+                entry.changed_at,
+            )
+        })
+}
+
+impl ApprovalMetrics {
+    pub fn compute(
+        pending: &[ApprovalItem],
+        decided: &[Booking],
+        sla: Duration,
+        calendar: &OfficeCalendar,
+    ) -> Self {
+        let approval_times: Vec<Duration> = decided
+            .iter()
+            .filter_map(|booking| approval_time(booking, calendar))
+            .collect();
+        let average_approval_minutes = (!approval_times.is_empty()).then(|| {
+            approval_times
+                .iter()
+                .map(|time| time.num_seconds() as f64 / 60.0)
+                .sum::<f64>()
+                / approval_times.len() as f64
+        });
+
+        Self {
+            sla_hours: sla.num_hours(),
+            pending: pending.len(),
+            pending_sla_breached: pending.iter().filter(|item| item.sla_breached).count(),
+            decided: approval_times.len(),
+            decided_sla_breached: approval_times.iter().filter(|time| **time > sla).count(),
+            average_approval_minutes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures;
+
+    fn booking(created_hours_ago: i64) -> Booking {
+        let mut booking = fixtures::booking();
+        booking.order_date = Utc::now() - Duration::hours(created_hours_ago);
+        booking
+    }
+
+    fn decided(created_hours_ago: i64, decided_after_hours: i64, rule: Option<&str>) -> Booking {
+        let mut booking = booking(created_hours_ago);
+        booking.set_status(
+            BookingStatus::Confirmed,
+            "car_manager".to_string(),
+            rule.map(str::to_string),
+        );
+        booking.history.last_mut().unwrap().changed_at =
+            booking.order_date + Duration::hours(decided_after_hours);
+        booking
+    }
+
+    #[test]
+    fn test_approval_metrics() {
+        let sla = Duration::hours(24);
+        let calendar = OfficeCalendar::default();
+        let now = Utc::now();
+        let pending = vec![
+            ApprovalItem::new(booking(30), VehicleType::Car, sla, &calendar, now),
+            ApprovalItem::new(booking(2), VehicleType::Car, sla, &calendar, now),
+        ];
+        let decided = vec![
+            decided(100, 2, None),
+            decided(100, 30, None),
+            // Auto-confirmed bookings did not wait for anyone
+            decided(100, 0, Some("vip")),
+        ];
+
+        let metrics = ApprovalMetrics::compute(&pending, &decided, sla, &calendar);
+
+        assert_eq!(metrics.pending, 2);
+        assert_eq!(metrics.pending_sla_breached, 1);
+        assert_eq!(metrics.decided, 2);
+        assert_eq!(metrics.decided_sla_breached, 1);
+        assert_eq!(metrics.average_approval_minutes, Some(16.0 * 60.0));
+    }
+}
@@ -0,0 +1,300 @@
+use bson::oid::ObjectId;
+use bson::Document;
+use chrono::{DateTime, Utc};
+use mongodb::options::FindOptions;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use crate::authentication::identity::{Identity, Role};
+use crate::models::{ToBsonFilter, ToFindOptions};
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuthOutcome {
+    Success,
+    Failure,
+}
+
+/// Credential the caller presented
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuthMethod {
+    ApiKey,
+    Session,
+    Signature,
+    Certificate,
+    None, // No credential at all
+}
+
+/// Change to data made by an admin
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuditAction {
+    BookingDeleted,
+    VehiclePriceAdjusted, // Bulk price adjustment, one entry per vehicle
+    VehicleDeleted,       // Soft delete, with the vehicle as it was
+    VehicleStatusChanged, // Maintenance or retirement, with the previous status and the reason
+    ChangesetRolledBack,  // One entry per rollback, with the documents restored and skipped
+}
+
+// =============================================================================
+// MAIN AUDIT STRUCT
+// =============================================================================
+
+/// One authentication attempt on a protected route
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuthAuditEntry {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub outcome: AuthOutcome,
+    pub method: AuthMethod,
+    pub credential_prefix: Option<String>, // Never the full credential
+    pub user_id: Option<String>,
+    pub role: Option<Role>,
+    #[serde(default)]
+    pub impersonated_by: Option<String>, // Admin behind an impersonation token
+    pub ip: Option<String>,
+    pub http_method: String,
+    pub path: String,
+    pub reason: Option<String>, // Why a failed attempt was rejected
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub at: DateTime<Utc>,
+}
+
+/// An admin action, with the state it replaced or destroyed
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ActionAuditEntry {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub action: AuditAction,
+    pub user_id: String,
+    pub role: Role,
+    #[serde(default)]
+    pub impersonated_by: Option<String>,
+    pub tenant_id: Option<String>,
+    pub resource_id: String,
+    /// Document as it was before the action, e.g. the deleted booking, or the change made
+    pub snapshot: Option<Document>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub at: DateTime<Utc>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AuthAuditFilters {
+    pub outcome: Option<AuthOutcome>,
+    pub method: Option<AuthMethod>,
+    pub user_id: Option<String>,
+    pub impersonated_by: Option<String>,
+    pub ip: Option<String>,
+    pub credential_prefix: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// A page of the audit log, newest attempts first
+#[derive(Clone, Debug, Serialize)]
+pub struct AuthAuditPage {
+    pub entries: Vec<AuthAuditEntry>,
+    pub page: i64,
+    pub limit: i64,
+    pub total: u64,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for AuthAuditEntry {
+    fn get_collection() -> &'static str {
+        "auth_audit"
+    }
+}
+
+impl crate::services::mongodb::MongoStruct for ActionAuditEntry {
+    fn get_collection() -> &'static str {
+        "action_audit"
+    }
+}
+
+impl ActionAuditEntry {
+    pub fn new(
+        action: AuditAction,
+        identity: &Identity,
+        resource_id: impl Into<String>,
+        snapshot: Option<Document>,
+    ) -> Self {
+        Self {
+            id: None,
+            action,
+            user_id: identity.user_id.clone(),
+            role: identity.role.clone(),
+            impersonated_by: identity.impersonated_by.clone(),
+            tenant_id: identity.tenant_id.clone(),
+            resource_id: resource_id.into(),
+            snapshot,
+            at: Utc::now(),
+        }
+    }
+}
+
+impl AuthAuditEntry {
+    pub fn new(
+        method: AuthMethod,
+        credential: Option<&str>,
+        result: Result<&Identity, String>,
+        ip: Option<String>,
+        http_method: &str,
+        path: &str,
+    ) -> Self {
+        let (outcome, identity, reason) = match result {
+            Ok(identity) => (AuthOutcome::Success, Some(identity), None),
+            Err(reason) => (AuthOutcome::Failure, None, Some(reason)),
+        };
+
+        Self {
+            id: None,
+            outcome,
+            method,
+            credential_prefix: credential.map(|credential| credential.chars().take(10).collect()),
+            user_id: identity.map(|identity| identity.user_id.clone()),
+            role: identity.map(|identity| identity.role.clone()),
+            impersonated_by: identity.and_then(|identity| identity.impersonated_by.clone()),
+            ip,
+            http_method: http_method.to_string(),
+            path: path.to_string(),
+            reason,
+            at: Utc::now(),
+        }
+    }
+}
+
+impl AuthAuditFilters {
+    fn page_query(&self) -> PageQuery {
+        PageQuery::new(self.page, self.limit)
+    }
+
+    pub fn page(&self) -> i64 {
+        self.page_query().page()
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.page_query().limit()
+    }
+}
+
+impl ToBsonFilter for AuthAuditFilters {
+    fn to_bson_filter(&self) -> Document {
+        let mut filter = Document::new();
+        let builder = services::mongodb::QueryBuilder::new();
+
+        builder.add_string_filter(&mut filter, "outcome", &self.outcome.map(|o| vec![o]));
+        builder.add_string_filter(&mut filter, "method", &self.method.map(|m| vec![m]));
+        builder.add_filter(
+            &mut filter,
+            "user_id",
+            &self.user_id.clone().map(|u| vec![u]),
+        );
+        builder.add_filter(
+            &mut filter,
+            "impersonated_by",
+            &self.impersonated_by.clone().map(|admin| vec![admin]),
+        );
+        builder.add_filter(&mut filter, "ip", &self.ip.clone().map(|ip| vec![ip]));
+        builder.add_filter(
+            &mut filter,
+            "credential_prefix",
+            &self.credential_prefix.clone().map(|prefix| vec![prefix]),
+        );
+        builder.add_range_filter(&mut filter, "at", self.from, self.to);
+
+        filter
+    }
+}
+
+impl ToFindOptions for AuthAuditFilters {
+    fn to_find_options(&self) -> FindOptions {
+        let mut options = FindOptions::builder().sort(bson::doc! { "at": -1 }).build();
+        self.page_query().apply(&mut options);
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_filters() {
+        let filters: AuthAuditFilters = serde_json::from_value(serde_json::json!({
+            "outcome": "FAILURE",
+            "ip": "203.0.113.7",
+            "page": 3,
+            "limit": 1000,
+        }))
+        .unwrap();
+
+        let filter = filters.to_bson_filter();
+        assert_eq!(filter.get_str("outcome").unwrap(), "FAILURE");
+        assert_eq!(filter.get_str("ip").unwrap(), "203.0.113.7");
+        assert!(filter.get("method").is_none());
+
+        let options = filters.to_find_options();
+        assert_eq!(options.limit, Some(100));
+        assert_eq!(options.skip, Some(200));
+    }
+
+    #[test]
+    fn test_entry_keeps_only_the_credential_prefix() {
+        let entry = AuthAuditEntry::new(
+            AuthMethod::ApiKey,
+            Some("vk_0123456789abcdef"),
+            Err("Invalid API key".to_string()),
+            None,
+            "GET",
+            "/protected/vehicles",
+        );
+
+        assert_eq!(entry.outcome, AuthOutcome::Failure);
+        assert_eq!(entry.credential_prefix.as_deref(), Some("vk_0123456"));
+        assert!(entry.user_id.is_none());
+    }
+
+    #[test]
+    fn test_entry_flags_impersonation() {
+        let identity = Identity {
+            role: Role::Customer,
+            user_id: "customer_user_1".to_string(),
+            email: None,
+            tenant_id: None,
+            permissions: Vec::new(),
+            profile: None,
+            impersonated_by: Some("admin_user_1".to_string()),
+        };
+        let entry = AuthAuditEntry::new(
+            AuthMethod::Session,
+            Some("vs_0123456789abcdef"),
+            Ok(&identity),
+            None,
+            "POST",
+            "/protected/bookings",
+        );
+
+        assert_eq!(entry.user_id.as_deref(), Some("customer_user_1"));
+        assert_eq!(entry.impersonated_by.as_deref(), Some("admin_user_1"));
+    }
+}
@@ -0,0 +1,129 @@
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Decimal, VehicleType};
+
+/// `changed_by` of the history entries written by the auto-confirmation
+pub const AUTO_CONFIRM_ACTOR: &str = "auto-confirm";
+
+// =============================================================================
+// MAIN POLICY STRUCTS
+// =============================================================================
+
+/// Conditions for a booking to be confirmed on creation, all of the set ones must hold.
+/// Empty lists and missing values match anything.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AutoConfirmRule {
+    pub name: String,
+    #[serde(default)]
+    pub customer_ids: Vec<String>, // Trusted customers
+    #[serde(default)]
+    pub min_confirmed_bookings: Option<u64>, // Customers with a good track record
+    /// Low-value bookings, in EUR
+    #[serde(default, with = "vehicle_api_types::money::amount::option")]
+    pub max_price: Option<Decimal>,
+    #[serde(default)]
+    pub vehicle_types: Vec<VehicleType>,
+}
+
+/// Auto-confirmation policy, evaluated top to bottom (first match wins)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AutoConfirmPolicy {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    #[serde(default)]
+    pub active: bool,
+    pub rules: Vec<AutoConfirmRule>,
+}
+
+/// What the rules know about a booking that passed validation
+#[derive(Clone, Debug)]
+pub struct AutoConfirmContext {
+    pub customer_id: String,
+    pub confirmed_bookings: u64,
+    pub price: Option<Decimal>, // In EUR, None without an exchange rate
+    pub vehicle_type: VehicleType,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for AutoConfirmPolicy {
+    fn get_collection() -> &'static str {
+        "auto_confirm_policies"
+    }
+}
+
+impl AutoConfirmRule {
+    pub fn matches(&self, context: &AutoConfirmContext) -> bool {
+        (self.customer_ids.is_empty() || self.customer_ids.contains(&context.customer_id))
+            && self
+                .min_confirmed_bookings
+                .is_none_or(|min| context.confirmed_bookings >= min)
+            && self
+                .max_price
+                .is_none_or(|max| context.price.is_some_and(|price| price <= max))
+            && (self.vehicle_types.is_empty() || self.vehicle_types.contains(&context.vehicle_type))
+    }
+}
+
+impl AutoConfirmPolicy {
+    /// Find the first rule confirming the booking, None keeps it pending
+    pub fn find_rule(&self, context: &AutoConfirmContext) -> Option<&AutoConfirmRule> {
+        self.rules.iter().find(|rule| rule.matches(context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(customer_id: &str, confirmed_bookings: u64, price: i64) -> AutoConfirmContext {
+        AutoConfirmContext {
+            customer_id: customer_id.to_string(),
+            confirmed_bookings,
+            price: Some(Decimal::from(price)),
+            vehicle_type: VehicleType::Motorbike,
+        }
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let policy: AutoConfirmPolicy = serde_json::from_str(
+            r#"{
+                "name": "default",
+                "active": true,
+                "rules": [
+                    { "name": "vip", "customer_ids": ["customer_vip"] },
+                    { "name": "regular-small", "min_confirmed_bookings": 3, "max_price": 200.0, "vehicle_types": ["MOTORBIKE"] }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let rule_name = |context: &AutoConfirmContext| {
+            policy
+                .find_rule(context)
+                .map(|rule| rule.name.as_str())
+                .map(str::to_string)
+        };
+
+        assert_eq!(
+            rule_name(&context("customer_vip", 0, 5000)),
+            Some("vip".to_string())
+        );
+        assert_eq!(
+            rule_name(&context("customer_user_1", 3, 150)),
+            Some("regular-small".to_string())
+        );
+        // Too expensive, or not enough history
+        assert_eq!(rule_name(&context("customer_user_1", 3, 250)), None);
+        assert_eq!(rule_name(&context("customer_user_1", 2, 150)), None);
+        // No rules, nothing is confirmed automatically
+        assert!(AutoConfirmPolicy::default()
+            .find_rule(&context("customer_vip", 10, 1))
+            .is_none());
+    }
+}
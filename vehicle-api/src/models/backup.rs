@@ -0,0 +1,165 @@
+use bson::{oid::ObjectId, Bson, Document};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+/// Problems listed at most in a restore report, the counts cover all of them
+pub const MAX_RESTORE_ERRORS: usize = 20;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum BackupStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+// =============================================================================
+// MAIN BACKUP STRUCTS
+// =============================================================================
+
+/// Logical dump of the database delivered to the storage: one file of canonical
+/// Extended JSON documents per collection, one document per line
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Backup {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub status: BackupStatus,
+    /// User who asked for it, None for the scheduled job
+    pub requested_by: Option<String>,
+    #[serde(default)]
+    pub collections: Vec<BackupCollection>,
+    pub error: Option<String>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub started_at: DateTime<Utc>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// File of one collection in a backup
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BackupCollection {
+    pub name: String,
+    pub documents: u64,
+    pub location: String,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// What restoring a backup would do, nothing is written
+#[derive(Clone, Debug, Serialize)]
+pub struct RestoreReport {
+    pub backup_id: ObjectId,
+    /// Every file readable, every document valid and as many as when dumped
+    pub valid: bool,
+    pub collections: Vec<RestoreCheck>,
+    /// First problems found, see MAX_RESTORE_ERRORS
+    pub errors: Vec<String>,
+}
+
+/// Restore dry run of one collection
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+pub struct RestoreCheck {
+    pub name: String,
+    pub expected: u64, // Documents when dumped
+    pub documents: u64,
+    pub invalid: u64,
+    /// Documents whose `_id` is in the database, replaced by a restore
+    pub existing: u64,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Backup {
+    fn get_collection() -> &'static str {
+        "backups"
+    }
+}
+
+impl Backup {
+    pub fn new(requested_by: Option<String>) -> Self {
+        Self {
+            id: None,
+            status: BackupStatus::Running,
+            requested_by,
+            collections: Vec::new(),
+            error: None,
+            started_at: Utc::now(),
+            finished_at: None,
+        }
+    }
+
+    /// Storage key of the file of a collection, unique per backup
+    pub fn key(&self, collection: &str) -> String {
+        format!(
+            "backups/{}/{}.ndjson",
+            self.id.map(|id| id.to_hex()).unwrap_or_default(),
+            collection
+        )
+    }
+}
+
+impl RestoreCheck {
+    pub fn is_valid(&self) -> bool {
+        self.invalid == 0 && self.documents == self.expected
+    }
+}
+
+/// Line of a backup file, types (ObjectId, dates, ...) kept by the canonical format
+pub fn dump_line(document: Document) -> String {
+    Bson::Document(document)
+        .into_canonical_extjson()
+        .to_string()
+}
+
+/// Document of a backup file line, which must have an `_id`
+pub fn parse_line(line: &str) -> Result<Document, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(line).map_err(|e| format!("not JSON: {}", e))?;
+    let Bson::Document(document) =
+        Bson::try_from(value).map_err(|e| format!("not Extended JSON: {}", e))?
+    else {
+        return Err("not a document".to_string());
+    };
+    if !document.contains_key("_id") {
+        return Err("no _id".to_string());
+    }
+    Ok(document)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::doc;
+
+    #[test]
+    fn test_lines_keep_bson_types() {
+        let document = doc! {
+            "_id": ObjectId::new(),
+            "order_date": bson::DateTime::now(),
+            "total_price": 120.5,
+            "days": 3_i64,
+        };
+
+        assert_eq!(parse_line(&dump_line(document.clone())), Ok(document));
+        assert!(parse_line("{\"brand\": \"TESLA\"}").is_err());
+        assert!(parse_line("[1, 2]").is_err());
+        assert!(parse_line("{\"_id\": ").is_err());
+    }
+}
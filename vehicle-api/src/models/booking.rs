@@ -1,55 +1,54 @@
 use bson::oid::ObjectId;
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use strum::{Display, EnumString};
 use validator::Validate;
+use vehicle_api_types::ErrorResponse;
 
-// =============================================================================
-// ENUMS
-// =============================================================================
+use crate::models::{Decimal, Money};
 
-#[derive(Clone, Debug, Serialize, Deserialize, EnumString, Display, PartialEq)]
-#[serde(tag = "status", content = "reason", rename_all = "UPPERCASE")]
-#[strum(serialize_all = "UPPERCASE")]
-pub enum BookingStatus {
-    Pending,
-    Confirmed,
-    Rejected(String),
-    Cancelled(String),
-}
+pub use vehicle_api_types::booking::*;
+
+/// Most bookings a bulk update can change in one call
+pub const MAX_BULK_BOOKINGS: usize = 100;
 
 // =============================================================================
-// MAIN BOOKING STRUCT
+// REQUEST/RESPONSE STRUCTS
 // =============================================================================
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Booking {
-    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
-    pub id: Option<ObjectId>,
-    pub vehicle_id: ObjectId,
-    pub customer_id: String, // User ID of the customer who made the booking
-    pub from_date: NaiveDate,
-    pub to_date: NaiveDate,
-    #[serde(flatten)]
+/// Same status for many bookings, e.g. to clear the pending queue in one call
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct BulkUpdateBookingRequest {
+    pub booking_ids: Vec<ObjectId>,
     pub status: BookingStatus,
-    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
-    pub order_date: DateTime<Utc>, // When the booking was created
 }
 
-// =============================================================================
-// REQUEST/RESPONSE STRUCTS
-// =============================================================================
+/// Outcome of a bulk update, one item per booking in the order they were sent
+#[derive(Clone, Debug, Serialize)]
+pub struct BulkUpdateReport {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub items: Vec<BulkUpdateItem>,
+}
 
-#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
-pub struct CreateBookingRequest {
-    pub vehicle_id: ObjectId,
-    pub from_date: NaiveDate,
-    pub to_date: NaiveDate,
+/// Fee of a vehicle returned after the end of its booking
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LateFee {
+    pub days: i64, // Days started after the end of the booking
+    pub amount: Money,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
-pub struct UpdateBookingRequest {
+/// Booking moved to the archive collection, stored with the same fields
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ArchivedBooking(pub Booking);
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BulkUpdateItem {
+    pub booking_id: ObjectId,
+    /// Status of the booking once updated, None when it failed
     pub status: Option<BookingStatus>,
+    /// Same body as the error the single update would have answered
+    pub error: Option<ErrorResponse>,
 }
 
 // =============================================================================
@@ -62,16 +61,49 @@ impl crate::services::mongodb::MongoStruct for Booking {
     }
 }
 
-impl Booking {
-    pub fn new(request: CreateBookingRequest, customer_id: String) -> Self {
+impl crate::services::mongodb::MongoStruct for ArchivedBooking {
+    fn get_collection() -> &'static str {
+        "bookings_archive"
+    }
+}
+
+impl LateFee {
+    /// Times `price_by_day` charged per day late (LATE_FEE_MULTIPLIER, default 1.5)
+    pub fn multiplier() -> Decimal {
+        std::env::var("LATE_FEE_MULTIPLIER")
+            .ok()
+            .and_then(|value| value.trim().parse::<Decimal>().ok())
+            .filter(|multiplier| *multiplier >= Decimal::ZERO)
+            .unwrap_or(Decimal::new(15, 1))
+    }
+
+    /// Fee of a vehicle due back at `ends_at` and returned at `returned_at`, every day
+    /// started counting in full. None when returned on time.
+    pub fn compute(
+        ends_at: DateTime<Utc>,
+        returned_at: DateTime<Utc>,
+        price_by_day: Money,
+        multiplier: Decimal,
+    ) -> Option<Self> {
+        let late_seconds = (returned_at - ends_at).num_seconds();
+        if late_seconds <= 0 {
+            return None;
+        }
+        let days = (late_seconds + 86_399) / 86_400;
+        Some(Self {
+            days,
+            amount: price_by_day.times(Decimal::from(days) * multiplier).round(),
+        })
+    }
+}
+
+impl BulkUpdateReport {
+    pub fn new(items: Vec<BulkUpdateItem>) -> Self {
+        let failed = items.iter().filter(|item| item.error.is_some()).count();
         Self {
-            id: None,
-            vehicle_id: request.vehicle_id,
-            customer_id,
-            from_date: request.from_date,
-            to_date: request.to_date,
-            status: BookingStatus::Pending,
-            order_date: Utc::now(),
+            succeeded: items.len() - failed,
+            failed,
+            items,
         }
     }
 }
@@ -83,23 +115,65 @@ impl Booking {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::AppError;
+    use crate::models::Currency;
+
+    fn eur(amount: i64, scale: u32) -> Money {
+        Money::new(Decimal::new(amount, scale), Currency::EUR)
+    }
 
     #[test]
-    fn test_booking_status_serialization() {
-        let pending = BookingStatus::Pending;
-        let cancelled = BookingStatus::Cancelled("User request".to_string());
-        let rejected = BookingStatus::Rejected("Invalid dates".to_string());
-
-        // Test that serialization works and uses UPPERCASE
-        let pending_json = serde_json::to_string(&pending).unwrap();
-        let cancelled_json = serde_json::to_string(&cancelled).unwrap();
-        let rejected_json = serde_json::to_string(&rejected).unwrap();
-
-        // Verify uppercase status values
-        assert!(pending_json.contains("\"status\":\"PENDING\""));
-        assert!(cancelled_json.contains("\"status\":\"CANCELLED\""));
-        assert!(cancelled_json.contains("\"reason\":\"User request\""));
-        assert!(rejected_json.contains("\"status\":\"REJECTED\""));
-        assert!(rejected_json.contains("\"reason\":\"Invalid dates\""));
+    fn test_late_fee_counts_every_day_started() {
+        let ends_at = DateTime::parse_from_rfc3339("2025-08-05T22:00:00Z")
+            .unwrap()
+            .to_utc();
+        let hours = chrono::Duration::hours;
+
+        assert_eq!(
+            LateFee::compute(ends_at, ends_at, eur(80, 0), Decimal::new(15, 1)),
+            None
+        );
+        assert_eq!(
+            LateFee::compute(ends_at, ends_at - hours(3), eur(80, 0), Decimal::new(15, 1)),
+            None
+        );
+        assert_eq!(
+            LateFee::compute(ends_at, ends_at + hours(2), eur(80, 0), Decimal::new(15, 1)),
+            Some(LateFee {
+                days: 1,
+                amount: eur(120, 0)
+            })
+        );
+        assert_eq!(
+            LateFee::compute(
+                ends_at,
+                ends_at + hours(25),
+                eur(3333, 2),
+                Decimal::new(15, 1)
+            ),
+            Some(LateFee {
+                days: 2,
+                amount: eur(9999, 2)
+            })
+        );
+    }
+
+    #[test]
+    fn test_bulk_report_counts_failures() {
+        let report = BulkUpdateReport::new(vec![
+            BulkUpdateItem {
+                booking_id: ObjectId::new(),
+                status: Some(BookingStatus::Confirmed),
+                error: None,
+            },
+            BulkUpdateItem {
+                booking_id: ObjectId::new(),
+                status: None,
+                error: Some(AppError::not_found("Booking not found").to_response()),
+            },
+        ]);
+
+        assert_eq!((report.succeeded, report.failed), (1, 1));
+        assert_eq!(report.items[1].error.as_ref().unwrap().code, 404);
     }
 }
@@ -0,0 +1,286 @@
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::authentication::identity::Role;
+use crate::models::BookingStatus;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+/// Outcome of a matching transition rule
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(
+    tag = "effect",
+    content = "message",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+pub enum RuleEffect {
+    Allow,
+    Forbidden(String),
+    BadRequest(String),
+}
+
+// =============================================================================
+// MAIN POLICY STRUCTS
+// =============================================================================
+
+/// A single row of the transition matrix.
+/// Empty `roles`, `from` or `to` lists match anything.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransitionRule {
+    #[serde(default)]
+    pub roles: Vec<Role>,
+    #[serde(default)]
+    pub from: Vec<String>, // Status names, e.g. "PENDING"
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(flatten)]
+    pub effect: RuleEffect,
+}
+
+/// Booking status transition policy, evaluated top to bottom (first match wins)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BookingPolicy {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    #[serde(default)]
+    pub active: bool,
+    pub rules: Vec<TransitionRule>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for BookingPolicy {
+    fn get_collection() -> &'static str {
+        "booking_policies"
+    }
+}
+
+impl TransitionRule {
+    fn new(roles: &[Role], from: &[&str], to: &[&str], effect: RuleEffect) -> Self {
+        Self {
+            roles: roles.to_vec(),
+            from: from.iter().map(|s| s.to_string()).collect(),
+            to: to.iter().map(|s| s.to_string()).collect(),
+            effect,
+        }
+    }
+
+    pub fn matches(&self, role: &Role, current: &BookingStatus, new: &BookingStatus) -> bool {
+        let current = current.to_string();
+        let new = new.to_string();
+
+        (self.roles.is_empty() || self.roles.contains(role))
+            && (self.from.is_empty() || self.from.contains(&current))
+            && (self.to.is_empty() || self.to.contains(&new))
+    }
+}
+
+impl BookingPolicy {
+    /// Find the first rule matching the given role and transition
+    pub fn find_rule(
+        &self,
+        role: &Role,
+        current: &BookingStatus,
+        new: &BookingStatus,
+    ) -> Option<&TransitionRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(role, current, new))
+    }
+}
+
+impl Default for BookingPolicy {
+    /// Built-in transition matrix, used when no policy is configured
+    fn default() -> Self {
+        let staff = [Role::Admin, Role::CarManager, Role::MotorbikeManager];
+        let customer = [Role::Customer];
+
+        Self {
+            id: None,
+            name: "default".to_string(),
+            active: true,
+            rules: vec![
+                // Customers can only cancel pending or confirmed bookings
+                TransitionRule::new(
+                    &customer,
+                    &["PENDING", "CONFIRMED"],
+                    &["CANCELLED"],
+                    RuleEffect::Allow,
+                ),
+                TransitionRule::new(
+                    &customer,
+                    &[],
+                    &["CANCELLED"],
+                    RuleEffect::Forbidden(
+                        "You can only cancel bookings that are pending or confirmed.".to_string(),
+                    ),
+                ),
+                TransitionRule::new(
+                    &customer,
+                    &[],
+                    &[],
+                    RuleEffect::Forbidden("Customers can only cancel their bookings.".to_string()),
+                ),
+                // Admin and managers confirm or reject
+                TransitionRule::new(
+                    &staff,
+                    &[],
+                    &["CANCELLED"],
+                    RuleEffect::Forbidden(
+                        "Only customers can cancel bookings. Use reject status instead."
+                            .to_string(),
+                    ),
+                ),
+                TransitionRule::new(
+                    &staff,
+                    &[],
+                    &["PENDING"],
+                    RuleEffect::Forbidden("Cannot change status back to pending.".to_string()),
+                ),
+                TransitionRule::new(
+                    &staff,
+                    &["PENDING"],
+                    &["CONFIRMED", "REJECTED"],
+                    RuleEffect::Allow,
+                ),
+                TransitionRule::new(&staff, &["CONFIRMED"], &["REJECTED"], RuleEffect::Allow),
+                TransitionRule::new(
+                    &staff,
+                    &["CONFIRMED"],
+                    &[],
+                    RuleEffect::BadRequest("Confirmed bookings can only be rejected.".to_string()),
+                ),
+                TransitionRule::new(
+                    &staff,
+                    &["REJECTED", "CANCELLED"],
+                    &[],
+                    RuleEffect::BadRequest(
+                        "Cannot modify rejected or cancelled bookings.".to_string(),
+                    ),
+                ),
+            ],
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effect(role: Role, current: BookingStatus, new: BookingStatus) -> Option<RuleEffect> {
+        BookingPolicy::default()
+            .find_rule(&role, &current, &new)
+            .map(|rule| rule.effect.clone())
+    }
+
+    #[test]
+    fn test_default_policy_customer_transitions() {
+        let cancelled = BookingStatus::Cancelled("Changed plans".to_string());
+
+        assert_eq!(
+            effect(Role::Customer, BookingStatus::Pending, cancelled.clone()),
+            Some(RuleEffect::Allow)
+        );
+        assert_eq!(
+            effect(Role::Customer, BookingStatus::Confirmed, cancelled.clone()),
+            Some(RuleEffect::Allow)
+        );
+        assert!(matches!(
+            effect(
+                Role::Customer,
+                BookingStatus::Rejected("No".to_string()),
+                cancelled
+            ),
+            Some(RuleEffect::Forbidden(_))
+        ));
+        assert!(matches!(
+            effect(
+                Role::Customer,
+                BookingStatus::Pending,
+                BookingStatus::Confirmed
+            ),
+            Some(RuleEffect::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn test_default_policy_staff_transitions() {
+        let rejected = BookingStatus::Rejected("Maintenance".to_string());
+
+        assert_eq!(
+            effect(
+                Role::CarManager,
+                BookingStatus::Pending,
+                BookingStatus::Confirmed
+            ),
+            Some(RuleEffect::Allow)
+        );
+        assert_eq!(
+            effect(Role::Admin, BookingStatus::Confirmed, rejected.clone()),
+            Some(RuleEffect::Allow)
+        );
+        assert!(matches!(
+            effect(
+                Role::Admin,
+                BookingStatus::Confirmed,
+                BookingStatus::Confirmed
+            ),
+            Some(RuleEffect::BadRequest(_))
+        ));
+        assert!(matches!(
+            effect(
+                Role::MotorbikeManager,
+                BookingStatus::Pending,
+                BookingStatus::Cancelled("x".to_string())
+            ),
+            Some(RuleEffect::Forbidden(_))
+        ));
+        assert!(matches!(
+            effect(Role::Admin, rejected, BookingStatus::Confirmed),
+            Some(RuleEffect::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_policy_deserialization() {
+        let json = r#"{
+            "name": "managers-may-cancel",
+            "active": true,
+            "rules": [
+                { "roles": ["CarManager"], "to": ["CANCELLED"], "effect": "ALLOW" },
+                { "effect": "FORBIDDEN", "message": "Not allowed" }
+            ]
+        }"#;
+        let policy: BookingPolicy = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            policy
+                .find_rule(
+                    &Role::CarManager,
+                    &BookingStatus::Confirmed,
+                    &BookingStatus::Cancelled("Vehicle damaged".to_string())
+                )
+                .map(|rule| &rule.effect),
+            Some(&RuleEffect::Allow)
+        );
+        assert_eq!(
+            policy
+                .find_rule(
+                    &Role::Customer,
+                    &BookingStatus::Pending,
+                    &BookingStatus::Confirmed
+                )
+                .map(|rule| &rule.effect),
+            Some(&RuleEffect::Forbidden("Not allowed".to_string()))
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{BookingIssue, BookingIssueCode};
+use crate::util::timezone;
+
+// =============================================================================
+// MAIN RULES STRUCT
+// =============================================================================
+
+/// Limits on new bookings, a rule left empty is not enforced
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct BookingRules {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub active: bool,
+    /// Hours between the booking and the start of `from_date` at the vehicle
+    pub min_notice_hours: Option<i64>,
+    /// Days between `from_date` and `to_date`
+    pub max_rental_days: Option<i64>,
+    /// Days from today to `from_date`
+    pub max_horizon_days: Option<i64>,
+    /// Pending or confirmed bookings not over yet a customer may hold at once
+    pub max_active_bookings: Option<u64>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for BookingRules {
+    fn get_collection() -> &'static str {
+        "booking_rules"
+    }
+}
+
+impl BookingRules {
+    /// Rules from BOOKING_MIN_NOTICE_HOURS, BOOKING_MAX_RENTAL_DAYS, BOOKING_MAX_HORIZON_DAYS
+    /// and BOOKING_MAX_ACTIVE_PER_CUSTOMER, unset or invalid variables leave the rule out
+    pub fn from_env() -> Self {
+        let read = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse::<i64>().ok())
+                .filter(|value| *value >= 0)
+        };
+        Self {
+            name: "env".to_string(),
+            active: true,
+            min_notice_hours: read("BOOKING_MIN_NOTICE_HOURS"),
+            max_rental_days: read("BOOKING_MAX_RENTAL_DAYS"),
+            max_horizon_days: read("BOOKING_MAX_HORIZON_DAYS"),
+            max_active_bookings: read("BOOKING_MAX_ACTIVE_PER_CUSTOMER").map(|max| max as u64),
+            ..Self::default()
+        }
+    }
+
+    /// Whether any rule applies to the dates, see `check`
+    pub fn limits_dates(&self) -> bool {
+        self.min_notice_hours.is_some()
+            || self.max_rental_days.is_some()
+            || self.max_horizon_days.is_some()
+    }
+
+    /// One issue per broken rule, `now` is compared to the dates in the vehicle's time zone
+    pub fn check(
+        &self,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        now: DateTime<Utc>,
+        tz: Tz,
+    ) -> Vec<BookingIssue> {
+        let mut issues = Vec::new();
+
+        if let Some(hours) = self.min_notice_hours {
+            let notice = timezone::start_of_day(from_date, tz) - now;
+            if notice.num_hours() < hours {
+                issues.push(BookingIssue::new(
+                    BookingIssueCode::TooShortNotice,
+                    Some("from_date"),
+                    format!("Bookings must be made at least {} hours in advance.", hours),
+                ));
+            }
+        }
+        if let Some(days) = self.max_rental_days {
+            if (to_date - from_date).num_days() > days {
+                issues.push(BookingIssue::new(
+                    BookingIssueCode::TooLongRental,
+                    Some("to_date"),
+                    format!("Rentals cannot last more than {} days.", days),
+                ));
+            }
+        }
+        if let Some(days) = self.max_horizon_days {
+            let today = now.with_timezone(&tz).date_naive();
+            let horizon = today.checked_add_days(Days::new(days as u64));
+            if horizon.is_some_and(|horizon| from_date > horizon) {
+                issues.push(BookingIssue::new(
+                    BookingIssueCode::BeyondHorizon,
+                    Some("from_date"),
+                    format!("Bookings cannot start more than {} days ahead.", days),
+                ));
+            }
+        }
+
+        issues
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures::date;
+    use chrono::TimeZone;
+
+    fn codes(issues: Vec<BookingIssue>) -> Vec<BookingIssueCode> {
+        issues.into_iter().map(|issue| issue.code).collect()
+    }
+
+    #[test]
+    fn test_each_rule_reports_its_own_issue() {
+        let rules = BookingRules {
+            min_notice_hours: Some(24),
+            max_rental_days: Some(14),
+            max_horizon_days: Some(90),
+            ..BookingRules::default()
+        };
+        // 10:00 in Paris on August 1st
+        let now = Utc.with_ymd_and_hms(2025, 8, 1, 8, 0, 0).unwrap();
+        let tz = chrono_tz::Europe::Paris;
+
+        assert!(rules.check(date(8, 3), date(8, 10), now, tz).is_empty());
+        // August 2nd starts 14 hours later
+        assert_eq!(
+            codes(rules.check(date(8, 2), date(8, 10), now, tz)),
+            vec![BookingIssueCode::TooShortNotice]
+        );
+        assert_eq!(
+            codes(rules.check(date(8, 3), date(8, 18), now, tz)),
+            vec![BookingIssueCode::TooLongRental]
+        );
+        assert!(rules.check(date(10, 30), date(11, 2), now, tz).is_empty());
+        assert_eq!(
+            codes(rules.check(date(10, 31), date(11, 2), now, tz)),
+            vec![BookingIssueCode::BeyondHorizon]
+        );
+
+        assert!(BookingRules::default()
+            .check(date(8, 1), date(12, 31), now, tz)
+            .is_empty());
+    }
+}
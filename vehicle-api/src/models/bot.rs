@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+/// What the bot detection does with a request, by increasing severity
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum BotAction {
+    Allow,
+    Throttle, // A few requests per minute, 429 beyond
+    Tarpit,   // Served after a delay
+    Block,    // 403
+}
+
+/// Decision of an admin about a client, replacing its score
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BotOverride {
+    Allow,
+    Block,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Anonymous client suspected of scraping, as tracked by this instance
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct BotClient {
+    pub ip: String,
+    pub score: u32,
+    pub action: BotAction,
+    /// Heuristics that matched its last request, e.g. "automation_user_agent"
+    pub signals: Vec<String>,
+    pub requests_last_minute: usize,
+    pub honeypot_hits: u32,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub override_action: Option<BotOverride>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BotOverrideRequest {
+    pub action: BotOverride,
+}
@@ -0,0 +1,299 @@
+use bson::{doc, oid::ObjectId, Document};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use crate::models::VehicleType;
+
+/// Longest subject and message of a broadcast
+pub const MAX_BROADCAST_SUBJECT_CHARS: usize = 200;
+pub const MAX_BROADCAST_MESSAGE_CHARS: usize = 2000;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum BroadcastStatus {
+    Sending,
+    Sent,
+}
+
+/// Outcome of a broadcast on one channel for one customer
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum BroadcastDeliveryStatus {
+    #[default]
+    Pending,
+    Sent,
+    Failed,
+    Skipped, // The channel cannot reach the customer, e.g. no email address
+}
+
+// =============================================================================
+// MAIN BROADCAST STRUCTS
+// =============================================================================
+
+/// Urgent message (recall, weather warning) sent to the customers of active and upcoming
+/// bookings, through every channel reaching them
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Broadcast {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub subject: String,
+    pub message: String,
+    pub filter: BroadcastFilter,
+    pub status: BroadcastStatus,
+    pub sent_by: String,
+    pub recipients: u32, // Customers matched, one delivery each
+    #[serde(default)]
+    pub in_app: ChannelStats,
+    #[serde(default)]
+    pub email: ChannelStats,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Bookings whose customers a broadcast reaches, every field set must match
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct BroadcastFilter {
+    #[serde(default, rename = "type")]
+    pub vehicle_type: Option<VehicleType>,
+    #[serde(default)]
+    pub location: Option<String>, // IANA time zone of the vehicles' branch
+    #[serde(default)]
+    pub from_date: Option<NaiveDate>, // Bookings overlapping these days, both included
+    #[serde(default)]
+    pub to_date: Option<NaiveDate>,
+}
+
+/// Deliveries of a broadcast on one channel, by outcome
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ChannelStats {
+    pub sent: u32,
+    pub failed: u32,
+    pub skipped: u32,
+}
+
+/// Delivery of a broadcast to one customer, on every channel
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BroadcastDelivery {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub broadcast_id: ObjectId,
+    pub customer_id: String,
+    pub booking_ids: Vec<ObjectId>, // Matching bookings of the customer
+    pub in_app: BroadcastDeliveryStatus,
+    pub email: BroadcastDeliveryStatus,
+    pub error: Option<String>, // Last channel failure
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CreateBroadcastRequest {
+    pub subject: String,
+    pub message: String,
+    #[serde(flatten)]
+    pub filter: BroadcastFilter,
+}
+
+/// `?status=FAILED` of GET /admin/broadcasts/{id}/deliveries, deliveries that failed on
+/// some channel
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct BroadcastDeliveryQuery {
+    pub status: Option<BroadcastDeliveryStatus>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Broadcast {
+    fn get_collection() -> &'static str {
+        "broadcasts"
+    }
+}
+
+impl crate::services::mongodb::MongoStruct for BroadcastDelivery {
+    fn get_collection() -> &'static str {
+        "broadcast_deliveries"
+    }
+}
+
+impl CreateBroadcastRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        let subject = self.subject.trim();
+        if subject.is_empty() || subject.chars().count() > MAX_BROADCAST_SUBJECT_CHARS {
+            return Err(format!(
+                "subject must have 1 to {} characters",
+                MAX_BROADCAST_SUBJECT_CHARS
+            ));
+        }
+        let message = self.message.trim();
+        if message.is_empty() || message.chars().count() > MAX_BROADCAST_MESSAGE_CHARS {
+            return Err(format!(
+                "message must have 1 to {} characters",
+                MAX_BROADCAST_MESSAGE_CHARS
+            ));
+        }
+        if let (Some(from_date), Some(to_date)) = (self.filter.from_date, self.filter.to_date) {
+            if from_date > to_date {
+                return Err("from_date must not be after to_date".to_string());
+            }
+        }
+        if let Some(location) = &self.filter.location {
+            crate::util::timezone::parse_timezone(location)?;
+        }
+        Ok(())
+    }
+}
+
+impl BroadcastFilter {
+    /// Vehicles to look for, None when every vehicle matches
+    pub fn vehicle_filter(&self) -> Option<Document> {
+        if self.vehicle_type.is_none() && self.location.is_none() {
+            return None;
+        }
+        let mut filter = Document::new();
+        if let Some(vehicle_type) = &self.vehicle_type {
+            filter.insert("type", vehicle_type.to_string());
+        }
+        if let Some(location) = &self.location {
+            filter.insert("timezone", location);
+        }
+        Some(filter)
+    }
+
+    /// Active and upcoming bookings (pending or confirmed, not over by `today`) matching
+    /// the dates, of the given vehicles when the filter is on vehicles
+    pub fn booking_filter(&self, vehicle_ids: Option<Vec<ObjectId>>, today: NaiveDate) -> Document {
+        let mut filter = doc! {
+            "status": { "$in": ["PENDING", "CONFIRMED"] },
+            "to_date": { "$gte": today.to_string() },
+        };
+        if let Some(vehicle_ids) = vehicle_ids {
+            filter.insert("vehicle_id", doc! { "$in": vehicle_ids });
+        }
+        // Dates are stored as YYYY-MM-DD, strings compare as dates
+        let mut overlap = Vec::new();
+        if let Some(from_date) = self.from_date {
+            overlap.push(doc! { "to_date": { "$gte": from_date.to_string() } });
+        }
+        if let Some(to_date) = self.to_date {
+            overlap.push(doc! { "from_date": { "$lte": to_date.to_string() } });
+        }
+        if !overlap.is_empty() {
+            filter.insert("$and", overlap);
+        }
+        filter
+    }
+}
+
+impl ChannelStats {
+    /// Counter of an outcome, `$inc` path under the channel's field
+    pub fn counter(status: BroadcastDeliveryStatus) -> Option<&'static str> {
+        match status {
+            BroadcastDeliveryStatus::Sent => Some("sent"),
+            BroadcastDeliveryStatus::Failed => Some("failed"),
+            BroadcastDeliveryStatus::Skipped => Some("skipped"),
+            BroadcastDeliveryStatus::Pending => None,
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures::date;
+
+    #[test]
+    fn test_booking_filter_keeps_active_and_upcoming_bookings() {
+        let filter = BroadcastFilter {
+            vehicle_type: Some(VehicleType::Car),
+            location: Some("Europe/Paris".to_string()),
+            from_date: Some(date(8, 10)),
+            to_date: Some(date(8, 12)),
+        };
+        assert_eq!(
+            filter.vehicle_filter(),
+            Some(doc! { "type": "CAR", "timezone": "Europe/Paris" })
+        );
+
+        let vehicle_id = ObjectId::new();
+        assert_eq!(
+            filter.booking_filter(Some(vec![vehicle_id]), date(8, 1)),
+            doc! {
+                "status": { "$in": ["PENDING", "CONFIRMED"] },
+                "to_date": { "$gte": "2025-08-01" },
+                "vehicle_id": { "$in": [vehicle_id] },
+                "$and": [
+                    { "to_date": { "$gte": "2025-08-10" } },
+                    { "from_date": { "$lte": "2025-08-12" } },
+                ],
+            }
+        );
+
+        let everyone = BroadcastFilter::default();
+        assert_eq!(everyone.vehicle_filter(), None);
+        assert_eq!(
+            everyone.booking_filter(None, date(8, 1)),
+            doc! {
+                "status": { "$in": ["PENDING", "CONFIRMED"] },
+                "to_date": { "$gte": "2025-08-01" },
+            }
+        );
+    }
+
+    #[test]
+    fn test_broadcast_request_validation() {
+        let request = |subject: &str, filter: BroadcastFilter| CreateBroadcastRequest {
+            subject: subject.to_string(),
+            message: "Storm warning, keep vehicles parked.".to_string(),
+            filter,
+        };
+
+        assert!(request("Weather warning", BroadcastFilter::default())
+            .validate()
+            .is_ok());
+        assert!(request(" ", BroadcastFilter::default()).validate().is_err());
+        assert!(request(
+            "Weather warning",
+            BroadcastFilter {
+                from_date: Some(date(8, 12)),
+                to_date: Some(date(8, 10)),
+                ..Default::default()
+            }
+        )
+        .validate()
+        .is_err());
+        assert!(request(
+            "Weather warning",
+            BroadcastFilter {
+                location: Some("Mars/Olympus".to_string()),
+                ..Default::default()
+            }
+        )
+        .validate()
+        .is_err());
+    }
+}
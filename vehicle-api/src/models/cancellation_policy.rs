@@ -0,0 +1,163 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Decimal, Money};
+
+// =============================================================================
+// MAIN POLICY STRUCTS
+// =============================================================================
+
+/// Fee of a cancellation made at least `min_hours_before` hours before the rental
+/// starts. A tier without `min_hours_before` matches any cancellation, late ones included.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CancellationTier {
+    #[serde(default)]
+    pub min_hours_before: Option<i64>,
+    /// Share of the rental price kept, from 0 to 100
+    pub fee_percent: f64,
+}
+
+/// Cancellation fees of confirmed bookings, tiers evaluated top to bottom (first match
+/// wins). No tier matching means a free cancellation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CancellationPolicy {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    #[serde(default)]
+    pub active: bool,
+    pub tiers: Vec<CancellationTier>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Fee of a cancellation, as computed by the policy
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct CancellationQuote {
+    pub hours_before: i64,
+    pub fee_percent: f64,
+    pub fee: Money,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for CancellationPolicy {
+    fn get_collection() -> &'static str {
+        "cancellation_policies"
+    }
+}
+
+impl CancellationTier {
+    fn new(min_hours_before: Option<i64>, fee_percent: f64) -> Self {
+        Self {
+            min_hours_before,
+            fee_percent,
+        }
+    }
+
+    pub fn matches(&self, hours_before: i64) -> bool {
+        self.min_hours_before.is_none_or(|min| hours_before >= min)
+    }
+}
+
+impl CancellationPolicy {
+    /// Find the first tier matching a cancellation `hours_before` the rental starts
+    pub fn find_tier(&self, hours_before: i64) -> Option<&CancellationTier> {
+        self.tiers.iter().find(|tier| tier.matches(hours_before))
+    }
+
+    /// Fee of cancelling a rental of `price` starting at `starts_at`, rounded to cents
+    pub fn quote(
+        &self,
+        price: Money,
+        starts_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> CancellationQuote {
+        let hours_before = (starts_at - now).num_hours();
+        let fee_percent = self
+            .find_tier(hours_before)
+            .map(|tier| tier.fee_percent.clamp(0.0, 100.0))
+            .unwrap_or(0.0);
+        CancellationQuote {
+            hours_before,
+            fee_percent,
+            fee: price
+                .times(Decimal::try_from(fee_percent).unwrap_or_default() / Decimal::ONE_HUNDRED)
+                .round(),
+        }
+    }
+}
+
+impl Default for CancellationPolicy {
+    /// Built-in fees, used when no policy is configured: free up to 7 days before the
+    /// rental, 25% up to 48 hours before, 50% afterwards
+    fn default() -> Self {
+        Self {
+            id: None,
+            name: "default".to_string(),
+            active: true,
+            tiers: vec![
+                CancellationTier::new(Some(7 * 24), 0.0),
+                CancellationTier::new(Some(48), 25.0),
+                CancellationTier::new(None, 50.0),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Currency;
+    use chrono::Duration;
+
+    fn eur(amount: i64, scale: u32) -> Money {
+        Money::new(Decimal::new(amount, scale), Currency::EUR)
+    }
+
+    #[test]
+    fn test_fee_grows_as_the_rental_gets_closer() {
+        let policy = CancellationPolicy::default();
+        let now = Utc::now();
+        let fee = |hours: i64| {
+            policy
+                .quote(eur(19999, 2), now + Duration::hours(hours), now)
+                .fee
+        };
+
+        assert_eq!(fee(10 * 24), eur(0, 0));
+        assert_eq!(fee(7 * 24), eur(0, 0));
+        assert_eq!(fee(72), eur(50, 0));
+        assert_eq!(fee(47), eur(100, 0));
+        // Cancelled once the rental started
+        assert_eq!(fee(-5), eur(100, 0));
+
+        let quote = policy.quote(eur(80, 0), now + Duration::hours(30), now);
+        assert_eq!(quote.hours_before, 30);
+        assert_eq!(quote.fee_percent, 50.0);
+        assert_eq!(quote.fee, eur(40, 0));
+    }
+
+    #[test]
+    fn test_policy_without_matching_tier_is_free() {
+        let policy: CancellationPolicy = serde_json::from_str(
+            r#"{ "name": "late-only", "active": true, "tiers": [{ "min_hours_before": 0, "fee_percent": 150 }] }"#,
+        )
+        .unwrap();
+        let now = Utc::now();
+
+        assert_eq!(
+            policy.quote(eur(100, 0), now + Duration::hours(1), now).fee,
+            eur(100, 0)
+        );
+        assert_eq!(
+            policy.quote(eur(100, 0), now - Duration::hours(1), now).fee,
+            eur(0, 0)
+        );
+    }
+}
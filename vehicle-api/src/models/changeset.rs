@@ -0,0 +1,191 @@
+use bson::{doc, oid::ObjectId, Document};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+/// Minutes a change-set can be rolled back when CHANGESET_TTL_MINUTES is not set
+pub const DEFAULT_CHANGESET_TTL_MINUTES: i64 = 60;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+/// Admin operation that produced a change-set
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChangesetKind {
+    VehiclePriceAdjustment,
+    BookingDeletion,
+}
+
+/// What happened to one document, with what is needed to put it back
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "operation", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Change {
+    /// Fields set by the operation, `before` holds the values they replaced
+    Updated {
+        resource_id: ObjectId,
+        before: Document,
+        after: Document,
+    },
+    /// The whole document as it was deleted
+    Deleted {
+        resource_id: ObjectId,
+        document: Document,
+    },
+    Created {
+        resource_id: ObjectId,
+    },
+}
+
+// =============================================================================
+// MAIN CHANGESET STRUCTS
+// =============================================================================
+
+/// Documents changed by one destructive admin operation, kept until `expires_at`
+/// (TTL index) to roll the operation back
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Changeset {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub kind: ChangesetKind,
+    pub collection: String,
+    pub changes: Vec<Change>,
+    pub created_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(default)]
+    pub rolled_back_at: Option<bson::DateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ChangesetRollback {
+    pub restored: Vec<ObjectId>,
+    /// Documents changed again since, left as they are
+    pub skipped: Vec<ObjectId>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Changeset {
+    fn get_collection() -> &'static str {
+        "changesets"
+    }
+}
+
+impl Changeset {
+    pub fn new(
+        kind: ChangesetKind,
+        collection: &str,
+        changes: Vec<Change>,
+        created_by: &str,
+        ttl: Duration,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: None,
+            kind,
+            collection: collection.to_string(),
+            changes,
+            created_by: created_by.to_string(),
+            created_at: now,
+            expires_at: now + ttl,
+            rolled_back_at: None,
+            tenant_id: None,
+        }
+    }
+
+    /// Why the change-set cannot be rolled back anymore
+    pub fn check_rollback(&self, now: DateTime<Utc>) -> Result<(), String> {
+        if self.rolled_back_at.is_some() {
+            return Err("Change-set already rolled back".to_string());
+        }
+        // The TTL monitor only runs every minute, expired change-sets may still be around
+        if self.expires_at <= now {
+            return Err("Change-set expired".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Change {
+    pub fn resource_id(&self) -> ObjectId {
+        match self {
+            Change::Updated { resource_id, .. }
+            | Change::Deleted { resource_id, .. }
+            | Change::Created { resource_id } => *resource_id,
+        }
+    }
+
+    /// Filter matching the document only while the change is still in place
+    pub fn guard(&self) -> Document {
+        let mut filter = doc! { "_id": self.resource_id() };
+        if let Change::Updated { after, .. } = self {
+            filter.extend(after.clone());
+        }
+        filter
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rollback_only_once_and_before_expiry() {
+        let mut changeset = Changeset::new(
+            ChangesetKind::BookingDeletion,
+            "bookings",
+            Vec::new(),
+            "admin_user_1",
+            Duration::minutes(60),
+        );
+        let now = Utc::now();
+
+        assert!(changeset.check_rollback(now).is_ok());
+        assert!(changeset
+            .check_rollback(now + Duration::minutes(61))
+            .is_err());
+
+        changeset.rolled_back_at = Some(bson::DateTime::now());
+        assert!(changeset.check_rollback(now).is_err());
+    }
+
+    #[test]
+    fn test_updates_are_only_undone_while_in_place() {
+        let resource_id = ObjectId::new();
+        let change = Change::Updated {
+            resource_id,
+            before: doc! { "price_by_day": 80.0 },
+            after: doc! { "price_by_day": 72.0 },
+        };
+        assert_eq!(
+            change.guard(),
+            doc! { "_id": resource_id, "price_by_day": 72.0 }
+        );
+
+        let change = Change::Deleted {
+            resource_id,
+            document: doc! { "_id": resource_id, "status": "CANCELLED" },
+        };
+        assert_eq!(change.guard(), doc! { "_id": resource_id });
+
+        let stored = bson::to_document(&change).unwrap();
+        assert_eq!(stored.get_str("operation"), Ok("DELETED"));
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+/// Fault injected when a chaos rule fires
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "UPPERCASE")]
+pub enum Fault {
+    /// Delay the request before handling it
+    Latency { delay_ms: u64 },
+    /// Answer with an error status instead of calling the handler
+    Error { status: u16 },
+    /// Hang, then answer 502 without a body and close the connection, as a proxy
+    /// losing its upstream
+    Close { after_ms: u64 },
+}
+
+// =============================================================================
+// MAIN CHAOS STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChaosRule {
+    pub path_prefix: String, // e.g. "/protected/bookings"
+    pub percentage: f64,     // Probability in percent (0-100)
+    pub fault: Fault,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ChaosConfig {
+    pub enabled: bool,
+    #[serde(default)]
+    pub rules: Vec<ChaosRule>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl ChaosRule {
+    pub fn matches(&self, path: &str) -> bool {
+        path.starts_with(&self.path_prefix)
+    }
+}
+
+impl ChaosConfig {
+    pub const fn disabled() -> Self {
+        Self {
+            enabled: false,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Pick the fault to inject for a path, `roll` being a random number in [0, 100)
+    pub fn fault_for(&self, path: &str, roll: f64) -> Option<Fault> {
+        if !self.enabled {
+            return None;
+        }
+
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(path))
+            .filter(|rule| roll < rule.percentage)
+            .map(|rule| rule.fault.clone())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for rule in &self.rules {
+            if !(0.0..=100.0).contains(&rule.percentage) {
+                return Err("percentage must be between 0 and 100".to_string());
+            }
+            if let Fault::Error { status } = rule.fault {
+                if !(400..=599).contains(&status) {
+                    return Err("Error fault status must be between 400 and 599".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ChaosConfig {
+        ChaosConfig {
+            enabled: true,
+            rules: vec![ChaosRule {
+                path_prefix: "/protected/bookings".to_string(),
+                percentage: 25.0,
+                fault: Fault::Error { status: 500 },
+            }],
+        }
+    }
+
+    #[test]
+    fn test_fault_for_matching_path_and_roll() {
+        let config = config();
+
+        assert_eq!(
+            config.fault_for("/protected/bookings/123", 10.0),
+            Some(Fault::Error { status: 500 })
+        );
+        assert_eq!(config.fault_for("/protected/bookings", 30.0), None);
+        assert_eq!(config.fault_for("/protected/vehicles", 0.0), None);
+    }
+
+    #[test]
+    fn test_disabled_config_never_injects() {
+        let config = ChaosConfig {
+            enabled: false,
+            ..config()
+        };
+        assert_eq!(config.fault_for("/protected/bookings", 0.0), None);
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let mut config = config();
+        assert!(config.validate().is_ok());
+
+        config.rules[0].percentage = 150.0;
+        assert!(config.validate().is_err());
+    }
+}
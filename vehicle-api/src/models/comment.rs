@@ -0,0 +1,120 @@
+use bson::{doc, oid::ObjectId, Document};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::authentication::identity::{Identity, Role};
+
+// =============================================================================
+// MAIN COMMENT STRUCT
+// =============================================================================
+
+/// Message in the thread of a booking, between the customer and the staff. Internal
+/// comments are only shown to the staff.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BookingComment {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub booking_id: ObjectId,
+    pub author_id: String,
+    pub author_role: Role,
+    /// Admin who wrote the comment while impersonating the author
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonated_by: Option<String>,
+    pub body: String,
+    #[serde(default)]
+    pub internal: bool,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct AddCommentRequest {
+    #[validate(length(min = 1, max = 2000))]
+    pub body: String,
+    /// Only shown to the staff, customers cannot post internal comments
+    #[serde(default)]
+    pub internal: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct UpdateCommentRequest {
+    pub internal: bool,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for BookingComment {
+    fn get_collection() -> &'static str {
+        "booking_comments"
+    }
+}
+
+impl BookingComment {
+    pub fn new(booking_id: ObjectId, identity: &Identity, request: AddCommentRequest) -> Self {
+        let now = Utc::now();
+        Self {
+            id: None,
+            booking_id,
+            author_id: identity.user_id.clone(),
+            author_role: identity.role.clone(),
+            impersonated_by: identity.impersonated_by.clone(),
+            body: request.body,
+            internal: request.internal,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Comments of a booking the caller may read, internal ones are left out for customers
+    pub fn visible_filter(identity: &Identity, booking_id: &ObjectId) -> Document {
+        let mut filter = doc! { "booking_id": booking_id };
+        if identity.role == Role::Customer {
+            filter.insert("internal", false);
+        }
+        filter
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(role: Role) -> Identity {
+        Identity {
+            role,
+            user_id: "user_1".to_string(),
+            email: None,
+            tenant_id: None,
+            permissions: Vec::new(),
+            profile: None,
+            impersonated_by: None,
+        }
+    }
+
+    #[test]
+    fn test_customers_only_see_public_comments() {
+        let booking_id = ObjectId::new();
+
+        assert_eq!(
+            BookingComment::visible_filter(&identity(Role::Customer), &booking_id),
+            doc! { "booking_id": booking_id, "internal": false }
+        );
+        assert_eq!(
+            BookingComment::visible_filter(&identity(Role::CarManager), &booking_id),
+            doc! { "booking_id": booking_id }
+        );
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use validator::Validate;
+
+/// Most photos sent at once
+pub const MAX_PHOTOS_PER_REQUEST: u64 = 30;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+/// When a photo was taken: handing the vehicle over or getting it back
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConditionStage {
+    CheckIn,
+    CheckOut,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum DamageSeverity {
+    Cosmetic,
+    Minor,
+    Major,
+}
+
+// =============================================================================
+// MAIN CONDITION PHOTO STRUCT
+// =============================================================================
+
+/// Photo of a rented vehicle from one angle ("front_left", "dashboard", ...) at
+/// check-in or check-out. The image itself lives in object storage.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConditionPhoto {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub booking_id: ObjectId,
+    pub stage: ConditionStage,
+    pub angle: String,
+    pub url: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub taken_at: DateTime<Utc>,
+    pub uploaded_by: String,
+    #[serde(default)]
+    pub annotations: Vec<PhotoAnnotation>,
+}
+
+/// Remark of a manager on a photo, optionally pointing at an area of it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PhotoAnnotation {
+    pub note: String,
+    pub severity: Option<DamageSeverity>,
+    pub region: Option<PhotoRegion>,
+    pub added_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub added_at: DateTime<Utc>,
+}
+
+/// Rectangle of a photo, as fractions of its width and height
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Validate, PartialEq)]
+pub struct PhotoRegion {
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub x: f64,
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub y: f64,
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub width: f64,
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub height: f64,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct AddConditionPhotosRequest {
+    pub stage: ConditionStage,
+    #[validate(length(min = 1, max = MAX_PHOTOS_PER_REQUEST), nested)]
+    pub photos: Vec<NewConditionPhoto>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
+pub struct NewConditionPhoto {
+    #[validate(length(min = 1, max = 50))]
+    pub angle: String,
+    #[validate(url, length(max = 2000))]
+    pub url: String,
+    pub taken_at: Option<DateTime<Utc>>, // Upload time when omitted
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct AnnotatePhotoRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub note: String,
+    pub severity: Option<DamageSeverity>,
+    #[validate(nested)]
+    pub region: Option<PhotoRegion>,
+}
+
+/// Latest check-in and check-out photos of one angle, side by side
+#[derive(Clone, Debug, Serialize)]
+pub struct ConditionPair {
+    pub angle: String,
+    pub check_in: Option<ConditionPhoto>,
+    pub check_out: Option<ConditionPhoto>,
+}
+
+/// Evidence bundle of a damage claim
+#[derive(Clone, Debug, Serialize)]
+pub struct ConditionDiff {
+    pub booking_id: ObjectId,
+    pub pairs: Vec<ConditionPair>,
+    /// Angles photographed at check-in but not at check-out, or the other way around
+    pub unmatched_angles: Vec<String>,
+    pub annotations: usize,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for ConditionPhoto {
+    fn get_collection() -> &'static str {
+        "condition_photos"
+    }
+}
+
+impl ConditionPhoto {
+    pub fn new(
+        booking_id: ObjectId,
+        stage: ConditionStage,
+        photo: NewConditionPhoto,
+        uploaded_by: String,
+    ) -> Self {
+        Self {
+            id: None,
+            booking_id,
+            stage,
+            angle: photo.angle.trim().to_lowercase(),
+            url: photo.url,
+            taken_at: photo.taken_at.unwrap_or_else(Utc::now),
+            uploaded_by,
+            annotations: Vec::new(),
+        }
+    }
+}
+
+impl PhotoAnnotation {
+    pub fn new(request: AnnotatePhotoRequest, added_by: String) -> Self {
+        Self {
+            note: request.note,
+            severity: request.severity,
+            region: request.region,
+            added_by,
+            added_at: Utc::now(),
+        }
+    }
+}
+
+impl ConditionDiff {
+    /// Pair the photos of a booking by angle, a newer photo of the same angle and
+    /// stage replacing an older one
+    pub fn pair(booking_id: ObjectId, photos: Vec<ConditionPhoto>) -> Self {
+        let mut by_angle: BTreeMap<String, ConditionPair> = BTreeMap::new();
+        for photo in photos {
+            let pair = by_angle
+                .entry(photo.angle.clone())
+                .or_insert_with(|| ConditionPair {
+                    angle: photo.angle.clone(),
+                    check_in: None,
+                    check_out: None,
+                });
+            let slot = match photo.stage {
+                ConditionStage::CheckIn => &mut pair.check_in,
+                ConditionStage::CheckOut => &mut pair.check_out,
+            };
+            if slot
+                .as_ref()
+                .is_none_or(|kept| kept.taken_at <= photo.taken_at)
+            {
+                *slot = Some(photo);
+            }
+        }
+
+        let pairs: Vec<ConditionPair> = by_angle.into_values().collect();
+        Self {
+            booking_id,
+            unmatched_angles: pairs
+                .iter()
+                .filter(|pair| pair.check_in.is_none() || pair.check_out.is_none())
+                .map(|pair| pair.angle.clone())
+                .collect(),
+            annotations: pairs
+                .iter()
+                .flat_map(|pair| [&pair.check_in, &pair.check_out])
+                .flatten()
+                .map(|photo| photo.annotations.len())
+                .sum(),
+            pairs,
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn photo(booking_id: ObjectId, stage: ConditionStage, angle: &str, age: i64) -> ConditionPhoto {
+        ConditionPhoto::new(
+            booking_id,
+            stage,
+            NewConditionPhoto {
+                angle: angle.to_string(),
+                url: format!("https://photos.example.com/{}.jpg", angle),
+                taken_at: Some(Utc::now() - Duration::hours(age)),
+            },
+            "manager_user_1".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_photos_are_paired_by_angle() {
+        let booking_id = ObjectId::new();
+        let mut scratched = photo(booking_id, ConditionStage::CheckOut, "Front_Left ", 1);
+        scratched.annotations.push(PhotoAnnotation::new(
+            AnnotatePhotoRequest {
+                note: "Scratch on the door".to_string(),
+                severity: Some(DamageSeverity::Minor),
+                region: None,
+            },
+            "manager_user_1".to_string(),
+        ));
+
+        let diff = ConditionDiff::pair(
+            booking_id,
+            vec![
+                photo(booking_id, ConditionStage::CheckIn, "front_left", 50),
+                photo(booking_id, ConditionStage::CheckIn, "front_left", 48),
+                scratched,
+                photo(booking_id, ConditionStage::CheckIn, "rear", 48),
+            ],
+        );
+
+        assert_eq!(diff.pairs.len(), 2);
+        let front = &diff.pairs[0];
+        assert_eq!(front.angle, "front_left");
+        let check_in = front.check_in.as_ref().unwrap();
+        assert!(check_in.taken_at > Utc::now() - Duration::hours(49));
+        assert_eq!(front.check_out.as_ref().unwrap().annotations.len(), 1);
+        assert_eq!(diff.unmatched_angles, vec!["rear"]);
+        assert_eq!(diff.annotations, 1);
+    }
+}
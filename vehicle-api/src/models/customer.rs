@@ -0,0 +1,62 @@
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::authentication::identity::CustomerProfile;
+
+// =============================================================================
+// MAIN CUSTOMER STRUCT
+// =============================================================================
+
+/// Profile document of a customer, keyed by the user ID of their credentials.
+/// Written by the customer onboarding, read on every request of the customer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Customer {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: String,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub verified: bool,
+    #[serde(default)]
+    pub email_preferences: EmailPreferences,
+}
+
+/// Optional emails a customer receives, booking lifecycle emails are always sent
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EmailPreferences {
+    #[serde(default = "subscribed")]
+    pub weekly_digest: bool,
+}
+
+fn subscribed() -> bool {
+    true
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Customer {
+    fn get_collection() -> &'static str {
+        "customers"
+    }
+}
+
+impl Default for EmailPreferences {
+    fn default() -> Self {
+        Self {
+            weekly_digest: subscribed(),
+        }
+    }
+}
+
+impl From<Customer> for CustomerProfile {
+    fn from(customer: Customer) -> Self {
+        Self {
+            name: customer.name,
+            email: customer.email,
+            verified: customer.verified,
+        }
+    }
+}
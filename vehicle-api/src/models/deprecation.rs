@@ -0,0 +1,124 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString};
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+/// Behavior scheduled for removal, still accepted while callers move away from it
+#[derive(
+    Clone, Copy, Debug, Serialize, Deserialize, EnumIter, EnumString, Display, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum DeprecatedFeature {
+    PlainPrice,       // Vehicle `price_by_day` sent as a number
+    PlainDescription, // Vehicle `description` sent as a string
+}
+
+// =============================================================================
+// MAIN DEPRECATION USAGE STRUCT
+// =============================================================================
+
+/// Requests of one caller relying on a deprecated feature, one document per feature,
+/// caller and API key
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeprecationUsage {
+    #[serde(rename = "_id")]
+    pub id: String, // See DeprecationUsage::key
+    pub feature: DeprecatedFeature,
+    pub user_id: String,
+    #[serde(default)]
+    pub api_key_prefix: Option<String>, // Same prefix as the API key listings, None for other credentials
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    pub requests: i64,
+    pub last_path: String, // e.g. "/protected/vehicles"
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub first_used_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub last_used_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// `?feature=plain_price` of GET /admin/deprecations
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct DeprecationQuery {
+    pub feature: Option<DeprecatedFeature>,
+}
+
+/// Callers still using a deprecated feature, most recent first
+#[derive(Clone, Debug, Serialize)]
+pub struct DeprecationReport {
+    pub feature: DeprecatedFeature,
+    pub replacement: &'static str,
+    pub sunset: Option<NaiveDate>,
+    pub requests: i64,
+    pub callers: Vec<DeprecationUsage>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for DeprecationUsage {
+    fn get_collection() -> &'static str {
+        "deprecation_usages"
+    }
+}
+
+impl DeprecatedFeature {
+    /// What callers should send instead
+    pub fn replacement(&self) -> &'static str {
+        match self {
+            Self::PlainPrice => "price_by_day as an {amount, currency} object",
+            Self::PlainDescription => "description as translations keyed by language",
+        }
+    }
+
+    /// Message of the `Warning` header and of the envelope warnings, without quotes
+    pub fn warning(&self, sunset: Option<NaiveDate>) -> String {
+        let removal = match sunset {
+            Some(sunset) => format!("removed on {}", sunset),
+            None => "scheduled for removal".to_string(),
+        };
+        format!(
+            "{} is deprecated ({}), send {}",
+            self,
+            removal,
+            self.replacement()
+        )
+    }
+}
+
+impl DeprecationUsage {
+    /// One usage per feature, caller and API key
+    pub fn key(feature: DeprecatedFeature, user_id: &str, api_key_prefix: Option<&str>) -> String {
+        format!(
+            "{}:{}:{}",
+            feature,
+            user_id,
+            api_key_prefix.unwrap_or_default()
+        )
+    }
+}
+
+impl DeprecationReport {
+    pub fn new(
+        feature: DeprecatedFeature,
+        sunset: Option<NaiveDate>,
+        callers: Vec<DeprecationUsage>,
+    ) -> Self {
+        Self {
+            feature,
+            replacement: feature.replacement(),
+            sunset,
+            requests: callers.iter().map(|usage| usage.requests).sum(),
+            callers,
+        }
+    }
+}
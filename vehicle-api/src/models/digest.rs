@@ -0,0 +1,123 @@
+use bson::oid::ObjectId;
+use serde::Deserialize;
+
+use crate::models::{Booking, Money, Payment, PaymentKind, PaymentStatus, Vehicle};
+
+// =============================================================================
+// MAIN DIGEST STRUCTS
+// =============================================================================
+
+/// Weekly summary emailed to a customer
+#[derive(Clone, Debug, Default)]
+pub struct BookingDigest {
+    /// Confirmed bookings starting soon, earliest first
+    pub upcoming: Vec<Booking>,
+    pub pending_actions: Vec<PendingAction>,
+    /// Vehicles like the ones the customer rented, cheapest first
+    pub recommendations: Vec<Vehicle>,
+}
+
+/// Something the customer has to do about a booking
+#[derive(Clone, Debug, PartialEq)]
+pub enum PendingAction {
+    /// Charges whose payment failed, still owed
+    UnpaidBalance { booking_id: ObjectId, amount: Money },
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Link of the digest email, signed for the customer it was sent to
+#[derive(Clone, Debug, Deserialize)]
+pub struct UnsubscribeQuery {
+    pub user_id: String,
+    pub token: String,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl BookingDigest {
+    /// Nothing worth an email, recommendations alone are not sent
+    pub fn is_empty(&self) -> bool {
+        self.upcoming.is_empty() && self.pending_actions.is_empty()
+    }
+}
+
+impl PendingAction {
+    /// Failed charges of a customer, summed per booking and currency
+    pub fn unpaid_balances(payments: &[Payment]) -> Vec<Self> {
+        let mut balances: Vec<(ObjectId, Money)> = Vec::new();
+        for payment in payments.iter().filter(|payment| {
+            payment.kind == PaymentKind::Charge && payment.status == PaymentStatus::Failed
+        }) {
+            match balances.iter_mut().find(|(booking_id, amount)| {
+                *booking_id == payment.booking_id && amount.currency == payment.amount.currency
+            }) {
+                Some((_, amount)) => amount.amount += payment.amount.amount,
+                None => balances.push((payment.booking_id, payment.amount)),
+            }
+        }
+        balances
+            .into_iter()
+            .map(|(booking_id, amount)| PendingAction::UnpaidBalance { booking_id, amount })
+            .collect()
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            PendingAction::UnpaidBalance { booking_id, amount } => format!(
+                "Booking {}: {} is still unpaid, please settle it before pickup",
+                booking_id.to_hex(),
+                amount
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Currency, Decimal};
+    use chrono::Utc;
+
+    fn payment(booking_id: ObjectId, kind: PaymentKind, status: PaymentStatus) -> Payment {
+        Payment {
+            id: None,
+            booking_id,
+            customer_id: "customer_user_1".to_string(),
+            kind,
+            amount: Money::new(Decimal::from(30), Currency::EUR),
+            reason: "Damaged mirror".to_string(),
+            status,
+            provider: "manual".to_string(),
+            provider_reference: None,
+            requested_by: "admin_user_1".to_string(),
+            requested_at: Utc::now(),
+            idempotency_key: None,
+        }
+    }
+
+    #[test]
+    fn test_failed_charges_are_unpaid_per_booking() {
+        let (first, second) = (ObjectId::new(), ObjectId::new());
+        let payments = vec![
+            payment(first, PaymentKind::Charge, PaymentStatus::Failed),
+            payment(first, PaymentKind::Charge, PaymentStatus::Failed),
+            payment(first, PaymentKind::Charge, PaymentStatus::Succeeded),
+            payment(second, PaymentKind::Refund, PaymentStatus::Failed),
+            payment(second, PaymentKind::Charge, PaymentStatus::Pending),
+        ];
+
+        assert_eq!(
+            PendingAction::unpaid_balances(&payments),
+            vec![PendingAction::UnpaidBalance {
+                booking_id: first,
+                amount: Money::new(Decimal::from(60), Currency::EUR)
+            }]
+        );
+        assert!(PendingAction::unpaid_balances(&[]).is_empty());
+    }
+}
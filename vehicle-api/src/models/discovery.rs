@@ -0,0 +1,38 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::models::AuthMethod;
+
+// =============================================================================
+// MAIN DISCOVERY STRUCTS
+// =============================================================================
+
+/// Root document telling clients what this deployment offers, see GET /
+#[derive(Clone, Debug, Serialize)]
+pub struct ApiDiscovery {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub features: DiscoveryFeatures,
+    pub auth_methods: Vec<AuthMethodInfo>,
+    /// Relation -> URL, e.g. "schemas" -> "/meta/schemas"
+    pub links: BTreeMap<&'static str, String>,
+}
+
+/// Optional features and whether this deployment enables them
+#[derive(Clone, Debug, Serialize)]
+pub struct DiscoveryFeatures {
+    pub webhooks: bool,
+    pub email_notifications: bool,
+    pub oidc_login: bool,
+    pub priority_lanes: bool,
+    pub chaos_testing: bool,
+    pub response_envelope_by_default: bool,
+    pub read_only: bool,
+}
+
+/// Way to authenticate on `/protected`, with where the credential goes
+#[derive(Clone, Debug, Serialize)]
+pub struct AuthMethodInfo {
+    pub method: AuthMethod,
+    pub usage: &'static str,
+}
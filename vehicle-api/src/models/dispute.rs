@@ -0,0 +1,288 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use validator::Validate;
+
+use crate::models::{Decimal, Money, PaymentKind};
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum DisputeStatus {
+    Open,        // Waiting for the rental company
+    UnderReview, // A manager is on it
+    Resolved,
+}
+
+/// How a dispute ends, money wise
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum DisputeOutcome {
+    Refund,   // The customer gets money back
+    Charge,   // The customer pays for damages
+    NoAction, // Dismissed, nothing to pay
+}
+
+// =============================================================================
+// MAIN DISPUTE STRUCT
+// =============================================================================
+
+/// Disagreement about a booking (damages, price, ...) between the customer and the
+/// rental company, with the evidence of both sides
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Dispute {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub booking_id: ObjectId,
+    pub customer_id: String,
+    pub opened_by: String,
+    pub reason: String,
+    pub description: Option<String>,
+    pub status: DisputeStatus,
+    #[serde(default)]
+    pub evidence: Vec<DisputeEvidence>,
+    /// Internal remarks of the staff, never shown to the customer
+    #[serde(default)]
+    pub notes: Vec<DisputeNote>,
+    pub resolution: Option<DisputeResolution>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub opened_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Document or photo supporting one side, stored in object storage
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DisputeEvidence {
+    pub url: String,
+    pub description: Option<String>,
+    pub added_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DisputeNote {
+    pub note: String,
+    pub added_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DisputeResolution {
+    pub outcome: DisputeOutcome,
+    pub amount: Option<Money>,
+    /// Refund or charge started for the outcome
+    pub payment_id: Option<ObjectId>,
+    pub comment: String,
+    pub resolved_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub resolved_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct OpenDisputeRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub reason: String,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct AddDisputeEvidenceRequest {
+    #[validate(url, length(max = 2000))]
+    pub url: String,
+    #[validate(length(max = 500))]
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct AddDisputeNoteRequest {
+    #[validate(length(min = 1, max = 2000))]
+    pub note: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct ResolveDisputeRequest {
+    pub outcome: DisputeOutcome,
+    /// Required for a refund or a charge, in the booking's currency
+    #[serde(default, with = "vehicle_api_types::money::amount::option")]
+    pub amount: Option<Decimal>,
+    #[validate(length(min = 1, max = 2000))]
+    pub comment: String,
+}
+
+/// Disputes by status and money moved by their resolutions, one sum per currency
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+pub struct DisputeStats {
+    pub open: u64,
+    pub under_review: u64,
+    pub resolved: u64,
+    pub refunded: Vec<Money>,
+    pub charged: Vec<Money>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Dispute {
+    fn get_collection() -> &'static str {
+        "disputes"
+    }
+}
+
+impl DisputeOutcome {
+    /// Payment carrying out the outcome, None when no money moves
+    pub fn payment_kind(&self) -> Option<PaymentKind> {
+        match self {
+            DisputeOutcome::Refund => Some(PaymentKind::Refund),
+            DisputeOutcome::Charge => Some(PaymentKind::Charge),
+            DisputeOutcome::NoAction => None,
+        }
+    }
+}
+
+impl Dispute {
+    pub fn new(
+        booking_id: ObjectId,
+        customer_id: String,
+        request: OpenDisputeRequest,
+        opened_by: String,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: None,
+            booking_id,
+            customer_id,
+            opened_by,
+            reason: request.reason,
+            description: request.description,
+            status: DisputeStatus::Open,
+            evidence: Vec::new(),
+            notes: Vec::new(),
+            resolution: None,
+            opened_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.status == DisputeStatus::Resolved
+    }
+}
+
+impl DisputeStats {
+    pub fn compute(disputes: &[Dispute]) -> Self {
+        let mut stats = Self::default();
+        for dispute in disputes {
+            match dispute.status {
+                DisputeStatus::Open => stats.open += 1,
+                DisputeStatus::UnderReview => stats.under_review += 1,
+                DisputeStatus::Resolved => stats.resolved += 1,
+            }
+        }
+        let moved = |outcome: DisputeOutcome| {
+            Money::sum_by_currency(
+                disputes
+                    .iter()
+                    .filter_map(|dispute| dispute.resolution.as_ref())
+                    .filter(|resolution| resolution.outcome == outcome)
+                    .filter_map(|resolution| resolution.amount),
+            )
+        };
+        stats.refunded = moved(DisputeOutcome::Refund);
+        stats.charged = moved(DisputeOutcome::Charge);
+        stats
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Currency;
+
+    fn eur(amount: i64) -> Money {
+        Money::new(Decimal::from(amount), Currency::EUR)
+    }
+
+    fn dispute(status: DisputeStatus, resolution: Option<(DisputeOutcome, Money)>) -> Dispute {
+        let request = OpenDisputeRequest {
+            reason: "Scratch was already there".to_string(),
+            description: None,
+        };
+        let mut dispute = Dispute::new(
+            ObjectId::new(),
+            "customer_user_1".to_string(),
+            request,
+            "customer_user_1".to_string(),
+        );
+        dispute.status = status;
+        dispute.resolution = resolution.map(|(outcome, amount)| DisputeResolution {
+            outcome,
+            amount: Some(amount),
+            payment_id: None,
+            comment: "Checked the check-in photos".to_string(),
+            resolved_by: "manager_user_1".to_string(),
+            resolved_at: Utc::now(),
+        });
+        dispute
+    }
+
+    #[test]
+    fn test_stats_count_statuses_and_amounts() {
+        let stats = DisputeStats::compute(&[
+            dispute(DisputeStatus::Open, None),
+            dispute(DisputeStatus::UnderReview, None),
+            dispute(
+                DisputeStatus::Resolved,
+                Some((DisputeOutcome::Refund, eur(40))),
+            ),
+            dispute(
+                DisputeStatus::Resolved,
+                Some((DisputeOutcome::Charge, eur(150))),
+            ),
+            dispute(
+                DisputeStatus::Resolved,
+                Some((DisputeOutcome::NoAction, eur(0))),
+            ),
+            dispute(
+                DisputeStatus::Resolved,
+                Some((
+                    DisputeOutcome::Refund,
+                    Money::new(Decimal::from(25), Currency::GBP),
+                )),
+            ),
+            dispute(
+                DisputeStatus::Resolved,
+                Some((DisputeOutcome::Refund, eur(10))),
+            ),
+        ]);
+
+        assert_eq!(
+            stats,
+            DisputeStats {
+                open: 1,
+                under_review: 1,
+                resolved: 5,
+                refunded: vec![eur(50), Money::new(Decimal::from(25), Currency::GBP)],
+                charged: vec![eur(150)],
+            }
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use bson::{doc, Document};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use validator::Validate;
+
+// =============================================================================
+// MAIN EXPERIMENT STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ExperimentVariant {
+    pub name: String, // e.g. "control"
+    pub weight: u32,  // Share of the identities relative to the other variants
+}
+
+/// A/B experiment running on the routes under `path_prefixes`. Every instance keeps
+/// the experiments in memory and assigns variants from a hash of the caller, so a
+/// caller gets the same variant everywhere as long as the variants do not change.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Experiment {
+    #[serde(rename = "_id")]
+    pub key: String, // e.g. "pricing"
+    pub description: Option<String>,
+    pub path_prefixes: Vec<String>, // e.g. "/protected/vehicles"
+    pub variants: Vec<ExperimentVariant>,
+    pub enabled: bool,
+    pub updated_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// First time a caller was served a variant, the exposure event analyses start from
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExperimentExposure {
+    #[serde(rename = "_id")]
+    pub id: String, // See ExperimentExposure::key
+    pub experiment: String,
+    pub variant: String,
+    pub user_id: String,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    pub path: String, // Route the caller was first exposed on
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub exposed_at: DateTime<Utc>,
+}
+
+/// Variant of one experiment served to the caller
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct ExperimentAssignment {
+    pub experiment: String,
+    pub variant: String,
+}
+
+/// Variants served on the current request, in the request extensions for handlers
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+pub struct ExperimentAssignments(pub Vec<ExperimentAssignment>);
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Body of PUT /admin/experiments/{key}
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct ExperimentRequest {
+    #[validate(length(max = 500))]
+    pub description: Option<String>,
+    #[validate(length(min = 1, max = 20))]
+    pub path_prefixes: Vec<String>,
+    #[validate(length(min = 2, max = 10))]
+    pub variants: Vec<ExperimentVariant>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// Callers exposed to one variant
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VariantExposures {
+    #[serde(alias = "_id")]
+    pub variant: String,
+    pub users: u32,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Experiment {
+    fn get_collection() -> &'static str {
+        "experiments"
+    }
+}
+
+impl crate::services::mongodb::MongoStruct for ExperimentExposure {
+    fn get_collection() -> &'static str {
+        "experiment_exposures"
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ExperimentRequest {
+    /// Keys go in URLs and in the X-Experiments header
+    pub fn validate_key(key: &str) -> Result<(), String> {
+        let valid = !key.is_empty()
+            && key.len() <= 50
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if valid {
+            Ok(())
+        } else {
+            Err("Experiment keys are 1 to 50 letters, digits, _ or -".to_string())
+        }
+    }
+
+    pub fn validate_variants(&self) -> Result<(), String> {
+        for (index, variant) in self.variants.iter().enumerate() {
+            if variant.name.is_empty() || variant.name.contains([',', '=', ' ']) {
+                return Err(format!("Invalid variant name \"{}\"", variant.name));
+            }
+            if self.variants[..index]
+                .iter()
+                .any(|other| other.name == variant.name)
+            {
+                return Err(format!("Variant \"{}\" is listed twice", variant.name));
+            }
+        }
+        if self.variants.iter().all(|variant| variant.weight == 0) {
+            return Err("At least one variant needs a weight above 0".to_string());
+        }
+        if self
+            .path_prefixes
+            .iter()
+            .any(|prefix| !prefix.starts_with('/'))
+        {
+            return Err("Path prefixes start with /".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Experiment {
+    pub fn new(key: String, request: ExperimentRequest, updated_by: String) -> Self {
+        Self {
+            key,
+            description: request.description,
+            path_prefixes: request.path_prefixes,
+            variants: request.variants,
+            enabled: request.enabled,
+            updated_by,
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn applies_to(&self, path: &str) -> bool {
+        self.enabled
+            && self
+                .path_prefixes
+                .iter()
+                .any(|prefix| path.starts_with(prefix))
+    }
+
+    /// Variant of a caller: the hash of the experiment key and the caller picks a
+    /// point among the weights, so experiments are assigned independently
+    pub fn assign(&self, user_id: &str) -> Option<&ExperimentVariant> {
+        let total: u64 = self
+            .variants
+            .iter()
+            .map(|variant| u64::from(variant.weight))
+            .sum();
+        if total == 0 {
+            return None;
+        }
+
+        let digest = Sha256::digest(format!("{}:{}", self.key, user_id).as_bytes());
+        let mut bucket = u64::from_be_bytes(digest[..8].try_into().ok()?) % total;
+        self.variants.iter().find(|variant| {
+            let weight = u64::from(variant.weight);
+            if bucket < weight {
+                true
+            } else {
+                bucket -= weight;
+                false
+            }
+        })
+    }
+}
+
+impl ExperimentExposure {
+    /// One exposure per experiment, variant and caller
+    pub fn key(experiment: &str, variant: &str, user_id: &str) -> String {
+        format!("{}:{}:{}", experiment, variant, user_id)
+    }
+}
+
+impl VariantExposures {
+    /// Callers exposed to each variant of an experiment, by variant name
+    pub fn pipeline(experiment: &str) -> Vec<Document> {
+        vec![
+            doc! { "$match": { "experiment": experiment } },
+            doc! { "$group": { "_id": "$variant", "users": { "$sum": 1 } } },
+            doc! { "$sort": { "_id": 1 } },
+        ]
+    }
+}
+
+impl ExperimentAssignments {
+    /// Variant of an experiment, None when the request is not part of it
+    pub fn variant(&self, experiment: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|assignment| assignment.experiment == experiment)
+            .map(|assignment| assignment.variant.as_str())
+    }
+
+    /// Value of the X-Experiments header, e.g. "pricing=discount, ranking=control"
+    pub fn header_value(&self) -> String {
+        self.0
+            .iter()
+            .map(|assignment| format!("{}={}", assignment.experiment, assignment.variant))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn experiment(weights: &[u32]) -> Experiment {
+        let variants = weights
+            .iter()
+            .enumerate()
+            .map(|(index, weight)| ExperimentVariant {
+                name: format!("variant_{}", index),
+                weight: *weight,
+            })
+            .collect();
+        Experiment::new(
+            "pricing".to_string(),
+            ExperimentRequest {
+                description: None,
+                path_prefixes: vec!["/protected/vehicles".to_string()],
+                variants,
+                enabled: true,
+            },
+            "admin_user_1".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_assignment_is_deterministic_and_follows_weights() {
+        let split = experiment(&[1, 3]);
+        let first = split.assign("customer_1").unwrap().name.clone();
+        assert_eq!(split.assign("customer_1").unwrap().name, first);
+
+        let in_second: usize = (0..4000)
+            .filter(|user| split.assign(&format!("customer_{}", user)).unwrap().name == "variant_1")
+            .count();
+        assert!((2800..3200).contains(&in_second), "{}", in_second);
+
+        // A variant without weight gets nobody
+        let all_in_second = experiment(&[0, 1]);
+        assert!((0..100).all(|user| {
+            all_in_second
+                .assign(&format!("customer_{}", user))
+                .unwrap()
+                .name
+                == "variant_1"
+        }));
+    }
+
+    #[test]
+    fn test_applies_to_enabled_experiments_on_their_routes() {
+        let mut experiment = experiment(&[1, 1]);
+        assert!(experiment.applies_to("/protected/vehicles/search"));
+        assert!(!experiment.applies_to("/protected/bookings"));
+
+        experiment.enabled = false;
+        assert!(!experiment.applies_to("/protected/vehicles"));
+    }
+
+    #[test]
+    fn test_variants_validation() {
+        let request = |names: &[&str], weights: &[u32]| ExperimentRequest {
+            description: None,
+            path_prefixes: vec!["/protected/vehicles".to_string()],
+            variants: names
+                .iter()
+                .zip(weights)
+                .map(|(name, weight)| ExperimentVariant {
+                    name: name.to_string(),
+                    weight: *weight,
+                })
+                .collect(),
+            enabled: true,
+        };
+
+        assert!(request(&["control", "discount"], &[1, 1])
+            .validate_variants()
+            .is_ok());
+        assert!(request(&["control", "control"], &[1, 1])
+            .validate_variants()
+            .is_err());
+        assert!(request(&["control", "a=b"], &[1, 1])
+            .validate_variants()
+            .is_err());
+        assert!(request(&["control", "discount"], &[0, 0])
+            .validate_variants()
+            .is_err());
+
+        assert!(ExperimentRequest::validate_key("search-ranking_v2").is_ok());
+        assert!(ExperimentRequest::validate_key("pricing=a").is_err());
+        assert!(ExperimentRequest::validate_key("").is_err());
+    }
+
+    #[test]
+    fn test_header_value() {
+        let assignments = ExperimentAssignments(vec![
+            ExperimentAssignment {
+                experiment: "pricing".to_string(),
+                variant: "discount".to_string(),
+            },
+            ExperimentAssignment {
+                experiment: "ranking".to_string(),
+                variant: "control".to_string(),
+            },
+        ]);
+        assert_eq!(
+            assignments.header_value(),
+            "pricing=discount, ranking=control"
+        );
+        assert_eq!(assignments.variant("ranking"), Some("control"));
+        assert_eq!(assignments.variant("search"), None);
+    }
+}
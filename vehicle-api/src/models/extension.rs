@@ -0,0 +1,158 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use validator::Validate;
+
+use crate::models::{Booking, BookingStatus, Money};
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExtensionStatus {
+    Pending, // Days held, waiting for a manager
+    Approved,
+    Rejected,
+}
+
+// =============================================================================
+// MAIN EXTENSION STRUCT
+// =============================================================================
+
+/// Request of a customer to keep the vehicle of a confirmed booking until a later
+/// `to_date`. The extra days are held while a manager decides.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BookingExtension {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub booking_id: ObjectId,
+    pub customer_id: String,
+    pub current_to_date: NaiveDate, // to_date of the booking when requested
+    pub to_date: NaiveDate,
+    pub status: ExtensionStatus,
+    /// Price of the extra days, quoted when requested and charged on approval
+    pub extra_price: Option<Money>,
+    pub requested_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub requested_at: DateTime<Utc>,
+    pub decided_by: Option<String>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub decided_at: Option<DateTime<Utc>>,
+    pub reason: Option<String>, // Why it was rejected
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Body of POST /bookings/{id}/extend
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct ExtendBookingRequest {
+    pub to_date: NaiveDate,
+}
+
+/// Body of POST /bookings/{id}/extensions/{extension_id}/reject
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct RejectExtensionRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub reason: String,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for BookingExtension {
+    fn get_collection() -> &'static str {
+        "booking_extensions"
+    }
+}
+
+impl BookingExtension {
+    pub fn new(
+        booking_id: ObjectId,
+        booking: &Booking,
+        to_date: NaiveDate,
+        extra_price: Option<Money>,
+        requested_by: String,
+    ) -> Self {
+        Self {
+            id: None,
+            booking_id,
+            customer_id: booking.customer_id.clone(),
+            current_to_date: booking.to_date,
+            to_date,
+            status: ExtensionStatus::Pending,
+            extra_price,
+            requested_by,
+            requested_at: Utc::now(),
+            decided_by: None,
+            decided_at: None,
+            reason: None,
+        }
+    }
+
+    /// First day the extension adds to the booking
+    pub fn first_day(&self) -> NaiveDate {
+        self.current_to_date + Days::new(1)
+    }
+
+    /// A booking can be extended while confirmed and not returned, to a later day
+    pub fn validate_request(booking: &Booking, to_date: NaiveDate) -> Result<(), String> {
+        if booking.status != BookingStatus::Confirmed {
+            return Err("Only confirmed bookings can be extended".to_string());
+        }
+        if booking.check_out.is_some() {
+            return Err("The vehicle was already returned".to_string());
+        }
+        if to_date <= booking.to_date {
+            return Err(format!("to_date must be after {}", booking.to_date));
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures::{booking_between, date};
+    use crate::models::{Currency, Handover};
+
+    #[test]
+    fn test_only_ongoing_confirmed_bookings_extend_to_later_days() {
+        let mut booking = booking_between(date(8, 1), date(8, 5));
+        assert!(BookingExtension::validate_request(&booking, date(8, 8)).is_err());
+
+        booking.status = BookingStatus::Confirmed;
+        assert!(BookingExtension::validate_request(&booking, date(8, 8)).is_ok());
+        assert!(BookingExtension::validate_request(&booking, date(8, 5)).is_err());
+
+        let extension = BookingExtension::new(
+            ObjectId::new(),
+            &booking,
+            date(8, 8),
+            Money::from_f64(150.0, Currency::EUR),
+            "customer_user_1".to_string(),
+        );
+        assert_eq!(extension.first_day(), date(8, 6));
+
+        booking.check_out = Some(Handover {
+            at: Utc::now(),
+            odometer_km: 1200,
+            fuel_percent: 80,
+            recorded_by: "manager_user_1".to_string(),
+        });
+        assert!(BookingExtension::validate_request(&booking, date(8, 8)).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+use bson::oid::ObjectId;
+use chrono::NaiveDate;
+
+use crate::models::{Booking, CreateBookingRequest};
+
+/// Day of 2025, the year the test bookings are made in
+pub fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+}
+
+/// Pending booking of customer_user_1 from 2025-08-01 to 2025-08-03, for a new vehicle
+/// and not stored yet
+pub fn booking() -> Booking {
+    booking_between(date(8, 1), date(8, 3))
+}
+
+/// Same booking between other dates
+pub fn booking_between(from_date: NaiveDate, to_date: NaiveDate) -> Booking {
+    let request = CreateBookingRequest {
+        vehicle_id: ObjectId::new(),
+        from_date,
+        to_date,
+        driver: None,
+    };
+    Booking::new(request, "customer_user_1".to_string())
+}
@@ -0,0 +1,185 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HolidaySource {
+    Calendar, // Loaded from the holiday provider
+    Override, // Set by an admin
+}
+
+// =============================================================================
+// MAIN HOLIDAY STRUCTS
+// =============================================================================
+
+/// A public holiday, as used by pricing and availability
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Holiday {
+    pub country: String,
+    pub date: NaiveDate,
+    pub name: String,
+    pub surcharge_percent: f64,
+    pub blackout: bool, // No pickup or return on that day
+    pub source: HolidaySource,
+}
+
+/// Admin override of a calendar day, stored in MongoDB
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HolidayOverride {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub country: String,
+    pub date: NaiveDate,
+    pub name: String,
+    pub surcharge_percent: f64,
+    pub blackout: bool,
+    pub active: bool, // false removes a holiday loaded from the calendar
+    pub updated_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct UpsertHolidayRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(range(min = 0.0, max = 500.0))]
+    pub surcharge_percent: f64,
+    pub blackout: bool,
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct HolidayQuery {
+    pub country: Option<String>,
+    pub year: Option<i32>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for HolidayOverride {
+    fn get_collection() -> &'static str {
+        "holiday_overrides"
+    }
+}
+
+fn default_active() -> bool {
+    true
+}
+
+impl HolidayOverride {
+    pub fn new(
+        country: String,
+        date: NaiveDate,
+        request: UpsertHolidayRequest,
+        updated_by: String,
+    ) -> Self {
+        Self {
+            id: None,
+            country,
+            date,
+            name: request.name,
+            surcharge_percent: request.surcharge_percent,
+            blackout: request.blackout,
+            active: request.active,
+            updated_by,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+impl Holiday {
+    /// Apply admin overrides to a calendar, overrides win on the same date
+    pub fn merge(calendar: Vec<Holiday>, overrides: Vec<HolidayOverride>) -> Vec<Holiday> {
+        let mut holidays: Vec<Holiday> = calendar
+            .into_iter()
+            .filter(|holiday| {
+                !overrides
+                    .iter()
+                    .any(|o| o.date == holiday.date && o.country == holiday.country)
+            })
+            .collect();
+
+        holidays.extend(overrides.into_iter().filter(|o| o.active).map(|o| Holiday {
+            country: o.country,
+            date: o.date,
+            name: o.name,
+            surcharge_percent: o.surcharge_percent,
+            blackout: o.blackout,
+            source: HolidaySource::Override,
+        }));
+
+        holidays.sort_by_key(|holiday| holiday.date);
+        holidays
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures::date;
+
+    fn holiday(month: u32, day: u32, name: &str) -> Holiday {
+        Holiday {
+            country: "FR".to_string(),
+            date: date(month, day),
+            name: name.to_string(),
+            surcharge_percent: 20.0,
+            blackout: true,
+            source: HolidaySource::Calendar,
+        }
+    }
+
+    fn override_for(month: u32, day: u32, active: bool) -> HolidayOverride {
+        let request = UpsertHolidayRequest {
+            name: "Local fair".to_string(),
+            surcharge_percent: 50.0,
+            blackout: false,
+            active,
+        };
+        HolidayOverride::new(
+            "FR".to_string(),
+            date(month, day),
+            request,
+            "Admin".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_merge_applies_overrides() {
+        let calendar = vec![
+            holiday(5, 1, "Fête du Travail"),
+            holiday(7, 14, "Fête nationale"),
+            holiday(12, 25, "Noël"),
+        ];
+        let overrides = vec![
+            override_for(7, 14, true),
+            override_for(12, 25, false),
+            override_for(6, 21, true),
+        ];
+
+        let merged = Holiday::merge(calendar, overrides);
+        let dates: Vec<NaiveDate> = merged.iter().map(|h| h.date).collect();
+
+        assert_eq!(dates, vec![date(5, 1), date(6, 21), date(7, 14)]);
+        assert_eq!(merged[2].surcharge_percent, 50.0);
+        assert_eq!(merged[2].source, HolidaySource::Override);
+    }
+}
@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum IdempotencyStatus {
+    InProgress, // The first request is still running
+    Completed,
+}
+
+// =============================================================================
+// MAIN IDEMPOTENCY STRUCTS
+// =============================================================================
+
+/// Request made with an `Idempotency-Key`, the status and resource of its response
+/// replayed to the retries using the same key. The response body is not kept, it may
+/// hold personal data.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    #[serde(rename = "_id")]
+    pub id: String, // Hash of the caller and the key
+    pub user_id: String,
+    pub request_hash: String,
+    pub status: IdempotencyStatus,
+    pub response_status: Option<u16>,
+    pub resource_id: Option<String>, // Read again to replay the response
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    /// Until then the key cannot be reused, a short lock while in progress
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub expires_at: DateTime<Utc>,
+}
+
+impl crate::services::mongodb::MongoStruct for IdempotencyRecord {
+    fn get_collection() -> &'static str {
+        "idempotency_keys"
+    }
+}
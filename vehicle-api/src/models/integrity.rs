@@ -0,0 +1,286 @@
+use std::collections::{HashMap, HashSet};
+
+use bson::oid::ObjectId;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use crate::models::{
+    Booking, BookingExtension, BookingStatus, ReservationDay, CLAIM_GRACE_SECONDS,
+};
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum IntegrityIssueKind {
+    MissingDay,   // Day of an active booking nobody holds, repaired by claiming it
+    OrphanDay,    // Day held by a booking gone, cancelled or rejected, repaired by freeing it
+    StrayDay,     // Day held by an active booking outside its dates, repaired by freeing it
+    DoubleBooked, // Day of an active booking held by another one, left to the managers
+}
+
+// =============================================================================
+// MAIN INTEGRITY STRUCTS
+// =============================================================================
+
+/// Result of a cross-check of the bookings against `reservation_days`, from today on
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    /// Whether the issues found were repaired
+    pub repair: bool,
+    pub bookings_checked: u64,
+    pub days_checked: u64,
+    pub issues: Vec<IntegrityIssue>,
+    /// User who asked for it, None for the scheduled job
+    pub requested_by: Option<String>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub started_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub finished_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct IntegrityIssue {
+    pub kind: IntegrityIssueKind,
+    pub vehicle_id: ObjectId,
+    pub day: NaiveDate,
+    /// Booking that should hold the day
+    pub booking_id: Option<ObjectId>,
+    /// Booking holding the day
+    pub holder_id: Option<ObjectId>,
+    #[serde(default)]
+    pub repaired: bool,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Query of POST /admin/integrity/check
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct IntegrityCheckQuery {
+    #[serde(default)]
+    pub repair: bool,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for IntegrityReport {
+    fn get_collection() -> &'static str {
+        "integrity_reports"
+    }
+}
+
+impl IntegrityIssue {
+    fn new(
+        kind: IntegrityIssueKind,
+        vehicle_id: ObjectId,
+        day: NaiveDate,
+        booking_id: Option<ObjectId>,
+        holder_id: Option<ObjectId>,
+    ) -> Self {
+        Self {
+            kind,
+            vehicle_id,
+            day,
+            booking_id,
+            holder_id,
+            repaired: false,
+        }
+    }
+}
+
+/// Compare the days the active bookings and their pending extensions should hold from
+/// `today` on with the held `days`. Claims younger than the grace period are creations
+/// in progress and ignored.
+pub fn find_issues(
+    bookings: &[Booking],
+    extensions: &[BookingExtension],
+    days: &[ReservationDay],
+    today: NaiveDate,
+    now: DateTime<Utc>,
+) -> Vec<IntegrityIssue> {
+    let active: HashMap<ObjectId, &Booking> = bookings
+        .iter()
+        .filter(|booking| {
+            matches!(
+                booking.status,
+                BookingStatus::Pending | BookingStatus::Confirmed
+            )
+        })
+        .filter_map(|booking| booking.id.map(|id| (id, booking)))
+        .collect();
+
+    // Vehicle, day and booking of every day that should be held
+    let mut expected: Vec<(ObjectId, NaiveDate, ObjectId)> = Vec::new();
+    for (booking_id, booking) in &active {
+        for day in ReservationDay::days(booking.from_date.max(today), booking.to_date) {
+            expected.push((booking.vehicle_id, day, *booking_id));
+        }
+    }
+    for extension in extensions {
+        let Some(booking) = active.get(&extension.booking_id) else {
+            continue;
+        };
+        for day in ReservationDay::days(extension.first_day().max(today), extension.to_date) {
+            expected.push((booking.vehicle_id, day, extension.booking_id));
+        }
+    }
+    expected.sort_by_key(|(vehicle_id, day, booking_id)| (*vehicle_id, *day, *booking_id));
+    let legitimate: HashSet<(String, ObjectId)> = expected
+        .iter()
+        .map(|(vehicle_id, day, booking_id)| (ReservationDay::key(vehicle_id, *day), *booking_id))
+        .collect();
+
+    let mut issues = Vec::new();
+    let mut held: HashMap<&str, &ReservationDay> = HashMap::new();
+    for reservation in days.iter().filter(|reservation| reservation.day >= today) {
+        held.insert(&reservation.id, reservation);
+        if legitimate.contains(&(reservation.id.clone(), reservation.booking_id)) {
+            continue;
+        }
+        let kind = if active.contains_key(&reservation.booking_id) {
+            IntegrityIssueKind::StrayDay
+        } else if reservation.claimed_at + Duration::seconds(CLAIM_GRACE_SECONDS) > now {
+            continue;
+        } else {
+            IntegrityIssueKind::OrphanDay
+        };
+        issues.push(IntegrityIssue::new(
+            kind,
+            reservation.vehicle_id,
+            reservation.day,
+            None,
+            Some(reservation.booking_id),
+        ));
+    }
+
+    for (vehicle_id, day, booking_id) in expected {
+        let key = ReservationDay::key(&vehicle_id, day);
+        let holder = held
+            .get(key.as_str())
+            .map(|reservation| reservation.booking_id);
+        if holder == Some(booking_id) {
+            continue;
+        }
+        let kind = match holder {
+            Some(holder) if legitimate.contains(&(key, holder)) => IntegrityIssueKind::DoubleBooked,
+            _ => IntegrityIssueKind::MissingDay,
+        };
+        issues.push(IntegrityIssue::new(
+            kind,
+            vehicle_id,
+            day,
+            Some(booking_id),
+            holder,
+        ));
+    }
+
+    issues
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures::{booking_between, date};
+
+    fn booking(vehicle_id: ObjectId, from: u32, to: u32) -> Booking {
+        let mut booking = booking_between(date(6, from), date(6, to));
+        booking.id = Some(ObjectId::new());
+        booking.vehicle_id = vehicle_id;
+        booking
+    }
+
+    fn held(booking: &Booking, day: u32, claimed_at: DateTime<Utc>) -> ReservationDay {
+        let mut reservation =
+            ReservationDay::new(booking.vehicle_id, date(6, day), booking.id.unwrap());
+        reservation.claimed_at = claimed_at;
+        reservation
+    }
+
+    fn kinds(issues: &[IntegrityIssue]) -> Vec<(IntegrityIssueKind, NaiveDate)> {
+        issues.iter().map(|issue| (issue.kind, issue.day)).collect()
+    }
+
+    #[test]
+    fn test_days_are_checked_both_ways_from_today() {
+        let now = Utc::now();
+        let old = now - Duration::hours(1);
+        let vehicle_id = ObjectId::new();
+        let mut confirmed = booking(vehicle_id, 1, 4);
+        confirmed.status = BookingStatus::Confirmed;
+        let mut cancelled = booking(vehicle_id, 8, 9);
+        cancelled.status = BookingStatus::Cancelled("plans changed".to_string());
+        let extension = BookingExtension::new(
+            confirmed.id.unwrap(),
+            &confirmed,
+            date(6, 5),
+            None,
+            "c".into(),
+        );
+
+        let days = vec![
+            held(&confirmed, 1, old), // Before today, not checked
+            held(&confirmed, 2, old),
+            held(&confirmed, 3, old),
+            held(&confirmed, 5, old), // Pending extension
+            held(&confirmed, 6, old),
+            held(&cancelled, 8, old),
+            held(&cancelled, 9, now), // Too recent to tell
+        ];
+        let bookings = [confirmed, cancelled];
+        let issues = find_issues(&bookings, &[extension], &days, date(6, 2), now);
+
+        assert_eq!(
+            kinds(&issues),
+            vec![
+                (IntegrityIssueKind::StrayDay, date(6, 6)),
+                (IntegrityIssueKind::OrphanDay, date(6, 8)),
+                (IntegrityIssueKind::MissingDay, date(6, 4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_active_bookings_are_double_booked() {
+        let now = Utc::now();
+        let vehicle_id = ObjectId::new();
+        let first = booking(vehicle_id, 1, 2);
+        let second = booking(vehicle_id, 2, 3);
+        let days = vec![
+            held(&first, 1, now),
+            held(&first, 2, now),
+            held(&second, 3, now),
+        ];
+
+        let issues = find_issues(
+            &[first.clone(), second.clone()],
+            &[],
+            &days,
+            date(6, 1),
+            now,
+        );
+        assert_eq!(
+            issues,
+            vec![IntegrityIssue::new(
+                IntegrityIssueKind::DoubleBooked,
+                vehicle_id,
+                date(6, 2),
+                second.id,
+                first.id,
+            )]
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use crate::models::{Currency, Decimal, Money, Payment, PaymentKind};
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum LedgerEntryKind {
+    Charge,  // Rental price on confirmation, extra charges afterwards
+    Refund,  // Money back to the customer
+    Fee,     // Cancellation, late return, ...
+    Deposit, // Security deposit, held and not revenue
+}
+
+// =============================================================================
+// MAIN LEDGER STRUCT
+// =============================================================================
+
+/// Financial event of a booking. Entries are only ever appended, a mistake is fixed
+/// with a new entry; `balance` is the running total after this entry. Every entry is in
+/// the booking's currency.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub booking_id: ObjectId,
+    pub customer_id: String,
+    /// Position in the booking's ledger, starting at 1
+    pub sequence: u32,
+    pub kind: LedgerEntryKind,
+    /// What the customer pays, negative for refunds
+    pub amount: Money,
+    /// Net amount charged to the customer so far, this entry included
+    pub balance: Money,
+    pub description: String,
+    /// Payment carrying out the entry, None for the rental price
+    pub payment_id: Option<ObjectId>,
+    pub recorded_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub recorded_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Entry to append, the ledger numbers it and computes the balance
+#[derive(Clone, Debug)]
+pub struct NewLedgerEntry {
+    pub booking_id: ObjectId,
+    pub customer_id: String,
+    pub kind: LedgerEntryKind,
+    /// Positive, the kind gives the direction
+    pub amount: Money,
+    pub description: String,
+    pub payment_id: Option<ObjectId>,
+    pub recorded_by: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BookingLedger {
+    pub booking_id: ObjectId,
+    pub entries: Vec<LedgerEntry>,
+    #[serde(flatten)]
+    pub totals: LedgerTotals,
+}
+
+/// Sums of a booking's ledger, in its currency
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+pub struct LedgerTotals {
+    pub charged: Money,
+    pub fees: Money,
+    pub deposits: Money,
+    pub refunded: Money,
+    /// Net amount charged to the customer: charges, fees and deposits less refunds
+    pub balance: Money,
+    /// Charges and fees net of refunds, deposits excluded
+    pub revenue: Money,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for LedgerEntry {
+    fn get_collection() -> &'static str {
+        "ledger_entries"
+    }
+}
+
+impl LedgerEntryKind {
+    /// Sign of the kind's amounts, from the customer's side
+    pub fn sign(&self) -> Decimal {
+        match self {
+            LedgerEntryKind::Refund => Decimal::NEGATIVE_ONE,
+            LedgerEntryKind::Charge | LedgerEntryKind::Fee | LedgerEntryKind::Deposit => {
+                Decimal::ONE
+            }
+        }
+    }
+}
+
+impl From<PaymentKind> for LedgerEntryKind {
+    fn from(kind: PaymentKind) -> Self {
+        match kind {
+            PaymentKind::Refund => LedgerEntryKind::Refund,
+            PaymentKind::Charge | PaymentKind::Rental => LedgerEntryKind::Charge,
+        }
+    }
+}
+
+impl NewLedgerEntry {
+    /// Entry of a refund or charge started with the payment provider
+    pub fn of_payment(payment: &Payment) -> Self {
+        Self {
+            booking_id: payment.booking_id,
+            customer_id: payment.customer_id.clone(),
+            kind: payment.kind.into(),
+            amount: payment.amount,
+            description: payment.reason.clone(),
+            payment_id: payment.id,
+            recorded_by: payment.requested_by.clone(),
+        }
+    }
+}
+
+impl LedgerEntry {
+    /// Entry following `previous`, the last entry of the booking's ledger. Err when the
+    /// entry is not in the currency the ledger is kept in.
+    pub fn append(previous: Option<&LedgerEntry>, entry: NewLedgerEntry) -> Result<Self, String> {
+        let currency = entry.amount.currency;
+        let balance = previous.map_or(Money::zero(currency), |previous| previous.balance);
+        if balance.currency != currency {
+            return Err(format!(
+                "The ledger of booking {} is kept in {}, not {}",
+                entry.booking_id.to_hex(),
+                balance.currency,
+                currency
+            ));
+        }
+
+        let amount = Money::new(entry.kind.sign() * entry.amount.amount.abs(), currency).round();
+        Ok(Self {
+            id: None,
+            booking_id: entry.booking_id,
+            customer_id: entry.customer_id,
+            sequence: previous.map_or(1, |previous| previous.sequence + 1),
+            kind: entry.kind,
+            amount,
+            balance: Money::new(balance.amount + amount.amount, currency),
+            description: entry.description,
+            payment_id: entry.payment_id,
+            recorded_by: entry.recorded_by,
+            recorded_at: Utc::now(),
+        })
+    }
+}
+
+impl LedgerTotals {
+    /// Totals of a ledger kept in `currency`
+    pub fn compute(currency: Currency, entries: &[LedgerEntry]) -> Self {
+        let total = |kinds: &[LedgerEntryKind]| -> Decimal {
+            entries
+                .iter()
+                .filter(|entry| kinds.contains(&entry.kind))
+                .map(|entry| entry.amount.amount)
+                .sum()
+        };
+        let money = |amount: Decimal| Money::new(amount, currency);
+        Self {
+            charged: money(total(&[LedgerEntryKind::Charge])),
+            fees: money(total(&[LedgerEntryKind::Fee])),
+            deposits: money(total(&[LedgerEntryKind::Deposit])),
+            refunded: money(-total(&[LedgerEntryKind::Refund])),
+            balance: money(total(&[
+                LedgerEntryKind::Charge,
+                LedgerEntryKind::Fee,
+                LedgerEntryKind::Deposit,
+                LedgerEntryKind::Refund,
+            ])),
+            revenue: money(total(&[
+                LedgerEntryKind::Charge,
+                LedgerEntryKind::Fee,
+                LedgerEntryKind::Refund,
+            ])),
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eur(amount: i64, scale: u32) -> Money {
+        Money::new(Decimal::new(amount, scale), Currency::EUR)
+    }
+
+    fn entry(kind: LedgerEntryKind, amount: Money) -> NewLedgerEntry {
+        NewLedgerEntry {
+            booking_id: ObjectId::new(),
+            customer_id: "customer_user_1".to_string(),
+            kind,
+            amount,
+            description: kind.to_string(),
+            payment_id: None,
+            recorded_by: "admin_user_1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_entries_keep_a_running_balance() {
+        let mut entries: Vec<LedgerEntry> = Vec::new();
+        for (kind, amount) in [
+            (LedgerEntryKind::Charge, eur(240, 0)),
+            (LedgerEntryKind::Deposit, eur(500, 0)),
+            (LedgerEntryKind::Fee, eur(355, 1)),
+            (LedgerEntryKind::Refund, eur(402, 1)),
+        ] {
+            let next = LedgerEntry::append(entries.last(), entry(kind, amount)).unwrap();
+            entries.push(next);
+        }
+
+        let sequences: Vec<u32> = entries.iter().map(|entry| entry.sequence).collect();
+        assert_eq!(sequences, vec![1, 2, 3, 4]);
+        assert_eq!(entries[3].amount, eur(-402, 1));
+        assert_eq!(entries[3].balance, eur(7353, 1));
+
+        let totals = LedgerTotals::compute(Currency::EUR, &entries);
+        assert_eq!(totals.balance, entries[3].balance);
+        assert_eq!(totals.revenue, eur(2353, 1));
+        assert_eq!(totals.refunded, eur(402, 1));
+    }
+
+    #[test]
+    fn test_entries_stay_in_the_ledger_currency() {
+        let first = LedgerEntry::append(None, entry(LedgerEntryKind::Charge, eur(240, 0))).unwrap();
+        let refund = entry(
+            LedgerEntryKind::Refund,
+            Money::new(Decimal::new(40, 0), Currency::USD),
+        );
+        assert!(LedgerEntry::append(Some(&first), refund).is_err());
+    }
+}
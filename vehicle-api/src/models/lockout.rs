@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Invalid API key attempts of a source address, as tracked by this instance
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct IpLockout {
+    pub ip: String,
+    pub consecutive_failures: u32, // Since the last lockout or valid key
+    pub last_failure_at: DateTime<Utc>,
+    pub locked_until: Option<DateTime<Utc>>, // Blocked until then, None when not locked
+}
@@ -0,0 +1,292 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use validator::Validate;
+
+use crate::authentication::identity::Identity;
+use crate::models::{Money, Vehicle};
+
+/// A service is due soon this many days before its date
+pub const SERVICE_DUE_SOON_DAYS: i64 = 30;
+/// A service is due soon this many kilometers before its mileage
+pub const SERVICE_DUE_SOON_KM: u32 = 1000;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum ServiceDue {
+    Soon,
+    Overdue,
+}
+
+// =============================================================================
+// MAIN MAINTENANCE STRUCT
+// =============================================================================
+
+/// Service carried out on a vehicle, with when the next one is due
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MaintenanceRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub vehicle_id: ObjectId,
+    pub service_date: NaiveDate,
+    pub mileage_km: u32, // Odometer at the service
+    pub cost: Money,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub next_service_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub next_service_mileage_km: Option<u32>,
+    pub created_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct CreateMaintenanceRecordRequest {
+    pub service_date: NaiveDate,
+    #[validate(range(max = 2_000_000))]
+    pub mileage_km: u32,
+    pub cost: Money,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    pub next_service_date: Option<NaiveDate>,
+    #[validate(range(max = 2_000_000))]
+    pub next_service_mileage_km: Option<u32>,
+}
+
+/// Fields to change, the others are kept
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct UpdateMaintenanceRecordRequest {
+    pub service_date: Option<NaiveDate>,
+    #[validate(range(max = 2_000_000))]
+    pub mileage_km: Option<u32>,
+    pub cost: Option<Money>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    pub next_service_date: Option<NaiveDate>,
+    #[validate(range(max = 2_000_000))]
+    pub next_service_mileage_km: Option<u32>,
+}
+
+/// Service of a vehicle due soon or overdue, by date or by mileage
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct ServiceWarning {
+    pub due: ServiceDue,
+    pub record_id: Option<ObjectId>, // Record that planned the service
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_service_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_service_mileage_km: Option<u32>,
+    pub message: String,
+}
+
+/// Response of GET /vehicles/{id}: the vehicle, with its service warnings for the staff
+/// managing it
+#[derive(Clone, Debug, Serialize)]
+pub struct VehicleDetail {
+    #[serde(flatten)]
+    pub vehicle: Vehicle,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub service_warnings: Vec<ServiceWarning>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for MaintenanceRecord {
+    fn get_collection() -> &'static str {
+        "maintenance_records"
+    }
+}
+
+impl MaintenanceRecord {
+    pub fn new(
+        vehicle_id: ObjectId,
+        identity: &Identity,
+        request: CreateMaintenanceRecordRequest,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: None,
+            vehicle_id,
+            service_date: request.service_date,
+            mileage_km: request.mileage_km,
+            cost: request.cost,
+            notes: request.notes,
+            next_service_date: request.next_service_date,
+            next_service_mileage_km: request.next_service_mileage_km,
+            created_by: identity.user_id.clone(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// The next service comes after this one, checked once an update is applied too
+    pub fn check_next_service(&self) -> Result<(), String> {
+        if self
+            .next_service_date
+            .is_some_and(|date| date <= self.service_date)
+        {
+            return Err("next_service_date must be after service_date".to_string());
+        }
+        if self
+            .next_service_mileage_km
+            .is_some_and(|mileage_km| mileage_km <= self.mileage_km)
+        {
+            return Err("next_service_mileage_km must be above mileage_km".to_string());
+        }
+        Ok(())
+    }
+
+    /// Apply the fields of an update
+    pub fn apply(&mut self, request: UpdateMaintenanceRecordRequest) {
+        if let Some(service_date) = request.service_date {
+            self.service_date = service_date;
+        }
+        if let Some(mileage_km) = request.mileage_km {
+            self.mileage_km = mileage_km;
+        }
+        if let Some(cost) = request.cost {
+            self.cost = cost;
+        }
+        if request.notes.is_some() {
+            self.notes = request.notes;
+        }
+        if request.next_service_date.is_some() {
+            self.next_service_date = request.next_service_date;
+        }
+        if request.next_service_mileage_km.is_some() {
+            self.next_service_mileage_km = request.next_service_mileage_km;
+        }
+        self.updated_at = Utc::now();
+    }
+
+    /// Next service planned by this record, due soon or overdue on `today` or at
+    /// `mileage_km`, the latest odometer reading of the vehicle
+    pub fn service_warnings(&self, today: NaiveDate, mileage_km: u32) -> Vec<ServiceWarning> {
+        let mut warnings = Vec::new();
+        if let Some(date) = self.next_service_date {
+            let due = if date < today {
+                Some(ServiceDue::Overdue)
+            } else if date <= today + Duration::days(SERVICE_DUE_SOON_DAYS) {
+                Some(ServiceDue::Soon)
+            } else {
+                None
+            };
+            if let Some(due) = due {
+                warnings.push(ServiceWarning {
+                    due,
+                    record_id: self.id,
+                    next_service_date: Some(date),
+                    next_service_mileage_km: None,
+                    message: format!("Service due on {}", date),
+                });
+            }
+        }
+        if let Some(next_km) = self.next_service_mileage_km {
+            let due = if mileage_km >= next_km {
+                Some(ServiceDue::Overdue)
+            } else if next_km - mileage_km <= SERVICE_DUE_SOON_KM {
+                Some(ServiceDue::Soon)
+            } else {
+                None
+            };
+            if let Some(due) = due {
+                warnings.push(ServiceWarning {
+                    due,
+                    record_id: self.id,
+                    next_service_date: None,
+                    next_service_mileage_km: Some(next_km),
+                    message: format!(
+                        "Service due at {} km, vehicle at {} km",
+                        next_km, mileage_km
+                    ),
+                });
+            }
+        }
+        warnings
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures::date;
+    use crate::models::{Currency, Decimal};
+
+    fn record(next_service_date: Option<NaiveDate>, next_km: Option<u32>) -> MaintenanceRecord {
+        MaintenanceRecord {
+            id: Some(ObjectId::new()),
+            vehicle_id: ObjectId::new(),
+            service_date: date(2, 1),
+            mileage_km: 15_000,
+            cost: Money::new(Decimal::new(24900, 2), Currency::EUR),
+            notes: None,
+            next_service_date,
+            next_service_mileage_km: next_km,
+            created_by: "car_manager_user".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_service_warnings_by_date_and_mileage() {
+        let today = date(8, 1);
+
+        let warnings = record(Some(date(8, 20)), Some(30_000)).service_warnings(today, 29_500);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings
+            .iter()
+            .all(|warning| warning.due == ServiceDue::Soon));
+
+        let warnings = record(Some(date(7, 31)), Some(30_000)).service_warnings(today, 30_100);
+        assert!(warnings
+            .iter()
+            .all(|warning| warning.due == ServiceDue::Overdue));
+
+        // Far enough on both counts, or nothing planned
+        assert!(record(Some(date(10, 1)), Some(30_000))
+            .service_warnings(today, 20_000)
+            .is_empty());
+        assert!(record(None, None)
+            .service_warnings(today, 50_000)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_next_service_comes_after_the_update() {
+        let mut record = record(Some(date(8, 1)), Some(30_000));
+        assert!(record.check_next_service().is_ok());
+
+        // Moving the service past the planned one is refused once applied
+        record.apply(UpdateMaintenanceRecordRequest {
+            service_date: Some(date(9, 1)),
+            mileage_km: None,
+            cost: None,
+            notes: Some("Brake pads".to_string()),
+            next_service_date: None,
+            next_service_mileage_km: None,
+        });
+        assert_eq!(record.notes.as_deref(), Some("Brake pads"));
+        assert!(record.check_next_service().is_err());
+    }
+}
@@ -1,5 +1,109 @@
+pub mod accounting;
+pub mod anomaly;
+pub mod api_key;
+pub mod approval;
+pub mod audit;
+pub mod auto_confirm;
+pub mod backup;
 pub mod booking;
+pub mod booking_policy;
+pub mod booking_rules;
+pub mod bot;
+pub mod broadcast;
+pub mod cancellation_policy;
+pub mod changeset;
+pub mod chaos;
+pub mod comment;
+pub mod condition;
+pub mod customer;
+pub mod deprecation;
+pub mod digest;
+pub mod discovery;
+pub mod dispute;
+pub mod experiment;
+pub mod extension;
+#[cfg(test)]
+pub mod fixtures;
+pub mod holiday;
+pub mod idempotency;
+pub mod integrity;
+pub mod ledger;
+pub mod lockout;
+pub mod maintenance;
+pub mod notification;
+pub mod office_hours;
+pub mod payment;
+pub mod pii;
+pub mod price_adjustment;
+pub mod priority;
+pub mod quarantine;
+pub mod ranking;
+pub mod recording;
+pub mod report;
+pub mod reservation;
+pub mod risk;
+pub mod schema;
+pub mod search;
+pub mod service_account;
+pub mod session;
+pub mod similar;
+pub mod station;
+pub mod suspension;
 pub mod vehicle;
+pub mod webhook_endpoint;
 
+pub use accounting::*;
+pub use anomaly::*;
+pub use api_key::*;
+pub use approval::*;
+pub use audit::*;
+pub use auto_confirm::*;
+pub use backup::*;
 pub use booking::*;
+pub use booking_policy::*;
+pub use booking_rules::*;
+pub use bot::*;
+pub use broadcast::*;
+pub use cancellation_policy::*;
+pub use changeset::*;
+pub use chaos::*;
+pub use comment::*;
+pub use condition::*;
+pub use customer::*;
+pub use deprecation::*;
+pub use digest::*;
+pub use discovery::*;
+pub use dispute::*;
+pub use experiment::*;
+pub use extension::*;
+pub use holiday::*;
+pub use idempotency::*;
+pub use integrity::*;
+pub use ledger::*;
+pub use lockout::*;
+pub use maintenance::*;
+pub use notification::*;
+pub use office_hours::*;
+pub use payment::*;
+pub use pii::*;
+pub use price_adjustment::*;
+pub use priority::*;
+pub use quarantine::*;
+pub use ranking::*;
+pub use recording::*;
+pub use report::*;
+pub use reservation::*;
+pub use risk::*;
+pub use schema::*;
+pub use search::*;
+pub use service_account::*;
+pub use session::*;
+pub use similar::*;
+pub use station::*;
+pub use suspension::*;
 pub use vehicle::*;
+pub use vehicle_api_types::event::*;
+pub use vehicle_api_types::locale::*;
+pub use vehicle_api_types::money::*;
+pub use vehicle_api_types::webhook::*;
+pub use webhook_endpoint::*;
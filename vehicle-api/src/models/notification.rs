@@ -0,0 +1,71 @@
+use bson::{doc, oid::ObjectId, Document};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::authentication::identity::{Identity, Role};
+
+// =============================================================================
+// MAIN NOTIFICATION STRUCT
+// =============================================================================
+
+/// In-app message for one user, or for every user of a role (the staff handling a
+/// vehicle type)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Notification {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: Option<String>,
+    pub role: Option<Role>,
+    pub subject: String,
+    pub message: String,
+    pub booking_id: Option<ObjectId>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Notification {
+    fn get_collection() -> &'static str {
+        "notifications"
+    }
+}
+
+impl Notification {
+    pub fn to_user(user_id: &str, subject: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            id: None,
+            user_id: Some(user_id.to_string()),
+            role: None,
+            subject: subject.into(),
+            message: message.into(),
+            booking_id: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn to_role(role: Role, subject: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            user_id: None,
+            role: Some(role),
+            ..Self::to_user("", subject, message)
+        }
+    }
+
+    pub fn about_booking(mut self, booking_id: ObjectId) -> Self {
+        self.booking_id = Some(booking_id);
+        self
+    }
+
+    /// Notifications addressed to an identity, personally or through its role
+    pub fn inbox_filter(identity: &Identity) -> Document {
+        doc! {
+            "$or": [
+                { "user_id": &identity.user_id },
+                { "role": identity.role.to_string() },
+            ]
+        }
+    }
+}
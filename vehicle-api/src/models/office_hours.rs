@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+use bson::oid::ObjectId;
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+/// What happens to a booking whose pickup falls outside staffed hours
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OutsideHoursAction {
+    #[default]
+    Flag, // Created, but kept pending for a manager and flagged in the approval queue
+    Reject, // Refused
+}
+
+// =============================================================================
+// MAIN OFFICE HOURS STRUCTS
+// =============================================================================
+
+/// Staffed hours of the branches of a location, local to it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OfficeHours {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub location: String, // IANA time zone of the branch, the `timezone` of its vehicles
+    #[serde(default)]
+    pub tenant_id: Option<String>, // None for every tenant without hours of its own
+    pub opening_hours: Vec<OpeningHours>,
+    #[serde(default)]
+    pub outside_hours: OutsideHoursAction,
+    pub updated_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One staffed slot on some days of the week, e.g. Mon-Fri 09:00-12:00
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct OpeningHours {
+    pub days: Vec<Weekday>, // "Mon", "Tue", ...
+    pub opens: NaiveTime,   // "09:00"
+    pub closes: NaiveTime,  // "18:00", after `opens`
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Body of PUT /admin/office-hours/{location}
+#[derive(Clone, Debug, Deserialize)]
+pub struct SetOfficeHoursRequest {
+    pub opening_hours: Vec<OpeningHours>,
+    #[serde(default)]
+    pub outside_hours: OutsideHoursAction,
+}
+
+/// Office hours of every location, by tenant. Locations without hours are always staffed.
+#[derive(Clone, Debug, Default)]
+pub struct OfficeCalendar(HashMap<(Option<String>, String), OfficeHours>);
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for OfficeHours {
+    fn get_collection() -> &'static str {
+        "office_hours"
+    }
+}
+
+impl SetOfficeHoursRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_opening_hours(&self.opening_hours)
+    }
+}
+
+/// Slots must open before they close and not overlap on a same day
+pub fn validate_opening_hours(opening_hours: &[OpeningHours]) -> Result<(), String> {
+    if opening_hours.is_empty() {
+        return Err("Set at least one opening slot".to_string());
+    }
+    for slot in opening_hours {
+        if slot.days.is_empty() {
+            return Err("Every opening slot needs at least one day".to_string());
+        }
+        if slot.opens >= slot.closes {
+            return Err(format!(
+                "Slot {}-{} must open before it closes",
+                slot.opens, slot.closes
+            ));
+        }
+    }
+    for (index, slot) in opening_hours.iter().enumerate() {
+        let overlapping = opening_hours[index + 1..].iter().find(|other| {
+            other.days.iter().any(|day| slot.days.contains(day))
+                && other.opens < slot.closes
+                && slot.opens < other.closes
+        });
+        if let Some(other) = overlapping {
+            return Err(format!(
+                "Slots {}-{} and {}-{} overlap",
+                slot.opens, slot.closes, other.opens, other.closes
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether slots let a vehicle be picked up on `date`: open that day, and when it is
+/// today (`now` being local), not closed yet
+pub fn allows_pickup(opening_hours: &[OpeningHours], date: NaiveDate, now: NaiveDateTime) -> bool {
+    opening_hours
+        .iter()
+        .filter(|slot| slot.days.contains(&date.weekday()))
+        .any(|slot| date > now.date() || (date == now.date() && slot.closes > now.time()))
+}
+
+impl OfficeHours {
+    /// Staffed slots of a local day
+    fn slots_on(&self, date: NaiveDate) -> impl Iterator<Item = &OpeningHours> {
+        self.opening_hours
+            .iter()
+            .filter(move |slot| slot.days.contains(&date.weekday()))
+    }
+
+    /// Whether a vehicle can be picked up on `date`: the branch opens that day, and when
+    /// it is today (`now` being local), has not closed yet
+    pub fn allows_pickup(&self, date: NaiveDate, now: NaiveDateTime) -> bool {
+        allows_pickup(&self.opening_hours, date, now)
+    }
+
+    /// Staffed time between two instants, timers only run meanwhile
+    pub fn open_time(&self, tz: Tz, from: DateTime<Utc>, to: DateTime<Utc>) -> Duration {
+        if to <= from {
+            return Duration::zero();
+        }
+        let to_utc = |date: NaiveDate, time: NaiveTime| {
+            tz.from_local_datetime(&date.and_time(time))
+                .earliest()
+                .map(|local| local.with_timezone(&Utc))
+        };
+
+        let mut open = Duration::zero();
+        let last = to.with_timezone(&tz).date_naive();
+        let mut date = from.with_timezone(&tz).date_naive();
+        while date <= last {
+            for slot in self.slots_on(date) {
+                let (Some(opens), Some(closes)) =
+                    (to_utc(date, slot.opens), to_utc(date, slot.closes))
+                else {
+                    continue;
+                };
+                let (start, end) = (opens.max(from), closes.min(to));
+                if start < end {
+                    open += end - start;
+                }
+            }
+            date += Duration::days(1);
+        }
+        open
+    }
+}
+
+impl OfficeCalendar {
+    pub fn new(hours: Vec<OfficeHours>) -> Self {
+        Self(
+            hours
+                .into_iter()
+                .map(|hours| ((hours.tenant_id.clone(), hours.location.clone()), hours))
+                .collect(),
+        )
+    }
+
+    /// Hours of a tenant's location, else the ones set for every tenant
+    pub fn get(&self, tenant_id: Option<&str>, location: &str) -> Option<&OfficeHours> {
+        self.0
+            .get(&(tenant_id.map(str::to_string), location.to_string()))
+            .or_else(|| self.0.get(&(None, location.to_string())))
+    }
+
+    /// Whether a location is staffed for a pickup on `date`, at instant `now`
+    pub fn allows_pickup(
+        &self,
+        tenant_id: Option<&str>,
+        location: &str,
+        date: NaiveDate,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let hours = self.get(tenant_id, location);
+        match (hours, location.parse::<Tz>()) {
+            (Some(hours), Ok(tz)) => {
+                hours.allows_pickup(date, now.with_timezone(&tz).naive_local())
+            }
+            _ => true,
+        }
+    }
+
+    /// Time a timer started at `from` has run by `to` at a location: only staffed time
+    /// counts where office hours are set
+    pub fn elapsed(
+        &self,
+        tenant_id: Option<&str>,
+        location: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Duration {
+        let hours = self.get(tenant_id, location);
+        match (hours, location.parse::<Tz>()) {
+            (Some(hours), Ok(tz)) => hours.open_time(tz, from, to),
+            _ => to - from,
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weekdays(opens: &str, closes: &str) -> OpeningHours {
+        OpeningHours {
+            days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            opens: opens.parse().unwrap(),
+            closes: closes.parse().unwrap(),
+        }
+    }
+
+    fn paris(opening_hours: Vec<OpeningHours>) -> OfficeHours {
+        OfficeHours {
+            id: None,
+            location: "Europe/Paris".to_string(),
+            tenant_id: None,
+            opening_hours,
+            outside_hours: OutsideHoursAction::Flag,
+            updated_by: "admin_user".to_string(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_pickup_needs_a_staffed_day() {
+        let hours = paris(vec![weekdays("09:00", "18:00")]);
+        // Friday 2025-08-01, 17:00 local
+        let now = NaiveDate::from_ymd_opt(2025, 8, 1)
+            .unwrap()
+            .and_hms_opt(17, 0, 0)
+            .unwrap();
+
+        assert!(hours.allows_pickup(now.date(), now));
+        assert!(!hours.allows_pickup(now.date(), now + Duration::hours(2)));
+        // Closed on Saturday, open again on Monday
+        assert!(!hours.allows_pickup(NaiveDate::from_ymd_opt(2025, 8, 2).unwrap(), now));
+        assert!(hours.allows_pickup(NaiveDate::from_ymd_opt(2025, 8, 4).unwrap(), now));
+    }
+
+    #[test]
+    fn test_timers_pause_outside_office_hours() {
+        let hours = paris(vec![weekdays("09:00", "12:00"), weekdays("14:00", "18:00")]);
+        let tz: Tz = "Europe/Paris".parse().unwrap();
+
+        // Friday 17:00 to Monday 10:00 Paris time (UTC+2): 1 hour on Friday, 1 on Monday
+        let open = hours.open_time(tz, utc("2025-08-01T15:00:00Z"), utc("2025-08-04T08:00:00Z"));
+        assert_eq!(open, Duration::hours(2));
+
+        let calendar = OfficeCalendar::new(vec![hours]);
+        assert_eq!(
+            calendar.elapsed(
+                Some("tenant_a"),
+                "Europe/Paris",
+                utc("2025-08-01T15:00:00Z"),
+                utc("2025-08-04T08:00:00Z")
+            ),
+            Duration::hours(2)
+        );
+        // Other locations are always staffed
+        assert_eq!(
+            calendar.elapsed(
+                None,
+                "Europe/Berlin",
+                utc("2025-08-01T15:00:00Z"),
+                utc("2025-08-04T08:00:00Z")
+            ),
+            Duration::hours(65)
+        );
+    }
+
+    #[test]
+    fn test_slots_cannot_overlap() {
+        let request = |opening_hours| SetOfficeHoursRequest {
+            opening_hours,
+            outside_hours: OutsideHoursAction::Reject,
+        };
+
+        assert!(
+            request(vec![weekdays("09:00", "12:00"), weekdays("14:00", "18:00")])
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            request(vec![weekdays("09:00", "15:00"), weekdays("14:00", "18:00")])
+                .validate()
+                .is_err()
+        );
+        assert!(request(vec![weekdays("18:00", "09:00")])
+            .validate()
+            .is_err());
+        assert!(request(Vec::new()).validate().is_err());
+    }
+}
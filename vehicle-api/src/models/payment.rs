@@ -0,0 +1,169 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strum::Display;
+use validator::Validate;
+
+use crate::models::{Decimal, Money};
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentKind {
+    Refund, // Money back to the customer
+    Charge, // Extra amount taken from the customer
+    Rental, // Total price paid by the customer through Stripe before confirmation
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentStatus {
+    Pending, // Waiting for the provider, or for finance with the manual provider
+    Succeeded,
+    Failed,
+}
+
+// =============================================================================
+// MAIN PAYMENT STRUCT
+// =============================================================================
+
+/// Money movement on a booking, requested by the API and carried out by the
+/// configured payment provider
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Payment {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub booking_id: ObjectId,
+    pub customer_id: String,
+    pub kind: PaymentKind,
+    /// In the booking's currency
+    pub amount: Money,
+    pub reason: String,
+    pub status: PaymentStatus,
+    pub provider: String,
+    /// Identifier of the operation at the provider, once it knows it
+    pub provider_reference: Option<String>,
+    pub requested_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub requested_at: DateTime<Utc>,
+    /// `Idempotency-Key` header of the request, a retry returns the same payment
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// What a caller asks the payment provider to do
+#[derive(Clone, Debug)]
+pub struct PaymentRequest {
+    pub booking_id: ObjectId,
+    pub customer_id: String,
+    pub kind: PaymentKind,
+    pub amount: Money,
+    pub reason: String,
+    pub requested_by: String,
+    pub idempotency_key: Option<String>,
+}
+
+/// Answer to POST /bookings/{id}/pay, the client confirms the payment with Stripe.js
+#[derive(Clone, Debug, Serialize)]
+pub struct BookingPaymentIntent {
+    pub payment_id: ObjectId,
+    pub intent_id: String,
+    pub client_secret: String,
+    pub amount: Money,
+}
+
+/// Fields of a Stripe PaymentIntent the API reads
+#[derive(Clone, Debug, Deserialize)]
+pub struct StripePaymentIntent {
+    pub id: String,
+    pub amount: i64, // In cents
+    pub currency: String,
+    pub status: String,
+    pub client_secret: Option<String>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// Event posted by Stripe to the webhook endpoint, only PaymentIntent events are handled
+#[derive(Clone, Debug, Deserialize)]
+pub struct StripeEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub data: StripeEventData,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct StripeEventData {
+    pub object: serde_json::Value,
+}
+
+/// Refund in the booking's currency, `amount` as a number or a string, e.g. `"40.20"`
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct RefundRequest {
+    #[serde(with = "vehicle_api_types::money::amount")]
+    pub amount: Decimal,
+    #[validate(length(min = 1, max = 500))]
+    pub reason: String,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Payment {
+    fn get_collection() -> &'static str {
+        "payments"
+    }
+}
+
+impl StripePaymentIntent {
+    /// Booking the intent pays for, from the metadata set when it was created
+    pub fn booking_id(&self) -> Option<ObjectId> {
+        self.metadata
+            .get("booking_id")
+            .and_then(|id| ObjectId::parse_str(id).ok())
+    }
+
+    /// Whether the intent is for `amount`, the amount the API asked Stripe for
+    pub fn is_for(&self, amount: Money) -> bool {
+        to_cents(amount) == Some(self.amount)
+            && self
+                .currency
+                .eq_ignore_ascii_case(&amount.currency.to_string())
+    }
+}
+
+/// Stripe amounts are integers in the smallest currency unit
+pub fn to_cents(amount: Money) -> Option<i64> {
+    i64::try_from(amount.round().amount * Decimal::ONE_HUNDRED).ok()
+}
+
+impl Payment {
+    pub fn new(request: PaymentRequest, provider: &str) -> Self {
+        Self {
+            id: None,
+            booking_id: request.booking_id,
+            customer_id: request.customer_id,
+            kind: request.kind,
+            amount: request.amount,
+            reason: request.reason,
+            status: PaymentStatus::Pending,
+            provider: provider.to_string(),
+            provider_reference: None,
+            requested_by: request.requested_by,
+            requested_at: Utc::now(),
+            idempotency_key: request.idempotency_key,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use serde::Serialize;
+pub use vehicle_api_types::pii::*;
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Outcome of re-encrypting the stored personal data with the current key
+#[derive(Clone, Debug, Serialize)]
+pub struct PiiRotationReport {
+    pub key_id: String,
+    pub scanned: usize, // Bookings with driver details
+    pub rotated: usize, // Bookings re-encrypted
+}
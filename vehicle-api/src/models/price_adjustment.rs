@@ -0,0 +1,268 @@
+use bson::{doc, oid::ObjectId, Document};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use validator::Validate;
+
+use crate::models::{Brand, Change, Decimal, Money, Vehicle, VehicleType};
+use crate::services;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum PriceAdjustmentKind {
+    Percent, // `value` percent of the current price, e.g. -10 for a 10% discount
+    Fixed,   // `value` added to the price per day, in the vehicle's currency
+}
+
+// =============================================================================
+// MAIN PRICE ADJUSTMENT STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PriceChange {
+    pub vehicle_id: ObjectId,
+    pub old_price: Money,
+    pub new_price: Money,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Vehicles to reprice and how, filters left out match every vehicle
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct PriceAdjustmentRequest {
+    pub kind: PriceAdjustmentKind,
+    #[validate(range(min = -1000.0, max = 1000.0))]
+    pub value: f64,
+    pub brand: Option<Vec<Brand>>,
+    #[serde(rename = "type")]
+    pub vehicle_type: Option<VehicleType>,
+    pub year_of_production: Option<Vec<u32>>,
+    /// Only report the new prices
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PriceAdjustmentResult {
+    pub dry_run: bool,
+    pub changes: Vec<PriceChange>,
+    /// Rolls the adjustment back with POST /admin/changesets/{id}/rollback, None for dry runs
+    pub changeset_id: Option<ObjectId>,
+    pub rollback_expires_at: Option<DateTime<Utc>>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl PriceAdjustmentRequest {
+    /// Vehicles the adjustment applies to
+    pub fn vehicle_filter(&self) -> Document {
+        let mut filter = Document::new();
+        let builder = services::mongodb::QueryBuilder::new();
+
+        builder.add_string_filter(&mut filter, "brand", &self.brand);
+        builder.add_string_filter(
+            &mut filter,
+            "type",
+            &self
+                .vehicle_type
+                .clone()
+                .map(|vehicle_type| vec![vehicle_type]),
+        );
+        builder.add_filter(&mut filter, "year_of_production", &self.year_of_production);
+        filter
+    }
+
+    /// Price per day after the adjustment, rounded to cents. None when the price would
+    /// not stay positive.
+    pub fn apply(&self, price: Money) -> Option<Money> {
+        let value = Money::from_f64(self.value, price.currency)?.amount;
+        let adjusted = match self.kind {
+            PriceAdjustmentKind::Percent => {
+                price.times(Decimal::ONE + value / Decimal::ONE_HUNDRED)
+            }
+            PriceAdjustmentKind::Fixed => Money::new(price.amount + value, price.currency),
+        }
+        .round();
+        (adjusted.amount > Decimal::ZERO).then_some(adjusted)
+    }
+
+    /// New prices of the vehicles, every one has to stay positive
+    pub fn changes(&self, vehicles: &[Vehicle]) -> Result<Vec<PriceChange>, String> {
+        vehicles
+            .iter()
+            .filter_map(|vehicle| vehicle.id.map(|id| (id, vehicle.price_by_day)))
+            .map(|(vehicle_id, old_price)| {
+                let new_price = self.apply(old_price).ok_or_else(|| {
+                    format!(
+                        "The price of vehicle {} would drop to zero or below",
+                        vehicle_id.to_hex()
+                    )
+                })?;
+                Ok(PriceChange {
+                    vehicle_id,
+                    old_price,
+                    new_price,
+                })
+            })
+            .collect()
+    }
+}
+
+impl PriceChange {
+    /// The change as kept in a change-set
+    pub fn to_change(&self) -> Change {
+        Change::Updated {
+            resource_id: self.vehicle_id,
+            before: doc! { "price_by_day": self.old_price },
+            after: doc! { "price_by_day": self.new_price },
+        }
+    }
+
+    /// Vehicles sharing the same old and new price, updated together
+    pub fn grouped(changes: &[PriceChange]) -> Vec<(Money, Money, Vec<ObjectId>)> {
+        let mut groups: Vec<(Money, Money, Vec<ObjectId>)> = Vec::new();
+        for change in changes {
+            match groups
+                .iter_mut()
+                .find(|(old_price, _, _)| *old_price == change.old_price)
+            {
+                Some((_, _, vehicle_ids)) => vehicle_ids.push(change.vehicle_id),
+                None => groups.push((change.old_price, change.new_price, vec![change.vehicle_id])),
+            }
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        CarMetadata, CarModel, Currency, FuelType, Gearbox, VehicleMetadata, VehicleStatus,
+    };
+
+    fn eur(amount: f64) -> Money {
+        Money::from_f64(amount, Currency::EUR).unwrap()
+    }
+
+    fn request(kind: PriceAdjustmentKind, value: f64) -> PriceAdjustmentRequest {
+        PriceAdjustmentRequest {
+            kind,
+            value,
+            brand: None,
+            vehicle_type: None,
+            year_of_production: None,
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn test_apply_percent_and_fixed_adjustments() {
+        assert_eq!(
+            request(PriceAdjustmentKind::Percent, 10.0).apply(eur(80.0)),
+            Some(eur(88.0))
+        );
+        assert_eq!(
+            request(PriceAdjustmentKind::Percent, -15.0).apply(eur(99.99)),
+            Some(eur(84.99))
+        );
+        assert_eq!(
+            request(PriceAdjustmentKind::Fixed, -5.5).apply(eur(40.0)),
+            Some(eur(34.5))
+        );
+
+        // Prices never drop to zero or below
+        assert_eq!(
+            request(PriceAdjustmentKind::Percent, -100.0).apply(eur(80.0)),
+            None
+        );
+        assert_eq!(
+            request(PriceAdjustmentKind::Fixed, -80.0).apply(eur(80.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_every_vehicle_has_to_stay_positive() {
+        let vehicle = |price_by_day: Money| Vehicle {
+            id: Some(ObjectId::new()),
+            brand: Brand::TESLA,
+            metadata: VehicleMetadata::Car(CarMetadata {
+                model: CarModel::MODEL_3,
+                seats: 5,
+                fuel_type: FuelType::ELECTRIC,
+                gearbox: Gearbox::AUTOMATIC,
+                engine_cc: 0,
+            }),
+            description: None,
+            price_by_day,
+            year_of_production: 2022,
+            added_at: Utc::now(),
+            added_by: "admin_user_1".to_string(),
+            timezone: "UTC".to_string(),
+            country: None,
+            tenant_id: None,
+            deleted_at: None,
+            images: Vec::new(),
+            status: VehicleStatus::Available,
+            location: None,
+            station_id: None,
+        };
+        let discount = request(PriceAdjustmentKind::Fixed, -20.0);
+
+        let changes = discount
+            .changes(&[vehicle(eur(80.0)), vehicle(eur(50.0))])
+            .unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            (changes[0].old_price, changes[0].new_price),
+            (eur(80.0), eur(60.0))
+        );
+        assert!(discount
+            .changes(&[vehicle(eur(80.0)), vehicle(eur(20.0))])
+            .is_err());
+
+        let changes = discount
+            .changes(&[vehicle(eur(80.0)), vehicle(eur(50.0)), vehicle(eur(80.0))])
+            .unwrap();
+        let groups = PriceChange::grouped(&changes);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            (groups[0].0, groups[0].1, groups[0].2.len()),
+            (eur(80.0), eur(60.0), 2)
+        );
+        assert_eq!(
+            (groups[1].0, groups[1].1, groups[1].2.len()),
+            (eur(50.0), eur(30.0), 1)
+        );
+    }
+
+    #[test]
+    fn test_vehicle_filter() {
+        let mut adjustment = request(PriceAdjustmentKind::Fixed, 5.0);
+        assert!(adjustment.vehicle_filter().is_empty());
+
+        adjustment.brand = Some(vec![Brand::TESLA, Brand::MERCEDES]);
+        adjustment.vehicle_type = Some(VehicleType::Car);
+        adjustment.year_of_production = Some(vec![2021, 2022]);
+        let filter = adjustment.vehicle_filter();
+        assert_eq!(
+            filter.get_document("brand").unwrap(),
+            &doc! { "$in": ["TESLA", "MERCEDES"] }
+        );
+        assert_eq!(filter.get_str("type"), Ok("CAR"));
+        assert!(filter
+            .get_document("year_of_production")
+            .unwrap()
+            .contains_key("$in"));
+    }
+}
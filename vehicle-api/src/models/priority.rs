@@ -0,0 +1,39 @@
+use serde::Serialize;
+use strum::{Display, EnumIter};
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+/// Class of a request for the priority lanes, by decreasing priority
+#[derive(Clone, Copy, Debug, Serialize, Display, EnumIter, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum TrafficClass {
+    Critical, // Creating or changing bookings
+    Standard, // Everything else
+    Browsing, // Reading the vehicle catalog
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Load of one lane on this instance, counters since startup
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct LaneStats {
+    pub class: TrafficClass,
+    pub in_flight: usize,
+    pub queued: usize, // Waiting for a slot right now
+    pub max_queued: usize,
+    pub admitted: u64,
+    pub shed: u64, // Answered 503, the queue was full or the wait too long
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct PriorityLanes {
+    pub enabled: bool,
+    pub capacity: usize,
+    pub reserved_critical: usize,
+    pub lanes: Vec<LaneStats>,
+}
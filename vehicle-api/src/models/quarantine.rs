@@ -0,0 +1,58 @@
+use bson::{Bson, Document};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// MAIN QUARANTINE STRUCT
+// =============================================================================
+
+/// Stored document a lenient read could not decode and skipped, one per collection
+/// and `_id`, kept as it was read so it can be repaired
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuarantinedDocument {
+    #[serde(rename = "_id")]
+    pub id: String, // See QuarantinedDocument::key
+    pub collection: String,
+    pub document_id: Bson,
+    pub error: String, // Deserialization error of the last read
+    pub document: Document,
+    #[serde(default)]
+    pub tenant_id: Option<String>, // Tenant of the document, when it has one
+    pub reads: i64, // Reads that skipped it
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub first_seen_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub last_seen_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// `?collection=bookings` of GET /admin/quarantine
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct QuarantineQuery {
+    pub collection: Option<String>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for QuarantinedDocument {
+    fn get_collection() -> &'static str {
+        "quarantined_documents"
+    }
+}
+
+impl QuarantinedDocument {
+    /// One entry per collection and document
+    pub fn key(collection: &str, document_id: &Bson) -> String {
+        let document_id = match document_id {
+            Bson::ObjectId(id) => id.to_hex(),
+            Bson::String(id) => id.clone(),
+            other => other.to_string(),
+        };
+        format!("{}:{}", collection, document_id)
+    }
+}
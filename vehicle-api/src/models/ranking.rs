@@ -0,0 +1,216 @@
+use bson::{doc, Document};
+use serde::Deserialize;
+use strum::{Display, EnumString};
+
+use crate::models::Booking;
+use crate::services::mongodb::MongoStruct;
+use crate::util::pagination::PageQuery;
+
+/// Experiment whose variants, named after strategies, rank GET /vehicles by default
+pub const RANKING_EXPERIMENT: &str = "vehicle-ranking";
+
+// Fields computed while ranking, removed before the vehicles are returned
+const POPULARITY_FIELD: &str = "ranking_popularity";
+const AFFINITY_FIELD: &str = "ranking_affinity";
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Deserialize, EnumString, Display, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum RankingStrategy {
+    PriceAsc,     // Cheapest first
+    Popularity,   // Most confirmed bookings first
+    Newest,       // Last added first
+    Personalized, // Brands and types the customer booked before first, then popularity
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// `rank` query parameter of GET /vehicles
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct RankQuery {
+    pub rank: Option<RankingStrategy>,
+}
+
+/// Brands and vehicle types a customer booked before
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CustomerAffinity {
+    pub brands: Vec<String>,
+    pub vehicle_types: Vec<String>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl RankQuery {
+    /// Strategy of a list request: `rank` when sent, else the caller's variant of the
+    /// ranking experiment unless the request sorts by itself. None keeps the find path.
+    pub fn resolve(
+        &self,
+        sort: Option<&str>,
+        variant: Option<&str>,
+    ) -> Result<Option<RankingStrategy>, String> {
+        match (self.rank, sort) {
+            (Some(_), Some(_)) => Err("rank and sort cannot be combined".to_string()),
+            (Some(rank), None) => Ok(Some(rank)),
+            (None, Some(_)) => Ok(None),
+            (None, None) => Ok(variant.and_then(|variant| variant.parse().ok())),
+        }
+    }
+}
+
+impl RankingStrategy {
+    /// Whether the strategy reads the customer's booking history
+    pub fn is_personalized(&self) -> bool {
+        *self == RankingStrategy::Personalized
+    }
+
+    /// Aggregation returning one page of the vehicles matching `filter`, ranked
+    pub fn pipeline(
+        &self,
+        filter: Document,
+        affinity: &CustomerAffinity,
+        page: PageQuery,
+    ) -> Vec<Document> {
+        let mut pipeline = vec![doc! { "$match": filter }];
+        let sort = match self {
+            RankingStrategy::PriceAsc => doc! { "price_by_day": 1, "_id": 1 },
+            RankingStrategy::Newest => doc! { "added_at": -1, "_id": -1 },
+            RankingStrategy::Popularity => {
+                pipeline.extend(popularity_stages());
+                doc! { POPULARITY_FIELD: -1, "_id": 1 }
+            }
+            RankingStrategy::Personalized => {
+                pipeline.extend(popularity_stages());
+                pipeline.push(affinity_stage(affinity));
+                doc! { AFFINITY_FIELD: -1, POPULARITY_FIELD: -1, "_id": 1 }
+            }
+        };
+        pipeline.extend([
+            doc! { "$sort": sort },
+            doc! { "$skip": page.skip() as i64 },
+            doc! { "$limit": page.limit() },
+        ]);
+        if matches!(
+            self,
+            RankingStrategy::Popularity | RankingStrategy::Personalized
+        ) {
+            pipeline.push(doc! { "$unset": [POPULARITY_FIELD, AFFINITY_FIELD] });
+        }
+        pipeline
+    }
+}
+
+/// Confirmed bookings of each vehicle
+fn popularity_stages() -> Vec<Document> {
+    vec![
+        doc! {
+            "$lookup": {
+                "from": Booking::get_collection(),
+                "let": { "vehicle_id": "$_id" },
+                "pipeline": [
+                    { "$match": {
+                        "$expr": { "$eq": ["$vehicle_id", "$$vehicle_id"] },
+                        "status": "CONFIRMED",
+                    } },
+                    { "$count": "bookings" },
+                ],
+                "as": POPULARITY_FIELD,
+            }
+        },
+        doc! {
+            "$set": {
+                POPULARITY_FIELD: {
+                    "$ifNull": [{ "$first": format!("${}.bookings", POPULARITY_FIELD) }, 0]
+                }
+            }
+        },
+    ]
+}
+
+/// 2 for a brand the customer booked before, 1 for a vehicle type
+fn affinity_stage(affinity: &CustomerAffinity) -> Document {
+    doc! {
+        "$set": {
+            AFFINITY_FIELD: { "$add": [
+                { "$cond": [{ "$in": ["$brand", affinity.brands.clone()] }, 2, 0] },
+                { "$cond": [{ "$in": ["$type", affinity.vehicle_types.clone()] }, 1, 0] },
+            ] }
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_parameter_then_experiment_variant() {
+        let rank = |rank| RankQuery { rank };
+
+        assert_eq!(
+            rank(Some(RankingStrategy::Newest)).resolve(None, Some("popularity")),
+            Ok(Some(RankingStrategy::Newest))
+        );
+        assert_eq!(
+            rank(None).resolve(None, Some("popularity")),
+            Ok(Some(RankingStrategy::Popularity))
+        );
+        // Variants that are not strategies, e.g. "control", keep the default order
+        assert_eq!(rank(None).resolve(None, Some("control")), Ok(None));
+        assert_eq!(
+            rank(None).resolve(Some("brand"), Some("popularity")),
+            Ok(None)
+        );
+        assert!(rank(Some(RankingStrategy::PriceAsc))
+            .resolve(Some("brand"), None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_pipeline_ranks_before_the_page() {
+        let page = PageQuery::new(Some(2), Some(10));
+        let pipeline = RankingStrategy::PriceAsc.pipeline(
+            doc! { "brand": "TESLA" },
+            &CustomerAffinity::default(),
+            page,
+        );
+        assert_eq!(
+            pipeline,
+            vec![
+                doc! { "$match": { "brand": "TESLA" } },
+                doc! { "$sort": { "price_by_day": 1, "_id": 1 } },
+                doc! { "$skip": 10_i64 },
+                doc! { "$limit": 10_i64 },
+            ]
+        );
+
+        let affinity = CustomerAffinity {
+            brands: vec!["TESLA".to_string()],
+            vehicle_types: vec!["CAR".to_string()],
+        };
+        let pipeline = RankingStrategy::Personalized.pipeline(doc! {}, &affinity, page);
+        let sort = pipeline
+            .iter()
+            .find_map(|stage| stage.get_document("$sort").ok())
+            .unwrap();
+        assert_eq!(
+            sort,
+            &doc! { AFFINITY_FIELD: -1, POPULARITY_FIELD: -1, "_id": 1 }
+        );
+        assert_eq!(
+            pipeline.last().unwrap(),
+            &doc! { "$unset": [POPULARITY_FIELD, AFFINITY_FIELD] }
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::authentication::identity::Role;
+
+/// JSON keys whose values are never stored in a recording: secrets and the personal
+/// data of drivers, encrypted at rest everywhere else
+const SENSITIVE_KEYS: [&str; 10] = [
+    "password",
+    "token",
+    "secret",
+    "api_key",
+    "card",
+    "iban",
+    "license_number",
+    "phone",
+    "address",
+    "date_of_birth",
+];
+
+// =============================================================================
+// MAIN RECORDING STRUCTS
+// =============================================================================
+
+/// A sanitized request/response pair captured for debugging
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Recording {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub request_id: String,
+    pub user_id: String,
+    pub role: Role,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub request_body: Option<Value>,
+    pub status: u16,
+    pub response_body: Option<Value>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Users and request IDs whose traffic is currently being recorded
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RecordingTargets {
+    #[serde(default)]
+    pub user_ids: Vec<String>,
+    #[serde(default)]
+    pub request_ids: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct RecordingFilters {
+    pub user_id: Option<String>,
+    pub request_id: Option<String>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Result of replaying a recording against the current validation code
+#[derive(Clone, Debug, Serialize)]
+pub struct ReplayResult {
+    pub recording_id: String,
+    pub original_status: u16,
+    pub valid: bool,
+    pub error: Option<String>,
+    /// True when the current code disagrees with the recorded outcome
+    pub diverged: bool,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Recording {
+    fn get_collection() -> &'static str {
+        "recordings"
+    }
+}
+
+impl RecordingTargets {
+    pub const fn empty() -> Self {
+        Self {
+            user_ids: Vec::new(),
+            request_ids: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.user_ids.is_empty() && self.request_ids.is_empty()
+    }
+
+    pub fn matches(&self, user_id: &str, request_id: &str) -> bool {
+        self.user_ids.iter().any(|id| id == user_id)
+            || self.request_ids.iter().any(|id| id == request_id)
+    }
+}
+
+impl Recording {
+    /// Parse a captured body as JSON and redact sensitive fields.
+    /// Non JSON bodies are not stored.
+    pub fn sanitize_body(bytes: &[u8]) -> Option<Value> {
+        let mut value = serde_json::from_slice::<Value>(bytes).ok()?;
+        redact(&mut value);
+        Some(value)
+    }
+}
+
+impl ReplayResult {
+    pub fn new(recording: &Recording, outcome: Result<(), String>) -> Self {
+        let originally_valid = (200..300).contains(&recording.status);
+        let valid = outcome.is_ok();
+
+        Self {
+            recording_id: recording.id.map(|id| id.to_hex()).unwrap_or_default(),
+            original_status: recording.status,
+            valid,
+            error: outcome.err(),
+            diverged: originally_valid != valid,
+        }
+    }
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SENSITIVE_KEYS
+                    .iter()
+                    .any(|sensitive| key.contains(sensitive))
+                {
+                    *field = Value::String("[REDACTED]".to_string());
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_body_redacts_nested_secrets() {
+        let body = br#"{"vehicle_id":"abc","payment":{"card_number":"4242","owner":"Jo"},"items":[{"api_key":"k"}]}"#;
+        let value = Recording::sanitize_body(body).unwrap();
+
+        assert_eq!(value["vehicle_id"], "abc");
+        assert_eq!(value["payment"]["card_number"], "[REDACTED]");
+        assert_eq!(value["payment"]["owner"], "Jo");
+        assert_eq!(value["items"][0]["api_key"], "[REDACTED]");
+        assert!(Recording::sanitize_body(b"not json").is_none());
+    }
+
+    #[test]
+    fn test_sanitize_body_redacts_driver_details() {
+        let body = br#"{"from_date":"2025-08-01","driver":{"license_number":"B123","phone":"+33600000000","address":"1 rue de Paris","date_of_birth":"1990-01-01"}}"#;
+        let value = Recording::sanitize_body(body).unwrap();
+
+        assert_eq!(value["from_date"], "2025-08-01");
+        for field in ["license_number", "phone", "address", "date_of_birth"] {
+            assert_eq!(value["driver"][field], "[REDACTED]");
+        }
+    }
+
+    #[test]
+    fn test_targets_matching() {
+        let targets = RecordingTargets {
+            user_ids: vec!["customer_user_1".to_string()],
+            request_ids: vec!["req-42".to_string()],
+        };
+
+        assert!(targets.matches("customer_user_1", "any"));
+        assert!(targets.matches("customer_user_2", "req-42"));
+        assert!(!targets.matches("customer_user_2", "req-43"));
+        assert!(RecordingTargets::empty().is_empty());
+    }
+}
@@ -0,0 +1,468 @@
+use bson::{doc, oid::ObjectId, Document};
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::models::{Booking, BookingStatus, Currency, Money, Vehicle, VehicleMetadata};
+
+/// Longest period a report may cover
+pub const MAX_REPORT_DAYS: i64 = 366;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, EnumString, Display, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum VehicleType {
+    Car,
+    Motorbike,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Period (both days included) and vehicle type of a report
+#[derive(Clone, Debug, Deserialize)]
+pub struct ReportQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(rename = "type")]
+    pub vehicle_type: Option<VehicleType>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct CapacityDay {
+    pub date: NaiveDate,
+    pub supply: u32, // Vehicles in the fleet that day
+    pub demand: u32, // Pending and confirmed bookings covering that day
+    pub utilization: f64,
+    pub over_capacity: bool,
+}
+
+/// Active bookings of one vehicle over the report period
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct UtilizationRow {
+    pub vehicle_id: ObjectId,
+    pub bookings: u32,
+    pub booked_days: u32,
+    pub utilization: f64, // Booked days over days in the period
+}
+
+/// Confirmed bookings starting in one month of the report period, one row per currency
+/// they are priced in
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RevenueRow {
+    pub month: String, // YYYY-MM
+    pub bookings: u32,
+    pub booked_days: u32,
+    pub revenue: Money,
+}
+
+/// Bookings by status, per vehicle and per month, from a single aggregation
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BookingStats {
+    pub by_status: Vec<StatusCount>,
+    /// Most booked vehicles first
+    pub per_vehicle: Vec<VehicleBookingCount>,
+    pub revenue_per_month: Vec<MonthRevenue>,
+    /// Average length in days, None without bookings
+    #[serde(default)]
+    pub average_days: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StatusCount {
+    pub status: String,
+    pub bookings: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VehicleBookingCount {
+    pub vehicle_id: ObjectId,
+    pub bookings: u32,
+    pub booked_days: u32,
+}
+
+/// Price of the confirmed bookings starting in one month, one per currency
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MonthRevenue {
+    pub month: String, // YYYY-MM
+    pub bookings: u32,
+    pub revenue: Money,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CapacityReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(rename = "type")]
+    pub vehicle_type: Option<VehicleType>,
+    pub over_capacity_days: usize,
+    pub days: Vec<CapacityDay>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl VehicleType {
+    pub fn of(vehicle: &Vehicle) -> Self {
+        match vehicle.metadata {
+            VehicleMetadata::Car(_) => VehicleType::Car,
+            VehicleMetadata::Motorbike(_) => VehicleType::Motorbike,
+        }
+    }
+}
+
+impl ReportQuery {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.from > self.to {
+            return Err("from must be before or equal to to".to_string());
+        }
+        if (self.to - self.from).num_days() >= MAX_REPORT_DAYS {
+            return Err(format!("A report covers at most {} days", MAX_REPORT_DAYS));
+        }
+        Ok(())
+    }
+
+    fn period_days(&self) -> i64 {
+        (self.to - self.from).num_days() + 1
+    }
+
+    /// Bookings overlapping the period with one of the statuses, joined to their vehicle
+    /// (dropped if it is not of the requested type), with the number of `days` inside the period
+    fn booking_stages(&self, statuses: &[&str]) -> Vec<Document> {
+        let (from, to) = (self.from.to_string(), self.to.to_string());
+        let mut vehicle_match = doc! { "$expr": { "$eq": ["$_id", "$$vehicle_id"] } };
+        if let Some(vehicle_type) = &self.vehicle_type {
+            vehicle_match.insert("type", vehicle_type.to_string());
+        }
+
+        vec![
+            doc! { "$match": {
+                "from_date": { "$lte": &to },
+                "to_date": { "$gte": &from },
+                "status": { "$in": statuses },
+            } },
+            doc! { "$lookup": {
+                "from": "vehicles",
+                "let": { "vehicle_id": "$vehicle_id" },
+                "pipeline": [{ "$match": vehicle_match }, { "$project": { "price_by_day": 1 } }],
+                "as": "vehicle",
+            } },
+            doc! { "$unwind": "$vehicle" },
+            // Dates are stored as YYYY-MM-DD, clipping them to the period compares as strings
+            doc! { "$set": { "days": { "$toInt": { "$add": [
+                { "$divide": [
+                    { "$subtract": [
+                        { "$dateFromString": { "dateString": { "$min": ["$to_date", &to] } } },
+                        { "$dateFromString": { "dateString": { "$max": ["$from_date", &from] } } },
+                    ] },
+                    86_400_000,
+                ] },
+                1,
+            ] } } } },
+        ]
+    }
+
+    /// Pending and confirmed bookings per vehicle, by vehicle ID
+    pub fn utilization_pipeline(&self) -> Vec<Document> {
+        let mut pipeline = self.booking_stages(&["PENDING", "CONFIRMED"]);
+        pipeline.extend([
+            doc! { "$group": {
+                "_id": "$vehicle_id",
+                "bookings": { "$sum": 1 },
+                "booked_days": { "$sum": "$days" },
+            } },
+            doc! { "$sort": { "_id": 1 } },
+            doc! { "$project": {
+                "_id": 0,
+                "vehicle_id": "$_id",
+                "bookings": 1,
+                "booked_days": 1,
+                "utilization": { "$divide": ["$booked_days", self.period_days()] },
+            } },
+        ]);
+        pipeline
+    }
+
+    /// Confirmed bookings per month of their first day, their ledger revenue prorated to
+    /// the days inside the period
+    pub fn revenue_pipeline(&self) -> Vec<Document> {
+        let mut pipeline = self.booking_stages(&["CONFIRMED"]);
+        pipeline.extend([
+            // Money comes from the ledger: charges and fees net of refunds, deposits excluded
+            doc! { "$lookup": {
+                "from": "ledger_entries",
+                "localField": "_id",
+                "foreignField": "booking_id",
+                "as": "ledger",
+            } },
+            doc! { "$set": {
+                "net": { "$sum": { "$map": {
+                    "input": { "$filter": {
+                        "input": "$ledger",
+                        "cond": { "$ne": ["$$this.kind", "DEPOSIT"] },
+                    } },
+                    "in": "$$this.amount.amount",
+                } } },
+                "total_days": { "$toInt": { "$add": [
+                    { "$divide": [
+                        { "$subtract": [
+                            { "$dateFromString": { "dateString": "$to_date" } },
+                            { "$dateFromString": { "dateString": "$from_date" } },
+                        ] },
+                        86_400_000,
+                    ] },
+                    1,
+                ] } },
+            } },
+            // Amounts of different currencies are never added up
+            doc! { "$group": {
+                "_id": { "month": { "$substrCP": ["$from_date", 0, 7] }, "currency": booking_currency() },
+                "bookings": { "$sum": 1 },
+                "booked_days": { "$sum": "$days" },
+                "revenue": { "$sum": { "$multiply": [
+                    "$net",
+                    { "$divide": ["$days", "$total_days"] },
+                ] } },
+            } },
+            doc! { "$sort": { "_id.month": 1, "_id.currency": 1 } },
+            doc! { "$project": {
+                "_id": 0,
+                "month": "$_id.month",
+                "bookings": 1,
+                "booked_days": 1,
+                "revenue": { "amount": { "$round": ["$revenue", 2] }, "currency": "$_id.currency" },
+            } },
+        ]);
+        pipeline
+    }
+}
+
+/// Currency of a booking in a pipeline, as `Booking::currency`
+fn booking_currency() -> Document {
+    doc! { "$ifNull": ["$total_price.currency", Currency::default().to_string()] }
+}
+
+impl BookingStats {
+    /// One `$facet` per statistic over the bookings matching `filter`
+    pub fn pipeline(filter: Document) -> Vec<Document> {
+        vec![
+            doc! { "$match": filter },
+            doc! { "$set": { "days": { "$toInt": { "$add": [
+                { "$divide": [
+                    { "$subtract": [
+                        { "$dateFromString": { "dateString": "$to_date" } },
+                        { "$dateFromString": { "dateString": "$from_date" } },
+                    ] },
+                    86_400_000,
+                ] },
+                1,
+            ] } } } },
+            doc! { "$facet": {
+                "by_status": [
+                    { "$group": { "_id": "$status", "bookings": { "$sum": 1 } } },
+                    { "$sort": { "_id": 1 } },
+                    { "$project": { "_id": 0, "status": "$_id", "bookings": 1 } },
+                ],
+                "per_vehicle": [
+                    { "$group": {
+                        "_id": "$vehicle_id",
+                        "bookings": { "$sum": 1 },
+                        "booked_days": { "$sum": "$days" },
+                    } },
+                    { "$sort": { "bookings": -1, "_id": 1 } },
+                    { "$project": { "_id": 0, "vehicle_id": "$_id", "bookings": 1, "booked_days": 1 } },
+                ],
+                // Bookings stored before prices were count for nothing, amounts are summed
+                // per currency
+                "revenue_per_month": [
+                    { "$match": { "status": "CONFIRMED" } },
+                    { "$group": {
+                        "_id": { "month": { "$substrCP": ["$from_date", 0, 7] }, "currency": booking_currency() },
+                        "bookings": { "$sum": 1 },
+                        "revenue": { "$sum": { "$ifNull": ["$total_price.amount", 0] } },
+                    } },
+                    { "$sort": { "_id.month": 1, "_id.currency": 1 } },
+                    { "$project": {
+                        "_id": 0,
+                        "month": "$_id.month",
+                        "bookings": 1,
+                        "revenue": { "amount": "$revenue", "currency": "$_id.currency" },
+                    } },
+                ],
+                "average": [{ "$group": { "_id": null, "days": { "$avg": "$days" } } }],
+            } },
+            doc! { "$project": {
+                "by_status": 1,
+                "per_vehicle": 1,
+                "revenue_per_month": 1,
+                "average_days": { "$arrayElemAt": ["$average.days", 0] },
+            } },
+        ]
+    }
+}
+
+impl CapacityReport {
+    /// Project day by day demand (bookings) against supply (vehicles)
+    pub fn project(query: &ReportQuery, vehicles: &[Vehicle], bookings: &[Booking]) -> Self {
+        let days: Vec<CapacityDay> = (0..=(query.to - query.from).num_days())
+            .map(|offset| {
+                let date = query.from + Duration::days(offset);
+                let supply = vehicles
+                    .iter()
+                    .filter(|vehicle| vehicle.added_at.date_naive() <= date)
+                    .count() as u32;
+                let demand = bookings
+                    .iter()
+                    .filter(|booking| {
+                        matches!(
+                            booking.status,
+                            BookingStatus::Pending | BookingStatus::Confirmed
+                        ) && booking.from_date <= date
+                            && booking.to_date >= date
+                    })
+                    .count() as u32;
+
+                CapacityDay {
+                    date,
+                    supply,
+                    demand,
+                    utilization: if supply == 0 {
+                        0.0
+                    } else {
+                        demand as f64 / supply as f64
+                    },
+                    over_capacity: demand > supply,
+                }
+            })
+            .collect();
+
+        Self {
+            from: query.from,
+            to: query.to,
+            vehicle_type: query.vehicle_type.clone(),
+            over_capacity_days: days.iter().filter(|day| day.over_capacity).count(),
+            days,
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures::{booking_between, date};
+    use crate::models::Decimal;
+
+    fn booking(from: u32, to: u32, status: BookingStatus) -> Booking {
+        let mut booking = booking_between(date(8, from), date(8, to));
+        booking.status = status;
+        booking
+    }
+
+    #[test]
+    fn test_project_flags_over_capacity_days() {
+        let query = ReportQuery {
+            from: date(8, 1),
+            to: date(8, 4),
+            vehicle_type: Some(VehicleType::Car),
+        };
+        let bookings = vec![
+            booking(1, 2, BookingStatus::Confirmed),
+            booking(2, 3, BookingStatus::Pending),
+            booking(2, 4, BookingStatus::Cancelled("Changed plans".to_string())),
+        ];
+
+        // No vehicle in the fleet: every booked day is over capacity
+        let report = CapacityReport::project(&query, &[], &bookings);
+        let demand: Vec<u32> = report.days.iter().map(|day| day.demand).collect();
+
+        assert_eq!(demand, vec![1, 2, 1, 0]);
+        assert_eq!(report.over_capacity_days, 3);
+    }
+
+    #[test]
+    fn test_query_validation() {
+        let mut query = ReportQuery {
+            from: date(8, 10),
+            to: date(8, 1),
+            vehicle_type: None,
+        };
+        assert!(query.validate().is_err());
+
+        query.to = date(8, 31);
+        assert!(query.validate().is_ok());
+    }
+
+    #[test]
+    fn test_pipelines_filter_vehicle_type_and_period() {
+        let query = ReportQuery {
+            from: date(8, 1),
+            to: date(8, 10),
+            vehicle_type: Some(VehicleType::Motorbike),
+        };
+
+        let pipeline = query.utilization_pipeline();
+        let lookup = pipeline[1].get_document("$lookup").unwrap();
+        let vehicle_match = lookup.get_array("pipeline").unwrap()[0]
+            .as_document()
+            .unwrap()
+            .get_document("$match")
+            .unwrap();
+        assert_eq!(vehicle_match.get_str("type"), Ok("MOTORBIKE"));
+
+        let project = pipeline.last().unwrap().get_document("$project").unwrap();
+        assert_eq!(
+            project.get_document("utilization").unwrap(),
+            &doc! { "$divide": ["$booked_days", 10_i64] }
+        );
+
+        // Only confirmed bookings bring revenue
+        let revenue = query.revenue_pipeline();
+        let status = revenue[0]
+            .get_document("$match")
+            .unwrap()
+            .get_document("status")
+            .unwrap();
+        assert_eq!(status, &doc! { "$in": ["CONFIRMED"] });
+    }
+
+    #[test]
+    fn test_booking_stats_pipeline() {
+        let filter = doc! { "customer_id": "customer_user_1" };
+        let pipeline = BookingStats::pipeline(filter.clone());
+
+        assert_eq!(pipeline[0].get_document("$match").unwrap(), &filter);
+        let facets = pipeline[2].get_document("$facet").unwrap();
+        let names: Vec<&str> = facets.keys().map(String::as_str).collect();
+        assert_eq!(
+            names,
+            vec!["by_status", "per_vehicle", "revenue_per_month", "average"]
+        );
+
+        // An empty facet result still deserializes
+        let stats: BookingStats = bson::from_document(doc! {
+            "by_status": [],
+            "per_vehicle": [],
+            "revenue_per_month": [{
+                "month": "2025-08",
+                "bookings": 2,
+                "revenue": { "amount": 320, "currency": "USD" },
+            }],
+        })
+        .unwrap();
+        assert_eq!(stats.average_days, None);
+        assert_eq!(
+            stats.revenue_per_month[0].revenue,
+            Money::new(Decimal::from(320), Currency::USD)
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Booking, BookingStatus};
+
+/// Time a booking being created has to be stored after claiming its days
+pub const CLAIM_GRACE_SECONDS: i64 = 60;
+
+// =============================================================================
+// MAIN RESERVATION STRUCTS
+// =============================================================================
+
+/// Day of a vehicle held by a PENDING or CONFIRMED booking. The `_id` is unique per
+/// vehicle and day, so two bookings can never hold the same day even when they are
+/// created at the same time.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReservationDay {
+    #[serde(rename = "_id")]
+    pub id: String, // See ReservationDay::key
+    pub vehicle_id: ObjectId,
+    pub day: NaiveDate,
+    pub booking_id: ObjectId,
+    /// A claim without its booking is a creation still in progress until
+    /// `claimed_at` + `CLAIM_GRACE_SECONDS`
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub claimed_at: DateTime<Utc>,
+}
+
+impl crate::services::mongodb::MongoStruct for ReservationDay {
+    fn get_collection() -> &'static str {
+        "reservation_days"
+    }
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl ReservationDay {
+    pub fn new(vehicle_id: ObjectId, day: NaiveDate, booking_id: ObjectId) -> Self {
+        Self {
+            id: Self::key(&vehicle_id, day),
+            vehicle_id,
+            day,
+            booking_id,
+            claimed_at: Utc::now(),
+        }
+    }
+
+    pub fn key(vehicle_id: &ObjectId, day: NaiveDate) -> String {
+        format!("{}:{}", vehicle_id.to_hex(), day)
+    }
+
+    /// Days held by a booking, both ends included like the overlap check
+    pub fn days(from_date: NaiveDate, to_date: NaiveDate) -> Vec<NaiveDate> {
+        from_date
+            .iter_days()
+            .take_while(|day| *day <= to_date)
+            .collect()
+    }
+
+    /// Whether the day is still taken, given the booking holding it (None when that
+    /// booking does not exist). Days of cancelled or rejected bookings, and of
+    /// creations that failed before storing their booking, are free again.
+    pub fn is_held(&self, holder: Option<&Booking>, now: DateTime<Utc>) -> bool {
+        match holder {
+            Some(booking) => matches!(
+                booking.status,
+                BookingStatus::Pending | BookingStatus::Confirmed
+            ),
+            None => self.claimed_at + Duration::seconds(CLAIM_GRACE_SECONDS) > now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures::date;
+    use crate::models::CreateBookingRequest;
+
+    #[test]
+    fn test_days_include_both_ends() {
+        assert_eq!(
+            ReservationDay::days(date(6, 1), date(6, 3)),
+            vec![date(6, 1), date(6, 2), date(6, 3)]
+        );
+        assert_eq!(
+            ReservationDay::days(date(6, 5), date(6, 5)),
+            vec![date(6, 5)]
+        );
+        assert!(ReservationDay::days(date(6, 5), date(6, 4)).is_empty());
+    }
+
+    #[test]
+    fn test_key_is_unique_per_vehicle_and_day() {
+        let vehicle_id = ObjectId::new();
+        let booking_id = ObjectId::new();
+        let day = ReservationDay::new(vehicle_id, date(6, 2), booking_id);
+        assert_eq!(day.id, format!("{}:2025-06-02", vehicle_id.to_hex()));
+        assert_ne!(day.id, ReservationDay::key(&vehicle_id, date(6, 3)));
+        assert_ne!(day.id, ReservationDay::key(&ObjectId::new(), date(6, 2)));
+    }
+
+    #[test]
+    fn test_only_active_or_recent_claims_hold_the_day() {
+        let request = CreateBookingRequest {
+            vehicle_id: ObjectId::new(),
+            from_date: date(6, 1),
+            to_date: date(6, 3),
+            driver: None,
+        };
+        let mut booking = Booking::new(request, "customer_user_1".to_string());
+        let claim = ReservationDay::new(booking.vehicle_id, date(6, 2), ObjectId::new());
+        let now = claim.claimed_at;
+
+        assert!(claim.is_held(Some(&booking), now));
+        booking.status = BookingStatus::Confirmed;
+        assert!(claim.is_held(Some(&booking), now));
+        booking.status = BookingStatus::Cancelled("plans changed".to_string());
+        assert!(!claim.is_held(Some(&booking), now));
+        booking.status = BookingStatus::Rejected("expired".to_string());
+        assert!(!claim.is_held(Some(&booking), now));
+
+        // Without its booking the claim is a creation in progress, then a leftover
+        assert!(claim.is_held(None, now));
+        assert!(!claim.is_held(None, now + Duration::seconds(CLAIM_GRACE_SECONDS)));
+    }
+}
@@ -0,0 +1,105 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::Booking;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RiskSignalCode {
+    NewCustomer,
+    FrequentCancellations,
+    LocationMismatch,
+    DisposableEmail,
+}
+
+// =============================================================================
+// MAIN RISK STRUCTS
+// =============================================================================
+
+/// A fraud signal raised by one rule, `score` is the weight it adds
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RiskSignal {
+    pub code: RiskSignalCode,
+    pub score: u32,
+    pub detail: String,
+}
+
+/// Risk of a booking, computed once when it is created
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RiskAssessment {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub booking_id: ObjectId,
+    pub customer_id: String,
+    pub score: u32,
+    pub level: RiskLevel,
+    pub signals: Vec<RiskSignal>,
+    /// High risk bookings are never confirmed automatically
+    pub manual_confirmation: bool,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub assessed_at: DateTime<Utc>,
+}
+
+/// Everything the rules know about a booking being created
+#[derive(Clone, Debug)]
+pub struct RiskContext {
+    pub booking: Booking,
+    pub email: Option<String>,
+    pub client_country: Option<String>, // From the edge proxy geolocation header
+    pub vehicle_country: String,
+    pub previous_bookings: Vec<Booking>, // Every earlier booking of the customer
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for RiskAssessment {
+    fn get_collection() -> &'static str {
+        "risk_assessments"
+    }
+}
+
+impl RiskLevel {
+    /// Level of a total score, `high_score` and above is High, half of it Medium
+    pub fn from_score(score: u32, high_score: u32) -> Self {
+        if score >= high_score {
+            RiskLevel::High
+        } else if score * 2 >= high_score {
+            RiskLevel::Medium
+        } else {
+            RiskLevel::Low
+        }
+    }
+}
+
+impl RiskAssessment {
+    pub fn new(booking: &Booking, signals: Vec<RiskSignal>, high_score: u32) -> Self {
+        let score = signals.iter().map(|signal| signal.score).sum();
+        let level = RiskLevel::from_score(score, high_score);
+
+        Self {
+            id: None,
+            booking_id: booking.id.unwrap_or_default(),
+            customer_id: booking.customer_id.clone(),
+            score,
+            level,
+            signals,
+            manual_confirmation: level == RiskLevel::High,
+            assessed_at: Utc::now(),
+        }
+    }
+}
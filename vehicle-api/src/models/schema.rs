@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+
+use crate::models::{
+    Booking, CreateBookingRequest, CreateVehicleRequest, UpdateBookingRequest,
+    UpdateVehicleRequest, Vehicle,
+};
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+/// Request/response models exposed at GET /meta/schemas/{name}
+#[derive(Clone, Copy, Debug, Serialize, EnumString, EnumIter, Display, PartialEq, Eq, Hash)]
+pub enum SchemaName {
+    CreateVehicleRequest,
+    UpdateVehicleRequest,
+    Vehicle,
+    CreateBookingRequest,
+    UpdateBookingRequest,
+    Booking,
+}
+
+// Schemas are generated once, on first use or during the warm-up
+static MODEL_SCHEMAS: LazyLock<HashMap<SchemaName, serde_json::Value>> = LazyLock::new(|| {
+    SchemaName::iter()
+        .map(|name| (name, name.generate()))
+        .collect()
+});
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Stored document breaking the validator of its collection
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SchemaViolation {
+    #[serde(alias = "_id")]
+    pub id: ObjectId,
+    pub missing_fields: Vec<String>, // Required fields it lacks, empty when a value is wrong
+}
+
+/// Documents of a collection breaking its validator, by `_id`
+#[derive(Clone, Debug, Serialize)]
+pub struct SchemaViolationReport {
+    pub collection: String,
+    pub total: i64,
+    pub violations: Vec<SchemaViolation>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+/// Generate every model schema now rather than on the first request
+pub fn preload_schemas() {
+    LazyLock::force(&MODEL_SCHEMAS);
+}
+
+impl SchemaName {
+    /// JSON Schema of the model, including its validation rules
+    pub fn schema(&self) -> serde_json::Value {
+        MODEL_SCHEMAS[self].clone()
+    }
+
+    fn generate(&self) -> serde_json::Value {
+        let schema = match self {
+            SchemaName::CreateVehicleRequest => schemars::schema_for!(CreateVehicleRequest),
+            SchemaName::UpdateVehicleRequest => schemars::schema_for!(UpdateVehicleRequest),
+            SchemaName::Vehicle => schemars::schema_for!(Vehicle),
+            SchemaName::CreateBookingRequest => schemars::schema_for!(CreateBookingRequest),
+            SchemaName::UpdateBookingRequest => schemars::schema_for!(UpdateBookingRequest),
+            SchemaName::Booking => schemars::schema_for!(Booking),
+        };
+        schema.to_value()
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_schema_name_parsing() {
+        assert_eq!(
+            SchemaName::from_str("CreateVehicleRequest").unwrap(),
+            SchemaName::CreateVehicleRequest
+        );
+        assert!(SchemaName::from_str("Unknown").is_err());
+    }
+
+    #[test]
+    fn test_schema_includes_validation_rules() {
+        let schema = SchemaName::CreateVehicleRequest.schema();
+        let description = &schema["properties"]["description"];
+        assert_eq!(description["additionalProperties"]["maxLength"], 249);
+
+        let year = &schema["properties"]["year_of_production"];
+        assert_eq!(year["minimum"], 1900);
+    }
+
+    #[test]
+    fn test_response_schema_uses_public_id() {
+        let schema = SchemaName::Booking.schema();
+        assert!(schema["properties"].get("id").is_some());
+        assert!(schema["properties"].get("_id").is_none());
+    }
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Brand, Currency, Money, Vehicle, VehicleType};
+
+/// Most suggestions returned at once
+pub const MAX_SUGGESTIONS: i64 = 20;
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Free text vehicle search, matched against brand, model and description
+#[derive(Clone, Debug, Deserialize)]
+pub struct VehicleSearchQuery {
+    pub q: String,
+    pub brand: Option<Brand>,
+    #[serde(rename = "type")]
+    pub vehicle_type: Option<VehicleType>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    /// Currency of the price filters and of the returned prices
+    pub currency: Option<Currency>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SuggestionQuery {
+    pub q: String,
+    pub limit: Option<i64>,
+}
+
+/// Number of matching vehicles sharing a value
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct FacetBucket {
+    #[serde(alias = "_id")]
+    pub value: String,
+    pub count: u64,
+}
+
+/// Breakdown of every match of a search, not only of the returned page
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct SearchFacets {
+    #[serde(default)]
+    pub brand: Vec<FacetBucket>,
+    #[serde(default, rename = "type")]
+    pub vehicle_type: Vec<FacetBucket>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VehicleSearchResults {
+    pub vehicles: Vec<Vehicle>,
+    pub total: u64,
+    pub facets: SearchFacets,
+}
+
+/// Brand or model completing what the user typed
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct VehicleSuggestion {
+    #[serde(alias = "_id")]
+    pub value: String,
+    pub vehicles: u64,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl VehicleSearchQuery {
+    /// Words of the query, a vehicle must match all of them
+    pub fn terms(&self) -> Vec<&str> {
+        self.q.split_whitespace().collect()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.terms().is_empty() {
+            return Err("q must contain at least one word".to_string());
+        }
+        if self.q.chars().count() > 100 {
+            return Err("q is limited to 100 characters".to_string());
+        }
+        Ok(())
+    }
+
+    /// `min_price` and `max_price` in the currency of the query, EUR by default
+    pub fn price_bounds(&self) -> (Option<Money>, Option<Money>) {
+        let currency = self.currency.unwrap_or_default();
+        let price = |amount: Option<f64>| amount.and_then(|a| Money::from_f64(a, currency));
+        (price(self.min_price), price(self.max_price))
+    }
+}
+
+impl SuggestionQuery {
+    pub fn prefix(&self) -> &str {
+        self.q.trim()
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(5).clamp(1, MAX_SUGGESTIONS)
+    }
+}
@@ -0,0 +1,188 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::authentication::identity::{Identity, Role};
+use crate::authentication::permission::Permission;
+
+/// Prefix of service account user IDs, followed by the account ID
+pub const SERVICE_USER_PREFIX: &str = "service:";
+
+// =============================================================================
+// MAIN SERVICE ACCOUNT STRUCT
+// =============================================================================
+
+/// Non-human identity of a backend integration (billing job, ...). It exchanges its
+/// client secret (only the hash is stored) for short-lived tokens carrying its own
+/// permissions instead of those of a role.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServiceAccount {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Vec<Permission>,
+    pub secret_hash: String,
+    pub created_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub disabled_at: Option<DateTime<Utc>>,
+    /// Rental company the account acts for, None for deployment wide accounts
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct CreateServiceAccountRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(max = 500))]
+    pub description: Option<String>,
+    /// e.g. ["vehicle:read_bookings", "pii:read"]
+    #[validate(length(min = 1, max = 20))]
+    pub permissions: Vec<Permission>,
+    /// Rental company of the account, the creator's own when omitted
+    #[validate(length(min = 1, max = 100))]
+    pub tenant_id: Option<String>,
+}
+
+/// Client credentials exchanged on POST /auth/service-token
+#[derive(Clone, Debug, Deserialize)]
+pub struct ServiceTokenRequest {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ServiceTokenResponse {
+    pub token: String,
+    pub token_type: String,
+    pub expires_at: DateTime<Utc>,
+    pub identity: Identity,
+}
+
+/// Service account as returned by the API, never includes the secret hash
+#[derive(Clone, Debug, Serialize)]
+pub struct ServiceAccountResponse {
+    pub id: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Vec<Permission>,
+    pub user_id: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub tenant_id: Option<String>,
+}
+
+/// Returned once on creation, the plain secret cannot be retrieved afterwards
+#[derive(Clone, Debug, Serialize)]
+pub struct CreatedServiceAccountResponse {
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(flatten)]
+    pub service_account: ServiceAccountResponse,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for ServiceAccount {
+    fn get_collection() -> &'static str {
+        "service_accounts"
+    }
+}
+
+impl ServiceAccount {
+    pub fn new(request: CreateServiceAccountRequest, secret: &str, created_by: String) -> Self {
+        Self {
+            id: None,
+            name: request.name,
+            description: request.description,
+            permissions: request.permissions,
+            secret_hash: crate::authentication::api_key::hash_key(secret),
+            created_by,
+            created_at: Utc::now(),
+            disabled_at: None,
+            tenant_id: request.tenant_id,
+        }
+    }
+
+    /// User ID of the account's tokens, known once stored
+    pub fn user_id(&self) -> Option<String> {
+        self.id
+            .map(|id| format!("{}{}", SERVICE_USER_PREFIX, id.to_hex()))
+    }
+
+    pub fn identity(&self) -> Identity {
+        Identity {
+            role: Role::Service,
+            user_id: self.user_id().unwrap_or_default(),
+            email: None,
+            tenant_id: self.tenant_id.clone(),
+            permissions: self
+                .permissions
+                .iter()
+                .map(|permission| permission.to_string())
+                .collect(),
+            profile: None,
+            impersonated_by: None,
+        }
+    }
+}
+
+impl From<ServiceAccount> for ServiceAccountResponse {
+    fn from(service_account: ServiceAccount) -> Self {
+        Self {
+            id: service_account.id.map(|id| id.to_hex()),
+            user_id: service_account.user_id(),
+            name: service_account.name,
+            description: service_account.description,
+            permissions: service_account.permissions,
+            created_by: service_account.created_by,
+            created_at: service_account.created_at,
+            disabled_at: service_account.disabled_at,
+            tenant_id: service_account.tenant_id,
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_carries_the_account_grants() {
+        let request: CreateServiceAccountRequest = serde_json::from_str(
+            r#"{ "name": "billing", "permissions": ["vehicle:read_bookings", "pii:read"], "tenant_id": "acme" }"#,
+        )
+        .unwrap();
+        let mut service_account =
+            ServiceAccount::new(request, "vsa_secret", "admin_user_1".to_string());
+        service_account.id = Some(ObjectId::new());
+
+        let identity = service_account.identity();
+        assert_eq!(identity.role, Role::Service);
+        assert!(identity.user_id.starts_with(SERVICE_USER_PREFIX));
+        assert_eq!(identity.tenant_id.as_deref(), Some("acme"));
+        assert_eq!(
+            identity.permissions,
+            vec!["vehicle:read_bookings", "pii:read"]
+        );
+        assert_ne!(service_account.secret_hash, "vsa_secret");
+    }
+}
@@ -0,0 +1,215 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::authentication::identity::{Identity, Role};
+
+// =============================================================================
+// MAIN SESSION STRUCTS
+// =============================================================================
+
+/// Session issued after an OIDC login, to a service account or to an admin impersonating
+/// a customer, only the token hash is stored
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub token_hash: String,
+    pub role: Role,
+    pub user_id: String,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>, // Service accounts only
+    #[serde(default)]
+    pub impersonated_by: Option<String>, // Admin holding an impersonation token
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Long-lived token renewing sessions without a new login. Rotated on every use,
+/// all the tokens descending from the same login share a `family_id`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RefreshToken {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub token_hash: String,
+    pub family_id: ObjectId,
+    pub role: Role,
+    pub user_id: String,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub used_at: Option<DateTime<Utc>>, // Set when exchanged for a new token
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Pending OIDC authorization request, consumed by the callback
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OidcLoginState {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: String,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+/// Token of an admin acting as a customer, no refresh token is issued
+#[derive(Clone, Debug, Serialize)]
+pub struct ImpersonationTokenResponse {
+    pub token: String,
+    pub token_type: String,
+    pub expires_at: DateTime<Utc>,
+    pub identity: Identity,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SessionResponse {
+    pub token: String,
+    pub token_type: String,
+    pub expires_at: DateTime<Utc>,
+    pub refresh_token: String,
+    pub refresh_expires_at: DateTime<Utc>,
+    pub identity: Identity,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Session {
+    fn get_collection() -> &'static str {
+        "sessions"
+    }
+}
+
+impl crate::services::mongodb::MongoStruct for RefreshToken {
+    fn get_collection() -> &'static str {
+        "refresh_tokens"
+    }
+}
+
+impl crate::services::mongodb::MongoStruct for OidcLoginState {
+    fn get_collection() -> &'static str {
+        "oidc_login_states"
+    }
+}
+
+impl Session {
+    pub fn identity(&self) -> Identity {
+        Identity {
+            role: self.role.clone(),
+            user_id: self.user_id.clone(),
+            email: self.email.clone(),
+            tenant_id: self.tenant_id.clone(),
+            permissions: self.permissions.clone(),
+            profile: None,
+            impersonated_by: self.impersonated_by.clone(),
+        }
+    }
+}
+
+/// Where a refresh token is in its lifecycle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RefreshTokenState {
+    Active,
+    Used, // Already exchanged, presenting it again means it leaked
+    Revoked,
+    Expired,
+}
+
+impl RefreshToken {
+    pub fn state(&self, now: DateTime<Utc>) -> RefreshTokenState {
+        if self.revoked_at.is_some() {
+            RefreshTokenState::Revoked
+        } else if self.used_at.is_some() {
+            RefreshTokenState::Used
+        } else if self.expires_at <= now {
+            RefreshTokenState::Expired
+        } else {
+            RefreshTokenState::Active
+        }
+    }
+
+    pub fn identity(&self) -> Identity {
+        Identity {
+            role: self.role.clone(),
+            user_id: self.user_id.clone(),
+            email: self.email.clone(),
+            tenant_id: self.tenant_id.clone(),
+            permissions: Vec::new(),
+            profile: None,
+            impersonated_by: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_refresh_token_state() {
+        let now = Utc::now();
+        let mut token = RefreshToken {
+            id: None,
+            token_hash: "hash".to_string(),
+            family_id: ObjectId::new(),
+            role: Role::Customer,
+            user_id: "oidc:user".to_string(),
+            email: None,
+            tenant_id: None,
+            created_at: now,
+            expires_at: now + Duration::days(30),
+            used_at: None,
+            revoked_at: None,
+        };
+        assert_eq!(token.state(now), RefreshTokenState::Active);
+        assert_eq!(
+            token.state(now + Duration::days(31)),
+            RefreshTokenState::Expired
+        );
+
+        token.used_at = Some(now);
+        assert_eq!(token.state(now), RefreshTokenState::Used);
+
+        // Revocation wins over everything else
+        token.revoked_at = Some(now);
+        assert_eq!(token.state(now), RefreshTokenState::Revoked);
+    }
+}
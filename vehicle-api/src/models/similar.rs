@@ -0,0 +1,240 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Vehicle, VehicleMetadata, VehicleType};
+use crate::services;
+
+/// Most similar vehicles returned at once
+pub const MAX_SIMILAR: u32 = 20;
+
+// =============================================================================
+// MAIN SIMILARITY STRUCTS
+// =============================================================================
+
+/// Weight of each criterion in the similarity score, a weight of 0 ignores it
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimilarityWeights {
+    pub vehicle_type: f64,
+    pub price: f64,
+    pub specs: f64, // Seats and engine
+    pub brand: f64,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+/// Optional period the similar vehicles must be free on, both days included
+#[derive(Clone, Debug, Deserialize)]
+pub struct SimilarQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SimilarVehicle {
+    pub vehicle: Vehicle,
+    /// From 0 (nothing in common) to 1
+    pub score: f64,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl Default for SimilarityWeights {
+    fn default() -> Self {
+        Self {
+            vehicle_type: 4.0,
+            price: 3.0,
+            specs: 2.0,
+            brand: 1.0,
+        }
+    }
+}
+
+impl SimilarityWeights {
+    /// SIMILARITY_WEIGHT_TYPE, _PRICE, _SPECS and _BRAND override the defaults (4, 3, 2, 1)
+    pub fn from_env() -> Self {
+        let weight = |name: &str, default: f64| {
+            std::env::var(format!("SIMILARITY_WEIGHT_{}", name))
+                .ok()
+                .and_then(|value| value.parse::<f64>().ok())
+                .filter(|weight| *weight >= 0.0)
+                .unwrap_or(default)
+        };
+        let defaults = Self::default();
+        Self {
+            vehicle_type: weight("TYPE", defaults.vehicle_type),
+            price: weight("PRICE", defaults.price),
+            specs: weight("SPECS", defaults.specs),
+            brand: weight("BRAND", defaults.brand),
+        }
+    }
+
+    /// Weighted average of the criteria, from 0 to 1
+    pub fn score(&self, vehicle: &Vehicle, candidate: &Vehicle) -> f64 {
+        let total = self.vehicle_type + self.price + self.specs + self.brand;
+        if total == 0.0 {
+            return 0.0;
+        }
+
+        let same_type = VehicleType::of(vehicle) == VehicleType::of(candidate);
+        let same_brand = vehicle.brand == candidate.brand;
+        // Prices compared in the vehicle's currency, nothing in common without a rate
+        let price = services::currency::rates()
+            .convert(candidate.price_by_day, vehicle.price_by_day.currency)
+            .map_or(0.0, |price| {
+                closeness(vehicle.price_by_day.to_f64(), price.to_f64())
+            });
+        let weighted = self.vehicle_type * f64::from(u8::from(same_type))
+            + self.price * price
+            + self.specs * specs_similarity(&vehicle.metadata, &candidate.metadata)
+            + self.brand * f64::from(u8::from(same_brand));
+        weighted / total
+    }
+}
+
+impl SimilarQuery {
+    pub fn validate(&self) -> Result<(), String> {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from > to => {
+                Err("from must be before or equal to to".to_string())
+            }
+            (Some(_), None) | (None, Some(_)) => Err("from and to go together".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(5).clamp(1, MAX_SIMILAR) as usize
+    }
+}
+
+/// 1 for equal values, down to 0 once one is twice the other
+fn closeness(a: f64, b: f64) -> f64 {
+    let largest = a.max(b);
+    if largest <= 0.0 {
+        return 1.0;
+    }
+    (1.0 - (a - b).abs() / largest * 2.0).max(0.0)
+}
+
+/// Same seats and a close engine size, vehicles of different types have nothing in common
+fn specs_similarity(a: &VehicleMetadata, b: &VehicleMetadata) -> f64 {
+    match (a, b) {
+        (VehicleMetadata::Car(a), VehicleMetadata::Car(b)) => {
+            let seats = f64::from(u8::from(a.seats == b.seats));
+            (seats + closeness(f64::from(a.engine_cc), f64::from(b.engine_cc))) / 2.0
+        }
+        (VehicleMetadata::Motorbike(a), VehicleMetadata::Motorbike(b)) => {
+            closeness(f64::from(a.engine_cc), f64::from(b.engine_cc))
+        }
+        _ => 0.0,
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        Brand, CarMetadata, CarModel, Currency, FuelType, Gearbox, Money, MotorbikeMetadata,
+        MotorbikeModel, VehicleStatus,
+    };
+    use chrono::Utc;
+
+    fn vehicle(brand: Brand, metadata: VehicleMetadata, price_by_day: f64) -> Vehicle {
+        Vehicle {
+            id: None,
+            brand,
+            metadata,
+            description: None,
+            price_by_day: Money::from_f64(price_by_day, Currency::EUR).unwrap(),
+            year_of_production: 2022,
+            added_at: Utc::now(),
+            added_by: "admin_user_1".to_string(),
+            timezone: "UTC".to_string(),
+            country: None,
+            tenant_id: None,
+            deleted_at: None,
+            images: Vec::new(),
+            status: VehicleStatus::Available,
+            location: None,
+            station_id: None,
+        }
+    }
+
+    fn car(seats: u8, engine_cc: u32) -> VehicleMetadata {
+        VehicleMetadata::Car(CarMetadata {
+            model: CarModel::C_CLASS,
+            seats,
+            fuel_type: FuelType::PETROL,
+            gearbox: Gearbox::AUTOMATIC,
+            engine_cc,
+        })
+    }
+
+    #[test]
+    fn test_closer_vehicles_score_higher() {
+        let weights = SimilarityWeights::default();
+        let reference = vehicle(Brand::MERCEDES, car(5, 2000), 100.0);
+
+        let twin = vehicle(Brand::MERCEDES, car(5, 2000), 100.0);
+        let other_brand = vehicle(Brand::TESLA, car(5, 2000), 100.0);
+        let pricier = vehicle(Brand::TESLA, car(5, 2000), 180.0);
+        let motorbike = vehicle(
+            Brand::HONDA,
+            VehicleMetadata::Motorbike(MotorbikeMetadata {
+                model: MotorbikeModel::CRUISER,
+                engine_cc: 1100,
+                has_sidecar: false,
+            }),
+            100.0,
+        );
+
+        assert_eq!(weights.score(&reference, &twin), 1.0);
+        let scores: Vec<f64> = [&other_brand, &pricier, &motorbike]
+            .iter()
+            .map(|candidate| weights.score(&reference, candidate))
+            .collect();
+        assert!(scores[0] > scores[1] && scores[1] > scores[2]);
+        assert_eq!(scores[2], 0.3); // Only the price in common
+    }
+
+    #[test]
+    fn test_zero_weights_ignore_a_criterion() {
+        let weights = SimilarityWeights {
+            vehicle_type: 0.0,
+            price: 0.0,
+            specs: 0.0,
+            brand: 1.0,
+        };
+        let reference = vehicle(Brand::MERCEDES, car(5, 2000), 100.0);
+
+        assert_eq!(
+            weights.score(&reference, &vehicle(Brand::MERCEDES, car(2, 4000), 300.0)),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_query_validation() {
+        let date = |day| NaiveDate::from_ymd_opt(2025, 8, day);
+        let query = |from, to| SimilarQuery {
+            from,
+            to,
+            limit: Some(50),
+        };
+
+        assert!(query(None, None).validate().is_ok());
+        assert!(query(date(1), date(5)).validate().is_ok());
+        assert!(query(date(5), date(1)).validate().is_err());
+        assert!(query(date(1), None).validate().is_err());
+        assert_eq!(query(None, None).limit(), MAX_SIMILAR as usize);
+    }
+}
@@ -0,0 +1,172 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::models::{office_hours, validate_location, GeoPoint, OpeningHours};
+
+// =============================================================================
+// MAIN STATION STRUCT
+// =============================================================================
+
+/// Pickup station vehicles are rented from. Its vehicles take its location and time zone,
+/// and are only picked up and returned while it is open.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Station {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    pub address: String,
+    pub location: GeoPoint,
+    pub timezone: String, // IANA time zone, opening hours are local to it
+    pub opening_hours: Vec<OpeningHours>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    pub created_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct CreateStationRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(min = 1, max = 300))]
+    pub address: String,
+    #[validate(custom(function = "validate_location"))]
+    pub location: GeoPoint,
+    pub timezone: String,
+    pub opening_hours: Vec<OpeningHours>,
+}
+
+/// Fields to change, the others are kept
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct UpdateStationRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+    #[validate(length(min = 1, max = 300))]
+    pub address: Option<String>,
+    #[validate(custom(function = "validate_location"))]
+    pub location: Option<GeoPoint>,
+    pub timezone: Option<String>,
+    pub opening_hours: Option<Vec<OpeningHours>>,
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Station {
+    fn get_collection() -> &'static str {
+        "stations"
+    }
+}
+
+impl Station {
+    pub fn new(request: CreateStationRequest, created_by: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: None,
+            name: request.name.trim().to_string(),
+            address: request.address.trim().to_string(),
+            location: request.location,
+            timezone: request.timezone,
+            opening_hours: request.opening_hours,
+            tenant_id: None,
+            created_by,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Apply the fields of an update
+    pub fn apply(&mut self, request: UpdateStationRequest) {
+        if let Some(name) = request.name {
+            self.name = name.trim().to_string();
+        }
+        if let Some(address) = request.address {
+            self.address = address.trim().to_string();
+        }
+        if let Some(location) = request.location {
+            self.location = location;
+        }
+        if let Some(timezone) = request.timezone {
+            self.timezone = timezone;
+        }
+        if let Some(opening_hours) = request.opening_hours {
+            self.opening_hours = opening_hours;
+        }
+        self.updated_at = Utc::now();
+    }
+
+    /// Whether a vehicle can be picked up on `date`, at instant `now`
+    pub fn allows_pickup(&self, date: NaiveDate, now: DateTime<Utc>) -> bool {
+        match self.timezone.parse::<Tz>() {
+            Ok(tz) => office_hours::allows_pickup(
+                &self.opening_hours,
+                date,
+                now.with_timezone(&tz).naive_local(),
+            ),
+            Err(_) => true,
+        }
+    }
+
+    /// Whether a vehicle can be returned on `date`: the station opens that day
+    pub fn allows_return(&self, date: NaiveDate) -> bool {
+        self.opening_hours
+            .iter()
+            .any(|slot| slot.days.contains(&date.weekday()))
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Weekday;
+
+    #[test]
+    fn test_station_opening_hours() {
+        let station = Station::new(
+            CreateStationRequest {
+                name: " Gare de Lyon ".to_string(),
+                address: "Place Louis-Armand, 75012 Paris".to_string(),
+                location: GeoPoint::new(48.8443, 2.3744),
+                timezone: "Europe/Paris".to_string(),
+                opening_hours: vec![OpeningHours {
+                    days: vec![
+                        Weekday::Mon,
+                        Weekday::Tue,
+                        Weekday::Wed,
+                        Weekday::Thu,
+                        Weekday::Fri,
+                    ],
+                    opens: "08:00".parse().unwrap(),
+                    closes: "20:00".parse().unwrap(),
+                }],
+            },
+            "admin_user".to_string(),
+        );
+        assert_eq!(station.name, "Gare de Lyon");
+
+        // Friday 2025-08-01 19:00 Paris time (UTC+2)
+        let now: DateTime<Utc> = "2025-08-01T17:00:00Z".parse().unwrap();
+        let friday = NaiveDate::from_ymd_opt(2025, 8, 1).unwrap();
+        let saturday = NaiveDate::from_ymd_opt(2025, 8, 2).unwrap();
+        assert!(station.allows_pickup(friday, now));
+        assert!(!station.allows_pickup(friday, now + chrono::Duration::hours(2)));
+        assert!(!station.allows_pickup(saturday, now));
+        assert!(station.allows_return(friday));
+        assert!(!station.allows_return(saturday));
+    }
+}
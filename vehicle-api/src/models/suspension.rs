@@ -0,0 +1,126 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// =============================================================================
+// MAIN SUSPENSION STRUCT
+// =============================================================================
+
+/// A customer barred from creating bookings. Existing bookings can still be
+/// viewed and cancelled.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Suspension {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: String,
+    pub reason: String,
+    pub suspended_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub suspended_at: DateTime<Utc>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub ends_at: Option<DateTime<Utc>>, // Indefinite when None
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub lifted_at: Option<DateTime<Utc>>, // Reinstated early by an admin
+    #[serde(default)]
+    pub lifted_by: Option<String>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct SuspendCustomerRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub user_id: String,
+    #[validate(length(min = 1, max = 500))]
+    pub reason: String,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SuspensionFilters {
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub include_inactive: bool, // Lifted and ended suspensions too
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for Suspension {
+    fn get_collection() -> &'static str {
+        "suspensions"
+    }
+}
+
+impl Suspension {
+    pub fn new(request: SuspendCustomerRequest, suspended_by: String) -> Self {
+        Self {
+            id: None,
+            user_id: request.user_id,
+            reason: request.reason,
+            suspended_by,
+            suspended_at: Utc::now(),
+            ends_at: request.ends_at,
+            lifted_at: None,
+            lifted_by: None,
+        }
+    }
+
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.lifted_at.is_none() && self.ends_at.is_none_or(|ends_at| ends_at > now)
+    }
+
+    /// Mongo filter matching the suspensions in force for a customer
+    pub fn active_filter(user_id: &str, now: DateTime<Utc>) -> bson::Document {
+        let mut filter = bson::doc! { "user_id": user_id };
+        filter.extend(Self::in_force_filter(now));
+        filter
+    }
+
+    /// Suspensions neither lifted nor ended, whoever they concern
+    pub fn in_force_filter(now: DateTime<Utc>) -> bson::Document {
+        bson::doc! {
+            "lifted_at": null,
+            "$or": [
+                { "ends_at": null },
+                { "ends_at": { "$gt": bson::DateTime::from_chrono(now) } },
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_suspension_is_active() {
+        let now = Utc::now();
+        let request = SuspendCustomerRequest {
+            user_id: "customer_user_1".to_string(),
+            reason: "Unpaid damages".to_string(),
+            ends_at: Some(now + Duration::days(7)),
+        };
+        let mut suspension = Suspension::new(request, "admin_user".to_string());
+
+        assert!(suspension.is_active(now));
+        assert!(!suspension.is_active(now + Duration::days(8)));
+
+        suspension.ends_at = None;
+        assert!(suspension.is_active(now + Duration::days(365)));
+
+        suspension.lifted_at = Some(now);
+        assert!(!suspension.is_active(now));
+    }
+}
@@ -1,236 +1,66 @@
-use bson::oid::ObjectId;
-use bson::Document;
-use chrono::{DateTime, Utc};
+use bson::{doc, oid::ObjectId, Bson, Document};
 use derive_builder::Builder;
-use macros::CustomValidate;
 use mongodb::options::FindOptions;
-use serde::{Deserialize, Serialize};
-use strum::{Display, EnumString};
-use validator::Validate;
+use serde::Deserialize;
 
-use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::models::{Currency, CurrencyQuery, Money, RankQuery, VehicleType};
 use crate::services;
+use crate::util::pagination::PageQuery;
 use crate::util::serde_helpers::parse_sort_fields;
-use crate::validator::CustomValidateTrait;
 
-// =============================================================================
-// ENUMS
-// =============================================================================
-
-#[derive(Clone, Debug, Serialize, Deserialize, EnumString, Display, PartialEq)]
-#[serde(rename_all = "UPPERCASE")]
-pub enum Gearbox {
-    MANUAL,
-    AUTOMATIC,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize, EnumString, Display, PartialEq)]
-#[serde(rename_all = "UPPERCASE")]
-pub enum FuelType {
-    PETROL,
-    DIESEL,
-    ELECTRIC,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize, EnumString, Display, PartialEq)]
-#[serde(rename_all = "UPPERCASE")]
-pub enum Brand {
-    // Car brands
-    TESLA,
-    MERCEDES,
-    // Motorbike brands
-    HONDA,
-    YAMAHA,
-    KAWASAKI,
-    DUCATI,
-    BMW,
-    HARLEY_DAVIDSON,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize, EnumString, Display, PartialEq)]
-#[serde(rename_all = "UPPERCASE")]
-pub enum CarModel {
-    // Tesla models
-    MODEL_S,
-    MODEL_3,
-    MODEL_X,
-    MODEL_Y,
-    CYBERTRUCK,
-    ROADSTER,
-    // Mercedes models
-    A_CLASS,
-    C_CLASS,
-    E_CLASS,
-    S_CLASS,
-    G_CLASS,
-    GLC,
-    GLE,
-    AMG_GT,
-}
+pub use vehicle_api_types::vehicle::*;
 
-#[derive(Clone, Debug, Serialize, Deserialize, EnumString, Display, PartialEq)]
-#[serde(rename_all = "UPPERCASE")]
-pub enum MotorbikeModel {
-    SPORTBIKE,
-    CRUISER,
-}
+/// Radius of the nearby search without `radius_km`, and the largest one
+pub const DEFAULT_RADIUS_KM: f64 = 25.0;
+pub const MAX_RADIUS_KM: f64 = 500.0;
 
 // =============================================================================
-// METADATA STRUCTS
+// QUERY BUILDER
 // =============================================================================
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct CarMetadata {
-    pub model: CarModel,
-    pub seats: u8,
-    pub fuel_type: FuelType,
-    pub gearbox: Gearbox,
-    pub engine_cc: u32,
+#[derive(Builder, Clone, Debug, Default)]
+#[builder(setter(into, strip_option), default)]
+pub struct VehicleQueryBuilder {
+    pub filters: Option<VehicleFilters>,
+    pub pagination: Option<VehiclePagination>,
+    /// Currency of the price filters, EUR by default
+    pub currency: Option<Currency>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct MotorbikeMetadata {
-    pub model: MotorbikeModel,
-    pub engine_cc: u32,
-    pub has_sidecar: bool,
+/// `type` query parameter of the vehicle and booking lists
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct VehicleTypeScope {
+    #[serde(rename = "type")]
+    pub vehicle_type: Option<VehicleTypeChoice>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(tag = "type", content = "metadata", rename_all = "UPPERCASE")]
-pub enum VehicleMetadata {
-    Car(CarMetadata),
-    Motorbike(MotorbikeMetadata),
+/// `include_deleted` query parameter of the vehicle list and details, soft deleted
+/// vehicles are left out without it
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct DeletedScope {
+    #[serde(default)]
+    pub include_deleted: bool,
 }
 
-// =============================================================================
-// MAIN VEHICLE STRUCT
-// =============================================================================
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Vehicle {
-    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
-    pub id: Option<ObjectId>,
-    pub brand: Brand,
+/// Scope, ranking and currency query parameters of GET /vehicles. Only parameters read
+/// from strings can be flattened, numbers and booleans keep their own query struct.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct VehicleListQuery {
     #[serde(flatten)]
-    pub metadata: VehicleMetadata,
-    pub description: Option<String>,
-    pub price_by_day: f64,
-    pub year_of_production: u32,
-    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
-    pub added_at: DateTime<Utc>,
-    pub added_by: String,
-}
-
-// =============================================================================
-// REQUEST/RESPONSE STRUCTS
-// =============================================================================
-
-#[derive(Clone, Debug, Serialize, Deserialize, Validate, CustomValidate)]
-pub struct CreateVehicleRequest {
-    pub brand: Brand,
+    pub scope: VehicleTypeScope,
     #[serde(flatten)]
-    #[custom_validate(custom(function = "crate::validator::vehicle::validate_metadata"))]
-    pub metadata: VehicleMetadata,
-    #[validate(length(
-        min = 1,
-        max = 249,
-        message = "Description must be between 1 and 249 characters"
-    ))]
-    pub description: Option<String>,
-    #[validate(range(min = 0.01, message = "Price must be greater than 0"))]
-    pub price_by_day: f64,
-    #[validate(range(min = 1900, max = 2030, message = "Year must be between 1900 and 2030"))]
-    pub year_of_production: u32,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
-pub struct UpdateVehicleRequest {
-    #[validate(length(
-        min = 1,
-        max = 249,
-        message = "Description must be between 1 and 249 characters"
-    ))]
-    pub description: Option<String>, // Option<Option<String>> to allow clearing
-    #[validate(range(min = 0.01, message = "Price must be greater than 0"))]
-    pub price_by_day: Option<f64>,
-}
-
-// =============================================================================
-// FILTERING AND PAGINATION STRUCTS
-// =============================================================================
-
-#[derive(Clone, Debug, Serialize, Deserialize, Default)]
-pub struct VehicleFilters {
-    // Brand and model filters (comma-separated, using enum types)
-    #[serde(
-        deserialize_with = "crate::util::serde_helpers::deserialize_comma_separated",
-        default
-    )]
-    pub brand: Option<Vec<Brand>>,
-
-    // Model can be either CarModel or MotorbikeModel, so we keep it as String for flexibility
-    #[serde(
-        deserialize_with = "crate::util::serde_helpers::deserialize_comma_separated",
-        default
-    )]
-    pub model: Option<Vec<String>>,
-
-    // Price filters (min and max separately)
-    pub min_price: Option<f64>,
-    pub max_price: Option<f64>,
-
-    // Year filters (comma-separated)
-    #[serde(
-        deserialize_with = "crate::util::serde_helpers::deserialize_comma_separated",
-        default
-    )]
-    pub year_of_production: Option<Vec<u32>>,
-
-    // Vehicle metadata filters (using enum types)
-    #[serde(
-        deserialize_with = "crate::util::serde_helpers::deserialize_comma_separated",
-        default
-    )]
-    pub fuel_type: Option<Vec<FuelType>>,
-
-    #[serde(
-        deserialize_with = "crate::util::serde_helpers::deserialize_comma_separated",
-        default
-    )]
-    pub gearbox: Option<Vec<Gearbox>>,
-
-    #[serde(
-        deserialize_with = "crate::util::serde_helpers::deserialize_comma_separated",
-        default
-    )]
-    pub seats: Option<Vec<u8>>,
-
-    #[serde(
-        deserialize_with = "crate::util::serde_helpers::deserialize_comma_separated",
-        default
-    )]
-    pub engine_cc: Option<Vec<u32>>,
-
-    // Boolean filter for motorbike sidecar
-    pub has_sidecar: Option<bool>,
-
-    // Date range filters (for added_at field)
-    pub added_at_from: Option<DateTime<Utc>>,
-    pub added_at_to: Option<DateTime<Utc>>,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct VehiclePagination {
-    pub page: Option<i64>,
-    pub limit: Option<i64>,
-    pub sort: Option<String>,
+    pub rank: RankQuery,
+    #[serde(flatten)]
+    pub currency: CurrencyQuery,
 }
 
-#[derive(Builder, Clone, Debug, Default)]
-#[builder(setter(into, strip_option), default)]
-pub struct VehicleQueryBuilder {
-    pub filters: Option<VehicleFilters>,
-    pub pagination: Option<VehiclePagination>,
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum VehicleTypeChoice {
+    Car,
+    Motorbike,
+    All, // Lifts the default filter of the role
 }
 
 // =============================================================================
@@ -243,28 +73,23 @@ impl crate::services::mongodb::MongoStruct for Vehicle {
     }
 }
 
-impl Vehicle {
-    pub fn new(request: CreateVehicleRequest, added_by: String) -> Result<Self, String> {
-        Ok(Self {
-            id: None,
-            brand: request.brand,
-            metadata: request.metadata,
-            description: request.description,
-            price_by_day: request.price_by_day,
-            year_of_production: request.year_of_production,
-            added_at: Utc::now(),
-            added_by,
-        })
-    }
-}
-
 // =============================================================================
 // IMPLEMENTATIONS - QUERY BUILDING
 // =============================================================================
 
-impl VehicleFilters {
+/// Convert filters to a BSON document for MongoDB query
+pub trait ToBsonFilter {
+    fn to_bson_filter(&self) -> Document;
+}
+
+/// Convert pagination to MongoDB FindOptions
+pub trait ToFindOptions {
+    fn to_find_options(&self) -> FindOptions;
+}
+
+impl ToBsonFilter for VehicleFilters {
     /// Convert filters to BSON document for MongoDB query (Ultra-clean version using QueryBuilder)
-    pub fn to_bson_filter(&self) -> Document {
+    fn to_bson_filter(&self) -> Document {
         let mut filter = Document::new();
         let builder = services::mongodb::QueryBuilder::new();
 
@@ -286,9 +111,6 @@ impl VehicleFilters {
         // Boolean filter
         builder.add_boolean_filter(&mut filter, "metadata.has_sidecar", self.has_sidecar);
 
-        // Price range filter using min/max
-        builder.add_range_filter(&mut filter, "price_by_day", self.min_price, self.max_price);
-
         // Date range filter using the range method
         builder.add_range_filter(
             &mut filter,
@@ -297,53 +119,113 @@ impl VehicleFilters {
             self.added_at_to,
         );
 
+        // Checked beforehand, see validator::vehicle::validate_vehicle_filters
+        if let Some(station_id) = self
+            .station_id
+            .as_deref()
+            .and_then(|id| ObjectId::parse_str(id).ok())
+        {
+            filter.insert("station_id", station_id);
+        }
+
+        // Nearby vehicles, nearest first. `$nearSphere` takes meters.
+        if let (Some(lat), Some(lng)) = (self.lat, self.lng) {
+            let radius_km = self.radius_km.unwrap_or(DEFAULT_RADIUS_KM);
+            filter.insert(
+                "location",
+                doc! { "$nearSphere": {
+                    "$geometry": { "type": "Point", "coordinates": [lng, lat] },
+                    "$maxDistance": radius_km * 1000.0,
+                } },
+            );
+        }
+
         filter
     }
 }
 
-impl VehiclePagination {
+impl ToFindOptions for VehiclePagination {
     /// Convert pagination to MongoDB FindOptions
-    pub fn to_find_options(&self) -> FindOptions {
+    fn to_find_options(&self) -> FindOptions {
         let mut options = FindOptions::default();
 
-        // Set limit
-        if let Some(limit) = self.limit {
-            options.limit = Some(limit);
-        }
-
-        // Set skip for pagination
-        if let Some(page) = self.page {
-            let limit = self.limit.unwrap_or(10);
-            let skip = (page - 1) * limit;
-            if skip > 0 {
-                options.skip = Some(skip as u64);
-            }
-        }
-
         // Set sort
         if let Some(sort_str) = &self.sort {
             let sort_fields = parse_sort_fields(sort_str);
             if !sort_fields.is_empty() {
                 let mut sort_doc = Document::new();
                 for (field, direction) in sort_fields {
+                    // Prices are only ordered within a currency, never across them
+                    if field == "price_by_day" || field == "price_by_day.amount" {
+                        sort_doc.insert("price_by_day.currency", direction);
+                        sort_doc.insert("price_by_day.amount", direction);
+                        continue;
+                    }
                     sort_doc.insert(field, direction);
                 }
                 options.sort = Some(sort_doc);
             }
         }
 
+        // Set skip and limit, the page size is bounded for every list endpoint
+        // and `_id` breaks ties of the sort
+        PageQuery::new(self.page, self.limit).apply(&mut options);
+
         options
     }
 }
 
+impl VehicleTypeScope {
+    /// Vehicle type a list is limited to: the one asked for, else the one the role
+    /// manages (managers see their own type unless they ask for another one or ALL)
+    pub fn resolve(&self, role: &Role) -> Option<VehicleType> {
+        match self.vehicle_type {
+            Some(VehicleTypeChoice::Car) => Some(VehicleType::Car),
+            Some(VehicleTypeChoice::Motorbike) => Some(VehicleType::Motorbike),
+            Some(VehicleTypeChoice::All) => None,
+            None => VehicleType::managed_by(role),
+        }
+    }
+}
+
+impl DeletedScope {
+    /// Whether soft deleted vehicles are returned too, only Admins may ask for them
+    pub fn resolve(&self, role: &Role) -> Result<bool, String> {
+        match (self.include_deleted, role) {
+            (false, _) => Ok(false),
+            (true, Role::Admin) => Ok(true),
+            (true, _) => Err("Only Admins can list deleted vehicles".to_string()),
+        }
+    }
+}
+
+/// Leave the soft deleted vehicles out of a filter, `deleted_at` is missing on the others
+pub fn exclude_deleted(filter: &mut Document) {
+    filter.insert("deleted_at", Bson::Null);
+}
+
 impl VehicleQueryBuilder {
     pub fn build_query(&self) -> (Document, FindOptions) {
-        let filter = self
+        let mut filter = self
             .filters
             .as_ref()
             .map(|f| f.to_bson_filter())
             .unwrap_or_else(|| Document::new());
 
+        // Price range in the request's currency, against prices stored in any currency
+        if let Some(filters) = &self.filters {
+            let currency = self.currency.unwrap_or_default();
+            let price = |amount: Option<f64>| amount.and_then(|a| Money::from_f64(a, currency));
+            let rates = services::currency::rates();
+            services::mongodb::QueryBuilder::new().add_money_range_filter(
+                &mut filter,
+                "price_by_day",
+                price(filters.min_price),
+                price(filters.max_price),
+                |money, to| rates.convert(money, to),
+            );
+        }
+
         let options = self
             .pagination
             .as_ref()
@@ -363,6 +245,22 @@ mod tests {
     use super::*;
     use crate::models::VehicleFilters;
 
+    #[test]
+    fn test_list_query_reads_flattened_parameters() {
+        use crate::models::RankingStrategy;
+        let query = actix_web::web::Query::<VehicleListQuery>::from_query(
+            "type=CAR&rank=newest&currency=USD",
+        )
+        .unwrap()
+        .into_inner();
+        assert_eq!(query.scope.vehicle_type, Some(VehicleTypeChoice::Car));
+        assert_eq!(query.rank.rank, Some(RankingStrategy::Newest));
+        assert_eq!(query.currency.currency, Some(Currency::USD));
+
+        let query = actix_web::web::Query::<VehicleListQuery>::from_query("").unwrap();
+        assert!(query.scope.vehicle_type.is_none() && query.rank.rank.is_none());
+    }
+
     #[test]
     fn test_brand_filter_single() {
         use crate::models::Brand;
@@ -385,6 +283,45 @@ mod tests {
         assert!(doc.contains_key("brand"));
     }
 
+    #[test]
+    fn test_nearby_filter_in_meters() {
+        let filters = VehicleFilters {
+            lat: Some(48.8566),
+            lng: Some(2.3522),
+            ..Default::default()
+        };
+        assert_eq!(
+            filters.to_bson_filter(),
+            bson::doc! { "location": { "$nearSphere": {
+                "$geometry": { "type": "Point", "coordinates": [2.3522, 48.8566] },
+                "$maxDistance": 25_000.0,
+            } } }
+        );
+    }
+
+    #[test]
+    fn test_price_range_in_the_request_currency() {
+        let query_builder = VehicleQueryBuilder {
+            filters: Some(VehicleFilters {
+                max_price: Some(100.0),
+                ..Default::default()
+            }),
+            pagination: None,
+            currency: Some(Currency::EUR),
+        };
+        let (filter, _) = query_builder.build_query();
+
+        // EUR has a rate whatever EXCHANGE_RATES holds
+        let ranges = filter.get_array("$or").unwrap();
+        assert!(ranges.contains(&bson::Bson::Document(bson::doc! {
+            "price_by_day.currency": "EUR",
+            "price_by_day.amount": {
+                "$lte": Money::from_f64(100.0, Currency::EUR).unwrap().to_decimal128(),
+            },
+        })));
+        assert!(!filter.contains_key("price_by_day"));
+    }
+
     #[test]
     fn test_sort_fields_parsing() {
         let pagination = VehiclePagination {
@@ -393,7 +330,77 @@ mod tests {
             sort: Some("price_by_day,-year_of_production,+brand".to_string()),
         };
         let options = pagination.to_find_options();
-        // Test that sort document is created correctly
-        assert!(options.sort.is_some());
+        // Test that sort document is created correctly, `_id` last
+        assert_eq!(
+            options.sort,
+            Some(bson::doc! {
+                "price_by_day.currency": 1,
+                "price_by_day.amount": 1,
+                "year_of_production": -1,
+                "brand": 1,
+                "_id": 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_default_vehicle_type_of_each_role() {
+        let default = VehicleTypeScope::default();
+        assert_eq!(default.resolve(&Role::Admin), None);
+        assert_eq!(default.resolve(&Role::Service), None);
+        assert_eq!(default.resolve(&Role::Customer), None);
+        assert_eq!(default.resolve(&Role::CarManager), Some(VehicleType::Car));
+        assert_eq!(
+            default.resolve(&Role::MotorbikeManager),
+            Some(VehicleType::Motorbike)
+        );
+
+        // An explicit type replaces the default, for every role
+        let all = VehicleTypeScope {
+            vehicle_type: Some(VehicleTypeChoice::All),
+        };
+        assert_eq!(all.resolve(&Role::CarManager), None);
+        let motorbikes = VehicleTypeScope {
+            vehicle_type: Some(VehicleTypeChoice::Motorbike),
+        };
+        assert_eq!(
+            motorbikes.resolve(&Role::CarManager),
+            Some(VehicleType::Motorbike)
+        );
+        assert_eq!(
+            motorbikes.resolve(&Role::Admin),
+            Some(VehicleType::Motorbike)
+        );
+    }
+
+    #[test]
+    fn test_only_admins_include_deleted_vehicles() {
+        assert_eq!(
+            DeletedScope::default().resolve(&Role::CarManager),
+            Ok(false)
+        );
+
+        let deleted = DeletedScope {
+            include_deleted: true,
+        };
+        assert_eq!(deleted.resolve(&Role::Admin), Ok(true));
+        assert!(deleted.resolve(&Role::CarManager).is_err());
+        assert!(deleted.resolve(&Role::Customer).is_err());
+
+        let mut filter = bson::doc! { "type": "CAR" };
+        exclude_deleted(&mut filter);
+        assert_eq!(filter, bson::doc! { "type": "CAR", "deleted_at": null });
+    }
+
+    #[test]
+    fn test_page_size_is_bounded() {
+        let pagination = VehiclePagination {
+            page: Some(2),
+            limit: Some(10_000),
+            sort: None,
+        };
+        let options = pagination.to_find_options();
+        assert_eq!(options.limit, Some(100));
+        assert_eq!(options.skip, Some(100));
     }
 }
@@ -0,0 +1,250 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use validator::Validate;
+
+use crate::models::EventType;
+
+// =============================================================================
+// ENUMS
+// =============================================================================
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeliveryStatus {
+    Pending,   // Waiting for its first or next attempt
+    Delivered, // The endpoint answered 2xx
+    Failed,    // Every attempt failed
+}
+
+// =============================================================================
+// MAIN WEBHOOK STRUCTS
+// =============================================================================
+
+/// Callback URL receiving the signed booking events of its rental company
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub url: String,
+    pub description: Option<String>,
+    /// Events sent to the endpoint, every event when empty
+    #[serde(default)]
+    pub event_types: Vec<EventType>,
+    pub created_by: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    /// Rental company of the endpoint, None receives the events of every company
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+/// One event sent to one endpoint. The body is stored as sent so that retries carry
+/// the same bytes, only the signature timestamp changes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub endpoint_id: ObjectId,
+    pub url: String,
+    pub event_type: EventType,
+    pub booking_id: ObjectId,
+    pub body: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub last_response_status: Option<u16>,
+    pub last_error: Option<String>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    #[serde(
+        default,
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub delivered_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+// =============================================================================
+// REQUEST/RESPONSE STRUCTS
+// =============================================================================
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct CreateWebhookEndpointRequest {
+    /// e.g. "https://partner.example.com/hooks/bookings"
+    #[validate(url, length(max = 2048))]
+    pub url: String,
+    #[validate(length(max = 500))]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub event_types: Vec<EventType>,
+}
+
+/// Outcome of one delivery attempt
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeliveryAttempt {
+    Delivered(u16),
+    Rejected(u16),
+    Unreachable(String),
+}
+
+// =============================================================================
+// IMPLEMENTATIONS
+// =============================================================================
+
+impl crate::services::mongodb::MongoStruct for WebhookEndpoint {
+    fn get_collection() -> &'static str {
+        "webhook_endpoints"
+    }
+}
+
+impl crate::services::mongodb::MongoStruct for WebhookDelivery {
+    fn get_collection() -> &'static str {
+        "webhook_deliveries"
+    }
+}
+
+impl WebhookEndpoint {
+    pub fn new(request: CreateWebhookEndpointRequest, created_by: String) -> Self {
+        Self {
+            id: None,
+            url: request.url,
+            description: request.description,
+            event_types: request.event_types,
+            created_by,
+            created_at: Utc::now(),
+            tenant_id: None,
+        }
+    }
+
+    pub fn subscribes_to(&self, event_type: EventType) -> bool {
+        self.event_types.is_empty() || self.event_types.contains(&event_type)
+    }
+}
+
+impl WebhookDelivery {
+    pub fn new(
+        endpoint: &WebhookEndpoint,
+        event_type: EventType,
+        booking_id: ObjectId,
+        body: String,
+    ) -> Option<Self> {
+        let now = Utc::now();
+        Some(Self {
+            id: None,
+            endpoint_id: endpoint.id?,
+            url: endpoint.url.clone(),
+            event_type,
+            booking_id,
+            body,
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            last_response_status: None,
+            last_error: None,
+            created_at: now,
+            next_attempt_at: Some(now),
+            delivered_at: None,
+            tenant_id: endpoint.tenant_id.clone(),
+        })
+    }
+
+    /// Record an attempt. Failed attempts are retried after 30 seconds, doubling each
+    /// time, until `max_attempts` is reached.
+    pub fn record(&mut self, attempt: DeliveryAttempt, max_attempts: u32, now: DateTime<Utc>) {
+        self.attempts += 1;
+        let (response_status, error) = match attempt {
+            DeliveryAttempt::Delivered(status) => {
+                self.status = DeliveryStatus::Delivered;
+                self.last_response_status = Some(status);
+                self.last_error = None;
+                self.next_attempt_at = None;
+                self.delivered_at = Some(now);
+                return;
+            }
+            DeliveryAttempt::Rejected(status) => {
+                (Some(status), format!("Endpoint answered {}", status))
+            }
+            DeliveryAttempt::Unreachable(error) => (None, error),
+        };
+
+        self.last_response_status = response_status;
+        self.last_error = Some(error);
+        if self.attempts >= max_attempts {
+            self.status = DeliveryStatus::Failed;
+            self.next_attempt_at = None;
+        } else {
+            let backoff = Duration::seconds(30 * 2_i64.pow(self.attempts - 1));
+            self.next_attempt_at = Some(now + backoff);
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delivery() -> WebhookDelivery {
+        let request: CreateWebhookEndpointRequest = serde_json::from_str(
+            r#"{ "url": "https://partner.example.com/hooks", "event_types": ["booking.status_changed"] }"#,
+        )
+        .unwrap();
+        let mut endpoint = WebhookEndpoint::new(request, "admin_user_1".to_string());
+        endpoint.id = Some(ObjectId::new());
+
+        assert!(endpoint.subscribes_to(EventType::BookingStatusChanged));
+        assert!(!endpoint.subscribes_to(EventType::BookingCreated));
+        WebhookDelivery::new(
+            &endpoint,
+            EventType::BookingStatusChanged,
+            ObjectId::new(),
+            "{}".to_string(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_failed_attempts_back_off_then_give_up() {
+        let mut delivery = delivery();
+        let now = Utc::now();
+
+        delivery.record(DeliveryAttempt::Rejected(500), 3, now);
+        assert_eq!(delivery.status, DeliveryStatus::Pending);
+        assert_eq!(delivery.next_attempt_at, Some(now + Duration::seconds(30)));
+
+        delivery.record(DeliveryAttempt::Unreachable("timeout".to_string()), 3, now);
+        assert_eq!(delivery.next_attempt_at, Some(now + Duration::seconds(60)));
+        assert_eq!(delivery.last_response_status, None);
+
+        delivery.record(DeliveryAttempt::Rejected(404), 3, now);
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+        assert_eq!(delivery.next_attempt_at, None);
+        assert_eq!(
+            delivery.last_error.as_deref(),
+            Some("Endpoint answered 404")
+        );
+    }
+
+    #[test]
+    fn test_delivered_on_success() {
+        let mut delivery = delivery();
+        let now = Utc::now();
+
+        delivery.record(DeliveryAttempt::Rejected(503), 5, now);
+        delivery.record(DeliveryAttempt::Delivered(204), 5, now);
+        assert_eq!(delivery.status, DeliveryStatus::Delivered);
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(delivery.delivered_at, Some(now));
+        assert_eq!(delivery.last_error, None);
+    }
+}
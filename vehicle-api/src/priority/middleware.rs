@@ -0,0 +1,27 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    middleware, Error, ResponseError, Result,
+};
+
+use crate::error::AppError;
+
+// Priority Middleware using from_fn, only registered when PRIORITY_LANES=true
+pub async fn priority_middleware(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let class = super::classify(req.method(), req.path());
+
+    // The slot is held until the response is produced
+    match super::acquire(class).await {
+        Some(_slot) => Ok(next.call(req).await?.map_into_left_body()),
+        None => {
+            log::warn!("Shed {} request {} {}", class, req.method(), req.path());
+            let error = AppError::service_unavailable("Server busy, try again shortly", 1);
+            Ok(req
+                .into_response(error.error_response())
+                .map_into_right_body())
+        }
+    }
+}
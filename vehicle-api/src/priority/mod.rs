@@ -0,0 +1,303 @@
+pub mod middleware;
+
+use actix_web::http::Method;
+use std::sync::{LazyLock, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use strum::IntoEnumIterator;
+use tokio::sync::Notify;
+
+use crate::models::{LaneStats, PriorityLanes, TrafficClass};
+
+/// How many requests run at once on this instance and how long the others may wait
+pub struct PriorityPolicy {
+    pub enabled: bool,
+    pub capacity: usize,
+    pub reserved_critical: usize, // Slots only booking-critical requests may take
+    pub queue_limit: usize,       // Waiting requests per class, more are shed
+    pub queue_timeout: Duration,
+}
+
+/// Requests of one class, running or waiting
+#[derive(Clone, Debug, Default)]
+struct Lane {
+    in_flight: usize,
+    queued: usize,
+    max_queued: usize,
+    admitted: u64,
+    shed: u64,
+}
+
+/// Slots and queues of every class
+#[derive(Clone, Debug, Default)]
+pub struct Lanes {
+    lanes: [Lane; 3],
+}
+
+// Read once from PRIORITY_LANES, PRIORITY_MAX_IN_FLIGHT, PRIORITY_RESERVED_CRITICAL,
+// PRIORITY_QUEUE_LIMIT and PRIORITY_QUEUE_TIMEOUT_MS
+pub(crate) static PRIORITY_POLICY: LazyLock<PriorityPolicy> =
+    LazyLock::new(PriorityPolicy::from_env);
+
+// Per instance like the rate limit buckets
+static LANES: LazyLock<Mutex<Lanes>> = LazyLock::new(|| Mutex::new(Lanes::default()));
+
+// Wakes the waiting requests when a slot is freed
+static SLOT_FREED: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+impl PriorityPolicy {
+    /// Disabled unless PRIORITY_LANES=true. 64 requests at once, a quarter of them
+    /// reserved for bookings, 100 waiting per class for at most 2 seconds.
+    pub fn from_env() -> Self {
+        let read = |name: &str, default: usize| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse::<usize>().ok())
+                .unwrap_or(default)
+        };
+        let capacity = read("PRIORITY_MAX_IN_FLIGHT", 64).max(1);
+        Self {
+            enabled: std::env::var("PRIORITY_LANES").as_deref() == Ok("true"),
+            capacity,
+            reserved_critical: read("PRIORITY_RESERVED_CRITICAL", capacity / 4).min(capacity - 1),
+            queue_limit: read("PRIORITY_QUEUE_LIMIT", 100),
+            queue_timeout: Duration::from_millis(read("PRIORITY_QUEUE_TIMEOUT_MS", 2000) as u64),
+        }
+    }
+}
+
+pub fn is_enabled() -> bool {
+    PRIORITY_POLICY.enabled
+}
+
+/// Booking creation and updates are critical, reading the vehicle catalog is browsing
+pub fn classify(method: &Method, path: &str) -> TrafficClass {
+    let Some(path) = path.strip_prefix("/protected") else {
+        return TrafficClass::Standard;
+    };
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match (method, segments.as_slice()) {
+        (&Method::POST, ["bookings"]) | (&Method::PATCH, ["bookings", _]) => TrafficClass::Critical,
+        (&Method::GET, ["vehicles", ..]) => TrafficClass::Browsing,
+        _ => TrafficClass::Standard,
+    }
+}
+
+impl Lanes {
+    fn lane(&self, class: TrafficClass) -> &Lane {
+        &self.lanes[class as usize]
+    }
+
+    fn lane_mut(&mut self, class: TrafficClass) -> &mut Lane {
+        &mut self.lanes[class as usize]
+    }
+
+    fn in_flight(&self) -> usize {
+        self.lanes.iter().map(|lane| lane.in_flight).sum()
+    }
+
+    /// A request may start when a slot is free for its class and no request of a
+    /// higher class is waiting. A new request also lets its own class' queue go first.
+    pub fn can_start(&self, class: TrafficClass, policy: &PriorityPolicy, queued: bool) -> bool {
+        let limit = match class {
+            TrafficClass::Critical => policy.capacity,
+            _ => policy.capacity - policy.reserved_critical,
+        };
+        let mut ahead = TrafficClass::iter()
+            .take_while(|other| *other != class)
+            .chain((!queued).then_some(class));
+        self.in_flight() < limit && ahead.all(|other| self.lane(other).queued == 0)
+    }
+
+    pub fn start(&mut self, class: TrafficClass) {
+        let lane = self.lane_mut(class);
+        lane.in_flight += 1;
+        lane.admitted += 1;
+    }
+
+    pub fn finish(&mut self, class: TrafficClass) {
+        let lane = self.lane_mut(class);
+        lane.in_flight = lane.in_flight.saturating_sub(1);
+    }
+
+    /// Queue a request, false when its class' queue is full and the request is shed
+    pub fn enqueue(&mut self, class: TrafficClass, policy: &PriorityPolicy) -> bool {
+        let lane = self.lane_mut(class);
+        if lane.queued >= policy.queue_limit {
+            lane.shed += 1;
+            return false;
+        }
+        lane.queued += 1;
+        lane.max_queued = lane.max_queued.max(lane.queued);
+        true
+    }
+
+    pub fn dequeue(&mut self, class: TrafficClass) {
+        let lane = self.lane_mut(class);
+        lane.queued = lane.queued.saturating_sub(1);
+    }
+
+    pub fn shed(&mut self, class: TrafficClass) {
+        self.lane_mut(class).shed += 1;
+    }
+
+    pub fn stats(&self) -> Vec<LaneStats> {
+        TrafficClass::iter()
+            .map(|class| {
+                let lane = self.lane(class);
+                LaneStats {
+                    class,
+                    in_flight: lane.in_flight,
+                    queued: lane.queued,
+                    max_queued: lane.max_queued,
+                    admitted: lane.admitted,
+                    shed: lane.shed,
+                }
+            })
+            .collect()
+    }
+}
+
+fn lock_lanes() -> MutexGuard<'static, Lanes> {
+    LANES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Slot held by a running request, freed when dropped
+pub struct Slot(TrafficClass);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        lock_lanes().finish(self.0);
+        SLOT_FREED.notify_waiters();
+    }
+}
+
+/// Place in a queue, left when dropped (admitted, timed out or client gone)
+struct Queued(TrafficClass);
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        lock_lanes().dequeue(self.0);
+        // Lower classes may be waiting on this one
+        SLOT_FREED.notify_waiters();
+    }
+}
+
+/// Wait for a slot for a request of this class, None when the request is shed
+pub async fn acquire(class: TrafficClass) -> Option<Slot> {
+    let policy = &*PRIORITY_POLICY;
+    {
+        let mut lanes = lock_lanes();
+        if lanes.can_start(class, policy, false) {
+            lanes.start(class);
+            return Some(Slot(class));
+        }
+        if !lanes.enqueue(class, policy) {
+            return None;
+        }
+    }
+    let _queued = Queued(class);
+
+    let deadline = tokio::time::Instant::now() + policy.queue_timeout;
+    loop {
+        let notified = SLOT_FREED.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        {
+            let mut lanes = lock_lanes();
+            if lanes.can_start(class, policy, true) {
+                lanes.start(class);
+                return Some(Slot(class));
+            }
+        }
+        if tokio::time::timeout_at(deadline, notified).await.is_err() {
+            lock_lanes().shed(class);
+            return None;
+        }
+    }
+}
+
+/// Configuration and load of the lanes of this instance
+pub fn stats() -> PriorityLanes {
+    let policy = &*PRIORITY_POLICY;
+    PriorityLanes {
+        enabled: policy.enabled,
+        capacity: policy.capacity,
+        reserved_critical: policy.reserved_critical,
+        lanes: lock_lanes().stats(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> PriorityPolicy {
+        PriorityPolicy {
+            enabled: true,
+            capacity: 4,
+            reserved_critical: 1,
+            queue_limit: 2,
+            queue_timeout: Duration::from_millis(100),
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            classify(&Method::POST, "/protected/bookings"),
+            TrafficClass::Critical
+        );
+        assert_eq!(
+            classify(
+                &Method::PATCH,
+                "/protected/bookings/66b1f0c2a9e4d3b2c1a09876"
+            ),
+            TrafficClass::Critical
+        );
+        assert_eq!(
+            classify(&Method::GET, "/protected/vehicles/search"),
+            TrafficClass::Browsing
+        );
+        assert_eq!(
+            classify(&Method::GET, "/protected/bookings"),
+            TrafficClass::Standard
+        );
+        assert_eq!(
+            classify(
+                &Method::POST,
+                "/protected/bookings/66b1f0c2a9e4d3b2c1a09876/condition-photos"
+            ),
+            TrafficClass::Standard
+        );
+    }
+
+    #[test]
+    fn test_critical_requests_go_first() {
+        let policy = policy();
+        let mut lanes = Lanes::default();
+        for _ in 0..3 {
+            assert!(lanes.can_start(TrafficClass::Browsing, &policy, false));
+            lanes.start(TrafficClass::Browsing);
+        }
+
+        // The last slot is kept for bookings
+        assert!(!lanes.can_start(TrafficClass::Standard, &policy, false));
+        assert!(lanes.can_start(TrafficClass::Critical, &policy, false));
+        lanes.start(TrafficClass::Critical);
+        assert!(!lanes.can_start(TrafficClass::Critical, &policy, false));
+
+        // Waiting bookings take the next free slot before the catalog
+        assert!(lanes.enqueue(TrafficClass::Browsing, &policy));
+        assert!(lanes.enqueue(TrafficClass::Browsing, &policy));
+        assert!(!lanes.enqueue(TrafficClass::Browsing, &policy));
+        assert!(lanes.enqueue(TrafficClass::Critical, &policy));
+        lanes.finish(TrafficClass::Browsing);
+        lanes.finish(TrafficClass::Browsing);
+        assert!(!lanes.can_start(TrafficClass::Browsing, &policy, true));
+        assert!(lanes.can_start(TrafficClass::Critical, &policy, true));
+
+        let browsing = &lanes.stats()[TrafficClass::Browsing as usize];
+        assert_eq!((browsing.in_flight, browsing.queued), (1, 2));
+        assert_eq!((browsing.admitted, browsing.shed), (3, 1));
+    }
+}
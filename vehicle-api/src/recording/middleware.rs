@@ -0,0 +1,88 @@
+use actix_web::{
+    body::{self, EitherBody, MessageBody},
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    error::ErrorInternalServerError,
+    http::header::{HeaderName, HeaderValue},
+    middleware, web, Error, HttpMessage, Result,
+};
+use bson::oid::ObjectId;
+
+use super::REQUEST_ID_HEADER;
+use crate::authentication::identity::Identity;
+use crate::models::Recording;
+use crate::services;
+
+// Recording Middleware using from_fn, must run after api_key_auth_middleware
+pub async fn recording_middleware(
+    mut req: ServiceRequest,
+    next: middleware::Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody, web::Bytes>>, Error> {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| ObjectId::new().to_hex());
+    let identity = req.extensions().get::<Identity>().cloned();
+
+    let targets = super::get_targets();
+    let identity = match identity {
+        Some(identity)
+            if !targets.is_empty() && targets.matches(&identity.user_id, &request_id) =>
+        {
+            identity
+        }
+        _ => {
+            let mut res = next.call(req).await?;
+            insert_request_id(&mut res, &request_id);
+            return Ok(res.map_into_left_body());
+        }
+    };
+
+    // Buffer the request body so it can be stored and still reach the handler
+    let request_bytes = req.extract::<web::Bytes>().await?;
+    req.set_payload(Payload::from(request_bytes.clone()));
+
+    let method = req.method().to_string();
+    let path = req.path().to_string();
+    let query = req.query_string().to_string();
+
+    let res = next.call(req).await?;
+    let status = res.status().as_u16();
+    let (http_req, http_res) = res.into_parts();
+    let (http_res, response_body) = http_res.into_parts();
+    let response_bytes = body::to_bytes(response_body)
+        .await
+        .map_err(|_| ErrorInternalServerError("Failed to read response body"))?;
+
+    let recording = Recording {
+        id: None,
+        request_id: request_id.clone(),
+        user_id: identity.user_id,
+        role: identity.role,
+        tenant_id: identity.tenant_id,
+        method,
+        path,
+        query,
+        request_body: Recording::sanitize_body(&request_bytes),
+        status,
+        response_body: Recording::sanitize_body(&response_bytes),
+        recorded_at: chrono::Utc::now(),
+    };
+
+    // A failing recording must never fail the request itself
+    if let Err(error) = services::mongodb::insert_one(&recording, None).await {
+        log::error!("Failed to store recording {}: {}", request_id, error);
+    }
+
+    let mut res = ServiceResponse::new(http_req, http_res.set_body(response_bytes));
+    insert_request_id(&mut res, &request_id);
+    Ok(res.map_into_right_body())
+}
+
+fn insert_request_id<B>(res: &mut ServiceResponse<B>, request_id: &str) {
+    if let Ok(value) = HeaderValue::from_str(request_id) {
+        res.headers_mut()
+            .insert(HeaderName::from_static("x-request-id"), value);
+    }
+}
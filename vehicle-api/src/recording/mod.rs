@@ -0,0 +1,24 @@
+pub mod middleware;
+
+use std::sync::RwLock;
+
+use crate::models::RecordingTargets;
+
+/// Header carrying the request ID, generated when the client does not send one
+pub const REQUEST_ID_HEADER: &str = "X-Request-ID";
+
+// Users and request IDs to record, changed through the admin endpoint
+static RECORDING_TARGETS: RwLock<RecordingTargets> = RwLock::new(RecordingTargets::empty());
+
+pub fn get_targets() -> RecordingTargets {
+    RECORDING_TARGETS
+        .read()
+        .map(|targets| targets.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_targets(targets: RecordingTargets) {
+    if let Ok(mut current) = RECORDING_TARGETS.write() {
+        *current = targets;
+    }
+}
@@ -0,0 +1,59 @@
+use actix_web::web::ReqData;
+use actix_web::{get, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+use crate::models::CreateExportRequest;
+use crate::util;
+use crate::util::pagination::PageQuery;
+
+/// POST /accounting/exports - Export the ledger entries of a period as CSV, JSON or DATEV
+/// to the storage, produced in the background (Admin only)
+#[post("/accounting/exports")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn create(
+    identity: ReqData<Identity>,
+    web::Json(request): web::Json<CreateExportRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::accounting::create(&identity, request).await;
+
+    match result {
+        Ok(export) => Ok(HttpResponse::Accepted().json(util::util_serde::to_value(export))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /accounting/exports - Exports with their status, newest first (Admin only)
+#[get("/accounting/exports")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(web::Query(page): web::Query<PageQuery>) -> Result<HttpResponse, AppError> {
+    let result = controllers::accounting::list(page).await;
+
+    match result {
+        Ok(exports) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(exports))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /accounting/exports/{export_id} - Status and location of an export (Admin only)
+#[get("/accounting/exports/{export_id}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn get(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+    let export_id = ObjectId::parse_str(path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid export ID format"))?;
+
+    let result = controllers::accounting::get(&export_id).await;
+
+    match result {
+        Ok(export) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(export))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(create).service(list).service(get);
+}
@@ -0,0 +1,60 @@
+use actix_web::web::ReqData;
+use actix_web::{get, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::error::AppError;
+use crate::models::AnomalyFilters;
+use crate::util::pagination::PageQuery;
+use crate::{controllers, util};
+
+/// GET /anomalies?kind=BOOKING_SPIKE&include_acknowledged=true - Detected anomalies (Admin only)
+#[get("/anomalies")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(
+    web::Query(filters): web::Query<AnomalyFilters>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::anomaly::list(filters, page).await;
+
+    match result {
+        Ok(anomalies) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(anomalies))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /anomalies/scan - Run the anomaly detection job now (Admin only)
+#[post("/anomalies/scan")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn scan() -> Result<HttpResponse, AppError> {
+    let result = controllers::anomaly::scan().await;
+
+    match result {
+        Ok(anomalies) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(anomalies))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /anomalies/{anomaly_id}/acknowledge - Close an anomaly (Admin only)
+#[post("/anomalies/{anomaly_id}/acknowledge")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn acknowledge(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let anomaly_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid anomaly ID format"))?;
+
+    let result = controllers::anomaly::acknowledge(&identity, &anomaly_id).await;
+
+    match result {
+        Ok(anomaly) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(anomaly))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(list).service(scan).service(acknowledge);
+}
@@ -0,0 +1,150 @@
+use actix_web::web::ReqData;
+use actix_web::{delete, get, post, put, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+use crate::models::{
+    ApiKeyResponse, CreateApiKeyRequest, CreatedApiKeyResponse, RevokeApiKeyRequest,
+    RotateApiKeyRequest, UpdateAllowedNetworksRequest,
+};
+use crate::util::pagination::PageQuery;
+use crate::{util, validator};
+
+/// POST /api-keys - Create an API key, the plain key is only returned here (Admin only)
+#[post("/api-keys")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn create(
+    identity: ReqData<Identity>,
+    request: validator::Json<CreateApiKeyRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::api_key::create(&identity, request.into_inner()).await;
+
+    match result {
+        Ok((api_key, key)) => Ok(HttpResponse::Created().json(CreatedApiKeyResponse {
+            key,
+            api_key: api_key.into(),
+        })),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /api-keys - List API keys without their secret (Admin only)
+#[get("/api-keys")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(web::Query(page): web::Query<PageQuery>) -> Result<HttpResponse, AppError> {
+    let result = controllers::api_key::list(page).await;
+
+    match result {
+        Ok(api_keys) => Ok(HttpResponse::Ok().json(
+            api_keys
+                .into_iter()
+                .map(ApiKeyResponse::from)
+                .collect::<Vec<_>>(),
+        )),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /api-keys/revocations - Blacklist a leaked key by value or hash (Admin only)
+#[post("/api-keys/revocations")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn blacklist(
+    identity: ReqData<Identity>,
+    request: validator::Json<RevokeApiKeyRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::api_key::blacklist(&identity, request.into_inner()).await;
+
+    match result {
+        Ok(revocation) => Ok(HttpResponse::Created().json(util::util_serde::to_value(revocation))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /api-keys/revocations - Revocation list, newest first (Admin only)
+#[get("/api-keys/revocations")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list_revocations(
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::api_key::list_revocations(page).await;
+
+    match result {
+        Ok(revocations) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(revocations))),
+        Err(error) => Err(error),
+    }
+}
+
+/// DELETE /api-keys/{api_key_id} - Revoke an API key (Admin only)
+#[delete("/api-keys/{api_key_id}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn revoke(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let api_key_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid API key ID format"))?;
+
+    let result = controllers::api_key::revoke(&identity, &api_key_id).await;
+
+    match result {
+        Ok(api_key) => Ok(HttpResponse::Ok().json(ApiKeyResponse::from(api_key))),
+        Err(error) => Err(error),
+    }
+}
+
+/// PUT /api-keys/{api_key_id}/allowed-networks - Restrict a key to networks (Admin only)
+#[put("/api-keys/{api_key_id}/allowed-networks")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn update_allowed_networks(
+    path: web::Path<String>,
+    request: validator::Json<UpdateAllowedNetworksRequest>,
+) -> Result<HttpResponse, AppError> {
+    let api_key_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid API key ID format"))?;
+
+    let result =
+        controllers::api_key::update_allowed_networks(&api_key_id, request.into_inner()).await;
+
+    match result {
+        Ok(api_key) => Ok(HttpResponse::Ok().json(ApiKeyResponse::from(api_key))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /api-keys/{api_key_id}/rotate - Issue a replacement key, the old one expires
+/// after the grace period (Admin only)
+#[post("/api-keys/{api_key_id}/rotate")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn rotate(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    request: validator::Json<RotateApiKeyRequest>,
+) -> Result<HttpResponse, AppError> {
+    let api_key_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid API key ID format"))?;
+
+    let result = controllers::api_key::rotate(&identity, &api_key_id, request.into_inner()).await;
+
+    match result {
+        Ok((api_key, key)) => Ok(HttpResponse::Created().json(CreatedApiKeyResponse {
+            key,
+            api_key: api_key.into(),
+        })),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(create)
+        .service(list)
+        .service(blacklist)
+        .service(list_revocations)
+        .service(revoke)
+        .service(update_allowed_networks)
+        .service(rotate);
+}
@@ -0,0 +1,103 @@
+use actix_web::web::ReqData;
+use actix_web::{get, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::permission::Permission;
+use crate::error::AppError;
+use crate::models::{ApprovalMetricsQuery, BookingStatus, RejectBookingRequest};
+use crate::{controllers, util};
+
+/// GET /approvals - Pending bookings of the managed vehicles, oldest first, with SLA flags
+#[get("/approvals")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn list(identity: ReqData<Identity>) -> Result<HttpResponse, AppError> {
+    let result = controllers::approval::list(&identity).await;
+
+    match result {
+        Ok(items) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(items))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /approvals/metrics?days=30 - Approval queue metrics
+#[get("/approvals/metrics")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn metrics(
+    identity: ReqData<Identity>,
+    web::Query(query): web::Query<ApprovalMetricsQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::approval::metrics(&identity, query).await;
+
+    match result {
+        Ok(metrics) => Ok(HttpResponse::Ok().json(metrics)),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /approvals/{booking_id}/approve - Confirm a pending booking
+#[post("/approvals/{booking_id}/approve")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn approve(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result =
+        controllers::approval::decide(&identity, &booking_id, BookingStatus::Confirmed).await;
+
+    match result {
+        Ok(booking) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(booking))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /approvals/{booking_id}/reject - Reject a pending booking with a reason
+#[post("/approvals/{booking_id}/reject")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn reject(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    web::Json(request): web::Json<RejectBookingRequest>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+    if request.reason.trim().is_empty() {
+        return Err(AppError::bad_request("A rejection reason is required"));
+    }
+
+    let result = controllers::approval::decide(
+        &identity,
+        &booking_id,
+        BookingStatus::Rejected(request.reason),
+    )
+    .await;
+
+    match result {
+        Ok(booking) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(booking))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(list)
+        .service(metrics)
+        .service(approve)
+        .service(reject);
+}
@@ -0,0 +1,38 @@
+use actix_web::{get, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+
+use crate::authentication::identity::Role;
+use crate::error::AppError;
+use crate::models::AuthAuditFilters;
+use crate::util::pagination::PageQuery;
+use crate::{controllers, util};
+
+/// GET /audit/auth?outcome=FAILURE&ip=...&from=...&page=2 - Authentication attempts (Admin only)
+#[get("/audit/auth")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list_auth(
+    web::Query(filters): web::Query<AuthAuditFilters>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::audit::list_auth(filters).await;
+
+    match result {
+        Ok(page) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(page))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /audit/actions?page=&limit= - Irreversible admin actions such as deletions (Admin only)
+#[get("/audit/actions")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list_actions(web::Query(page): web::Query<PageQuery>) -> Result<HttpResponse, AppError> {
+    let result = controllers::audit::list_actions(page).await;
+
+    match result {
+        Ok(entries) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(entries))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(list_auth).service(list_actions);
+}
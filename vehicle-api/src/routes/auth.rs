@@ -0,0 +1,73 @@
+use actix_web::{get, http::header, post, web, HttpResponse, Result};
+
+use crate::controllers;
+use crate::error::AppError;
+use crate::models::{OidcCallbackQuery, RefreshTokenRequest, ServiceTokenRequest};
+
+/// GET /auth/login - Redirect to the OIDC provider login page (Public)
+#[get("/auth/login")]
+async fn login() -> Result<HttpResponse, AppError> {
+    let result = controllers::auth::login().await;
+
+    match result {
+        Ok(url) => Ok(HttpResponse::Found()
+            .insert_header((header::LOCATION, url))
+            .finish()),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /auth/callback - Exchange the authorization code for a session token (Public)
+#[get("/auth/callback")]
+async fn callback(
+    web::Query(query): web::Query<OidcCallbackQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::auth::callback(query).await;
+
+    match result {
+        Ok(session) => Ok(HttpResponse::Ok().json(session)),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /auth/refresh - Exchange a refresh token for a new session token (Public)
+#[post("/auth/refresh")]
+async fn refresh(request: web::Json<RefreshTokenRequest>) -> Result<HttpResponse, AppError> {
+    let result = controllers::auth::refresh(request.into_inner()).await;
+
+    match result {
+        Ok(session) => Ok(HttpResponse::Ok().json(session)),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /auth/revoke - Revoke a refresh token and the tokens rotated from it (Public)
+#[post("/auth/revoke")]
+async fn revoke(request: web::Json<RefreshTokenRequest>) -> Result<HttpResponse, AppError> {
+    let result = controllers::auth::revoke(request.into_inner()).await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /auth/service-token - Exchange service account credentials for a token (Public)
+#[post("/auth/service-token")]
+async fn service_token(request: web::Json<ServiceTokenRequest>) -> Result<HttpResponse, AppError> {
+    let result = controllers::auth::service_token(request.into_inner()).await;
+
+    match result {
+        Ok(token) => Ok(HttpResponse::Ok().json(token)),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(login)
+        .service(callback)
+        .service(refresh)
+        .service(revoke)
+        .service(service_token);
+}
@@ -0,0 +1,84 @@
+use actix_web::web::ReqData;
+use actix_web::{get, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+use crate::util;
+use crate::util::pagination::PageQuery;
+
+fn parse_backup_id(path: web::Path<String>) -> Result<ObjectId, AppError> {
+    ObjectId::parse_str(path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid backup ID format"))
+}
+
+/// POST /admin/backups - Dump every collection to the storage, in the background (Admin only)
+#[post("/admin/backups")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn create(identity: ReqData<Identity>) -> Result<HttpResponse, AppError> {
+    let result = controllers::backup::create(&identity).await;
+
+    match result {
+        Ok(backup) => Ok(HttpResponse::Accepted().json(util::util_serde::to_value(backup))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /admin/backups - Backups with their status, newest first (Admin only)
+#[get("/admin/backups")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(
+    identity: ReqData<Identity>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::backup::list(&identity, page).await;
+
+    match result {
+        Ok(backups) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(backups))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /admin/backups/{backup_id} - Status and files of a backup (Admin only)
+#[get("/admin/backups/{backup_id}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn get(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let backup_id = parse_backup_id(path)?;
+    let result = controllers::backup::get(&identity, &backup_id).await;
+
+    match result {
+        Ok(backup) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(backup))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /admin/backups/{backup_id}/restore-dry-run - Read a backup back and report what a
+/// restore would do, nothing is written (Admin only)
+#[post("/admin/backups/{backup_id}/restore-dry-run")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn restore_dry_run(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let backup_id = parse_backup_id(path)?;
+    let result = controllers::backup::restore_dry_run(&identity, &backup_id).await;
+
+    match result {
+        Ok(report) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(report))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(create)
+        .service(list)
+        .service(get)
+        .service(restore_dry_run);
+}
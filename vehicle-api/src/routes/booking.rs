@@ -1,35 +1,100 @@
+use actix_web::http::StatusCode;
 use actix_web::web::ReqData;
-use actix_web::{get, patch, post, web, HttpResponse, Result};
+use actix_web::{delete, get, patch, post, web, HttpRequest, HttpResponse, Result};
 use actix_web_grants::proc_macro::protect;
 use bson::oid::ObjectId;
 
 use crate::authentication::identity::Identity;
 use crate::authentication::identity::Role;
+use crate::authentication::permission::Permission;
 use crate::error::AppError;
-use crate::models::{CreateBookingRequest, UpdateBookingRequest};
-use crate::{controllers, util};
+use crate::models::{
+    AddConditionPhotosRequest, AnnotatePhotoRequest, BulkUpdateBookingRequest, ConditionStage,
+    CreateBookingRequest, CurrencyQuery, ExtendBookingRequest, HandoverRequest,
+    RejectExtensionRequest, UpdateBookingRequest, VehicleTypeScope,
+};
+use crate::util::pagination::PageQuery;
+use crate::{controllers, services, util, validator};
 
-/// POST /bookings - Create a new booking (Customer only)
+/// POST /bookings - Create a new booking (Customer only), `Idempotency-Key` makes retries safe
 #[post("/bookings")]
-#[protect("Role::Customer", ty = "crate::authentication::identity::Role")]
+#[protect(
+    "Permission::BookingCreate",
+    ty = "crate::authentication::permission::Permission"
+)]
 async fn create(
+    req: HttpRequest,
     identity: ReqData<Identity>,
     web::Json(request): web::Json<CreateBookingRequest>,
 ) -> Result<HttpResponse, AppError> {
-    let result = controllers::booking::create(&identity, request).await;
+    // Retries sent with the same Idempotency-Key get the response of the first request
+    let idempotency_key = services::idempotency::key(&req)?;
+    if let Some(key) = &idempotency_key {
+        if let Some(replay) = services::idempotency::begin(&identity.user_id, key, &request).await?
+        {
+            let booking_id = ObjectId::parse_str(&replay.resource_id)
+                .map_err(|_| AppError::internal_server_error("Invalid replayed booking ID"))?;
+            let booking = controllers::booking::get(&identity, &booking_id)
+                .await?
+                .ok_or_else(|| AppError::not_found("Booking not found"))?;
+            return Ok(replay.respond(util::util_serde::to_value(booking)));
+        }
+    }
+
+    let client_country = services::risk::client_country(&req);
+    let result = controllers::booking::create(&identity, request, client_country).await;
+
+    match result {
+        Ok(booking) => {
+            if let (Some(key), Some(booking_id)) = (&idempotency_key, booking.id) {
+                services::idempotency::complete(
+                    &identity.user_id,
+                    key,
+                    StatusCode::CREATED,
+                    &booking_id.to_hex(),
+                )
+                .await;
+            }
+            Ok(HttpResponse::Created().json(util::util_serde::to_value(booking)))
+        }
+        Err(error) => {
+            if let Some(key) = &idempotency_key {
+                services::idempotency::release(&identity.user_id, key).await;
+            }
+            Err(error)
+        }
+    }
+}
+
+/// POST /bookings/validate - Report every problem of a prospective booking (Customer only)
+#[post("/bookings/validate")]
+#[protect(
+    "Permission::BookingCreate",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn validate(
+    identity: ReqData<Identity>,
+    web::Json(request): web::Json<CreateBookingRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::booking::validate(&identity, request).await;
 
     match result {
-        Ok(booking) => Ok(HttpResponse::Created().json(util::util_serde::to_value(booking))),
+        Ok(report) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(report))),
         Err(error) => Err(error),
     }
 }
 
-/// GET /bookings - List bookings (simplified)
+/// GET /bookings?page=&limit=&type=&currency= - List bookings (simplified)
 /// Customer: only sees their own bookings
-/// Admin/Managers: can view all bookings
+/// Admin/Managers: can view all bookings, managers see their vehicle type by default
 #[get("/bookings")]
-async fn list(identity: ReqData<Identity>) -> Result<HttpResponse, AppError> {
-    let result = controllers::booking::list(&identity).await;
+async fn list(
+    identity: ReqData<Identity>,
+    web::Query(scope): web::Query<VehicleTypeScope>,
+    web::Query(page): web::Query<PageQuery>,
+    web::Query(currency): web::Query<CurrencyQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::booking::list(&identity, scope, page, currency).await;
 
     match result {
         Ok(bookings) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(bookings))),
@@ -37,6 +102,40 @@ async fn list(identity: ReqData<Identity>) -> Result<HttpResponse, AppError> {
     }
 }
 
+/// GET /bookings/stats?type= - Counts by status, per vehicle and per month, average length
+/// (Admin, CarManager, MotorbikeManager), managers see their vehicle type by default
+#[get("/bookings/stats")]
+#[protect(
+    any("Role::Admin", "Role::CarManager", "Role::MotorbikeManager"),
+    ty = "crate::authentication::identity::Role"
+)]
+async fn stats(
+    identity: ReqData<Identity>,
+    web::Query(scope): web::Query<VehicleTypeScope>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::booking::stats(&identity, scope).await;
+
+    match result {
+        Ok(stats) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(stats))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /bookings/export.ics - Confirmed bookings of the caller as an iCalendar feed
+/// Calendar apps that cannot send headers pass the API key as `?api_key=`
+#[get("/bookings/export.ics")]
+async fn export_calendar(identity: ReqData<Identity>) -> Result<HttpResponse, AppError> {
+    let result = controllers::booking::export_calendar(&identity).await;
+
+    match result {
+        Ok(calendar) => Ok(HttpResponse::Ok()
+            .content_type("text/calendar; charset=utf-8")
+            .insert_header(("Content-Disposition", "inline; filename=\"bookings.ics\""))
+            .body(calendar)),
+        Err(error) => Err(error),
+    }
+}
+
 /// PATCH /bookings/{booking_id} - Update a booking (Admin, CarManager, MotorbikeManager, Customer for own bookings)
 #[patch("/bookings/{booking_id}")]
 async fn update(
@@ -55,6 +154,98 @@ async fn update(
     }
 }
 
+/// POST /bookings/{booking_id}/check-in - Vehicle handed over, with its odometer and fuel level (Admin, CarManager, MotorbikeManager)
+#[post("/bookings/{booking_id}/check-in")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn check_in(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    request: web::Json<HandoverRequest>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result = controllers::booking::handover(
+        &identity,
+        &booking_id,
+        ConditionStage::CheckIn,
+        request.into_inner(),
+    )
+    .await;
+
+    match result {
+        Ok(booking) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(booking))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /bookings/{booking_id}/check-out - Vehicle returned, with its odometer and fuel level (Admin, CarManager, MotorbikeManager)
+#[post("/bookings/{booking_id}/check-out")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn check_out(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    request: web::Json<HandoverRequest>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result = controllers::booking::handover(
+        &identity,
+        &booking_id,
+        ConditionStage::CheckOut,
+        request.into_inner(),
+    )
+    .await;
+
+    match result {
+        Ok(booking) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(booking))),
+        Err(error) => Err(error),
+    }
+}
+
+/// PATCH /bookings/bulk - Same status for many bookings, with a report per booking (Admin, CarManager, MotorbikeManager)
+#[patch("/bookings/bulk")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn bulk_update(
+    identity: ReqData<Identity>,
+    request: validator::Json<BulkUpdateBookingRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::booking::bulk_update(&identity, request.into_inner()).await;
+
+    match result {
+        Ok(report) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(report))),
+        Err(error) => Err(error),
+    }
+}
+
+/// DELETE /bookings/{booking_id} - Permanently delete a cancelled or rejected booking (Admin only)
+#[delete("/bookings/{booking_id}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn delete(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result = controllers::booking::delete(&identity, &booking_id).await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(error) => Err(error),
+    }
+}
+
 /// GET /bookings/{booking_id} - Get a single booking
 /// Customer: only their own bookings
 /// Admin/Managers: any booking
@@ -76,10 +267,239 @@ async fn get(
     }
 }
 
+/// GET /bookings/{booking_id}/risk - Fraud risk assessment of a booking (Admin, CarManager, MotorbikeManager)
+#[get("/bookings/{booking_id}/risk")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn risk(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result = controllers::booking::risk(&booking_id).await;
+
+    match result {
+        Ok(assessment) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(assessment))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /bookings/{booking_id}/ledger - Charges, refunds, fees and deposits of a booking
+/// with the running balance (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+#[get("/bookings/{booking_id}/ledger")]
+async fn ledger(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result = controllers::booking::ledger(&identity, &booking_id).await;
+
+    match result {
+        Ok(ledger) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(ledger))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /bookings/{booking_id}/condition-photos - Record check-in or check-out photos
+/// of a confirmed booking (Admin, CarManager, MotorbikeManager)
+#[post("/bookings/{booking_id}/condition-photos")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn add_condition_photos(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    request: validator::Json<AddConditionPhotosRequest>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result =
+        controllers::condition::add_photos(&identity, &booking_id, request.into_inner()).await;
+
+    match result {
+        Ok(photos) => Ok(HttpResponse::Created().json(util::util_serde::to_value(photos))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /bookings/{booking_id}/condition-photos/{photo_id}/annotations - Annotate a photo
+/// (Admin, CarManager, MotorbikeManager)
+#[post("/bookings/{booking_id}/condition-photos/{photo_id}/annotations")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn annotate_condition_photo(
+    identity: ReqData<Identity>,
+    path: web::Path<(String, String)>,
+    request: validator::Json<AnnotatePhotoRequest>,
+) -> Result<HttpResponse, AppError> {
+    let (booking_id, photo_id) = path.into_inner();
+    let booking_id = ObjectId::parse_str(&booking_id)
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+    let photo_id = ObjectId::parse_str(&photo_id)
+        .map_err(|_| AppError::bad_request("Invalid photo ID format"))?;
+
+    let result =
+        controllers::condition::annotate(&identity, &booking_id, &photo_id, request.into_inner())
+            .await;
+
+    match result {
+        Ok(photo) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(photo))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /bookings/{booking_id}/condition-diff - Check-in and check-out photos paired by
+/// angle with their annotations (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+#[get("/bookings/{booking_id}/condition-diff")]
+async fn condition_diff(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result = controllers::condition::diff(&identity, &booking_id).await;
+
+    match result {
+        Ok(diff) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(diff))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /bookings/{booking_id}/extend - Ask to keep the vehicle until a later to_date, the
+/// extra days are held until a manager decides (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+#[post("/bookings/{booking_id}/extend")]
+async fn extend(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    request: validator::Json<ExtendBookingRequest>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result =
+        controllers::extension::request(&identity, &booking_id, request.into_inner()).await;
+
+    match result {
+        Ok(extension) => Ok(HttpResponse::Created().json(util::util_serde::to_value(extension))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /bookings/{booking_id}/extensions - Extensions of a booking, newest first
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+#[get("/bookings/{booking_id}/extensions")]
+async fn list_extensions(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result = controllers::extension::list(&identity, &booking_id).await;
+
+    match result {
+        Ok(extensions) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(extensions))),
+        Err(error) => Err(error),
+    }
+}
+
+fn parse_extension_path(
+    path: web::Path<(String, String)>,
+) -> Result<(ObjectId, ObjectId), AppError> {
+    let (booking_id, extension_id) = path.into_inner();
+    let booking_id = ObjectId::parse_str(&booking_id)
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+    let extension_id = ObjectId::parse_str(&extension_id)
+        .map_err(|_| AppError::bad_request("Invalid extension ID format"))?;
+    Ok((booking_id, extension_id))
+}
+
+/// POST /bookings/{booking_id}/extensions/{extension_id}/approve - Extend the booking,
+/// priced again, and charge the extra days (Admin, CarManager, MotorbikeManager)
+#[post("/bookings/{booking_id}/extensions/{extension_id}/approve")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn approve_extension(
+    identity: ReqData<Identity>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (booking_id, extension_id) = parse_extension_path(path)?;
+
+    let result = controllers::extension::approve(&identity, &booking_id, &extension_id).await;
+
+    match result {
+        Ok(extension) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(extension))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /bookings/{booking_id}/extensions/{extension_id}/reject - Refuse an extension,
+/// its days are free again (Admin, CarManager, MotorbikeManager)
+#[post("/bookings/{booking_id}/extensions/{extension_id}/reject")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn reject_extension(
+    identity: ReqData<Identity>,
+    path: web::Path<(String, String)>,
+    request: validator::Json<RejectExtensionRequest>,
+) -> Result<HttpResponse, AppError> {
+    let (booking_id, extension_id) = parse_extension_path(path)?;
+
+    let result =
+        controllers::extension::reject(&identity, &booking_id, &extension_id, request.into_inner())
+            .await;
+
+    match result {
+        Ok(extension) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(extension))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /cancellation-policy - Fees kept when a customer cancels a confirmed booking
+#[get("/cancellation-policy")]
+async fn cancellation_policy() -> Result<HttpResponse, AppError> {
+    let result = controllers::booking::cancellation_policy().await;
+
+    match result {
+        Ok(policy) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(policy))),
+        Err(error) => Err(error),
+    }
+}
+
 pub fn configure(config: &mut web::ServiceConfig) {
     config
         .service(create)
+        .service(validate)
         .service(list)
+        .service(export_calendar)
+        // Before /bookings/{booking_id}, which would take "stats" or "bulk" for an ID
+        .service(stats)
+        .service(bulk_update)
         .service(update)
-        .service(get);
+        .service(delete)
+        .service(get)
+        .service(risk)
+        .service(ledger)
+        .service(check_in)
+        .service(check_out)
+        .service(add_condition_photos)
+        .service(annotate_condition_photo)
+        .service(condition_diff)
+        .service(extend)
+        .service(list_extensions)
+        .service(approve_extension)
+        .service(reject_extension)
+        .service(cancellation_policy);
 }
@@ -0,0 +1,61 @@
+use actix_web::{delete, get, put, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use std::net::IpAddr;
+
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+use crate::models::BotOverrideRequest;
+
+fn parse_ip(path: web::Path<String>) -> Result<IpAddr, AppError> {
+    path.into_inner()
+        .parse()
+        .map_err(|_| AppError::bad_request("Invalid IP address"))
+}
+
+/// GET /bot-clients - Anonymous clients flagged as bots on this instance (Admin only)
+#[get("/bot-clients")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list() -> Result<HttpResponse, AppError> {
+    let result = controllers::bot::list().await;
+
+    match result {
+        Ok(clients) => Ok(HttpResponse::Ok().json(clients)),
+        Err(error) => Err(error),
+    }
+}
+
+/// PUT /bot-clients/{ip}/override - Allow or block a client whatever its score (Admin only)
+#[put("/bot-clients/{ip}/override")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn set_override(
+    path: web::Path<String>,
+    web::Json(request): web::Json<BotOverrideRequest>,
+) -> Result<HttpResponse, AppError> {
+    let ip = parse_ip(path)?;
+
+    let result = controllers::bot::set_override(ip, request.action).await;
+
+    match result {
+        Ok(client) => Ok(HttpResponse::Ok().json(client)),
+        Err(error) => Err(error),
+    }
+}
+
+/// DELETE /bot-clients/{ip} - Forget a client and its override (Admin only)
+#[delete("/bot-clients/{ip}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn clear(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+    let ip = parse_ip(path)?;
+
+    let result = controllers::bot::clear(ip).await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(list).service(set_override).service(clear);
+}
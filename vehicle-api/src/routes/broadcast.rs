@@ -0,0 +1,87 @@
+use actix_web::web::ReqData;
+use actix_web::{get, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+use crate::models::{BroadcastDeliveryQuery, CreateBroadcastRequest};
+use crate::util;
+use crate::util::pagination::PageQuery;
+
+/// POST /admin/broadcasts - Send an urgent message (recall, weather warning) to the
+/// customers of active and upcoming bookings, in app and by email (Admin only)
+#[post("/admin/broadcasts")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn create(
+    identity: ReqData<Identity>,
+    web::Json(request): web::Json<CreateBroadcastRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::broadcast::create(&identity, request).await;
+
+    match result {
+        Ok(broadcast) => Ok(HttpResponse::Accepted().json(util::util_serde::to_value(broadcast))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /admin/broadcasts - Broadcasts with their delivery counts, newest first (Admin only)
+#[get("/admin/broadcasts")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(web::Query(page): web::Query<PageQuery>) -> Result<HttpResponse, AppError> {
+    let result = controllers::broadcast::list(page).await;
+
+    match result {
+        Ok(broadcasts) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(broadcasts))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /admin/broadcasts/{broadcast_id} - Status and delivery counts of a broadcast
+/// (Admin only)
+#[get("/admin/broadcasts/{broadcast_id}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn get(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+    let broadcast_id = parse_broadcast_id(path.into_inner())?;
+
+    let result = controllers::broadcast::get(&broadcast_id).await;
+
+    match result {
+        Ok(broadcast) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(broadcast))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /admin/broadcasts/{broadcast_id}/deliveries?status=FAILED - Outcome of each
+/// channel for each customer (Admin only)
+#[get("/admin/broadcasts/{broadcast_id}/deliveries")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn deliveries(
+    path: web::Path<String>,
+    web::Query(query): web::Query<BroadcastDeliveryQuery>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let broadcast_id = parse_broadcast_id(path.into_inner())?;
+
+    let result = controllers::broadcast::deliveries(&broadcast_id, query, page).await;
+
+    match result {
+        Ok(deliveries) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(deliveries))),
+        Err(error) => Err(error),
+    }
+}
+
+fn parse_broadcast_id(broadcast_id: String) -> Result<ObjectId, AppError> {
+    ObjectId::parse_str(broadcast_id)
+        .map_err(|_| AppError::bad_request("Invalid broadcast ID format"))
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(create)
+        .service(list)
+        .service(get)
+        .service(deliveries);
+}
@@ -0,0 +1,43 @@
+use actix_web::web::ReqData;
+use actix_web::{get, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::{Identity, Role};
+use crate::error::AppError;
+use crate::util::pagination::PageQuery;
+use crate::{controllers, util};
+
+/// GET /admin/changesets?page=&limit= - Admin operations that can still be rolled back (Admin only)
+#[get("/admin/changesets")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(web::Query(page): web::Query<PageQuery>) -> Result<HttpResponse, AppError> {
+    let result = controllers::changeset::list(page).await;
+
+    match result {
+        Ok(changesets) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(changesets))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /admin/changesets/{changeset_id}/rollback - Restore the documents an operation changed (Admin only)
+#[post("/admin/changesets/{changeset_id}/rollback")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn rollback(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let changeset_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid change-set ID format"))?;
+
+    let result = controllers::changeset::rollback(&identity, changeset_id).await;
+
+    match result {
+        Ok(rollback) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(rollback))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(list).service(rollback);
+}
@@ -0,0 +1,47 @@
+use actix_web::{delete, get, put, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+use crate::models::ChaosConfig;
+
+/// GET /chaos - Current fault injection configuration (Admin only, non-prod)
+#[get("/chaos")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn get() -> Result<HttpResponse, AppError> {
+    let result = controllers::chaos::get().await;
+
+    match result {
+        Ok(config) => Ok(HttpResponse::Ok().json(config)),
+        Err(error) => Err(error),
+    }
+}
+
+/// PUT /chaos - Replace fault injection rules (Admin only, non-prod)
+#[put("/chaos")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn update(web::Json(config): web::Json<ChaosConfig>) -> Result<HttpResponse, AppError> {
+    let result = controllers::chaos::update(config).await;
+
+    match result {
+        Ok(config) => Ok(HttpResponse::Ok().json(config)),
+        Err(error) => Err(error),
+    }
+}
+
+/// DELETE /chaos - Disable fault injection (Admin only, non-prod)
+#[delete("/chaos")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn reset() -> Result<HttpResponse, AppError> {
+    let result = controllers::chaos::reset().await;
+
+    match result {
+        Ok(config) => Ok(HttpResponse::Ok().json(config)),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(get).service(update).service(reset);
+}
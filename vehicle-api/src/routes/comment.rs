@@ -0,0 +1,79 @@
+use actix_web::web::ReqData;
+use actix_web::{get, patch, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::permission::Permission;
+use crate::error::AppError;
+use crate::models::{AddCommentRequest, UpdateCommentRequest};
+use crate::util::pagination::PageQuery;
+use crate::{controllers, util, validator};
+
+/// POST /bookings/{booking_id}/comments - Comment on a booking, `internal` ones are only
+/// shown to the staff (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+#[post("/bookings/{booking_id}/comments")]
+async fn add(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    request: validator::Json<AddCommentRequest>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result = controllers::comment::add(&identity, &booking_id, request.into_inner()).await;
+
+    match result {
+        Ok(comment) => Ok(HttpResponse::Created().json(util::util_serde::to_value(comment))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /bookings/{booking_id}/comments - Comments of a booking, oldest first
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings without internal ones)
+#[get("/bookings/{booking_id}/comments")]
+async fn list(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result = controllers::comment::list(&identity, &booking_id, page).await;
+
+    match result {
+        Ok(comments) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(comments))),
+        Err(error) => Err(error),
+    }
+}
+
+/// PATCH /bookings/{booking_id}/comments/{comment_id} - Mark a comment internal or not
+/// (Admin, CarManager, MotorbikeManager)
+#[patch("/bookings/{booking_id}/comments/{comment_id}")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn update(
+    identity: ReqData<Identity>,
+    path: web::Path<(String, String)>,
+    web::Json(request): web::Json<UpdateCommentRequest>,
+) -> Result<HttpResponse, AppError> {
+    let (booking_id, comment_id) = path.into_inner();
+    let booking_id = ObjectId::parse_str(&booking_id)
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+    let comment_id = ObjectId::parse_str(&comment_id)
+        .map_err(|_| AppError::bad_request("Invalid comment ID format"))?;
+
+    let result = controllers::comment::update(&identity, &booking_id, &comment_id, request).await;
+
+    match result {
+        Ok(comment) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(comment))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(add).service(list).service(update);
+}
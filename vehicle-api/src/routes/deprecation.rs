@@ -0,0 +1,23 @@
+use actix_web::{get, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+
+use crate::authentication::identity::Role;
+use crate::error::AppError;
+use crate::models::DeprecationQuery;
+use crate::{controllers, util};
+
+/// GET /admin/deprecations - Callers still using deprecated features (Admin only)
+#[get("/admin/deprecations")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn report(query: web::Query<DeprecationQuery>) -> Result<HttpResponse, AppError> {
+    let result = controllers::deprecation::report(query.into_inner()).await;
+
+    match result {
+        Ok(reports) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(reports))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(report);
+}
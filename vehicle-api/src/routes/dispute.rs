@@ -0,0 +1,176 @@
+use actix_web::web::ReqData;
+use actix_web::{get, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::authentication::permission::Permission;
+use crate::error::AppError;
+use crate::models::{
+    AddDisputeEvidenceRequest, AddDisputeNoteRequest, OpenDisputeRequest, ResolveDisputeRequest,
+};
+use crate::util::pagination::PageQuery;
+use crate::{controllers, util, validator};
+
+fn parse_ids(booking_id: &str, dispute_id: &str) -> Result<(ObjectId, ObjectId), AppError> {
+    let booking_id = ObjectId::parse_str(booking_id)
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+    let dispute_id = ObjectId::parse_str(dispute_id)
+        .map_err(|_| AppError::bad_request("Invalid dispute ID format"))?;
+    Ok((booking_id, dispute_id))
+}
+
+/// POST /bookings/{booking_id}/disputes - Open a dispute on a booking
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+#[post("/bookings/{booking_id}/disputes")]
+async fn open(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    request: validator::Json<OpenDisputeRequest>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result = controllers::dispute::open(&identity, &booking_id, request.into_inner()).await;
+
+    match result {
+        Ok(dispute) => Ok(HttpResponse::Created().json(util::util_serde::to_value(dispute))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /bookings/{booking_id}/disputes - Disputes of a booking, newest first
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+#[get("/bookings/{booking_id}/disputes")]
+async fn list(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result = controllers::dispute::list(&identity, &booking_id, page).await;
+
+    match result {
+        Ok(disputes) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(disputes))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /bookings/{booking_id}/disputes/{dispute_id} - Get a dispute, staff notes are
+/// hidden from customers (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+#[get("/bookings/{booking_id}/disputes/{dispute_id}")]
+async fn get(
+    identity: ReqData<Identity>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (booking_id, dispute_id) = path.into_inner();
+    let (booking_id, dispute_id) = parse_ids(&booking_id, &dispute_id)?;
+
+    let result = controllers::dispute::get(&identity, &booking_id, &dispute_id).await;
+
+    match result {
+        Ok(dispute) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(dispute))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /bookings/{booking_id}/disputes/{dispute_id}/evidence - Attach evidence
+/// (Admin, CarManager, MotorbikeManager, Customer for own bookings)
+#[post("/bookings/{booking_id}/disputes/{dispute_id}/evidence")]
+async fn add_evidence(
+    identity: ReqData<Identity>,
+    path: web::Path<(String, String)>,
+    request: validator::Json<AddDisputeEvidenceRequest>,
+) -> Result<HttpResponse, AppError> {
+    let (booking_id, dispute_id) = path.into_inner();
+    let (booking_id, dispute_id) = parse_ids(&booking_id, &dispute_id)?;
+
+    let result = controllers::dispute::add_evidence(
+        &identity,
+        &booking_id,
+        &dispute_id,
+        request.into_inner(),
+    )
+    .await;
+
+    match result {
+        Ok(dispute) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(dispute))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /bookings/{booking_id}/disputes/{dispute_id}/notes - Add an internal note
+/// (Admin, CarManager, MotorbikeManager)
+#[post("/bookings/{booking_id}/disputes/{dispute_id}/notes")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn add_note(
+    identity: ReqData<Identity>,
+    path: web::Path<(String, String)>,
+    request: validator::Json<AddDisputeNoteRequest>,
+) -> Result<HttpResponse, AppError> {
+    let (booking_id, dispute_id) = path.into_inner();
+    let (booking_id, dispute_id) = parse_ids(&booking_id, &dispute_id)?;
+
+    let result =
+        controllers::dispute::add_note(&identity, &booking_id, &dispute_id, request.into_inner())
+            .await;
+
+    match result {
+        Ok(dispute) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(dispute))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /bookings/{booking_id}/disputes/{dispute_id}/resolve - Resolve a dispute with a
+/// refund, a charge or no action (Admin, CarManager, MotorbikeManager)
+#[post("/bookings/{booking_id}/disputes/{dispute_id}/resolve")]
+#[protect(
+    "Permission::BookingApprove",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn resolve(
+    identity: ReqData<Identity>,
+    path: web::Path<(String, String)>,
+    request: validator::Json<ResolveDisputeRequest>,
+) -> Result<HttpResponse, AppError> {
+    let (booking_id, dispute_id) = path.into_inner();
+    let (booking_id, dispute_id) = parse_ids(&booking_id, &dispute_id)?;
+
+    let result =
+        controllers::dispute::resolve(&identity, &booking_id, &dispute_id, request.into_inner())
+            .await;
+
+    match result {
+        Ok(dispute) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(dispute))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /disputes/stats - Disputes by status and amounts refunded or charged (Admin only)
+#[get("/disputes/stats")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn stats() -> Result<HttpResponse, AppError> {
+    let result = controllers::dispute::stats().await;
+
+    match result {
+        Ok(stats) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(stats))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(stats)
+        .service(open)
+        .service(list)
+        .service(get)
+        .service(add_evidence)
+        .service(add_note)
+        .service(resolve);
+}
@@ -0,0 +1,23 @@
+use actix_web::{get, web, HttpResponse, Result};
+
+use crate::controllers;
+use crate::error::AppError;
+use crate::models::UnsubscribeQuery;
+
+/// GET /emails/unsubscribe?user_id=&token= - Stop the weekly digest, link of the digest
+/// email (public, the token is signed for the customer)
+#[get("/emails/unsubscribe")]
+async fn unsubscribe(
+    web::Query(query): web::Query<UnsubscribeQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::notification::unsubscribe(query).await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().body("You will no longer receive the weekly summary.")),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(unsubscribe);
+}
@@ -0,0 +1,54 @@
+use actix_web::web::ReqData;
+use actix_web::{get, put, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+
+use crate::authentication::identity::{Identity, Role};
+use crate::error::AppError;
+use crate::models::ExperimentRequest;
+use crate::validator;
+use crate::{controllers, util};
+
+/// GET /admin/experiments - Every A/B experiment (Admin only)
+#[get("/admin/experiments")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list() -> Result<HttpResponse, AppError> {
+    let result = controllers::experiment::list().await;
+
+    match result {
+        Ok(experiments) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(experiments))),
+        Err(error) => Err(error),
+    }
+}
+
+/// PUT /admin/experiments/{key} - Create or replace an experiment (Admin only)
+#[put("/admin/experiments/{key}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn upsert(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    request: validator::Json<ExperimentRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result =
+        controllers::experiment::upsert(&identity, path.into_inner(), request.into_inner()).await;
+
+    match result {
+        Ok(experiment) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(experiment))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /admin/experiments/{key}/exposures - Callers exposed to each variant (Admin only)
+#[get("/admin/experiments/{key}/exposures")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn exposures(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+    let result = controllers::experiment::exposures(&path.into_inner()).await;
+
+    match result {
+        Ok(exposures) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(exposures))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(list).service(upsert).service(exposures);
+}
@@ -0,0 +1,72 @@
+use actix_web::web::ReqData;
+use actix_web::{delete, get, put, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use chrono::NaiveDate;
+use validator::Validate;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::error::AppError;
+use crate::models::{HolidayQuery, UpsertHolidayRequest};
+use crate::{controllers, util};
+
+/// GET /holidays?country=FR&year=2025 - Loaded holiday calendar (Admin only)
+#[get("/holidays")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(web::Query(query): web::Query<HolidayQuery>) -> Result<HttpResponse, AppError> {
+    let result = controllers::holiday::list(query).await;
+
+    match result {
+        Ok(holidays) => Ok(HttpResponse::Ok().json(holidays)),
+        Err(error) => Err(error),
+    }
+}
+
+/// PUT /holidays/{country}/{date} - Override a day of the calendar (Admin only)
+#[put("/holidays/{country}/{date}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn upsert(
+    identity: ReqData<Identity>,
+    path: web::Path<(String, String)>,
+    web::Json(request): web::Json<UpsertHolidayRequest>,
+) -> Result<HttpResponse, AppError> {
+    let (country, date) = path.into_inner();
+    let date = parse_date(&date)?;
+    request
+        .validate()
+        .map_err(|e| AppError::bad_request(e.to_string()))?;
+
+    let result = controllers::holiday::upsert(&identity, &country, date, request).await;
+
+    match result {
+        Ok(holiday) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(holiday))),
+        Err(error) => Err(error),
+    }
+}
+
+/// DELETE /holidays/{country}/{date} - Drop an override (Admin only)
+#[delete("/holidays/{country}/{date}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn delete_override(path: web::Path<(String, String)>) -> Result<HttpResponse, AppError> {
+    let (country, date) = path.into_inner();
+    let date = parse_date(&date)?;
+
+    let result = controllers::holiday::delete_override(&country, date).await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(error) => Err(error),
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| AppError::bad_request("Invalid date format, expected YYYY-MM-DD"))
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(list)
+        .service(upsert)
+        .service(delete_override);
+}
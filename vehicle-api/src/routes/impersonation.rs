@@ -0,0 +1,28 @@
+use actix_web::web::ReqData;
+use actix_web::{post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+
+/// POST /impersonate/{user_id} - Short-lived token acting as a customer, every request
+/// made with it is flagged with the admin (Admin only)
+#[post("/impersonate/{user_id}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn impersonate(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::impersonation::impersonate(&identity, &path.into_inner()).await;
+
+    match result {
+        Ok(token) => Ok(HttpResponse::Created().json(token)),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(impersonate);
+}
@@ -0,0 +1,46 @@
+use actix_web::web::ReqData;
+use actix_web::{get, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+use crate::models::IntegrityCheckQuery;
+use crate::util;
+use crate::util::pagination::PageQuery;
+
+/// POST /admin/integrity/check?repair=true - Cross-check bookings and reservation days,
+/// repairing what can be when `repair` is set (Admin only)
+#[post("/admin/integrity/check")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn check(
+    identity: ReqData<Identity>,
+    web::Query(query): web::Query<IntegrityCheckQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::integrity::check(&identity, query.repair).await;
+
+    match result {
+        Ok(report) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(report))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /admin/integrity - Reports of the integrity checks, newest first (Admin only)
+#[get("/admin/integrity")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(
+    identity: ReqData<Identity>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::integrity::list(&identity, page).await;
+
+    match result {
+        Ok(reports) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(reports))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(check).service(list);
+}
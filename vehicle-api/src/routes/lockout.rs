@@ -0,0 +1,40 @@
+use actix_web::{delete, get, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use std::net::IpAddr;
+
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+
+/// GET /lockouts - Addresses tracked for invalid API keys on this instance (Admin only)
+#[get("/lockouts")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list() -> Result<HttpResponse, AppError> {
+    let result = controllers::lockout::list().await;
+
+    match result {
+        Ok(lockouts) => Ok(HttpResponse::Ok().json(lockouts)),
+        Err(error) => Err(error),
+    }
+}
+
+/// DELETE /lockouts/{ip} - Lift the lockout of an address (Admin only)
+#[delete("/lockouts/{ip}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn clear(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+    let ip: IpAddr = path
+        .into_inner()
+        .parse()
+        .map_err(|_| AppError::bad_request("Invalid IP address"))?;
+
+    let result = controllers::lockout::clear(ip).await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(list).service(clear);
+}
@@ -0,0 +1,144 @@
+use actix_web::web::ReqData;
+use actix_web::{delete, get, patch, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::permission::Permission;
+use crate::error::AppError;
+use crate::models::{CreateMaintenanceRecordRequest, UpdateMaintenanceRecordRequest};
+use crate::util::pagination::PageQuery;
+use crate::{controllers, util, validator};
+
+fn parse_ids(vehicle_id: &str, record_id: &str) -> Result<(ObjectId, ObjectId), AppError> {
+    let vehicle_id = ObjectId::parse_str(vehicle_id)
+        .map_err(|_| AppError::bad_request("Invalid vehicle ID format"))?;
+    let record_id = ObjectId::parse_str(record_id)
+        .map_err(|_| AppError::bad_request("Invalid maintenance record ID format"))?;
+    Ok((vehicle_id, record_id))
+}
+
+/// POST /vehicles/{vehicle_id}/maintenance - Record a service of a vehicle
+/// (Admin, CarManager, MotorbikeManager)
+#[post("/vehicles/{vehicle_id}/maintenance")]
+#[protect(
+    "Permission::VehicleUpdate",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn create(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    request: validator::Json<CreateMaintenanceRecordRequest>,
+) -> Result<HttpResponse, AppError> {
+    let vehicle_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid vehicle ID format"))?;
+
+    let result =
+        controllers::maintenance::create(&identity, &vehicle_id, request.into_inner()).await;
+
+    match result {
+        Ok(record) => Ok(HttpResponse::Created().json(util::util_serde::to_value(record))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /vehicles/{vehicle_id}/maintenance - Services of a vehicle, latest first
+/// (Admin, CarManager, MotorbikeManager)
+#[get("/vehicles/{vehicle_id}/maintenance")]
+#[protect(
+    "Permission::VehicleUpdate",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn list(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let vehicle_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid vehicle ID format"))?;
+
+    let result = controllers::maintenance::list(&identity, &vehicle_id, page).await;
+
+    match result {
+        Ok(records) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(records))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /vehicles/{vehicle_id}/maintenance/{record_id} - One service of a vehicle
+/// (Admin, CarManager, MotorbikeManager)
+#[get("/vehicles/{vehicle_id}/maintenance/{record_id}")]
+#[protect(
+    "Permission::VehicleUpdate",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn get(
+    identity: ReqData<Identity>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (vehicle_id, record_id) = path.into_inner();
+    let (vehicle_id, record_id) = parse_ids(&vehicle_id, &record_id)?;
+
+    let result = controllers::maintenance::get(&identity, &vehicle_id, &record_id).await;
+
+    match result {
+        Ok(record) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(record))),
+        Err(error) => Err(error),
+    }
+}
+
+/// PATCH /vehicles/{vehicle_id}/maintenance/{record_id} - Correct a service
+/// (Admin, CarManager, MotorbikeManager)
+#[patch("/vehicles/{vehicle_id}/maintenance/{record_id}")]
+#[protect(
+    "Permission::VehicleUpdate",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn update(
+    identity: ReqData<Identity>,
+    path: web::Path<(String, String)>,
+    request: validator::Json<UpdateMaintenanceRecordRequest>,
+) -> Result<HttpResponse, AppError> {
+    let (vehicle_id, record_id) = path.into_inner();
+    let (vehicle_id, record_id) = parse_ids(&vehicle_id, &record_id)?;
+
+    let result =
+        controllers::maintenance::update(&identity, &vehicle_id, &record_id, request.into_inner())
+            .await;
+
+    match result {
+        Ok(record) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(record))),
+        Err(error) => Err(error),
+    }
+}
+
+/// DELETE /vehicles/{vehicle_id}/maintenance/{record_id} - Remove a service recorded by
+/// mistake (Admin, CarManager, MotorbikeManager)
+#[delete("/vehicles/{vehicle_id}/maintenance/{record_id}")]
+#[protect(
+    "Permission::VehicleUpdate",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn delete(
+    identity: ReqData<Identity>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (vehicle_id, record_id) = path.into_inner();
+    let (vehicle_id, record_id) = parse_ids(&vehicle_id, &record_id)?;
+
+    let result = controllers::maintenance::delete(&identity, &vehicle_id, &record_id).await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(create)
+        .service(list)
+        .service(get)
+        .service(update)
+        .service(delete);
+}
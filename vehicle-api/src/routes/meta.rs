@@ -0,0 +1,56 @@
+use actix_web::{get, web, HttpResponse, Result};
+
+use crate::controllers;
+use crate::error::AppError;
+
+/// GET / - Version, enabled features, authentication methods and links (public)
+#[get("/")]
+async fn discovery() -> Result<HttpResponse, AppError> {
+    let result = controllers::meta::discovery().await;
+
+    match result {
+        Ok(discovery) => Ok(HttpResponse::Ok().json(discovery)),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /meta/events - JSON Schemas of every published event type (public)
+#[get("/meta/events")]
+async fn events() -> Result<HttpResponse, AppError> {
+    let result = controllers::meta::events().await;
+
+    match result {
+        Ok(event_schemas) => Ok(HttpResponse::Ok().json(event_schemas)),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /meta/schemas - List models with a published JSON Schema (public)
+#[get("/meta/schemas")]
+async fn schemas() -> Result<HttpResponse, AppError> {
+    let result = controllers::meta::schemas().await;
+
+    match result {
+        Ok(names) => Ok(HttpResponse::Ok().json(names)),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /meta/schemas/{name} - JSON Schema of a request/response model (public)
+#[get("/meta/schemas/{name}")]
+async fn schema(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+    let result = controllers::meta::schema(&path.into_inner()).await;
+
+    match result {
+        Ok(schema) => Ok(HttpResponse::Ok().json(schema)),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(discovery)
+        .service(events)
+        .service(schemas)
+        .service(schema);
+}
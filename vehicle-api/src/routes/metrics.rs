@@ -0,0 +1,22 @@
+use actix_web::{get, web, HttpResponse, Result};
+
+use crate::controllers;
+use crate::error::AppError;
+use crate::services;
+
+/// GET /metrics - Prometheus metrics, e.g. validation failures by rule (public)
+#[get("/metrics")]
+async fn metrics() -> Result<HttpResponse, AppError> {
+    let result = controllers::metrics::render().await;
+
+    match result {
+        Ok(metrics) => Ok(HttpResponse::Ok()
+            .content_type(services::metrics::CONTENT_TYPE)
+            .body(metrics)),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(metrics);
+}
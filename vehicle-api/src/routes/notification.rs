@@ -0,0 +1,55 @@
+use actix_web::web::ReqData;
+use actix_web::{get, put, web, HttpResponse, Result};
+
+use crate::authentication::identity::Identity;
+use crate::controllers;
+use crate::error::AppError;
+use crate::models::EmailPreferences;
+use crate::util;
+use crate::util::pagination::PageQuery;
+
+/// GET /notifications - Notifications of the caller, newest first (any authenticated user)
+#[get("/notifications")]
+async fn list(
+    identity: ReqData<Identity>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::notification::list(&identity, page).await;
+
+    match result {
+        Ok(notifications) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(notifications))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /notifications/preferences - Optional emails the caller receives (Customer only)
+#[get("/notifications/preferences")]
+async fn preferences(identity: ReqData<Identity>) -> Result<HttpResponse, AppError> {
+    let result = controllers::notification::preferences(&identity).await;
+
+    match result {
+        Ok(preferences) => Ok(HttpResponse::Ok().json(preferences)),
+        Err(error) => Err(error),
+    }
+}
+
+/// PUT /notifications/preferences - Choose the optional emails (Customer only)
+#[put("/notifications/preferences")]
+async fn update_preferences(
+    identity: ReqData<Identity>,
+    web::Json(request): web::Json<EmailPreferences>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::notification::update_preferences(&identity, request).await;
+
+    match result {
+        Ok(updated) => Ok(HttpResponse::Ok().json(updated)),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(list)
+        .service(preferences)
+        .service(update_preferences);
+}
@@ -0,0 +1,57 @@
+use actix_web::web::ReqData;
+use actix_web::{delete, get, put, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::error::AppError;
+use crate::models::SetOfficeHoursRequest;
+use crate::{controllers, util};
+
+/// GET /admin/office-hours - Office hours of every location (Admin only)
+#[get("/admin/office-hours")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(identity: ReqData<Identity>) -> Result<HttpResponse, AppError> {
+    let result = controllers::office_hours::list(&identity).await;
+
+    match result {
+        Ok(hours) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(hours))),
+        Err(error) => Err(error),
+    }
+}
+
+/// PUT /admin/office-hours/{location} - Set the staffed hours of a location, an IANA
+/// time zone such as Europe/Paris (Admin only)
+#[put("/admin/office-hours/{location:.+}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn set(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    web::Json(request): web::Json<SetOfficeHoursRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::office_hours::set(&identity, &path.into_inner(), request).await;
+
+    match result {
+        Ok(hours) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(hours))),
+        Err(error) => Err(error),
+    }
+}
+
+/// DELETE /admin/office-hours/{location} - Drop the hours of a location (Admin only)
+#[delete("/admin/office-hours/{location:.+}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn delete(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::office_hours::delete(&identity, &path.into_inner()).await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(list).service(set).service(delete);
+}
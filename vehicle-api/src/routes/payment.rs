@@ -0,0 +1,86 @@
+use actix_web::web::ReqData;
+use actix_web::{post, web, HttpRequest, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::authentication::permission::Permission;
+use crate::error::AppError;
+use crate::models::RefundRequest;
+use crate::{controllers, util, validator};
+
+/// Longest accepted `Idempotency-Key` header
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 200;
+
+fn idempotency_key(req: &HttpRequest) -> Result<Option<String>, AppError> {
+    let Some(value) = req.headers().get("Idempotency-Key") else {
+        return Ok(None);
+    };
+    match value.to_str().map(str::trim) {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LENGTH => {
+            Ok(Some(key.to_string()))
+        }
+        _ => Err(AppError::bad_request(format!(
+            "Idempotency-Key must be 1 to {} visible ASCII characters",
+            MAX_IDEMPOTENCY_KEY_LENGTH
+        ))),
+    }
+}
+
+/// POST /bookings/{booking_id}/refunds - Refund the customer of a booking, at most what
+/// they paid. Retries with the same `Idempotency-Key` return the first refund (Admin only)
+#[post("/bookings/{booking_id}/refunds")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn refund(
+    req: HttpRequest,
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    request: validator::Json<RefundRequest>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+    let idempotency_key = idempotency_key(&req)?;
+
+    let result = controllers::payment::refund(
+        &identity,
+        &booking_id,
+        request.into_inner(),
+        idempotency_key,
+    )
+    .await;
+
+    match result {
+        Ok((payment, true)) => {
+            Ok(HttpResponse::Created().json(util::util_serde::to_value(payment)))
+        }
+        Ok((payment, false)) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(payment))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /bookings/{booking_id}/pay - Start paying the total price with Stripe, answers
+/// the PaymentIntent client secret (Customer, own bookings)
+#[post("/bookings/{booking_id}/pay")]
+#[protect(
+    "Permission::BookingCreate",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn pay(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let booking_id = ObjectId::parse_str(path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid booking ID format"))?;
+
+    let result = controllers::payment::pay(&identity, &booking_id).await;
+
+    match result {
+        Ok(intent) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(intent))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(refund).service(pay);
+}
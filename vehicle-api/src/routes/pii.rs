@@ -0,0 +1,22 @@
+use actix_web::{post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+
+/// POST /pii/rotate - Re-encrypt personal data with the current key (Admin only)
+#[post("/pii/rotate")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn rotate() -> Result<HttpResponse, AppError> {
+    let result = controllers::pii::rotate().await;
+
+    match result {
+        Ok(report) => Ok(HttpResponse::Ok().json(report)),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(rotate);
+}
@@ -0,0 +1,22 @@
+use actix_web::{get, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+
+/// GET /priority-lanes - Load of the priority lanes on this instance (Admin only)
+#[get("/priority-lanes")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn stats() -> Result<HttpResponse, AppError> {
+    let result = controllers::priority::stats().await;
+
+    match result {
+        Ok(lanes) => Ok(HttpResponse::Ok().json(lanes)),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(stats);
+}
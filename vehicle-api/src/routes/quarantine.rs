@@ -0,0 +1,28 @@
+use actix_web::{get, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+
+use crate::authentication::identity::Role;
+use crate::error::AppError;
+use crate::models::QuarantineQuery;
+use crate::util::pagination::PageQuery;
+use crate::{controllers, util};
+
+/// GET /admin/quarantine?collection=bookings - Stored documents lists skipped because
+/// they could not be decoded (Admin only)
+#[get("/admin/quarantine")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(
+    web::Query(query): web::Query<QuarantineQuery>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::quarantine::list(query, page).await;
+
+    match result {
+        Ok(documents) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(documents))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(list);
+}
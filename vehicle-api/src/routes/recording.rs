@@ -0,0 +1,90 @@
+use actix_web::{get, post, put, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Role;
+use crate::error::AppError;
+use crate::models::{RecordingFilters, RecordingTargets};
+use crate::util::pagination::PageQuery;
+use crate::{controllers, util};
+
+/// GET /recordings/targets - Users and request IDs being recorded (Admin only)
+#[get("/recordings/targets")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn get_targets() -> Result<HttpResponse, AppError> {
+    let result = controllers::recording::get_targets().await;
+
+    match result {
+        Ok(targets) => Ok(HttpResponse::Ok().json(targets)),
+        Err(error) => Err(error),
+    }
+}
+
+/// PUT /recordings/targets - Choose which users and request IDs to record (Admin only)
+#[put("/recordings/targets")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn update_targets(
+    web::Json(targets): web::Json<RecordingTargets>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::recording::update_targets(targets).await;
+
+    match result {
+        Ok(targets) => Ok(HttpResponse::Ok().json(targets)),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /recordings - List recordings by user or request ID (Admin only)
+#[get("/recordings")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(
+    web::Query(filters): web::Query<RecordingFilters>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::recording::list(filters, page).await;
+
+    match result {
+        Ok(recordings) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(recordings))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /recordings/{recording_id} - Get a single recording (Admin only)
+#[get("/recordings/{recording_id}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn get(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+    let recording_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid recording ID format"))?;
+
+    let result = controllers::recording::get(&recording_id).await;
+
+    match result {
+        Ok(Some(recording)) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(recording))),
+        Ok(None) => Err(AppError::not_found("Recording not found")),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /recordings/{recording_id}/replay - Dry-run a recorded request (Admin only)
+#[post("/recordings/{recording_id}/replay")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn replay(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+    let recording_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid recording ID format"))?;
+
+    let result = controllers::recording::replay(&recording_id).await;
+
+    match result {
+        Ok(replay) => Ok(HttpResponse::Ok().json(replay)),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(get_targets)
+        .service(update_targets)
+        .service(list)
+        .service(get)
+        .service(replay);
+}
@@ -0,0 +1,57 @@
+use actix_web::{get, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+use crate::models::ReportQuery;
+use crate::util::ndjson;
+
+/// GET /reports/capacity?from=&to=&type=CAR - Demand vs supply per day (Admin, CarManager, MotorbikeManager)
+#[get("/reports/capacity")]
+#[protect(
+    any("Role::Admin", "Role::CarManager", "Role::MotorbikeManager"),
+    ty = "crate::authentication::identity::Role"
+)]
+async fn capacity(web::Query(query): web::Query<ReportQuery>) -> Result<HttpResponse, AppError> {
+    let result = controllers::report::capacity(query).await;
+
+    match result {
+        Ok(report) => Ok(HttpResponse::Ok().json(report)),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /reports/utilization?from=&to=&type=CAR - Booked days per vehicle as NDJSON (Admin, CarManager, MotorbikeManager)
+#[get("/reports/utilization")]
+#[protect(
+    any("Role::Admin", "Role::CarManager", "Role::MotorbikeManager"),
+    ty = "crate::authentication::identity::Role"
+)]
+async fn utilization(web::Query(query): web::Query<ReportQuery>) -> Result<HttpResponse, AppError> {
+    let result = controllers::report::utilization(query).await;
+
+    match result {
+        Ok(rows) => Ok(ndjson::response(rows)),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /reports/revenue?from=&to=&type=CAR - Confirmed revenue per month as NDJSON (Admin)
+#[get("/reports/revenue")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn revenue(web::Query(query): web::Query<ReportQuery>) -> Result<HttpResponse, AppError> {
+    let result = controllers::report::revenue(query).await;
+
+    match result {
+        Ok(rows) => Ok(ndjson::response(rows)),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(capacity)
+        .service(utilization)
+        .service(revenue);
+}
@@ -0,0 +1,27 @@
+use actix_web::{get, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+
+use crate::authentication::identity::Role;
+use crate::error::AppError;
+use crate::util::pagination::PageQuery;
+use crate::{controllers, util};
+
+/// GET /admin/schema-violations/{collection} - Stored documents breaking the schema of
+/// `vehicles` or `bookings` (Admin only)
+#[get("/admin/schema-violations/{collection}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn violations(
+    path: web::Path<String>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::schema::violations(&path.into_inner(), page).await;
+
+    match result {
+        Ok(report) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(report))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(violations);
+}
@@ -0,0 +1,75 @@
+use actix_web::web::ReqData;
+use actix_web::{delete, get, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+use crate::models::{
+    CreateServiceAccountRequest, CreatedServiceAccountResponse, ServiceAccountResponse,
+};
+use crate::util::pagination::PageQuery;
+use crate::validator;
+
+/// POST /service-accounts - Create a service account, the client secret is only
+/// returned here (Admin only)
+#[post("/service-accounts")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn create(
+    identity: ReqData<Identity>,
+    request: validator::Json<CreateServiceAccountRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::service_account::create(&identity, request.into_inner()).await;
+
+    match result {
+        Ok((service_account, client_secret)) => {
+            Ok(HttpResponse::Created().json(CreatedServiceAccountResponse {
+                client_id: service_account.id.map(|id| id.to_hex()).unwrap_or_default(),
+                client_secret,
+                service_account: service_account.into(),
+            }))
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /service-accounts - List service accounts without their secret (Admin only)
+#[get("/service-accounts")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(web::Query(page): web::Query<PageQuery>) -> Result<HttpResponse, AppError> {
+    let result = controllers::service_account::list(page).await;
+
+    match result {
+        Ok(service_accounts) => Ok(HttpResponse::Ok().json(
+            service_accounts
+                .into_iter()
+                .map(ServiceAccountResponse::from)
+                .collect::<Vec<_>>(),
+        )),
+        Err(error) => Err(error),
+    }
+}
+
+/// DELETE /service-accounts/{service_account_id} - Disable a service account and
+/// end its sessions (Admin only)
+#[delete("/service-accounts/{service_account_id}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn disable(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+    let service_account_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid service account ID format"))?;
+
+    let result = controllers::service_account::disable(&service_account_id).await;
+
+    match result {
+        Ok(service_account) => {
+            Ok(HttpResponse::Ok().json(ServiceAccountResponse::from(service_account)))
+        }
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(create).service(list).service(disable);
+}
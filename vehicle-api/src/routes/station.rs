@@ -0,0 +1,103 @@
+use actix_web::web::ReqData;
+use actix_web::{delete, get, patch, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::error::AppError;
+use crate::models::{CreateStationRequest, UpdateStationRequest};
+use crate::util::pagination::PageQuery;
+use crate::{controllers, util, validator};
+
+/// GET /stations - Pickup stations, by name (All users)
+#[get("/stations")]
+async fn list(
+    _identity: ReqData<Identity>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::station::list(page).await;
+
+    match result {
+        Ok(stations) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(stations))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /stations/{station_id} - One pickup station (All users)
+#[get("/stations/{station_id}")]
+async fn get(
+    _identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let station_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid station ID format"))?;
+
+    let result = controllers::station::get(&station_id).await;
+
+    match result {
+        Ok(station) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(station))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /stations - Open a pickup station (Admin only)
+#[post("/stations")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn create(
+    identity: ReqData<Identity>,
+    request: validator::Json<CreateStationRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::station::create(&identity, request.into_inner()).await;
+
+    match result {
+        Ok(station) => Ok(HttpResponse::Created().json(util::util_serde::to_value(station))),
+        Err(error) => Err(error),
+    }
+}
+
+/// PATCH /stations/{station_id} - Change a station, its vehicles follow a move (Admin only)
+#[patch("/stations/{station_id}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn update(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    request: validator::Json<UpdateStationRequest>,
+) -> Result<HttpResponse, AppError> {
+    let station_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid station ID format"))?;
+
+    let result = controllers::station::update(&identity, &station_id, request.into_inner()).await;
+
+    match result {
+        Ok(station) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(station))),
+        Err(error) => Err(error),
+    }
+}
+
+/// DELETE /stations/{station_id} - Close a station without vehicles (Admin only)
+#[delete("/stations/{station_id}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn delete(
+    _identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let station_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid station ID format"))?;
+
+    let result = controllers::station::delete(&station_id).await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(list)
+        .service(get)
+        .service(create)
+        .service(update)
+        .service(delete);
+}
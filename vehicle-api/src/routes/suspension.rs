@@ -0,0 +1,63 @@
+use actix_web::web::ReqData;
+use actix_web::{get, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::error::AppError;
+use crate::models::{SuspendCustomerRequest, SuspensionFilters};
+use crate::util::pagination::PageQuery;
+use crate::{controllers, util, validator};
+
+/// POST /suspensions - Suspend a customer, optionally until a date (Admin only)
+#[post("/suspensions")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn suspend(
+    identity: ReqData<Identity>,
+    request: validator::Json<SuspendCustomerRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::suspension::suspend(&identity, request.into_inner()).await;
+
+    match result {
+        Ok(suspension) => Ok(HttpResponse::Created().json(util::util_serde::to_value(suspension))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /suspensions?user_id=...&include_inactive=true - Customer suspensions (Admin only)
+#[get("/suspensions")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(
+    web::Query(filters): web::Query<SuspensionFilters>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::suspension::list(filters, page).await;
+
+    match result {
+        Ok(suspensions) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(suspensions))),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /suspensions/{suspension_id}/lift - Reinstate a suspended customer (Admin only)
+#[post("/suspensions/{suspension_id}/lift")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn lift(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let suspension_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid suspension ID format"))?;
+
+    let result = controllers::suspension::lift(&identity, &suspension_id).await;
+
+    match result {
+        Ok(suspension) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(suspension))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(suspend).service(list).service(lift);
+}
@@ -1,20 +1,32 @@
+use actix_multipart::{Multipart, MultipartError};
+use actix_web::http::header;
 use actix_web::web::ReqData;
-use actix_web::{get, patch, post, web, HttpResponse, Result};
+use actix_web::{delete, get, patch, post, put, web, HttpRequest, HttpResponse, Result};
 use actix_web_grants::proc_macro::protect;
 use bson::oid::ObjectId;
+use futures::TryStreamExt;
 
 use crate::authentication::identity::Identity;
 use crate::authentication::identity::Role;
+use crate::authentication::permission::Permission;
 use crate::error::AppError;
 use crate::models::{
-    CreateVehicleRequest, UpdateVehicleRequest, VehicleFilters, VehiclePagination,
+    CreateVehicleRequest, DeletedScope, ExperimentAssignments, PriceAdjustmentRequest,
+    SimilarQuery, SuggestionQuery, UpdateVehicleRequest, UpdateVehicleStatusRequest,
+    VehicleFilters, VehicleListQuery, VehiclePagination, VehicleSearchQuery,
 };
+use crate::services;
+use crate::services::image::{ImageUpload, MAX_IMAGES_PER_VEHICLE};
+use crate::util::pagination::PageQuery;
 use crate::validator;
 use crate::{controllers, util};
 
 /// POST /vehicles - Create a new vehicle (Admin only)
 #[post("/vehicles")]
-#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+#[protect(
+    "Permission::VehicleCreate",
+    ty = "crate::authentication::permission::Permission"
+)]
 async fn create(
     identity: ReqData<Identity>,
     request: validator::Json<CreateVehicleRequest>,
@@ -28,13 +40,30 @@ async fn create(
 }
 
 /// GET /vehicles - List vehicles with filters and pagination (All users)
+/// Managers only see their vehicle type unless they pass `type=CAR|MOTORBIKE|ALL`
+/// `rank=price_asc|popularity|newest|personalized` ranks the results, see models::ranking
+/// `currency=USD` converts the prices, `min_price` and `max_price` are then in USD
+/// `include_deleted=true` adds the soft deleted vehicles (Admin only)
 #[get("/vehicles")]
 async fn list(
-    _identity: ReqData<Identity>,
+    req: HttpRequest,
+    identity: ReqData<Identity>,
     web::Query(filters): web::Query<VehicleFilters>,
+    web::Query(deleted): web::Query<DeletedScope>,
     web::Query(pagination): web::Query<VehiclePagination>,
+    web::Query(query): web::Query<VehicleListQuery>,
+    assignments: Option<ReqData<ExperimentAssignments>>,
 ) -> Result<HttpResponse, AppError> {
-    let result = controllers::vehicle::list(filters, pagination).await;
+    let result = controllers::vehicle::list(
+        &identity,
+        filters,
+        deleted,
+        pagination,
+        query,
+        assignments.as_deref(),
+        util::locale::accepted_languages(&req).as_deref(),
+    )
+    .await;
 
     match result {
         Ok(vehicles) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(vehicles))),
@@ -42,11 +71,42 @@ async fn list(
     }
 }
 
+/// GET /vehicles/search?q=tesla&type=CAR - Free text search with facets (All users)
+#[get("/vehicles/search")]
+async fn search(
+    req: HttpRequest,
+    _identity: ReqData<Identity>,
+    web::Query(query): web::Query<VehicleSearchQuery>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let languages = util::locale::accepted_languages(&req);
+    let result = controllers::vehicle::search(query, page, languages.as_deref()).await;
+
+    match result {
+        Ok(results) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(results))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /vehicles/suggestions?q=tes - Brands and models completing what was typed (All users)
+#[get("/vehicles/suggestions")]
+async fn suggestions(
+    _identity: ReqData<Identity>,
+    web::Query(query): web::Query<SuggestionQuery>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::vehicle::suggest(query).await;
+
+    match result {
+        Ok(suggestions) => Ok(HttpResponse::Ok().json(suggestions)),
+        Err(error) => Err(error),
+    }
+}
+
 /// PATCH /vehicles/{vehicle_id} - Update a vehicle (Admin, CarManager, MotorbikeManager)
 #[patch("/vehicles/{vehicle_id}")]
 #[protect(
-    any("Role::Admin", "Role::CarManager", "Role::MotorbikeManager"),
-    ty = "crate::authentication::identity::Role"
+    "Permission::VehicleUpdate",
+    ty = "crate::authentication::permission::Permission"
 )]
 async fn update(
     identity: ReqData<Identity>,
@@ -64,16 +124,65 @@ async fn update(
     }
 }
 
+/// PUT /vehicles/{vehicle_id}/status - Put a vehicle in maintenance, retire it or make it
+/// available again (Admin, CarManager, MotorbikeManager)
+#[put("/vehicles/{vehicle_id}/status")]
+#[protect(
+    "Permission::VehicleUpdate",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn set_status(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    web::Json(request): web::Json<UpdateVehicleStatusRequest>,
+) -> Result<HttpResponse, AppError> {
+    let vehicle_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid vehicle ID format"))?;
+
+    let result = controllers::vehicle::set_status(&identity, &vehicle_id, request).await;
+
+    match result {
+        Ok(vehicle) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(vehicle))),
+        Err(error) => Err(error),
+    }
+}
+
+/// DELETE /vehicles/{vehicle_id} - Soft delete a vehicle (Admin, CarManager, MotorbikeManager)
+#[delete("/vehicles/{vehicle_id}")]
+#[protect(
+    "Permission::VehicleDelete",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn delete(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let vehicle_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid vehicle ID format"))?;
+
+    let result = controllers::vehicle::delete(&identity, &vehicle_id).await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(error) => Err(error),
+    }
+}
+
 /// GET /vehicles/{vehicle_id} - Get a single vehicle (All users)
+/// `include_deleted=true` also finds a soft deleted vehicle (Admin only)
 #[get("/vehicles/{vehicle_id}")]
 async fn get(
-    _identity: ReqData<Identity>,
+    req: HttpRequest,
+    identity: ReqData<Identity>,
     path: web::Path<String>,
+    web::Query(deleted): web::Query<DeletedScope>,
 ) -> Result<HttpResponse, AppError> {
     let vehicle_id = ObjectId::parse_str(&path.into_inner())
         .map_err(|_| AppError::bad_request("Invalid vehicle ID format"))?;
 
-    let result = controllers::vehicle::get(&vehicle_id).await;
+    let languages = util::locale::accepted_languages(&req);
+    let result =
+        controllers::vehicle::get(&identity, &vehicle_id, deleted, languages.as_deref()).await;
 
     match result {
         Ok(Some(vehicle)) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(vehicle))),
@@ -82,20 +191,134 @@ async fn get(
     }
 }
 
+/// POST /vehicles/{vehicle_id}/images - Upload photos of a vehicle, one multipart file per
+/// photo (Admin, CarManager, MotorbikeManager)
+#[post("/vehicles/{vehicle_id}/images")]
+#[protect(
+    "Permission::VehicleUpdate",
+    ty = "crate::authentication::permission::Permission"
+)]
+async fn add_images(
+    identity: ReqData<Identity>,
+    path: web::Path<String>,
+    mut payload: Multipart,
+) -> Result<HttpResponse, AppError> {
+    let vehicle_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid vehicle ID format"))?;
+
+    let uploads = read_images(&mut payload).await?;
+    let result = controllers::vehicle::add_images(&identity, &vehicle_id, uploads).await;
+
+    match result {
+        Ok(images) => Ok(HttpResponse::Created().json(util::util_serde::to_value(images))),
+        Err(error) => Err(error),
+    }
+}
+
+/// Files of a multipart body, fields without a file name are ignored. Stops reading as
+/// soon as a file is too large.
+async fn read_images(payload: &mut Multipart) -> Result<Vec<ImageUpload>, AppError> {
+    let invalid =
+        |error: MultipartError| AppError::bad_request(format!("Invalid multipart body: {}", error));
+    let max_bytes = services::image::max_image_bytes();
+
+    let mut uploads = Vec::new();
+    while let Some(mut field) = payload.try_next().await.map_err(invalid)? {
+        let Some(filename) = field
+            .content_disposition()
+            .and_then(|disposition| disposition.get_filename())
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        if uploads.len() == MAX_IMAGES_PER_VEHICLE {
+            return Err(AppError::bad_request(format!(
+                "At most {} images can be uploaded at once",
+                MAX_IMAGES_PER_VEHICLE
+            )));
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.try_next().await.map_err(invalid)? {
+            if bytes.len() + chunk.len() > max_bytes {
+                return Err(AppError::payload_too_large(format!(
+                    "{} is larger than {} bytes",
+                    filename, max_bytes
+                )));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        uploads.push(ImageUpload {
+            filename: Some(filename).filter(|filename| !filename.is_empty()),
+            bytes,
+        });
+    }
+    Ok(uploads)
+}
+
+/// GET /vehicles/{vehicle_id}/images/{image_id} - A photo of a vehicle, in its stored
+/// format when the `Accept` header allows it, 406 otherwise (All users)
+#[get("/vehicles/{vehicle_id}/images/{image_id}")]
+async fn get_image(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (vehicle_id, image_id) = path.into_inner();
+    let vehicle_id = ObjectId::parse_str(&vehicle_id)
+        .map_err(|_| AppError::bad_request("Invalid vehicle ID format"))?;
+
+    let accept = req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok());
+    let result = controllers::vehicle::get_image(&vehicle_id, &image_id, accept).await;
+
+    match result {
+        // An image ID always has the same bytes
+        Ok((image, bytes)) => Ok(HttpResponse::Ok()
+            .content_type(image.content_type)
+            .insert_header((header::VARY, "Accept"))
+            .insert_header((header::CACHE_CONTROL, "private, max-age=86400"))
+            .body(bytes)),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /vehicles/{vehicle_id}/similar?from=2025-08-01&to=2025-08-05 - Vehicles most like this one, free on those days (All users)
+#[get("/vehicles/{vehicle_id}/similar")]
+async fn similar(
+    req: HttpRequest,
+    _identity: ReqData<Identity>,
+    path: web::Path<String>,
+    web::Query(query): web::Query<SimilarQuery>,
+) -> Result<HttpResponse, AppError> {
+    let vehicle_id = ObjectId::parse_str(&path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid vehicle ID format"))?;
+
+    let languages = util::locale::accepted_languages(&req);
+    let result = controllers::vehicle::similar(&vehicle_id, query, languages.as_deref()).await;
+
+    match result {
+        Ok(vehicles) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(vehicles))),
+        Err(error) => Err(error),
+    }
+}
+
 /// GET /vehicles/{vehicle_id}/bookings - Get all bookings for a vehicle (Admin, CarManager, MotorbikeManager)
 #[get("/vehicles/{vehicle_id}/bookings")]
 #[protect(
-    any("Role::Admin", "Role::CarManager", "Role::MotorbikeManager"),
-    ty = "crate::authentication::identity::Role"
+    "Permission::VehicleReadBookings",
+    ty = "crate::authentication::permission::Permission"
 )]
 async fn list_bookings(
     identity: ReqData<Identity>,
     path: web::Path<String>,
+    web::Query(page): web::Query<PageQuery>,
 ) -> Result<HttpResponse, AppError> {
     let vehicle_id_str = path.into_inner();
     let vehicle_id = ObjectId::parse_str(&vehicle_id_str)
         .map_err(|_| AppError::bad_request("Invalid vehicle ID format"))?;
-    let result = controllers::vehicle::list_bookings(&identity, &vehicle_id).await;
+    let result = controllers::vehicle::list_bookings(&identity, &vehicle_id, page).await;
 
     match result {
         Ok(bookings) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(bookings))),
@@ -103,11 +326,35 @@ async fn list_bookings(
     }
 }
 
+/// POST /admin/vehicles/price-adjust - Reprice the vehicles matching a filter, `dry_run` only previews (Admin only)
+#[post("/admin/vehicles/price-adjust")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn adjust_prices(
+    identity: ReqData<Identity>,
+    request: validator::Json<PriceAdjustmentRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::vehicle::adjust_prices(&identity, request.into_inner()).await;
+
+    match result {
+        Ok(adjustment) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(adjustment))),
+        Err(error) => Err(error),
+    }
+}
+
 pub fn configure(config: &mut web::ServiceConfig) {
     config
         .service(create)
         .service(list)
+        // Before /vehicles/{vehicle_id}, which would take "search" for an ID
+        .service(search)
+        .service(suggestions)
         .service(update)
+        .service(set_status)
+        .service(delete)
         .service(get)
-        .service(list_bookings);
+        .service(similar)
+        .service(add_images)
+        .service(get_image)
+        .service(list_bookings)
+        .service(adjust_prices);
 }
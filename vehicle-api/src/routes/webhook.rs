@@ -0,0 +1,37 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Result};
+
+use crate::controllers;
+use crate::error::AppError;
+use crate::services::payments::stripe::SIGNATURE_HEADER;
+
+/// GET /webhooks/signing-keys - JWKS used to verify webhook signatures (public)
+#[get("/webhooks/signing-keys")]
+async fn signing_keys() -> Result<HttpResponse, AppError> {
+    let result = controllers::webhook::signing_keys().await;
+
+    match result {
+        Ok(jwks) => Ok(HttpResponse::Ok().json(jwks)),
+        Err(error) => Err(error),
+    }
+}
+
+/// POST /webhooks/stripe - PaymentIntent events from Stripe (public, signed by Stripe)
+#[post("/webhooks/stripe")]
+async fn stripe(req: HttpRequest, body: web::Bytes) -> Result<HttpResponse, AppError> {
+    let signature = req
+        .headers()
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| AppError::bad_request("Missing Stripe-Signature header"))?;
+
+    let result = controllers::payment::stripe_webhook(&body, signature).await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().finish()),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config.service(signing_keys).service(stripe);
+}
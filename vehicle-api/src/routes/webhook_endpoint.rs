@@ -0,0 +1,84 @@
+use actix_web::web::ReqData;
+use actix_web::{delete, get, post, web, HttpResponse, Result};
+use actix_web_grants::proc_macro::protect;
+use bson::oid::ObjectId;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::identity::Role;
+use crate::controllers;
+use crate::error::AppError;
+use crate::models::CreateWebhookEndpointRequest;
+use crate::util::pagination::PageQuery;
+use crate::{util, validator};
+
+fn parse_endpoint_id(path: web::Path<String>) -> Result<ObjectId, AppError> {
+    ObjectId::parse_str(path.into_inner())
+        .map_err(|_| AppError::bad_request("Invalid webhook endpoint ID format"))
+}
+
+/// POST /webhooks - Register a callback URL for booking events (Admin only)
+#[post("/webhooks")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn create(
+    identity: ReqData<Identity>,
+    request: validator::Json<CreateWebhookEndpointRequest>,
+) -> Result<HttpResponse, AppError> {
+    let result = controllers::webhook_endpoint::create(&identity, request.into_inner()).await;
+
+    match result {
+        Ok(endpoint) => Ok(HttpResponse::Created().json(util::util_serde::to_value(endpoint))),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /webhooks - List the registered endpoints (Admin only)
+#[get("/webhooks")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn list(web::Query(page): web::Query<PageQuery>) -> Result<HttpResponse, AppError> {
+    let result = controllers::webhook_endpoint::list(page).await;
+
+    match result {
+        Ok(endpoints) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(endpoints))),
+        Err(error) => Err(error),
+    }
+}
+
+/// DELETE /webhooks/{endpoint_id} - Stop sending events to an endpoint (Admin only)
+#[delete("/webhooks/{endpoint_id}")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn delete(path: web::Path<String>) -> Result<HttpResponse, AppError> {
+    let endpoint_id = parse_endpoint_id(path)?;
+
+    let result = controllers::webhook_endpoint::delete(&endpoint_id).await;
+
+    match result {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(error) => Err(error),
+    }
+}
+
+/// GET /webhooks/{endpoint_id}/deliveries?page=&limit= - Deliveries of an endpoint with
+/// their status, newest first (Admin only)
+#[get("/webhooks/{endpoint_id}/deliveries")]
+#[protect("Role::Admin", ty = "crate::authentication::identity::Role")]
+async fn deliveries(
+    path: web::Path<String>,
+    web::Query(page): web::Query<PageQuery>,
+) -> Result<HttpResponse, AppError> {
+    let endpoint_id = parse_endpoint_id(path)?;
+
+    let result = controllers::webhook_endpoint::deliveries(&endpoint_id, page).await;
+
+    match result {
+        Ok(deliveries) => Ok(HttpResponse::Ok().json(util::util_serde::to_value(deliveries))),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        .service(create)
+        .service(list)
+        .service(delete)
+        .service(deliveries);
+}
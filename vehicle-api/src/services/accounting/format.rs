@@ -0,0 +1,209 @@
+use chrono::{NaiveDate, Utc};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{ExportFormat, LedgerEntry, LedgerEntryKind};
+use crate::util::util_serde;
+
+/// Columns of the CSV export
+const CSV_HEADER: &str =
+    "recorded_at,booking_id,customer_id,sequence,kind,amount,balance,currency,description,payment_id";
+
+/// Accounts and numbers of the DATEV export, from the DATEV_* variables
+#[derive(Clone, Debug, PartialEq)]
+pub struct DatevSettings {
+    pub consultant_number: String,
+    pub client_number: String,
+    pub customer_account: String, // Debitor booked against
+    pub revenue_account: String,  // Charges and fees
+    pub deposit_account: String,  // Deposits are a liability, not revenue
+    pub currency: String,
+}
+
+impl DatevSettings {
+    pub fn from_env() -> Self {
+        let read =
+            |name: &str, default: &str| std::env::var(name).unwrap_or_else(|_| default.to_string());
+        Self {
+            consultant_number: read("DATEV_CONSULTANT_NUMBER", "1001"),
+            client_number: read("DATEV_CLIENT_NUMBER", "1"),
+            customer_account: read("DATEV_CUSTOMER_ACCOUNT", "10000"),
+            revenue_account: read("DATEV_REVENUE_ACCOUNT", "8400"),
+            deposit_account: read("DATEV_DEPOSIT_ACCOUNT", "1590"),
+            currency: read("ACCOUNTING_CURRENCY", "EUR"),
+        }
+    }
+}
+
+/// File content of the entries of a period in a format
+pub fn render(
+    format: ExportFormat,
+    from: NaiveDate,
+    to: NaiveDate,
+    entries: &[LedgerEntry],
+) -> AppResult<Vec<u8>> {
+    match format {
+        ExportFormat::Csv => Ok(csv(entries).into_bytes()),
+        // Amounts as strings, as the API sends them
+        ExportFormat::Json => {
+            serde_json::to_vec_pretty(&util_serde::to_value(entries)).map_err(|e| {
+                AppError::internal_server_error(format!("Cannot serialize ledger entries: {}", e))
+            })
+        }
+        ExportFormat::Datev => {
+            let settings = DatevSettings::from_env();
+            // DATEV files are read as Windows-1252, ASCII only keeps every reader happy
+            Ok(datev(&settings, from, to, entries)
+                .chars()
+                .map(|c| if c.is_ascii() { c } else { '?' })
+                .collect::<String>()
+                .into_bytes())
+        }
+    }
+}
+
+/// Quote a field when it holds a separator, a quote or a line break
+fn quote(field: &str, separator: char) -> String {
+    if field.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv(entries: &[LedgerEntry]) -> String {
+    let mut lines = vec![CSV_HEADER.to_string()];
+    lines.extend(entries.iter().map(|entry| {
+        [
+            entry.recorded_at.to_rfc3339(),
+            entry.booking_id.to_hex(),
+            quote(&entry.customer_id, ','),
+            entry.sequence.to_string(),
+            entry.kind.to_string(),
+            format!("{:.2}", entry.amount.amount),
+            format!("{:.2}", entry.balance.amount),
+            entry.amount.currency.to_string(),
+            quote(&entry.description, ','),
+            entry.payment_id.map(|id| id.to_hex()).unwrap_or_default(),
+        ]
+        .join(",")
+    }));
+    lines.join("\r\n") + "\r\n"
+}
+
+/// DATEV Buchungsstapel: a header line, the column names, then one booking line per
+/// entry with the amount unsigned and its side as S (debit) or H (credit)
+fn datev(
+    settings: &DatevSettings,
+    from: NaiveDate,
+    to: NaiveDate,
+    entries: &[LedgerEntry],
+) -> String {
+    let header = format!(
+        "\"EXTF\";700;21;\"Buchungsstapel\";13;{};;\"RE\";\"\";\"\";{};{};{};4;{};{};\"Ledger\";\"\";1;0;0;\"{}\"",
+        Utc::now().format("%Y%m%d%H%M%S%3f"),
+        settings.consultant_number,
+        settings.client_number,
+        from.format("%Y0101"),
+        from.format("%Y%m%d"),
+        to.format("%Y%m%d"),
+        settings.currency,
+    );
+    let columns = "Umsatz (ohne Soll/Haben-Kz);Soll/Haben-Kennzeichen;WKZ Umsatz;Konto;Gegenkonto (ohne BU-Schl\u{fc}ssel);Belegdatum;Belegfeld 1;Buchungstext";
+
+    let mut lines = vec![header, columns.to_string()];
+    lines.extend(entries.iter().map(|entry| {
+        let side = if entry.amount.amount.is_sign_negative() {
+            "H"
+        } else {
+            "S"
+        };
+        let counter_account = match entry.kind {
+            LedgerEntryKind::Deposit => &settings.deposit_account,
+            LedgerEntryKind::Charge | LedgerEntryKind::Fee | LedgerEntryKind::Refund => {
+                &settings.revenue_account
+            }
+        };
+        // Booking texts are limited to 60 characters
+        let text: String = entry.description.chars().take(60).collect();
+        [
+            format!("{:.2}", entry.amount.amount.abs()).replace('.', ","),
+            side.to_string(),
+            entry.amount.currency.to_string(),
+            settings.customer_account.clone(),
+            counter_account.clone(),
+            entry.recorded_at.format("%d%m").to_string(),
+            format!("\"{}\"", entry.booking_id.to_hex()),
+            format!("\"{}\"", text.replace('"', "'")),
+        ]
+        .join(";")
+    }));
+    lines.join("\r\n") + "\r\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Currency, Decimal, Money, NewLedgerEntry};
+    use bson::oid::ObjectId;
+
+    fn entries() -> Vec<LedgerEntry> {
+        let booking_id = ObjectId::new();
+        let entry = |kind, amount, description: &str| NewLedgerEntry {
+            booking_id,
+            customer_id: "customer_user_1".to_string(),
+            kind,
+            amount: Money::new(amount, Currency::EUR),
+            description: description.to_string(),
+            payment_id: None,
+            recorded_by: "admin_user_1".to_string(),
+        };
+        let charge = LedgerEntry::append(
+            None,
+            entry(
+                LedgerEntryKind::Charge,
+                Decimal::from(240),
+                "Rental, \"premium\" package",
+            ),
+        )
+        .unwrap();
+        let refund = LedgerEntry::append(
+            Some(&charge),
+            entry(
+                LedgerEntryKind::Refund,
+                Decimal::new(405, 1),
+                "Late delivery",
+            ),
+        )
+        .unwrap();
+        vec![charge, refund]
+    }
+
+    #[test]
+    fn test_csv_quotes_descriptions() {
+        let csv = csv(&entries());
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[1].contains(",CHARGE,240.00,240.00,EUR,\"Rental, \"\"premium\"\" package\","));
+        assert!(lines[2].contains(",REFUND,-40.50,199.50,EUR,Late delivery,"));
+    }
+
+    #[test]
+    fn test_datev_books_refunds_on_the_credit_side() {
+        let settings = DatevSettings {
+            consultant_number: "1001".to_string(),
+            client_number: "1".to_string(),
+            customer_account: "10000".to_string(),
+            revenue_account: "8400".to_string(),
+            deposit_account: "1590".to_string(),
+            currency: "EUR".to_string(),
+        };
+        let day = NaiveDate::from_ymd_opt(2025, 8, 1).unwrap();
+        let datev = datev(&settings, day, day, &entries());
+        let lines: Vec<&str> = datev.lines().collect();
+
+        assert!(lines[0].starts_with("\"EXTF\";700;21;\"Buchungsstapel\""));
+        assert!(lines[2].starts_with("240,00;S;EUR;10000;8400;"));
+        assert!(lines[3].starts_with("40,50;H;EUR;10000;8400;"));
+    }
+}
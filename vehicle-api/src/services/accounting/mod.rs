@@ -0,0 +1,149 @@
+pub mod format;
+
+use bson::doc;
+use chrono::{Duration, NaiveDate, Utc};
+use mongodb::options::FindOptions;
+use std::str::FromStr;
+
+use crate::error::AppResult;
+use crate::models::{
+    AccountingExport, CreateExportRequest, ExportFormat, ExportPeriod, ExportStatus, LedgerEntry,
+};
+use crate::services;
+use crate::services::storage::{Storage, STORAGE_BACKEND};
+
+/// An export still RUNNING after this long is considered crashed and done again
+const STALE_EXPORT_MINUTES: i64 = 60;
+
+/// Period and format of the scheduled export, None when ACCOUNTING_EXPORT_PERIOD
+/// (daily or monthly) is not set
+fn schedule() -> Option<(ExportPeriod, ExportFormat)> {
+    let period = ExportPeriod::from_str(&std::env::var("ACCOUNTING_EXPORT_PERIOD").ok()?).ok()?;
+    let format = std::env::var("ACCOUNTING_EXPORT_FORMAT")
+        .ok()
+        .and_then(|format| ExportFormat::from_str(&format).ok())
+        .unwrap_or(ExportFormat::Csv);
+    Some((period, format))
+}
+
+/// Export the last completed period in the background, checked every hour so a period
+/// missed while the server was down is exported on the next start
+pub fn spawn_scheduler() {
+    let Some((period, format)) = schedule() else {
+        log::info!("Scheduled accounting export disabled");
+        return;
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            ticker.tick().await;
+            let (from, to) = period.last_completed(Utc::now().date_naive());
+            match export_once(from, to, format).await {
+                Ok(Some(export)) => log::info!(
+                    "Accounting export {} to {} finished {}",
+                    from,
+                    to,
+                    export.status
+                ),
+                Ok(None) => {}
+                Err(error) => log::error!("Scheduled accounting export failed: {}", error),
+            }
+        }
+    });
+}
+
+/// Run the scheduled export of a period unless it already succeeded or is running
+async fn export_once(
+    from: NaiveDate,
+    to: NaiveDate,
+    format: ExportFormat,
+) -> AppResult<Option<AccountingExport>> {
+    let stale = Utc::now() - Duration::minutes(STALE_EXPORT_MINUTES);
+    let filter = doc! {
+        "from": from.to_string(),
+        "to": to.to_string(),
+        "format": format.to_string(),
+        "requested_by": null,
+        "$or": [
+            { "status": ExportStatus::Succeeded.to_string() },
+            {
+                "status": ExportStatus::Running.to_string(),
+                "started_at": { "$gte": bson::DateTime::from_chrono(stale) },
+            },
+        ],
+    };
+    if services::mongodb::get_one::<AccountingExport>(filter, None)
+        .await?
+        .is_some()
+    {
+        return Ok(None);
+    }
+
+    let export = start(CreateExportRequest { from, to, format }, None).await?;
+    Ok(Some(run(export).await))
+}
+
+/// Record a new RUNNING export, `run` produces its file
+pub async fn start(
+    request: CreateExportRequest,
+    requested_by: Option<String>,
+) -> AppResult<AccountingExport> {
+    let mut export = AccountingExport::new(request, requested_by);
+    export.id = Some(services::mongodb::insert_one(&export, None).await?);
+    Ok(export)
+}
+
+/// Produce the file of an export and deliver it to the storage, returns the export
+/// SUCCEEDED or FAILED
+pub async fn run(mut export: AccountingExport) -> AccountingExport {
+    match deliver(&export).await {
+        Ok((entries, location)) => {
+            export.status = ExportStatus::Succeeded;
+            export.entries = entries;
+            export.location = Some(location);
+        }
+        Err(error) => {
+            log::error!("Accounting export {:?} failed: {}", export.id, error);
+            export.status = ExportStatus::Failed;
+            export.error = Some(error.to_string());
+        }
+    }
+    export.finished_at = Some(Utc::now());
+
+    if let Err(error) =
+        services::mongodb::find_one_and_replace(doc! { "_id": export.id }, &export, None).await
+    {
+        log::error!(
+            "Failed to save accounting export {:?}: {}",
+            export.id,
+            error
+        );
+    }
+    export
+}
+
+/// Ledger entries recorded over the export period (UTC days), returns their number
+/// and the location of the file
+async fn deliver(export: &AccountingExport) -> AppResult<(u32, String)> {
+    let start = export.from.and_time(chrono::NaiveTime::MIN).and_utc();
+    let end = (export.to + Duration::days(1))
+        .and_time(chrono::NaiveTime::MIN)
+        .and_utc();
+    let filter = doc! {
+        "recorded_at": {
+            "$gte": bson::DateTime::from_chrono(start),
+            "$lt": bson::DateTime::from_chrono(end),
+        },
+    };
+    let options = FindOptions::builder()
+        .sort(doc! { "recorded_at": 1, "booking_id": 1, "sequence": 1 })
+        .build();
+    let entries: Vec<LedgerEntry> = services::mongodb::collect_many(filter, options).await?;
+
+    let body = format::render(export.format, export.from, export.to, &entries)?;
+    let location = STORAGE_BACKEND
+        .put(&export.key(), export.format.content_type(), body)
+        .await?;
+    Ok((entries.len() as u32, location))
+}
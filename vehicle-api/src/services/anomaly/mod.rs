@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, HashSet};
+
+use bson::doc;
+use chrono::{Duration, Utc};
+
+use crate::error::AppResult;
+use crate::models::{Anomaly, AnomalyThresholds, Booking, Vehicle};
+use crate::services;
+
+/// Seconds between two scans (ANOMALY_SCAN_INTERVAL_SECS, default 1 hour, 0 disables the job)
+fn scan_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("ANOMALY_SCAN_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(3600);
+
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// Run the anomaly scan periodically in the background
+pub fn spawn_scheduler() {
+    let Some(interval) = scan_interval() else {
+        log::info!("Anomaly detection job disabled");
+        return;
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match scan().await {
+                Ok(anomalies) => log::info!("Anomaly scan raised {} anomalies", anomalies.len()),
+                Err(error) => log::error!("Anomaly scan failed: {}", error),
+            }
+        }
+    });
+}
+
+/// Look for anomalies in the recent bookings and the fleet. Returns the newly raised
+/// ones; an anomaly already open for the same subject only has its evidence refreshed.
+pub async fn scan() -> AppResult<Vec<Anomaly>> {
+    let thresholds = AnomalyThresholds::from_env();
+    let now = Utc::now();
+
+    let window_start = now - Duration::hours(thresholds.window_hours);
+    let (recent_bookings, vehicles, booked_vehicle_ids) = services::fanout::try_join3(
+        services::mongodb::collect_many::<Booking>(
+            doc! { "order_date": { "$gte": bson::DateTime::from_chrono(window_start) } },
+            None,
+        ),
+        services::mongodb::collect_many::<Vehicle>(doc! {}, None),
+        services::mongodb::distinct::<Booking>("vehicle_id", doc! {}),
+    )
+    .await?;
+    let booked_vehicle_ids: HashSet<_> = booked_vehicle_ids
+        .into_iter()
+        .filter_map(|value| value.as_object_id())
+        .collect();
+
+    let detected = Anomaly::detect(
+        &recent_bookings,
+        &vehicles,
+        &booked_vehicle_ids,
+        &thresholds,
+        now,
+    );
+
+    let mut raised = Vec::new();
+    for mut anomaly in detected {
+        let open_filter = doc! {
+            "kind": anomaly.kind.to_string(),
+            "subject": &anomaly.subject,
+            "acknowledged_at": null,
+        };
+        let open: Option<Anomaly> = services::mongodb::get_one(open_filter.clone(), None).await?;
+
+        match open {
+            Some(open) => {
+                anomaly.id = open.id;
+                anomaly.detected_at = open.detected_at;
+                services::mongodb::find_one_and_replace(open_filter, &anomaly, None).await?;
+            }
+            None => {
+                anomaly.id = Some(services::mongodb::insert_one(&anomaly, None).await?);
+                notify(&anomaly);
+                raised.push(anomaly);
+            }
+        }
+    }
+
+    Ok(raised)
+}
+
+/// Report a new anomaly to Sentry with its evidence attached
+fn notify(anomaly: &Anomaly) {
+    log::warn!(
+        "Anomaly {} on {}: {}",
+        anomaly.kind,
+        anomaly.subject,
+        anomaly.message
+    );
+
+    let mut extra = BTreeMap::new();
+    extra.insert(
+        "anomaly_id".to_string(),
+        sentry::protocol::Value::from(anomaly.id.map(|id| id.to_hex())),
+    );
+    extra.insert(
+        "booking_ids".to_string(),
+        sentry::protocol::Value::from(
+            anomaly
+                .booking_ids
+                .iter()
+                .map(|id| id.to_hex())
+                .collect::<Vec<_>>(),
+        ),
+    );
+
+    let mut tags = BTreeMap::new();
+    tags.insert("anomaly_kind".to_string(), anomaly.kind.to_string());
+    tags.insert("anomaly_subject".to_string(), anomaly.subject.clone());
+
+    sentry::capture_event(sentry::protocol::Event {
+        message: Some(format!("Anomaly {}: {}", anomaly.kind, anomaly.message)),
+        level: sentry::Level::Warning,
+        extra,
+        tags,
+        ..Default::default()
+    });
+}
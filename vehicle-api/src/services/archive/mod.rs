@@ -0,0 +1,139 @@
+use bson::{doc, oid::ObjectId, Document};
+use chrono::{Months, NaiveDate, Utc};
+use mongodb::options::{FindOptions, UpdateOptions};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{ArchivedBooking, Booking};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// Bookings moved per round trip
+const BATCH_SIZE: i64 = 500;
+
+/// Seconds between two archive runs (BOOKING_ARCHIVE_INTERVAL_SECS, default 1 day,
+/// 0 disables the job)
+fn run_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("BOOKING_ARCHIVE_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(86_400);
+
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// Months after the end of a rental its booking is archived
+/// (BOOKING_ARCHIVE_AFTER_MONTHS, default 12)
+pub fn archive_after() -> Months {
+    let months = std::env::var("BOOKING_ARCHIVE_AFTER_MONTHS")
+        .ok()
+        .and_then(|value| value.parse::<u32>().ok())
+        .filter(|months| *months > 0)
+        .unwrap_or(12);
+
+    Months::new(months)
+}
+
+/// Bookings done with, confirmed, rejected or cancelled, whose rental ended before `cutoff`
+pub fn archivable(cutoff: NaiveDate) -> Document {
+    doc! {
+        "status": { "$in": ["CONFIRMED", "REJECTED", "CANCELLED"] },
+        "to_date": { "$lt": cutoff.to_string() },
+    }
+}
+
+/// Archive old bookings periodically in the background
+pub fn spawn_scheduler() {
+    let Some(interval) = run_interval() else {
+        log::info!("Booking archive job disabled");
+        return;
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match archive_old(Utc::now().date_naive()).await {
+                Ok(0) => {}
+                Ok(archived) => log::info!("{} old bookings archived", archived),
+                Err(error) => log::error!("Booking archive failed: {}", error),
+            }
+        }
+    });
+}
+
+/// Move every booking ended `archive_after` months before `today` to the archive
+/// collection, so the queries on bookings only go through recent ones. Returns the
+/// number of bookings archived.
+pub async fn archive_old(today: NaiveDate) -> AppResult<usize> {
+    let cutoff = today
+        .checked_sub_months(archive_after())
+        .unwrap_or(NaiveDate::MIN);
+    let filter = archivable(cutoff);
+    let options = FindOptions::builder()
+        .sort(doc! { "_id": 1 })
+        .limit(BATCH_SIZE)
+        .build();
+
+    let mut archived = 0;
+    loop {
+        let bookings: Vec<Booking> =
+            services::mongodb::collect_many(filter.clone(), options.clone()).await?;
+        for booking in &bookings {
+            archive(booking).await?;
+            archived += 1;
+        }
+        if bookings.len() < BATCH_SIZE as usize {
+            return Ok(archived);
+        }
+    }
+}
+
+/// Copy the booking to the archive then delete it, a run stopped in between copies it
+/// again the next time
+async fn archive(booking: &Booking) -> AppResult<()> {
+    let Some(booking_id) = booking.id else {
+        return Ok(());
+    };
+    let document = bson::to_document(booking).map_err(|e| {
+        AppError::internal_server_error(format!("Cannot serialize document: {}", e))
+    })?;
+    services::mongodb::update_one(
+        ArchivedBooking::get_collection(),
+        doc! { "_id": booking_id },
+        doc! { "$setOnInsert": document },
+        UpdateOptions::builder().upsert(true).build(),
+    )
+    .await?;
+    services::mongodb::delete_one(Booking::get_collection(), doc! { "_id": booking_id }, None).await
+}
+
+/// Archived booking, for reads of a booking missing from the bookings collection
+pub async fn find(booking_id: &ObjectId) -> AppResult<Option<Booking>> {
+    let archived: Option<ArchivedBooking> =
+        services::mongodb::get_one(doc! { "_id": booking_id }, None).await?;
+
+    Ok(archived.map(|archived| archived.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_finished_bookings_are_archivable() {
+        let cutoff = NaiveDate::from_ymd_opt(2024, 10, 16).unwrap();
+        let filter = archivable(cutoff);
+
+        assert_eq!(
+            filter.get_document("to_date").unwrap(),
+            &doc! { "$lt": "2024-10-16" }
+        );
+        let statuses = filter
+            .get_document("status")
+            .unwrap()
+            .get_array("$in")
+            .unwrap();
+        assert!(!statuses.contains(&bson::Bson::from("PENDING")));
+        assert_eq!(statuses.len(), 3);
+    }
+}
@@ -0,0 +1,23 @@
+use crate::models::{AuthAuditEntry, AuthOutcome};
+use crate::services;
+
+/// Store an authentication attempt in the audit log. The insert runs in the
+/// background so a slow database does not hold the request; a lost entry is logged.
+pub fn record(entry: AuthAuditEntry) {
+    if entry.outcome == AuthOutcome::Failure {
+        log::warn!(
+            "Authentication failed ({}) from {} on {} {}: {}",
+            entry.method,
+            entry.ip.as_deref().unwrap_or("unknown address"),
+            entry.http_method,
+            entry.path,
+            entry.reason.as_deref().unwrap_or_default()
+        );
+    }
+
+    actix_web::rt::spawn(async move {
+        if let Err(error) = services::mongodb::insert_one(&entry, None).await {
+            log::error!("Failed to store authentication audit entry: {}", error);
+        }
+    });
+}
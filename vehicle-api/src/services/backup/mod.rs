@@ -0,0 +1,222 @@
+use bson::{doc, Bson, Document};
+use chrono::{Duration, Utc};
+use futures::TryStreamExt;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    dump_line, parse_line, Backup, BackupCollection, BackupStatus, Booking, RestoreCheck,
+    RestoreReport, Vehicle, MAX_RESTORE_ERRORS,
+};
+use crate::services;
+use crate::services::mongodb::{MongoStruct, DATABASE_NAME};
+use crate::services::storage::{Storage, STORAGE_BACKEND};
+
+/// A backup still RUNNING after this long is considered crashed and done again
+const STALE_BACKUP_MINUTES: i64 = 6 * 60;
+
+/// `_id`s looked up at once by the restore dry run
+const EXISTING_BATCH: usize = 1000;
+
+/// Hours between two scheduled backups, None when BACKUP_INTERVAL_HOURS is not set
+fn schedule() -> Option<i64> {
+    std::env::var("BACKUP_INTERVAL_HOURS")
+        .ok()
+        .and_then(|hours| hours.parse::<i64>().ok())
+        .filter(|hours| *hours > 0)
+}
+
+/// Back the database up in the background, checked every hour so a backup missed while
+/// the server was down is made on the next start
+pub fn spawn_scheduler() {
+    let Some(hours) = schedule() else {
+        log::info!("Scheduled backup disabled");
+        return;
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            ticker.tick().await;
+            match backup_once(Duration::hours(hours)).await {
+                Ok(Some(backup)) => log::info!("Scheduled backup finished {}", backup.status),
+                Ok(None) => {}
+                Err(error) => log::error!("Scheduled backup failed: {}", error),
+            }
+        }
+    });
+}
+
+/// Run a backup unless one succeeded over the last `interval` or one is running
+async fn backup_once(interval: Duration) -> AppResult<Option<Backup>> {
+    let now = Utc::now();
+    let stale = now - Duration::minutes(STALE_BACKUP_MINUTES);
+    let filter = doc! {
+        "$or": [
+            {
+                "status": BackupStatus::Succeeded.to_string(),
+                "started_at": { "$gte": bson::DateTime::from_chrono(now - interval) },
+            },
+            {
+                "status": BackupStatus::Running.to_string(),
+                "started_at": { "$gte": bson::DateTime::from_chrono(stale) },
+            },
+        ],
+    };
+    if services::mongodb::get_one::<Backup>(filter, None)
+        .await?
+        .is_some()
+    {
+        return Ok(None);
+    }
+
+    let backup = start(None).await?;
+    Ok(Some(run(backup).await))
+}
+
+/// Record a new RUNNING backup, `run` dumps the collections
+pub async fn start(requested_by: Option<String>) -> AppResult<Backup> {
+    let mut backup = Backup::new(requested_by);
+    backup.id = Some(services::mongodb::insert_one(&backup, None).await?);
+    Ok(backup)
+}
+
+/// Dump every collection to the storage, returns the backup SUCCEEDED or FAILED
+pub async fn run(mut backup: Backup) -> Backup {
+    match dump(&mut backup).await {
+        Ok(()) => backup.status = BackupStatus::Succeeded,
+        Err(error) => {
+            log::error!("Backup {:?} failed: {}", backup.id, error);
+            backup.status = BackupStatus::Failed;
+            backup.error = Some(error.to_string());
+        }
+    }
+    backup.finished_at = Some(Utc::now());
+
+    if let Err(error) =
+        services::mongodb::find_one_and_replace(doc! { "_id": backup.id }, &backup, None).await
+    {
+        log::error!("Failed to save backup {:?}: {}", backup.id, error);
+    }
+    backup
+}
+
+/// Collections of the database, every tenant included, one file each. The collection
+/// of the backups is left out, it changes while they run.
+async fn dump(backup: &mut Backup) -> AppResult<()> {
+    let database = services::mongodb::get_database(DATABASE_NAME).await?;
+    let mut names = database.list_collection_names().await?;
+    names.retain(|name| name != Backup::get_collection());
+    names.sort();
+
+    for name in names {
+        let mut documents = 0;
+        let mut body = Vec::new();
+        let mut cursor = database
+            .collection::<Document>(&name)
+            .find(doc! {})
+            .sort(doc! { "_id": 1 })
+            .await?;
+        while let Some(document) = cursor.try_next().await? {
+            body.extend_from_slice(dump_line(document).as_bytes());
+            body.push(b'\n');
+            documents += 1;
+        }
+
+        let location = STORAGE_BACKEND
+            .put(&backup.key(&name), "application/x-ndjson", body)
+            .await?;
+        backup.collections.push(BackupCollection {
+            name,
+            documents,
+            location,
+        });
+    }
+    Ok(())
+}
+
+/// Read back every file of a succeeded backup and check its documents, without writing
+pub async fn restore_dry_run(backup: &Backup) -> AppResult<RestoreReport> {
+    let Some(backup_id) = backup.id else {
+        return Err(AppError::not_found("Backup not found"));
+    };
+    if backup.status != BackupStatus::Succeeded {
+        return Err(AppError::conflict(format!(
+            "Backup is {}, only succeeded backups can be restored",
+            backup.status
+        )));
+    }
+
+    let database = services::mongodb::get_database(DATABASE_NAME).await?;
+    let mut errors = Vec::new();
+    let mut collections = Vec::with_capacity(backup.collections.len());
+    for collection in &backup.collections {
+        let mut check = RestoreCheck {
+            name: collection.name.clone(),
+            expected: collection.documents,
+            ..RestoreCheck::default()
+        };
+        let mut report = |error: String| {
+            if errors.len() < MAX_RESTORE_ERRORS {
+                errors.push(format!("{}: {}", collection.name, error));
+            }
+        };
+
+        let body = match STORAGE_BACKEND.get(&backup.key(&collection.name)).await {
+            Ok(body) => body,
+            Err(error) => {
+                report(format!("cannot read the file: {}", error));
+                collections.push(check);
+                continue;
+            }
+        };
+
+        let mut ids: Vec<Bson> = Vec::new();
+        for (index, line) in String::from_utf8_lossy(&body).lines().enumerate() {
+            check.documents += 1;
+            let checked = parse_line(line).and_then(|document| {
+                check_model(&collection.name, &document)?;
+                Ok(document)
+            });
+            match checked {
+                Ok(mut document) => ids.extend(document.remove("_id")),
+                Err(error) => {
+                    check.invalid += 1;
+                    report(format!("line {}: {}", index + 1, error));
+                }
+            }
+        }
+        if check.documents != check.expected {
+            report(format!(
+                "{} documents instead of {}",
+                check.documents, check.expected
+            ));
+        }
+
+        let existing = database.collection::<Document>(&collection.name);
+        for batch in ids.chunks(EXISTING_BATCH) {
+            check.existing += existing
+                .count_documents(doc! { "_id": { "$in": batch } })
+                .await?;
+        }
+        collections.push(check);
+    }
+
+    Ok(RestoreReport {
+        backup_id,
+        valid: errors.is_empty() && collections.iter().all(RestoreCheck::is_valid),
+        collections,
+        errors,
+    })
+}
+
+/// Documents of the collections the API reads the most must still load as its models
+fn check_model(collection: &str, document: &Document) -> Result<(), String> {
+    let loaded = if collection == Booking::get_collection() {
+        bson::from_document::<Booking>(document.clone()).map(|_| ())
+    } else if collection == Vehicle::get_collection() {
+        bson::from_document::<Vehicle>(document.clone()).map(|_| ())
+    } else {
+        return Ok(());
+    };
+    loaded.map_err(|e| format!("does not load as a {}: {}", collection, e))
+}
@@ -0,0 +1,447 @@
+use bson::{doc, oid::ObjectId};
+use chrono::{NaiveDate, NaiveTime, Utc};
+
+use crate::authentication::identity::{Identity, Role};
+use crate::domain::events::{BookingCreated, BookingDatesChanged, BookingStatusChanged};
+use crate::domain::{Undo, UnitOfWork};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    exclude_deleted, AutoConfirmContext, Booking, BookingStatus, CancellationPolicy,
+    CreateBookingRequest, Currency, Decimal, LedgerEntryKind, LedgerTotals, Money, NewLedgerEntry,
+    OutsideHoursAction, UpdateBookingRequest, Vehicle, VehicleType, AUTO_CONFIRM_ACTOR,
+};
+use crate::services;
+use crate::services::email::BookingEmail;
+use crate::services::mongodb::MongoStruct;
+use crate::util;
+use crate::validator;
+
+/// Make a booking already validated for `identity`: priced, scored, confirmed when a rule
+/// allows it, holding its days and charged when confirmed. Runs as a unit of work, a
+/// failing step leaves nothing behind and publishes nothing. The booking is returned
+/// sealed, as stored.
+pub async fn create(
+    identity: &Identity,
+    request: CreateBookingRequest,
+    client_country: Option<String>,
+) -> AppResult<Booking> {
+    let mut work = UnitOfWork::new();
+    let result = make_booking(&mut work, identity, request, client_country).await;
+    let booking = work.finish(result).await?;
+
+    services::email::notify_customer(BookingEmail::for_status(&booking.status), &booking).await;
+    Ok(booking)
+}
+
+async fn make_booking(
+    work: &mut UnitOfWork,
+    identity: &Identity,
+    request: CreateBookingRequest,
+    client_country: Option<String>,
+) -> AppResult<Booking> {
+    // Check if vehicle exists, soft deleted ones cannot be booked anymore
+    let mut vehicle_filter = doc! { "_id": request.vehicle_id };
+    exclude_deleted(&mut vehicle_filter);
+    let vehicle: Vehicle = services::mongodb::get_one(vehicle_filter, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Vehicle not found"))?;
+
+    // No pickup or return on blackout holidays
+    let calendar = services::holidays::get_calendar(
+        &services::holidays::vehicle_country(&vehicle),
+        request.from_date,
+        request.to_date,
+    )
+    .await?;
+    if let Some(issue) =
+        validator::booking::check_blackout_dates(request.from_date, request.to_date, &calendar)
+            .into_iter()
+            .next()
+    {
+        let message = validator::metrics::reject_issue("CreateBookingRequest", issue);
+        return Err(AppError::bad_request(message));
+    }
+
+    // Booking dates are local to the vehicle's branch, store the UTC boundaries too
+    let tz = util::timezone::parse_timezone(&vehicle.timezone)
+        .map_err(AppError::internal_server_error)?;
+    let (starts_at, ends_at) =
+        util::timezone::booking_bounds(request.from_date, request.to_date, tz);
+
+    // A pickup outside office hours is refused, or left for a manager to confirm
+    let mut outside_office_hours = false;
+    if let Some(hours) = services::office_hours::for_vehicle(&vehicle).await? {
+        let now = Utc::now().with_timezone(&tz).naive_local();
+        if let Some(issue) = validator::booking::check_office_hours(request.from_date, now, &hours)
+        {
+            if hours.outside_hours == OutsideHoursAction::Reject {
+                let message = validator::metrics::reject_issue("CreateBookingRequest", issue);
+                return Err(AppError::bad_request(message));
+            }
+            outside_office_hours = true;
+        }
+    }
+
+    let (price, _) =
+        validator::booking::estimate_price(request.from_date, request.to_date, &vehicle, &calendar);
+
+    // Create the booking
+    let mut booking = Booking::new(request, identity.user_id.clone());
+    booking.timezone = vehicle.timezone.clone();
+    booking.starts_at = Some(starts_at);
+    booking.ends_at = Some(ends_at);
+    booking.total_price = price;
+
+    // Score the fraud risk, high risk bookings wait for a manager's confirmation
+    let assessment = match services::risk::assess_booking(
+        &booking,
+        identity.email.clone(),
+        client_country,
+        services::holidays::vehicle_country(&vehicle),
+    )
+    .await
+    {
+        Ok(assessment) => Some(assessment),
+        Err(error) => {
+            log::error!("Risk assessment failed, booking left pending: {}", error);
+            None
+        }
+    };
+
+    // Qualifying bookings skip the PENDING state, unless nobody staffs the pickup
+    if !outside_office_hours
+        && assessment
+            .as_ref()
+            .is_some_and(|assessment| !assessment.manual_confirmation)
+    {
+        let (policy, transitions, confirmed_bookings) = services::fanout::try_join3(
+            services::mongodb::booking::get_auto_confirm_policy(),
+            services::mongodb::booking::get_booking_policy(),
+            services::mongodb::count(
+                Booking::get_collection(),
+                doc! { "customer_id": &booking.customer_id, "status": "CONFIRMED" },
+                None,
+            ),
+        )
+        .await?;
+        let context = AutoConfirmContext {
+            customer_id: booking.customer_id.clone(),
+            confirmed_bookings,
+            // Rules cap prices in EUR, the base currency
+            price: price
+                .and_then(|price| services::currency::rates().convert(price, Currency::EUR))
+                .map(|price| price.amount),
+            vehicle_type: VehicleType::of(&vehicle),
+        };
+        if let Some(rule) = policy.find_rule(&context) {
+            let checked = validator::booking::check_auto_confirm(
+                &transitions,
+                context.vehicle_type,
+                &booking,
+                services::payments::stripe::is_enabled(),
+            );
+            match checked {
+                Ok(()) => booking.set_status(
+                    BookingStatus::Confirmed,
+                    AUTO_CONFIRM_ACTOR.to_string(),
+                    Some(rule.name.clone()),
+                ),
+                Err(error) => log::info!(
+                    "Auto-confirm rule {} not applied, booking left pending: {}",
+                    rule.name,
+                    error
+                ),
+            }
+        }
+    }
+
+    // Driver details are only stored encrypted
+    services::encryption::seal_booking(&mut booking).await?;
+
+    // The overlap check races with concurrent requests: the booking holds its days
+    // first, only one booking can hold a day
+    let inserted_id = ObjectId::new();
+    services::reservation::claim(
+        inserted_id,
+        booking.vehicle_id,
+        booking.from_date,
+        booking.to_date,
+    )
+    .await?;
+    work.on_rollback(Undo::ReleaseDays(inserted_id));
+    booking.id = Some(inserted_id);
+    services::mongodb::insert_one(&booking, None).await?;
+    work.on_rollback(Undo::DeleteBooking(inserted_id));
+
+    if let Some(mut assessment) = assessment {
+        if assessment.manual_confirmation {
+            log::warn!(
+                "Booking {} is high risk (score {}), manual confirmation required",
+                inserted_id,
+                assessment.score
+            );
+        }
+        assessment.booking_id = inserted_id;
+        services::mongodb::insert_one(&assessment, None).await?;
+    }
+
+    // The ledger is append only, it is written last
+    if booking.status == BookingStatus::Confirmed {
+        charge_rental(&booking, AUTO_CONFIRM_ACTOR).await?;
+    }
+    work.record(BookingCreated::of(&booking));
+    Ok(booking)
+}
+
+/// Apply an update already validated for `identity`: new dates held and priced again, new
+/// status with its ledger entries. Runs as a unit of work, a failing step puts the booking
+/// and its days back as they were. The booking is returned sealed, as stored.
+pub async fn update(
+    identity: &Identity,
+    booking: Booking,
+    request: UpdateBookingRequest,
+) -> AppResult<Booking> {
+    let changes_status = request
+        .status
+        .as_ref()
+        .is_some_and(|status| *status != booking.status);
+
+    let mut work = UnitOfWork::new();
+    let result = apply_update(&mut work, identity, booking, request).await;
+    let booking = work.finish(result).await?;
+
+    if changes_status {
+        services::email::notify_customer(BookingEmail::for_status(&booking.status), &booking).await;
+    }
+    Ok(booking)
+}
+
+async fn apply_update(
+    work: &mut UnitOfWork,
+    identity: &Identity,
+    mut booking: Booking,
+    request: UpdateBookingRequest,
+) -> AppResult<Booking> {
+    let booking_id = booking
+        .id
+        .ok_or_else(|| AppError::internal_server_error("Booking without ID"))?;
+    let stored = booking.clone();
+
+    // New dates are checked like a new booking, held, then priced again
+    let new_dates = validator::booking::requested_dates(&booking, &request);
+    if let Some((from_date, to_date)) = new_dates {
+        validator::booking::validate_date_change(&booking, from_date, to_date).await?;
+        change_dates(&mut booking, from_date, to_date).await?;
+        services::reservation::claim(booking_id, booking.vehicle_id, from_date, to_date).await?;
+        work.on_rollback(Undo::ReleaseDaysOutside {
+            booking_id,
+            from_date: stored.from_date,
+            to_date: stored.to_date,
+        });
+    }
+
+    // Update the booking status
+    let confirms = request.status == Some(BookingStatus::Confirmed)
+        && booking.status != BookingStatus::Confirmed;
+    let cancels_confirmed = matches!(request.status, Some(BookingStatus::Cancelled(_)))
+        && booking.status == BookingStatus::Confirmed;
+    let changes_status = request
+        .status
+        .as_ref()
+        .is_some_and(|status| *status != booking.status);
+    if cancels_confirmed && matches!(identity.role, Role::Customer) {
+        let policy = services::mongodb::booking::get_cancellation_policy().await?;
+        booking.cancellation_fee = Some(cancellation_fee(&booking, &policy));
+    }
+    if let Some(new_status) = request.status {
+        booking.set_status(new_status, identity.user_id.clone(), None);
+    }
+
+    // Save the updated booking
+    services::mongodb::find_one_and_replace(doc! { "_id": booking_id }, &booking, None)
+        .await?
+        .ok_or_else(|| AppError::internal_server_error("Failed to update booking"))?;
+    work.on_rollback(Undo::RestoreBooking(Box::new(stored.clone())));
+
+    // Cancelled and rejected bookings free their days, moved ones the days left behind
+    if matches!(
+        booking.status,
+        BookingStatus::Cancelled(_) | BookingStatus::Rejected(_)
+    ) {
+        services::reservation::release(booking_id).await?;
+    } else if new_dates.is_some() {
+        services::reservation::release_outside(booking_id, booking.from_date, booking.to_date)
+            .await?;
+    }
+
+    // The ledger is append only, it is written last
+    if confirms {
+        charge_rental(&booking, &identity.user_id).await?;
+    }
+    if cancels_confirmed {
+        settle_cancellation(&booking, &identity.user_id).await?;
+    }
+    if new_dates.is_some() {
+        work.record(BookingDatesChanged::of(
+            &booking,
+            stored.from_date,
+            stored.to_date,
+        ));
+    }
+    if changes_status {
+        work.record(BookingStatusChanged::of(&booking, stored.status));
+    }
+    Ok(booking)
+}
+
+/// Move a booking to new dates: blackout holidays, UTC boundaries and total price
+pub async fn change_dates(
+    booking: &mut Booking,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+) -> AppResult<()> {
+    let vehicle: Vehicle = services::mongodb::get_one(doc! { "_id": booking.vehicle_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Vehicle not found"))?;
+
+    let calendar = services::holidays::get_calendar(
+        &services::holidays::vehicle_country(&vehicle),
+        from_date,
+        to_date,
+    )
+    .await?;
+    if let Some(issue) =
+        validator::booking::check_blackout_dates(from_date, to_date, &calendar).first()
+    {
+        return Err(AppError::bad_request(&issue.message));
+    }
+
+    let tz = util::timezone::parse_timezone(&booking.timezone)
+        .map_err(AppError::internal_server_error)?;
+    let (starts_at, ends_at) = util::timezone::booking_bounds(from_date, to_date, tz);
+    let (price, _) = validator::booking::estimate_price(from_date, to_date, &vehicle, &calendar);
+
+    booking.from_date = from_date;
+    booking.to_date = to_date;
+    booking.starts_at = Some(starts_at);
+    booking.ends_at = Some(ends_at);
+    booking.total_price = price;
+    Ok(())
+}
+
+/// Charge the rental price on the ledger of a booking just confirmed. A booking paid
+/// online was charged by its payment already.
+async fn charge_rental(booking: &Booking, confirmed_by: &str) -> AppResult<()> {
+    let (Some(booking_id), Some(total_price)) = (booking.id, booking.total_price) else {
+        return Ok(());
+    };
+    if booking.paid_at.is_some() {
+        return Ok(());
+    }
+    services::ledger::append(NewLedgerEntry {
+        booking_id,
+        customer_id: booking.customer_id.clone(),
+        kind: LedgerEntryKind::Charge,
+        amount: total_price,
+        description: format!("Rental from {} to {}", booking.from_date, booking.to_date),
+        payment_id: None,
+        recorded_by: confirmed_by.to_string(),
+    })
+    .await?;
+    Ok(())
+}
+
+/// Fee the customer pays to cancel a confirmed booking now
+fn cancellation_fee(booking: &Booking, policy: &CancellationPolicy) -> Money {
+    // Bookings stored before the UTC boundaries start at midnight UTC
+    let starts_at = booking
+        .starts_at
+        .unwrap_or_else(|| booking.from_date.and_time(NaiveTime::MIN).and_utc());
+    let price = booking
+        .total_price
+        .unwrap_or_else(|| Money::zero(booking.currency()));
+    policy.quote(price, starts_at, Utc::now()).fee
+}
+
+/// Release the rental price of a cancelled booking on its ledger, keeping the
+/// cancellation fee the customer owes
+async fn settle_cancellation(booking: &Booking, cancelled_by: &str) -> AppResult<()> {
+    let Some(booking_id) = booking.id else {
+        return Ok(());
+    };
+    let totals = LedgerTotals::compute(
+        booking.currency(),
+        &services::ledger::entries(&booking_id).await?,
+    );
+    let entry = |kind: LedgerEntryKind, amount: Money, description: String| NewLedgerEntry {
+        booking_id,
+        customer_id: booking.customer_id.clone(),
+        kind,
+        amount,
+        description,
+        payment_id: None,
+        recorded_by: cancelled_by.to_string(),
+    };
+
+    let outstanding = Money::new(
+        totals.charged.amount - totals.refunded.amount,
+        booking.currency(),
+    );
+    if outstanding.amount > Decimal::ZERO {
+        services::ledger::append(entry(
+            LedgerEntryKind::Refund,
+            outstanding,
+            "Rental cancelled".to_string(),
+        ))
+        .await?;
+    }
+    if let Some(fee) = booking
+        .cancellation_fee
+        .filter(|fee| fee.amount > Decimal::ZERO)
+    {
+        services::ledger::append(entry(
+            LedgerEntryKind::Fee,
+            fee,
+            "Cancellation fee".to_string(),
+        ))
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn confirmed(starts_in: Duration, total_price: Money) -> Booking {
+        let starts_at = Utc::now() + starts_in;
+        let request = CreateBookingRequest {
+            vehicle_id: ObjectId::new(),
+            from_date: starts_at.date_naive(),
+            to_date: starts_at.date_naive() + Duration::days(2),
+            driver: None,
+        };
+        let mut booking = Booking::new(request, "customer_user_1".to_string());
+        booking.status = BookingStatus::Confirmed;
+        booking.starts_at = Some(starts_at);
+        booking.total_price = Some(total_price);
+        booking
+    }
+
+    #[test]
+    fn test_cancellation_fee_depends_on_the_notice() {
+        let policy = CancellationPolicy::default();
+
+        let price = Money::new(Decimal::from(300), Currency::USD);
+
+        assert_eq!(
+            cancellation_fee(&confirmed(Duration::days(30), price), &policy),
+            Money::zero(Currency::USD)
+        );
+        assert_eq!(
+            cancellation_fee(&confirmed(Duration::hours(12), price), &policy),
+            Money::new(Decimal::from(150), Currency::USD)
+        );
+    }
+}
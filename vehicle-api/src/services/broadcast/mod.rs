@@ -0,0 +1,218 @@
+use bson::{doc, oid::ObjectId};
+use chrono::Utc;
+use std::collections::BTreeMap;
+
+use crate::error::AppResult;
+use crate::models::{
+    Booking, Broadcast, BroadcastDelivery, BroadcastDeliveryStatus, BroadcastStatus, ChannelStats,
+    CreateBroadcastRequest, Notification, Vehicle,
+};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// Find the customers the broadcast reaches and record one pending delivery each. The
+/// broadcast is returned SENDING, `run` delivers it.
+pub async fn start(
+    request: CreateBroadcastRequest,
+    sent_by: String,
+) -> AppResult<(Broadcast, Vec<BroadcastDelivery>)> {
+    // Soft deleted vehicles still have renters to warn, they are not excluded
+    let vehicle_ids = match request.filter.vehicle_filter() {
+        Some(filter) => {
+            let vehicles: Vec<Vehicle> = services::mongodb::collect_many(filter, None).await?;
+            Some(vehicles.iter().filter_map(|vehicle| vehicle.id).collect())
+        }
+        None => None,
+    };
+    let filter = request
+        .filter
+        .booking_filter(vehicle_ids, Utc::now().date_naive());
+    let bookings: Vec<Booking> = services::mongodb::collect_many(filter, None).await?;
+
+    let recipients = recipients(&bookings);
+    let mut broadcast = Broadcast {
+        id: None,
+        subject: request.subject.trim().to_string(),
+        message: request.message.trim().to_string(),
+        filter: request.filter,
+        status: BroadcastStatus::Sending,
+        sent_by,
+        recipients: recipients.len() as u32,
+        in_app: ChannelStats::default(),
+        email: ChannelStats::default(),
+        created_at: Utc::now(),
+        finished_at: None,
+    };
+    let broadcast_id = services::mongodb::insert_one(&broadcast, None).await?;
+    broadcast.id = Some(broadcast_id);
+
+    let mut deliveries = Vec::with_capacity(recipients.len());
+    for (customer_id, booking_ids) in recipients {
+        let mut delivery = BroadcastDelivery {
+            id: None,
+            broadcast_id,
+            customer_id,
+            booking_ids,
+            in_app: BroadcastDeliveryStatus::Pending,
+            email: BroadcastDeliveryStatus::Pending,
+            error: None,
+            delivered_at: None,
+        };
+        delivery.id = Some(services::mongodb::insert_one(&delivery, None).await?);
+        deliveries.push(delivery);
+    }
+    Ok((broadcast, deliveries))
+}
+
+/// Bookings of each customer, one delivery per customer whatever their bookings
+fn recipients(bookings: &[Booking]) -> BTreeMap<String, Vec<ObjectId>> {
+    let mut recipients: BTreeMap<String, Vec<ObjectId>> = BTreeMap::new();
+    for booking in bookings {
+        if let Some(booking_id) = booking.id {
+            recipients
+                .entry(booking.customer_id.clone())
+                .or_default()
+                .push(booking_id);
+        }
+    }
+    recipients
+}
+
+/// Deliver a broadcast in app and by email, recording the outcome of each channel for
+/// each customer. A failing customer does not stop the others.
+pub async fn run(broadcast: Broadcast, deliveries: Vec<BroadcastDelivery>) {
+    let Some(broadcast_id) = broadcast.id else {
+        return;
+    };
+    for delivery in deliveries {
+        if let Err(error) = deliver(&broadcast, delivery).await {
+            log::error!("Broadcast {} delivery failed: {}", broadcast_id, error);
+        }
+    }
+
+    let result = services::mongodb::update_one(
+        Broadcast::get_collection(),
+        doc! { "_id": broadcast_id },
+        doc! { "$set": {
+            "status": BroadcastStatus::Sent.to_string(),
+            "finished_at": bson::DateTime::from_chrono(Utc::now()),
+        } },
+        None,
+    )
+    .await;
+    match result {
+        Ok(_) => log::info!(
+            "Broadcast {} sent to {} customers",
+            broadcast_id,
+            broadcast.recipients
+        ),
+        Err(error) => log::error!("Failed to finish broadcast {}: {}", broadcast_id, error),
+    }
+}
+
+async fn deliver(broadcast: &Broadcast, mut delivery: BroadcastDelivery) -> AppResult<()> {
+    let mut errors = Vec::new();
+
+    let mut notification = Notification::to_user(
+        &delivery.customer_id,
+        broadcast.subject.clone(),
+        broadcast.message.clone(),
+    );
+    if let Some(booking_id) = delivery.booking_ids.first() {
+        notification = notification.about_booking(*booking_id);
+    }
+    delivery.in_app = match services::mongodb::insert_one(&notification, None).await {
+        Ok(_) => BroadcastDeliveryStatus::Sent,
+        Err(error) => {
+            errors.push(format!("in_app: {}", error));
+            BroadcastDeliveryStatus::Failed
+        }
+    };
+
+    // Without a relay emails are only logged, they reach nobody
+    delivery.email = if !services::email::is_configured() {
+        BroadcastDeliveryStatus::Skipped
+    } else {
+        let booking_ids = delivery
+            .booking_ids
+            .iter()
+            .map(|booking_id| booking_id.to_hex())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let variables = [
+            ("subject", broadcast.subject.clone()),
+            ("message", broadcast.message.clone()),
+            ("booking_ids", booking_ids),
+        ];
+        match services::email::send_broadcast(&delivery.customer_id, &variables).await {
+            Ok(true) => BroadcastDeliveryStatus::Sent,
+            Ok(false) => BroadcastDeliveryStatus::Skipped,
+            Err(error) => {
+                errors.push(format!("email: {}", error));
+                BroadcastDeliveryStatus::Failed
+            }
+        }
+    };
+
+    let error = (!errors.is_empty()).then(|| errors.join("; "));
+    services::mongodb::update_one(
+        BroadcastDelivery::get_collection(),
+        doc! { "_id": delivery.id },
+        doc! { "$set": {
+            "in_app": delivery.in_app.to_string(),
+            "email": delivery.email.to_string(),
+            "error": error,
+            "delivered_at": bson::DateTime::from_chrono(Utc::now()),
+        } },
+        None,
+    )
+    .await?;
+
+    let mut counters = bson::Document::new();
+    for (channel, status) in [("in_app", delivery.in_app), ("email", delivery.email)] {
+        if let Some(counter) = ChannelStats::counter(status) {
+            counters.insert(format!("{}.{}", channel, counter), 1_i64);
+        }
+    }
+    services::mongodb::update_one(
+        Broadcast::get_collection(),
+        doc! { "_id": delivery.broadcast_id },
+        doc! { "$inc": counters },
+        None,
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures;
+
+    fn booking(customer_id: &str) -> Booking {
+        let mut booking = fixtures::booking();
+        booking.customer_id = customer_id.to_string();
+        booking.id = Some(ObjectId::new());
+        booking
+    }
+
+    #[test]
+    fn test_customers_get_one_delivery_for_all_their_bookings() {
+        let bookings = vec![
+            booking("customer_user_2"),
+            booking("customer_user_1"),
+            booking("customer_user_2"),
+        ];
+
+        let recipients = recipients(&bookings);
+
+        assert_eq!(
+            recipients.keys().collect::<Vec<_>>(),
+            vec!["customer_user_1", "customer_user_2"]
+        );
+        assert_eq!(
+            recipients["customer_user_2"],
+            vec![bookings[0].id.unwrap(), bookings[2].id.unwrap()]
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use bson::oid::ObjectId;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::HashMap;
+
+use crate::models::{Booking, Vehicle, VehicleMetadata};
+
+/// Product identifier of the generated calendars
+const PRODID: &str = "-//Vehicle Booking API//Bookings//EN";
+
+/// Calendar apps poll subscribed feeds about this often
+const REFRESH_INTERVAL: &str = "PT1H";
+
+/// Vehicle of an event summary, e.g. "TESLA MODEL_3"
+fn vehicle_name(vehicle: &Vehicle) -> String {
+    let model = match &vehicle.metadata {
+        VehicleMetadata::Car(car) => car.model.to_string(),
+        VehicleMetadata::Motorbike(motorbike) => motorbike.model.to_string(),
+    };
+    format!("{} {}", vehicle.brand, model)
+}
+
+/// Escape a TEXT value (RFC 5545, 3.3.11)
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line at 75 octets, continuation lines start with a space (RFC 5545, 3.1)
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+/// iCalendar feed with one all-day event per booking. Dates are the booking's local
+/// dates, the end date is exclusive so it is the day after `to_date`.
+pub fn render_bookings(
+    bookings: &[Booking],
+    vehicles: &HashMap<ObjectId, Vehicle>,
+    now: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:Vehicle bookings".to_string(),
+        format!("REFRESH-INTERVAL;VALUE=DURATION:{}", REFRESH_INTERVAL),
+        format!("X-PUBLISHED-TTL:{}", REFRESH_INTERVAL),
+    ];
+
+    for booking in bookings {
+        let Some(booking_id) = booking.id else {
+            continue;
+        };
+        let vehicle = vehicles
+            .get(&booking.vehicle_id)
+            .map(vehicle_name)
+            .unwrap_or_else(|| "vehicle".to_string());
+        let mut description = format!("Booking {}", booking_id);
+        if let Some(total_price) = booking.total_price {
+            description.push_str(&format!("\nTotal price: {}", total_price));
+        }
+
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}@vehicle-booking", booking_id),
+            format!("DTSTAMP:{}", now.format("%Y%m%dT%H%M%SZ")),
+            format!("DTSTART;VALUE=DATE:{}", date(booking.from_date)),
+            format!(
+                "DTEND;VALUE=DATE:{}",
+                date(booking.to_date + Duration::days(1))
+            ),
+            format!("SUMMARY:{}", escape(&format!("Rental: {}", vehicle))),
+            format!("DESCRIPTION:{}", escape(&description)),
+            "STATUS:CONFIRMED".to_string(),
+            "TRANSP:OPAQUE".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateBookingRequest, Currency, Money};
+
+    #[test]
+    fn test_render_bookings() {
+        let request = CreateBookingRequest {
+            vehicle_id: ObjectId::new(),
+            from_date: NaiveDate::from_ymd_opt(2026, 7, 1).unwrap(),
+            to_date: NaiveDate::from_ymd_opt(2026, 7, 4).unwrap(),
+            driver: None,
+        };
+        let mut booking = Booking::new(request, "customer_user_1".to_string());
+        booking.id = Some(ObjectId::new());
+        booking.total_price = Money::from_f64(320.0, Currency::EUR);
+
+        let now = Utc::now();
+        let calendar = render_bookings(&[booking.clone()], &HashMap::new(), now);
+        let lines: Vec<&str> = calendar.split("\r\n").collect();
+
+        assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20260701"));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20260705"));
+        assert!(lines.contains(&"SUMMARY:Rental: vehicle"));
+        assert!(lines.contains(&format!("UID:{}@vehicle-booking", booking.id.unwrap()).as_str()));
+        assert!(lines.contains(
+            &format!(
+                "DESCRIPTION:Booking {}\\nTotal price: 320.00 EUR",
+                booking.id.unwrap()
+            )
+            .as_str()
+        ));
+    }
+
+    #[test]
+    fn test_long_lines_are_folded() {
+        let folded = fold(&format!("SUMMARY:{}", "x".repeat(100)));
+        let lines: Vec<&str> = folded.trim_end().split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 75);
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(escape("a,b;c\\d"), "a\\,b\\;c\\\\d");
+    }
+}
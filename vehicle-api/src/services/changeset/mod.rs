@@ -0,0 +1,132 @@
+use bson::{doc, oid::ObjectId, Document};
+use chrono::{Duration, Utc};
+use mongodb::options::{IndexOptions, UpdateOptions};
+use mongodb::IndexModel;
+
+use crate::authentication::identity::Identity;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    ActionAuditEntry, AuditAction, Change, Changeset, ChangesetKind, ChangesetRollback,
+    DEFAULT_CHANGESET_TTL_MINUTES,
+};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// How long a change-set can be rolled back (CHANGESET_TTL_MINUTES, default 60)
+pub fn ttl() -> Duration {
+    let minutes = std::env::var("CHANGESET_TTL_MINUTES")
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|minutes| *minutes > 0)
+        .unwrap_or(DEFAULT_CHANGESET_TTL_MINUTES);
+    Duration::minutes(minutes)
+}
+
+/// TTL index dropping change-sets once they expire
+pub async fn ensure_indexes() -> AppResult<()> {
+    let database = services::mongodb::get_database(services::mongodb::DATABASE_NAME).await?;
+    let index = IndexModel::builder()
+        .keys(doc! { "expires_at": 1 })
+        .options(
+            IndexOptions::builder()
+                .expire_after(std::time::Duration::ZERO)
+                .build(),
+        )
+        .build();
+    database
+        .collection::<Document>(Changeset::get_collection())
+        .create_index(index)
+        .await?;
+    Ok(())
+}
+
+/// Keep what an operation changed so it can be rolled back. Returns the stored change-set.
+pub async fn record(
+    identity: &Identity,
+    kind: ChangesetKind,
+    collection: &str,
+    changes: Vec<Change>,
+) -> AppResult<Changeset> {
+    let mut changeset = Changeset::new(kind, collection, changes, &identity.user_id, ttl());
+    changeset.id = Some(services::mongodb::insert_one(&changeset, None).await?);
+    Ok(changeset)
+}
+
+/// Put back the documents of a change-set, those changed again since are skipped.
+/// A change-set is rolled back once, and only before it expires.
+pub async fn rollback(identity: &Identity, changeset_id: ObjectId) -> AppResult<ChangesetRollback> {
+    let changeset: Changeset = services::mongodb::get_one(doc! { "_id": changeset_id }, None)
+        .await?
+        .ok_or_else(|| AppError::not_found("Change-set not found"))?;
+    changeset
+        .check_rollback(Utc::now())
+        .map_err(AppError::conflict)?;
+
+    // Marked first, so two rollbacks cannot both restore the documents
+    let result = services::mongodb::update_one(
+        Changeset::get_collection(),
+        doc! { "_id": changeset_id, "rolled_back_at": null },
+        doc! { "$set": { "rolled_back_at": bson::DateTime::now() } },
+        None,
+    )
+    .await?;
+    if result.modified_count == 0 {
+        return Err(AppError::conflict("Change-set already rolled back"));
+    }
+
+    let mut rollback = ChangesetRollback {
+        restored: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for change in &changeset.changes {
+        match revert(&changeset.collection, change).await? {
+            true => rollback.restored.push(change.resource_id()),
+            false => rollback.skipped.push(change.resource_id()),
+        }
+    }
+
+    let entry = ActionAuditEntry::new(
+        AuditAction::ChangesetRolledBack,
+        identity,
+        changeset_id.to_hex(),
+        Some(doc! {
+            "kind": changeset.kind.to_string(),
+            "collection": &changeset.collection,
+            "restored": rollback.restored.clone(),
+            "skipped": rollback.skipped.clone(),
+        }),
+    );
+    services::mongodb::insert_one(&entry, None).await?;
+    Ok(rollback)
+}
+
+/// Undo one change, false when the document changed since
+async fn revert(collection: &str, change: &Change) -> AppResult<bool> {
+    match change {
+        Change::Updated { before, .. } => {
+            let result = services::mongodb::update_one(
+                collection,
+                change.guard(),
+                doc! { "$set": before.clone() },
+                None,
+            )
+            .await?;
+            Ok(result.modified_count > 0)
+        }
+        // Put back unless a document with the same ID came back meanwhile
+        Change::Deleted { document, .. } => {
+            let result = services::mongodb::update_one(
+                collection,
+                change.guard(),
+                doc! { "$setOnInsert": document.clone() },
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await?;
+            Ok(result.upserted_id.is_some())
+        }
+        Change::Created { .. } => {
+            let deleted = services::mongodb::delete_many(collection, change.guard(), None).await?;
+            Ok(deleted > 0)
+        }
+    }
+}
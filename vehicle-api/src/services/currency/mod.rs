@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use bson::{doc, Bson, Document};
+use strum::IntoEnumIterator;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    ArchivedBooking, Booking, BookingExtension, Currency, Decimal, Dispute, LedgerEntry,
+    MaintenanceRecord, Money, Payment, Vehicle,
+};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// Units of each currency worth 1 EUR, the base currency
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExchangeRates(HashMap<Currency, Decimal>);
+
+// Read once from EXCHANGE_RATES
+static EXCHANGE_RATES: LazyLock<ExchangeRates> =
+    LazyLock::new(|| ExchangeRates::parse(&std::env::var("EXCHANGE_RATES").unwrap_or_default()));
+
+/// Exchange rates of the instance
+pub fn rates() -> &'static ExchangeRates {
+    &EXCHANGE_RATES
+}
+
+impl ExchangeRates {
+    /// `USD=1.08,GBP=0.85`: units per EUR, EUR is always 1. Unknown currencies and
+    /// rates that are not strictly positive are ignored.
+    pub fn parse(value: &str) -> Self {
+        let mut rates = HashMap::from([(Currency::EUR, Decimal::ONE)]);
+        for pair in value.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let parsed = pair.split_once('=').and_then(|(currency, rate)| {
+                let currency = Currency::from_str(currency.trim().to_uppercase().as_str()).ok()?;
+                let rate = Decimal::from_str(rate.trim()).ok()?;
+                (rate > Decimal::ZERO && currency != Currency::EUR).then_some((currency, rate))
+            });
+            match parsed {
+                Some((currency, rate)) => {
+                    rates.insert(currency, rate);
+                }
+                None => log::warn!("Ignored exchange rate {:?}", pair),
+            }
+        }
+        Self(rates)
+    }
+
+    /// Currencies amounts can be converted from and to
+    pub fn supports(&self, currency: Currency) -> bool {
+        self.0.contains_key(&currency)
+    }
+
+    /// `money` in `to`, rounded to the cent, None without a rate for either currency
+    pub fn convert(&self, money: Money, to: Currency) -> Option<Money> {
+        if money.currency == to {
+            return Some(money);
+        }
+        let (from_rate, to_rate) = (self.0.get(&money.currency)?, self.0.get(&to)?);
+        Some(Money::new(money.amount / from_rate * to_rate, to).round())
+    }
+
+    /// 400 for a `?currency=` amounts cannot be converted to
+    pub fn check(&self, currency: Option<Currency>) -> AppResult<()> {
+        match currency {
+            Some(currency) if !self.supports(currency) => Err(AppError::bad_request(format!(
+                "No exchange rate for {}",
+                currency
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Price of a list item in `currency`, left in its own when it has no rate
+    pub fn convert_in_place(&self, money: &mut Money, currency: Option<Currency>) {
+        if let Some(converted) = currency.and_then(|currency| self.convert(*money, currency)) {
+            *money = converted;
+        }
+    }
+}
+
+/// Stage storing the amount in `field` as a Decimal128 rounded to the cent: a plain
+/// number, stored before currencies were, becomes an amount in `currency` (an expression),
+/// a float amount keeps its currency
+fn money_stage(field: &str, currency: impl Into<Bson>) -> Document {
+    let path = format!("${}", field);
+    let amount = format!("${}.amount", field);
+    let decimal = |value: &str| doc! { "$round": [{ "$toDecimal": value }, 2] };
+    doc! { "$set": { field: { "$switch": {
+        "branches": [
+            {
+                "case": { "$in": [{ "$type": &path }, FLOAT_TYPES.to_vec()] },
+                "then": { "amount": decimal(&path), "currency": currency.into() },
+            },
+            {
+                "case": { "$in": [{ "$type": &amount }, FLOAT_TYPES.to_vec()] },
+                "then": { "amount": decimal(&amount), "currency": format!("{}.currency", path) },
+            },
+        ],
+        "default": &path,
+    } } } }
+}
+
+/// Documents whose `field` still holds a plain number or a float amount
+fn legacy_filter(field: &str) -> Document {
+    doc! { "$or": [
+        { field: { "$type": FLOAT_TYPES.to_vec() } },
+        { format!("{}.amount", field): { "$type": FLOAT_TYPES.to_vec() } },
+    ] }
+}
+
+// Number types amounts were stored as before Decimal128
+const FLOAT_TYPES: [&str; 3] = ["double", "int", "long"];
+
+/// Migrate `field` in the documents of `collection` matching `filter`
+async fn migrate_field(
+    collection: &str,
+    mut filter: Document,
+    field: &str,
+    currency: impl Into<Bson>,
+) -> AppResult<u64> {
+    filter.extend(legacy_filter(field));
+    let result = services::mongodb::update_many(
+        collection,
+        filter,
+        vec![money_stage(field, currency)],
+        None,
+    )
+    .await?;
+    Ok(result.modified_count)
+}
+
+/// Store the amounts saved as plain numbers or floats as Decimal128 amounts: prices
+/// without a currency are EUR, the fees, payments and ledger entries of a booking are
+/// in the booking's currency. Run at every start, documents already migrated are not
+/// matched.
+pub async fn migrate_prices() -> AppResult<u64> {
+    let eur = Currency::EUR.to_string();
+    let mut migrated = 0;
+    for (collection, field) in [
+        (Vehicle::get_collection(), "price_by_day"),
+        (MaintenanceRecord::get_collection(), "cost"),
+        (Booking::get_collection(), "total_price"),
+        (ArchivedBooking::get_collection(), "total_price"),
+    ] {
+        migrated += migrate_field(collection, doc! {}, field, &eur).await?;
+    }
+
+    // Fees of a booking are in the currency of its price, migrated above
+    let booking_currency = doc! { "$ifNull": ["$total_price.currency", &eur] };
+    for collection in [Booking::get_collection(), ArchivedBooking::get_collection()] {
+        for field in ["cancellation_fee", "late_fee"] {
+            migrated += migrate_field(collection, doc! {}, field, booking_currency.clone()).await?;
+        }
+    }
+
+    // Amounts stored apart from their booking, bookings in another currency first and
+    // what is left is EUR
+    let booking_amounts = [
+        (LedgerEntry::get_collection(), "amount"),
+        (LedgerEntry::get_collection(), "balance"),
+        (Payment::get_collection(), "amount"),
+        (Dispute::get_collection(), "resolution.amount"),
+        (BookingExtension::get_collection(), "extra_price"),
+    ];
+    for currency in Currency::iter().filter(|currency| *currency != Currency::EUR) {
+        let priced_in = doc! { "total_price.currency": currency.to_string() };
+        let mut booking_ids =
+            services::mongodb::distinct::<Booking>("_id", priced_in.clone()).await?;
+        booking_ids.extend(services::mongodb::distinct::<ArchivedBooking>("_id", priced_in).await?);
+        if booking_ids.is_empty() {
+            continue;
+        }
+        for (collection, field) in booking_amounts {
+            let filter = doc! { "booking_id": { "$in": booking_ids.clone() } };
+            migrated += migrate_field(collection, filter, field, currency.to_string()).await?;
+        }
+    }
+    for (collection, field) in booking_amounts {
+        migrated += migrate_field(collection, doc! {}, field, &eur).await?;
+    }
+
+    if migrated > 0 {
+        log::info!("{} amounts stored as decimals", migrated);
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eur(amount: i64, scale: u32) -> Money {
+        Money::new(Decimal::new(amount, scale), Currency::EUR)
+    }
+
+    #[test]
+    fn test_conversions_go_through_the_base_currency() {
+        let rates = ExchangeRates::parse("USD=1.08, gbp=0.85,CHF=-1,XYZ=2");
+
+        assert!(rates.supports(Currency::GBP));
+        assert!(!rates.supports(Currency::CHF));
+        assert_eq!(
+            rates.convert(eur(10000, 2), Currency::USD),
+            Some(Money::new(Decimal::new(10800, 2), Currency::USD))
+        );
+        // 108 USD is 100 EUR, 85 GBP
+        assert_eq!(
+            rates.convert(
+                Money::new(Decimal::new(108, 0), Currency::USD),
+                Currency::GBP
+            ),
+            Some(Money::new(Decimal::new(8500, 2), Currency::GBP))
+        );
+        assert_eq!(rates.convert(eur(100, 0), Currency::CHF), None);
+        assert!(rates.check(Some(Currency::CHF)).is_err());
+        assert!(rates.check(None).is_ok());
+    }
+
+    #[test]
+    fn test_plain_numbers_become_decimal_amounts() {
+        let decimal = |value: &str| doc! { "$round": [{ "$toDecimal": value }, 2] };
+        assert_eq!(
+            money_stage("price_by_day", "EUR"),
+            doc! { "$set": { "price_by_day": { "$switch": {
+                "branches": [
+                    {
+                        "case": { "$in": [{ "$type": "$price_by_day" }, ["double", "int", "long"]] },
+                        "then": { "amount": decimal("$price_by_day"), "currency": "EUR" },
+                    },
+                    {
+                        "case": { "$in": [{ "$type": "$price_by_day.amount" }, ["double", "int", "long"]] },
+                        "then": {
+                            "amount": decimal("$price_by_day.amount"),
+                            "currency": "$price_by_day.currency",
+                        },
+                    },
+                ],
+                "default": "$price_by_day",
+            } } } }
+        );
+        assert_eq!(
+            legacy_filter("late_fee"),
+            doc! { "$or": [
+                { "late_fee": { "$type": ["double", "int", "long"] } },
+                { "late_fee.amount": { "$type": ["double", "int", "long"] } },
+            ] }
+        );
+    }
+}
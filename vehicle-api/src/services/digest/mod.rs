@@ -0,0 +1,138 @@
+use bson::doc;
+use chrono::{DateTime, Duration, Utc};
+use mongodb::options::FindOptions;
+
+use crate::error::AppResult;
+use crate::models::{
+    Booking, BookingDigest, Customer, Payment, PendingAction, Vehicle, VehicleType,
+};
+use crate::services;
+
+/// Vehicles suggested in a digest
+const RECOMMENDATIONS: i64 = 3;
+
+/// Seconds between two digests (DIGEST_INTERVAL_SECS, default one week, 0 disables the job)
+fn send_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("DIGEST_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(7 * 24 * 3600);
+
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// Confirmed bookings starting within this many days are listed (DIGEST_UPCOMING_DAYS, default 14)
+fn upcoming_days() -> i64 {
+    std::env::var("DIGEST_UPCOMING_DAYS")
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(14)
+}
+
+/// Email the weekly digest periodically in the background, the first one an interval
+/// after startup so restarts do not send it again
+pub fn spawn_scheduler() {
+    let Some(interval) = send_interval() else {
+        log::info!("Booking digest job disabled");
+        return;
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        loop {
+            ticker.tick().await;
+            match send_all(Utc::now()).await {
+                Ok(sent) => log::info!("{} booking digests sent", sent),
+                Err(error) => log::error!("Booking digest failed: {}", error),
+            }
+        }
+    });
+}
+
+/// Email their digest to the customers who did not unsubscribe and have something
+/// coming up or to do. Returns the number of digests sent.
+pub async fn send_all(now: DateTime<Utc>) -> AppResult<usize> {
+    let filter = doc! {
+        "email": { "$ne": null },
+        "email_preferences.weekly_digest": { "$ne": false },
+    };
+    let customers: Vec<Customer> = services::mongodb::collect_many(filter, None).await?;
+
+    let mut sent = 0;
+    for customer in customers {
+        // One customer failing does not stop the others' digests
+        let result = match build(&customer.user_id, now).await {
+            Ok(digest) if digest.is_empty() => Ok(false),
+            Ok(digest) => services::email::send_digest(&customer, &digest).await,
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(true) => sent += 1,
+            Ok(false) => {}
+            Err(error) => log::error!("Digest of customer {} failed: {}", customer.user_id, error),
+        }
+    }
+    Ok(sent)
+}
+
+/// Upcoming bookings, pending actions and recommendations of a customer
+pub async fn build(customer_id: &str, now: DateTime<Utc>) -> AppResult<BookingDigest> {
+    let today = now.date_naive();
+    let horizon = today + Duration::days(upcoming_days());
+    let (upcoming, payments, bookings) = services::fanout::try_join3(
+        services::mongodb::collect_many::<Booking>(
+            doc! {
+                "customer_id": customer_id,
+                "status": "CONFIRMED",
+                "from_date": { "$gte": today.to_string(), "$lte": horizon.to_string() },
+            },
+            FindOptions::builder().sort(doc! { "from_date": 1 }).build(),
+        ),
+        services::mongodb::collect_many::<Payment>(
+            doc! { "customer_id": customer_id, "kind": "CHARGE", "status": "FAILED" },
+            None,
+        ),
+        services::mongodb::collect_many::<Booking>(
+            doc! { "customer_id": customer_id },
+            FindOptions::builder()
+                .sort(doc! { "order_date": -1 })
+                .build(),
+        ),
+    )
+    .await?;
+
+    Ok(BookingDigest {
+        upcoming,
+        pending_actions: PendingAction::unpaid_balances(&payments),
+        recommendations: recommend(&bookings).await?,
+    })
+}
+
+/// Cheapest vehicles of the type the customer rented last, among the ones they never rented
+async fn recommend(bookings: &[Booking]) -> AppResult<Vec<Vehicle>> {
+    let Some(latest) = bookings.first() else {
+        return Ok(Vec::new());
+    };
+    let Some(vehicle) =
+        services::mongodb::get_one::<Vehicle>(doc! { "_id": latest.vehicle_id }, None).await?
+    else {
+        return Ok(Vec::new());
+    };
+
+    let rented: Vec<_> = bookings.iter().map(|booking| booking.vehicle_id).collect();
+    let filter = doc! {
+        "type": VehicleType::of(&vehicle).to_string(),
+        "_id": { "$nin": rented },
+    };
+    let options = FindOptions::builder()
+        .sort(doc! { "price_by_day": 1, "_id": 1 })
+        .limit(RECOMMENDATIONS)
+        .build();
+    // Only vehicles of the rental company the customer rented from
+    services::mongodb::tenant::scope(
+        vehicle.tenant_id.clone(),
+        services::mongodb::collect_many(filter, options),
+    )
+    .await
+}
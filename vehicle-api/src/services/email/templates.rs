@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::models::{Booking, BookingDigest, BookingStatus, Money};
+
+/// Emails sent to the customer about their bookings
+#[derive(Clone, Copy, Debug, Display, EnumIter, PartialEq, Eq, Hash)]
+#[strum(serialize_all = "snake_case")]
+pub enum BookingEmail {
+    Created,
+    Confirmed,
+    Rejected,
+    Cancelled,
+    Reminder,  // The rental starts soon, see services::reminder
+    Digest,    // Weekly summary, see services::digest
+    Broadcast, // Urgent message from the rental company, see services::broadcast
+}
+
+impl BookingEmail {
+    /// Email announcing a booking in this status, PENDING being a booking just made
+    pub fn for_status(status: &BookingStatus) -> Self {
+        match status {
+            BookingStatus::Pending => Self::Created,
+            BookingStatus::Confirmed => Self::Confirmed,
+            BookingStatus::Rejected(_) => Self::Rejected,
+            BookingStatus::Cancelled(_) => Self::Cancelled,
+        }
+    }
+
+    /// Template file replacing the built-in one in EMAIL_TEMPLATE_DIR
+    pub fn file_name(self) -> String {
+        format!("booking_{}.txt", self)
+    }
+
+    fn default_template(self) -> &'static str {
+        match self {
+            Self::Created => {
+                "Booking received\n\n\
+                 Hello {customer_name},\n\n\
+                 We received your booking {booking_id} from {from_date} to {to_date} \
+                 ({total_price}). We will let you know once it is confirmed.\n"
+            }
+            Self::Confirmed => {
+                "Booking confirmed\n\n\
+                 Hello {customer_name},\n\n\
+                 Your booking {booking_id} from {from_date} to {to_date} is confirmed \
+                 ({total_price}). See you soon!\n"
+            }
+            Self::Rejected => {
+                "Booking declined\n\n\
+                 Hello {customer_name},\n\n\
+                 We are sorry, your booking {booking_id} from {from_date} to {to_date} \
+                 could not be accepted: {reason}.\n"
+            }
+            Self::Cancelled => {
+                "Booking cancelled\n\n\
+                 Hello {customer_name},\n\n\
+                 Your booking {booking_id} from {from_date} to {to_date} is cancelled: \
+                 {reason}. Cancellation fee: {cancellation_fee}.\n"
+            }
+            Self::Reminder => {
+                "Your rental starts soon\n\n\
+                 Hello {customer_name},\n\n\
+                 A reminder that your booking {booking_id} starts on {from_date} and ends \
+                 on {to_date}. See you soon!\n"
+            }
+            Self::Digest => {
+                "Your bookings this week\n\n\
+                 Hello {customer_name},\n\n\
+                 Coming up:\n{upcoming}\n\n\
+                 Waiting for you:\n{pending_actions}\n\n\
+                 You may also like:\n{recommendations}\n\n\
+                 No more weekly summaries: {unsubscribe_url}\n"
+            }
+            Self::Broadcast => {
+                "{subject}\n\n\
+                 Hello {customer_name},\n\n\
+                 {message}\n\n\
+                 This concerns your bookings: {booking_ids}.\n"
+            }
+        }
+    }
+}
+
+/// Subject and body of an email, with `{placeholders}` filled in by `render`
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmailTemplate {
+    pub subject: String,
+    pub body: String,
+}
+
+impl EmailTemplate {
+    /// First line is the subject, the body follows a blank line
+    pub fn parse(text: &str) -> Option<Self> {
+        let (subject, body) = text.split_once('\n')?;
+        let subject = subject.trim();
+        (!subject.is_empty()).then(|| Self {
+            subject: subject.to_string(),
+            body: body.trim_start_matches(['\r', '\n']).to_string(),
+        })
+    }
+
+    /// Replace every `{name}` of the template, unknown placeholders are left as written
+    pub fn render(&self, variables: &[(&str, String)]) -> (String, String) {
+        let fill = |text: &str| {
+            variables
+                .iter()
+                .fold(text.to_string(), |text, (name, value)| {
+                    text.replace(&format!("{{{}}}", name), value)
+                })
+        };
+        (fill(&self.subject), fill(&self.body))
+    }
+}
+
+// Loaded once, files of EMAIL_TEMPLATE_DIR replace the built-in templates
+static TEMPLATES: LazyLock<HashMap<BookingEmail, EmailTemplate>> = LazyLock::new(|| {
+    let directory = std::env::var("EMAIL_TEMPLATE_DIR").ok();
+    BookingEmail::iter()
+        .filter_map(|email| {
+            let custom = directory.as_ref().and_then(|directory| {
+                let path = std::path::Path::new(directory).join(email.file_name());
+                let text = std::fs::read_to_string(&path).ok()?;
+                let template = EmailTemplate::parse(&text);
+                if template.is_none() {
+                    log::error!(
+                        "Invalid email template {}, using the default",
+                        path.display()
+                    );
+                }
+                template
+            });
+            let template = custom.or_else(|| EmailTemplate::parse(email.default_template()))?;
+            Some((email, template))
+        })
+        .collect()
+});
+
+pub fn preload() {
+    LazyLock::force(&TEMPLATES);
+}
+
+/// Template of an email
+pub fn get(email: BookingEmail) -> Option<&'static EmailTemplate> {
+    TEMPLATES.get(&email)
+}
+
+/// Values of the placeholders for a booking email
+pub fn booking_variables(booking: &Booking, customer_name: &str) -> Vec<(&'static str, String)> {
+    let money = |amount: Option<Money>| {
+        amount
+            .map(|amount| amount.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let reason = match &booking.status {
+        BookingStatus::Rejected(reason) | BookingStatus::Cancelled(reason) => reason.clone(),
+        _ => String::new(),
+    };
+    vec![
+        ("customer_name", customer_name.to_string()),
+        (
+            "booking_id",
+            booking.id.map(|id| id.to_hex()).unwrap_or_default(),
+        ),
+        ("from_date", booking.from_date.to_string()),
+        ("to_date", booking.to_date.to_string()),
+        ("total_price", money(booking.total_price)),
+        ("reason", reason),
+        (
+            "cancellation_fee",
+            // Fees are in the currency of the booking's price
+            money(Some(
+                booking
+                    .cancellation_fee
+                    .unwrap_or_else(|| Money::zero(booking.currency())),
+            )),
+        ),
+    ]
+}
+
+/// Values of the placeholders for the weekly digest, one line per item
+pub fn digest_variables(
+    digest: &BookingDigest,
+    customer_name: &str,
+    unsubscribe_url: &str,
+) -> Vec<(&'static str, String)> {
+    let list = |lines: Vec<String>| {
+        if lines.is_empty() {
+            "- Nothing".to_string()
+        } else {
+            lines
+                .iter()
+                .map(|line| format!("- {}", line))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    };
+    let upcoming = digest
+        .upcoming
+        .iter()
+        .map(|booking| {
+            format!(
+                "Booking {} from {} to {}",
+                booking.id.map(|id| id.to_hex()).unwrap_or_default(),
+                booking.from_date,
+                booking.to_date
+            )
+        })
+        .collect();
+    let pending_actions = digest
+        .pending_actions
+        .iter()
+        .map(|action| action.describe())
+        .collect();
+    let recommendations = digest
+        .recommendations
+        .iter()
+        .map(|vehicle| {
+            format!(
+                "{} ({}), {} per day",
+                vehicle.brand, vehicle.year_of_production, vehicle.price_by_day
+            )
+        })
+        .collect();
+    vec![
+        ("customer_name", customer_name.to_string()),
+        ("upcoming", list(upcoming)),
+        ("pending_actions", list(pending_actions)),
+        ("recommendations", list(recommendations)),
+        ("unsubscribe_url", unsubscribe_url.to_string()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateBookingRequest, Currency, Decimal, PendingAction};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_every_email_has_a_template() {
+        for email in BookingEmail::iter() {
+            assert!(get(email).is_some(), "no template for {}", email);
+        }
+        assert_eq!(BookingEmail::Cancelled.file_name(), "booking_cancelled.txt");
+        assert_eq!(EmailTemplate::parse("No body"), None);
+        assert_eq!(EmailTemplate::parse("\nBody"), None);
+    }
+
+    #[test]
+    fn test_render_booking_email() {
+        let request = CreateBookingRequest {
+            vehicle_id: bson::oid::ObjectId::new(),
+            from_date: NaiveDate::from_ymd_opt(2026, 7, 1).unwrap(),
+            to_date: NaiveDate::from_ymd_opt(2026, 7, 4).unwrap(),
+            driver: None,
+        };
+        let mut booking = Booking::new(request, "customer_user_1".to_string());
+        booking.total_price = Money::from_f64(320.0, Currency::EUR);
+        booking.set_status(
+            BookingStatus::Cancelled("plans changed".to_string()),
+            "customer_user_1".to_string(),
+            None,
+        );
+        booking.cancellation_fee = Money::from_f64(80.0, Currency::EUR);
+
+        let email = BookingEmail::for_status(&booking.status);
+        assert_eq!(email, BookingEmail::Cancelled);
+        let (subject, body) = get(email)
+            .unwrap()
+            .render(&booking_variables(&booking, "Jane"));
+        assert_eq!(subject, "Booking cancelled");
+        assert!(body.starts_with("Hello Jane,\n\n"));
+        assert!(body.contains("from 2026-07-01 to 2026-07-04 is cancelled: plans changed."));
+        assert!(body.contains("Cancellation fee: 80.00 EUR."));
+    }
+
+    #[test]
+    fn test_render_digest_email() {
+        let request = CreateBookingRequest {
+            vehicle_id: bson::oid::ObjectId::new(),
+            from_date: NaiveDate::from_ymd_opt(2026, 7, 1).unwrap(),
+            to_date: NaiveDate::from_ymd_opt(2026, 7, 4).unwrap(),
+            driver: None,
+        };
+        let mut booking = Booking::new(request, "customer_user_1".to_string());
+        let booking_id = bson::oid::ObjectId::new();
+        booking.id = Some(booking_id);
+        let digest = BookingDigest {
+            upcoming: vec![booking],
+            pending_actions: vec![PendingAction::UnpaidBalance {
+                booking_id,
+                amount: Money::new(Decimal::new(425, 1), Currency::EUR),
+            }],
+            recommendations: Vec::new(),
+        };
+
+        let (subject, body) = get(BookingEmail::Digest).unwrap().render(&digest_variables(
+            &digest,
+            "Jane",
+            "http://localhost:8080/emails/unsubscribe?user_id=customer_user_1&token=t",
+        ));
+        assert_eq!(subject, "Your bookings this week");
+        assert!(body.contains(&format!(
+            "Coming up:\n- Booking {} from 2026-07-01 to 2026-07-04\n",
+            booking_id.to_hex()
+        )));
+        assert!(body.contains("42.50 EUR is still unpaid"));
+        assert!(body.contains("You may also like:\n- Nothing\n"));
+        assert!(body.contains("unsubscribe?user_id=customer_user_1&token=t"));
+    }
+}
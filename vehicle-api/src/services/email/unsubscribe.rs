@@ -0,0 +1,77 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+use std::sync::LazyLock;
+
+use crate::error::{AppError, AppResult};
+
+/// Address of the API in email links (PUBLIC_URL, default "http://localhost:8080")
+fn public_url() -> String {
+    std::env::var("PUBLIC_URL").unwrap_or_else(|_| "http://localhost:8080".to_string())
+}
+
+// Key of the unsubscribe links from EMAIL_UNSUBSCRIBE_SECRET. Without it links are
+// signed with a random key and stop working at the next restart.
+static SECRET: LazyLock<Vec<u8>> =
+    LazyLock::new(|| match std::env::var("EMAIL_UNSUBSCRIBE_SECRET") {
+        Ok(secret) if !secret.is_empty() => secret.into_bytes(),
+        _ => {
+            log::warn!(
+                "EMAIL_UNSUBSCRIBE_SECRET not set, unsubscribe links only last until restart"
+            );
+            rand::random::<[u8; 32]>().to_vec()
+        }
+    });
+
+fn mac(secret: &[u8], user_id: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(b"unsubscribe:");
+    mac.update(user_id.as_bytes());
+    mac
+}
+
+fn sign(secret: &[u8], user_id: &str) -> String {
+    URL_SAFE_NO_PAD.encode(mac(secret, user_id).finalize().into_bytes())
+}
+
+fn check(secret: &[u8], user_id: &str, token: &str) -> bool {
+    URL_SAFE_NO_PAD
+        .decode(token)
+        .is_ok_and(|signature| mac(secret, user_id).verify_slice(&signature).is_ok())
+}
+
+/// Token proving an unsubscribe link was sent to the customer
+pub fn token(user_id: &str) -> String {
+    sign(&SECRET, user_id)
+}
+
+pub fn verify(user_id: &str, token: &str) -> bool {
+    check(&SECRET, user_id, token)
+}
+
+/// Link turning the weekly digest off for a customer, no login needed
+pub fn url(user_id: &str) -> AppResult<String> {
+    reqwest::Url::parse_with_params(
+        &format!("{}/emails/unsubscribe", public_url().trim_end_matches('/')),
+        &[("user_id", user_id), ("token", token(user_id).as_str())],
+    )
+    .map(|url| url.to_string())
+    .map_err(|e| AppError::internal_server_error(format!("Invalid PUBLIC_URL: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_only_unsubscribe_their_customer() {
+        let secret = b"test-secret";
+        let token = sign(secret, "customer_user_1");
+
+        assert!(check(secret, "customer_user_1", &token));
+        assert!(!check(secret, "customer_user_2", &token));
+        assert!(!check(b"other-secret", "customer_user_1", &token));
+        assert!(!check(secret, "customer_user_1", "not base64!"));
+    }
+}
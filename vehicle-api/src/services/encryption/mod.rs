@@ -0,0 +1,302 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::env;
+
+use crate::authentication::identity::Identity;
+use crate::authentication::permission::{self, Permission};
+use crate::error::{AppError, AppResult};
+use crate::models::{Booking, EncryptedValue, SensitiveString};
+
+// Global cipher using OnceCell for lazy initialization
+static FIELD_CIPHER: tokio::sync::OnceCell<FieldCipher> = tokio::sync::OnceCell::const_new();
+
+/// Get the PII field cipher, fails when no key is configured
+pub async fn get_field_cipher() -> AppResult<&'static FieldCipher> {
+    FIELD_CIPHER
+        .get_or_try_init(|| async { Ok(FieldCipher::new(Box::new(EnvKeyProvider::from_env()?))) })
+        .await
+}
+
+/// Source of the data keys. Keys come from the environment, a KMS backed
+/// provider only has to implement this trait.
+pub trait KeyProvider: Send + Sync {
+    /// Key new values are encrypted with
+    fn current_key_id(&self) -> &str;
+    fn key(&self, key_id: &str) -> Option<&[u8; 32]>;
+}
+
+/// Keys from PII_ENCRYPTION_KEYS ("kid:base64key,kid:base64key", newest first).
+/// Older keys are kept to decrypt the values sealed before a rotation.
+pub struct EnvKeyProvider {
+    keys: Vec<(String, [u8; 32])>,
+}
+
+/// Types holding personal data fields
+pub trait EncryptedFields {
+    fn sensitive_fields(&mut self) -> Vec<&mut SensitiveString>;
+}
+
+/// AES-256-GCM encryption of single fields
+pub struct FieldCipher {
+    provider: Box<dyn KeyProvider>,
+}
+
+impl EnvKeyProvider {
+    pub fn new(keys: Vec<(String, [u8; 32])>) -> AppResult<Self> {
+        if keys.is_empty() {
+            return Err(AppError::internal_server_error(
+                "PII encryption needs at least one key",
+            ));
+        }
+        Ok(Self { keys })
+    }
+
+    pub fn from_env() -> AppResult<Self> {
+        let raw = env::var("PII_ENCRYPTION_KEYS").map_err(|_| {
+            AppError::internal_server_error("PII_ENCRYPTION_KEYS is not set, cannot store PII")
+        })?;
+
+        let keys = raw
+            .split(',')
+            .map(|entry| entry.trim())
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (kid, key) = entry.split_once(':').ok_or_else(|| {
+                    AppError::internal_server_error("PII encryption key must be 'kid:key'")
+                })?;
+                let key: [u8; 32] = STANDARD
+                    .decode(key)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| {
+                        AppError::internal_server_error(format!(
+                            "PII encryption key '{}' must be a base64 encoded 32 bytes key",
+                            kid
+                        ))
+                    })?;
+                Ok((kid.to_string(), key))
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Self::new(keys)
+    }
+}
+
+impl KeyProvider for EnvKeyProvider {
+    fn current_key_id(&self) -> &str {
+        &self.keys[0].0
+    }
+
+    fn key(&self, key_id: &str) -> Option<&[u8; 32]> {
+        self.keys
+            .iter()
+            .find(|(kid, _)| kid == key_id)
+            .map(|(_, key)| key)
+    }
+}
+
+impl EncryptedFields for Booking {
+    fn sensitive_fields(&mut self) -> Vec<&mut SensitiveString> {
+        self.driver
+            .as_mut()
+            .map(|driver| driver.fields_mut())
+            .unwrap_or_default()
+    }
+}
+
+impl FieldCipher {
+    pub fn new(provider: Box<dyn KeyProvider>) -> Self {
+        Self { provider }
+    }
+
+    pub fn current_key_id(&self) -> &str {
+        self.provider.current_key_id()
+    }
+
+    fn cipher(&self, key_id: &str) -> AppResult<Aes256Gcm> {
+        let key = self.provider.key(key_id).ok_or_else(|| {
+            AppError::internal_server_error(format!("Unknown PII encryption key '{}'", key_id))
+        })?;
+        Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)))
+    }
+
+    pub fn encrypt(&self, plain: &str) -> AppResult<EncryptedValue> {
+        let kid = self.current_key_id();
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher(kid)?
+            .encrypt(&nonce, plain.as_bytes())
+            .map_err(|_| AppError::internal_server_error("PII encryption failed"))?;
+
+        Ok(EncryptedValue {
+            kid: kid.to_string(),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        })
+    }
+
+    pub fn decrypt(&self, value: &EncryptedValue) -> AppResult<String> {
+        let failed = || AppError::internal_server_error("PII decryption failed");
+        let nonce = STANDARD.decode(&value.nonce).map_err(|_| failed())?;
+        if nonce.len() != 12 {
+            return Err(failed());
+        }
+        let ciphertext = STANDARD.decode(&value.ciphertext).map_err(|_| failed())?;
+
+        let plain = self
+            .cipher(&value.kid)?
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| failed())?;
+        String::from_utf8(plain).map_err(|_| failed())
+    }
+
+    /// Encrypt the clear fields, before storing
+    pub fn seal(&self, value: &mut impl EncryptedFields) -> AppResult<()> {
+        for field in value.sensitive_fields() {
+            if let SensitiveString::Plain(plain) = field {
+                *field = SensitiveString::Encrypted(self.encrypt(plain)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Decrypt the encrypted fields, for an authorized read
+    pub fn open(&self, value: &mut impl EncryptedFields) -> AppResult<()> {
+        for field in value.sensitive_fields() {
+            if let SensitiveString::Encrypted(encrypted) = field {
+                *field = SensitiveString::Plain(self.decrypt(encrypted)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-encrypt the fields sealed with an older key, true when something changed
+    pub fn rotate(&self, value: &mut impl EncryptedFields) -> AppResult<bool> {
+        let mut rotated = false;
+        for field in value.sensitive_fields() {
+            if let SensitiveString::Encrypted(encrypted) = field {
+                if encrypted.kid != self.current_key_id() {
+                    *field = SensitiveString::Encrypted(self.encrypt(&self.decrypt(encrypted)?)?);
+                    rotated = true;
+                }
+            }
+        }
+        Ok(rotated)
+    }
+}
+
+/// Replace every field by its masked form
+pub fn redact(value: &mut impl EncryptedFields) {
+    for field in value.sensitive_fields() {
+        *field = SensitiveString::Plain(field.masked());
+    }
+}
+
+/// Encrypt the personal data of a booking before it is stored
+pub async fn seal_booking(booking: &mut Booking) -> AppResult<()> {
+    if booking.sensitive_fields().is_empty() {
+        return Ok(());
+    }
+    get_field_cipher().await?.seal(booking)
+}
+
+/// Prepare a stored booking for a response: personal data is decrypted for its
+/// customer and the identities with `pii:read`, masked for everyone else
+pub async fn present_booking(booking: &mut Booking, identity: &Identity) -> AppResult<()> {
+    if booking.sensitive_fields().is_empty() {
+        return Ok(());
+    }
+
+    let authorized = booking.customer_id == identity.user_id
+        || permission::granted(identity).contains(&Permission::PiiRead);
+    if authorized {
+        let opened = match get_field_cipher().await {
+            Ok(cipher) => cipher.open(booking),
+            Err(error) => Err(error),
+        };
+        match opened {
+            Ok(()) => return Ok(()),
+            Err(error) => log::error!(
+                "Cannot decrypt booking {:?}, returning it masked: {}",
+                booking.id,
+                error
+            ),
+        }
+    }
+
+    redact(booking);
+    Ok(())
+}
+
+pub async fn present_bookings(bookings: &mut [Booking], identity: &Identity) -> AppResult<()> {
+    for booking in bookings.iter_mut() {
+        present_booking(booking, identity).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{fixtures, DriverDetails};
+
+    fn cipher(keys: &[(&str, u8)]) -> FieldCipher {
+        let keys = keys
+            .iter()
+            .map(|(kid, byte)| (kid.to_string(), [*byte; 32]))
+            .collect();
+        FieldCipher::new(Box::new(EnvKeyProvider::new(keys).unwrap()))
+    }
+
+    fn booking() -> Booking {
+        let mut booking = fixtures::booking();
+        booking.driver = Some(DriverDetails {
+            license_number: "B1234567".into(),
+            phone: Some("+33600000000".into()),
+            address: None,
+        });
+        booking
+    }
+
+    #[test]
+    fn test_seal_and_open_booking() {
+        let cipher = cipher(&[("k1", 1)]);
+        let mut booking = booking();
+
+        cipher.seal(&mut booking).unwrap();
+        let driver = booking.driver.clone().unwrap();
+        let SensitiveString::Encrypted(license_number) = &driver.license_number else {
+            panic!("license number not encrypted");
+        };
+        assert_eq!(license_number.kid, "k1");
+        assert!(!license_number.ciphertext.contains("B1234567"));
+
+        cipher.open(&mut booking).unwrap();
+        assert_eq!(
+            booking.driver.unwrap().license_number.plain(),
+            Some("B1234567")
+        );
+    }
+
+    #[test]
+    fn test_rotate_to_new_key() {
+        let mut booking = booking();
+        cipher(&[("k1", 1)]).seal(&mut booking).unwrap();
+
+        // k2 is the new key, k1 is kept to read the old values
+        let rotated = cipher(&[("k2", 2), ("k1", 1)]);
+        assert!(rotated.rotate(&mut booking).unwrap());
+        assert!(!rotated.rotate(&mut booking).unwrap());
+
+        // Once rotated, k1 is no longer needed
+        let mut opened = booking.clone();
+        cipher(&[("k2", 2)]).open(&mut opened).unwrap();
+        assert_eq!(
+            opened.driver.unwrap().phone.unwrap().plain(),
+            Some("+33600000000")
+        );
+        assert!(cipher(&[("k1", 1)]).open(&mut booking).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use bson::doc;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::domain;
+use crate::domain::events::BookingStatusChanged;
+use crate::error::AppResult;
+use crate::models::{Booking, BookingStatus, Notification};
+use crate::services;
+use crate::services::email::BookingEmail;
+
+/// `changed_by` of the history entries written by the expiration job
+pub const EXPIRATION_ACTOR: &str = "expiration";
+
+/// Rejection reason of expired bookings
+pub const EXPIRED_REASON: &str = "expired";
+
+/// Seconds between two sweeps (PENDING_EXPIRATION_INTERVAL_SECS, default 15 minutes,
+/// 0 disables the job)
+fn sweep_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("PENDING_EXPIRATION_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(900);
+
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// Age after which a pending booking expires (PENDING_EXPIRATION_HOURS, default 48)
+pub fn max_pending_age() -> Duration {
+    let hours = std::env::var("PENDING_EXPIRATION_HOURS")
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|hours| *hours > 0)
+        .unwrap_or(48);
+
+    Duration::hours(hours)
+}
+
+/// Expire stale pending bookings periodically in the background
+pub fn spawn_scheduler() {
+    let Some(interval) = sweep_interval() else {
+        log::info!("Pending booking expiration job disabled");
+        return;
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match expire_stale(Utc::now()).await {
+                Ok(0) => {}
+                Ok(expired) => log::info!("{} stale pending bookings expired", expired),
+                Err(error) => log::error!("Pending booking expiration failed: {}", error),
+            }
+        }
+    });
+}
+
+/// Reject a pending booking as expired. The history entry carries the rule so the
+/// approval metrics do not count it as a manager's decision.
+pub fn expire(booking: &mut Booking, max_age: Duration) {
+    booking.set_status(
+        BookingStatus::Rejected(EXPIRED_REASON.to_string()),
+        EXPIRATION_ACTOR.to_string(),
+        Some(format!(
+            "pending for more than {} hours",
+            max_age.num_hours()
+        )),
+    );
+}
+
+/// Reject every booking still PENDING `max_pending_age` after it was made, which frees
+/// its dates. The age only counts office time where the branch has office hours.
+/// Returns the number of bookings expired.
+pub async fn expire_stale(now: DateTime<Utc>) -> AppResult<usize> {
+    let max_age = max_pending_age();
+    // Office time is at most wall time, older bookings only are candidates
+    let filter = doc! {
+        "status": "PENDING",
+        "order_date": { "$lt": bson::DateTime::from_chrono(now - max_age) },
+    };
+    let (bookings, calendar) = services::fanout::try_join2(
+        services::mongodb::collect_many::<Booking>(filter, None),
+        services::office_hours::calendar(),
+    )
+    .await?;
+
+    let mut expired = 0;
+    for mut booking in bookings {
+        let Some(booking_id) = booking.id else {
+            continue;
+        };
+        let age = calendar.elapsed(
+            booking.tenant_id.as_deref(),
+            &booking.timezone,
+            booking.order_date,
+            now,
+        );
+        if age < max_age {
+            continue;
+        }
+        expire(&mut booking, max_age);
+
+        // A manager deciding meanwhile wins, the booking is then left alone
+        let filter = doc! { "_id": booking_id, "status": "PENDING" };
+        if services::mongodb::find_one_and_replace(filter, &booking, None)
+            .await?
+            .is_none()
+        {
+            continue;
+        }
+        expired += 1;
+        services::reservation::release(booking_id).await?;
+        domain::events::publish(BookingStatusChanged::of(&booking, BookingStatus::Pending)).await;
+        services::email::notify_customer(BookingEmail::Rejected, &booking).await;
+
+        let notification = Notification::to_user(
+            &booking.customer_id,
+            "Booking expired",
+            format!(
+                "Your booking from {} to {} was not confirmed in time and has expired.",
+                booking.from_date, booking.to_date
+            ),
+        )
+        .about_booking(booking_id);
+        services::notification::send(vec![notification]).await;
+    }
+    Ok(expired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{approval_time, CreateBookingRequest, OfficeCalendar};
+    use bson::oid::ObjectId;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_expired_bookings_are_not_manager_decisions() {
+        let request = CreateBookingRequest {
+            vehicle_id: ObjectId::new(),
+            from_date: NaiveDate::from_ymd_opt(2025, 8, 1).unwrap(),
+            to_date: NaiveDate::from_ymd_opt(2025, 8, 3).unwrap(),
+            driver: None,
+        };
+        let mut booking = Booking::new(request, "customer_user_1".to_string());
+        expire(&mut booking, Duration::hours(48));
+
+        assert_eq!(
+            booking.status,
+            BookingStatus::Rejected(EXPIRED_REASON.to_string())
+        );
+        let entry = booking.history.last().unwrap();
+        assert_eq!(entry.changed_by, EXPIRATION_ACTOR);
+        assert_eq!(
+            entry.rule.as_deref(),
+            Some("pending for more than 48 hours")
+        );
+        assert_eq!(approval_time(&booking, &OfficeCalendar::default()), None);
+    }
+}
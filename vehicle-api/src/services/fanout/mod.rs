@@ -0,0 +1,100 @@
+use futures::{stream, StreamExt, TryStreamExt};
+use std::future::Future;
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult};
+
+/// Queries of a bounded fan-out in flight at once (FANOUT_CONCURRENCY, default 8)
+fn concurrency() -> usize {
+    std::env::var("FANOUT_CONCURRENCY")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|limit| *limit > 0)
+        .unwrap_or(8)
+}
+
+/// Time each query of a fan-out may take (FANOUT_QUERY_TIMEOUT_MS, default 10 seconds)
+fn query_deadline() -> Duration {
+    let millis = std::env::var("FANOUT_QUERY_TIMEOUT_MS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(10_000);
+
+    Duration::from_millis(millis)
+}
+
+/// Fail a query that outlives its deadline
+pub async fn with_deadline<T>(query: impl Future<Output = AppResult<T>>) -> AppResult<T> {
+    let deadline = query_deadline();
+    tokio::time::timeout(deadline, query).await.map_err(|_| {
+        AppError::internal_server_error(format!(
+            "Query did not complete within {} ms",
+            deadline.as_millis()
+        ))
+    })?
+}
+
+/// Run two independent queries concurrently, the first error cancels the other one.
+/// Queries run on the current task, so they keep the request's tenant scope.
+pub async fn try_join2<A, B>(
+    a: impl Future<Output = AppResult<A>>,
+    b: impl Future<Output = AppResult<B>>,
+) -> AppResult<(A, B)> {
+    futures::try_join!(with_deadline(a), with_deadline(b))
+}
+
+/// Run three independent queries concurrently, the first error cancels the others
+pub async fn try_join3<A, B, C>(
+    a: impl Future<Output = AppResult<A>>,
+    b: impl Future<Output = AppResult<B>>,
+    c: impl Future<Output = AppResult<C>>,
+) -> AppResult<(A, B, C)> {
+    futures::try_join!(with_deadline(a), with_deadline(b), with_deadline(c))
+}
+
+/// Run queries of the same kind with a bounded number in flight, results keep
+/// the order of the queries
+pub async fn try_join_all<T, F>(queries: impl IntoIterator<Item = F>) -> AppResult<Vec<T>>
+where
+    F: Future<Output = AppResult<T>>,
+{
+    stream::iter(queries.into_iter().map(with_deadline))
+        .buffered(concurrency())
+        .try_collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_queries_run_concurrently_and_keep_their_order() {
+        let started = std::time::Instant::now();
+        let results = block_on(try_join_all((0..4u64).map(|i| async move {
+            tokio::time::sleep(Duration::from_millis(100 - i * 20)).await;
+            Ok(i)
+        })))
+        .unwrap();
+
+        assert_eq!(results, vec![0, 1, 2, 3]);
+        assert!(started.elapsed() < Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_first_error_wins() {
+        let result = block_on(try_join2(async { Ok::<_, AppError>(1) }, async {
+            Err::<u8, _>(AppError::not_found("Vehicle not found"))
+        }));
+
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+}
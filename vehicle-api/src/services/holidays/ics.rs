@@ -0,0 +1,165 @@
+use chrono::{Datelike, NaiveDate};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use super::HolidayProvider;
+use crate::error::AppResult;
+use crate::models::{Holiday, HolidaySource};
+
+/// Holidays imported from static ICS files: `<HOLIDAYS_ICS_DIR>/<COUNTRY>.ics`
+pub struct IcsHolidayProvider {
+    dir: Option<PathBuf>,
+    surcharge_percent: f64,
+    blackout: bool,
+    // Parsed calendars per country, files are only read once
+    cache: RwLock<HashMap<String, Vec<Holiday>>>,
+}
+
+impl IcsHolidayProvider {
+    pub fn new(dir: Option<PathBuf>, surcharge_percent: f64, blackout: bool) -> Self {
+        Self {
+            dir,
+            surcharge_percent,
+            blackout,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// HOLIDAYS_ICS_DIR, HOLIDAY_SURCHARGE_PERCENT (default 20) and
+    /// HOLIDAY_BLACKOUT (default true) configure the imported holidays
+    pub fn from_env() -> Self {
+        let dir = std::env::var("HOLIDAYS_ICS_DIR").ok().map(PathBuf::from);
+        let surcharge_percent = std::env::var("HOLIDAY_SURCHARGE_PERCENT")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(20.0);
+        let blackout = std::env::var("HOLIDAY_BLACKOUT")
+            .map(|value| value != "false")
+            .unwrap_or(true);
+
+        if dir.is_none() {
+            log::warn!("HOLIDAYS_ICS_DIR not set, only holiday overrides are used");
+        }
+
+        Self::new(dir, surcharge_percent, blackout)
+    }
+
+    fn load_country(&self, country: &str) -> AppResult<Vec<Holiday>> {
+        if let Some(holidays) = self.cache.read().ok().and_then(|c| c.get(country).cloned()) {
+            return Ok(holidays);
+        }
+
+        let path = match &self.dir {
+            Some(dir) => dir.join(format!("{}.ics", country)),
+            None => return Ok(Vec::new()),
+        };
+        let holidays = match std::fs::read_to_string(&path) {
+            Ok(content) => parse_ics(&content, country, self.surcharge_percent, self.blackout),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error.into()),
+        };
+
+        if let Ok(mut cache) = self.cache.write() {
+            cache.insert(country.to_string(), holidays.clone());
+        }
+        Ok(holidays)
+    }
+}
+
+impl HolidayProvider for IcsHolidayProvider {
+    async fn holidays(&self, country: &str, year: i32) -> AppResult<Vec<Holiday>> {
+        Ok(self
+            .load_country(country)?
+            .into_iter()
+            .filter(|holiday| holiday.date.year() == year)
+            .collect())
+    }
+}
+
+/// Parse the all-day VEVENTs of an ICS calendar (DTSTART;VALUE=DATE + SUMMARY)
+pub fn parse_ics(
+    content: &str,
+    country: &str,
+    surcharge_percent: f64,
+    blackout: bool,
+) -> Vec<Holiday> {
+    // Unfold continuation lines (RFC 5545: lines starting with a space or a tab)
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.trim_end().to_string()),
+        }
+    }
+
+    let mut holidays = Vec::new();
+    let mut date = None;
+    let mut name = None;
+
+    for line in lines {
+        let (key, value) = match line.split_once(':') {
+            Some(parts) => parts,
+            None => continue,
+        };
+        // Drop parameters such as ";VALUE=DATE"
+        let key = key.split(';').next().unwrap_or(key);
+
+        match key {
+            "BEGIN" if value == "VEVENT" => {
+                date = None;
+                name = None;
+            }
+            "DTSTART" => {
+                date = NaiveDate::parse_from_str(&value[..value.len().min(8)], "%Y%m%d").ok()
+            }
+            "SUMMARY" => name = Some(value.replace("\\,", ",")),
+            "END" if value == "VEVENT" => {
+                if let (Some(date), Some(name)) = (date.take(), name.take()) {
+                    holidays.push(Holiday {
+                        country: country.to_string(),
+                        date,
+                        name,
+                        surcharge_percent,
+                        blackout,
+                        source: HolidaySource::Calendar,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    holidays.sort_by_key(|holiday| holiday.date);
+    holidays
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ics_all_day_events() {
+        let content = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20250714\r\n\
+            SUMMARY:Fête nationale\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20250101\r\n\
+            SUMMARY:Jour de\r\n  l'an\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let holidays = parse_ics(content, "FR", 20.0, true);
+
+        assert_eq!(holidays.len(), 2);
+        assert_eq!(
+            holidays[0].date,
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+        );
+        assert_eq!(holidays[0].name, "Jour de l'an");
+        assert_eq!(holidays[1].name, "Fête nationale");
+        assert!(holidays[1].blackout);
+    }
+}
@@ -0,0 +1,59 @@
+pub mod ics;
+
+use bson::doc;
+use chrono::{Datelike, NaiveDate};
+
+use crate::error::AppResult;
+use crate::models::{Holiday, HolidayOverride, Vehicle};
+use crate::services;
+
+pub use ics::IcsHolidayProvider;
+
+/// Source of public holidays per country (static ICS import today, an external API later)
+pub(crate) trait HolidayProvider {
+    async fn holidays(&self, country: &str, year: i32) -> AppResult<Vec<Holiday>>;
+}
+
+// Global holiday provider using OnceCell for lazy initialization
+pub(crate) static HOLIDAY_PROVIDER: tokio::sync::OnceCell<IcsHolidayProvider> =
+    tokio::sync::OnceCell::const_new();
+
+pub async fn get_holiday_provider() -> &'static IcsHolidayProvider {
+    HOLIDAY_PROVIDER
+        .get_or_init(|| async { IcsHolidayProvider::from_env() })
+        .await
+}
+
+/// Country used for vehicles without one (HOLIDAYS_DEFAULT_COUNTRY, default "FR")
+pub fn default_country() -> String {
+    std::env::var("HOLIDAYS_DEFAULT_COUNTRY").unwrap_or_else(|_| "FR".to_string())
+}
+
+pub fn vehicle_country(vehicle: &Vehicle) -> String {
+    vehicle.country.clone().unwrap_or_else(default_country)
+}
+
+/// Holidays of a country between two dates (included), admin overrides applied
+pub async fn get_calendar(
+    country: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> AppResult<Vec<Holiday>> {
+    let provider = get_holiday_provider().await;
+
+    let mut calendar = Vec::new();
+    for year in from.year()..=to.year() {
+        calendar.extend(provider.holidays(country, year).await?);
+    }
+
+    let filter = doc! {
+        "country": country,
+        "date": { "$gte": from.to_string(), "$lte": to.to_string() },
+    };
+    let overrides: Vec<HolidayOverride> = services::mongodb::collect_many(filter, None).await?;
+
+    Ok(Holiday::merge(calendar, overrides)
+        .into_iter()
+        .filter(|holiday| holiday.date >= from && holiday.date <= to)
+        .collect())
+}
@@ -0,0 +1,318 @@
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse};
+use bson::{doc, Document};
+use chrono::{DateTime, Duration, Utc};
+use mongodb::options::{IndexOptions, UpdateOptions};
+use mongodb::IndexModel;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::authentication::api_key::hash_key;
+use crate::error::{AppError, AppResult};
+use crate::models::{IdempotencyRecord, IdempotencyStatus};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// Header carrying the client's key, e.g. a UUID generated once per booking attempt
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+
+/// Header set on responses replayed from an earlier request
+pub const REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Responses are replayed for this long, the key can be reused afterwards
+const RETENTION_HOURS: i64 = 24;
+
+/// A request still in progress after this long is assumed lost (e.g. a restart) and
+/// its key may be used again
+const LOCK_SECONDS: i64 = 60;
+
+/// Request already made with a key: the status it answered and the resource it created.
+/// No response body is stored, the resource is read again to send its current state.
+#[derive(Debug, PartialEq)]
+pub struct Replay {
+    pub status: StatusCode,
+    pub resource_id: String,
+}
+
+/// TTL index dropping the records once their key can be reused
+pub async fn ensure_indexes() -> AppResult<()> {
+    let database = services::mongodb::get_database(services::mongodb::DATABASE_NAME).await?;
+    let index = IndexModel::builder()
+        .keys(doc! { "expires_at": 1 })
+        .options(
+            IndexOptions::builder()
+                .expire_after(std::time::Duration::ZERO)
+                .build(),
+        )
+        .build();
+    database
+        .collection::<Document>(IdempotencyRecord::get_collection())
+        .create_index(index)
+        .await?;
+    Ok(())
+}
+
+/// Key sent by the client, None without the header
+pub fn key(req: &HttpRequest) -> AppResult<Option<String>> {
+    let Some(value) = req.headers().get(IDEMPOTENCY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LENGTH)
+        .ok_or_else(|| {
+            AppError::bad_request(format!(
+                "{} must be 1 to {} visible characters",
+                IDEMPOTENCY_HEADER, MAX_KEY_LENGTH
+            ))
+        })?;
+    Ok(Some(key.to_string()))
+}
+
+/// Keys are scoped to the caller, two users may pick the same key
+fn record_id(user_id: &str, key: &str) -> String {
+    hash_key(&format!("{}:{}", user_id, key))
+}
+
+fn request_hash(request: &impl Serialize) -> AppResult<String> {
+    let body = serde_json::to_vec(request)
+        .map_err(|e| AppError::internal_server_error(format!("Cannot serialize request: {}", e)))?;
+    Ok(hash_key(&String::from_utf8_lossy(&body)))
+}
+
+/// What to do with a request carrying a key already seen
+pub fn check_existing(
+    record: &IdempotencyRecord,
+    request_hash: &str,
+    now: DateTime<Utc>,
+) -> AppResult<Option<Replay>> {
+    if record.request_hash != request_hash {
+        return Err(AppError::bad_request(format!(
+            "{} was already used for a different request",
+            IDEMPOTENCY_HEADER
+        )));
+    }
+    match record.status {
+        IdempotencyStatus::Completed => {
+            let resource_id = record.resource_id.clone().ok_or_else(|| {
+                AppError::conflict(format!(
+                    "{} was already used, retry with a new one",
+                    IDEMPOTENCY_HEADER
+                ))
+            })?;
+            let status = record
+                .response_status
+                .and_then(|status| StatusCode::from_u16(status).ok())
+                .unwrap_or(StatusCode::OK);
+            Ok(Some(Replay {
+                status,
+                resource_id,
+            }))
+        }
+        IdempotencyStatus::InProgress if record.expires_at > now => Err(AppError::conflict(
+            "A request with this Idempotency-Key is still in progress, retry later",
+        )),
+        // Lost lock: the request runs again
+        IdempotencyStatus::InProgress => Ok(None),
+    }
+}
+
+/// Claim a key before running the request. Returns what to replay when the key was
+/// already used for the same request, None when the request should run.
+pub async fn begin(
+    user_id: &str,
+    key: &str,
+    request: &impl Serialize,
+) -> AppResult<Option<Replay>> {
+    let id = record_id(user_id, key);
+    let request_hash = request_hash(request)?;
+    let now = Utc::now();
+
+    // Expired keys are free again
+    services::mongodb::delete_one(
+        IdempotencyRecord::get_collection(),
+        doc! {
+            "_id": &id,
+            "status": IdempotencyStatus::Completed.to_string(),
+            "expires_at": { "$lte": bson::DateTime::from_chrono(now) },
+        },
+        None,
+    )
+    .await?;
+
+    let record = IdempotencyRecord {
+        id: id.clone(),
+        user_id: user_id.to_string(),
+        request_hash: request_hash.clone(),
+        status: IdempotencyStatus::InProgress,
+        response_status: None,
+        resource_id: None,
+        created_at: now,
+        expires_at: now + Duration::seconds(LOCK_SECONDS),
+    };
+    let document = bson::to_document(&record).map_err(|e| {
+        AppError::internal_server_error(format!("Cannot serialize document: {}", e))
+    })?;
+    let result = services::mongodb::update_one(
+        IdempotencyRecord::get_collection(),
+        doc! { "_id": &id },
+        doc! { "$setOnInsert": document },
+        UpdateOptions::builder().upsert(true).build(),
+    )
+    .await?;
+    if result.upserted_id.is_some() {
+        return Ok(None);
+    }
+
+    let existing: IdempotencyRecord = services::mongodb::get_one(doc! { "_id": &id }, None)
+        .await?
+        .ok_or_else(|| AppError::conflict("Idempotency-Key changed meanwhile, retry later"))?;
+    let response = check_existing(&existing, &request_hash, now)?;
+    if response.is_none() {
+        // Take over the lost lock, unless another retry just did
+        let result = services::mongodb::update_one(
+            IdempotencyRecord::get_collection(),
+            doc! {
+                "_id": &id,
+                "status": IdempotencyStatus::InProgress.to_string(),
+                "expires_at": { "$lte": bson::DateTime::from_chrono(now) },
+            },
+            doc! { "$set": {
+                "created_at": bson::DateTime::from_chrono(now),
+                "expires_at": bson::DateTime::from_chrono(now + Duration::seconds(LOCK_SECONDS)),
+            } },
+            None,
+        )
+        .await?;
+        if result.modified_count == 0 {
+            return Err(AppError::conflict(
+                "A request with this Idempotency-Key is still in progress, retry later",
+            ));
+        }
+    }
+    Ok(response)
+}
+
+/// Store the status of a claimed key and the ID of the resource created, never the
+/// response body. Best effort: the request already succeeded, a failure only means a
+/// retry runs it again.
+pub async fn complete(user_id: &str, key: &str, status: StatusCode, resource_id: &str) {
+    let now = Utc::now();
+    let result = services::mongodb::update_one(
+        IdempotencyRecord::get_collection(),
+        doc! { "_id": record_id(user_id, key) },
+        doc! { "$set": {
+            "status": IdempotencyStatus::Completed.to_string(),
+            "response_status": status.as_u16() as i32,
+            "resource_id": resource_id,
+            "expires_at": bson::DateTime::from_chrono(now + Duration::hours(RETENTION_HOURS)),
+        } },
+        None,
+    )
+    .await;
+    if let Err(error) = result {
+        log::error!(
+            "Failed to store the response of an idempotent request: {}",
+            error
+        );
+    }
+}
+
+/// Free a claimed key after a failed request, so the client can retry with it
+pub async fn release(user_id: &str, key: &str) {
+    let result = services::mongodb::delete_one(
+        IdempotencyRecord::get_collection(),
+        doc! {
+            "_id": record_id(user_id, key),
+            "status": IdempotencyStatus::InProgress.to_string(),
+        },
+        None,
+    )
+    .await;
+    if let Err(error) = result {
+        log::error!("Failed to release an idempotency key: {}", error);
+    }
+}
+
+impl Replay {
+    /// Response sent again with the first status, `body` being the resource read again
+    pub fn respond(&self, body: Value) -> HttpResponse {
+        HttpResponse::build(self.status)
+            .insert_header((REPLAYED_HEADER, "true"))
+            .json(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn record(status: IdempotencyStatus, expires_at: DateTime<Utc>) -> IdempotencyRecord {
+        IdempotencyRecord {
+            id: record_id("customer_user_1", "key-1"),
+            user_id: "customer_user_1".to_string(),
+            request_hash: "hash".to_string(),
+            status,
+            response_status: Some(201),
+            resource_id: Some("66c1f0a2e4b0a1b2c3d4e5f6".to_string()),
+            created_at: expires_at,
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_key_header() {
+        let req = TestRequest::default().to_http_request();
+        assert!(key(&req).unwrap().is_none());
+
+        let req = TestRequest::default()
+            .insert_header((IDEMPOTENCY_HEADER, " 3f2a-77 "))
+            .to_http_request();
+        assert_eq!(key(&req).unwrap().as_deref(), Some("3f2a-77"));
+
+        let req = TestRequest::default()
+            .insert_header((IDEMPOTENCY_HEADER, "k".repeat(300)))
+            .to_http_request();
+        assert!(key(&req).is_err());
+        assert_ne!(
+            record_id("customer_user_1", "key-1"),
+            record_id("customer_user_2", "key-1")
+        );
+    }
+
+    #[test]
+    fn test_retries_replay_the_response() {
+        let now = Utc::now();
+        let completed = record(IdempotencyStatus::Completed, now + Duration::hours(1));
+        let replay = check_existing(&completed, "hash", now).unwrap().unwrap();
+        assert_eq!(
+            replay,
+            Replay {
+                status: StatusCode::CREATED,
+                resource_id: "66c1f0a2e4b0a1b2c3d4e5f6".to_string(),
+            }
+        );
+        let response = replay.respond(serde_json::json!({ "id": replay.resource_id }));
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers().get(REPLAYED_HEADER).unwrap(), "true");
+
+        // Same key, different booking
+        assert!(matches!(
+            check_existing(&completed, "other", now),
+            Err(AppError::BadRequest { .. })
+        ));
+
+        let running = record(IdempotencyStatus::InProgress, now + Duration::seconds(30));
+        assert!(matches!(
+            check_existing(&running, "hash", now),
+            Err(AppError::Conflict { .. })
+        ));
+        let lost = record(IdempotencyStatus::InProgress, now - Duration::seconds(1));
+        assert!(check_existing(&lost, "hash", now).unwrap().is_none());
+    }
+}
@@ -0,0 +1,154 @@
+use bson::oid::ObjectId;
+use std::sync::LazyLock;
+
+use crate::error::AppResult;
+use crate::services::storage::{Storage, STORAGE_BACKEND};
+
+/// Photos a vehicle can have at most
+pub const MAX_IMAGES_PER_VEHICLE: usize = 20;
+
+// Read once from VEHICLE_IMAGE_MAX_BYTES, 5 MiB by default
+static MAX_IMAGE_BYTES: LazyLock<usize> = LazyLock::new(|| {
+    std::env::var("VEHICLE_IMAGE_MAX_BYTES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|bytes| *bytes > 0)
+        .unwrap_or(5 * 1024 * 1024)
+});
+
+/// Largest image file accepted
+pub fn max_image_bytes() -> usize {
+    *MAX_IMAGE_BYTES
+}
+
+/// File of a multipart upload, before it is checked
+pub struct ImageUpload {
+    pub filename: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
+/// Image formats accepted, recognized by their first bytes rather than the content
+/// type the client announces
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl ImageFormat {
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0xFF, 0xD8, 0xFF, ..] => Some(Self::Jpeg),
+            [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some(Self::Png),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::Webp => "image/webp",
+        }
+    }
+}
+
+/// Whether an `Accept` header allows a content type: an exact match, `type/*` or `*/*`
+/// with a non zero quality. No header accepts anything.
+pub fn accepts(accept: Option<&str>, content_type: &str) -> bool {
+    let Some(accept) = accept.filter(|accept| !accept.trim().is_empty()) else {
+        return true;
+    };
+    let (kind, _) = content_type.split_once('/').unwrap_or((content_type, ""));
+
+    accept.split(',').any(|range| {
+        let mut parts = range.split(';').map(str::trim);
+        let media = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let refused = parts.any(|parameter| {
+            parameter
+                .strip_prefix("q=")
+                .and_then(|quality| quality.parse::<f32>().ok())
+                .is_some_and(|quality| quality <= 0.0)
+        });
+        let matches = media == "*/*"
+            || media == content_type
+            || media.strip_suffix("/*").is_some_and(|range| range == kind);
+        matches && !refused
+    })
+}
+
+/// Where the bytes of an image are stored
+fn storage_key(vehicle_id: &ObjectId, image_id: &str) -> String {
+    format!("vehicles/{}/images/{}", vehicle_id.to_hex(), image_id)
+}
+
+pub async fn store(
+    vehicle_id: &ObjectId,
+    image_id: &str,
+    format: ImageFormat,
+    bytes: Vec<u8>,
+) -> AppResult<()> {
+    STORAGE_BACKEND
+        .put(
+            &storage_key(vehicle_id, image_id),
+            format.content_type(),
+            bytes,
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn load(vehicle_id: &ObjectId, image_id: &str) -> AppResult<Vec<u8>> {
+    STORAGE_BACKEND
+        .get(&storage_key(vehicle_id, image_id))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_are_recognized_by_their_bytes() {
+        assert_eq!(
+            ImageFormat::sniff(&[0xFF, 0xD8, 0xFF, 0xE0, 0x00]),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(
+            ImageFormat::sniff(b"\x89PNG\r\n\x1a\n\x00\x00"),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(
+            ImageFormat::sniff(b"RIFF\x24\x00\x00\x00WEBPVP8 "),
+            Some(ImageFormat::Webp)
+        );
+        assert_eq!(ImageFormat::sniff(b"GIF89a"), None);
+        assert_eq!(ImageFormat::sniff(b"<svg xmlns="), None);
+    }
+
+    #[test]
+    fn test_accept_header_negotiation() {
+        assert!(accepts(None, "image/webp"));
+        assert!(accepts(Some("image/webp,image/*;q=0.8"), "image/png"));
+        assert!(accepts(Some("text/html, */*;q=0.1"), "image/jpeg"));
+        assert!(accepts(Some("IMAGE/JPEG"), "image/jpeg"));
+
+        assert!(!accepts(Some("image/avif, image/png"), "image/webp"));
+        assert!(!accepts(Some("image/webp;q=0, image/png"), "image/webp"));
+        assert!(!accepts(Some("application/json"), "image/jpeg"));
+    }
+
+    #[test]
+    fn test_storage_keys_are_valid() {
+        let vehicle_id = ObjectId::parse_str("66b1f0c2a1b2c3d4e5f60718").unwrap();
+        let key = storage_key(&vehicle_id, "66b1f0c2a1b2c3d4e5f60719");
+
+        assert_eq!(
+            key,
+            "vehicles/66b1f0c2a1b2c3d4e5f60718/images/66b1f0c2a1b2c3d4e5f60719"
+        );
+        assert!(crate::services::storage::is_valid_key(&key));
+    }
+}
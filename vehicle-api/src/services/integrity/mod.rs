@@ -0,0 +1,132 @@
+use bson::doc;
+use chrono::Utc;
+
+use crate::error::AppResult;
+use crate::models::{
+    find_issues, Booking, BookingExtension, ExtensionStatus, IntegrityIssue, IntegrityIssueKind,
+    IntegrityReport, ReservationDay,
+};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// Seconds between two scheduled checks (INTEGRITY_CHECK_INTERVAL_SECS, default 1 day,
+/// 0 disables the job)
+fn run_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("INTEGRITY_CHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(86_400);
+
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// Whether the scheduled check repairs what it finds (INTEGRITY_AUTO_REPAIR, default false)
+fn auto_repair() -> bool {
+    std::env::var("INTEGRITY_AUTO_REPAIR")
+        .map(|value| value == "true")
+        .unwrap_or(false)
+}
+
+/// Cross-check the bookings and their reservation days periodically in the background
+pub fn spawn_scheduler() {
+    let Some(interval) = run_interval() else {
+        log::info!("Integrity check disabled");
+        return;
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match check(auto_repair(), None).await {
+                Ok(report) if report.issues.is_empty() => {}
+                Ok(report) => log::warn!(
+                    "Integrity check found {} issues between bookings and reservation days",
+                    report.issues.len()
+                ),
+                Err(error) => log::error!("Integrity check failed: {}", error),
+            }
+        }
+    });
+}
+
+/// Compare the active bookings with the reservation days from today on, repair the
+/// issues when asked to, and store the report
+pub async fn check(repair: bool, requested_by: Option<String>) -> AppResult<IntegrityReport> {
+    let started_at = Utc::now();
+    let today = started_at.date_naive();
+
+    let bookings: Vec<Booking> = services::mongodb::collect_many(
+        doc! {
+            "status": { "$in": ["PENDING", "CONFIRMED"] },
+            "to_date": { "$gte": today.to_string() },
+        },
+        None,
+    )
+    .await?;
+    let extensions: Vec<BookingExtension> = services::mongodb::collect_many(
+        doc! {
+            "status": ExtensionStatus::Pending.to_string(),
+            "to_date": { "$gte": today.to_string() },
+        },
+        None,
+    )
+    .await?;
+    let days: Vec<ReservationDay> =
+        services::mongodb::collect_many(doc! { "day": { "$gte": today.to_string() } }, None)
+            .await?;
+
+    let mut issues = find_issues(&bookings, &extensions, &days, today, Utc::now());
+    if repair {
+        // Free the wrong days first, the missing ones may be among them
+        issues.sort_by_key(|issue| issue.kind == IntegrityIssueKind::MissingDay);
+        for issue in &mut issues {
+            match repair_issue(issue).await {
+                Ok(repaired) => issue.repaired = repaired,
+                Err(error) => log::error!(
+                    "Cannot repair {} of vehicle {} on {}: {}",
+                    issue.kind,
+                    issue.vehicle_id,
+                    issue.day,
+                    error
+                ),
+            }
+        }
+    }
+
+    let mut report = IntegrityReport {
+        id: None,
+        repair,
+        bookings_checked: bookings.len() as u64,
+        days_checked: days.len() as u64,
+        issues,
+        requested_by,
+        started_at,
+        finished_at: Utc::now(),
+    };
+    report.id = Some(services::mongodb::insert_one(&report, None).await?);
+    Ok(report)
+}
+
+/// Fix one issue, false when it cannot be fixed automatically
+async fn repair_issue(issue: &IntegrityIssue) -> AppResult<bool> {
+    match (issue.kind, issue.booking_id, issue.holder_id) {
+        (IntegrityIssueKind::OrphanDay | IntegrityIssueKind::StrayDay, _, Some(holder_id)) => {
+            // Only if still held by the same booking, it may have been claimed meanwhile
+            let filter = doc! {
+                "_id": ReservationDay::key(&issue.vehicle_id, issue.day),
+                "booking_id": holder_id,
+            };
+            let deleted =
+                services::mongodb::delete_many(ReservationDay::get_collection(), filter, None)
+                    .await?;
+            Ok(deleted > 0)
+        }
+        (IntegrityIssueKind::MissingDay, Some(booking_id), _) => {
+            services::reservation::claim(booking_id, issue.vehicle_id, issue.day, issue.day)
+                .await?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
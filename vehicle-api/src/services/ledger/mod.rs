@@ -0,0 +1,56 @@
+use bson::{doc, oid::ObjectId, Document};
+use mongodb::options::{FindOneOptions, FindOptions, IndexOptions};
+use mongodb::IndexModel;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{LedgerEntry, NewLedgerEntry};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// Appends tried while concurrent ones take the next sequence
+const APPEND_ATTEMPTS: u32 = 3;
+
+/// Entries of a booking's ledger, in order
+pub async fn entries(booking_id: &ObjectId) -> AppResult<Vec<LedgerEntry>> {
+    let options = FindOptions::builder().sort(doc! { "sequence": 1 }).build();
+    services::mongodb::collect_many(doc! { "booking_id": booking_id }, options).await
+}
+
+/// Unique sequence per booking: of two concurrent appends only one takes the next number
+pub async fn ensure_indexes() -> AppResult<()> {
+    let database = services::mongodb::get_database(services::mongodb::DATABASE_NAME).await?;
+    let index = IndexModel::builder()
+        .keys(doc! { "booking_id": 1, "sequence": 1 })
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    database
+        .collection::<Document>(LedgerEntry::get_collection())
+        .create_index(index)
+        .await?;
+    Ok(())
+}
+
+/// Append an entry to a booking's ledger. Entries are never updated nor deleted.
+pub async fn append(entry: NewLedgerEntry) -> AppResult<LedgerEntry> {
+    let options = FindOneOptions::builder()
+        .sort(doc! { "sequence": -1 })
+        .build();
+    let mut attempt = 1;
+    loop {
+        let last: Option<LedgerEntry> =
+            services::mongodb::get_one(doc! { "booking_id": entry.booking_id }, options.clone())
+                .await?;
+
+        let mut appended =
+            LedgerEntry::append(last.as_ref(), entry.clone()).map_err(AppError::conflict)?;
+        match services::mongodb::insert_one(&appended, None).await {
+            Ok(id) => {
+                appended.id = Some(id);
+                return Ok(appended);
+            }
+            // Another entry took the sequence meanwhile, append after it
+            Err(AppError::Conflict { .. }) if attempt < APPEND_ATTEMPTS => attempt += 1,
+            Err(error) => return Err(error),
+        }
+    }
+}
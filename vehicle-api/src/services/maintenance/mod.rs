@@ -0,0 +1,41 @@
+use bson::{doc, oid::ObjectId};
+use chrono::Utc;
+use mongodb::options::FindOneOptions;
+
+use crate::error::AppResult;
+use crate::models::{Booking, MaintenanceRecord, ServiceWarning};
+use crate::services;
+
+/// Latest service of a vehicle, the one planning the next
+async fn latest(vehicle_id: &ObjectId) -> AppResult<Option<MaintenanceRecord>> {
+    let options = FindOneOptions::builder()
+        .sort(doc! { "service_date": -1, "mileage_km": -1 })
+        .build();
+    services::mongodb::get_one(doc! { "vehicle_id": vehicle_id }, options).await
+}
+
+/// Odometer of the vehicle at its last handover, the return when it came back
+async fn last_odometer(vehicle_id: &ObjectId) -> AppResult<Option<u32>> {
+    let options = FindOneOptions::builder()
+        .sort(doc! { "check_in.at": -1 })
+        .build();
+    let filter = doc! { "vehicle_id": vehicle_id, "check_in": { "$exists": true } };
+    let booking: Option<Booking> = services::mongodb::get_one(filter, options).await?;
+
+    Ok(booking
+        .and_then(|booking| booking.check_out.or(booking.check_in))
+        .map(|handover| handover.odometer_km))
+}
+
+/// Next service of a vehicle due soon or overdue, against today and its latest mileage
+/// from services and handovers
+pub async fn service_warnings(vehicle_id: &ObjectId) -> AppResult<Vec<ServiceWarning>> {
+    let (record, odometer) =
+        services::fanout::try_join2(latest(vehicle_id), last_odometer(vehicle_id)).await?;
+    let Some(record) = record else {
+        return Ok(Vec::new());
+    };
+
+    let mileage_km = odometer.unwrap_or_default().max(record.mileage_km);
+    Ok(record.service_warnings(Utc::now().date_naive(), mileage_km))
+}
@@ -0,0 +1,28 @@
+use std::sync::LazyLock;
+
+use prometheus::core::Collector;
+use prometheus::{Encoder, Registry, TextEncoder};
+
+use crate::error::{AppError, AppResult};
+
+/// Content type of the Prometheus text format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+// Metrics exposed on GET /metrics, each module registering its own
+static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+/// Expose a metric, a name already taken is logged and the metric left out
+pub fn register(collector: impl Collector + 'static) {
+    if let Err(error) = REGISTRY.register(Box::new(collector)) {
+        log::error!("Failed to register metric: {}", error);
+    }
+}
+
+/// Every registered metric in the Prometheus text format
+pub fn render() -> AppResult<String> {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&REGISTRY.gather(), &mut buffer)
+        .map_err(|e| AppError::internal_server_error(format!("Cannot encode metrics: {}", e)))?;
+    String::from_utf8(buffer).map_err(|e| AppError::internal_server_error(e.to_string()))
+}
@@ -1 +1,35 @@
+pub mod accounting;
+pub mod anomaly;
+pub mod archive;
+pub mod audit;
+pub mod backup;
+pub mod booking;
+pub mod broadcast;
+pub mod calendar;
+pub mod changeset;
+pub mod currency;
+pub mod digest;
+pub mod email;
+pub mod encryption;
+pub mod expiration;
+pub mod fanout;
+pub mod holidays;
+pub mod idempotency;
+pub mod image;
+pub mod integrity;
+pub mod ledger;
+pub mod maintenance;
+pub mod metrics;
 pub mod mongodb;
+pub mod notification;
+pub mod office_hours;
+pub mod payments;
+pub mod reminder;
+pub mod reservation;
+pub mod risk;
+pub mod search;
+pub mod similar;
+pub mod station;
+pub mod storage;
+pub mod warmup;
+pub mod webhook;
@@ -0,0 +1,28 @@
+use bson::doc;
+use std::env;
+
+use crate::error::{AppError, AppResult};
+use crate::models::AutoConfirmPolicy;
+use crate::services;
+
+/// Load the auto-confirmation policy
+/// Priority: active policy in MongoDB > JSON file from AUTO_CONFIRM_POLICY_PATH > no rules
+pub async fn get_auto_confirm_policy() -> AppResult<AutoConfirmPolicy> {
+    let filter = doc! { "active": true };
+    if let Some(policy) = services::mongodb::get_one::<AutoConfirmPolicy>(filter, None).await? {
+        return Ok(policy);
+    }
+
+    if let Ok(path) = env::var("AUTO_CONFIRM_POLICY_PATH") {
+        let content = std::fs::read_to_string(&path)?;
+        let policy = serde_json::from_str(&content).map_err(|e| {
+            AppError::internal_server_error(format!(
+                "Invalid auto-confirm policy in {}: {}",
+                path, e
+            ))
+        })?;
+        return Ok(policy);
+    }
+
+    Ok(AutoConfirmPolicy::default())
+}
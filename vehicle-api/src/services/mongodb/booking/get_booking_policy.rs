@@ -0,0 +1,35 @@
+use bson::doc;
+use std::env;
+use std::sync::LazyLock;
+
+use crate::error::{AppError, AppResult};
+use crate::models::BookingPolicy;
+use crate::services;
+
+// Read once from BOOKING_POLICY_PATH, None without it. A broken file is reported at
+// every use, as when it was read each time.
+static FILE_POLICY: LazyLock<Result<Option<BookingPolicy>, String>> = LazyLock::new(|| {
+    let Ok(path) = env::var("BOOKING_POLICY_PATH") else {
+        return Ok(None);
+    };
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Cannot read booking policy {}: {}", path, e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Invalid booking policy in {}: {}", path, e))
+});
+
+/// Load the booking transition policy
+/// Priority: active policy in MongoDB > JSON file from BOOKING_POLICY_PATH > built-in default
+pub async fn get_booking_policy() -> AppResult<BookingPolicy> {
+    let filter = doc! { "active": true };
+    if let Some(policy) = services::mongodb::get_one::<BookingPolicy>(filter, None).await? {
+        return Ok(policy);
+    }
+
+    match &*FILE_POLICY {
+        Ok(Some(policy)) => Ok(policy.clone()),
+        Ok(None) => Ok(BookingPolicy::default()),
+        Err(message) => Err(AppError::internal_server_error(message)),
+    }
+}
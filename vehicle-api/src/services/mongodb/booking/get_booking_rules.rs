@@ -0,0 +1,16 @@
+use bson::doc;
+
+use crate::error::AppResult;
+use crate::models::BookingRules;
+use crate::services;
+
+/// Load the booking rules
+/// Priority: active rules in MongoDB > BOOKING_* environment variables
+pub async fn get_booking_rules() -> AppResult<BookingRules> {
+    let filter = doc! { "active": true };
+    if let Some(rules) = services::mongodb::get_one::<BookingRules>(filter, None).await? {
+        return Ok(rules);
+    }
+
+    Ok(BookingRules::from_env())
+}
@@ -0,0 +1,28 @@
+use bson::doc;
+use std::env;
+
+use crate::error::{AppError, AppResult};
+use crate::models::CancellationPolicy;
+use crate::services;
+
+/// Load the cancellation fee policy
+/// Priority: active policy in MongoDB > JSON file from CANCELLATION_POLICY_PATH > built-in default
+pub async fn get_cancellation_policy() -> AppResult<CancellationPolicy> {
+    let filter = doc! { "active": true };
+    if let Some(policy) = services::mongodb::get_one::<CancellationPolicy>(filter, None).await? {
+        return Ok(policy);
+    }
+
+    if let Ok(path) = env::var("CANCELLATION_POLICY_PATH") {
+        let content = std::fs::read_to_string(&path)?;
+        let policy = serde_json::from_str(&content).map_err(|e| {
+            AppError::internal_server_error(format!(
+                "Invalid cancellation policy in {}: {}",
+                path, e
+            ))
+        })?;
+        return Ok(policy);
+    }
+
+    Ok(CancellationPolicy::default())
+}
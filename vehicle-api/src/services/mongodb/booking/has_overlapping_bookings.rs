@@ -6,10 +6,12 @@ use crate::services;
 
 /// Check if there are any overlapping bookings for a specific vehicle and date range
 /// Only considers bookings with PENDING or CONFIRMED status as conflicts
+/// `excluded` is a booking changing dates, it cannot conflict with itself
 pub async fn has_overlapping_bookings(
     vehicle_id: ObjectId,
     from_date: NaiveDate,
     to_date: NaiveDate,
+    excluded: Option<ObjectId>,
 ) -> AppResult<bool> {
     // Build the overlap query with status filtering
     let from_bson = bson::to_bson(&from_date).map_err(|e| {
@@ -20,7 +22,7 @@ pub async fn has_overlapping_bookings(
     })?;
 
     // Find overlapping bookings that are PENDING or CONFIRMED
-    let filter = doc! {
+    let mut filter = doc! {
         "vehicle_id": vehicle_id,
         "$and": [
             { "from_date": { "$lte": to_bson } },      // existing.start <= new.end
@@ -31,6 +33,9 @@ pub async fn has_overlapping_bookings(
             ]}
         ]
     };
+    if let Some(excluded) = excluded {
+        filter.insert("_id", doc! { "$ne": excluded });
+    }
 
     let bookings: Vec<crate::models::Booking> =
         services::mongodb::collect_many(filter, None).await?;
@@ -1,2 +1,10 @@
+pub mod get_auto_confirm_policy;
+pub mod get_booking_policy;
+pub mod get_booking_rules;
+pub mod get_cancellation_policy;
 pub mod has_overlapping_bookings;
+pub use get_auto_confirm_policy::get_auto_confirm_policy;
+pub use get_booking_policy::get_booking_policy;
+pub use get_booking_rules::get_booking_rules;
+pub use get_cancellation_policy::get_cancellation_policy;
 pub use has_overlapping_bookings::has_overlapping_bookings;
@@ -0,0 +1,21 @@
+use bson::{doc, Document};
+use mongodb::IndexModel;
+
+use crate::error::AppResult;
+use crate::models::Vehicle;
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+/// 2dsphere index on the vehicles' `location`, `$nearSphere` needs it. Vehicles without
+/// a location are left out of the index.
+pub async fn ensure_indexes() -> AppResult<()> {
+    let database = services::mongodb::get_database(services::mongodb::DATABASE_NAME).await?;
+    let index = IndexModel::builder()
+        .keys(doc! { "location": "2dsphere" })
+        .build();
+    database
+        .collection::<Document>(Vehicle::get_collection())
+        .create_index(index)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult};
+
+/// A ping slower than this counts as failed
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Consecutive failed pings and client replacements since startup
+pub struct PingTracker {
+    consecutive_failures: AtomicU32,
+    reconnects: AtomicU64,
+}
+
+/// Returned by GET /health/mongodb
+#[derive(Clone, Debug, Serialize)]
+pub struct MongoHealth {
+    pub status: &'static str,
+    pub message: String,
+    pub consecutive_failures: u32,
+    pub reconnects: u64,
+}
+
+static PING_TRACKER: PingTracker = PingTracker::new();
+
+/// Consecutive failed pings before the client is replaced
+/// (MONGODB_RECONNECT_AFTER_FAILURES, default 3)
+fn failure_threshold() -> u32 {
+    std::env::var("MONGODB_RECONNECT_AFTER_FAILURES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|threshold| *threshold > 0)
+        .unwrap_or(3)
+}
+
+/// Seconds between two background pings (MONGODB_HEALTH_INTERVAL_SECS, default 10, 0 disables them)
+fn check_interval() -> Option<Duration> {
+    let seconds = std::env::var("MONGODB_HEALTH_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(10);
+
+    (seconds > 0).then(|| Duration::from_secs(seconds))
+}
+
+impl PingTracker {
+    pub const fn new() -> Self {
+        Self {
+            consecutive_failures: AtomicU32::new(0),
+            reconnects: AtomicU64::new(0),
+        }
+    }
+
+    /// Count a ping, true when the failures reached the threshold and the client
+    /// must be replaced (the count starts over)
+    pub fn record(&self, healthy: bool, threshold: u32) -> bool {
+        if healthy {
+            self.consecutive_failures.store(0, Ordering::Relaxed);
+            return false;
+        }
+
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures < threshold {
+            return false;
+        }
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures.load(Ordering::Relaxed)
+    }
+
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+}
+
+/// Ping through the current client, connecting first when there is none. Both count
+/// in the timeout, a connection hanging on a down server fails the ping too.
+async fn ping() -> AppResult<()> {
+    tokio::time::timeout(PING_TIMEOUT, async {
+        let client = super::get_mongodb_client().await?;
+        client
+            .database("admin")
+            .run_command(bson::doc! { "ping": 1 })
+            .await?;
+        AppResult::Ok(())
+    })
+    .await
+    .map_err(|_| AppError::internal_server_error("MongoDB ping timed out"))?
+}
+
+/// Ping MongoDB and replace the client after too many consecutive failures,
+/// so a client stuck on a restarted server does not keep failing
+pub async fn check() -> MongoHealth {
+    let result = ping().await;
+    if PING_TRACKER.record(result.is_ok(), failure_threshold()) {
+        log::warn!("MongoDB unreachable, replacing the client");
+        super::reset_client().await;
+    }
+
+    let (status, message) = match result {
+        Ok(()) => ("healthy", "MongoDB connection is working".to_string()),
+        Err(error) => ("unhealthy", format!("MongoDB ping failed: {}", error)),
+    };
+    MongoHealth {
+        status,
+        message,
+        consecutive_failures: PING_TRACKER.consecutive_failures(),
+        reconnects: PING_TRACKER.reconnects(),
+    }
+}
+
+/// Ping MongoDB periodically in the background
+pub fn spawn_monitor() {
+    let Some(interval) = check_interval() else {
+        log::info!("MongoDB health monitor disabled");
+        return;
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            check().await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_replaced_after_consecutive_failures() {
+        let tracker = PingTracker::new();
+
+        assert!(!tracker.record(false, 3));
+        assert!(!tracker.record(false, 3));
+        // A success in between starts the count over
+        assert!(!tracker.record(true, 3));
+        assert!(!tracker.record(false, 3));
+        assert!(!tracker.record(false, 3));
+        assert!(tracker.record(false, 3));
+
+        assert_eq!(tracker.reconnects(), 1);
+        assert_eq!(tracker.consecutive_failures(), 0);
+    }
+}
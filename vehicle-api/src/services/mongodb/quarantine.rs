@@ -0,0 +1,176 @@
+use bson::{doc, Bson, Document, RawDocumentBuf};
+use chrono::Utc;
+use mongodb::options::UpdateOptions;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use super::MongoStruct;
+use crate::error::AppResult;
+use crate::models::QuarantinedDocument;
+use crate::services;
+use crate::util;
+
+// Read once from MONGODB_LENIENT_READS, true to skip the documents a list cannot decode
+// rather than failing it
+static LENIENT_READS: LazyLock<bool> =
+    LazyLock::new(|| std::env::var("MONGODB_LENIENT_READS").as_deref() == Ok("true"));
+
+/// Whether lists skip and quarantine undecodable documents
+pub fn is_lenient() -> bool {
+    *LENIENT_READS
+}
+
+/// Decode the documents read from `collection`, skipping those that fail. Skipped
+/// documents are quarantined in the background, a failure there never fails the read.
+pub fn decode<T: DeserializeOwned>(
+    collection: &'static str,
+    documents: Vec<RawDocumentBuf>,
+) -> Vec<T> {
+    let (decoded, rejected) = split::<T>(documents);
+    if rejected.is_empty() {
+        return decoded;
+    }
+
+    log::warn!(
+        "Skipped {} undecodable documents of {}",
+        rejected.len(),
+        collection
+    );
+    // A read-only instance cannot write the quarantine, the primary region's fills it
+    if !util::read_only::is_enabled() {
+        actix_web::rt::spawn(async move {
+            for (document, error) in rejected {
+                if let Err(failure) = quarantine(collection, document, error).await {
+                    log::error!(
+                        "Failed to quarantine a {} document: {}",
+                        collection,
+                        failure
+                    );
+                }
+            }
+        });
+    }
+    decoded
+}
+
+/// Decoded documents, and the others with their deserialization error
+fn split<T: DeserializeOwned>(
+    documents: Vec<RawDocumentBuf>,
+) -> (Vec<T>, Vec<(RawDocumentBuf, String)>) {
+    let mut decoded = Vec::with_capacity(documents.len());
+    let mut rejected = Vec::new();
+    for document in documents {
+        match bson::from_slice::<T>(document.as_bytes()) {
+            Ok(value) => decoded.push(value),
+            Err(error) => rejected.push((document, error.to_string())),
+        }
+    }
+    (decoded, rejected)
+}
+
+/// Keep a copy of an undecodable document, reported the first time only
+async fn quarantine(collection: &str, document: RawDocumentBuf, error: String) -> AppResult<()> {
+    let document: Document = document.to_document().unwrap_or_default();
+    let document_id = document.get("_id").cloned().unwrap_or(Bson::Null);
+    let tenant_id = document.get_str("tenant_id").ok().map(str::to_string);
+    let now = bson::DateTime::from_chrono(Utc::now());
+
+    let result = services::mongodb::update_one(
+        QuarantinedDocument::get_collection(),
+        doc! { "_id": QuarantinedDocument::key(collection, &document_id) },
+        doc! {
+            "$setOnInsert": {
+                "collection": collection,
+                "document_id": document_id.clone(),
+                "tenant_id": tenant_id,
+                "first_seen_at": now,
+            },
+            "$inc": { "reads": 1_i64 },
+            "$set": { "error": &error, "document": document, "last_seen_at": now },
+        },
+        UpdateOptions::builder().upsert(true).build(),
+    )
+    .await?;
+    if result.upserted_id.is_some() {
+        report(collection, &document_id, &error);
+    }
+    Ok(())
+}
+
+fn report(collection: &str, document_id: &Bson, error: &str) {
+    log::error!(
+        "Quarantined {} document {}: {}",
+        collection,
+        document_id,
+        error
+    );
+
+    let mut extra = BTreeMap::new();
+    extra.insert(
+        "document_id".to_string(),
+        sentry::protocol::Value::from(document_id.to_string()),
+    );
+    extra.insert(
+        "error".to_string(),
+        sentry::protocol::Value::from(error.to_string()),
+    );
+
+    let mut tags = BTreeMap::new();
+    tags.insert("collection".to_string(), collection.to_string());
+
+    sentry::capture_event(sentry::protocol::Event {
+        message: Some(format!("Undecodable {} document quarantined", collection)),
+        level: sentry::Level::Warning,
+        extra,
+        tags,
+        ..Default::default()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Sample {
+        name: String,
+        seats: u32,
+    }
+
+    #[test]
+    fn test_undecodable_documents_are_set_aside() {
+        let documents = vec![
+            RawDocumentBuf::from_document(&doc! { "name": "Model 3", "seats": 5 }).unwrap(),
+            RawDocumentBuf::from_document(&doc! { "name": "Legacy", "seats": "five" }).unwrap(),
+            RawDocumentBuf::from_document(&doc! { "name": "Zoe", "seats": 4 }).unwrap(),
+        ];
+
+        let (decoded, rejected) = split::<Sample>(documents);
+
+        assert_eq!(
+            decoded
+                .iter()
+                .map(|sample| (sample.name.as_str(), sample.seats))
+                .collect::<Vec<_>>(),
+            vec![("Model 3", 5), ("Zoe", 4)]
+        );
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].0.get_str("name").unwrap(), "Legacy");
+    }
+
+    #[test]
+    fn test_quarantine_keys_use_the_document_id() {
+        let id = bson::oid::ObjectId::parse_str("66b1f0c2a1b2c3d4e5f60718").unwrap();
+
+        assert_eq!(
+            QuarantinedDocument::key("bookings", &Bson::ObjectId(id)),
+            "bookings:66b1f0c2a1b2c3d4e5f60718"
+        );
+        assert_eq!(
+            QuarantinedDocument::key("lockouts", &Bson::String("10.0.0.1".to_string())),
+            "lockouts:10.0.0.1"
+        );
+    }
+}
@@ -1,5 +1,8 @@
 use bson::{doc, Bson, Document};
 use std::fmt::Display;
+use strum::IntoEnumIterator;
+
+use crate::models::{Currency, Money};
 
 /// Generic MongoDB query builder for filters
 pub struct QueryBuilder;
@@ -84,6 +87,51 @@ impl QueryBuilder {
         filter.insert(field, range_doc);
     }
 
+    /// Range filter on a money field (`{amount, currency}`) whose bounds are given in one
+    /// currency: one range per stored currency, bounds converted to it by `convert`.
+    /// Amounts in a currency `convert` cannot convert to never match.
+    pub fn add_money_range_filter(
+        &self,
+        filter: &mut Document,
+        field: &str,
+        min_val: Option<Money>,
+        max_val: Option<Money>,
+        convert: impl Fn(Money, Currency) -> Option<Money>,
+    ) {
+        if min_val.is_none() && max_val.is_none() {
+            return;
+        }
+
+        let ranges: Vec<Document> = Currency::iter()
+            .filter_map(|currency| {
+                let min = min_val.map(|min| convert(min, currency));
+                let max = max_val.map(|max| convert(max, currency));
+                if matches!(min, Some(None)) || matches!(max, Some(None)) {
+                    return None;
+                }
+
+                let currency_field = format!("{}.currency", field);
+                let mut range = doc! { currency_field: currency.to_string() };
+                self.add_range_filter(
+                    &mut range,
+                    &format!("{}.amount", field),
+                    min.flatten().map(|min| min.to_decimal128()),
+                    max.flatten().map(|max| max.to_decimal128()),
+                );
+                Some(range)
+            })
+            .collect();
+
+        // Next to the `$and` or `$or` the filter may already have
+        if !filter.contains_key("$and") && !filter.contains_key("$or") {
+            filter.insert("$or", ranges);
+            return;
+        }
+        let mut and = filter.get_array("$and").cloned().unwrap_or_default();
+        and.push(doc! { "$or": ranges }.into());
+        filter.insert("$and", and);
+    }
+
     /// Add a boolean filter
     pub fn add_boolean_filter(&self, filter: &mut Document, field: &str, value: Option<bool>) {
         if let Some(val) = value {
@@ -101,6 +149,7 @@ impl Default for QueryBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Decimal;
 
     #[test]
     fn test_equal_filter_single_value() {
@@ -161,6 +210,36 @@ mod tests {
         assert_eq!(price_filter.get_f64("$lte").unwrap(), 500.0);
     }
 
+    #[test]
+    fn test_money_range_filter_per_currency() {
+        let builder = QueryBuilder::new();
+        let mut filter = doc! { "type": "CAR" };
+        let eur = |amount: i64| Money::new(Decimal::from(amount), Currency::EUR);
+
+        // Only EUR and USD have a rate, USD amounts are twice EUR ones
+        builder.add_money_range_filter(&mut filter, "price", Some(eur(50)), None, |money, to| {
+            match to {
+                Currency::EUR => Some(money),
+                Currency::USD => Some(Money::new(money.amount * Decimal::from(2), to)),
+                _ => None,
+            }
+        });
+
+        assert_eq!(
+            filter,
+            doc! {
+                "type": "CAR",
+                "$or": [
+                    { "price.currency": "EUR", "price.amount": { "$gte": eur(50).to_decimal128() } },
+                    {
+                        "price.currency": "USD",
+                        "price.amount": { "$gte": Money::new(Decimal::from(100), Currency::USD).to_decimal128() },
+                    },
+                ],
+            }
+        );
+    }
+
     #[test]
     fn test_string_filter() {
         let builder = QueryBuilder::new();
@@ -0,0 +1,156 @@
+use bson::{doc, Document};
+use std::future::Future;
+
+use super::MongoStruct;
+use crate::models::{
+    AccountingExport, ApiKey, ArchivedBooking, Booking, BookingComment, BookingExtension,
+    Broadcast, BroadcastDelivery, Changeset, DeprecationUsage, Dispute, LedgerEntry,
+    MaintenanceRecord, Notification, Payment, QuarantinedDocument, ServiceAccount, Station,
+    Vehicle, WebhookDelivery, WebhookEndpoint,
+};
+
+tokio::task_local! {
+    // Tenant of the identity handling the current request
+    static CURRENT_TENANT: Option<String>;
+}
+
+/// Run a request on behalf of a tenant: meanwhile every query on a tenant scoped
+/// collection only sees the documents of that tenant, and new documents belong to it
+pub async fn scope<F: Future>(tenant_id: Option<String>, future: F) -> F::Output {
+    CURRENT_TENANT.scope(tenant_id, future).await
+}
+
+/// Tenant of the current request. None for deployment wide identities and outside
+/// of a request (background jobs), which see every tenant.
+pub fn current() -> Option<String> {
+    CURRENT_TENANT
+        .try_with(|tenant_id| tenant_id.clone())
+        .ok()
+        .flatten()
+}
+
+/// Collections whose documents belong to a tenant
+pub fn is_tenant_scoped(collection_name: &str) -> bool {
+    [
+        Vehicle::get_collection(),
+        Booking::get_collection(),
+        BookingExtension::get_collection(),
+        ArchivedBooking::get_collection(),
+        ApiKey::get_collection(),
+        ServiceAccount::get_collection(),
+        Dispute::get_collection(),
+        BookingComment::get_collection(),
+        Payment::get_collection(),
+        LedgerEntry::get_collection(),
+        AccountingExport::get_collection(),
+        Notification::get_collection(),
+        WebhookEndpoint::get_collection(),
+        WebhookDelivery::get_collection(),
+        Changeset::get_collection(),
+        DeprecationUsage::get_collection(),
+        QuarantinedDocument::get_collection(),
+        Broadcast::get_collection(),
+        BroadcastDelivery::get_collection(),
+        MaintenanceRecord::get_collection(),
+        Station::get_collection(),
+    ]
+    .contains(&collection_name)
+}
+
+/// Limit a filter to the current tenant. Applied on top of the caller's filter,
+/// a filter on another tenant_id is overridden.
+pub fn scope_filter(collection_name: &str, mut filter: Document) -> Document {
+    if let Some(tenant_id) = current().filter(|_| is_tenant_scoped(collection_name)) {
+        filter.insert("tenant_id", tenant_id);
+    }
+    filter
+}
+
+/// Atlas Search clause limiting a `$search` stage to the current tenant. A `$match`
+/// cannot come before `$search`, search stages have to scope themselves with it.
+pub fn search_filter(collection_name: &str) -> Option<Document> {
+    current()
+        .filter(|_| is_tenant_scoped(collection_name))
+        .map(|tenant_id| doc! { "equals": { "path": "tenant_id", "value": tenant_id } })
+}
+
+/// Make a new or replaced document belong to the current tenant
+pub fn stamp(collection_name: &str, document: &mut Document) {
+    if let Some(tenant_id) = current().filter(|_| is_tenant_scoped(collection_name)) {
+        document.insert("tenant_id", tenant_id);
+    }
+}
+
+/// Whether a replacement has to keep the tenant of the document it replaces: no
+/// tenant is current (background jobs, deployment wide admins) and the model does
+/// not carry its tenant_id itself
+pub fn is_inherited(collection_name: &str, replacement: &Document) -> bool {
+    current().is_none()
+        && is_tenant_scoped(collection_name)
+        && !replacement.contains_key("tenant_id")
+}
+
+/// Copy the tenant of the stored document onto its replacement, so that replacing
+/// it outside of a tenant does not orphan it
+pub fn inherit(replacement: &mut Document, stored: Option<&Document>) {
+    if let Some(tenant_id) = stored.and_then(|stored| stored.get("tenant_id")) {
+        replacement.insert("tenant_id", tenant_id.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters_are_scoped_to_the_current_tenant() {
+        let filter = CURRENT_TENANT.sync_scope(Some("acme".to_string()), || {
+            scope_filter(
+                "bookings",
+                doc! { "customer_id": "c1", "tenant_id": "other" },
+            )
+        });
+        assert_eq!(filter, doc! { "customer_id": "c1", "tenant_id": "acme" });
+
+        // Deployment wide data and identities are not scoped
+        let filter = CURRENT_TENANT.sync_scope(Some("acme".to_string()), || {
+            scope_filter("suspensions", doc! {})
+        });
+        assert!(filter.is_empty());
+        let filter = CURRENT_TENANT.sync_scope(None, || scope_filter("vehicles", doc! {}));
+        assert!(filter.is_empty());
+        assert!(scope_filter("vehicles", doc! {}).is_empty());
+    }
+
+    #[test]
+    fn test_new_documents_belong_to_the_current_tenant() {
+        let mut document = doc! { "brand": "Peugeot" };
+        CURRENT_TENANT.sync_scope(Some("acme".to_string()), || {
+            stamp("vehicles", &mut document)
+        });
+        assert_eq!(document.get_str("tenant_id").unwrap(), "acme");
+    }
+
+    #[test]
+    fn test_replacements_without_tenant_keep_the_stored_one() {
+        let stored = doc! { "_id": 1, "status": "RUNNING", "tenant_id": "acme" };
+        let mut replacement = doc! { "_id": 1, "status": "SUCCEEDED" };
+        assert!(is_inherited("accounting_exports", &replacement));
+        inherit(&mut replacement, Some(&stored));
+        assert_eq!(replacement.get_str("tenant_id").unwrap(), "acme");
+
+        // Deployment wide documents stay so
+        let mut replacement = doc! { "_id": 1, "status": "SUCCEEDED" };
+        inherit(&mut replacement, Some(&doc! { "_id": 1 }));
+        assert!(!replacement.contains_key("tenant_id"));
+
+        // The current tenant and models carrying their tenant_id decide themselves
+        let replacement = doc! { "_id": 1, "status": "SUCCEEDED" };
+        let inherited = CURRENT_TENANT.sync_scope(Some("acme".to_string()), || {
+            is_inherited("accounting_exports", &replacement)
+        });
+        assert!(!inherited);
+        assert!(!is_inherited("bookings", &doc! { "tenant_id": null }));
+        assert!(!is_inherited("suspensions", &doc! {}));
+    }
+}
@@ -0,0 +1,381 @@
+use bson::{doc, Bson, Document};
+use futures::TryStreamExt;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::str::FromStr;
+use std::sync::LazyLock;
+use strum::{Display, EnumString};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Booking, SchemaName, SchemaViolation, SchemaViolationReport, Vehicle};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+use crate::util::pagination::PageQuery;
+
+/// What MongoDB does with a write breaking the schema of its collection
+#[derive(Clone, Copy, Debug, Default, PartialEq, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum ValidationAction {
+    Off, // Validators are left as they are
+    #[default]
+    Warn, // The write is done and logged by the server
+    Error, // The write is refused
+}
+
+// Read once from SCHEMA_VALIDATION
+static VALIDATION_ACTION: LazyLock<ValidationAction> = LazyLock::new(|| {
+    let value = std::env::var("SCHEMA_VALIDATION").unwrap_or_default();
+    if value.trim().is_empty() {
+        return ValidationAction::default();
+    }
+    ValidationAction::from_str(value.trim()).unwrap_or_else(|_| {
+        log::warn!("Ignored schema validation {:?}", value);
+        ValidationAction::default()
+    })
+});
+
+/// Keywords of the JSON Schema draft 4 subset `$jsonSchema` understands, the others
+/// (`$schema`, `format`, `default`, `examples`...) are dropped
+const SUPPORTED_KEYWORDS: [&str; 27] = [
+    "bsonType",
+    "enum",
+    "required",
+    "properties",
+    "patternProperties",
+    "additionalProperties",
+    "minProperties",
+    "maxProperties",
+    "items",
+    "additionalItems",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+    "title",
+    "description",
+];
+
+/// `$jsonSchema` of a schemars schema: references inlined, `type` given as BSON types
+/// and date-times as dates, as the driver stores them
+pub fn to_bson_schema(schema: &Value) -> Document {
+    let defs = schema.get("$defs").cloned().unwrap_or(Value::Null);
+    match bson::to_bson(&convert(schema, &defs)) {
+        Ok(Bson::Document(document)) => document,
+        _ => Document::new(),
+    }
+}
+
+fn convert(schema: &Value, defs: &Value) -> Value {
+    // `true` and `false` schemas are kept as is
+    let Some(object) = schema.as_object() else {
+        return schema.clone();
+    };
+    // A reference is replaced by its definition, keywords next to it still apply
+    let mut converted = match object
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix("#/$defs/"))
+    {
+        Some(name) => match convert(&defs[name], defs) {
+            Value::Object(definition) => definition,
+            _ => Map::new(),
+        },
+        None => Map::new(),
+    };
+    let format = object.get("format").and_then(Value::as_str);
+
+    for (keyword, value) in object {
+        let value = match keyword.as_str() {
+            "type" => {
+                converted.insert("bsonType".to_string(), bson_types(value, format));
+                continue;
+            }
+            "const" => {
+                converted.insert("enum".to_string(), json!([value]));
+                continue;
+            }
+            // Draft 4 bounds are a flag next to minimum and maximum
+            "exclusiveMinimum" | "exclusiveMaximum" if value.is_number() => {
+                let bound = match keyword.as_str() {
+                    "exclusiveMinimum" => "minimum",
+                    _ => "maximum",
+                };
+                converted.insert(bound.to_string(), value.clone());
+                converted.insert(keyword.clone(), json!(true));
+                continue;
+            }
+            "properties" | "patternProperties" => Value::Object(
+                value
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, property)| (name.clone(), convert(property, defs)))
+                    .collect(),
+            ),
+            "items"
+            | "additionalItems"
+            | "additionalProperties"
+            | "not"
+            | "allOf"
+            | "anyOf"
+            | "oneOf" => match value {
+                Value::Array(schemas) => schemas.iter().map(|item| convert(item, defs)).collect(),
+                _ => convert(value, defs),
+            },
+            keyword if SUPPORTED_KEYWORDS.contains(&keyword) => value.clone(),
+            _ => continue,
+        };
+        converted.insert(keyword.clone(), value);
+    }
+    Value::Object(converted)
+}
+
+/// `bsonType` of a JSON Schema `type`, one name or a list of them. Dates and money
+/// amounts are strings in the schema, stored as BSON dates and decimals.
+fn bson_types(types: &Value, format: Option<&str>) -> Value {
+    let names: Vec<&str> = match types {
+        Value::String(name) => vec![name.as_str()],
+        Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let mut bson_types = Vec::new();
+    for name in names {
+        match name {
+            "integer" => bson_types.extend(["int", "long"]),
+            "boolean" => bson_types.push("bool"),
+            "string" if format == Some("date-time") => bson_types.push("date"),
+            "string" if format == Some("decimal") => bson_types.push("decimal"),
+            // string, number, object, array and null share their name
+            other => bson_types.push(other),
+        }
+    }
+    match bson_types.as_slice() {
+        [single] => json!(single),
+        _ => json!(bson_types),
+    }
+}
+
+/// Validator of a stored model, None for the request models. Identifiers are ObjectIds
+/// stored as `_id` and driver details may be encrypted, unlike what the API shows.
+pub fn validator(name: SchemaName) -> Option<Document> {
+    let overrides = match name {
+        SchemaName::Vehicle => doc! {},
+        SchemaName::Booking => doc! {
+            "vehicle_id": { "bsonType": "objectId" },
+            // Each field is a string, or an object once encrypted
+            "driver": { "bsonType": "object" },
+        },
+        _ => return None,
+    };
+
+    let mut schema = to_bson_schema(&name.schema());
+    let mut properties = schema
+        .get_document("properties")
+        .cloned()
+        .unwrap_or_default();
+    properties.remove("id");
+    properties.insert("_id", doc! { "bsonType": "objectId" });
+    for (field, property) in overrides {
+        properties.insert(field, property);
+    }
+    schema.insert("properties", properties);
+    Some(schema)
+}
+
+/// Collection validated against a model, None for the other collections
+pub fn validated_model(collection: &str) -> Option<SchemaName> {
+    match collection {
+        collection if collection == Vehicle::get_collection() => Some(SchemaName::Vehicle),
+        collection if collection == Booking::get_collection() => Some(SchemaName::Booking),
+        _ => None,
+    }
+}
+
+/// Set the validators of the vehicles and bookings collections, creating them when
+/// missing. Moderate level: documents already breaking the schema can still be updated.
+pub async fn apply_validators() -> AppResult<()> {
+    let action = *VALIDATION_ACTION;
+    if action == ValidationAction::Off {
+        return Ok(());
+    }
+
+    let database = services::mongodb::get_database(services::mongodb::DATABASE_NAME).await?;
+    let existing = database.list_collection_names().await?;
+    for collection in [Vehicle::get_collection(), Booking::get_collection()] {
+        let Some(schema) = validated_model(collection).and_then(validator) else {
+            continue;
+        };
+        let command = if existing.iter().any(|name| name == collection) {
+            "collMod"
+        } else {
+            "create"
+        };
+        database
+            .run_command(doc! {
+                command: collection,
+                "validator": { "$jsonSchema": schema },
+                "validationLevel": "moderate",
+                "validationAction": action.to_string(),
+            })
+            .await?;
+    }
+    log::info!("Schema validators applied ({})", action);
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct ViolationPage {
+    total: Vec<ViolationCount>,
+    violations: Vec<SchemaViolation>,
+}
+
+#[derive(Deserialize)]
+struct ViolationCount {
+    count: i64,
+}
+
+/// Documents of `collection` breaking its schema, with the required fields they lack
+pub async fn violations(collection: &str, page: PageQuery) -> AppResult<SchemaViolationReport> {
+    let (model, schema) = validated_model(collection)
+        .and_then(|model| Some((model, validator(model)?)))
+        .ok_or_else(|| {
+            AppError::not_found(format!(
+                "No schema validation for collection {}",
+                collection
+            ))
+        })?;
+    let required = schema.get_array("required").cloned().unwrap_or_default();
+
+    let pipeline = vec![
+        doc! { "$match": { "$nor": [{ "$jsonSchema": schema }] } },
+        doc! { "$facet": {
+            "total": [{ "$count": "count" }],
+            "violations": [
+                { "$sort": { "_id": 1 } },
+                { "$skip": page.skip() as i64 },
+                { "$limit": page.limit() },
+                { "$project": {
+                    "missing_fields": { "$setDifference": [
+                        { "$literal": required },
+                        { "$map": { "input": { "$objectToArray": "$$ROOT" }, "in": "$$this.k" } },
+                    ] },
+                } },
+            ],
+        } },
+    ];
+    let rows: Vec<ViolationPage> = match model {
+        SchemaName::Booking => {
+            services::mongodb::aggregate::<Booking, _>(pipeline)
+                .await?
+                .try_collect()
+                .await?
+        }
+        _ => {
+            services::mongodb::aggregate::<Vehicle, _>(pipeline)
+                .await?
+                .try_collect()
+                .await?
+        }
+    };
+
+    let (total, violations) = match rows.into_iter().next() {
+        Some(row) => (
+            row.total.first().map_or(0, |count| count.count),
+            row.violations,
+        ),
+        None => (0, Vec::new()),
+    };
+    Ok(SchemaViolationReport {
+        collection: collection.to_string(),
+        total,
+        violations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_references_are_inlined_with_bson_types() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Sample",
+            "type": "object",
+            "properties": {
+                "count": { "type": "integer", "format": "uint32", "minimum": 0 },
+                "at": { "type": ["string", "null"], "format": "date-time" },
+                "price": { "type": "string", "format": "decimal" },
+                "active": { "type": "boolean", "default": true },
+                "format": { "$ref": "#/$defs/Format" },
+            },
+            "required": ["count"],
+            "$defs": {
+                "Format": { "type": "string", "enum": ["A", "B"] },
+            },
+        });
+
+        assert_eq!(
+            to_bson_schema(&schema),
+            doc! {
+                "title": "Sample",
+                "bsonType": "object",
+                "properties": {
+                    "count": { "bsonType": ["int", "long"], "minimum": 0_i64 },
+                    "at": { "bsonType": ["date", "null"] },
+                    "price": { "bsonType": "decimal" },
+                    "active": { "bsonType": "bool" },
+                    "format": { "bsonType": "string", "enum": ["A", "B"] },
+                },
+                "required": ["count"],
+            }
+        );
+    }
+
+    #[test]
+    fn test_constants_and_exclusive_bounds_use_draft_4() {
+        let schema = json!({
+            "oneOf": [{ "const": "CAR" }, { "type": "number", "exclusiveMinimum": 0 }],
+        });
+
+        assert_eq!(
+            to_bson_schema(&schema),
+            doc! {
+                "oneOf": [
+                    { "enum": ["CAR"] },
+                    { "bsonType": "number", "minimum": 0_i64, "exclusiveMinimum": true },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_stored_models_are_identified_by_object_ids() {
+        let schema = validator(SchemaName::Booking).unwrap();
+        let properties = schema.get_document("properties").unwrap();
+
+        assert!(properties.get("id").is_none());
+        assert_eq!(
+            properties.get_document("_id").unwrap(),
+            &doc! { "bsonType": "objectId" }
+        );
+        assert_eq!(
+            properties.get_document("vehicle_id").unwrap(),
+            &doc! { "bsonType": "objectId" }
+        );
+        assert!(validator(SchemaName::CreateVehicleRequest).is_none());
+        assert_eq!(validated_model("vehicles"), Some(SchemaName::Vehicle));
+        assert_eq!(validated_model("tenants"), None);
+    }
+}
@@ -0,0 +1,16 @@
+use crate::models::Notification;
+use crate::services;
+
+/// Store notifications. Best effort: a failure is logged and never fails the change
+/// being notified.
+pub async fn send(notifications: Vec<Notification>) {
+    for notification in notifications {
+        if let Err(error) = services::mongodb::insert_one(&notification, None).await {
+            log::error!(
+                "Failed to store notification '{}': {}",
+                notification.subject,
+                error
+            );
+        }
+    }
+}
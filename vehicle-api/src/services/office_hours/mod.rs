@@ -0,0 +1,30 @@
+use bson::{doc, Document};
+
+use crate::error::AppResult;
+use crate::models::{OfficeCalendar, OfficeHours, Vehicle};
+use crate::services;
+
+/// Filter on the hours a tenant uses: its own and the ones set for every tenant
+pub fn tenant_filter(tenant_id: Option<&str>) -> Document {
+    match tenant_id {
+        Some(tenant_id) => doc! { "tenant_id": { "$in": [tenant_id, null] } },
+        None => Document::new(),
+    }
+}
+
+/// Office hours of every location, to run timers of bookings across tenants
+pub async fn calendar() -> AppResult<OfficeCalendar> {
+    let hours: Vec<OfficeHours> = services::mongodb::collect_many(Document::new(), None).await?;
+    Ok(OfficeCalendar::new(hours))
+}
+
+/// Office hours of the branch of a vehicle, None when it is always staffed
+pub async fn for_vehicle(vehicle: &Vehicle) -> AppResult<Option<OfficeHours>> {
+    let mut filter = tenant_filter(vehicle.tenant_id.as_deref());
+    filter.insert("location", &vehicle.timezone);
+    let hours: Vec<OfficeHours> = services::mongodb::collect_many(filter, None).await?;
+
+    Ok(OfficeCalendar::new(hours)
+        .get(vehicle.tenant_id.as_deref(), &vehicle.timezone)
+        .cloned())
+}
@@ -0,0 +1,51 @@
+pub mod stripe;
+
+use std::sync::LazyLock;
+
+use crate::error::AppResult;
+use crate::models::{NewLedgerEntry, Payment, PaymentRequest};
+use crate::services;
+
+/// Carries out refunds and charges. Only the manual provider exists today, a payment
+/// processor only has to implement this trait.
+pub(crate) trait PaymentProvider {
+    /// Name stored on the payments it handles
+    fn name(&self) -> &'static str;
+
+    /// Start a refund or a charge, returns the stored payment
+    async fn execute(&self, request: PaymentRequest) -> AppResult<Payment>;
+}
+
+/// Records payments as PENDING for the finance team to carry out by hand
+pub struct ManualPaymentProvider;
+
+// Global payment provider, selected once at startup
+pub(crate) static PAYMENT_PROVIDER: LazyLock<ManualPaymentProvider> =
+    LazyLock::new(|| ManualPaymentProvider);
+
+/// Start a refund or a charge with the configured provider and record it on the
+/// booking's ledger
+pub async fn execute(request: PaymentRequest) -> AppResult<Payment> {
+    let payment = PAYMENT_PROVIDER.execute(request).await?;
+    services::ledger::append(NewLedgerEntry::of_payment(&payment)).await?;
+    Ok(payment)
+}
+
+impl PaymentProvider for ManualPaymentProvider {
+    fn name(&self) -> &'static str {
+        "manual"
+    }
+
+    async fn execute(&self, request: PaymentRequest) -> AppResult<Payment> {
+        let mut payment = Payment::new(request, self.name());
+        payment.id = Some(services::mongodb::insert_one(&payment, None).await?);
+
+        log::info!(
+            "{} of {} on booking {} waiting for finance",
+            payment.kind,
+            payment.amount,
+            payment.booking_id
+        );
+        Ok(payment)
+    }
+}
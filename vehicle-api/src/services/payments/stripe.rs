@@ -0,0 +1,186 @@
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+use std::sync::LazyLock;
+
+use crate::authentication::request_signing::decode_hex;
+use crate::error::{AppError, AppResult};
+use crate::models::{to_cents, Money, StripeEvent, StripePaymentIntent};
+
+/// Header carrying the signature of webhook events
+pub const SIGNATURE_HEADER: &str = "Stripe-Signature";
+
+/// Seconds a webhook event is accepted after Stripe signed it, as Stripe's own libraries
+const SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+/// Stripe account the API creates PaymentIntents on
+pub struct StripeClient {
+    api_url: String,
+    secret_key: String,
+    webhook_secret: String,
+    pub currency: String,
+    client: reqwest::Client,
+}
+
+// Read once, None when Stripe is not configured
+pub(crate) static STRIPE: LazyLock<Option<StripeClient>> = LazyLock::new(StripeClient::from_env);
+
+/// Whether bookings are paid through Stripe, and must be paid before they are confirmed
+pub fn is_enabled() -> bool {
+    STRIPE.is_some()
+}
+
+pub fn get() -> AppResult<&'static StripeClient> {
+    STRIPE
+        .as_ref()
+        .ok_or_else(|| AppError::not_found("Payments are not enabled on this deployment"))
+}
+
+impl StripeClient {
+    /// STRIPE_SECRET_KEY and STRIPE_WEBHOOK_SECRET are required. STRIPE_CURRENCY defaults
+    /// to eur and STRIPE_API_URL to https://api.stripe.com.
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            api_url: std::env::var("STRIPE_API_URL")
+                .unwrap_or_else(|_| "https://api.stripe.com".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            secret_key: std::env::var("STRIPE_SECRET_KEY").ok()?,
+            webhook_secret: std::env::var("STRIPE_WEBHOOK_SECRET").ok()?,
+            currency: std::env::var("STRIPE_CURRENCY")
+                .map(|currency| currency.to_lowercase())
+                .unwrap_or_else(|_| "eur".to_string()),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Create a PaymentIntent. Stripe answers a retry with the same `idempotency_key`
+    /// with the intent it created first.
+    pub async fn create_payment_intent(
+        &self,
+        amount: Money,
+        metadata: &[(&str, String)],
+        idempotency_key: &str,
+    ) -> AppResult<StripePaymentIntent> {
+        let mut form = vec![
+            (
+                "amount".to_string(),
+                to_cents(amount)
+                    .ok_or_else(|| AppError::bad_request(format!("Invalid amount {}", amount)))?
+                    .to_string(),
+            ),
+            ("currency".to_string(), self.currency.clone()),
+            (
+                "automatic_payment_methods[enabled]".to_string(),
+                "true".to_string(),
+            ),
+        ];
+        form.extend(
+            metadata
+                .iter()
+                .map(|(key, value)| (format!("metadata[{}]", key), value.clone())),
+        );
+
+        let response = self
+            .client
+            .post(format!("{}/v1/payment_intents", self.api_url))
+            .bearer_auth(&self.secret_key)
+            .header("Idempotency-Key", idempotency_key)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AppError::internal_server_error(format!("Stripe unreachable: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            log::error!("Stripe refused a PaymentIntent ({}): {}", status, body);
+            return Err(AppError::internal_server_error(format!(
+                "Stripe refused the payment with status {}",
+                status
+            )));
+        }
+        response.json().await.map_err(|e| {
+            AppError::internal_server_error(format!("Unexpected Stripe answer: {}", e))
+        })
+    }
+
+    /// Check the `Stripe-Signature` header of a webhook event and parse it
+    pub fn verify_event(
+        &self,
+        payload: &[u8],
+        header: &str,
+        now: i64,
+    ) -> Result<StripeEvent, String> {
+        verify_signature(&self.webhook_secret, payload, header, now)?;
+        serde_json::from_slice(payload).map_err(|e| format!("Invalid Stripe event: {}", e))
+    }
+}
+
+/// Stripe signs `{timestamp}.{payload}` with HMAC-SHA256, the header reads
+/// `t=1492774577,v1=5257a869...`. Several v1 signatures are sent while a secret rolls.
+pub fn verify_signature(
+    secret: &str,
+    payload: &[u8],
+    header: &str,
+    now: i64,
+) -> Result<(), String> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.extend(decode_hex(value)),
+            _ => {}
+        }
+    }
+    let timestamp = timestamp.ok_or_else(|| "Stripe-Signature has no timestamp".to_string())?;
+    if (now - timestamp).abs() > SIGNATURE_TOLERANCE_SECS {
+        return Err("Stripe-Signature timestamp is outside the tolerance".to_string());
+    }
+
+    let mut signed = format!("{}.", timestamp).into_bytes();
+    signed.extend_from_slice(payload);
+    let valid = signatures.iter().any(|signature| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any size");
+        mac.update(&signed);
+        mac.verify_slice(signature).is_ok()
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err("Stripe-Signature does not match the payload".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &str, timestamp: i64, payload: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("{}.", timestamp).as_bytes());
+        mac.update(payload);
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let payload = br#"{"id":"evt_1","type":"payment_intent.succeeded"}"#;
+        let signature = sign("whsec_test", 1000, payload);
+        let header = format!("t=1000,v1=deadbeef,v1={}", signature);
+
+        assert!(verify_signature("whsec_test", payload, &header, 1100).is_ok());
+        assert!(verify_signature("whsec_other", payload, &header, 1100).is_err());
+        assert!(verify_signature("whsec_test", b"{}", &header, 1100).is_err());
+        // Replayed too late
+        assert!(verify_signature("whsec_test", payload, &header, 1301).is_err());
+        assert!(
+            verify_signature("whsec_test", payload, &format!("v1={}", signature), 1100).is_err()
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use bson::{doc, Document};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::domain;
+use crate::domain::events::BookingReminder;
+use crate::error::AppResult;
+use crate::models::{Booking, Notification};
+use crate::services;
+use crate::services::email::BookingEmail;
+use crate::services::mongodb::MongoStruct;
+
+/// Seconds between two sweeps (BOOKING_REMINDER_INTERVAL_SECS, default 15 minutes,
+/// 0 disables the job)
+fn sweep_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("BOOKING_REMINDER_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(900);
+
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// How long before the rental starts customers are reminded (BOOKING_REMINDER_HOURS,
+/// default 24)
+pub fn reminder_lead() -> Duration {
+    let hours = std::env::var("BOOKING_REMINDER_HOURS")
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|hours| *hours > 0)
+        .unwrap_or(24);
+
+    Duration::hours(hours)
+}
+
+/// Confirmed bookings not reminded yet whose rental starts within `lead` after `now`
+pub fn due(now: DateTime<Utc>, lead: Duration) -> Document {
+    doc! {
+        "status": "CONFIRMED",
+        "reminder_sent_at": null,
+        "starts_at": {
+            "$gt": bson::DateTime::from_chrono(now),
+            "$lte": bson::DateTime::from_chrono(now + lead),
+        },
+    }
+}
+
+/// Remind customers of their coming rentals periodically in the background
+pub fn spawn_scheduler() {
+    let Some(interval) = sweep_interval() else {
+        log::info!("Booking reminder job disabled");
+        return;
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match send_due(Utc::now()).await {
+                Ok(0) => {}
+                Ok(sent) => log::info!("{} booking reminders sent", sent),
+                Err(error) => log::error!("Booking reminders failed: {}", error),
+            }
+        }
+    });
+}
+
+/// Remind the customer of every booking starting within `reminder_lead`, by email, in-app
+/// notification and the `booking.reminder` event. Each booking is marked with
+/// `reminder_sent_at` first, so it is reminded once even with several instances running.
+/// Returns the number of reminders sent.
+pub async fn send_due(now: DateTime<Utc>) -> AppResult<usize> {
+    let bookings: Vec<Booking> =
+        services::mongodb::collect_many(due(now, reminder_lead()), None).await?;
+
+    let mut sent = 0;
+    for mut booking in bookings {
+        let Some(booking_id) = booking.id else {
+            continue;
+        };
+
+        // Claim the reminder first, another instance may be sending it
+        let result = services::mongodb::update_one(
+            Booking::get_collection(),
+            doc! { "_id": booking_id, "reminder_sent_at": null },
+            doc! { "$set": { "reminder_sent_at": bson::DateTime::from_chrono(now) } },
+            None,
+        )
+        .await?;
+        if result.modified_count == 0 {
+            continue;
+        }
+        booking.reminder_sent_at = Some(now);
+        sent += 1;
+
+        domain::events::publish(BookingReminder::of(&booking)).await;
+        services::email::notify_customer(BookingEmail::Reminder, &booking).await;
+
+        let notification = Notification::to_user(
+            &booking.customer_id,
+            "Your rental starts soon",
+            format!(
+                "Your booking starts on {} and ends on {}.",
+                booking.from_date, booking.to_date
+            ),
+        )
+        .about_booking(booking_id);
+        services::notification::send(vec![notification]).await;
+    }
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_only_bookings_starting_within_the_lead_are_due() {
+        let now = Utc.with_ymd_and_hms(2025, 8, 1, 9, 0, 0).unwrap();
+        let filter = due(now, Duration::hours(24));
+
+        assert_eq!(filter.get_str("status"), Ok("CONFIRMED"));
+        assert_eq!(filter.get("reminder_sent_at"), Some(&bson::Bson::Null));
+        let starts_at = filter.get_document("starts_at").unwrap();
+        assert_eq!(starts_at.get_datetime("$gt").unwrap().to_chrono(), now);
+        assert_eq!(
+            starts_at.get_datetime("$lte").unwrap().to_chrono(),
+            Utc.with_ymd_and_hms(2025, 8, 2, 9, 0, 0).unwrap()
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use bson::{doc, oid::ObjectId};
+use chrono::{NaiveDate, Utc};
+use mongodb::options::UpdateOptions;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Booking, ReservationDay};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+
+const OVERLAP_MESSAGE: &str = "Vehicle is already booked for overlapping dates.";
+
+/// Hold the days of a booking on its vehicle. Days the booking already holds are kept.
+/// Fails with a conflict when another active booking holds one of the days, the days
+/// claimed by this call are then given back.
+pub async fn claim(
+    booking_id: ObjectId,
+    vehicle_id: ObjectId,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+) -> AppResult<()> {
+    let mut claimed = Vec::new();
+    for day in ReservationDay::days(from_date, to_date) {
+        match claim_day(booking_id, vehicle_id, day).await {
+            Ok(true) => claimed.push(ReservationDay::key(&vehicle_id, day)),
+            Ok(false) => {}
+            Err(error) => {
+                let filter = doc! { "_id": { "$in": claimed }, "booking_id": booking_id };
+                if let Err(release_error) =
+                    services::mongodb::delete_many(ReservationDay::get_collection(), filter, None)
+                        .await
+                {
+                    log::error!(
+                        "Cannot release the days claimed by booking {}: {}",
+                        booking_id,
+                        release_error
+                    );
+                }
+                return Err(error);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Claim one day, true when the booking did not hold it yet
+async fn claim_day(booking_id: ObjectId, vehicle_id: ObjectId, day: NaiveDate) -> AppResult<bool> {
+    let claim = ReservationDay::new(vehicle_id, day, booking_id);
+    let document = bson::to_document(&claim).map_err(|e| {
+        AppError::internal_server_error(format!("Cannot serialize document: {}", e))
+    })?;
+
+    // The _id is the vehicle and day: of two concurrent claims only one inserts
+    let result = services::mongodb::update_one(
+        ReservationDay::get_collection(),
+        doc! { "_id": &claim.id },
+        doc! { "$setOnInsert": document },
+        UpdateOptions::builder().upsert(true).build(),
+    )
+    .await?;
+    if result.upserted_id.is_some() {
+        return Ok(true);
+    }
+
+    let existing: ReservationDay = services::mongodb::get_one(doc! { "_id": &claim.id }, None)
+        .await?
+        .ok_or_else(|| AppError::conflict("Vehicle availability changed meanwhile, retry later"))?;
+    if existing.booking_id == booking_id {
+        return Ok(false);
+    }
+    let holder: Option<Booking> =
+        services::mongodb::get_one(doc! { "_id": existing.booking_id }, None).await?;
+    if existing.is_held(holder.as_ref(), Utc::now()) {
+        return Err(AppError::conflict(OVERLAP_MESSAGE));
+    }
+
+    // The day was left behind, take it over unless another booking just did
+    let result = services::mongodb::update_one(
+        ReservationDay::get_collection(),
+        doc! { "_id": &claim.id, "booking_id": existing.booking_id },
+        doc! { "$set": {
+            "booking_id": booking_id,
+            "claimed_at": bson::DateTime::from_chrono(claim.claimed_at),
+        } },
+        None,
+    )
+    .await?;
+    if result.modified_count == 0 {
+        return Err(AppError::conflict(OVERLAP_MESSAGE));
+    }
+    Ok(true)
+}
+
+/// Give back every day held by a booking, once it is cancelled, rejected or deleted
+pub async fn release(booking_id: ObjectId) -> AppResult<()> {
+    services::mongodb::delete_many(
+        ReservationDay::get_collection(),
+        doc! { "booking_id": booking_id },
+        None,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Give back the days a booking holds outside its new dates
+pub async fn release_outside(
+    booking_id: ObjectId,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+) -> AppResult<()> {
+    services::mongodb::delete_many(
+        ReservationDay::get_collection(),
+        doc! {
+            "booking_id": booking_id,
+            "$or": [
+                { "day": { "$lt": from_date.to_string() } },
+                { "day": { "$gt": to_date.to_string() } },
+            ],
+        },
+        None,
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,100 @@
+pub mod rules;
+
+use actix_web::HttpRequest;
+use bson::doc;
+
+use crate::error::AppResult;
+use crate::models::{Booking, RiskAssessment, RiskContext, RiskSignal};
+use crate::services;
+
+/// A fraud rule, returns a signal when the booking looks suspicious
+pub trait RiskRule: Send + Sync {
+    fn evaluate(&self, context: &RiskContext) -> Option<RiskSignal>;
+}
+
+/// Runs every registered rule and sums their scores
+pub struct RiskEngine {
+    rules: Vec<Box<dyn RiskRule>>,
+    high_score: u32,
+}
+
+impl RiskEngine {
+    pub fn new(high_score: u32) -> Self {
+        Self {
+            rules: Vec::new(),
+            high_score,
+        }
+    }
+
+    pub fn with_rule(mut self, rule: impl RiskRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Built-in rules, configured from the environment
+    pub fn from_env() -> Self {
+        let high_score = std::env::var("RISK_HIGH_SCORE")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(60);
+
+        Self::new(high_score)
+            .with_rule(rules::NewCustomer::from_env())
+            .with_rule(rules::FrequentCancellations::from_env())
+            .with_rule(rules::LocationMismatch)
+            .with_rule(rules::DisposableEmail::from_env())
+    }
+
+    pub fn assess(&self, context: &RiskContext) -> RiskAssessment {
+        let signals = self
+            .rules
+            .iter()
+            .filter_map(|rule| rule.evaluate(context))
+            .collect();
+
+        RiskAssessment::new(&context.booking, signals, self.high_score)
+    }
+}
+
+// Global risk engine using OnceCell for lazy initialization
+pub(crate) static RISK_ENGINE: tokio::sync::OnceCell<RiskEngine> =
+    tokio::sync::OnceCell::const_new();
+
+pub async fn get_risk_engine() -> &'static RiskEngine {
+    RISK_ENGINE
+        .get_or_init(|| async { RiskEngine::from_env() })
+        .await
+}
+
+/// Client country set by the edge proxy (RISK_COUNTRY_HEADER, default "CF-IPCountry")
+pub fn client_country(req: &HttpRequest) -> Option<String> {
+    let header =
+        std::env::var("RISK_COUNTRY_HEADER").unwrap_or_else(|_| "CF-IPCountry".to_string());
+
+    req.headers()
+        .get(header.as_str())
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_uppercase())
+        .filter(|value| value.len() == 2 && value != "XX")
+}
+
+/// Score a booking about to be created, the caller stores the assessment
+pub async fn assess_booking(
+    booking: &Booking,
+    email: Option<String>,
+    client_country: Option<String>,
+    vehicle_country: String,
+) -> AppResult<RiskAssessment> {
+    let previous_bookings: Vec<Booking> =
+        services::mongodb::collect_many(doc! { "customer_id": &booking.customer_id }, None).await?;
+
+    let context = RiskContext {
+        booking: booking.clone(),
+        email,
+        client_country,
+        vehicle_country,
+        previous_bookings,
+    };
+
+    Ok(get_risk_engine().await.assess(&context))
+}
@@ -0,0 +1,233 @@
+use chrono::Duration;
+
+use crate::models::{BookingStatus, RiskContext, RiskSignal, RiskSignalCode};
+
+use super::RiskRule;
+
+/// Disposable email domains flagged even without RISK_DISPOSABLE_EMAIL_DOMAINS
+const DISPOSABLE_EMAIL_DOMAINS: [&str; 6] = [
+    "mailinator.com",
+    "guerrillamail.com",
+    "10minutemail.com",
+    "tempmail.com",
+    "yopmail.com",
+    "trashmail.com",
+];
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// First booking of the customer, or a customer first seen only a few days ago
+pub struct NewCustomer {
+    pub days: i64,
+    pub score: u32,
+}
+
+impl NewCustomer {
+    pub fn from_env() -> Self {
+        Self {
+            days: env_or("RISK_NEW_CUSTOMER_DAYS", 7),
+            score: 20,
+        }
+    }
+}
+
+impl RiskRule for NewCustomer {
+    fn evaluate(&self, context: &RiskContext) -> Option<RiskSignal> {
+        let first_seen = context
+            .previous_bookings
+            .iter()
+            .map(|booking| booking.order_date)
+            .min();
+
+        let detail = match first_seen {
+            None => "First booking of this customer".to_string(),
+            Some(first_seen)
+                if context.booking.order_date - first_seen < Duration::days(self.days) =>
+            {
+                format!("Customer first seen on {}", first_seen.date_naive())
+            }
+            Some(_) => return None,
+        };
+
+        Some(RiskSignal {
+            code: RiskSignalCode::NewCustomer,
+            score: self.score,
+            detail,
+        })
+    }
+}
+
+/// Customer who cancelled many of their earlier bookings
+pub struct FrequentCancellations {
+    pub min_cancellations: usize,
+    pub score: u32,
+}
+
+impl FrequentCancellations {
+    pub fn from_env() -> Self {
+        Self {
+            min_cancellations: env_or("RISK_MIN_CANCELLATIONS", 3),
+            score: 40,
+        }
+    }
+}
+
+impl RiskRule for FrequentCancellations {
+    fn evaluate(&self, context: &RiskContext) -> Option<RiskSignal> {
+        let cancelled = context
+            .previous_bookings
+            .iter()
+            .filter(|booking| matches!(booking.status, BookingStatus::Cancelled(_)))
+            .count();
+
+        (cancelled >= self.min_cancellations).then(|| RiskSignal {
+            code: RiskSignalCode::FrequentCancellations,
+            score: self.score,
+            detail: format!(
+                "{} of {} earlier bookings cancelled",
+                cancelled,
+                context.previous_bookings.len()
+            ),
+        })
+    }
+}
+
+/// Booking made from another country than the vehicle's
+pub struct LocationMismatch;
+
+impl RiskRule for LocationMismatch {
+    fn evaluate(&self, context: &RiskContext) -> Option<RiskSignal> {
+        let client_country = context.client_country.as_deref()?;
+
+        (!client_country.eq_ignore_ascii_case(&context.vehicle_country)).then(|| RiskSignal {
+            code: RiskSignalCode::LocationMismatch,
+            score: 30,
+            detail: format!(
+                "Booked from {} for a vehicle in {}",
+                client_country.to_uppercase(),
+                context.vehicle_country
+            ),
+        })
+    }
+}
+
+/// Email address from a throwaway mailbox provider
+pub struct DisposableEmail {
+    pub domains: Vec<String>,
+    pub score: u32,
+}
+
+impl DisposableEmail {
+    /// Built-in domains plus the comma separated RISK_DISPOSABLE_EMAIL_DOMAINS
+    pub fn from_env() -> Self {
+        let mut domains: Vec<String> = DISPOSABLE_EMAIL_DOMAINS
+            .iter()
+            .map(|domain| domain.to_string())
+            .collect();
+        domains.extend(
+            std::env::var("RISK_DISPOSABLE_EMAIL_DOMAINS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|domain| domain.trim().to_lowercase())
+                .filter(|domain| !domain.is_empty()),
+        );
+
+        Self { domains, score: 40 }
+    }
+}
+
+impl RiskRule for DisposableEmail {
+    fn evaluate(&self, context: &RiskContext) -> Option<RiskSignal> {
+        let email = context.email.as_deref()?;
+        let domain = email.rsplit_once('@')?.1.to_lowercase();
+
+        self.domains.contains(&domain).then(|| RiskSignal {
+            code: RiskSignalCode::DisposableEmail,
+            score: self.score,
+            detail: format!("Disposable email domain {}", domain),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{fixtures, Booking, RiskLevel};
+    use crate::services::risk::RiskEngine;
+    use chrono::Utc;
+
+    fn booking(status: BookingStatus, days_ago: i64) -> Booking {
+        let mut booking = fixtures::booking();
+        booking.status = status;
+        booking.order_date = Utc::now() - Duration::days(days_ago);
+        booking
+    }
+
+    fn engine() -> RiskEngine {
+        RiskEngine::new(60)
+            .with_rule(NewCustomer { days: 7, score: 20 })
+            .with_rule(FrequentCancellations {
+                min_cancellations: 2,
+                score: 40,
+            })
+            .with_rule(LocationMismatch)
+            .with_rule(DisposableEmail {
+                domains: vec!["yopmail.com".to_string()],
+                score: 40,
+            })
+    }
+
+    #[test]
+    fn test_trusted_customer_is_low_risk() {
+        let context = RiskContext {
+            booking: booking(BookingStatus::Pending, 0),
+            email: Some("jane@example.com".to_string()),
+            client_country: Some("fr".to_string()),
+            vehicle_country: "FR".to_string(),
+            previous_bookings: vec![booking(BookingStatus::Confirmed, 90)],
+        };
+
+        let assessment = engine().assess(&context);
+
+        assert!(assessment.signals.is_empty());
+        assert_eq!(assessment.level, RiskLevel::Low);
+        assert!(!assessment.manual_confirmation);
+    }
+
+    #[test]
+    fn test_signals_add_up_to_high_risk() {
+        let cancelled = || BookingStatus::Cancelled("Changed plans".to_string());
+        let context = RiskContext {
+            booking: booking(BookingStatus::Pending, 0),
+            email: Some("someone@YOPMAIL.com".to_string()),
+            client_country: Some("US".to_string()),
+            vehicle_country: "FR".to_string(),
+            previous_bookings: vec![booking(cancelled(), 2), booking(cancelled(), 1)],
+        };
+
+        let assessment = engine().assess(&context);
+
+        let codes: Vec<_> = assessment
+            .signals
+            .iter()
+            .map(|signal| signal.code)
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                RiskSignalCode::NewCustomer,
+                RiskSignalCode::FrequentCancellations,
+                RiskSignalCode::LocationMismatch,
+                RiskSignalCode::DisposableEmail,
+            ]
+        );
+        assert_eq!(assessment.score, 130);
+        assert_eq!(assessment.level, RiskLevel::High);
+        assert!(assessment.manual_confirmation);
+    }
+}
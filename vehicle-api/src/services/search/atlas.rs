@@ -0,0 +1,281 @@
+use bson::{doc, Bson, Document};
+use futures::TryStreamExt;
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+
+use super::{search_fields, suggestion_stages, SearchProvider, SUGGESTION_FIELDS};
+use crate::error::AppResult;
+use crate::models::{
+    Currency, FacetBucket, Money, SearchFacets, Vehicle, VehicleSearchQuery, VehicleSearchResults,
+    VehicleSuggestion,
+};
+use crate::services;
+use crate::services::mongodb::{tenant, MongoStruct};
+use crate::util::pagination::PageQuery;
+
+/// Typos tolerated per search word
+const FUZZY_MAX_EDITS: i32 = 1;
+
+/// MongoDB Atlas Search: relevance ranking, typo tolerance and facets computed by the
+/// search index. Needs the index described in the Readme.
+pub struct AtlasSearchProvider {
+    index: String,
+}
+
+/// `$searchMeta` output of a facet query
+#[derive(Debug, Deserialize)]
+struct SearchMeta {
+    count: SearchCount,
+    #[serde(default)]
+    facet: MetaFacets,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchCount {
+    #[serde(rename = "lowerBound")]
+    lower_bound: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MetaFacets {
+    #[serde(default)]
+    brand: MetaBuckets,
+    #[serde(default, rename = "type")]
+    vehicle_type: MetaBuckets,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MetaBuckets {
+    buckets: Vec<FacetBucket>,
+}
+
+/// Operator matching the soft deleted vehicles, to leave them out
+fn not_deleted() -> Document {
+    doc! { "exists": { "path": "deleted_at" } }
+}
+
+impl AtlasSearchProvider {
+    pub fn new(index: String) -> Self {
+        Self { index }
+    }
+
+    /// Every word must match one of the searched fields, typos allowed past the first
+    /// letter. Filters and the tenant do not affect relevance.
+    fn compound(&self, query: &VehicleSearchQuery) -> Document {
+        let must: Vec<Document> = query
+            .terms()
+            .into_iter()
+            .map(|term| {
+                doc! {
+                    "text": {
+                        "query": term,
+                        "path": search_fields(),
+                        "fuzzy": { "maxEdits": FUZZY_MAX_EDITS, "prefixLength": 1 },
+                    }
+                }
+            })
+            .collect();
+
+        let mut filter: Vec<Document> = Vec::new();
+        if let Some(brand) = &query.brand {
+            filter.push(doc! { "equals": { "path": "brand", "value": brand.to_string() } });
+        }
+        if let Some(vehicle_type) = &query.vehicle_type {
+            filter.push(doc! { "equals": { "path": "type", "value": vehicle_type.to_string() } });
+        }
+        let (min_price, max_price) = query.price_bounds();
+        if min_price.is_some() || max_price.is_some() {
+            // One range per stored currency, bounds converted to it
+            let rates = services::currency::rates();
+            let mut should: Vec<Document> = Vec::new();
+            for currency in Currency::iter() {
+                let convert = |price: Option<Money>| price.map(|p| rates.convert(p, currency));
+                let (min, max) = (convert(min_price), convert(max_price));
+                if matches!(min, Some(None)) || matches!(max, Some(None)) {
+                    continue;
+                }
+
+                let mut range = doc! { "path": "price_by_day.amount" };
+                if let Some(min) = min.flatten() {
+                    range.insert("gte", min.to_f64());
+                }
+                if let Some(max) = max.flatten() {
+                    range.insert("lte", max.to_f64());
+                }
+                should.push(doc! { "compound": { "filter": [
+                    { "equals": { "path": "price_by_day.currency", "value": currency.to_string() } },
+                    { "range": range },
+                ] } });
+            }
+            filter.push(doc! { "compound": { "should": should, "minimumShouldMatch": 1 } });
+        }
+        filter.extend(tenant::search_filter(Vehicle::get_collection()));
+
+        let mut compound = doc! { "must": must, "mustNot": [not_deleted()] };
+        if !filter.is_empty() {
+            compound.insert("filter", filter);
+        }
+        compound
+    }
+
+    /// One page of matches, most relevant first
+    pub fn search_pipeline(&self, query: &VehicleSearchQuery, page: PageQuery) -> Vec<Document> {
+        vec![
+            doc! { "$search": { "index": &self.index, "compound": self.compound(query) } },
+            doc! { "$skip": page.skip() as i64 },
+            doc! { "$limit": page.limit() },
+        ]
+    }
+
+    /// Total and facets of all the matches
+    pub fn meta_pipeline(&self, query: &VehicleSearchQuery) -> Vec<Document> {
+        vec![doc! {
+            "$searchMeta": {
+                "index": &self.index,
+                "facet": {
+                    "operator": { "compound": self.compound(query) },
+                    "facets": {
+                        "brand": { "type": "string", "path": "brand" },
+                        "type": { "type": "string", "path": "type" },
+                    },
+                },
+            }
+        }]
+    }
+
+    /// Autocomplete on brands and models
+    pub fn suggest_pipeline(&self, prefix: &str, limit: i64) -> Vec<Document> {
+        let should: Vec<Document> = SUGGESTION_FIELDS
+            .iter()
+            .map(|field| doc! { "autocomplete": { "query": prefix, "path": *field } })
+            .collect();
+        let mut compound = doc! {
+            "should": should,
+            "minimumShouldMatch": 1,
+            "mustNot": [not_deleted()],
+        };
+        if let Some(tenant) = tenant::search_filter(Vehicle::get_collection()) {
+            compound.insert("filter", vec![Bson::Document(tenant)]);
+        }
+
+        let mut pipeline = vec![doc! { "$search": { "index": &self.index, "compound": compound } }];
+        pipeline.extend(suggestion_stages(prefix, limit));
+        pipeline
+    }
+}
+
+impl SearchProvider for AtlasSearchProvider {
+    async fn search(
+        &self,
+        query: &VehicleSearchQuery,
+        page: PageQuery,
+    ) -> AppResult<VehicleSearchResults> {
+        let (vehicles, meta) = services::fanout::try_join2(
+            async {
+                services::mongodb::aggregate::<Vehicle, Vehicle>(self.search_pipeline(query, page))
+                    .await?
+                    .try_collect::<Vec<_>>()
+                    .await
+            },
+            async {
+                services::mongodb::aggregate::<Vehicle, SearchMeta>(self.meta_pipeline(query))
+                    .await?
+                    .try_collect::<Vec<_>>()
+                    .await
+            },
+        )
+        .await?;
+
+        let meta = meta.into_iter().next();
+        Ok(VehicleSearchResults {
+            vehicles,
+            total: meta
+                .as_ref()
+                .map(|meta| meta.count.lower_bound)
+                .unwrap_or(0),
+            facets: meta
+                .map(|meta| SearchFacets {
+                    brand: meta.facet.brand.buckets,
+                    vehicle_type: meta.facet.vehicle_type.buckets,
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    async fn suggest(&self, prefix: &str, limit: i64) -> AppResult<Vec<VehicleSuggestion>> {
+        services::mongodb::aggregate::<Vehicle, VehicleSuggestion>(
+            self.suggest_pipeline(prefix, limit),
+        )
+        .await?
+        .try_collect()
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Brand;
+
+    #[test]
+    fn test_search_stage_leads_the_pipeline() {
+        let provider = AtlasSearchProvider::new("vehicles".to_string());
+        let query = VehicleSearchQuery {
+            q: "tesla  modle".to_string(),
+            brand: Some(Brand::TESLA),
+            vehicle_type: None,
+            min_price: None,
+            max_price: Some(120.0),
+            currency: None,
+        };
+
+        let pipeline = provider.search_pipeline(&query, PageQuery::new(Some(2), Some(10)));
+        let search = pipeline[0].get_document("$search").unwrap();
+        assert_eq!(search.get_str("index").unwrap(), "vehicles");
+
+        let compound = search.get_document("compound").unwrap();
+        let must = compound.get_array("must").unwrap();
+        assert_eq!(must.len(), 2);
+        let text = must[1].as_document().unwrap().get_document("text").unwrap();
+        assert_eq!(text.get_str("query").unwrap(), "modle");
+        assert!(text.contains_key("fuzzy"));
+
+        let filter = compound.get_array("filter").unwrap();
+        assert_eq!(filter.len(), 2);
+        assert_eq!(
+            compound.get_array("mustNot").unwrap(),
+            &vec![Bson::Document(not_deleted())]
+        );
+        assert_eq!(pipeline[1], doc! { "$skip": 10_i64 });
+
+        let meta = provider.meta_pipeline(&query);
+        let facet = meta[0]
+            .get_document("$searchMeta")
+            .unwrap()
+            .get_document("facet")
+            .unwrap();
+        assert!(facet.get_document("facets").unwrap().contains_key("brand"));
+    }
+
+    #[test]
+    fn test_search_meta_rows() {
+        let meta: SearchMeta = bson::from_document(doc! {
+            "count": { "lowerBound": 3_i64 },
+            "facet": {
+                "brand": { "buckets": [{ "_id": "TESLA", "count": 2_i64 }, { "_id": "BMW", "count": 1_i64 }] },
+                "type": { "buckets": [{ "_id": "CAR", "count": 3_i64 }] },
+            },
+        })
+        .unwrap();
+
+        assert_eq!(meta.count.lower_bound, 3);
+        assert_eq!(
+            meta.facet.brand.buckets[0],
+            FacetBucket {
+                value: "TESLA".to_string(),
+                count: 2
+            }
+        );
+        assert_eq!(meta.facet.vehicle_type.buckets.len(), 1);
+    }
+}
@@ -0,0 +1,126 @@
+pub mod atlas;
+pub mod regex;
+
+use bson::{doc, Document};
+use std::sync::LazyLock;
+
+use crate::error::AppResult;
+use crate::models::{VehicleSearchQuery, VehicleSearchResults, VehicleSuggestion};
+use crate::util::pagination::PageQuery;
+
+pub use atlas::AtlasSearchProvider;
+pub use regex::RegexSearchProvider;
+
+/// Fields a free text search looks into, descriptions through each supported translation
+pub const SEARCH_FIELDS: [&str; 4] = ["brand", "metadata.model", "type", "description"];
+
+/// `SEARCH_FIELDS` with a `description.<language>` path per supported language,
+/// `description` still matching the descriptions stored before translations
+pub fn search_fields() -> Vec<String> {
+    let translations = crate::util::locale::supported_languages()
+        .into_iter()
+        .map(|language| format!("description.{}", language));
+    SEARCH_FIELDS
+        .iter()
+        .map(|field| field.to_string())
+        .chain(translations)
+        .collect()
+}
+
+/// Fields completed by suggestions
+pub const SUGGESTION_FIELDS: [&str; 2] = ["brand", "metadata.model"];
+
+/// Vehicle search engine: Atlas Search on MongoDB Atlas, regular expressions elsewhere
+pub(crate) trait SearchProvider {
+    /// Vehicles matching every word of the query, with facets over all the matches
+    async fn search(
+        &self,
+        query: &VehicleSearchQuery,
+        page: PageQuery,
+    ) -> AppResult<VehicleSearchResults>;
+
+    /// Brands and models starting with a prefix, most common first
+    async fn suggest(&self, prefix: &str, limit: i64) -> AppResult<Vec<VehicleSuggestion>>;
+}
+
+/// Provider selected by SEARCH_PROVIDER
+pub enum SearchBackend {
+    Atlas(AtlasSearchProvider),
+    Regex(RegexSearchProvider),
+}
+
+// Selected once from SEARCH_PROVIDER and ATLAS_SEARCH_INDEX
+pub(crate) static SEARCH_BACKEND: LazyLock<SearchBackend> = LazyLock::new(SearchBackend::from_env);
+
+impl SearchBackend {
+    /// SEARCH_PROVIDER=atlas searches the ATLAS_SEARCH_INDEX index (default "vehicles"),
+    /// anything else falls back to regular expressions, which need no index
+    pub fn from_env() -> Self {
+        match std::env::var("SEARCH_PROVIDER").as_deref() {
+            Ok("atlas") => Self::Atlas(AtlasSearchProvider::new(
+                std::env::var("ATLAS_SEARCH_INDEX").unwrap_or_else(|_| "vehicles".to_string()),
+            )),
+            _ => Self::Regex(RegexSearchProvider),
+        }
+    }
+}
+
+impl SearchProvider for SearchBackend {
+    async fn search(
+        &self,
+        query: &VehicleSearchQuery,
+        page: PageQuery,
+    ) -> AppResult<VehicleSearchResults> {
+        match self {
+            Self::Atlas(provider) => provider.search(query, page).await,
+            Self::Regex(provider) => provider.search(query, page).await,
+        }
+    }
+
+    async fn suggest(&self, prefix: &str, limit: i64) -> AppResult<Vec<VehicleSuggestion>> {
+        match self {
+            Self::Atlas(provider) => provider.suggest(prefix, limit).await,
+            Self::Regex(provider) => provider.suggest(prefix, limit).await,
+        }
+    }
+}
+
+/// Escape a user input so it matches literally in a regular expression
+pub fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.^$|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Stages turning the vehicles matched for a prefix into suggestions: each of their
+/// brands and models starting with the prefix, with the number of vehicles having it
+fn suggestion_stages(prefix: &str, limit: i64) -> Vec<Document> {
+    let values: Vec<String> = SUGGESTION_FIELDS
+        .iter()
+        .map(|field| format!("${}", field))
+        .collect();
+    vec![
+        doc! { "$project": { "values": values } },
+        doc! { "$unwind": "$values" },
+        doc! { "$match": { "values": { "$regex": format!("^{}", escape_regex(prefix)), "$options": "i" } } },
+        doc! { "$group": { "_id": "$values", "vehicles": { "$sum": 1 } } },
+        doc! { "$sort": { "vehicles": -1, "_id": 1 } },
+        doc! { "$limit": limit },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_input_is_matched_literally() {
+        assert_eq!(escape_regex("model.3 (x)"), "model\\.3 \\(x\\)");
+        assert_eq!(escape_regex("tesla"), "tesla");
+    }
+}
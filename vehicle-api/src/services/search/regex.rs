@@ -0,0 +1,191 @@
+use bson::{doc, Bson, Document};
+use futures::TryStreamExt;
+use serde::Deserialize;
+
+use super::{escape_regex, search_fields, suggestion_stages, SearchProvider, SUGGESTION_FIELDS};
+use crate::error::AppResult;
+use crate::models::{
+    exclude_deleted, FacetBucket, SearchFacets, Vehicle, VehicleSearchQuery, VehicleSearchResults,
+    VehicleSuggestion,
+};
+use crate::services;
+use crate::util::pagination::PageQuery;
+
+/// Case insensitive substring matching, works on any MongoDB deployment without a
+/// search index. No typo tolerance, matches are returned in `_id` order.
+pub struct RegexSearchProvider;
+
+/// `$facet` output of the fallback
+#[derive(Debug, Deserialize)]
+struct FacetRow {
+    brand: Vec<FacetBucket>,
+    #[serde(rename = "type")]
+    vehicle_type: Vec<FacetBucket>,
+    total: Vec<TotalRow>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TotalRow {
+    count: u64,
+}
+
+impl RegexSearchProvider {
+    /// Every word must appear in one of the searched fields
+    pub fn filter(query: &VehicleSearchQuery) -> Document {
+        let terms: Vec<Document> = query
+            .terms()
+            .into_iter()
+            .map(|term| {
+                let pattern = escape_regex(term);
+                let fields: Vec<Document> = search_fields()
+                    .into_iter()
+                    .map(|field| doc! { field: { "$regex": &pattern, "$options": "i" } })
+                    .collect();
+                doc! { "$or": fields }
+            })
+            .collect();
+
+        let mut filter = doc! { "$and": terms };
+        if let Some(brand) = &query.brand {
+            filter.insert("brand", brand.to_string());
+        }
+        if let Some(vehicle_type) = &query.vehicle_type {
+            filter.insert("type", vehicle_type.to_string());
+        }
+        let builder = services::mongodb::QueryBuilder::new();
+        let (min_price, max_price) = query.price_bounds();
+        let rates = services::currency::rates();
+        builder.add_money_range_filter(
+            &mut filter,
+            "price_by_day",
+            min_price,
+            max_price,
+            |price, to| rates.convert(price, to),
+        );
+        exclude_deleted(&mut filter);
+        filter
+    }
+
+    /// Total and facets of all the matches
+    pub fn facet_pipeline(query: &VehicleSearchQuery) -> Vec<Document> {
+        vec![
+            doc! { "$match": Self::filter(query) },
+            doc! {
+                "$facet": {
+                    "brand": [{ "$sortByCount": "$brand" }],
+                    "type": [{ "$sortByCount": "$type" }],
+                    "total": [{ "$count": "count" }],
+                }
+            },
+        ]
+    }
+
+    pub fn suggest_pipeline(prefix: &str, limit: i64) -> Vec<Document> {
+        let pattern = format!("^{}", escape_regex(prefix));
+        let fields: Vec<Bson> = SUGGESTION_FIELDS
+            .iter()
+            .map(|field| Bson::Document(doc! { *field: { "$regex": &pattern, "$options": "i" } }))
+            .collect();
+
+        let mut pipeline = vec![doc! { "$match": { "$or": fields, "deleted_at": null } }];
+        pipeline.extend(suggestion_stages(prefix, limit));
+        pipeline
+    }
+}
+
+impl SearchProvider for RegexSearchProvider {
+    async fn search(
+        &self,
+        query: &VehicleSearchQuery,
+        page: PageQuery,
+    ) -> AppResult<VehicleSearchResults> {
+        let (vehicles, facets) = services::fanout::try_join2(
+            services::mongodb::collect_many::<Vehicle>(Self::filter(query), page.to_find_options()),
+            async {
+                services::mongodb::aggregate::<Vehicle, FacetRow>(Self::facet_pipeline(query))
+                    .await?
+                    .try_collect::<Vec<_>>()
+                    .await
+            },
+        )
+        .await?;
+
+        let facets = facets.into_iter().next();
+        Ok(VehicleSearchResults {
+            vehicles,
+            total: facets
+                .as_ref()
+                .and_then(|facets| facets.total.first())
+                .map(|total| total.count)
+                .unwrap_or(0),
+            facets: facets
+                .map(|facets| SearchFacets {
+                    brand: facets.brand,
+                    vehicle_type: facets.vehicle_type,
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    async fn suggest(&self, prefix: &str, limit: i64) -> AppResult<Vec<VehicleSuggestion>> {
+        services::mongodb::aggregate::<Vehicle, VehicleSuggestion>(Self::suggest_pipeline(
+            prefix, limit,
+        ))
+        .await?
+        .try_collect()
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Currency, Money, VehicleType};
+
+    #[test]
+    fn test_every_word_must_match_a_field() {
+        let query = VehicleSearchQuery {
+            q: "tesla s.".to_string(),
+            brand: None,
+            vehicle_type: Some(VehicleType::Car),
+            min_price: Some(50.0),
+            max_price: None,
+            currency: None,
+        };
+
+        let filter = RegexSearchProvider::filter(&query);
+        let terms = filter.get_array("$and").unwrap();
+        // Both words, then the price ranges
+        assert_eq!(terms.len(), 3);
+        let fields = terms[1].as_document().unwrap().get_array("$or").unwrap();
+        assert_eq!(fields.len(), search_fields().len());
+        assert!(fields
+            .iter()
+            .any(|field| field.as_document().unwrap().contains_key("description.fr")));
+        assert_eq!(
+            fields[0].as_document().unwrap(),
+            &doc! { "brand": { "$regex": "s\\.", "$options": "i" } }
+        );
+        assert_eq!(filter.get_str("type").unwrap(), "CAR");
+        assert_eq!(filter.get("deleted_at"), Some(&Bson::Null));
+        let prices = terms[2].as_document().unwrap().get_array("$or").unwrap();
+        assert!(prices.contains(&Bson::Document(doc! {
+            "price_by_day.currency": "EUR",
+            "price_by_day.amount": {
+                "$gte": Money::from_f64(50.0, Currency::EUR).unwrap().to_decimal128(),
+            },
+        })));
+    }
+
+    #[test]
+    fn test_facet_rows() {
+        let row: FacetRow = bson::from_document(doc! {
+            "brand": [{ "_id": "TESLA", "count": 2 }],
+            "type": [{ "_id": "CAR", "count": 2 }],
+            "total": [{ "count": 2 }],
+        })
+        .unwrap();
+        assert_eq!(row.brand[0].value, "TESLA");
+        assert_eq!(row.total[0].count, 2);
+    }
+}
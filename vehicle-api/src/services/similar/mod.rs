@@ -0,0 +1,104 @@
+use bson::{doc, oid::ObjectId};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::error::AppResult;
+use crate::models::{
+    exclude_deleted, ReservationDay, SimilarVehicle, SimilarityWeights, Vehicle, MAX_SIMILAR,
+};
+use crate::services;
+
+/// Candidates kept per vehicle, more than returned so some can be unavailable
+const RANKED_CANDIDATES: usize = 5 * MAX_SIMILAR as usize;
+
+static WEIGHTS: LazyLock<SimilarityWeights> = LazyLock::new(SimilarityWeights::from_env);
+
+/// Seconds a ranking is reused (SIMILAR_CACHE_SECS, default 300, 0 disables the cache)
+static CACHE_TTL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(
+        std::env::var("SIMILAR_CACHE_SECS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(300),
+    )
+});
+
+// Rankings per tenant and vehicle, with the time they were computed
+type Rankings = HashMap<(Option<String>, ObjectId), (Instant, Vec<SimilarVehicle>)>;
+static CACHE: LazyLock<RwLock<Rankings>> = LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Vehicles most similar to `vehicle`, best first, the ones held on a day of
+/// `free_on` left out
+pub async fn similar(
+    vehicle: &Vehicle,
+    free_on: Option<(NaiveDate, NaiveDate)>,
+    limit: usize,
+) -> AppResult<Vec<SimilarVehicle>> {
+    let Some(vehicle_id) = vehicle.id else {
+        return Ok(Vec::new());
+    };
+    let ranking = ranked(vehicle, vehicle_id).await?;
+
+    let held: Vec<ObjectId> = match free_on {
+        Some((from, to)) => {
+            let ids: Vec<ObjectId> = ranking
+                .iter()
+                .filter_map(|similar| similar.vehicle.id)
+                .collect();
+            services::mongodb::distinct::<ReservationDay>(
+                "vehicle_id",
+                doc! {
+                    "vehicle_id": { "$in": ids },
+                    "day": { "$gte": from.to_string(), "$lte": to.to_string() },
+                },
+            )
+            .await?
+            .into_iter()
+            .filter_map(|id| id.as_object_id())
+            .collect()
+        }
+        None => Vec::new(),
+    };
+
+    Ok(ranking
+        .into_iter()
+        .filter(|similar| similar.vehicle.id.is_some_and(|id| !held.contains(&id)))
+        .take(limit)
+        .collect())
+}
+
+/// Every other vehicle scored against `vehicle`, from the cache while it is fresh
+async fn ranked(vehicle: &Vehicle, vehicle_id: ObjectId) -> AppResult<Vec<SimilarVehicle>> {
+    let key = (services::mongodb::tenant::current(), vehicle_id);
+    let cached = CACHE.read().ok().and_then(|cache| {
+        cache
+            .get(&key)
+            .filter(|(computed_at, _)| computed_at.elapsed() < *CACHE_TTL)
+            .map(|(_, ranking)| ranking.clone())
+    });
+    if let Some(ranking) = cached {
+        return Ok(ranking);
+    }
+
+    let mut filter = doc! { "_id": { "$ne": vehicle_id } };
+    exclude_deleted(&mut filter);
+    let candidates: Vec<Vehicle> = services::mongodb::collect_many(filter, None).await?;
+    let mut ranking: Vec<SimilarVehicle> = candidates
+        .into_iter()
+        .map(|candidate| SimilarVehicle {
+            score: WEIGHTS.score(vehicle, &candidate),
+            vehicle: candidate,
+        })
+        .collect();
+    ranking.sort_by(|a, b| b.score.total_cmp(&a.score));
+    ranking.truncate(RANKED_CANDIDATES);
+
+    if let Ok(mut cache) = CACHE.write() {
+        // Expired rankings go first, the cache only holds recently viewed vehicles
+        cache.retain(|_, (computed_at, _)| computed_at.elapsed() < *CACHE_TTL);
+        cache.insert(key, (Instant::now(), ranking.clone()));
+    }
+    Ok(ranking)
+}
@@ -0,0 +1,17 @@
+use bson::{doc, oid::ObjectId};
+
+use crate::error::AppResult;
+use crate::models::{Station, Vehicle};
+use crate::services;
+
+pub async fn get(station_id: &ObjectId) -> AppResult<Option<Station>> {
+    services::mongodb::get_one(doc! { "_id": station_id }, None).await
+}
+
+/// Station a vehicle is picked up from, None for vehicles without one
+pub async fn for_vehicle(vehicle: &Vehicle) -> AppResult<Option<Station>> {
+    match &vehicle.station_id {
+        Some(station_id) => get(station_id).await,
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use crate::error::{AppError, AppResult};
+use crate::services::storage::{is_valid_key, Storage};
+
+/// Files below a directory of the server, for development and mounted volumes
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, _content_type: &str, body: Vec<u8>) -> AppResult<String> {
+        if !is_valid_key(key) {
+            return Err(AppError::internal_server_error(format!(
+                "Invalid storage key: {}",
+                key
+            )));
+        }
+
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, body).await?;
+        Ok(path.display().to_string())
+    }
+
+    async fn get(&self, key: &str) -> AppResult<Vec<u8>> {
+        if !is_valid_key(key) {
+            return Err(AppError::internal_server_error(format!(
+                "Invalid storage key: {}",
+                key
+            )));
+        }
+
+        Ok(tokio::fs::read(self.root.join(key)).await?)
+    }
+}
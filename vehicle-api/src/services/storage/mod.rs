@@ -0,0 +1,89 @@
+pub mod local;
+pub mod s3;
+
+use std::sync::LazyLock;
+
+use crate::error::AppResult;
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+/// Where generated files (accounting exports, ...) are delivered
+pub(crate) trait Storage {
+    /// Store a file under a key such as `accounting/2025-08.csv`, returns its location
+    async fn put(&self, key: &str, content_type: &str, body: Vec<u8>) -> AppResult<String>;
+
+    /// Content of a file stored under `key`
+    async fn get(&self, key: &str) -> AppResult<Vec<u8>>;
+}
+
+/// Storage selected by STORAGE_PROVIDER
+pub enum StorageBackend {
+    Local(LocalStorage),
+    S3(S3Storage),
+}
+
+// Selected once from STORAGE_PROVIDER and the settings of the provider
+pub(crate) static STORAGE_BACKEND: LazyLock<StorageBackend> =
+    LazyLock::new(StorageBackend::from_env);
+
+impl StorageBackend {
+    /// STORAGE_PROVIDER=s3 uploads to the S3_BUCKET bucket when it is configured, anything
+    /// else writes below STORAGE_LOCAL_DIR (default "storage")
+    pub fn from_env() -> Self {
+        if std::env::var("STORAGE_PROVIDER").as_deref() == Ok("s3") {
+            match S3Storage::from_env() {
+                Some(storage) => return Self::S3(storage),
+                None => log::error!(
+                    "STORAGE_PROVIDER=s3 needs S3_BUCKET, AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, using local storage"
+                ),
+            }
+        }
+        Self::Local(LocalStorage::new(
+            std::env::var("STORAGE_LOCAL_DIR").unwrap_or_else(|_| "storage".to_string()),
+        ))
+    }
+}
+
+impl Storage for StorageBackend {
+    async fn put(&self, key: &str, content_type: &str, body: Vec<u8>) -> AppResult<String> {
+        match self {
+            Self::Local(storage) => storage.put(key, content_type, body).await,
+            Self::S3(storage) => storage.put(key, content_type, body).await,
+        }
+    }
+
+    async fn get(&self, key: &str) -> AppResult<Vec<u8>> {
+        match self {
+            Self::Local(storage) => storage.get(key).await,
+            Self::S3(storage) => storage.get(key).await,
+        }
+    }
+}
+
+/// Keys are relative paths made of letters, digits, `-`, `_` and `.`, never `..`
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_stay_below_the_root() {
+        assert!(is_valid_key("accounting/2025-08-01_2025-08-31.csv"));
+        assert!(!is_valid_key("../etc/passwd"));
+        assert!(!is_valid_key("/accounting/export.csv"));
+        assert!(!is_valid_key("accounting//export.csv"));
+        assert!(!is_valid_key("accounting/export 1.csv"));
+    }
+}
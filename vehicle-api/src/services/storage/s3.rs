@@ -0,0 +1,231 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::error::{AppError, AppResult};
+use crate::services::storage::{is_valid_key, Storage};
+
+/// Amazon S3, or any S3 compatible service (MinIO, ...) through S3_ENDPOINT.
+/// Requests are signed with AWS Signature Version 4.
+pub struct S3Storage {
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    client: reqwest::Client,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hmac(key: &[u8], message: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(message.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Key derived from the secret for one day, region and service
+fn signing_key(secret_access_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac(format!("AWS4{}", secret_access_key).as_bytes(), date);
+    let key = hmac(&key, region);
+    let key = hmac(&key, service);
+    hmac(&key, "aws4_request")
+}
+
+/// Percent-encode a key as S3 expects in the path, `/` kept
+fn uri_encode(key: &str) -> String {
+    key.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+impl S3Storage {
+    /// S3_BUCKET, AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY are required. S3_REGION
+    /// defaults to us-east-1 and S3_ENDPOINT to the AWS endpoint of the region.
+    pub fn from_env() -> Option<Self> {
+        let region = std::env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+        Some(Self {
+            endpoint: std::env::var("S3_ENDPOINT")
+                .unwrap_or_else(|_| format!("https://s3.{}.amazonaws.com", region))
+                .trim_end_matches('/')
+                .to_string(),
+            bucket: std::env::var("S3_BUCKET").ok()?,
+            access_key_id: std::env::var("AWS_ACCESS_KEY_ID").ok()?,
+            secret_access_key: std::env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            region,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// URL of an object and the host it is signed for
+    fn object_url(&self, key: &str) -> AppResult<(reqwest::Url, String)> {
+        let url = format!("{}/{}/{}", self.endpoint, self.bucket, uri_encode(key));
+        let parsed = reqwest::Url::parse(&url)
+            .map_err(|e| AppError::internal_server_error(format!("Invalid S3 URL: {}", e)))?;
+        let host = match (parsed.host_str(), parsed.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(AppError::internal_server_error("S3 URL has no host")),
+        };
+        Ok((parsed, host))
+    }
+
+    /// Authorization header of a request, path style (`/bucket/key`). Requests with a
+    /// body sign its content type as well.
+    fn authorization(
+        &self,
+        method: &str,
+        host: &str,
+        path: &str,
+        content_type: Option<&str>,
+        payload_hash: &str,
+        now: DateTime<Utc>,
+    ) -> String {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+
+        let (content_type_header, signed_headers) = match content_type {
+            Some(content_type) => (
+                format!("content-type:{}\n", content_type),
+                "content-type;host;x-amz-content-sha256;x-amz-date",
+            ),
+            None => (String::new(), "host;x-amz-content-sha256;x-amz-date"),
+        };
+        let canonical_request = format!(
+            "{}\n{}\n\n{}host:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method,
+            path,
+            content_type_header,
+            host,
+            payload_hash,
+            amz_date,
+            signed_headers,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(&self.secret_access_key, &date, &self.region, "s3");
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id,
+            scope,
+            signed_headers,
+            hex(&hmac(&key, &string_to_sign))
+        )
+    }
+}
+
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, content_type: &str, body: Vec<u8>) -> AppResult<String> {
+        if !is_valid_key(key) {
+            return Err(AppError::internal_server_error(format!(
+                "Invalid storage key: {}",
+                key
+            )));
+        }
+
+        let (parsed, host) = self.object_url(key)?;
+        let now = Utc::now();
+        let payload_hash = hex(&Sha256::digest(&body));
+        let authorization = self.authorization(
+            "PUT",
+            &host,
+            parsed.path(),
+            Some(content_type),
+            &payload_hash,
+            now,
+        );
+
+        let response = self
+            .client
+            .put(parsed)
+            .header("Authorization", authorization)
+            .header("Content-Type", content_type)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string())
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::internal_server_error(format!("S3 upload failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::internal_server_error(format!(
+                "S3 upload of {} failed with status {}",
+                key,
+                response.status()
+            )));
+        }
+        Ok(format!("s3://{}/{}", self.bucket, key))
+    }
+
+    async fn get(&self, key: &str) -> AppResult<Vec<u8>> {
+        if !is_valid_key(key) {
+            return Err(AppError::internal_server_error(format!(
+                "Invalid storage key: {}",
+                key
+            )));
+        }
+
+        let (parsed, host) = self.object_url(key)?;
+        let now = Utc::now();
+        let payload_hash = hex(&Sha256::digest(b""));
+        let authorization =
+            self.authorization("GET", &host, parsed.path(), None, &payload_hash, now);
+
+        let response = self
+            .client
+            .get(parsed)
+            .header("Authorization", authorization)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string())
+            .send()
+            .await
+            .map_err(|e| AppError::internal_server_error(format!("S3 download failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::internal_server_error(format!(
+                "S3 download of {} failed with status {}",
+                key,
+                response.status()
+            )));
+        }
+        let body = response.bytes().await.map_err(|e| {
+            AppError::internal_server_error(format!("S3 download of {} failed: {}", key, e))
+        })?;
+        Ok(body.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signing_key_matches_the_aws_example() {
+        // Example of the AWS Signature Version 4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex(&key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+        assert_eq!(uri_encode("exports/a b.csv"), "exports/a%20b.csv");
+    }
+}
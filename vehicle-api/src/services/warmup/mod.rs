@@ -0,0 +1,83 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use bson::doc;
+
+use crate::authentication;
+use crate::error::AppResult;
+use crate::experiment;
+use crate::models;
+use crate::services;
+use crate::util;
+
+/// Delay before retrying a failed warm-up (e.g. MongoDB not reachable yet)
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+// Flipped once the warm-up succeeded, reported by GET /health/ready
+static READY: AtomicBool = AtomicBool::new(false);
+
+/// True once the warm-up is done and the instance can take traffic
+pub fn is_ready() -> bool {
+    READY.load(Ordering::Relaxed)
+}
+
+/// Warm the instance up in the background, retrying until it succeeds,
+/// then flip readiness. The server already answers meanwhile.
+pub fn spawn() {
+    actix_web::rt::spawn(async {
+        loop {
+            let started = Instant::now();
+            match run().await {
+                Ok(()) => {
+                    READY.store(true, Ordering::Relaxed);
+                    log::info!("Warm-up done in {:?}, ready", started.elapsed());
+                    return;
+                }
+                Err(error) => {
+                    log::warn!("Warm-up failed, retrying in {:?}: {}", RETRY_DELAY, error);
+                    tokio::time::sleep(RETRY_DELAY).await;
+                }
+            }
+        }
+    });
+}
+
+/// Pay every lazy initialization cost now instead of on the first requests
+async fn run() -> AppResult<()> {
+    // MongoDB pool: connect, then open the minimum number of connections at once
+    let database = services::mongodb::get_database(services::mongodb::DATABASE_NAME).await?;
+    let pings = (0..services::mongodb::min_pool_size())
+        .map(|_| async { database.run_command(doc! { "ping": 1 }).await });
+    futures::future::try_join_all(pings).await?;
+    // Index creation, validators and migrations are writes, a read-only instance relies
+    // on the primary region's
+    if !util::read_only::is_enabled() {
+        services::changeset::ensure_indexes().await?;
+        services::idempotency::ensure_indexes().await?;
+        services::ledger::ensure_indexes().await?;
+        services::mongodb::geo::ensure_indexes().await?;
+        services::mongodb::validation::apply_validators().await?;
+        services::currency::migrate_prices().await?;
+    }
+
+    // Catalog and enum caches
+    models::preload_schemas();
+    services::webhook::schema::event_schemas();
+    services::holidays::get_holiday_provider().await;
+
+    // Policies and keys, a broken configuration keeps the instance unready
+    authentication::preload();
+    authentication::revocation::refresh().await?;
+    experiment::refresh().await?;
+    services::risk::get_risk_engine().await;
+    services::webhook::get_key_ring().await?;
+    services::email::preload();
+    if std::env::var("PII_ENCRYPTION_KEYS").is_ok() {
+        services::encryption::get_field_cipher().await?;
+    }
+    services::mongodb::booking::get_booking_policy().await?;
+    services::mongodb::booking::get_auto_confirm_policy().await?;
+    services::mongodb::booking::get_cancellation_policy().await?;
+
+    Ok(())
+}
@@ -0,0 +1,206 @@
+use bson::{doc, oid::ObjectId};
+use chrono::Utc;
+use std::sync::LazyLock;
+
+use super::{schema, signing};
+use crate::error::AppResult;
+use crate::models::{BookingEventData, DeliveryAttempt, Event, WebhookDelivery, WebhookEndpoint};
+use crate::services;
+use crate::services::mongodb::MongoStruct;
+use crate::util::deadline;
+
+/// Header naming the event type of a delivery
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+
+/// Header carrying the delivery ID, the same on every retry so consumers can deduplicate
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+/// Time endpoints get to answer
+const DELIVERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+// Shared client
+static HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+});
+
+/// Attempts before a delivery is marked FAILED (WEBHOOK_MAX_ATTEMPTS, default 6)
+pub fn max_attempts() -> u32 {
+    std::env::var("WEBHOOK_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|value| value.parse::<u32>().ok())
+        .filter(|attempts| *attempts > 0)
+        .unwrap_or(6)
+}
+
+/// Seconds between two retry sweeps (WEBHOOK_RETRY_INTERVAL_SECS, default 30,
+/// 0 disables retries)
+fn retry_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("WEBHOOK_RETRY_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(30);
+
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// Retry the pending deliveries periodically in the background
+pub fn spawn_scheduler() {
+    let Some(interval) = retry_interval() else {
+        log::info!("Webhook retries disabled");
+        return;
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(error) = retry_due().await {
+                log::error!("Webhook retries failed: {}", error);
+            }
+        }
+    });
+}
+
+/// Body of a booking event, checked against its published schema in debug builds. A
+/// mismatch is our bug, it is logged and the event is sent all the same.
+pub fn booking_event_body(event: &Event<BookingEventData>) -> Option<String> {
+    let payload = serde_json::to_value(event).ok()?;
+    if let Err(error) = schema::check_outgoing_payload(event.event_type, &payload) {
+        log::error!("{}", error);
+    }
+    serde_json::to_string(&payload).ok()
+}
+
+/// Send a booking event to the endpoints of the booking's rental company. Best effort: a
+/// failure is logged and never fails the change being published, deliveries run in the
+/// background. Called by the domain events publisher.
+pub async fn publish(
+    booking_id: ObjectId,
+    tenant_id: Option<String>,
+    event: Event<BookingEventData>,
+) {
+    // Global endpoints are not the tenant's, look them up with the booking's tenant by hand
+    let event_type = event.event_type;
+    let enqueued =
+        services::mongodb::tenant::scope(None, enqueue(booking_id, tenant_id, event)).await;
+    if let Err(error) = enqueued {
+        log::error!("Failed to publish {}: {}", event_type, error);
+    }
+}
+
+async fn enqueue(
+    booking_id: ObjectId,
+    tenant_id: Option<String>,
+    event: Event<BookingEventData>,
+) -> AppResult<()> {
+    let event_type = event.event_type;
+    let Some(body) = booking_event_body(&event) else {
+        return Ok(());
+    };
+
+    let filter = doc! { "$or": [{ "tenant_id": &tenant_id }, { "tenant_id": null }] };
+    let endpoints: Vec<WebhookEndpoint> = services::mongodb::collect_many(filter, None).await?;
+
+    for endpoint in endpoints
+        .iter()
+        .filter(|endpoint| endpoint.subscribes_to(event_type))
+    {
+        let Some(mut delivery) =
+            WebhookDelivery::new(endpoint, event_type, booking_id, body.clone())
+        else {
+            continue;
+        };
+        delivery.id = Some(services::mongodb::insert_one(&delivery, None).await?);
+        let deadline = deadline::current();
+        actix_web::rt::spawn(async move {
+            if let Err(error) = attempt(delivery, deadline).await {
+                log::error!("Webhook delivery failed: {}", error);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Attempt every pending delivery whose retry time has come
+pub async fn retry_due() -> AppResult<()> {
+    let filter = doc! {
+        "status": "PENDING",
+        "next_attempt_at": { "$lte": bson::DateTime::from_chrono(Utc::now()) },
+    };
+    let deliveries: Vec<WebhookDelivery> = services::mongodb::collect_many(filter, None).await?;
+    for delivery in deliveries {
+        if let Err(error) = attempt(delivery, None).await {
+            log::error!("Webhook delivery failed: {}", error);
+        }
+    }
+    Ok(())
+}
+
+/// POST a delivery to its endpoint, signed with the active key, and store the outcome.
+/// The first attempt only has the time left to the request publishing the event.
+async fn attempt(
+    mut delivery: WebhookDelivery,
+    deadline: Option<tokio::time::Instant>,
+) -> AppResult<()> {
+    let Some(delivery_id) = delivery.id else {
+        return Ok(());
+    };
+    let budget =
+        deadline.map(|deadline| deadline.saturating_duration_since(tokio::time::Instant::now()));
+    if budget.is_some_and(|budget| budget.is_zero()) {
+        // Left pending for the retries
+        return Ok(());
+    }
+    // Claimed by pushing the next attempt back, another instance sweeping meanwhile skips it
+    if !claim(&delivery_id).await? {
+        return Ok(());
+    }
+
+    let key_ring = services::webhook::get_key_ring().await?;
+    let signature = key_ring.sign(delivery.body.as_bytes(), Utc::now().timestamp());
+    let mut request = HTTP_CLIENT
+        .post(&delivery.url)
+        .header("Content-Type", "application/json")
+        .header(signing::SIGNATURE_HEADER, signature.to_header_value())
+        .header(EVENT_HEADER, delivery.event_type.to_string())
+        .header(DELIVERY_HEADER, delivery_id.to_hex());
+    if let Some(budget) = budget.map(|budget| budget.min(DELIVERY_TIMEOUT)) {
+        request = request
+            .timeout(budget)
+            .header(deadline::DEADLINE_HEADER, budget.as_millis().to_string());
+    }
+    let response = request.body(delivery.body.clone()).send().await;
+
+    let outcome = match response {
+        Ok(response) if response.status().is_success() => {
+            DeliveryAttempt::Delivered(response.status().as_u16())
+        }
+        Ok(response) => DeliveryAttempt::Rejected(response.status().as_u16()),
+        Err(error) => DeliveryAttempt::Unreachable(error.to_string()),
+    };
+    delivery.record(outcome, max_attempts(), Utc::now());
+
+    services::mongodb::find_one_and_replace(doc! { "_id": delivery_id }, &delivery, None).await?;
+    Ok(())
+}
+
+/// Take a pending delivery for an attempt, false when another attempt took it first
+async fn claim(delivery_id: &ObjectId) -> AppResult<bool> {
+    let now = Utc::now();
+    let lease = now + chrono::Duration::minutes(1);
+    let result = services::mongodb::update_one(
+        WebhookDelivery::get_collection(),
+        doc! {
+            "_id": delivery_id,
+            "status": "PENDING",
+            "next_attempt_at": { "$lte": bson::DateTime::from_chrono(now) },
+        },
+        doc! { "$set": { "next_attempt_at": bson::DateTime::from_chrono(lease) } },
+        None,
+    )
+    .await?;
+    Ok(result.modified_count == 1)
+}
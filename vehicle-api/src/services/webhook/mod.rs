@@ -0,0 +1,7 @@
+pub mod delivery;
+pub mod schema;
+// Signing helpers are also meant for webhook consumers, not all of them are used by the API itself
+#[allow(dead_code)]
+pub mod signing;
+pub use delivery::publish;
+pub use signing::get_key_ring;
@@ -0,0 +1,95 @@
+use std::sync::LazyLock;
+
+use strum::IntoEnumIterator;
+
+use crate::models::{EventSchema, EventType};
+
+// Schemas are generated once, on first use or during the warm-up
+static EVENT_SCHEMAS: LazyLock<Vec<EventSchema>> = LazyLock::new(|| {
+    EventType::iter()
+        .map(|event_type| EventSchema {
+            event_type,
+            schema: event_type.schema(),
+        })
+        .collect()
+});
+
+/// JSON Schemas of every published event type
+pub fn event_schemas() -> Vec<EventSchema> {
+    EVENT_SCHEMAS.clone()
+}
+
+/// Check an outgoing payload against its published schema.
+/// Only enforced in debug builds, release builds skip the check.
+pub fn check_outgoing_payload(
+    event_type: EventType,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    if !cfg!(debug_assertions) {
+        return Ok(());
+    }
+
+    let schema = event_type.schema();
+    let validator = jsonschema::validator_for(&schema)
+        .map_err(|e| format!("Invalid schema for {}: {}", event_type, e))?;
+
+    let errors: Vec<String> = validator
+        .iter_errors(payload)
+        .map(|error| format!("{} at {}", error, error.instance_path))
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Payload does not match {} schema: {}",
+            event_type,
+            errors.join("; ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BookingEventData, BookingStatus, Event};
+    use chrono::NaiveDate;
+
+    fn booking_event() -> Event<BookingEventData> {
+        Event {
+            event_type: EventType::BookingStatusChanged,
+            occurred_at: chrono::Utc::now(),
+            data: BookingEventData {
+                booking_id: "66b1f0c2a1b2c3d4e5f60718".to_string(),
+                vehicle_id: "66b1f0c2a1b2c3d4e5f60719".to_string(),
+                customer_id: "customer_user_1".to_string(),
+                from_date: NaiveDate::from_ymd_opt(2025, 8, 1).unwrap(),
+                to_date: NaiveDate::from_ymd_opt(2025, 8, 10).unwrap(),
+                status: BookingStatus::Rejected("Maintenance".to_string()),
+            },
+        }
+    }
+
+    #[test]
+    fn test_every_event_type_has_a_schema() {
+        let schemas = event_schemas();
+        assert_eq!(schemas.len(), EventType::iter().count());
+        assert!(schemas.iter().all(|entry| entry.schema.is_object()));
+    }
+
+    #[test]
+    fn test_outgoing_payload_matches_schema() {
+        let payload = serde_json::to_value(booking_event()).unwrap();
+        assert!(check_outgoing_payload(EventType::BookingStatusChanged, &payload).is_ok());
+    }
+
+    #[test]
+    fn test_schema_drift_is_detected() {
+        let mut payload = serde_json::to_value(booking_event()).unwrap();
+        payload["data"]
+            .as_object_mut()
+            .unwrap()
+            .remove("customer_id");
+        assert!(check_outgoing_payload(EventType::BookingStatusChanged, &payload).is_err());
+    }
+}
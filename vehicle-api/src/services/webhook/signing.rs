@@ -0,0 +1,261 @@
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use std::env;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Jwk, JwkSet};
+
+/// Header carrying the webhook signature: `t=<unix>,kid=<key id>,v1=<base64url signature>`
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+/// Maximum accepted age (in seconds) of a signature timestamp
+pub const DEFAULT_TOLERANCE_SECS: i64 = 300;
+
+// Global key ring using OnceCell for lazy initialization
+pub(crate) static KEY_RING: tokio::sync::OnceCell<KeyRing> = tokio::sync::OnceCell::const_new();
+
+/// Get the webhook signing key ring
+pub async fn get_key_ring() -> AppResult<&'static KeyRing> {
+    KEY_RING
+        .get_or_try_init(|| async { KeyRing::from_env() })
+        .await
+}
+
+pub struct SigningKeyEntry {
+    pub kid: String,
+    pub key: SigningKey,
+}
+
+/// Ordered signing keys: the first one signs, the others are only published
+/// so that payloads signed before a rotation can still be verified.
+pub struct KeyRing {
+    keys: Vec<SigningKeyEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebhookSignature {
+    pub timestamp: i64,
+    pub kid: String,
+    pub signature: String,
+}
+
+impl KeyRing {
+    pub fn new(keys: Vec<SigningKeyEntry>) -> AppResult<Self> {
+        if keys.is_empty() {
+            return Err(AppError::internal_server_error(
+                "Webhook key ring needs at least one key",
+            ));
+        }
+        Ok(Self { keys })
+    }
+
+    /// Load keys from WEBHOOK_SIGNING_KEYS ("kid:base64seed,kid:base64seed", newest first).
+    /// Falls back to an ephemeral key when unset (development only).
+    pub fn from_env() -> AppResult<Self> {
+        let raw = match env::var("WEBHOOK_SIGNING_KEYS") {
+            Ok(raw) if !raw.trim().is_empty() => raw,
+            _ => {
+                log::warn!("WEBHOOK_SIGNING_KEYS not set, using an ephemeral signing key");
+                let key = SigningKey::generate(&mut rand::rngs::OsRng);
+                return Self::new(vec![SigningKeyEntry {
+                    kid: format!("ephemeral-{}", chrono::Utc::now().timestamp()),
+                    key,
+                }]);
+            }
+        };
+
+        let keys = raw
+            .split(',')
+            .map(|entry| entry.trim())
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (kid, seed) = entry.split_once(':').ok_or_else(|| {
+                    AppError::internal_server_error("Webhook signing key must be 'kid:seed'")
+                })?;
+                let seed: [u8; 32] = STANDARD
+                    .decode(seed)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| {
+                        AppError::internal_server_error(format!(
+                            "Webhook signing key '{}' must be a base64 encoded 32 bytes seed",
+                            kid
+                        ))
+                    })?;
+                Ok(SigningKeyEntry {
+                    kid: kid.to_string(),
+                    key: SigningKey::from_bytes(&seed),
+                })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Self::new(keys)
+    }
+
+    /// Sign a payload with the active key
+    pub fn sign(&self, body: &[u8], timestamp: i64) -> WebhookSignature {
+        let active = &self.keys[0];
+        let signature = active.key.sign(&signed_message(timestamp, body));
+
+        WebhookSignature {
+            timestamp,
+            kid: active.kid.clone(),
+            signature: URL_SAFE_NO_PAD.encode(signature.to_bytes()),
+        }
+    }
+
+    /// Public keys in JWKS format
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .keys
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| Jwk {
+                    kty: "OKP".to_string(),
+                    crv: "Ed25519".to_string(),
+                    kid: entry.kid.clone(),
+                    x: URL_SAFE_NO_PAD.encode(entry.key.verifying_key().to_bytes()),
+                    key_use: "sig".to_string(),
+                    alg: "EdDSA".to_string(),
+                    active: index == 0,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl WebhookSignature {
+    pub fn to_header_value(&self) -> String {
+        format!(
+            "t={},kid={},v1={}",
+            self.timestamp, self.kid, self.signature
+        )
+    }
+
+    pub fn parse(header: &str) -> Result<Self, String> {
+        let mut timestamp = None;
+        let mut kid = None;
+        let mut signature = None;
+
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+                Some(("kid", value)) => kid = Some(value.to_string()),
+                Some(("v1", value)) => signature = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        match (timestamp, kid, signature) {
+            (Some(timestamp), Some(kid), Some(signature)) => Ok(Self {
+                timestamp,
+                kid,
+                signature,
+            }),
+            _ => Err("Malformed webhook signature header.".to_string()),
+        }
+    }
+}
+
+/// Verify a webhook payload against the published JWKS (consumer side helper)
+pub fn verify(
+    header: &str,
+    body: &[u8],
+    jwks: &JwkSet,
+    now: i64,
+    tolerance_secs: i64,
+) -> Result<(), String> {
+    let parsed = WebhookSignature::parse(header)?;
+
+    if (now - parsed.timestamp).abs() > tolerance_secs {
+        return Err("Webhook signature timestamp is outside the tolerance window.".to_string());
+    }
+
+    let jwk = jwks
+        .keys
+        .iter()
+        .find(|jwk| jwk.kid == parsed.kid)
+        .ok_or_else(|| format!("Unknown webhook signing key '{}'.", parsed.kid))?;
+
+    let public_key: [u8; 32] = URL_SAFE_NO_PAD
+        .decode(&jwk.x)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| "Invalid public key in JWKS.".to_string())?;
+    let verifying_key =
+        VerifyingKey::from_bytes(&public_key).map_err(|_| "Invalid public key in JWKS.")?;
+
+    let signature: [u8; 64] = URL_SAFE_NO_PAD
+        .decode(&parsed.signature)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| "Malformed webhook signature.".to_string())?;
+
+    verifying_key
+        .verify(
+            &signed_message(parsed.timestamp, body),
+            &Signature::from_bytes(&signature),
+        )
+        .map_err(|_| "Webhook signature does not match payload.".to_string())
+}
+
+/// Message actually signed: "<timestamp>.<body>"
+fn signed_message(timestamp: i64, body: &[u8]) -> Vec<u8> {
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_ring() -> KeyRing {
+        KeyRing::new(vec![
+            SigningKeyEntry {
+                kid: "2025-02".to_string(),
+                key: SigningKey::from_bytes(&[2u8; 32]),
+            },
+            SigningKeyEntry {
+                kid: "2025-01".to_string(),
+                key: SigningKey::from_bytes(&[1u8; 32]),
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        let ring = key_ring();
+        let body = br#"{"event":"booking.confirmed"}"#;
+        let header = ring.sign(body, 1_700_000_000).to_header_value();
+
+        assert!(header.starts_with("t=1700000000,kid=2025-02,v1="));
+        assert!(verify(&header, body, &ring.jwks(), 1_700_000_010, 300).is_ok());
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_or_stale_payload() {
+        let ring = key_ring();
+        let header = ring.sign(b"original", 1_700_000_000).to_header_value();
+
+        assert!(verify(&header, b"tampered", &ring.jwks(), 1_700_000_000, 300).is_err());
+        assert!(verify(&header, b"original", &ring.jwks(), 1_700_001_000, 300).is_err());
+    }
+
+    #[test]
+    fn test_rotated_key_still_verifies() {
+        let old_ring = KeyRing::new(vec![SigningKeyEntry {
+            kid: "2025-01".to_string(),
+            key: SigningKey::from_bytes(&[1u8; 32]),
+        }])
+        .unwrap();
+        let header = old_ring.sign(b"payload", 1_700_000_000).to_header_value();
+
+        let jwks = key_ring().jwks();
+        assert!(jwks.keys[0].active && !jwks.keys[1].active);
+        assert!(verify(&header, b"payload", &jwks, 1_700_000_000, 300).is_ok());
+    }
+}
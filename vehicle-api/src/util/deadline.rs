@@ -0,0 +1,176 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::HeaderMap,
+    middleware, Error, ResponseError,
+};
+use chrono::{DateTime, Utc};
+use std::future::Future;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::error::{AppError, AppResult};
+
+/// Header carrying the caller's deadline: milliseconds left (`2500`) or an RFC 3339
+/// instant (`2025-08-01T10:00:02.5Z`)
+pub const DEADLINE_HEADER: &str = "X-Request-Deadline";
+
+tokio::task_local! {
+    // Instant the current request must be answered by
+    static DEADLINE: Instant;
+}
+
+// Read once from REQUEST_TIMEOUT_MS, 0 leaves requests without a deadline of their own
+static REQUEST_TIMEOUT: LazyLock<Option<Duration>> = LazyLock::new(|| {
+    let millis = std::env::var("REQUEST_TIMEOUT_MS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(30_000);
+    (millis > 0).then(|| Duration::from_millis(millis))
+});
+
+/// Budget requested by the caller, None when the header is missing or invalid
+pub fn requested_budget(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(DEADLINE_HEADER)?.to_str().ok()?.trim();
+    if let Ok(millis) = value.parse::<u64>() {
+        return Some(Duration::from_millis(millis));
+    }
+    let deadline = DateTime::parse_from_rfc3339(value)
+        .ok()?
+        .with_timezone(&Utc);
+    Some((deadline - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Budget of a request: the caller may shorten the configured timeout, never extend it
+pub fn budget(requested: Option<Duration>, timeout: Option<Duration>) -> Option<Duration> {
+    match (requested, timeout) {
+        (Some(requested), Some(timeout)) => Some(requested.min(timeout)),
+        (requested, timeout) => requested.or(timeout),
+    }
+}
+
+/// Run a future with a deadline, every operation it starts gets the time left
+pub async fn scope<F: Future>(deadline: Option<Instant>, future: F) -> F::Output {
+    match deadline {
+        Some(deadline) => DEADLINE.scope(deadline, future).await,
+        None => future.await,
+    }
+}
+
+/// Deadline of the current request, None outside of a request or without a deadline
+pub fn current() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Time left before the deadline of the current request
+pub fn remaining() -> Option<Duration> {
+    current().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+/// Fail with 504 once the deadline has passed, rather than starting work nobody waits for
+pub fn check() -> AppResult<()> {
+    match remaining() {
+        Some(Duration::ZERO) => Err(AppError::gateway_timeout("Request deadline exceeded")),
+        _ => Ok(()),
+    }
+}
+
+/// Shorten an operation timeout to the time left, e.g. a MongoDB `max_time`
+pub fn limit(timeout: Option<Duration>) -> Option<Duration> {
+    match (timeout, remaining()) {
+        (Some(timeout), Some(remaining)) => Some(timeout.min(remaining)),
+        (timeout, remaining) => timeout.or(remaining),
+    }
+}
+
+// Deadline Middleware using from_fn: answers 504 once the budget is spent
+pub async fn deadline_middleware(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let Some(budget) = budget(
+        requested_budget(req.headers(), Utc::now()),
+        *REQUEST_TIMEOUT,
+    ) else {
+        return Ok(next.call(req).await?.map_into_left_body());
+    };
+
+    let request = req.request().clone();
+    let deadline = Instant::now() + budget;
+    let response = if budget.is_zero() {
+        None
+    } else {
+        tokio::time::timeout_at(deadline, scope(Some(deadline), next.call(req)))
+            .await
+            .ok()
+    };
+
+    match response {
+        Some(response) => Ok(response?.map_into_left_body()),
+        None => {
+            log::warn!(
+                "Deadline of {} {} exceeded after {:?}",
+                request.method(),
+                request.path(),
+                budget
+            );
+            let error = AppError::gateway_timeout("Request deadline exceeded");
+            Ok(ServiceResponse::new(request, error.error_response()).map_into_right_body())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue};
+
+    #[test]
+    fn test_caller_may_only_shorten_the_budget() {
+        let now = Utc::now();
+        let name = HeaderName::from_static("x-request-deadline");
+        let mut headers = HeaderMap::new();
+        assert_eq!(requested_budget(&headers, now), None);
+
+        headers.insert(name.clone(), HeaderValue::from_static("2500"));
+        let requested = requested_budget(&headers, now);
+        assert_eq!(requested, Some(Duration::from_millis(2500)));
+        let timeout = Some(Duration::from_secs(30));
+        assert_eq!(budget(requested, timeout), requested);
+        assert_eq!(budget(Some(Duration::from_secs(60)), timeout), timeout);
+        assert_eq!(budget(None, None), None);
+
+        let deadline = (now + chrono::Duration::seconds(3)).to_rfc3339();
+        headers.insert(name.clone(), HeaderValue::from_str(&deadline).unwrap());
+        assert_eq!(
+            requested_budget(&headers, now),
+            Some(Duration::from_secs(3))
+        );
+        let past = (now - chrono::Duration::seconds(3)).to_rfc3339();
+        headers.insert(name.clone(), HeaderValue::from_str(&past).unwrap());
+        assert_eq!(requested_budget(&headers, now), Some(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_operations_get_the_time_left() {
+        assert_eq!(
+            limit(Some(Duration::from_secs(10))),
+            Some(Duration::from_secs(10))
+        );
+        assert!(check().is_ok());
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        scope(Some(deadline), async {
+            let max_time = limit(Some(Duration::from_secs(10))).unwrap();
+            assert!(max_time <= Duration::from_secs(2));
+            assert!(check().is_ok());
+        })
+        .await;
+
+        scope(Some(Instant::now()), async {
+            assert!(check().is_err());
+        })
+        .await;
+    }
+}
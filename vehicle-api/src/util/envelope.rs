@@ -0,0 +1,178 @@
+use actix_web::{
+    body::{self, BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE},
+    middleware, Error, HttpMessage, HttpRequest,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::sync::LazyLock;
+
+use crate::error::AppError;
+
+/// Value of the `Prefer` header asking for enveloped responses
+pub const PREFER_ENVELOPE: &str = "envelope";
+
+/// Value of the `Prefer` header asking for bare responses when envelopes are the default
+pub const PREFER_NO_ENVELOPE: &str = "no-envelope";
+
+// Read once from RESPONSE_ENVELOPE, true envelopes every response unless the client opts out
+static ENVELOPE_BY_DEFAULT: LazyLock<bool> =
+    LazyLock::new(|| std::env::var("RESPONSE_ENVELOPE").as_deref() == Ok("true"));
+
+/// Whether responses are enveloped unless the client opts out
+pub fn is_default() -> bool {
+    *ENVELOPE_BY_DEFAULT
+}
+
+/// Metadata and warnings gathered while a request runs, the envelope of its response
+#[derive(Clone, Debug, Default)]
+pub struct EnvelopeInfo {
+    pub meta: Map<String, Value>,
+    pub warnings: Vec<String>,
+}
+
+/// Whether the response should be enveloped: `Prefer: envelope` or `Prefer: no-envelope`
+/// when sent, the RESPONSE_ENVELOPE default otherwise
+pub fn wants_envelope(headers: &HeaderMap, default: bool) -> bool {
+    headers
+        .get_all("prefer")
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .find_map(|preference| {
+            if preference.eq_ignore_ascii_case(PREFER_ENVELOPE) {
+                Some(true)
+            } else if preference.eq_ignore_ascii_case(PREFER_NO_ENVELOPE) {
+                Some(false)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(default)
+}
+
+/// Whether a response carries a JSON body that can be enveloped
+pub fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+/// Add an entry to `meta`, e.g. the rate limit quota. Kept out of the body when the
+/// response is not enveloped.
+pub fn add_meta(req: &HttpRequest, key: &str, value: impl Serialize) {
+    let Ok(value) = serde_json::to_value(value) else {
+        return;
+    };
+    let mut extensions = req.extensions_mut();
+    if let Some(info) = extensions.get_mut::<EnvelopeInfo>() {
+        info.meta.insert(key.to_string(), value);
+    } else {
+        let mut info = EnvelopeInfo::default();
+        info.meta.insert(key.to_string(), value);
+        extensions.insert(info);
+    }
+}
+
+/// Add a warning to `warnings`, the caller is expected to send it as a `Warning` header too
+pub fn add_warning(req: &HttpRequest, warning: impl Into<String>) {
+    let mut extensions = req.extensions_mut();
+    if let Some(info) = extensions.get_mut::<EnvelopeInfo>() {
+        info.warnings.push(warning.into());
+    } else {
+        extensions.insert(EnvelopeInfo {
+            warnings: vec![warning.into()],
+            ..Default::default()
+        });
+    }
+}
+
+/// Wrap a JSON body as `{ "data": ..., "meta": ..., "warnings": [...] }`, None when it
+/// is not JSON
+pub fn wrap(body: &[u8], info: EnvelopeInfo) -> Option<Vec<u8>> {
+    let data: Value = serde_json::from_slice(body).ok()?;
+    serde_json::to_vec(&json!({
+        "data": data,
+        "meta": info.meta,
+        "warnings": info.warnings,
+    }))
+    .ok()
+}
+
+// Envelope Middleware using from_fn: wraps successful JSON responses once the inner
+// middlewares and the handler have added their metadata and warnings
+pub async fn envelope_middleware(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let enveloped = wants_envelope(req.headers(), *ENVELOPE_BY_DEFAULT);
+    let response = next.call(req).await?;
+    if !enveloped || !response.status().is_success() || !is_json(response.headers()) {
+        return Ok(response.map_into_boxed_body());
+    }
+
+    let (req, response) = response.into_parts();
+    let (mut head, body) = response.into_parts();
+    let bytes = body::to_bytes(body)
+        .await
+        .map_err(|_| AppError::internal_server_error("Failed to read the response body"))?;
+    let info = req
+        .extensions_mut()
+        .remove::<EnvelopeInfo>()
+        .unwrap_or_default();
+    let body = wrap(&bytes, info).unwrap_or_else(|| bytes.to_vec());
+
+    head.headers_mut().remove(CONTENT_LENGTH);
+    head.headers_mut().insert(
+        HeaderName::from_static("preference-applied"),
+        HeaderValue::from_static(PREFER_ENVELOPE),
+    );
+    Ok(ServiceResponse::new(req, head.set_body(BoxBody::new(body))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_envelope_is_opt_in() {
+        let mut headers = HeaderMap::new();
+        assert!(!wants_envelope(&headers, false));
+        assert!(wants_envelope(&headers, true));
+
+        headers.insert(
+            "prefer".parse().unwrap(),
+            HeaderValue::from_static("respond-async, Envelope"),
+        );
+        assert!(wants_envelope(&headers, false));
+        headers.insert(
+            "prefer".parse().unwrap(),
+            HeaderValue::from_static("no-envelope"),
+        );
+        assert!(!wants_envelope(&headers, true));
+    }
+
+    #[test]
+    fn test_wrap_with_meta_and_warnings() {
+        let req = TestRequest::default().to_http_request();
+        add_meta(&req, "quota", json!({ "remaining": 12 }));
+        add_warning(&req, "limit 1000 is out of range");
+        let info = req.extensions_mut().remove::<EnvelopeInfo>().unwrap();
+
+        let body = wrap(br#"[{"brand":"Fiat"}]"#, info).unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["data"][0]["brand"], "Fiat");
+        assert_eq!(body["meta"]["quota"]["remaining"], 12);
+        assert_eq!(body["warnings"][0], "limit 1000 is out of range");
+
+        let body = wrap(b"{}", EnvelopeInfo::default()).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap(),
+            json!({ "data": {}, "meta": {}, "warnings": [] })
+        );
+        assert!(wrap(b"not json", EnvelopeInfo::default()).is_none());
+    }
+}
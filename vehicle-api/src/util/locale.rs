@@ -0,0 +1,99 @@
+use actix_web::http::header::ACCEPT_LANGUAGE;
+use actix_web::HttpRequest;
+
+use crate::models::{LocalizedText, Vehicle, DEFAULT_LANGUAGE};
+
+/// Languages texts may be translated in (SUPPORTED_LANGUAGES, default "en,fr,de")
+pub fn supported_languages() -> Vec<String> {
+    std::env::var("SUPPORTED_LANGUAGES")
+        .unwrap_or_else(|_| "en,fr,de".to_string())
+        .split(',')
+        .map(|language| language.trim().to_lowercase())
+        .filter(|language| !language.is_empty())
+        .collect()
+}
+
+/// Language answered when none of the requested ones is translated (DEFAULT_LOCALE,
+/// default "en")
+pub fn default_language() -> String {
+    std::env::var("DEFAULT_LOCALE")
+        .map(|language| language.trim().to_lowercase())
+        .unwrap_or_else(|_| DEFAULT_LANGUAGE.to_string())
+}
+
+/// Languages of an `Accept-Language` header, most preferred first. `*` and languages
+/// refused with `q=0` are left out.
+pub fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut languages: Vec<(String, f32)> = header
+        .split(',')
+        .filter_map(|part| {
+            let mut params = part.split(';');
+            let language = params.next()?.trim().to_lowercase();
+            let quality = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!language.is_empty() && language != "*" && quality > 0.0)
+                .then_some((language, quality))
+        })
+        .collect();
+    // Stable: languages of the same quality keep the order they were sent in
+    languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+    languages
+        .into_iter()
+        .map(|(language, _)| language)
+        .collect()
+}
+
+/// Languages asked for by the request, None without an `Accept-Language` header to answer
+/// every translation
+pub fn accepted_languages(req: &HttpRequest) -> Option<Vec<String>> {
+    req.headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(parse_accept_language)
+        .filter(|languages| !languages.is_empty())
+}
+
+/// Keep the description translation best matching `languages`, all of them when None
+pub fn localize(vehicle: &mut Vehicle, languages: Option<&[String]>) {
+    if let (Some(description), Some(languages)) = (vehicle.description.as_mut(), languages) {
+        description.narrow(languages, &default_language());
+    }
+}
+
+/// Reject translations in a language that is not supported
+pub fn check_supported(text: &LocalizedText) -> Result<(), String> {
+    let supported = supported_languages();
+    match text
+        .languages()
+        .find(|language| !supported.iter().any(|supported| supported == language))
+    {
+        Some(language) => Err(format!(
+            "Language '{}' is not supported, expected one of: {}",
+            language,
+            supported.join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_language_is_ordered_by_quality() {
+        assert_eq!(
+            parse_accept_language("de;q=0.7, fr-CH, fr;q=0.9, *;q=0.5, it;q=0"),
+            vec!["fr-ch", "fr", "de"]
+        );
+        assert_eq!(parse_accept_language("en"), vec!["en"]);
+        assert!(parse_accept_language("*").is_empty());
+    }
+
+    #[test]
+    fn test_only_supported_languages_are_accepted() {
+        assert!(check_supported(&LocalizedText::single("fr", "Grande autonomie")).is_ok());
+        assert!(check_supported(&LocalizedText::single("xx", "Long range")).is_err());
+    }
+}
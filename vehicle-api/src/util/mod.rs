@@ -1,2 +1,9 @@
+pub mod deadline;
+pub mod envelope;
+pub mod locale;
+pub mod ndjson;
+pub mod pagination;
+pub mod read_only;
 pub mod serde_helpers;
+pub mod timezone;
 pub mod util_serde;
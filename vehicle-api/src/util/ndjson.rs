@@ -0,0 +1,28 @@
+use actix_web::{web::Bytes, HttpResponse};
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::util::util_serde;
+
+/// Stream rows as newline delimited JSON, each line written as soon as its row is read.
+/// The status is already sent when a row fails, the error ends the body early.
+pub fn response<T, S>(rows: S) -> HttpResponse
+where
+    T: Serialize,
+    S: Stream<Item = Result<T, AppError>> + 'static,
+{
+    let body = rows.map(|row| {
+        let row = row.map_err(|error| {
+            log::error!("Streamed response aborted: {}", error);
+            actix_web::Error::from(error)
+        })?;
+        let mut line = serde_json::to_vec(&util_serde::to_value(row))?;
+        line.push(b'\n');
+        Ok::<_, actix_web::Error>(Bytes::from(line))
+    });
+
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(body)
+}
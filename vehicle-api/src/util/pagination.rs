@@ -0,0 +1,256 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderValue, WARNING},
+    middleware, web, Error,
+};
+use bson::{doc, Bson, Document};
+use mongodb::options::FindOptions;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::LazyLock;
+
+use crate::util::envelope;
+
+/// Default and largest page size of every list endpoint
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PageSizeLimits {
+    pub default: i64,
+    pub max: i64,
+}
+
+/// `page` and `limit` query parameters of a list endpoint
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct PageQuery {
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+// Read once from PAGE_SIZE_DEFAULT and PAGE_SIZE_MAX
+static PAGE_SIZE_LIMITS: LazyLock<PageSizeLimits> = LazyLock::new(PageSizeLimits::from_env);
+
+impl PageSizeLimits {
+    /// 20 rows by default, 100 at most
+    pub fn from_env() -> Self {
+        let read = |name: &str, default: i64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse::<i64>().ok())
+                .filter(|value| *value > 0)
+                .unwrap_or(default)
+        };
+        let max = read("PAGE_SIZE_MAX", 100);
+        Self {
+            default: read("PAGE_SIZE_DEFAULT", 20).min(max),
+            max,
+        }
+    }
+
+    /// Page size to use for a requested limit
+    pub fn resolve(&self, requested: Option<i64>) -> i64 {
+        requested.unwrap_or(self.default).clamp(1, self.max)
+    }
+
+    /// Warning for a requested limit out of bounds, None when it is used as is
+    pub fn clamp_warning(&self, requested: i64) -> Option<String> {
+        let limit = self.resolve(Some(requested));
+        (limit != requested).then(|| {
+            format!(
+                "limit {} is out of range, {} rows per page returned (at most {})",
+                requested, limit, self.max
+            )
+        })
+    }
+}
+
+impl PageQuery {
+    pub fn new(page: Option<i64>, limit: Option<i64>) -> Self {
+        Self { page, limit }
+    }
+
+    pub fn page(&self) -> i64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn limit(&self) -> i64 {
+        PAGE_SIZE_LIMITS.resolve(self.limit)
+    }
+
+    pub fn skip(&self) -> u64 {
+        ((self.page() - 1) * self.limit()) as u64
+    }
+
+    /// Set the skip and limit of the page on find options, and `_id` as last sort key
+    /// so documents sharing the sort key keep the same position from page to page
+    pub fn apply(&self, options: &mut FindOptions) {
+        options.skip = Some(self.skip()).filter(|skip| *skip > 0);
+        options.limit = Some(self.limit());
+        options.sort = Some(with_tiebreaker(options.sort.take()));
+    }
+
+    /// Find options of the page, sorted by `_id`
+    pub fn to_find_options(self) -> FindOptions {
+        let mut options = FindOptions::default();
+        self.apply(&mut options);
+        options
+    }
+}
+
+/// Append `_id` to a sort document, in the direction of its last key (ascending when
+/// there is none). A sort already ending on `_id` is left as is.
+pub fn with_tiebreaker(sort: Option<Document>) -> Document {
+    let mut sort = sort.unwrap_or_default();
+    if !sort.contains_key("_id") {
+        let direction = match sort.iter().last().map(|(_, direction)| direction) {
+            Some(Bson::Int32(direction)) if *direction < 0 => -1,
+            Some(Bson::Int64(direction)) if *direction < 0 => -1,
+            _ => 1,
+        };
+        sort.insert("_id", direction);
+    }
+    sort
+}
+
+// Page size warning middleware using from_fn: tells the client its `limit` was clamped,
+// and gives the page served as `meta.page` of enveloped responses
+pub async fn page_size_warning_middleware(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let query = web::Query::<PageQuery>::from_query(req.query_string())
+        .ok()
+        .map(|query| query.into_inner())
+        .filter(|query| query.page.is_some() || query.limit.is_some());
+    let warning = query
+        .and_then(|query| query.limit)
+        .and_then(|limit| PAGE_SIZE_LIMITS.clamp_warning(limit));
+
+    let mut response = next.call(req).await?;
+    if !response.status().is_success() {
+        return Ok(response);
+    }
+    if let Some(query) = query {
+        let page = json!({ "page": query.page(), "limit": query.limit() });
+        envelope::add_meta(response.request(), "page", page);
+    }
+    if let Some(warning) = warning {
+        if let Ok(value) = HeaderValue::from_str(&format!("299 - \"{}\"", warning)) {
+            response.headers_mut().append(WARNING, value);
+        }
+        envelope::add_warning(response.request(), warning);
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_clamp_requested_page_size() {
+        let limits = PageSizeLimits {
+            default: 20,
+            max: 100,
+        };
+
+        assert_eq!(limits.resolve(None), 20);
+        assert_eq!(limits.resolve(Some(50)), 50);
+        assert_eq!(limits.resolve(Some(1000)), 100);
+        assert_eq!(limits.resolve(Some(0)), 1);
+
+        assert!(limits.clamp_warning(100).is_none());
+        assert!(limits
+            .clamp_warning(1000)
+            .unwrap()
+            .contains("100 rows per page"));
+    }
+
+    #[test]
+    fn test_page_find_options() {
+        let options = PageQuery::new(Some(3), Some(10)).to_find_options();
+        assert_eq!(options.skip, Some(20));
+        assert_eq!(options.limit, Some(10));
+
+        let options = PageQuery::default().to_find_options();
+        assert_eq!(options.skip, None);
+        assert_eq!(options.sort, Some(doc! { "_id": 1 }));
+    }
+
+    #[test]
+    fn test_sort_ends_with_id_tiebreaker() {
+        assert_eq!(
+            with_tiebreaker(Some(doc! { "price_by_day": 1, "recorded_at": -1 })),
+            doc! { "price_by_day": 1, "recorded_at": -1, "_id": -1 }
+        );
+        assert_eq!(
+            with_tiebreaker(Some(doc! { "_id": -1, "brand": 1 })),
+            doc! { "_id": -1, "brand": 1 }
+        );
+
+        let mut options = FindOptions::builder()
+            .sort(doc! { "detected_at": -1 })
+            .build();
+        PageQuery::new(Some(2), Some(5)).apply(&mut options);
+        assert_eq!(options.sort, Some(doc! { "detected_at": -1, "_id": -1 }));
+    }
+
+    /// Sort documents the way MongoDB does for the integer and ObjectId keys used here
+    fn sorted(documents: &[Document], sort: &Document) -> Vec<Document> {
+        let mut documents = documents.to_vec();
+        documents.sort_by(|a, b| {
+            sort.iter()
+                .map(|(key, direction)| {
+                    let ordering = match (a.get(key), b.get(key)) {
+                        (Some(Bson::Int32(a)), Some(Bson::Int32(b))) => a.cmp(b),
+                        (Some(Bson::ObjectId(a)), Some(Bson::ObjectId(b))) => a.cmp(b),
+                        _ => std::cmp::Ordering::Equal,
+                    };
+                    if direction.as_i32() == Some(-1) {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        documents
+    }
+
+    fn page_of(documents: &[Document], page: PageQuery) -> Vec<bson::oid::ObjectId> {
+        let mut options = FindOptions::builder().sort(doc! { "price": 1 }).build();
+        page.apply(&mut options);
+        sorted(documents, options.sort.as_ref().unwrap())
+            .into_iter()
+            .skip(options.skip.unwrap_or(0) as usize)
+            .take(options.limit.unwrap() as usize)
+            .map(|document| document.get_object_id("_id").unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_pages_are_stable_across_inserts() {
+        // Every document shares the sort key, only the tiebreaker orders them
+        let mut documents: Vec<Document> = (0..6)
+            .map(|_| doc! { "_id": bson::oid::ObjectId::new(), "price": 50 })
+            .collect();
+        // Stored out of insertion order, as after updates or a restore
+        documents.reverse();
+
+        let first = page_of(&documents, PageQuery::new(Some(1), Some(3)));
+        documents.push(doc! { "_id": bson::oid::ObjectId::new(), "price": 50 });
+        let second = page_of(&documents, PageQuery::new(Some(2), Some(3)));
+
+        assert_eq!(page_of(&documents, PageQuery::new(Some(1), Some(3))), first);
+        assert!(second.iter().all(|id| !first.contains(id)));
+
+        let mut seen = [first, second].concat();
+        seen.sort();
+        let mut original: Vec<_> = documents[..6]
+            .iter()
+            .map(|document| document.get_object_id("_id").unwrap())
+            .collect();
+        original.sort();
+        assert_eq!(seen, original);
+    }
+}
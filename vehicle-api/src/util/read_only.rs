@@ -0,0 +1,79 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::Method,
+    middleware, Error, ResponseError,
+};
+use std::sync::LazyLock;
+
+use crate::error::AppError;
+
+/// Seconds clients are told to wait before retrying a rejected write
+const RETRY_AFTER_SECS: u64 = 300;
+
+// Read once from READ_ONLY, true when serving from a disaster recovery region against a
+// MongoDB secondary
+static READ_ONLY: LazyLock<bool> =
+    LazyLock::new(|| std::env::var("READ_ONLY").as_deref() == Ok("true"));
+
+/// POST routes that only check or preview, still served by a read-only instance
+const PREVIEW_ROUTES: [&str; 1] = ["/protected/bookings/validate"];
+
+/// Whether this instance refuses every write
+pub fn is_enabled() -> bool {
+    *READ_ONLY
+}
+
+/// Whether a request may change data: anything but GET, HEAD and OPTIONS, previews aside
+pub fn is_mutating(method: &Method, path: &str) -> bool {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return false;
+    }
+    let preview = PREVIEW_ROUTES.contains(&path) || path.ends_with("/restore-dry-run");
+    !(*method == Method::POST && preview)
+}
+
+/// Error answered to writes, READ_ONLY_MESSAGE tells clients where to go instead
+fn read_only_error() -> AppError {
+    let message = std::env::var("READ_ONLY_MESSAGE").unwrap_or_else(|_| {
+        "This instance is read-only (disaster recovery), changes are not accepted".to_string()
+    });
+    AppError::service_unavailable(message, RETRY_AFTER_SECS)
+}
+
+// Read-only Middleware using from_fn, only registered when READ_ONLY=true
+pub async fn read_only_middleware(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    if !is_mutating(req.method(), req.path()) {
+        return Ok(next.call(req).await?.map_into_left_body());
+    }
+
+    log::warn!("Read-only mode, refused {} {}", req.method(), req.path());
+    let error = read_only_error();
+    Ok(req
+        .into_response(error.error_response())
+        .map_into_right_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_reads_and_previews_are_served() {
+        assert!(!is_mutating(&Method::GET, "/protected/bookings"));
+        assert!(!is_mutating(&Method::OPTIONS, "/protected/bookings"));
+        assert!(!is_mutating(&Method::POST, "/protected/bookings/validate"));
+        assert!(!is_mutating(
+            &Method::POST,
+            "/protected/admin/backups/66b1f0c2a1b2c3d4e5f60718/restore-dry-run"
+        ));
+
+        assert!(is_mutating(&Method::POST, "/protected/bookings"));
+        assert!(is_mutating(&Method::PATCH, "/protected/bookings/validate"));
+        assert!(is_mutating(&Method::DELETE, "/protected/api-keys/key_1"));
+        assert!(is_mutating(&Method::POST, "/webhooks/stripe"));
+    }
+}
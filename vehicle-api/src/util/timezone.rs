@@ -0,0 +1,68 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Parse an IANA time zone name (e.g. "Europe/Paris")
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.parse::<Tz>()
+        .map_err(|_| format!("Unknown time zone '{}'.", name))
+}
+
+/// Current date at the given location
+pub fn today_in(tz: Tz) -> NaiveDate {
+    Utc::now().with_timezone(&tz).date_naive()
+}
+
+/// UTC instant at which a local day starts.
+/// When midnight does not exist (DST gap) the first existing instant of the day is used.
+pub fn start_of_day(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+
+    (0..=2)
+        .find_map(|hours| {
+            tz.from_local_datetime(&(midnight + Duration::hours(hours)))
+                .earliest()
+        })
+        .map(|local| local.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+/// Canonical UTC boundaries of a booking: start of `from_date` to end of `to_date` (included)
+pub fn booking_bounds(
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    tz: Tz,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    (
+        start_of_day(from_date, tz),
+        start_of_day(to_date + Duration::days(1), tz),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_booking_bounds_are_local_days() {
+        let tz = parse_timezone("Europe/Paris").unwrap();
+        let (starts_at, ends_at) = booking_bounds(date(2025, 8, 1), date(2025, 8, 10), tz);
+
+        // Paris is UTC+2 in summer
+        assert_eq!(starts_at.to_rfc3339(), "2025-07-31T22:00:00+00:00");
+        assert_eq!(ends_at.to_rfc3339(), "2025-08-10T22:00:00+00:00");
+    }
+
+    #[test]
+    fn test_start_of_day_in_dst_gap() {
+        // Midnight does not exist on this day in Santiago (clocks jump to 01:00)
+        let tz = parse_timezone("America/Santiago").unwrap();
+        let start = start_of_day(date(2024, 9, 8), tz);
+
+        assert_eq!(start.to_rfc3339(), "2024-09-08T04:00:00+00:00");
+        assert!(parse_timezone("Mars/Olympus").is_err());
+    }
+}
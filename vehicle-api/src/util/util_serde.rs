@@ -19,6 +19,7 @@ fn bson_to_value(value: Bson) -> Value {
             json!(s)
         }
         Bson::Double(f) => json!((f * 100.0).round() / 100.0),
+        Bson::Decimal128(d) => json!(d.to_string()),
         Bson::String(s) => json!(s),
         Bson::Array(arr) => Value::Array(arr.into_iter().map(bson_to_value).collect()),
         Bson::Document(arr) => Value::Object(
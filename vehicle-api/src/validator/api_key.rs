@@ -0,0 +1,42 @@
+use crate::authentication::identity::{Identity, Role};
+use crate::models::{
+    parse_network, CreateApiKeyRequest, RevokeApiKeyRequest, RotateApiKeyRequest,
+    UpdateAllowedNetworksRequest,
+};
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for CreateApiKeyRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self.user_id.trim().is_empty() {
+            return Err("user_id cannot be blank.".to_string());
+        }
+        if self.role == Role::Service {
+            return Err("The Service role is reserved to service accounts.".to_string());
+        }
+        validate_networks(&self.allowed_networks)
+    }
+}
+
+impl CustomValidateTrait for RotateApiKeyRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl CustomValidateTrait for UpdateAllowedNetworksRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        validate_networks(&self.allowed_networks)
+    }
+}
+
+impl CustomValidateTrait for RevokeApiKeyRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        self.key_hash().map(|_| ())
+    }
+}
+
+fn validate_networks(networks: &[String]) -> Result<(), String> {
+    networks
+        .iter()
+        .try_for_each(|network| parse_network(network).map(|_| ()))
+}
@@ -1,26 +1,127 @@
-use crate::authentication::identity::{Identity, Role};
+use bson::doc;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use validator::Validate;
+
+use crate::authentication::{
+    self,
+    identity::{Identity, Role},
+};
 use crate::error::{AppError, AppResult};
-use crate::models::{Booking, BookingStatus, CreateBookingRequest, UpdateBookingRequest};
+use crate::models::{
+    exclude_deleted, Booking, BookingIssue, BookingIssueCode, BookingPolicy, BookingRules,
+    BookingStatus, BookingValidationReport, BulkUpdateBookingRequest, ConditionStage,
+    CreateBookingRequest, Decimal, DriverDetails, HandoverRequest, Holiday, Money, OfficeHours,
+    OutsideHoursAction, RuleEffect, Station, UpdateBookingRequest, Vehicle, VehicleStatus,
+    VehicleType, MAX_BULK_BOOKINGS,
+};
+use crate::services;
+use crate::services::holidays;
 use crate::services::mongodb::booking;
+use crate::services::mongodb::MongoStruct;
+use crate::util::timezone;
+use crate::validator::metrics;
+use crate::validator::CustomValidateTrait;
+
+/// Rentals longer than this (in days) get a pricing warning in the pre-check
+const LONG_RENTAL_DAYS: i64 = 30;
+
+// Request names of the validation failure metrics
+const CREATE_REQUEST: &str = "CreateBookingRequest";
+const UPDATE_REQUEST: &str = "UpdateBookingRequest";
 
 /// Validate booking creation request
-/// Checks date range and vehicle availability (overlap conflicts)
+/// Checks the vehicle is in service and its station open, the date range and vehicle
+/// availability (overlap conflicts), then the customer's quota
 pub async fn validate_booking_creation(
-    _identity: &Identity,
+    identity: &Identity,
     request: &CreateBookingRequest,
+) -> AppResult<()> {
+    // A missing vehicle is reported by the creation itself
+    let mut vehicle_filter = doc! { "_id": request.vehicle_id };
+    exclude_deleted(&mut vehicle_filter);
+    let vehicle: Option<Vehicle> = services::mongodb::get_one(vehicle_filter, None).await?;
+    if let Some(vehicle) = &vehicle {
+        let mut issues: Vec<BookingIssue> = check_vehicle_status(vehicle).into_iter().collect();
+        if let Some(station) = services::station::for_vehicle(vehicle).await? {
+            issues.extend(check_station_hours(
+                request.from_date,
+                request.to_date,
+                &station,
+                Utc::now(),
+            ));
+        }
+        if let Some(issue) = issues.into_iter().next() {
+            return Err(AppError::bad_request(metrics::reject_issue(
+                CREATE_REQUEST,
+                issue,
+            )));
+        }
+    }
+
+    let rules = booking::get_booking_rules().await?;
+    validate_booking_request(identity, request, &rules)
+        .await
+        .map_err(AppError::bad_request)?;
+
+    match check_quota(identity, &rules).await? {
+        Some(issue) => Err(AppError::forbidden(metrics::reject_issue(
+            CREATE_REQUEST,
+            issue,
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Checks of the request itself, any failure is a bad request
+async fn validate_booking_request(
+    identity: &Identity,
+    request: &CreateBookingRequest,
+    rules: &BookingRules,
 ) -> Result<(), String> {
+    if let Some(issue) = check_customer(identity) {
+        return Err(metrics::reject_issue(CREATE_REQUEST, issue));
+    }
+
     // Validate date range
     if request.from_date >= request.to_date {
-        return Err("from_date must be before to_date".to_string());
+        return Err(metrics::reject(
+            CREATE_REQUEST,
+            "to_date",
+            "invalid_date_range",
+            "from_date must be before to_date",
+        ));
+    }
+
+    if let Some(driver) = &request.driver {
+        validate_driver(driver)
+            .map_err(|message| metrics::reject(CREATE_REQUEST, "driver", "driver", message))?;
+    }
+
+    if let Some(issue) = check_booking_rules(request, rules)
+        .await?
+        .into_iter()
+        .next()
+    {
+        return Err(metrics::reject_issue(CREATE_REQUEST, issue));
     }
 
     // Check for overlapping bookings
-    match booking::has_overlapping_bookings(request.vehicle_id, request.from_date, request.to_date)
-        .await
+    match booking::has_overlapping_bookings(
+        request.vehicle_id,
+        request.from_date,
+        request.to_date,
+        None,
+    )
+    .await
     {
         Ok(has_overlap) => {
             if has_overlap {
-                return Err("Vehicle is already booked for overlapping dates.".to_string());
+                return Err(metrics::reject(
+                    CREATE_REQUEST,
+                    "",
+                    "unavailable",
+                    "Vehicle is already booked for overlapping dates.",
+                ));
             }
         }
         Err(_) => {
@@ -31,35 +132,482 @@ pub async fn validate_booking_creation(
     Ok(())
 }
 
+/// Driver details must be sent in clear, non blank and at most 200 characters
+pub fn validate_driver(driver: &DriverDetails) -> Result<(), String> {
+    let fields = [
+        ("license_number", Some(&driver.license_number)),
+        ("phone", driver.phone.as_ref()),
+        ("address", driver.address.as_ref()),
+    ];
+    for (name, value) in fields {
+        let Some(value) = value else { continue };
+        match value.plain() {
+            Some(plain) if !plain.trim().is_empty() && plain.chars().count() <= 200 => {}
+            _ => {
+                return Err(format!(
+                    "driver.{} must be a non blank string of at most 200 characters",
+                    name
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Pre-check a prospective booking, collecting every problem instead of stopping at the first one
+pub async fn precheck_booking(
+    identity: &Identity,
+    request: &CreateBookingRequest,
+) -> AppResult<BookingValidationReport> {
+    let mut vehicle_filter = doc! { "_id": request.vehicle_id };
+    exclude_deleted(&mut vehicle_filter);
+    let vehicle: Option<Vehicle> = services::mongodb::get_one(vehicle_filter, None).await?;
+
+    // "Today" is evaluated at the vehicle's location
+    let today = vehicle
+        .as_ref()
+        .and_then(|vehicle| timezone::parse_timezone(&vehicle.timezone).ok())
+        .map(timezone::today_in)
+        .unwrap_or_else(|| Utc::now().date_naive());
+    let (mut errors, mut warnings) = check_booking_dates(request, today);
+    errors.extend(check_customer(identity));
+    let rules = booking::get_booking_rules().await?;
+    if errors.is_empty() {
+        errors.extend(
+            check_booking_rules(request, &rules)
+                .await
+                .map_err(AppError::internal_server_error)?,
+        );
+    }
+    errors.extend(check_quota(identity, &rules).await?);
+
+    let estimated_price = match vehicle {
+        None => {
+            errors.push(BookingIssue::new(
+                BookingIssueCode::VehicleNotFound,
+                Some("vehicle_id"),
+                "Vehicle not found.",
+            ));
+            None
+        }
+        Some(vehicle) => {
+            errors.extend(check_vehicle_status(&vehicle));
+            if let Some(station) = services::station::for_vehicle(&vehicle).await? {
+                errors.extend(check_station_hours(
+                    request.from_date,
+                    request.to_date,
+                    &station,
+                    Utc::now(),
+                ));
+            }
+
+            // Availability only makes sense for a valid date range
+            if errors.is_empty()
+                && booking::has_overlapping_bookings(
+                    request.vehicle_id,
+                    request.from_date,
+                    request.to_date,
+                    None,
+                )
+                .await?
+            {
+                errors.push(BookingIssue::new(
+                    BookingIssueCode::Unavailable,
+                    None,
+                    "Vehicle is already booked for overlapping dates.",
+                ));
+            }
+
+            let calendar = if request.from_date < request.to_date {
+                holidays::get_calendar(
+                    &holidays::vehicle_country(&vehicle),
+                    request.from_date,
+                    request.to_date,
+                )
+                .await?
+            } else {
+                Vec::new()
+            };
+            errors.extend(check_blackout_dates(
+                request.from_date,
+                request.to_date,
+                &calendar,
+            ));
+
+            // Outside office hours the booking is refused or waits for a manager
+            if let (Some(hours), Ok(tz)) = (
+                services::office_hours::for_vehicle(&vehicle).await?,
+                timezone::parse_timezone(&vehicle.timezone),
+            ) {
+                let now = Utc::now().with_timezone(&tz).naive_local();
+                if let Some(issue) = check_office_hours(request.from_date, now, &hours) {
+                    match hours.outside_hours {
+                        OutsideHoursAction::Reject => errors.push(issue),
+                        OutsideHoursAction::Flag => warnings.push(issue),
+                    }
+                }
+            }
+
+            let (price, pricing_warnings) =
+                estimate_price(request.from_date, request.to_date, &vehicle, &calendar);
+            warnings.extend(pricing_warnings);
+            price
+        }
+    };
+
+    Ok(BookingValidationReport::new(
+        errors,
+        warnings,
+        estimated_price,
+    ))
+}
+
+/// Customers must have a verified profile when REQUIRE_VERIFIED_CUSTOMERS is set,
+/// staff booking on behalf of a customer are not concerned
+fn check_customer(identity: &Identity) -> Option<BookingIssue> {
+    (identity.role == Role::Customer
+        && authentication::profile::verification_required()
+        && !identity.is_verified_customer())
+    .then(|| {
+        BookingIssue::new(
+            BookingIssueCode::UnverifiedCustomer,
+            None,
+            "Only verified customers can create bookings.",
+        )
+    })
+}
+
+/// Configured limits on notice, rental length and horizon, see `BookingRules`
+async fn check_booking_rules(
+    request: &CreateBookingRequest,
+    rules: &BookingRules,
+) -> Result<Vec<BookingIssue>, String> {
+    if !rules.limits_dates() {
+        return Ok(Vec::new());
+    }
+
+    let vehicle: Option<Vehicle> =
+        services::mongodb::get_one(doc! { "_id": request.vehicle_id }, None)
+            .await
+            .map_err(|_| "Failed to load the vehicle.".to_string())?;
+    let tz = vehicle
+        .and_then(|vehicle| timezone::parse_timezone(&vehicle.timezone).ok())
+        .unwrap_or(chrono_tz::UTC);
+    Ok(rules.check(request.from_date, request.to_date, Utc::now(), tz))
+}
+
+/// Customers may hold at most `max_active_bookings` pending or confirmed bookings not
+/// over yet, staff booking on behalf of a customer are not concerned
+async fn check_quota(identity: &Identity, rules: &BookingRules) -> AppResult<Option<BookingIssue>> {
+    let Some(max) = rules.max_active_bookings else {
+        return Ok(None);
+    };
+    if identity.role != Role::Customer {
+        return Ok(None);
+    }
+
+    let filter = doc! {
+        "customer_id": &identity.user_id,
+        "status": { "$in": ["PENDING", "CONFIRMED"] },
+        "to_date": { "$gte": Utc::now().date_naive().to_string() },
+    };
+    let active = services::mongodb::count(Booking::get_collection(), filter, None).await?;
+    Ok((active >= max).then(|| {
+        BookingIssue::new(
+            BookingIssueCode::QuotaExceeded,
+            None,
+            format!(
+                "You already have {} pending or confirmed bookings, the most allowed at once. \
+                 Cancel one or wait until one is over before booking again.",
+                active
+            ),
+        )
+    }))
+}
+
+/// Date checks of the pre-check, returns (errors, warnings)
+fn check_booking_dates(
+    request: &CreateBookingRequest,
+    today: NaiveDate,
+) -> (Vec<BookingIssue>, Vec<BookingIssue>) {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    if request.from_date >= request.to_date {
+        errors.push(BookingIssue::new(
+            BookingIssueCode::InvalidDateRange,
+            Some("to_date"),
+            "from_date must be before to_date",
+        ));
+    }
+    if request.from_date < today {
+        warnings.push(BookingIssue::new(
+            BookingIssueCode::StartInPast,
+            Some("from_date"),
+            "Booking starts in the past.",
+        ));
+    }
+
+    (errors, warnings)
+}
+
+/// Only AVAILABLE vehicles can be booked, not those in maintenance or retired
+pub fn check_vehicle_status(vehicle: &Vehicle) -> Option<BookingIssue> {
+    (vehicle.status != VehicleStatus::Available).then(|| {
+        BookingIssue::new(
+            BookingIssueCode::OutOfService,
+            Some("vehicle_id"),
+            format!(
+                "Vehicle is out of service ({}) and cannot be booked.",
+                vehicle.status
+            ),
+        )
+    })
+}
+
+/// The vehicle's station must be open for the pickup on `from_date` (and not closed yet
+/// when it is today) and for the return on `to_date`
+pub fn check_station_hours(
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    station: &Station,
+    now: DateTime<Utc>,
+) -> Vec<BookingIssue> {
+    let mut issues = Vec::new();
+    if !station.allows_pickup(from_date, now) {
+        issues.push(BookingIssue::new(
+            BookingIssueCode::StationClosed,
+            Some("from_date"),
+            format!(
+                "Station {} is closed for a pickup on {}.",
+                station.name, from_date
+            ),
+        ));
+    }
+    if !station.allows_return(to_date) {
+        issues.push(BookingIssue::new(
+            BookingIssueCode::StationClosed,
+            Some("to_date"),
+            format!(
+                "Station {} is closed for a return on {}.",
+                station.name, to_date
+            ),
+        ));
+    }
+    issues
+}
+
+/// Pickup and return are not possible on blackout holidays
+pub fn check_blackout_dates(
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    calendar: &[Holiday],
+) -> Vec<BookingIssue> {
+    [("from_date", from_date), ("to_date", to_date)]
+        .into_iter()
+        .filter_map(|(field, date)| {
+            calendar
+                .iter()
+                .find(|holiday| holiday.blackout && holiday.date == date)
+                .map(|holiday| {
+                    BookingIssue::new(
+                        BookingIssueCode::HolidayBlackout,
+                        Some(field),
+                        format!(
+                            "Branch is closed on {} ({}), pick another {}.",
+                            date, holiday.name, field
+                        ),
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Pickup is only possible on a day the branch is staffed, not after it closed today
+pub fn check_office_hours(
+    from_date: NaiveDate,
+    now: NaiveDateTime,
+    hours: &OfficeHours,
+) -> Option<BookingIssue> {
+    (!hours.allows_pickup(from_date, now)).then(|| {
+        BookingIssue::new(
+            BookingIssueCode::OutsideOfficeHours,
+            Some("from_date"),
+            format!("Branch is not staffed for a pickup on {}.", from_date),
+        )
+    })
+}
+
+/// Estimated total price (holiday surcharges included) and pricing warnings,
+/// None for an invalid date range
+pub fn estimate_price(
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    vehicle: &Vehicle,
+    calendar: &[Holiday],
+) -> (Option<Money>, Vec<BookingIssue>) {
+    let days = (to_date - from_date).num_days();
+    if days <= 0 {
+        return (None, Vec::new());
+    }
+
+    let mut warnings = Vec::new();
+    if days > LONG_RENTAL_DAYS {
+        warnings.push(BookingIssue::new(
+            BookingIssueCode::LongRental,
+            None,
+            format!(
+                "Rentals longer than {} days may need manual approval.",
+                LONG_RENTAL_DAYS
+            ),
+        ));
+    }
+
+    // In the vehicle's currency, exact until rounded
+    let mut price = Money::zero(vehicle.price_by_day.currency);
+    let mut surcharged = Vec::new();
+    for date in from_date.iter_days().take(days as usize) {
+        match calendar.iter().find(|holiday| holiday.date == date) {
+            Some(holiday) => {
+                let surcharge = Decimal::try_from(holiday.surcharge_percent).unwrap_or_default();
+                price.amount +=
+                    vehicle.price_by_day.amount * (Decimal::ONE + surcharge / Decimal::ONE_HUNDRED);
+                surcharged.push(holiday.name.clone());
+            }
+            None => price.amount += vehicle.price_by_day.amount,
+        }
+    }
+
+    if !surcharged.is_empty() {
+        warnings.push(BookingIssue::new(
+            BookingIssueCode::HolidaySurcharge,
+            None,
+            format!("Holiday surcharge applies: {}.", surcharged.join(", ")),
+        ));
+    }
+
+    // Stored on the booking, rounded to the cent
+    (Some(price.round()), warnings)
+}
+
 /// Check if user has permission to update this booking and validate the update
 pub fn validate_update_booking(
     identity: &Identity,
     booking: &Booking,
     request: &UpdateBookingRequest,
+    policy: &BookingPolicy,
 ) -> AppResult<()> {
     // Check general update permission
     check_booking_update_permission(identity, booking)?;
 
-    // If status change is requested, validate it based on role
+    // If status change is requested, validate it against the transition policy
     if let Some(ref new_status) = request.status {
-        match identity.role {
-            Role::Customer => {
-                validate_customer_status_change(&booking.status, new_status)?;
-            }
-            Role::Admin | Role::CarManager | Role::MotorbikeManager => {
-                validate_non_customer_status_change(&booking.status, new_status)?;
-            }
+        validate_status_transition(policy, &identity.role, &booking.status, new_status)?;
+    }
+    check_paid_before_confirm(booking, request, services::payments::stripe::is_enabled())?;
+
+    if let Some((from_date, to_date)) = requested_dates(booking, request) {
+        if booking.status != BookingStatus::Pending {
+            return Err(AppError::bad_request(metrics::reject(
+                UPDATE_REQUEST,
+                "",
+                "dates_locked",
+                "Dates can only be changed while the booking is pending",
+            )));
+        }
+        if from_date >= to_date {
+            return Err(AppError::bad_request(metrics::reject(
+                UPDATE_REQUEST,
+                "to_date",
+                "invalid_date_range",
+                "from_date must be before to_date",
+            )));
         }
     }
 
     Ok(())
 }
 
+/// With payments enabled, a booking is confirmed once its customer paid it
+pub fn check_paid_before_confirm(
+    booking: &Booking,
+    request: &UpdateBookingRequest,
+    payments_enabled: bool,
+) -> AppResult<()> {
+    let confirms = request.status == Some(BookingStatus::Confirmed)
+        && booking.status != BookingStatus::Confirmed;
+    if confirms && payments_enabled && booking.paid_at.is_none() {
+        return Err(AppError::bad_request(metrics::reject(
+            UPDATE_REQUEST,
+            "status",
+            "unpaid",
+            "Booking must be paid before it is confirmed",
+        )));
+    }
+    Ok(())
+}
+
+/// A booking confirmed by an auto-confirm rule passes the checks of the manager of its
+/// vehicle type confirming it: the transition policy, then the payment
+pub fn check_auto_confirm(
+    policy: &BookingPolicy,
+    vehicle_type: VehicleType,
+    booking: &Booking,
+    payments_enabled: bool,
+) -> AppResult<()> {
+    let confirm = UpdateBookingRequest {
+        status: Some(BookingStatus::Confirmed),
+        from_date: None,
+        to_date: None,
+    };
+    validate_status_transition(
+        policy,
+        &vehicle_type.manager(),
+        &booking.status,
+        &BookingStatus::Confirmed,
+    )?;
+    check_paid_before_confirm(booking, &confirm, payments_enabled)
+}
+
+/// New date range of an update, the booking's own date filling the side not sent.
+/// None when the update keeps the dates.
+pub fn requested_dates(
+    booking: &Booking,
+    request: &UpdateBookingRequest,
+) -> Option<(NaiveDate, NaiveDate)> {
+    if request.from_date.is_none() && request.to_date.is_none() {
+        return None;
+    }
+    Some((
+        request.from_date.unwrap_or(booking.from_date),
+        request.to_date.unwrap_or(booking.to_date),
+    ))
+}
+
+/// The vehicle must be free on the new dates, other bookings than this one considered
+pub async fn validate_date_change(
+    booking: &Booking,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+) -> AppResult<()> {
+    if booking::has_overlapping_bookings(booking.vehicle_id, from_date, to_date, booking.id).await?
+    {
+        return Err(AppError::bad_request(metrics::reject(
+            UPDATE_REQUEST,
+            "",
+            "unavailable",
+            "Vehicle is already booked for overlapping dates.",
+        )));
+    }
+    Ok(())
+}
+
 /// Check if user has permission to update this booking
 pub fn check_booking_update_permission(identity: &Identity, booking: &Booking) -> AppResult<()> {
     match identity.role {
         Role::Admin => Ok(()), // Admin can update any booking
         Role::CarManager | Role::MotorbikeManager => Ok(()), // Managers can update any booking
+        Role::Service => Ok(()), // Route grants decide for service accounts
         Role::Customer => {
             // Customers can only update their own bookings
             if booking.customer_id == identity.user_id {
@@ -76,8 +624,8 @@ pub fn check_booking_update_permission(identity: &Identity, booking: &Booking) -
 /// Check if user has permission to view this booking
 pub fn check_booking_view_permission(identity: &Identity, booking: &Booking) -> AppResult<()> {
     match identity.role {
-        Role::Admin | Role::CarManager | Role::MotorbikeManager => {
-            // Managers and Admin can see all bookings
+        Role::Admin | Role::CarManager | Role::MotorbikeManager | Role::Service => {
+            // Managers, Admin and service accounts can see all bookings
             Ok(())
         }
         Role::Customer => {
@@ -91,57 +639,354 @@ pub fn check_booking_view_permission(identity: &Identity, booking: &Booking) ->
     }
 }
 
-/// Validate that customers can only cancel bookings if status is PENDING or CONFIRMED
-fn validate_customer_status_change(
-    current_status: &BookingStatus,
-    new_status: &BookingStatus,
+impl CustomValidateTrait for BulkUpdateBookingRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self.booking_ids.is_empty() {
+            return Err("booking_ids cannot be empty.".to_string());
+        }
+        if self.booking_ids.len() > MAX_BULK_BOOKINGS {
+            return Err(format!(
+                "At most {} bookings can be updated at once.",
+                MAX_BULK_BOOKINGS
+            ));
+        }
+        // A booking listed twice would get two items and be updated twice
+        for (index, booking_id) in self.booking_ids.iter().enumerate() {
+            if self.booking_ids[..index].contains(booking_id) {
+                return Err(format!("Booking {} is listed twice.", booking_id.to_hex()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check a check-in or check-out of a confirmed booking. The vehicle is handed over
+/// during the booked period; it may come back late, never before it was handed over.
+/// Odometers only go up: a check-in reads at least the vehicle's last returned reading.
+pub fn validate_handover(
+    booking: &Booking,
+    stage: ConditionStage,
+    request: &HandoverRequest,
+    last_odometer_km: Option<u32>,
+    now: DateTime<Utc>,
 ) -> AppResult<()> {
-    match new_status {
-        BookingStatus::Cancelled(_) => {
-            // Customers can cancel only if current status is PENDING or CONFIRMED
-            match current_status {
-                BookingStatus::Pending | BookingStatus::Confirmed => Ok(()),
-                _ => Err(AppError::forbidden(
-                    "You can only cancel bookings that are pending or confirmed.",
-                )),
+    Validate::validate(request).map_err(|e| AppError::bad_request(e.to_string()))?;
+    if booking.status != BookingStatus::Confirmed {
+        return Err(AppError::bad_request(
+            "Check-in and check-out are only possible for confirmed bookings",
+        ));
+    }
+
+    match stage {
+        ConditionStage::CheckIn => {
+            if booking.check_in.is_some() {
+                return Err(AppError::conflict("Booking already checked in"));
+            }
+            let (starts_at, ends_at) = booked_window(booking)?;
+            if now < starts_at || now >= ends_at {
+                return Err(AppError::bad_request(
+                    "Check-in is only possible during the booked period",
+                ));
+            }
+            if let Some(last_odometer_km) =
+                last_odometer_km.filter(|last| request.odometer_km < *last)
+            {
+                return Err(AppError::bad_request(format!(
+                    "Odometer reading below the vehicle's last one ({} km)",
+                    last_odometer_km
+                )));
             }
         }
-        _ => {
-            // Customers can only cancel bookings, not change to other statuses
-            Err(AppError::forbidden(
-                "Customers can only cancel their bookings.",
-            ))
+        ConditionStage::CheckOut => {
+            let Some(check_in) = &booking.check_in else {
+                return Err(AppError::bad_request("Booking not checked in yet"));
+            };
+            if booking.check_out.is_some() {
+                return Err(AppError::conflict("Booking already checked out"));
+            }
+            if request.odometer_km < check_in.odometer_km {
+                return Err(AppError::bad_request(format!(
+                    "Odometer reading below the check-in one ({} km)",
+                    check_in.odometer_km
+                )));
+            }
         }
     }
+    Ok(())
 }
 
-/// Validate that non-customers (Admin, Managers) can only use CONFIRMED/REJECTED statuses
-fn validate_non_customer_status_change(
+/// UTC instants the booking starts and ends at, computed for bookings stored without them
+pub fn booked_window(booking: &Booking) -> AppResult<(DateTime<Utc>, DateTime<Utc>)> {
+    if let (Some(starts_at), Some(ends_at)) = (booking.starts_at, booking.ends_at) {
+        return Ok((starts_at, ends_at));
+    }
+    let tz =
+        timezone::parse_timezone(&booking.timezone).map_err(AppError::internal_server_error)?;
+    Ok(timezone::booking_bounds(
+        booking.from_date,
+        booking.to_date,
+        tz,
+    ))
+}
+
+/// Evaluate a status transition against the booking policy (first matching rule wins)
+pub fn validate_status_transition(
+    policy: &BookingPolicy,
+    role: &Role,
     current_status: &BookingStatus,
     new_status: &BookingStatus,
 ) -> AppResult<()> {
-    match new_status {
-        BookingStatus::Cancelled(_) => Err(AppError::forbidden(
-            "Only customers can cancel bookings. Use reject status instead.",
-        )),
-        BookingStatus::Pending => Err(AppError::forbidden("Cannot change status back to pending.")),
-        BookingStatus::Confirmed | BookingStatus::Rejected(_) => {
-            // Additional business logic for admin/manager status changes
-            match current_status {
-                BookingStatus::Pending => Ok(()), // Can confirm or reject pending bookings
-                BookingStatus::Confirmed => {
-                    // Can only reject confirmed bookings
-                    match new_status {
-                        BookingStatus::Rejected(_) => Ok(()),
-                        _ => Err(AppError::bad_request(
-                            "Confirmed bookings can only be rejected.",
-                        )),
-                    }
-                }
-                BookingStatus::Rejected(_) | BookingStatus::Cancelled(_) => Err(
-                    AppError::bad_request("Cannot modify rejected or cancelled bookings."),
-                ),
-            }
+    match policy.find_rule(role, current_status, new_status) {
+        Some(rule) => match &rule.effect {
+            RuleEffect::Allow => Ok(()),
+            RuleEffect::Forbidden(message) => Err(AppError::forbidden(message)),
+            RuleEffect::BadRequest(message) => Err(AppError::bad_request(message)),
+        },
+        None => Err(AppError::forbidden(format!(
+            "Status transition from {} to {} is not allowed.",
+            current_status, new_status
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures::date;
+    use crate::models::{Currency, Handover, HolidaySource};
+    use bson::oid::ObjectId;
+
+    fn request(from_date: NaiveDate, to_date: NaiveDate) -> CreateBookingRequest {
+        CreateBookingRequest {
+            vehicle_id: ObjectId::new(),
+            from_date,
+            to_date,
+            driver: None,
         }
     }
+
+    #[test]
+    fn test_check_booking_dates_reports_all_problems() {
+        let (errors, warnings) = check_booking_dates(&request(date(8, 10), date(8, 1)), date(9, 1));
+
+        assert_eq!(errors[0].code, BookingIssueCode::InvalidDateRange);
+        assert_eq!(warnings[0].code, BookingIssueCode::StartInPast);
+
+        let (errors, warnings) = check_booking_dates(&request(date(8, 1), date(8, 10)), date(7, 1));
+        assert!(errors.is_empty() && warnings.is_empty());
+    }
+
+    #[test]
+    fn test_requested_dates_keep_the_side_not_sent() {
+        let booking = Booking::new(request(date(8, 1), date(8, 10)), "customer_user_1".into());
+        let update = |from_date, to_date| UpdateBookingRequest {
+            status: None,
+            from_date,
+            to_date,
+        };
+
+        assert_eq!(requested_dates(&booking, &update(None, None)), None);
+        assert_eq!(
+            requested_dates(&booking, &update(None, Some(date(8, 12)))),
+            Some((date(8, 1), date(8, 12)))
+        );
+        assert_eq!(
+            requested_dates(&booking, &update(Some(date(8, 3)), Some(date(8, 5)))),
+            Some((date(8, 3), date(8, 5)))
+        );
+    }
+
+    #[test]
+    fn test_only_paid_bookings_are_confirmed() {
+        let mut booking = Booking::new(request(date(8, 1), date(8, 10)), "customer_user_1".into());
+        let confirm = UpdateBookingRequest {
+            status: Some(BookingStatus::Confirmed),
+            from_date: None,
+            to_date: None,
+        };
+
+        assert!(check_paid_before_confirm(&booking, &confirm, false).is_ok());
+        assert!(check_paid_before_confirm(&booking, &confirm, true).is_err());
+
+        booking.paid_at = Some(Utc::now());
+        assert!(check_paid_before_confirm(&booking, &confirm, true).is_ok());
+    }
+
+    #[test]
+    fn test_auto_confirm_follows_the_manager_checks() {
+        let mut booking = Booking::new(request(date(8, 1), date(8, 10)), "customer_user_1".into());
+        let policy = BookingPolicy::default();
+
+        assert!(check_auto_confirm(&policy, VehicleType::Car, &booking, false).is_ok());
+        // Unpaid while payments are enabled
+        assert!(check_auto_confirm(&policy, VehicleType::Car, &booking, true).is_err());
+
+        // A policy not letting managers confirm leaves it pending too
+        let no_confirmation = BookingPolicy {
+            rules: policy
+                .rules
+                .iter()
+                .filter(|rule| !rule.to.contains(&"CONFIRMED".to_string()))
+                .cloned()
+                .collect(),
+            ..BookingPolicy::default()
+        };
+        booking.paid_at = Some(Utc::now());
+        assert!(check_auto_confirm(&policy, VehicleType::Motorbike, &booking, true).is_ok());
+        assert!(matches!(
+            check_auto_confirm(&no_confirmation, VehicleType::Car, &booking, true),
+            Err(AppError::Forbidden { .. })
+        ));
+    }
+
+    #[test]
+    fn test_handover_during_booked_period() {
+        let mut booking = Booking::new(request(date(8, 1), date(8, 10)), "customer_user_1".into());
+        let reading = |odometer_km| HandoverRequest {
+            odometer_km,
+            fuel_percent: 100,
+        };
+        let at = |month, day| {
+            NaiveDate::from_ymd_opt(2025, month, day)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_utc()
+        };
+        let check_in = ConditionStage::CheckIn;
+
+        // Pending bookings are not handed over
+        assert!(validate_handover(&booking, check_in, &reading(1000), None, at(8, 2)).is_err());
+
+        booking.status = BookingStatus::Confirmed;
+        assert!(validate_handover(&booking, check_in, &reading(1000), None, at(7, 31)).is_err());
+        assert!(validate_handover(&booking, check_in, &reading(1000), None, at(8, 11)).is_err());
+        assert!(validate_handover(&booking, check_in, &reading(1000), None, at(8, 2)).is_ok());
+        // Not below the reading the vehicle came back with last time, fuel in percent
+        assert!(
+            validate_handover(&booking, check_in, &reading(1000), Some(1200), at(8, 2)).is_err()
+        );
+        assert!(
+            validate_handover(&booking, check_in, &reading(1200), Some(1200), at(8, 2)).is_ok()
+        );
+        let overfull = HandoverRequest {
+            odometer_km: 1000,
+            fuel_percent: 120,
+        };
+        assert!(validate_handover(&booking, check_in, &overfull, None, at(8, 2)).is_err());
+
+        // Checked out after check-in, late returns included
+        let check_out = ConditionStage::CheckOut;
+        assert!(validate_handover(&booking, check_out, &reading(1400), None, at(8, 9)).is_err());
+        booking.check_in = Some(Handover {
+            at: at(8, 2),
+            odometer_km: 1000,
+            fuel_percent: 100,
+            recorded_by: "car_manager_1".into(),
+        });
+        assert!(validate_handover(&booking, check_in, &reading(1000), None, at(8, 3)).is_err());
+        assert!(validate_handover(&booking, check_out, &reading(900), None, at(8, 9)).is_err());
+        assert!(validate_handover(&booking, check_out, &reading(1400), None, at(8, 12)).is_ok());
+    }
+
+    #[test]
+    fn test_validate_driver() {
+        let mut driver = DriverDetails {
+            license_number: "B1234567".into(),
+            phone: None,
+            address: Some(" ".into()),
+        };
+        assert!(validate_driver(&driver)
+            .unwrap_err()
+            .contains("driver.address"));
+
+        driver.address = None;
+        assert!(validate_driver(&driver).is_ok());
+
+        // Clients cannot send ciphertext of their own
+        driver.license_number =
+            serde_json::from_str(r#"{ "kid": "k1", "nonce": "bm9uY2U", "ciphertext": "Y3Q" }"#)
+                .unwrap();
+        assert!(validate_driver(&driver).is_err());
+    }
+
+    #[test]
+    fn test_holiday_blackout_on_pickup_day() {
+        let calendar = vec![Holiday {
+            country: "FR".to_string(),
+            date: date(8, 15),
+            name: "Assomption".to_string(),
+            surcharge_percent: 20.0,
+            blackout: true,
+            source: HolidaySource::Calendar,
+        }];
+
+        let issues = check_blackout_dates(date(8, 15), date(8, 20), &calendar);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field.as_deref(), Some("from_date"));
+
+        // Holidays in the middle of a rental do not block it
+        assert!(check_blackout_dates(date(8, 10), date(8, 20), &calendar).is_empty());
+    }
+
+    #[test]
+    fn test_total_price_includes_surcharges() {
+        let vehicle: Vehicle = serde_json::from_value(serde_json::json!({
+            "brand": "TESLA",
+            "type": "CAR",
+            "metadata": { "model": "MODEL_3", "seats": 5, "fuel_type": "ELECTRIC", "gearbox": "AUTOMATIC", "engine_cc": 0 },
+            "description": null,
+            "price_by_day": 33.333,
+            "year_of_production": 2022,
+            "added_at": "2025-01-01T00:00:00Z",
+            "added_by": "admin_user_1",
+        }))
+        .unwrap();
+        let calendar = vec![Holiday {
+            country: "FR".to_string(),
+            date: date(8, 15),
+            name: "Assomption".to_string(),
+            surcharge_percent: 20.0,
+            blackout: false,
+            source: HolidaySource::Calendar,
+        }];
+
+        let (price, warnings) = estimate_price(date(8, 14), date(8, 17), &vehicle, &calendar);
+        // A price stored as a plain number is in EUR
+        assert_eq!(
+            price,
+            Some(Money::new(Decimal::new(10667, 2), Currency::EUR))
+        );
+        assert_eq!(warnings[0].code, BookingIssueCode::HolidaySurcharge);
+
+        assert_eq!(
+            estimate_price(date(8, 17), date(8, 14), &vehicle, &[]).0,
+            None
+        );
+    }
+
+    #[test]
+    fn test_only_available_vehicles_can_be_booked() {
+        // Vehicles stored before the status existed are available
+        let mut vehicle: Vehicle = serde_json::from_value(serde_json::json!({
+            "brand": "TESLA",
+            "type": "CAR",
+            "metadata": { "model": "MODEL_3", "seats": 5, "fuel_type": "ELECTRIC", "gearbox": "AUTOMATIC", "engine_cc": 0 },
+            "description": null,
+            "price_by_day": 50.0,
+            "year_of_production": 2022,
+            "added_at": "2025-01-01T00:00:00Z",
+            "added_by": "admin_user_1",
+        }))
+        .unwrap();
+        assert!(check_vehicle_status(&vehicle).is_none());
+
+        vehicle.status = VehicleStatus::Maintenance;
+        let issue = check_vehicle_status(&vehicle).unwrap();
+        assert_eq!(issue.code, BookingIssueCode::OutOfService);
+        assert_eq!(issue.field.as_deref(), Some("vehicle_id"));
+
+        vehicle.status = VehicleStatus::Retired;
+        assert!(check_vehicle_status(&vehicle).is_some());
+    }
 }
@@ -0,0 +1,15 @@
+use crate::authentication::identity::{Identity, Role};
+use crate::models::AddCommentRequest;
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for AddCommentRequest {
+    async fn validate(&self, identity: &Identity) -> Result<(), String> {
+        if self.body.trim().is_empty() {
+            return Err("body cannot be blank.".to_string());
+        }
+        if self.internal && identity.role == Role::Customer {
+            return Err("Customers cannot post internal comments.".to_string());
+        }
+        Ok(())
+    }
+}
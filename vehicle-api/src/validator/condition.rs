@@ -0,0 +1,32 @@
+use crate::authentication::identity::Identity;
+use crate::models::{AddConditionPhotosRequest, AnnotatePhotoRequest};
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for AddConditionPhotosRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        for photo in &self.photos {
+            if photo.angle.trim().is_empty() {
+                return Err("angle cannot be blank.".to_string());
+            }
+            // Evidence must be served over TLS, never from a local or plain HTTP address
+            if !photo.url.starts_with("https://") {
+                return Err(format!("Photo URL must use https: {}", photo.url));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CustomValidateTrait for AnnotatePhotoRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self.note.trim().is_empty() {
+            return Err("note cannot be blank.".to_string());
+        }
+        if let Some(region) = &self.region {
+            if region.x + region.width > 1.0 || region.y + region.height > 1.0 {
+                return Err("region must fit inside the photo.".to_string());
+            }
+        }
+        Ok(())
+    }
+}
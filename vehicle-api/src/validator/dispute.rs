@@ -0,0 +1,52 @@
+use crate::authentication::identity::Identity;
+use crate::models::{
+    AddDisputeEvidenceRequest, AddDisputeNoteRequest, Decimal, OpenDisputeRequest,
+    ResolveDisputeRequest,
+};
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for OpenDisputeRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self.reason.trim().is_empty() {
+            return Err("reason cannot be blank.".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl CustomValidateTrait for AddDisputeEvidenceRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        // Same rule as condition photos, evidence is only linked over TLS
+        if !self.url.starts_with("https://") {
+            return Err(format!("Evidence URL must use https: {}", self.url));
+        }
+        Ok(())
+    }
+}
+
+impl CustomValidateTrait for AddDisputeNoteRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self.note.trim().is_empty() {
+            return Err("note cannot be blank.".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl CustomValidateTrait for ResolveDisputeRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self.comment.trim().is_empty() {
+            return Err("comment cannot be blank.".to_string());
+        }
+        if let Some(amount) = self.amount {
+            if amount <= Decimal::ZERO {
+                return Err("amount must be positive.".to_string());
+            }
+            // Money is handled in cents
+            if amount.normalize().scale() > 2 {
+                return Err("amount cannot have more than 2 decimals.".to_string());
+            }
+        }
+        Ok(())
+    }
+}
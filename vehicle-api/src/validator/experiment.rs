@@ -0,0 +1,16 @@
+use crate::authentication::identity::Identity;
+use crate::models::ExperimentRequest;
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for ExperimentRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self
+            .description
+            .as_ref()
+            .is_some_and(|description| description.trim().is_empty())
+        {
+            return Err("description cannot be blank.".to_string());
+        }
+        self.validate_variants()
+    }
+}
@@ -0,0 +1,25 @@
+use chrono::Utc;
+
+use crate::authentication::identity::Identity;
+use crate::models::{ExtendBookingRequest, RejectExtensionRequest};
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for ExtendBookingRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        // Being after the current to_date is checked against the booking itself, see
+        // BookingExtension::validate_request. A day already over never is.
+        if self.to_date < Utc::now().date_naive() {
+            return Err("to_date cannot be in the past.".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl CustomValidateTrait for RejectExtensionRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self.reason.trim().is_empty() {
+            return Err("reason cannot be blank.".to_string());
+        }
+        Ok(())
+    }
+}
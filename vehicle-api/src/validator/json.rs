@@ -7,7 +7,7 @@ use validator::Validate;
 
 use crate::authentication::identity::Identity;
 
-use super::CustomValidateTrait;
+use super::{metrics, CustomValidateTrait};
 
 #[derive(Debug)]
 pub struct Json<T>(pub T);
@@ -49,8 +49,15 @@ where
         let json = web::Json::<T>::from_request(req, payload);
         Box::pin(async move {
             let json: T = json.await?.into_inner();
-            Validate::validate(&json)?;
-            CustomValidateTrait::validate(&json, &identity).await?;
+            let request = metrics::request_name::<T>();
+            if let Err(errors) = Validate::validate(&json) {
+                metrics::record_errors(request, &errors);
+                return Err(errors.into());
+            }
+            if let Err(message) = CustomValidateTrait::validate(&json, &identity).await {
+                metrics::record(request, "", metrics::CUSTOM_RULE);
+                return Err(message.into());
+            }
             Ok(Json(json))
         })
     }
@@ -0,0 +1,31 @@
+use crate::authentication::identity::Identity;
+use crate::models::{
+    CreateMaintenanceRecordRequest, Decimal, Money, UpdateMaintenanceRecordRequest,
+};
+use crate::validator::CustomValidateTrait;
+
+/// Costs can be nothing (e.g. under warranty), never negative
+fn check_cost(cost: &Money) -> Result<(), String> {
+    if cost.amount < Decimal::ZERO {
+        return Err("cost cannot be negative.".to_string());
+    }
+    if cost.amount.normalize().scale() > 2 {
+        return Err("cost cannot have more than 2 decimals.".to_string());
+    }
+    Ok(())
+}
+
+impl CustomValidateTrait for CreateMaintenanceRecordRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        check_cost(&self.cost)
+    }
+}
+
+impl CustomValidateTrait for UpdateMaintenanceRecordRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if let Some(cost) = &self.cost {
+            check_cost(cost)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use std::sync::LazyLock;
+
+use prometheus::{IntCounterVec, Opts};
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+use crate::models::BookingIssue;
+use crate::services;
+
+/// Rule of the checks written by hand in `CustomValidateTrait` implementations
+pub const CUSTOM_RULE: &str = "custom";
+
+// validation_failures_total{request, field, rule}
+static VALIDATION_FAILURES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new(
+            "validation_failures_total",
+            "Requests rejected by a validation rule",
+        ),
+        &["request", "field", "rule"],
+    )
+    .expect("Invalid validation failures metric");
+    services::metrics::register(counter.clone());
+    counter
+});
+
+/// Count a request rejected by `rule` on `field` (empty for the request as a whole)
+pub fn record(request: &str, field: &str, rule: &str) {
+    VALIDATION_FAILURES
+        .with_label_values(&[request, field, rule])
+        .inc();
+}
+
+/// Count a rejection and return its message, for validators failing with a message
+pub fn reject(request: &str, field: &str, rule: &str, message: impl Into<String>) -> String {
+    record(request, field, rule);
+    message.into()
+}
+
+/// Count a booking rejected for `issue`, and return its message
+pub fn reject_issue(request: &str, issue: BookingIssue) -> String {
+    let field = issue.field.as_deref().unwrap_or_default();
+    record(request, field, &issue_rule(&issue));
+    issue.message
+}
+
+/// Count every rule a request broke on the `validator` derive
+pub fn record_errors(request: &str, errors: &ValidationErrors) {
+    for (field, rule) in failures(errors) {
+        record(request, &field, &rule);
+    }
+}
+
+/// Request type name without its module path, e.g. `CreateBookingRequest`
+pub fn request_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Issue code as a rule name, e.g. `too_short_notice`
+fn issue_rule(issue: &BookingIssue) -> String {
+    serde_json::to_value(&issue.code)
+        .ok()
+        .and_then(|code| code.as_str().map(str::to_lowercase))
+        .unwrap_or_default()
+}
+
+/// Field path and rule code of each error, nested fields as `driver.phone` and list items
+/// as `items[]`
+pub fn failures(errors: &ValidationErrors) -> Vec<(String, String)> {
+    let mut failures = Vec::new();
+    collect(errors, "", &mut failures);
+    failures.sort();
+    failures
+}
+
+fn collect(errors: &ValidationErrors, prefix: &str, failures: &mut Vec<(String, String)>) {
+    for (field, kind) in errors.errors() {
+        let path = format!("{}{}", prefix, field);
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                for error in errors {
+                    failures.push((path.clone(), error.code.to_string()));
+                }
+            }
+            ValidationErrorsKind::Struct(nested) => {
+                collect(nested, &format!("{}.", path), failures);
+            }
+            ValidationErrorsKind::List(items) => {
+                for nested in items.values() {
+                    collect(nested, &format!("{}[].", path), failures);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BookingIssueCode;
+    use validator::Validate;
+
+    #[derive(Validate)]
+    struct Contact {
+        #[validate(length(min = 1))]
+        phone: String,
+    }
+
+    #[derive(Validate)]
+    struct Request {
+        #[validate(range(min = 1))]
+        seats: u32,
+        #[validate(nested)]
+        contact: Contact,
+    }
+
+    #[test]
+    fn test_failures_name_the_field_and_the_rule() {
+        let request = Request {
+            seats: 0,
+            contact: Contact {
+                phone: String::new(),
+            },
+        };
+        let errors = request.validate().unwrap_err();
+
+        assert_eq!(
+            failures(&errors),
+            vec![
+                ("contact.phone".to_string(), "length".to_string()),
+                ("seats".to_string(), "range".to_string()),
+            ]
+        );
+        assert_eq!(request_name::<Request>(), "Request");
+    }
+
+    #[test]
+    fn test_rejections_are_counted_by_rule() {
+        let issue = BookingIssue::new(
+            BookingIssueCode::TooShortNotice,
+            Some("from_date"),
+            "Bookings must be made at least 24 hours in advance.",
+        );
+        let labels = ["TestRequest", "from_date", "too_short_notice"];
+        let before = VALIDATION_FAILURES.with_label_values(&labels).get();
+
+        let message = reject_issue("TestRequest", issue);
+
+        assert_eq!(
+            message,
+            "Bookings must be made at least 24 hours in advance."
+        );
+        assert_eq!(
+            VALIDATION_FAILURES.with_label_values(&labels).get(),
+            before + 1
+        );
+    }
+}
@@ -1,6 +1,20 @@
+pub mod api_key;
 pub mod booking;
+pub mod comment;
+pub mod condition;
+pub mod dispute;
+pub mod experiment;
+pub mod extension;
 mod json;
+pub mod maintenance;
+pub mod metrics;
+pub mod payment;
+pub mod price_adjustment;
+pub mod service_account;
+pub mod station;
+pub mod suspension;
 pub mod vehicle;
+pub mod webhook_endpoint;
 
 pub use json::Json;
 
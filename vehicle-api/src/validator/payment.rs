@@ -0,0 +1,19 @@
+use crate::authentication::identity::Identity;
+use crate::models::{Decimal, RefundRequest};
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for RefundRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self.reason.trim().is_empty() {
+            return Err("reason cannot be blank.".to_string());
+        }
+        if self.amount <= Decimal::ZERO {
+            return Err("amount must be positive.".to_string());
+        }
+        // Money is handled in cents
+        if self.amount.normalize().scale() > 2 {
+            return Err("amount cannot have more than 2 decimals.".to_string());
+        }
+        Ok(())
+    }
+}
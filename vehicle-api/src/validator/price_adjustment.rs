@@ -0,0 +1,26 @@
+use crate::authentication::identity::Identity;
+use crate::models::{PriceAdjustmentKind, PriceAdjustmentRequest};
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for PriceAdjustmentRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self.value == 0.0 {
+            return Err("value cannot be 0, nothing would change.".to_string());
+        }
+        // Money is handled in cents, percents the same way
+        let cents = self.value * 100.0;
+        if (cents - cents.round()).abs() > 1e-6 {
+            return Err("value cannot have more than 2 decimals.".to_string());
+        }
+        if self.kind == PriceAdjustmentKind::Percent && self.value <= -100.0 {
+            return Err("A percent adjustment must stay above -100.".to_string());
+        }
+        // An empty list is dropped from the filter, repricing every vehicle by mistake
+        if self.brand.as_ref().is_some_and(Vec::is_empty)
+            || self.year_of_production.as_ref().is_some_and(Vec::is_empty)
+        {
+            return Err("Filters cannot be empty lists.".to_string());
+        }
+        Ok(())
+    }
+}
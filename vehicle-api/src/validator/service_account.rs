@@ -0,0 +1,12 @@
+use crate::authentication::identity::Identity;
+use crate::models::CreateServiceAccountRequest;
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for CreateServiceAccountRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name cannot be blank.".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use crate::authentication::identity::Identity;
+use crate::models::{validate_opening_hours, CreateStationRequest, UpdateStationRequest};
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for CreateStationRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name cannot be blank.".to_string());
+        }
+        crate::util::timezone::parse_timezone(&self.timezone)?;
+        validate_opening_hours(&self.opening_hours)
+    }
+}
+
+impl CustomValidateTrait for UpdateStationRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self
+            .name
+            .as_ref()
+            .is_some_and(|name| name.trim().is_empty())
+        {
+            return Err("name cannot be blank.".to_string());
+        }
+        if let Some(timezone) = &self.timezone {
+            crate::util::timezone::parse_timezone(timezone)?;
+        }
+        if let Some(opening_hours) = &self.opening_hours {
+            validate_opening_hours(opening_hours)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+use chrono::Utc;
+
+use crate::authentication::identity::Identity;
+use crate::models::SuspendCustomerRequest;
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for SuspendCustomerRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        if self.user_id.trim().is_empty() {
+            return Err("user_id cannot be blank.".to_string());
+        }
+        if self.reason.trim().is_empty() {
+            return Err("reason cannot be blank.".to_string());
+        }
+        if self.ends_at.is_some_and(|ends_at| ends_at <= Utc::now()) {
+            return Err("ends_at must be in the future.".to_string());
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,25 @@
+use bson::oid::ObjectId;
 use validator::Validate;
 
 use crate::authentication::identity::{Identity, Role};
 use crate::error::{AppError, AppResult};
-use crate::models::{Brand, CarModel, FuelType, UpdateVehicleRequest, Vehicle, VehicleMetadata};
+use crate::models::{
+    Brand, CarModel, CreateVehicleRequest, FuelType, GeoPoint, UpdateVehicleRequest,
+    UpdateVehicleStatusRequest, Vehicle, VehicleFilters, VehicleMetadata, MAX_RADIUS_KM,
+};
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for CreateVehicleRequest {
+    async fn validate(&self, identity: &Identity) -> Result<(), String> {
+        if let Some(timezone) = &self.timezone {
+            crate::util::timezone::parse_timezone(timezone)?;
+        }
+        if let Some(description) = &self.description {
+            crate::util::locale::check_supported(description)?;
+        }
+        validate_metadata(identity, &self.metadata).await
+    }
+}
 
 /// Validate Tesla constraints on metadata
 pub async fn validate_metadata(
@@ -112,7 +129,8 @@ pub(crate) fn check_vehicle_type_permission(
     vehicle: &Vehicle,
 ) -> Result<(), AppError> {
     match identity.role {
-        Role::Admin => Ok(()), // Admin can manage all vehicle types
+        // Admin and service accounts (through their grants) can manage all vehicle types
+        Role::Admin | Role::Service => Ok(()),
         Role::CarManager => {
             if matches!(vehicle.metadata, VehicleMetadata::Car(_)) {
                 Ok(())
@@ -139,6 +157,21 @@ pub(crate) fn validate_update_vehicle(
     identity: &Identity,
     vehicle: &Vehicle,
     request: &UpdateVehicleRequest,
+) -> AppResult<()> {
+    request
+        .validate()
+        .map_err(|e| AppError::bad_request(e.to_string()))?;
+    if let Some(description) = &request.description {
+        crate::util::locale::check_supported(description).map_err(AppError::bad_request)?;
+    }
+
+    check_vehicle_type_permission(identity, vehicle)
+}
+
+pub(crate) fn validate_update_vehicle_status(
+    identity: &Identity,
+    vehicle: &Vehicle,
+    request: &UpdateVehicleStatusRequest,
 ) -> AppResult<()> {
     request
         .validate()
@@ -146,3 +179,36 @@ pub(crate) fn validate_update_vehicle(
 
     check_vehicle_type_permission(identity, vehicle)
 }
+
+/// Nearby search needs both `lat` and `lng`, and `radius_km` only goes with them.
+/// `station_id` must be an ObjectId.
+pub(crate) fn validate_vehicle_filters(filters: &VehicleFilters) -> AppResult<()> {
+    if let Some(station_id) = &filters.station_id {
+        ObjectId::parse_str(station_id)
+            .map_err(|_| AppError::bad_request("Invalid station ID format"))?;
+    }
+    match (filters.lat, filters.lng) {
+        (Some(lat), Some(lng)) => {
+            if !GeoPoint::new(lat, lng).is_valid() {
+                return Err(AppError::bad_request(
+                    "lat must be within ±90 and lng within ±180 degrees",
+                ));
+            }
+        }
+        (None, None) if filters.radius_km.is_none() => return Ok(()),
+        _ => {
+            return Err(AppError::bad_request(
+                "lat and lng are required together, and with radius_km",
+            ))
+        }
+    }
+    if let Some(radius_km) = filters.radius_km {
+        if !(radius_km > 0.0 && radius_km <= MAX_RADIUS_KM) {
+            return Err(AppError::bad_request(format!(
+                "radius_km must be greater than 0 and at most {}",
+                MAX_RADIUS_KM
+            )));
+        }
+    }
+    Ok(())
+}
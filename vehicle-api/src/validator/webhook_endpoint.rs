@@ -0,0 +1,16 @@
+use crate::authentication::identity::Identity;
+use crate::models::CreateWebhookEndpointRequest;
+use crate::validator::CustomValidateTrait;
+
+impl CustomValidateTrait for CreateWebhookEndpointRequest {
+    async fn validate(&self, _identity: &Identity) -> Result<(), String> {
+        // Payloads carry customer IDs, they only travel encrypted unless allowed for development
+        let allow_http = std::env::var("WEBHOOK_ALLOW_HTTP").as_deref() == Ok("true");
+        let accepted =
+            self.url.starts_with("https://") || (allow_http && self.url.starts_with("http://"));
+        if !accepted {
+            return Err("url must use https.".to_string());
+        }
+        Ok(())
+    }
+}